@@ -0,0 +1,343 @@
+use core::time::Duration;
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::{Address, Result, TransportMessage};
+
+#[cfg(feature = "std")]
+use ockam_core::{Decodable, Encodable};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+/// Location of the messages buffered by a forwarder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwarderBufferStorage {
+    /// Messages are kept in memory and lost when the node stops
+    Memory,
+    /// Messages are stored as files, in a sub-directory of the given directory
+    /// named after the forwarder alias, so that they survive a restart of the node
+    #[cfg(feature = "std")]
+    Disk(PathBuf),
+}
+
+/// Options for the buffered mode of the forwarders created by a
+/// [`ForwardingService`](crate::ForwardingService)
+///
+/// In this mode, messages which can't be forwarded because the node which registered the
+/// forwarder is disconnected are queued, and delivered once the
+/// [`RemoteForwarder`](crate::remote::RemoteForwarder) registers again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwarderBufferOptions {
+    pub(super) capacity: usize,
+    pub(super) ttl: Option<Duration>,
+    pub(super) storage: ForwarderBufferStorage,
+}
+
+impl ForwarderBufferOptions {
+    /// Buffer at most `capacity` messages per forwarder, in memory.
+    /// When a buffer is full, its oldest message is dropped
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ttl: None,
+            storage: ForwarderBufferStorage::Memory,
+        }
+    }
+
+    /// Drop the buffered messages which are older than the given duration
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Store the buffered messages on disk, under the given directory
+    #[cfg(feature = "std")]
+    pub fn on_disk(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.storage = ForwarderBufferStorage::Disk(dir.into());
+        self
+    }
+}
+
+/// Statistics of a buffered forwarder
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwarderBufferStats {
+    /// Number of messages currently buffered
+    pub buffered: u64,
+    /// Number of buffered messages which were delivered after a new registration
+    pub redelivered: u64,
+    /// Number of messages dropped because the buffer was full
+    pub dropped_overflow: u64,
+    /// Number of messages dropped because they were buffered for too long
+    pub dropped_expired: u64,
+}
+
+struct BufferedMessage {
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    sequence: u64,
+    /// Enqueuing time, in milliseconds since the UNIX epoch
+    enqueued_at: Option<u64>,
+    message: TransportMessage,
+}
+
+/// Bounded queue of the messages of a forwarder
+pub(super) struct MessageBuffer {
+    options: ForwarderBufferOptions,
+    queue: VecDeque<BufferedMessage>,
+    next_sequence: u64,
+    stats: ForwarderBufferStats,
+    #[cfg(feature = "std")]
+    dir: Option<PathBuf>,
+}
+
+impl MessageBuffer {
+    /// Create the buffer of a forwarder. Messages previously stored on disk for the same
+    /// forwarder are loaded
+    pub(super) fn create(options: ForwarderBufferOptions, alias: &Address) -> Result<Self> {
+        let mut buffer = Self {
+            options,
+            queue: VecDeque::new(),
+            next_sequence: 0,
+            stats: ForwarderBufferStats::default(),
+            #[cfg(feature = "std")]
+            dir: None,
+        };
+        #[cfg(feature = "std")]
+        if let ForwarderBufferStorage::Disk(root) = &buffer.options.storage {
+            let dir = root.join(directory_name(alias));
+            buffer.load(&dir)?;
+            buffer.dir = Some(dir);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = alias;
+
+        Ok(buffer)
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub(super) fn stats(&self) -> ForwarderBufferStats {
+        ForwarderBufferStats {
+            buffered: self.queue.len() as u64,
+            ..self.stats.clone()
+        }
+    }
+
+    /// Add a message at the end of the buffer, dropping the oldest message if the buffer is full
+    pub(super) fn push(&mut self, message: TransportMessage) -> Result<()> {
+        self.remove_expired()?;
+        if self.options.capacity == 0 {
+            self.stats.dropped_overflow += 1;
+            return Ok(());
+        }
+        while self.queue.len() >= self.options.capacity {
+            self.pop()?;
+            self.stats.dropped_overflow += 1;
+        }
+
+        let message = BufferedMessage {
+            sequence: self.next_sequence,
+            enqueued_at: now_millis(),
+            message,
+        };
+        self.next_sequence += 1;
+        #[cfg(feature = "std")]
+        self.store(&message)?;
+        self.queue.push_back(message);
+        Ok(())
+    }
+
+    /// Return the oldest message which has not expired, without removing it
+    pub(super) fn front(&mut self) -> Result<Option<TransportMessage>> {
+        self.remove_expired()?;
+        Ok(self.queue.front().map(|m| m.message.clone()))
+    }
+
+    /// Remove the oldest message, after it has been delivered
+    pub(super) fn delivered(&mut self) -> Result<()> {
+        if self.pop()?.is_some() {
+            self.stats.redelivered += 1;
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Option<BufferedMessage>> {
+        let message = self.queue.pop_front();
+        #[cfg(feature = "std")]
+        if let (Some(message), Some(dir)) = (&message, &self.dir) {
+            std::fs::remove_file(dir.join(file_name(message.sequence))).map_err(io_error)?;
+        }
+        Ok(message)
+    }
+
+    fn remove_expired(&mut self) -> Result<()> {
+        let (ttl, now) = match (self.options.ttl, now_millis()) {
+            (Some(ttl), Some(now)) => (ttl.as_millis() as u64, now),
+            _ => return Ok(()),
+        };
+        while let Some(enqueued_at) = self.queue.front().and_then(|m| m.enqueued_at) {
+            if now.saturating_sub(enqueued_at) <= ttl {
+                break;
+            }
+            self.pop()?;
+            self.stats.dropped_expired += 1;
+        }
+        Ok(())
+    }
+
+    /// Each message is stored in its own file, named after its sequence number,
+    /// and containing its enqueuing time followed by the encoded message
+    #[cfg(feature = "std")]
+    fn store(&self, message: &BufferedMessage) -> Result<()> {
+        if let Some(dir) = &self.dir {
+            let mut bytes = message
+                .enqueued_at
+                .unwrap_or_default()
+                .to_le_bytes()
+                .to_vec();
+            bytes.extend(message.message.encode()?);
+            std::fs::write(dir.join(file_name(message.sequence)), bytes).map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn load(&mut self, dir: &std::path::Path) -> Result<()> {
+        std::fs::create_dir_all(dir).map_err(io_error)?;
+        let mut sequences = vec![];
+        for entry in std::fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if let Some(sequence) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                sequences.push(sequence);
+            }
+        }
+        sequences.sort_unstable();
+        for sequence in sequences {
+            let bytes = std::fs::read(dir.join(file_name(sequence))).map_err(io_error)?;
+            if bytes.len() < 8 {
+                continue;
+            }
+            let (time, message) = bytes.split_at(8);
+            let time = u64::from_le_bytes(time.try_into().unwrap());
+            self.queue.push_back(BufferedMessage {
+                sequence,
+                enqueued_at: (time != 0).then(|| time),
+                message: TransportMessage::decode(message)?,
+            });
+            self.next_sequence = sequence + 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+fn now_millis() -> Option<u64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+#[cfg(not(feature = "std"))]
+fn now_millis() -> Option<u64> {
+    None
+}
+
+#[cfg(feature = "std")]
+fn file_name(sequence: u64) -> String {
+    format!("{sequence:020}.msg")
+}
+
+/// Aliases are used as directory names
+#[cfg(feature = "std")]
+fn directory_name(alias: &Address) -> String {
+    alias
+        .address()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "std")]
+fn io_error(e: std::io::Error) -> ockam_core::Error {
+    ockam_core::Error::new(
+        ockam_core::errcode::Origin::Core,
+        ockam_core::errcode::Kind::Io,
+        e,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    fn message(payload: &str) -> TransportMessage {
+        TransportMessage::v1(route!["app"], route![], payload.as_bytes().to_vec())
+    }
+
+    fn payload(message: Option<TransportMessage>) -> Option<Vec<u8>> {
+        message.map(|m| m.payload)
+    }
+
+    #[test]
+    fn test_bounded_buffer() -> Result<()> {
+        let mut buffer = MessageBuffer::create(ForwarderBufferOptions::new(2), &"alias".into())?;
+        buffer.push(message("1"))?;
+        buffer.push(message("2"))?;
+        buffer.push(message("3"))?;
+
+        assert_eq!(payload(buffer.front()?), Some(b"2".to_vec()));
+        buffer.delivered()?;
+        assert_eq!(payload(buffer.front()?), Some(b"3".to_vec()));
+        buffer.delivered()?;
+        assert!(buffer.is_empty());
+
+        let stats = buffer.stats();
+        assert_eq!(stats.redelivered, 2);
+        assert_eq!(stats.dropped_overflow, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_messages_are_dropped() -> Result<()> {
+        let options = ForwarderBufferOptions::new(10).with_ttl(Duration::from_millis(10));
+        let mut buffer = MessageBuffer::create(options, &"alias".into())?;
+        buffer.push(message("1"))?;
+        std::thread::sleep(Duration::from_millis(20));
+        buffer.push(message("2"))?;
+
+        assert_eq!(payload(buffer.front()?), Some(b"2".to_vec()));
+        assert_eq!(buffer.stats().dropped_expired, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disk_buffer_is_reloaded() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ForwarderBufferOptions::new(10).on_disk(dir.path());
+        let alias: Address = "0#alias".into();
+
+        let mut buffer = MessageBuffer::create(options.clone(), &alias)?;
+        buffer.push(message("1"))?;
+        buffer.push(message("2"))?;
+        buffer.delivered()?;
+
+        let mut buffer = MessageBuffer::create(options, &alias)?;
+        assert_eq!(buffer.stats().buffered, 1);
+        assert_eq!(payload(buffer.front()?), Some(b"2".to_vec()));
+        buffer.push(message("3"))?;
+        buffer.delivered()?;
+        assert_eq!(payload(buffer.front()?), Some(b"3".to_vec()));
+        Ok(())
+    }
+}
@@ -1,12 +1,16 @@
+use crate::forwarding_service::buffer::MessageBuffer;
+use crate::forwarding_service::{ForwarderBufferOptions, ForwarderStatus, ForwardersRegistry};
 use crate::Context;
-use ockam_core::compat::sync::Arc;
+use ockam_core::compat::sync::{Arc, Mutex};
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::{
-    Address, AllowAll, AllowOnwardAddress, Any, IncomingAccessControl, LocalMessage,
-    OutgoingAccessControl, Result, Route, Routed, TransportMessage, Worker,
+    async_trait, Address, AllowSourceAddress, Any, DenyAll, IncomingAccessControl, LocalMessage,
+    Mailbox, Mailboxes, OutgoingAccessControl, RelayMessage, Result, Route, Routed,
+    TransportMessage, Worker,
 };
+use ockam_identity::IdentityIdentifier;
 use ockam_node::WorkerBuilder;
-use tracing::info;
+use tracing::{debug, info, warn};
 
 pub(super) struct Forwarder {
     forward_route: Route,
@@ -14,41 +18,123 @@ pub(super) struct Forwarder {
     // while initializing, the worker will send the payload contained in this
     // field to the `forward_route`, to indicate a successful connection
     payload: Option<Vec<u8>>,
+    registration_address: Address,
+    next_hop: NextHopAccessControl,
+    buffer: Option<MessageBuffer>,
+    registry: ForwardersRegistry,
+}
+
+/// Parameters of a new forwarder
+pub(super) struct ForwarderRegistration {
+    pub(super) address: Address,
+    pub(super) forward_route: Route,
+    pub(super) payload: Vec<u8>,
+    pub(super) registrant: Option<IdentityIdentifier>,
 }
 
 impl Forwarder {
     pub(super) async fn create(
         ctx: &Context,
-        address: Address,
-        forward_route: Route,
-        registration_payload: Vec<u8>,
+        registration: ForwarderRegistration,
+        service_address: Address,
         incoming_access_control: Arc<dyn IncomingAccessControl>,
+        buffer: Option<ForwarderBufferOptions>,
+        registry: ForwardersRegistry,
     ) -> Result<()> {
+        let ForwarderRegistration {
+            address,
+            forward_route,
+            payload,
+            registrant,
+        } = registration;
         info!("Created new alias {} for {}", address, forward_route);
 
-        // Should be able to reach last and second last hops
-        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = if forward_route.len() == 1 {
-            // We are accessed with our node, no transport is involved
-            Arc::new(AllowAll)
-        } else {
-            let next_hop = forward_route.next()?.clone();
-            Arc::new(AllowOnwardAddress(next_hop))
+        // Should be able to reach last and second last hops.
+        // The next hop changes when the forwarder is registered again
+        let next_hop = NextHopAccessControl::new(&forward_route)?;
+
+        // New registrations of this alias are sent by the service to this address
+        let registration_address = Address::random_tagged("Forwarder.registration");
+
+        let buffer = match buffer {
+            Some(options) => Some(MessageBuffer::create(options, &address)?),
+            None => None,
         };
 
         let forwarder = Self {
             forward_route,
-            payload: Some(registration_payload.clone()),
+            payload: Some(payload),
+            registration_address: registration_address.clone(),
+            next_hop: next_hop.clone(),
+            buffer,
+            registry: registry.clone(),
         };
 
+        registry.insert(
+            registration_address.clone(),
+            registrant,
+            forwarder.status(&address),
+        );
+
+        let mailboxes = Mailboxes::new(
+            Mailbox::new(address, incoming_access_control, Arc::new(next_hop)),
+            vec![Mailbox::new(
+                registration_address,
+                Arc::new(AllowSourceAddress(service_address)),
+                Arc::new(DenyAll),
+            )],
+        );
         WorkerBuilder::new(forwarder)
-            .with_address(address)
-            .with_incoming_access_control_arc(incoming_access_control)
-            .with_outgoing_access_control_arc(outgoing_access_control)
+            .with_mailboxes(mailboxes)
             .start(ctx)
             .await?;
 
         Ok(())
     }
+
+    fn status(&self, address: &Address) -> ForwarderStatus {
+        ForwarderStatus {
+            address: address.clone(),
+            forward_route: self.forward_route.clone(),
+            stats: self.buffer.as_ref().map(|b| b.stats()),
+        }
+    }
+
+    /// Confirm a registration to the node which sent it, use the route to that node to
+    /// forward messages, and deliver the messages buffered while that node was unreachable
+    async fn register(
+        &mut self,
+        ctx: &Context,
+        forward_route: Route,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        self.next_hop.update(&forward_route)?;
+
+        let msg = TransportMessage::v1(forward_route.clone(), ctx.address(), payload);
+        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+
+        // Remove the last hop so that just route to the node itself is left
+        self.forward_route = forward_route;
+        self.forward_route.modify().pop_back();
+
+        if let Some(buffer) = &mut self.buffer {
+            while let Some(message) = buffer.front()? {
+                let message = LocalMessage::new(message, Vec::new());
+                if let Err(e) = forward(ctx, &self.forward_route, message).await {
+                    warn!(
+                        "Could not deliver the messages buffered for {}: {}",
+                        ctx.address(),
+                        e
+                    );
+                    break;
+                }
+                buffer.delivered()?;
+            }
+        }
+
+        self.registry.update(self.status(&ctx.address()));
+        Ok(())
+    }
 }
 
 #[crate::worker]
@@ -61,13 +147,14 @@ impl Worker for Forwarder {
             .payload
             .take()
             .expect("payload must be available on init");
-        let msg = TransportMessage::v1(self.forward_route.clone(), ctx.address(), payload);
 
-        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
-
-        // Remove the last hop so that just route to the node itself is left
-        self.forward_route.modify().pop_back();
+        self.register(ctx, self.forward_route.clone(), payload)
+            .await
+    }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // the alias can then be registered again by any node
+        self.registry.remove(&ctx.address());
         Ok(())
     }
 
@@ -76,37 +163,104 @@ impl Worker for Forwarder {
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if msg.msg_addr() == self.registration_address {
+            let forward_route = msg.return_route();
+            let payload = msg.into_transport_message().payload;
+            debug!(
+                "Alias {} registered again for {}",
+                ctx.address(),
+                forward_route
+            );
+            return self.register(ctx, forward_route, payload).await;
+        }
+
         let mut message = msg.into_local_message();
-        let transport_message = message.transport_mut();
 
         // Remove my address from the onward_route
-        transport_message.onward_route.step()?;
-
-        // Prepend forward route
-        transport_message
-            .onward_route
-            .modify()
-            .prepend_route(self.forward_route.clone());
-
-        let next_hop = transport_message.onward_route.next()?.clone();
-        let prev_hop = transport_message.return_route.next()?.clone();
-
-        if let Some(info) = ctx
-            .flow_controls()
-            .find_flow_control_with_producer_address(&next_hop)
-        {
-            ctx.flow_controls()
-                .add_consumer(prev_hop.clone(), info.flow_control_id());
+        message.transport_mut().onward_route.step()?;
+
+        let buffer = match &mut self.buffer {
+            Some(buffer) => buffer,
+            None => return forward(ctx, &self.forward_route, message).await,
+        };
+
+        // Keep the messages in order while some messages are still waiting for delivery
+        if buffer.is_empty() {
+            match forward(ctx, &self.forward_route, message.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) => debug!("Buffering a message for {}: {}", ctx.address(), e),
+            }
         }
+        buffer.push(message.into_transport_message())?;
+
+        self.registry.update(self.status(&ctx.address()));
+        Ok(())
+    }
+}
+
+/// Forward a message along the forward route
+async fn forward(ctx: &Context, forward_route: &Route, mut message: LocalMessage) -> Result<()> {
+    let transport_message = message.transport_mut();
+
+    // Prepend forward route
+    transport_message
+        .onward_route
+        .modify()
+        .prepend_route(forward_route.clone());
+
+    let next_hop = transport_message.onward_route.next()?.clone();
+    let prev_hop = transport_message.return_route.next()?.clone();
+
+    if let Some(info) = ctx
+        .flow_controls()
+        .find_flow_control_with_producer_address(&next_hop)
+    {
+        ctx.flow_controls()
+            .add_consumer(prev_hop.clone(), info.flow_control_id());
+    }
 
-        if let Some(info) = ctx
-            .flow_controls()
-            .find_flow_control_with_producer_address(&prev_hop)
-        {
-            ctx.flow_controls()
-                .add_consumer(next_hop, info.flow_control_id());
+    if let Some(info) = ctx
+        .flow_controls()
+        .find_flow_control_with_producer_address(&prev_hop)
+    {
+        ctx.flow_controls()
+            .add_consumer(next_hop, info.flow_control_id());
+    }
+
+    ctx.forward(message).await
+}
+
+/// Allow the forwarder to only send messages to the next hop of its current forward route
+#[derive(Debug, Clone)]
+struct NextHopAccessControl(Arc<Mutex<Option<Address>>>);
+
+impl NextHopAccessControl {
+    fn new(forward_route: &Route) -> Result<Self> {
+        Ok(Self(Arc::new(Mutex::new(Self::next_hop(forward_route)?))))
+    }
+
+    fn update(&self, forward_route: &Route) -> Result<()> {
+        *self.0.lock().unwrap() = Self::next_hop(forward_route)?;
+        Ok(())
+    }
+
+    /// `None` if we are accessed within our node, in which case no transport is involved
+    fn next_hop(forward_route: &Route) -> Result<Option<Address>> {
+        if forward_route.len() == 1 {
+            Ok(None)
+        } else {
+            Ok(Some(forward_route.next()?.clone()))
         }
+    }
+}
 
-        ctx.forward(message).await
+#[async_trait]
+impl OutgoingAccessControl for NextHopAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let next_hop = self.0.lock().unwrap().clone();
+        match next_hop {
+            Some(next_hop) if &next_hop != relay_msg.onward_route().next()? => ockam_core::deny(),
+            _ => ockam_core::allow(),
+        }
     }
 }
@@ -1,15 +1,23 @@
-use crate::forwarding_service::forwarder::Forwarder;
+use crate::forwarding_service::forwarder::{Forwarder, ForwarderRegistration};
+use crate::forwarding_service::registry::AllowRegistrationAddresses;
 use crate::{Context, ForwardingServiceOptions};
 use core::str::from_utf8;
-use ockam_core::compat::boxed::Box;
-use ockam_core::{Address, Any, DenyAll, Result, Routed, Worker};
+use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::{Address, Any, LocalMessage, Result, Routed, TransportMessage, Worker};
+use ockam_identity::IdentitySecureChannelLocalInfo;
 use ockam_node::WorkerBuilder;
+use tracing::warn;
 
 /// Alias worker to register remote workers under local names.
 ///
 /// To talk with this worker, you can use the
 /// [`RemoteForwarder`](crate::remote::RemoteForwarder) which is a
 /// compatible client for this server.
+///
+/// When an alias is registered again, for instance after the node which registered it
+/// reconnected, the existing forwarder is updated to use the new route. Both registrations
+/// must be made through a secure channel, by the same identity, otherwise the new
+/// registration is rejected.
 #[non_exhaustive]
 pub struct ForwardingService {
     options: ForwardingServiceOptions,
@@ -27,13 +35,15 @@ impl ForwardingService {
         options.setup_flow_control_for_forwarding_service(ctx.flow_controls(), &address);
 
         let service_incoming_access_control = options.service_incoming_access_control.clone();
+        // The service only sends messages to its forwarders, when they are registered again
+        let service_outgoing_access_control = AllowRegistrationAddresses(options.registry());
 
         let s = Self { options };
 
         WorkerBuilder::new(s)
             .with_address(address)
             .with_incoming_access_control_arc(service_incoming_access_control)
-            .with_outgoing_access_control(DenyAll)
+            .with_outgoing_access_control(service_outgoing_access_control)
             .start(ctx)
             .await?;
 
@@ -52,6 +62,9 @@ impl Worker for ForwardingService {
         msg: Routed<Self::Message>,
     ) -> Result<()> {
         let forward_route = msg.return_route();
+        let registrant = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id());
         let payload = msg.into_transport_message().payload;
 
         let random_address = Address::random_tagged("Forwarder.service");
@@ -66,15 +79,35 @@ impl Worker for ForwardingService {
             None => random_address,
         };
 
+        if let Some((registration_address, previous_registrant)) =
+            self.options.registry.registration(&address)
+        {
+            match (previous_registrant, registrant) {
+                (Some(previous), Some(registrant)) if previous == registrant => (),
+                _ => {
+                    warn!("Rejecting a new registration of the alias {}", address);
+                    return Ok(());
+                }
+            }
+            let msg = TransportMessage::v1(registration_address, forward_route, payload);
+            return ctx.forward(LocalMessage::new(msg, Vec::new())).await;
+        }
+
         self.options
             .setup_flow_control_for_forwarder(ctx.flow_controls(), &address);
 
         Forwarder::create(
             ctx,
-            address,
-            forward_route,
-            payload,
+            ForwarderRegistration {
+                address,
+                forward_route,
+                payload,
+                registrant,
+            },
+            ctx.address(),
             self.options.forwarders_incoming_access_control.clone(),
+            self.options.buffer.clone(),
+            self.options.registry(),
         )
         .await?;
 
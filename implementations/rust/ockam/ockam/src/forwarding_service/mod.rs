@@ -1,7 +1,11 @@
+mod buffer;
 mod forwarder;
 #[allow(clippy::module_inception)]
 mod forwarding_service;
 mod options;
+mod registry;
 
+pub use buffer::{ForwarderBufferOptions, ForwarderBufferStats, ForwarderBufferStorage};
 pub use forwarding_service::*;
 pub use options::*;
+pub use registry::{ForwarderStatus, ForwardersRegistry};
@@ -1,3 +1,4 @@
+use crate::forwarding_service::{ForwarderBufferOptions, ForwardersRegistry};
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
@@ -9,6 +10,8 @@ pub struct ForwardingServiceOptions {
     pub(super) forwarders_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(super) consumer_service: Vec<FlowControlId>,
     pub(super) consumer_forwarder: Vec<FlowControlId>,
+    pub(super) buffer: Option<ForwarderBufferOptions>,
+    pub(super) registry: ForwardersRegistry,
 }
 
 impl ForwardingServiceOptions {
@@ -19,6 +22,8 @@ impl ForwardingServiceOptions {
             forwarders_incoming_access_control: Arc::new(AllowAll),
             consumer_service: vec![],
             consumer_forwarder: vec![],
+            buffer: None,
+            registry: ForwardersRegistry::default(),
         }
     }
 
@@ -72,6 +77,19 @@ impl ForwardingServiceOptions {
         self
     }
 
+    /// Buffer the messages of the spawned forwarders while the node which registered
+    /// them is unreachable, and deliver them when that node registers again
+    pub fn with_buffer(mut self, buffer: ForwarderBufferOptions) -> Self {
+        self.buffer = Some(buffer);
+        self
+    }
+
+    /// Registry of the spawned forwarders, which can be used to list them
+    /// once the service is started
+    pub fn registry(&self) -> ForwardersRegistry {
+        self.registry.clone()
+    }
+
     pub(super) fn setup_flow_control_for_forwarding_service(
         &self,
         flow_controls: &FlowControls,
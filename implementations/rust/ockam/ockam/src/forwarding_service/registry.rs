@@ -0,0 +1,113 @@
+use crate::forwarding_service::ForwarderBufferStats;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, OutgoingAccessControl, RelayMessage, Result, Route};
+use ockam_identity::IdentityIdentifier;
+
+/// Status of a forwarder created by a [`ForwardingService`](crate::ForwardingService)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwarderStatus {
+    /// Alias of the forwarder
+    pub address: Address,
+    /// Route to the node which registered the forwarder
+    pub forward_route: Route,
+    /// Statistics of the forwarder buffer, if the buffered mode is enabled
+    pub stats: Option<ForwarderBufferStats>,
+}
+
+#[derive(Debug)]
+struct ForwarderEntry {
+    registration_address: Address,
+    registrant: Option<IdentityIdentifier>,
+    status: ForwarderStatus,
+}
+
+/// Forwarders created by a [`ForwardingService`](crate::ForwardingService)
+///
+/// The registry is shared between the service and its forwarders, and can be kept by the
+/// caller to list the forwarders, see [`ForwardingServiceOptions::registry`](crate::ForwardingServiceOptions::registry).
+#[derive(Debug, Clone, Default)]
+pub struct ForwardersRegistry {
+    entries: Arc<Mutex<BTreeMap<Address, ForwarderEntry>>>,
+}
+
+impl ForwardersRegistry {
+    /// Status of all the forwarders
+    pub fn list(&self) -> Vec<ForwarderStatus> {
+        let entries = self.entries.lock().unwrap();
+        entries.values().map(|e| e.status.clone()).collect()
+    }
+
+    /// Status of a forwarder
+    pub fn get(&self, address: &Address) -> Option<ForwarderStatus> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(address).map(|e| e.status.clone())
+    }
+
+    pub(super) fn insert(
+        &self,
+        registration_address: Address,
+        registrant: Option<IdentityIdentifier>,
+        status: ForwarderStatus,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            status.address.clone(),
+            ForwarderEntry {
+                registration_address,
+                registrant,
+                status,
+            },
+        );
+    }
+
+    pub(super) fn remove(&self, address: &Address) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(address);
+    }
+
+    pub(super) fn update(&self, status: ForwarderStatus) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(&status.address) {
+            entry.status = status;
+        }
+    }
+
+    /// Address receiving the new registrations of an existing forwarder, and identity of
+    /// the node which registered it, if it was registered through a secure channel
+    pub(super) fn registration(
+        &self,
+        address: &Address,
+    ) -> Option<(Address, Option<IdentityIdentifier>)> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(address)
+            .map(|e| (e.registration_address.clone(), e.registrant.clone()))
+    }
+
+    fn is_registration_address(&self, address: &Address) -> bool {
+        let entries = self.entries.lock().unwrap();
+        entries.values().any(|e| &e.registration_address == address)
+    }
+}
+
+/// Allow the [`ForwardingService`](crate::ForwardingService) to only send messages to the
+/// registration addresses of its forwarders
+#[derive(Debug)]
+pub(super) struct AllowRegistrationAddresses(pub(super) ForwardersRegistry);
+
+#[async_trait]
+impl OutgoingAccessControl for AllowRegistrationAddresses {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        if self
+            .0
+            .is_registration_address(relay_msg.onward_route().next()?)
+        {
+            ockam_core::allow()
+        } else {
+            ockam_core::deny()
+        }
+    }
+}
@@ -74,7 +74,10 @@ mod system;
 mod unique;
 
 pub use error::OckamError;
-pub use forwarding_service::{ForwardingService, ForwardingServiceOptions};
+pub use forwarding_service::{
+    ForwarderBufferOptions, ForwarderBufferStats, ForwarderBufferStorage, ForwarderStatus,
+    ForwardersRegistry, ForwardingService, ForwardingServiceOptions,
+};
 pub use metadata::OckamMessage;
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
@@ -159,8 +162,8 @@ pub mod vault {
 
 #[cfg(feature = "ockam_transport_tcp")]
 pub use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
-    TcpTransportExtension,
+    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpProxy,
+    TcpProxySettings, TcpTransport, TcpTransportExtension,
 };
 
 /// List of all top-level services
@@ -1,8 +1,12 @@
 use ockam::remote::{RemoteForwarder, RemoteForwarderOptions};
 use ockam::workers::Echoer;
-use ockam::{ForwardingService, ForwardingServiceOptions};
-use ockam_core::{route, AllowAll, Result};
-use ockam_identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
+use ockam::{
+    ForwarderBufferOptions, ForwardersRegistry, ForwardingService, ForwardingServiceOptions,
+};
+use ockam_core::{route, Address, AllowAll, Result};
+use ockam_identity::{
+    secure_channels, Identity, SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
+};
 use ockam_node::{Context, MessageReceiveOptions};
 use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
 use std::time::Duration;
@@ -259,3 +263,208 @@ async fn test4(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+// Cloud: Hosts a Forwarding service with buffered forwarders and listens on a tcp port
+// Server: Connects to the Cloud using a secure channel, registers a static alias, disconnects,
+//         and registers the same alias again using a new secure channel
+// Client: Sends messages to the Server while it is disconnected, they are delivered to the
+//         Server once it has registered again
+#[ockam_macros::test]
+async fn test_buffered_forwarder(ctx: &mut Context) -> Result<()> {
+    let cloud = Cloud::create(ctx, Some(ForwarderBufferOptions::new(10))).await?;
+
+    let secure_channels = secure_channels();
+    let server_identity = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let mut server_ctx = ctx.new_detached("server", AllowAll, AllowAll).await?;
+    let server_tcp = TcpTransport::create(ctx).await?;
+    let (cloud_connection, cloud_channel) = cloud
+        .connect(ctx, &server_tcp, &secure_channels, &server_identity)
+        .await?;
+    let remote_info = RemoteForwarder::create_static_without_heartbeats(
+        ctx,
+        cloud_channel,
+        "alias",
+        RemoteForwarderOptions::new(),
+    )
+    .await?;
+
+    let client_tcp = TcpTransport::create(ctx).await?;
+    let client_connection = client_tcp
+        .connect(&cloud.address, TcpConnectionOptions::new())
+        .await?;
+    let server_route = route![client_connection, remote_info.remote_address(), "server"];
+
+    ctx.send(server_route.clone(), "1".to_string()).await?;
+    assert_eq!(server_ctx.receive::<String>().await?.body(), "1");
+
+    // The messages sent while the Server is disconnected are buffered
+    server_tcp.disconnect(&cloud_connection).await?;
+    // the secure channel of the Cloud is closed with the connection
+    let forward_route = cloud.registry.get(&"alias".into()).unwrap().forward_route;
+    ctx.stop_worker(forward_route.next()?.clone()).await?;
+    ctx.sleep(Duration::from_millis(250)).await;
+    ctx.send(server_route.clone(), "2".to_string()).await?;
+    ctx.send(server_route.clone(), "3".to_string()).await?;
+    ctx.sleep(Duration::from_millis(250)).await;
+
+    let stats = cloud.registry.get(&"alias".into()).unwrap().stats.unwrap();
+    assert_eq!(stats.buffered, 2);
+
+    // The buffered messages are delivered once the Server registers again
+    let (_, cloud_channel) = cloud
+        .connect(ctx, &server_tcp, &secure_channels, &server_identity)
+        .await?;
+    RemoteForwarder::create_static_without_heartbeats(
+        ctx,
+        cloud_channel,
+        "alias",
+        RemoteForwarderOptions::new(),
+    )
+    .await?;
+
+    assert_eq!(server_ctx.receive::<String>().await?.body(), "2");
+    assert_eq!(server_ctx.receive::<String>().await?.body(), "3");
+
+    let stats = cloud.registry.get(&"alias".into()).unwrap().stats.unwrap();
+    assert_eq!(stats.buffered, 0);
+    assert_eq!(stats.redelivered, 2);
+
+    ctx.stop().await
+}
+
+// Cloud: Hosts a Forwarding service and listens on a tcp port
+// Server: Registers a static alias through a secure channel
+// Others: Try to register the same alias, with another identity or without a secure channel
+#[ockam_macros::test]
+async fn test_alias_cannot_be_taken_over(ctx: &mut Context) -> Result<()> {
+    let cloud = Cloud::create(ctx, None).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let server_identity = identities_creation.create_identity().await?;
+    let other_identity = identities_creation.create_identity().await?;
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let (_, cloud_channel) = cloud
+        .connect(ctx, &tcp, &secure_channels, &server_identity)
+        .await?;
+    RemoteForwarder::create_static_without_heartbeats(
+        ctx,
+        cloud_channel.clone(),
+        "alias",
+        RemoteForwarderOptions::new(),
+    )
+    .await?;
+    let forward_route = cloud.registry.get(&"alias".into()).unwrap().forward_route;
+
+    let (_, other_channel) = cloud
+        .connect(ctx, &tcp, &secure_channels, &other_identity)
+        .await?;
+    let plain_connection = tcp
+        .connect(&cloud.address, TcpConnectionOptions::new())
+        .await?;
+    for hub_route in [route![other_channel], route![plain_connection]] {
+        let mut child = ctx
+            .new_detached(Address::random_local(), AllowAll, AllowAll)
+            .await?;
+        if let Some(info) = ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(hub_route.next()?)
+        {
+            ctx.flow_controls()
+                .add_consumer(child.address(), info.flow_control_id());
+        }
+        child
+            .send(route![hub_route, "forwarding_service"], "alias".to_string())
+            .await?;
+        let reply = child
+            .receive_extended::<String>(
+                MessageReceiveOptions::new().with_timeout(Duration::from_millis(500)),
+            )
+            .await;
+        assert!(reply.is_err());
+        assert_eq!(
+            cloud.registry.get(&"alias".into()).unwrap().forward_route,
+            forward_route
+        );
+    }
+
+    ctx.stop().await
+}
+
+/// Node hosting a Forwarding service, reachable with a tcp connection and with a secure channel
+struct Cloud {
+    address: String,
+    registry: ForwardersRegistry,
+}
+
+impl Cloud {
+    async fn create(ctx: &Context, buffer: Option<ForwarderBufferOptions>) -> Result<Cloud> {
+        let tcp_listener_options = TcpListenerOptions::new();
+        let secure_channel_listener_options = SecureChannelListenerOptions::new()
+            .as_consumer(&tcp_listener_options.spawner_flow_control_id());
+
+        let mut options = ForwardingServiceOptions::new()
+            .service_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+            .service_as_consumer(&secure_channel_listener_options.spawner_flow_control_id())
+            .forwarder_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+            .forwarder_as_consumer(&secure_channel_listener_options.spawner_flow_control_id());
+        if let Some(buffer) = buffer {
+            options = options.with_buffer(buffer);
+        }
+        let registry = options.registry();
+        ForwardingService::create(ctx, "forwarding_service", options).await?;
+
+        let secure_channels = secure_channels();
+        let cloud_identity = secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+        secure_channels
+            .create_secure_channel_listener(
+                ctx,
+                &cloud_identity.identifier(),
+                "cloud_listener",
+                secure_channel_listener_options,
+            )
+            .await?;
+
+        let tcp = TcpTransport::create(ctx).await?;
+        let listener = tcp.listen("127.0.0.1:0", tcp_listener_options).await?;
+        Ok(Cloud {
+            address: listener.socket_string(),
+            registry,
+        })
+    }
+
+    /// Connect to the Cloud and create a secure channel to it for the given identity.
+    /// The "server" address can receive the messages sent through the secure channel
+    async fn connect(
+        &self,
+        ctx: &Context,
+        tcp: &TcpTransport,
+        secure_channels: &SecureChannels,
+        identity: &Identity,
+    ) -> Result<(Address, Address)> {
+        let connection = tcp
+            .connect(&self.address, TcpConnectionOptions::new())
+            .await?;
+        let options = SecureChannelOptions::new();
+        ctx.flow_controls()
+            .add_consumer("server", &options.producer_flow_control_id());
+        let channel = secure_channels
+            .create_secure_channel(
+                ctx,
+                &identity.identifier(),
+                route![connection.clone(), "cloud_listener"],
+                options,
+            )
+            .await?;
+        Ok((connection.sender_address().clone(), channel.into()))
+    }
+}
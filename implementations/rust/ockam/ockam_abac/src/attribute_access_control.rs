@@ -106,6 +106,11 @@ impl AbacAccessControl {
                     }
                 }
             }
+            // Typed values take precedence over the plain value of an attribute with
+            // the same name. Lists become sequences, which can be checked with `member?`
+            for (key, value) in attrs.values() {
+                environment.put(format!("subject.{key}"), Expr::from(value.clone()));
+            }
         };
 
         // add the identifier itself as a subject parameter
@@ -9,6 +9,7 @@ use core::fmt;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::string::String;
 use ockam_core::compat::vec::{vec, Vec};
+use ockam_identity::credential::AttributeValue;
 
 #[derive(Debug, Clone, Encode, Decode)]
 #[rustfmt::skip]
@@ -44,6 +45,17 @@ impl From<Val> for Expr {
     }
 }
 
+impl From<AttributeValue> for Expr {
+    fn from(v: AttributeValue) -> Self {
+        match v {
+            AttributeValue::Str(s) => Expr::Str(s),
+            AttributeValue::Int(i) => Expr::Int(i),
+            AttributeValue::Bool(b) => Expr::Bool(b),
+            AttributeValue::List(xs) => Expr::Seq(xs.into_iter().map(Expr::from).collect()),
+        }
+    }
+}
+
 impl Expr {
     pub fn is_true(&self) -> bool {
         matches!(self, Expr::Bool(true))
@@ -385,6 +397,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn attribute_values() {
+        use ockam_identity::credential::AttributeValue;
+
+        let mut env = Env::new();
+        env.put(
+            "subject.groups",
+            Expr::from(AttributeValue::from(vec!["admins", "devs"])),
+        )
+        .put("subject.level", Expr::from(AttributeValue::from(3)))
+        .put("subject.active", Expr::from(AttributeValue::from(true)));
+
+        let allowed = |policy: &str| eval(&parse(policy).unwrap().unwrap(), &env).unwrap();
+        assert!(allowed(r#"(member? "devs" subject.groups)"#).is_true());
+        assert!(allowed(r#"(member? "ops" subject.groups)"#).is_false());
+        assert!(allowed("(and (> subject.level 2) subject.active)").is_true());
+    }
+
     #[test]
     fn write_read() {
         fn property(e: Expr) -> bool {
@@ -1 +1,2 @@
 pub mod direct;
+pub mod expiry;
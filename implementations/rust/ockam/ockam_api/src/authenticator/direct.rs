@@ -1,26 +1,36 @@
+pub mod enrollment_tokens;
 pub mod types;
 
 use core::str;
-use lru::LruCache;
+use core::str::FromStr;
 use minicbor::Decoder;
-use ockam::identity::{AttributesEntry, IdentityAttributesReader, IdentityAttributesWriter};
+use ockam::identity::{
+    AttributeValue, AttributesEntry, IdentityAttributesReader, IdentityAttributesWriter,
+};
 use ockam::identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
-use ockam::identity::{OneTimeCode, Timestamp};
+use ockam::identity::{OneTimeCode, Storage, Timestamp};
 use ockam_core::api::{self, Method, Request, Response, Status};
-use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{self, CowStr, Result, Routed, Worker};
+use ockam_core::{self, CowStr, Error, Result, Routed, Worker};
 use ockam_identity::{secure_channel_required, LEGACY_ID, TRUST_CONTEXT_ID};
 use ockam_node::{Context, RpcClient};
-use std::collections::HashMap;
-use std::num::NonZeroUsize;
-use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 use tracing::{trace, warn};
 use types::AddMember;
 
-use crate::authenticator::direct::types::CreateToken;
+use crate::authenticator::direct::enrollment_tokens::{now, EnrollmentTokensStorage};
+use crate::authenticator::direct::types::{CreateToken, EnrollmentToken};
 
-const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
+/// Default duration of an enrollment token, when not specified by the enroller
+const DEFAULT_TOKEN_DURATION: Duration = Duration::from_secs(600);
+
+/// Maximum duration of an enrollment token
+const MAX_TOKEN_DURATION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Default number of times an enrollment token can be used, when not specified by the enroller
+const DEFAULT_TOKEN_USAGE_COUNT: u64 = 1;
 
 /// Schema identifier for a project membership credential.
 ///
@@ -170,6 +180,8 @@ impl DirectAuthenticator {
         enroller: &IdentityIdentifier,
         id: &IdentityIdentifier,
         attrs: &HashMap<CowStr<'a>, CowStr<'a>>,
+        values: BTreeMap<String, AttributeValue>,
+        ttl_secs: Option<u64>,
     ) -> Result<()> {
         let auth_attrs = attrs
             .iter()
@@ -185,12 +197,14 @@ impl DirectAuthenticator {
                 .into_iter(),
             )
             .collect();
+        let now = Timestamp::now().unwrap();
         let entry = AttributesEntry::new(
             auth_attrs,
-            Timestamp::now().unwrap(),
-            None,
+            now,
+            ttl_secs.map(|ttl| now.add_seconds(ttl)),
             Some(enroller.clone()),
-        );
+        )
+        .with_values(values);
         self.attributes_writer.put_attributes(id, entry).await
     }
 
@@ -233,8 +247,14 @@ impl Worker for DirectAuthenticator {
             let res = match (req.method(), path_segments.as_slice()) {
                 (Some(Method::Post), [""]) | (Some(Method::Post), ["members"]) => {
                     let add: AddMember = dec.decode()?;
-                    self.add_member(&from, add.member(), add.attributes())
-                        .await?;
+                    self.add_member(
+                        &from,
+                        add.member(),
+                        add.attributes(),
+                        add.attribute_values(),
+                        add.ttl_secs(),
+                    )
+                    .await?;
                     Response::ok(req.id()).to_vec()?
                 }
                 (Some(Method::Get), ["member_ids"]) => {
@@ -275,7 +295,7 @@ impl Worker for DirectAuthenticator {
 #[derive(Clone)]
 pub struct EnrollmentTokenAuthenticator {
     trust_context: String,
-    tokens: Arc<RwLock<LruCache<[u8; 32], Token>>>,
+    tokens: EnrollmentTokensStorage,
 }
 
 pub struct EnrollmentTokenIssuer(EnrollmentTokenAuthenticator);
@@ -286,15 +306,16 @@ pub struct EnrollmentTokenAcceptor(
 );
 
 impl EnrollmentTokenAuthenticator {
+    /// Create an issuer and an acceptor of enrollment tokens.
+    /// The tokens are persisted in the given storage
     pub fn new_worker_pair(
         trust_context: String,
         attributes_writer: Arc<dyn IdentityAttributesWriter>,
+        storage: Arc<dyn Storage>,
     ) -> (EnrollmentTokenIssuer, EnrollmentTokenAcceptor) {
         let base = Self {
             trust_context,
-            tokens: Arc::new(RwLock::new(LruCache::new(
-                NonZeroUsize::new(128).expect("0 < 128"),
-            ))),
+            tokens: EnrollmentTokensStorage::new(storage),
         };
         (
             EnrollmentTokenIssuer(base.clone()),
@@ -307,28 +328,61 @@ impl EnrollmentTokenIssuer {
     async fn issue_token(
         &self,
         enroller: &IdentityIdentifier,
-        attrs: HashMap<String, String>,
+        create_token: CreateToken<'_>,
     ) -> Result<OneTimeCode> {
         let otc = OneTimeCode::new();
-        let tkn = Token {
-            attrs,
-            generated_by: enroller.clone(),
-            time: Instant::now(),
-        };
-        self.0
+        let now = now()?;
+        let ttl_secs = create_token
+            .ttl_secs()
+            .unwrap_or(DEFAULT_TOKEN_DURATION.as_secs());
+        if ttl_secs == 0 || ttl_secs > MAX_TOKEN_DURATION.as_secs() {
+            return Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                format!(
+                    "the duration of an enrollment token must be between 1 and {} seconds",
+                    MAX_TOKEN_DURATION.as_secs()
+                ),
+            ));
+        }
+        let ttl_count = create_token
+            .ttl_count()
+            .unwrap_or(DEFAULT_TOKEN_USAGE_COUNT);
+        if ttl_count == 0 {
+            return Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                "an enrollment token must be usable at least once",
+            ));
+        }
+        let required_identifier = create_token.required_identifier().cloned();
+        let member_ttl_secs = create_token.member_ttl_secs();
+        let attribute_values = create_token.attribute_values();
+        let tkn = EnrollmentToken::new(
+            otc.clone(),
+            create_token.into_owned_attributes(),
+            enroller.clone(),
+            now,
+            now.add_seconds(ttl_secs),
+            ttl_count,
+            required_identifier,
+        )
+        .with_member_ttl_secs(member_ttl_secs)
+        .with_attribute_values(attribute_values);
+        self.0.tokens.put_token(&tkn).await?;
+        Ok(otc)
+    }
+
+    /// Return the tokens generated by a given enroller
+    async fn list_tokens(&self, enroller: &IdentityIdentifier) -> Result<Vec<EnrollmentToken>> {
+        Ok(self
+            .0
             .tokens
-            .write()
-            .map(|mut r| {
-                r.put(*otc.code(), tkn);
-                otc
-            })
-            .map_err(|_| {
-                ockam_core::Error::new(
-                    Origin::Other,
-                    Kind::Internal,
-                    "failed to get read lock on tokens table",
-                )
-            })
+            .list_tokens()
+            .await?
+            .into_iter()
+            .filter(|t| t.generated_by() == enroller)
+            .collect())
     }
 }
 
@@ -351,14 +405,37 @@ impl Worker for EnrollmentTokenIssuer {
                 body   = %req.has_body(),
                 "request"
             }
-            let res = match (req.method(), req.path()) {
-                (Some(Method::Post), "/") | (Some(Method::Post), "/tokens") => {
+            let path_segments = req.path_segments::<5>();
+            let res = match (req.method(), path_segments.as_slice()) {
+                (Some(Method::Post), [""]) | (Some(Method::Post), ["tokens"]) => {
                     let att: CreateToken = dec.decode()?;
-                    match self.issue_token(&from, att.into_owned_attributes()).await {
+                    match self.issue_token(&from, att).await {
                         Ok(otc) => Response::ok(req.id()).body(&otc).to_vec()?,
+                        Err(error) if error.code().kind == Kind::Invalid => {
+                            api::bad_request(&req, &error.to_string()).to_vec()?
+                        }
                         Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
                     }
                 }
+                // List the tokens generated by our identity (enroller)
+                (Some(Method::Get), ["tokens"]) => {
+                    let tokens = self.list_tokens(&from).await?;
+                    Response::ok(req.id()).body(tokens).to_vec()?
+                }
+                // Revoke a token if it was generated by our identity (enroller)
+                (Some(Method::Delete), ["tokens", code]) => {
+                    let otc = OneTimeCode::from_str(code)?;
+                    match self.0.tokens.get_token(&otc).await? {
+                        Some(token) if token.generated_by() != &from => {
+                            api::forbidden(&req, "not generated by current enroller").to_vec()?
+                        }
+                        Some(_) => {
+                            self.0.tokens.delete_token(&otc).await?;
+                            Response::ok(req.id()).to_vec()?
+                        }
+                        None => Response::ok(req.id()).to_vec()?,
+                    }
+                }
                 _ => api::unknown_path(&req).to_vec()?,
             };
             c.send(m.return_route(), res).await
@@ -368,6 +445,32 @@ impl Worker for EnrollmentTokenIssuer {
     }
 }
 
+impl EnrollmentTokenAcceptor {
+    /// Check that a token can be used by a given identity and record its use.
+    /// The token is deleted once it has been used as many times as allowed
+    async fn use_token(
+        &self,
+        from: &IdentityIdentifier,
+        otc: &OneTimeCode,
+    ) -> Result<core::result::Result<EnrollmentToken, &'static str>> {
+        let mut tkn = match self.0.tokens.get_token(otc).await? {
+            Some(tkn) => tkn,
+            None => return Ok(Err("unknown or expired token")),
+        };
+        if let Some(required) = tkn.required_identifier() {
+            if required != from {
+                return Ok(Err("token not issued for this identity"));
+            }
+        }
+        if tkn.use_once() {
+            self.0.tokens.put_token(&tkn).await?;
+        } else {
+            self.0.tokens.delete_token(otc).await?;
+        }
+        Ok(Ok(tkn))
+    }
+}
+
 #[ockam_core::worker]
 impl Worker for EnrollmentTokenAcceptor {
     type Context = Context;
@@ -389,31 +492,13 @@ impl Worker for EnrollmentTokenAcceptor {
             }
             let res = match (req.method(), req.path()) {
                 (Some(Method::Post), "/") | (Some(Method::Post), "/credential") => {
-                    //TODO: move out of the worker handle_message implementation
                     let otc: OneTimeCode = dec.decode()?;
-                    let token = match self.0.tokens.write() {
-                        Ok(mut r) => {
-                            if let Some(tkn) = r.pop(otc.code()) {
-                                if tkn.time.elapsed() > MAX_TOKEN_DURATION {
-                                    Err(api::forbidden(&req, "expired token"))
-                                } else {
-                                    Ok(tkn)
-                                }
-                            } else {
-                                Err(api::forbidden(&req, "unknown token"))
-                            }
-                        }
-                        Err(_) => Err(api::internal_error(
-                            &req,
-                            "Failed to get read lock on tokens table",
-                        )),
-                    };
-                    match token {
+                    match self.use_token(&from, &otc).await? {
                         Ok(tkn) => {
                             //TODO: fixme:  unify use of hashmap vs btreemap
                             let trust_context = self.0.trust_context.as_bytes().to_vec();
                             let attrs = tkn
-                                .attrs
+                                .attrs()
                                 .iter()
                                 .map(|(k, v)| (k.to_string(), v.as_bytes().to_vec()))
                                 .chain(
@@ -424,16 +509,18 @@ impl Worker for EnrollmentTokenAcceptor {
                                     .into_iter(),
                                 )
                                 .collect();
+                            let now = Timestamp::now().unwrap();
                             let entry = AttributesEntry::new(
                                 attrs,
-                                Timestamp::now().unwrap(),
-                                None,
-                                Some(tkn.generated_by),
-                            );
+                                now,
+                                tkn.member_ttl_secs().map(|ttl| now.add_seconds(ttl)),
+                                Some(tkn.generated_by().clone()),
+                            )
+                            .with_values(tkn.attribute_values());
                             self.1.put_attributes(&from, entry).await?;
                             Response::ok(req.id()).to_vec()?
                         }
-                        Err(err) => err.to_vec()?,
+                        Err(message) => api::forbidden(&req, message).to_vec()?,
                     }
                 }
                 _ => api::unknown_path(&req).to_vec()?,
@@ -445,12 +532,6 @@ impl Worker for EnrollmentTokenAcceptor {
     }
 }
 
-struct Token {
-    attrs: HashMap<String, String>,
-    generated_by: IdentityIdentifier,
-    time: Instant,
-}
-
 pub struct DirectAuthenticatorClient(RpcClient);
 
 impl DirectAuthenticatorClient {
@@ -458,14 +539,35 @@ impl DirectAuthenticatorClient {
         DirectAuthenticatorClient(client)
     }
 
+    /// Add a member with some attributes.
+    /// If a duration is given, the member attributes expire after that duration
     pub async fn add_member(
         &self,
         id: IdentityIdentifier,
         attributes: HashMap<&str, &str>,
+        duration: Option<Duration>,
+    ) -> Result<()> {
+        self.add_member_with_attribute_values(id, attributes, BTreeMap::new(), duration)
+            .await
+    }
+
+    /// Add a member with some plain and typed attributes.
+    /// If a duration is given, the member attributes expire after that duration
+    pub async fn add_member_with_attribute_values(
+        &self,
+        id: IdentityIdentifier,
+        attributes: HashMap<&str, &str>,
+        attribute_values: BTreeMap<String, AttributeValue>,
+        duration: Option<Duration>,
     ) -> Result<()> {
         self.0
             .request_no_resp_body(
-                &Request::post("/").body(AddMember::new(id).with_attributes(attributes)),
+                &Request::post("/").body(
+                    AddMember::new(id)
+                        .with_attributes(attributes)
+                        .with_attribute_values(attribute_values)
+                        .with_duration(duration),
+                ),
             )
             .await
     }
@@ -492,9 +594,20 @@ impl TokenIssuerClient {
         TokenIssuerClient(client)
     }
 
-    pub async fn create_token(&self, attributes: HashMap<&str, &str>) -> Result<OneTimeCode> {
+    /// Create an enrollment token. The request is built with [`CreateToken::new`] and its
+    /// `with_*` methods, which set the attributes, the expiration and the usage count of the
+    /// token
+    pub async fn create_token(&self, req: CreateToken<'_>) -> Result<OneTimeCode> {
+        self.0.request(&Request::post("/").body(req)).await
+    }
+
+    pub async fn list_tokens(&self) -> Result<Vec<EnrollmentToken>> {
+        self.0.request(&Request::get("/tokens")).await
+    }
+
+    pub async fn revoke_token(&self, code: &OneTimeCode) -> Result<()> {
         self.0
-            .request(&Request::post("/").body(CreateToken::new().with_attributes(attributes)))
+            .request_no_resp_body(&Request::delete(format!("/tokens/{}", code.to_string())))
             .await
     }
 }
@@ -0,0 +1,162 @@
+use crate::authenticator::direct::types::EnrollmentToken;
+use ockam::identity::{OneTimeCode, Storage, Timestamp};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+
+/// Namespace used to store enrollment tokens in a `Storage`
+const ENROLLMENT_TOKENS_KEY: &str = "ENROLLMENT_TOKENS";
+
+/// Persistent table of the enrollment tokens issued by an authority.
+///
+/// Tokens are stored with their one-time code, hex-encoded, as a key so that they can
+/// survive a restart of the authority node.
+#[derive(Clone)]
+pub struct EnrollmentTokensStorage {
+    storage: Arc<dyn Storage>,
+}
+
+impl EnrollmentTokensStorage {
+    /// Create a new enrollment tokens storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Store a token. A token with the same one-time code is overridden
+    pub async fn put_token(&self, token: &EnrollmentToken) -> Result<()> {
+        self.storage
+            .set(
+                &token.one_time_code().to_string(),
+                ENROLLMENT_TOKENS_KEY.to_string(),
+                minicbor::to_vec(token)?,
+            )
+            .await
+    }
+
+    /// Return the token for a given one-time code.
+    /// An expired token is deleted and not returned
+    pub async fn get_token(&self, code: &OneTimeCode) -> Result<Option<EnrollmentToken>> {
+        let id = code.to_string();
+        let token: EnrollmentToken = match self.storage.get(&id, ENROLLMENT_TOKENS_KEY).await? {
+            Some(bytes) => minicbor::decode(&bytes)?,
+            None => return Ok(None),
+        };
+
+        if token.is_expired(now()?) {
+            self.storage.del(&id, ENROLLMENT_TOKENS_KEY).await?;
+            Ok(None)
+        } else {
+            Ok(Some(token))
+        }
+    }
+
+    /// Delete a token, if it exists
+    pub async fn delete_token(&self, code: &OneTimeCode) -> Result<()> {
+        self.storage
+            .del(&code.to_string(), ENROLLMENT_TOKENS_KEY)
+            .await
+    }
+
+    /// List all the tokens which have not expired yet
+    pub async fn list_tokens(&self) -> Result<Vec<EnrollmentToken>> {
+        let mut tokens = vec![];
+        for id in self.storage.keys(ENROLLMENT_TOKENS_KEY).await? {
+            let code = id.parse::<OneTimeCode>()?;
+            if let Some(token) = self.get_token(&code).await? {
+                tokens.push(token)
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+/// Return the current time
+pub(super) fn now() -> Result<Timestamp> {
+    Timestamp::now().ok_or_else(|| Error::new(Origin::Core, Kind::Internal, "invalid system time"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::{IdentityIdentifier, InMemoryStorage, LmdbStorage};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_expired_tokens_are_not_returned() -> Result<()> {
+        let tokens = EnrollmentTokensStorage::new(InMemoryStorage::create());
+        let enroller = IdentityIdentifier::try_from(
+            "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a",
+        )?;
+        let now = now()?;
+
+        let valid = EnrollmentToken::new(
+            OneTimeCode::new(),
+            HashMap::new(),
+            enroller.clone(),
+            now,
+            now.add_seconds(60),
+            1,
+            None,
+        );
+        let expired = EnrollmentToken::new(
+            OneTimeCode::new(),
+            HashMap::new(),
+            enroller,
+            now,
+            now,
+            1,
+            None,
+        );
+        tokens.put_token(&valid).await?;
+        tokens.put_token(&expired).await?;
+
+        assert_eq!(
+            tokens.get_token(valid.one_time_code()).await?,
+            Some(valid.clone())
+        );
+        assert_eq!(tokens.get_token(expired.one_time_code()).await?, None);
+        assert_eq!(tokens.list_tokens().await?, vec![valid.clone()]);
+
+        tokens.delete_token(valid.one_time_code()).await?;
+        assert!(tokens.list_tokens().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_persisted_in_lmdb() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.lmdb");
+        let enroller = IdentityIdentifier::try_from(
+            "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a",
+        )?;
+        let now = now()?;
+
+        let mut token = EnrollmentToken::new(
+            OneTimeCode::new(),
+            HashMap::from([("component".to_string(), "edge".to_string())]),
+            enroller,
+            now,
+            now.add_seconds(60),
+            2,
+            None,
+        );
+        {
+            let tokens = EnrollmentTokensStorage::new(Arc::new(LmdbStorage::new(&path).await?));
+            tokens.put_token(&token).await?;
+            assert!(token.use_once());
+            tokens.put_token(&token).await?;
+        }
+
+        // the tokens, and their usage count, survive a restart of the authority
+        let tokens = EnrollmentTokensStorage::new(Arc::new(LmdbStorage::new(&path).await?));
+        assert_eq!(
+            tokens.get_token(token.one_time_code()).await?,
+            Some(token.clone())
+        );
+        assert_eq!(tokens.list_tokens().await?, vec![token.clone()]);
+
+        tokens.delete_token(token.one_time_code()).await?;
+        assert!(tokens.list_tokens().await?.is_empty());
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use minicbor::{Decode, Encode};
-use ockam::identity::IdentityIdentifier;
+use ockam::identity::{AttributeValue, IdentityIdentifier, OneTimeCode, Timestamp};
 use ockam_core::CowStr;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -14,6 +16,8 @@ pub struct AddMember<'a> {
     #[n(0)] tag: TypeTag<2820828>,
     #[n(1)] member: IdentityIdentifier,
     #[b(2)] attributes: HashMap<CowStr<'a>, CowStr<'a>>,
+    #[n(3)] ttl_secs: Option<u64>,
+    #[n(4)] attribute_values: Option<BTreeMap<String, AttributeValue>>,
 }
 
 impl<'a> AddMember<'a> {
@@ -23,6 +27,8 @@ impl<'a> AddMember<'a> {
             tag: TypeTag,
             member,
             attributes: HashMap::new(),
+            ttl_secs: None,
+            attribute_values: None,
         }
     }
 
@@ -34,6 +40,22 @@ impl<'a> AddMember<'a> {
         self
     }
 
+    /// Set typed attribute values, for example a list of groups
+    pub fn with_attribute_values(mut self, values: BTreeMap<String, AttributeValue>) -> Self {
+        self.attribute_values = if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        };
+        self
+    }
+
+    /// Set the duration after which the member attributes expire
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.ttl_secs = duration.map(|d| d.as_secs());
+        self
+    }
+
     pub fn member(&self) -> &IdentityIdentifier {
         &self.member
     }
@@ -41,6 +63,14 @@ impl<'a> AddMember<'a> {
     pub fn attributes(&self) -> &HashMap<CowStr, CowStr> {
         &self.attributes
     }
+
+    pub fn ttl_secs(&self) -> Option<u64> {
+        self.ttl_secs
+    }
+
+    pub fn attribute_values(&self) -> BTreeMap<String, AttributeValue> {
+        self.attribute_values.clone().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Decode, Encode)]
@@ -50,6 +80,11 @@ pub struct CreateToken<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<2502742>,
     #[b(1)] attributes: HashMap<CowStr<'a>, CowStr<'a>>,
+    #[n(2)] ttl_secs: Option<u64>,
+    #[n(3)] ttl_count: Option<u64>,
+    #[n(4)] required_identifier: Option<IdentityIdentifier>,
+    #[n(5)] member_ttl_secs: Option<u64>,
+    #[n(6)] attribute_values: Option<BTreeMap<String, AttributeValue>>,
 }
 
 impl<'a> CreateToken<'a> {
@@ -59,6 +94,11 @@ impl<'a> CreateToken<'a> {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             attributes: HashMap::new(),
+            ttl_secs: None,
+            ttl_count: None,
+            required_identifier: None,
+            member_ttl_secs: None,
+            attribute_values: None,
         }
     }
 
@@ -70,6 +110,60 @@ impl<'a> CreateToken<'a> {
         self
     }
 
+    /// Set typed attribute values, for example a list of groups
+    pub fn with_attribute_values(mut self, values: BTreeMap<String, AttributeValue>) -> Self {
+        self.attribute_values = if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        };
+        self
+    }
+
+    /// Set the duration after which the token can not be used anymore
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.ttl_secs = duration.map(|d| d.as_secs());
+        self
+    }
+
+    /// Set the number of times the token can be used
+    pub fn with_ttl_count(mut self, ttl_count: Option<u64>) -> Self {
+        self.ttl_count = ttl_count;
+        self
+    }
+
+    /// Only allow the given identity to use the token
+    pub fn with_required_identifier(mut self, identifier: Option<IdentityIdentifier>) -> Self {
+        self.required_identifier = identifier;
+        self
+    }
+
+    /// Set the duration after which the attributes of the members enrolled with the token expire
+    pub fn with_member_duration(mut self, duration: Option<Duration>) -> Self {
+        self.member_ttl_secs = duration.map(|d| d.as_secs());
+        self
+    }
+
+    pub fn ttl_secs(&self) -> Option<u64> {
+        self.ttl_secs
+    }
+
+    pub fn ttl_count(&self) -> Option<u64> {
+        self.ttl_count
+    }
+
+    pub fn required_identifier(&self) -> Option<&IdentityIdentifier> {
+        self.required_identifier.as_ref()
+    }
+
+    pub fn member_ttl_secs(&self) -> Option<u64> {
+        self.member_ttl_secs
+    }
+
+    pub fn attribute_values(&self) -> BTreeMap<String, AttributeValue> {
+        self.attribute_values.clone().unwrap_or_default()
+    }
+
     pub fn into_owned_attributes(self) -> HashMap<String, String> {
         self.attributes
             .into_iter()
@@ -77,3 +171,123 @@ impl<'a> CreateToken<'a> {
             .collect()
     }
 }
+
+/// An enrollment token, as persisted by the enrollment token issuer
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollmentToken {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4385621>,
+    #[n(1)] one_time_code: OneTimeCode,
+    #[n(2)] attrs: HashMap<String, String>,
+    #[n(3)] generated_by: IdentityIdentifier,
+    #[n(4)] created_at: Timestamp,
+    #[n(5)] expires_at: Timestamp,
+    #[n(6)] ttl_count: u64,
+    #[n(7)] usage_count: u64,
+    #[n(8)] required_identifier: Option<IdentityIdentifier>,
+    #[n(9)] member_ttl_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(10)] attribute_values: Option<BTreeMap<String, AttributeValue>>,
+}
+
+impl EnrollmentToken {
+    pub fn new(
+        one_time_code: OneTimeCode,
+        attrs: HashMap<String, String>,
+        generated_by: IdentityIdentifier,
+        created_at: Timestamp,
+        expires_at: Timestamp,
+        ttl_count: u64,
+        required_identifier: Option<IdentityIdentifier>,
+    ) -> Self {
+        EnrollmentToken {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            one_time_code,
+            attrs,
+            generated_by,
+            created_at,
+            expires_at,
+            ttl_count,
+            usage_count: 0,
+            required_identifier,
+            member_ttl_secs: None,
+            attribute_values: None,
+        }
+    }
+
+    /// Set the number of seconds after which the attributes of the members
+    /// enrolled with this token expire
+    pub fn with_member_ttl_secs(mut self, member_ttl_secs: Option<u64>) -> Self {
+        self.member_ttl_secs = member_ttl_secs;
+        self
+    }
+
+    /// Set the typed attribute values given to the members enrolled with this token
+    pub fn with_attribute_values(mut self, values: BTreeMap<String, AttributeValue>) -> Self {
+        self.attribute_values = if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        };
+        self
+    }
+
+    pub fn one_time_code(&self) -> &OneTimeCode {
+        &self.one_time_code
+    }
+
+    pub fn attrs(&self) -> &HashMap<String, String> {
+        &self.attrs
+    }
+
+    /// Typed attribute values given to the enrolled members
+    pub fn attribute_values(&self) -> BTreeMap<String, AttributeValue> {
+        self.attribute_values.clone().unwrap_or_default()
+    }
+
+    pub fn generated_by(&self) -> &IdentityIdentifier {
+        &self.generated_by
+    }
+
+    pub fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    pub fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+
+    /// Maximum number of times the token can be used
+    pub fn ttl_count(&self) -> u64 {
+        self.ttl_count
+    }
+
+    /// Number of times the token has been used so far
+    pub fn usage_count(&self) -> u64 {
+        self.usage_count
+    }
+
+    pub fn required_identifier(&self) -> Option<&IdentityIdentifier> {
+        self.required_identifier.as_ref()
+    }
+
+    /// Number of seconds after which the attributes of the enrolled members expire
+    pub fn member_ttl_secs(&self) -> Option<u64> {
+        self.member_ttl_secs
+    }
+
+    /// Return true if the token can not be used anymore at the given time
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Record one more use of the token and return true if it can still be used afterwards
+    pub fn use_once(&mut self) -> bool {
+        self.usage_count = self.usage_count.saturating_add(1);
+        self.usage_count < self.ttl_count
+    }
+}
@@ -0,0 +1,121 @@
+use ockam::identity::{IdentityAttributesWriter, IdentityIdentifier, Timestamp};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{
+    Address, AllowOnwardAddresses, AllowSourceAddress, Error, Message, Result, Routed, Worker,
+};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Default interval between two purges of the expired member attributes
+pub const DEFAULT_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// This worker periodically removes the attributes of members which have expired.
+///
+/// Expired attributes are never returned when read, but they stay on disk until the
+/// member is read again. The sweeper makes sure that they are deleted even if the member
+/// never comes back, and sends an [`AttributesExpired`] event to its listeners for each
+/// purged member.
+pub struct AttributesSweeper {
+    attributes_writer: Arc<dyn IdentityAttributesWriter>,
+    interval: Duration,
+    heartbeat: DelayedEvent<Vec<u8>>,
+    listeners: Vec<Address>,
+}
+
+/// Event sent by an [`AttributesSweeper`] when the attributes of a member are purged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Message)]
+pub struct AttributesExpired {
+    /// Identifier of the member
+    pub member: IdentityIdentifier,
+    /// Identity which attested the purged attributes
+    pub attested_by: Option<IdentityIdentifier>,
+    /// Expiration date of the purged attributes
+    pub expired_at: Option<Timestamp>,
+}
+
+impl AttributesSweeper {
+    /// Start a sweeper purging expired attributes every `interval`
+    pub async fn start(
+        ctx: &Context,
+        attributes_writer: Arc<dyn IdentityAttributesWriter>,
+        interval: Duration,
+    ) -> Result<Address> {
+        Self::start_with_listeners(ctx, attributes_writer, interval, vec![]).await
+    }
+
+    /// Start a sweeper purging expired attributes every `interval` and sending an
+    /// [`AttributesExpired`] event to each of the `listeners` for every purged member
+    pub async fn start_with_listeners(
+        ctx: &Context,
+        attributes_writer: Arc<dyn IdentityAttributesWriter>,
+        interval: Duration,
+        listeners: Vec<Address>,
+    ) -> Result<Address> {
+        let address = Address::random_tagged("AttributesSweeper");
+        let mut heartbeat = DelayedEvent::create(ctx, address.clone(), vec![]).await?;
+        heartbeat.schedule(interval).await?;
+        let heartbeat_address = heartbeat.address();
+
+        let sweeper = Self {
+            attributes_writer,
+            interval,
+            heartbeat,
+            listeners: listeners.clone(),
+        };
+        WorkerBuilder::new(sweeper)
+            .with_address(address.clone())
+            .with_incoming_access_control(AllowSourceAddress(heartbeat_address))
+            .with_outgoing_access_control(AllowOnwardAddresses(listeners))
+            .start(ctx)
+            .await?;
+        Ok(address)
+    }
+
+    async fn sweep(&self, ctx: &Context) -> Result<()> {
+        let now = Timestamp::now()
+            .ok_or_else(|| Error::new(Origin::Identity, Kind::Internal, "invalid system time"))?;
+        let purged = self.attributes_writer.purge_expired(now).await?;
+        debug!(
+            "purged the attributes of {} expired member(s)",
+            purged.len()
+        );
+        for (identifier, entry) in purged {
+            info! {
+                target: "ockam_api::authenticator::expiry",
+                member      = %identifier,
+                attested_by = ?entry.attested_by(),
+                expired_at  = ?entry.expires(),
+                "attributes expired"
+            }
+            let event = AttributesExpired {
+                member: identifier,
+                attested_by: entry.attested_by(),
+                expired_at: entry.expires(),
+            };
+            for listener in &self.listeners {
+                // a listener which is gone must not prevent the others from being notified
+                if let Err(e) = ctx.send(listener.clone(), event.clone()).await {
+                    warn!("failed to notify '{listener}' of expired attributes: {e}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for AttributesSweeper {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, ctx: &mut Context, _m: Routed<Self::Message>) -> Result<()> {
+        // a failed purge is retried at the next tick
+        if let Err(e) = self.sweep(ctx).await {
+            warn!("failed to purge the expired member attributes: {e}");
+        }
+        self.heartbeat.schedule(self.interval).await
+    }
+}
@@ -1,10 +1,14 @@
 use ockam::identity::{
-    AttributesEntry, IdentitiesReader, IdentitiesRepository, IdentitiesWriter, Identity,
-    IdentityAttributesReader, IdentityAttributesWriter, IdentityIdentifier, Timestamp,
+    AttributeValue, AttributesEntry, IdentitiesReader, IdentitiesRepository, IdentitiesWriter,
+    Identity, IdentityAttributesReader, IdentityAttributesWriter, IdentityIdentifier, Timestamp,
 };
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
-use ockam_core::compat::{collections::HashMap, string::String, vec::Vec};
+use ockam_core::compat::{
+    collections::{BTreeMap, HashMap},
+    string::String,
+    vec::Vec,
+};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Result;
 use serde::{Deserialize, Serialize};
@@ -100,6 +104,14 @@ impl IdentityAttributesWriter for BootstrapedIdentityStore {
     async fn delete(&self, identity: &IdentityIdentifier) -> Result<()> {
         self.repository.delete(identity).await
     }
+
+    async fn purge_expired(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(IdentityIdentifier, AttributesEntry)>> {
+        // bootstrapped identities never expire
+        self.repository.purge_expired(now).await
+    }
 }
 
 #[async_trait]
@@ -173,23 +185,35 @@ impl PreTrustedIdentities {
         Self::parse(&contents)
     }
 
+    /// Parse a JSON map of identifiers to attributes.
+    /// String values are plain attributes, lists, integers and booleans are typed values
     fn parse(entries: &str) -> Result<HashMap<IdentityIdentifier, AttributesEntry>> {
         let raw_map =
-            json::from_str::<HashMap<IdentityIdentifier, HashMap<String, String>>>(entries)
+            json::from_str::<HashMap<IdentityIdentifier, HashMap<String, json::Value>>>(entries)
                 .map_err(|e| ockam_core::Error::new(Origin::Other, Kind::Invalid, e))?;
-        Ok(raw_map
+        raw_map
             .into_iter()
             .map(|(identity_id, raw_attrs)| {
-                let attrs = raw_attrs
-                    .into_iter()
-                    .map(|(k, v)| (k, v.as_bytes().to_vec()))
-                    .collect();
-                (
-                    identity_id,
-                    AttributesEntry::new(attrs, Timestamp::now().unwrap(), None, None),
-                )
+                let mut attrs = BTreeMap::new();
+                let mut values = BTreeMap::new();
+                for (k, v) in raw_attrs {
+                    match v {
+                        json::Value::String(s) => {
+                            attrs.insert(k, s.into_bytes());
+                        }
+                        v => {
+                            let v = json::from_value::<AttributeValue>(v).map_err(|e| {
+                                ockam_core::Error::new(Origin::Other, Kind::Invalid, e)
+                            })?;
+                            values.insert(k, v);
+                        }
+                    }
+                }
+                let entry = AttributesEntry::new(attrs, Timestamp::now().unwrap(), None, None)
+                    .with_values(values);
+                Ok((identity_id, entry))
             })
-            .collect())
+            .collect()
     }
 }
 
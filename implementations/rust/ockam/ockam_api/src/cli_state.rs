@@ -16,7 +16,7 @@ pub use crate::cli_state::traits::*;
 pub use crate::cli_state::trust_contexts::*;
 pub use crate::cli_state::vaults::*;
 use crate::config::cli::LegacyCliConfig;
-use ockam::identity::Identities;
+use ockam::identity::{Identities, SecureChannels};
 use ockam_core::compat::sync::Arc;
 use ockam_core::env::get_env_with_default;
 use ockam_identity::IdentityIdentifier;
@@ -244,6 +244,13 @@ impl CliState {
             .with_identities_repository(self.identities.identities_repository().await?)
             .build())
     }
+
+    pub async fn default_secure_channels(&self) -> Result<Arc<SecureChannels>> {
+        Ok(SecureChannels::builder()
+            .with_identities_vault(self.vaults.default()?.identities_vault().await?)
+            .with_identities_repository(self.identities.identities_repository().await?)
+            .build())
+    }
 }
 
 /// Test support
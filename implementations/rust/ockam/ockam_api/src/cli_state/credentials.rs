@@ -1,14 +1,41 @@
 use super::Result;
-use crate::cli_state::CliStateError;
-use ockam_identity::{Credential, Identity, IdentityHistoryComparison};
+use crate::cli_state::{CliStateError, StateDirTrait, StateItemTrait};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error};
+use ockam_identity::{
+    Credential, Credentials, CredentialsRetriever, Identity, IdentityHistoryComparison,
+    IdentityIdentifier, Timestamp,
+};
+use ockam_node::Context;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
+use tracing::debug;
+
+/// A stored credential is renewed when it expires in less than this duration
+pub const DEFAULT_CREDENTIAL_RENEWAL_MARGIN: Duration = Duration::from_secs(10 * 60);
+
+/// Name of the directory storing the credentials retrieved by the nodes from their authority
+const RETRIEVED_CREDENTIALS_DIR_NAME: &str = "retrieved";
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CredentialsState {
     dir: PathBuf,
 }
 
+impl CredentialsState {
+    /// Return the state of the credentials retrieved by the nodes from their authority.
+    ///
+    /// They are kept in their own directory so that they are not listed, nor used as the
+    /// default credential, like the credentials stored with `ockam credential store`
+    pub fn retrieved(&self) -> Result<CredentialsState> {
+        let dir = self.dir.join(RETRIEVED_CREDENTIALS_DIR_NAME);
+        std::fs::create_dir_all(&dir)?;
+        Ok(CredentialsState { dir })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct CredentialState {
     name: String,
@@ -59,6 +86,105 @@ impl CredentialConfig {
     }
 }
 
+/// Credentials retriever storing the credentials obtained from another retriever
+/// in the `credentials/retrieved` directory.
+///
+/// A stored credential is returned as long as it is valid, so that a node doesn't need to
+/// contact its authority every time it starts. A new credential is retrieved when the stored
+/// one is about to expire.
+pub struct StoredCredentialsRetriever {
+    credentials_state: CredentialsState,
+    credentials: Arc<dyn Credentials>,
+    issuer: Identity,
+    retriever: Arc<dyn CredentialsRetriever>,
+    renewal_margin: Duration,
+}
+
+impl StoredCredentialsRetriever {
+    /// Create a new retriever storing the credentials issued by `issuer`
+    pub fn new(
+        credentials_state: CredentialsState,
+        credentials: Arc<dyn Credentials>,
+        issuer: Identity,
+        retriever: Arc<dyn CredentialsRetriever>,
+    ) -> Self {
+        Self {
+            credentials_state,
+            credentials,
+            issuer,
+            retriever,
+            renewal_margin: DEFAULT_CREDENTIAL_RENEWAL_MARGIN,
+        }
+    }
+
+    /// Renew a stored credential when it expires in less than `margin`
+    pub fn with_renewal_margin(mut self, margin: Duration) -> Self {
+        self.renewal_margin = margin;
+        self
+    }
+
+    /// Return the name of the stored credential for a given identity
+    pub fn credential_name(&self, for_identity: &IdentityIdentifier) -> String {
+        format!("{}-{}", self.issuer.identifier(), for_identity)
+    }
+
+    /// Return the stored credential if it is still valid for more than the renewal margin
+    async fn stored(&self, for_identity: &IdentityIdentifier) -> Option<Credential> {
+        let state = self
+            .credentials_state
+            .get(self.credential_name(for_identity))
+            .ok()?;
+        let credential = state.config().credential().ok()?;
+        let data = self
+            .credentials
+            .verify_credential(
+                for_identity,
+                std::slice::from_ref(&self.issuer),
+                credential.clone(),
+            )
+            .await
+            .ok()?;
+        let renew_after = Timestamp::now()?.add_seconds(self.renewal_margin.as_secs());
+        (data.expires_at() > renew_after).then_some(credential)
+    }
+}
+
+#[async_trait]
+impl CredentialsRetriever for StoredCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &IdentityIdentifier,
+    ) -> ockam_core::Result<Credential> {
+        if let Some(credential) = self.stored(for_identity).await {
+            debug!(%for_identity, "using the stored credential");
+            return Ok(credential);
+        }
+
+        debug!(%for_identity, "retrieving a new credential");
+        let credential = self.retriever.retrieve(ctx, for_identity).await?;
+        self.credentials
+            .verify_credential(
+                for_identity,
+                std::slice::from_ref(&self.issuer),
+                credential.clone(),
+            )
+            .await?;
+
+        let encoded = minicbor::to_vec(&credential)
+            .map_err(|e| Error::new(Origin::Application, Kind::Serialization, e))?;
+        let config = CredentialConfig::new(self.issuer.clone(), hex::encode(encoded))?;
+        // the file is written directly, since a retrieved credential must never become
+        // the default credential
+        CredentialState::new(
+            self.credentials_state
+                .path(self.credential_name(for_identity)),
+            config,
+        )?;
+        Ok(credential)
+    }
+}
+
 mod traits {
     use super::*;
     use crate::cli_state::file_stem;
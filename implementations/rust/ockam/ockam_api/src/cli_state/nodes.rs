@@ -153,6 +153,21 @@ impl NodeState {
         Ok(LmdbStorage::new(self.paths.policies_storage()).await?)
     }
 
+    /// Directory where the streams of the node stream service are stored
+    pub fn streams_dir(&self) -> PathBuf {
+        self.paths.streams()
+    }
+
+    /// Directory where the messages buffered by the relays hosted by the node are stored
+    pub fn relay_buffers_dir(&self) -> PathBuf {
+        self.paths.relay_buffers()
+    }
+
+    /// Unix domain socket on which the node manager of the node receives the requests of the CLI
+    pub fn api_socket(&self) -> PathBuf {
+        self.paths.api_socket()
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -303,6 +318,14 @@ pub struct NodeSetupConfig {
     pub authority_node: Option<bool>,
     pub project: Option<ProjectLookup>,
     transports: Vec<CreateTransportJson>,
+    /// Identities, besides the identity of the node, allowed to use its node manager.
+    /// The field might be missing in previous configuration files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    admins: Vec<IdentityIdentifier>,
+    /// Options used to buffer the messages sent to the relays hosted by the node.
+    /// The field might be missing in previous configuration files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relay_buffer: Option<RelayBufferConfig>,
     // TODO
     // secure_channels: ?,
     // inlets: ?,
@@ -337,6 +360,39 @@ impl NodeSetupConfig {
         self.transports.push(transport);
         self
     }
+
+    pub fn admins(&self) -> &[IdentityIdentifier] {
+        &self.admins
+    }
+
+    pub fn add_admins(mut self, admins: impl IntoIterator<Item = IdentityIdentifier>) -> Self {
+        for admin in admins {
+            if !self.admins.contains(&admin) {
+                self.admins.push(admin);
+            }
+        }
+        self
+    }
+
+    pub fn relay_buffer(&self) -> Option<&RelayBufferConfig> {
+        self.relay_buffer.as_ref()
+    }
+
+    pub fn set_relay_buffer(mut self, relay_buffer: Option<RelayBufferConfig>) -> Self {
+        self.relay_buffer = relay_buffer;
+        self
+    }
+}
+
+/// Buffering of the messages sent to the relays hosted by a node,
+/// kept so that the node is restarted with the same options
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct RelayBufferConfig {
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+    #[serde(default)]
+    pub on_disk: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -382,6 +438,18 @@ impl NodePaths {
     fn policies_storage(&self) -> PathBuf {
         self.path.join("policies_storage.lmdb")
     }
+
+    fn streams(&self) -> PathBuf {
+        self.path.join("streams")
+    }
+
+    fn relay_buffers(&self) -> PathBuf {
+        self.path.join("relay_buffers")
+    }
+
+    fn api_socket(&self) -> PathBuf {
+        self.path.join("api.sock")
+    }
 }
 
 mod traits {
@@ -1,9 +1,11 @@
 use super::Result;
 use crate::cli_state::traits::StateItemTrait;
 use crate::cli_state::{CliStateError, StateDirTrait, DATA_DIR_NAME};
+use ockam_core::env::get_env;
 use ockam_identity::IdentitiesVault;
 use ockam_vault::Vault;
 use ockam_vault_aws::{AwsKmsConfig, AwsSecurityModule};
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SecurityModule};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
@@ -20,7 +22,11 @@ impl VaultsState {
             return Err(CliStateError::AlreadyExists);
         }
         let state = VaultState::new(self.path(name), config)?;
-        state.get().await?;
+        // don't keep a vault which can't be used, for example if its security module is unreachable
+        if let Err(e) = state.get().await {
+            let _ = state.delete();
+            return Err(e);
+        }
         if !self.default_path()?.exists() {
             self.set_default(name)?;
         }
@@ -48,6 +54,16 @@ impl VaultState {
                 )
                 .await?,
             ))
+        } else if let Some(pkcs11) = &self.config.pkcs11 {
+            Ok(Vault::builder()
+                .with_security_module(
+                    Pkcs11SecurityModule::create_with_storage_path(
+                        pkcs11.to_pkcs11_config()?,
+                        self.vault_file_path().as_path(),
+                    )
+                    .await?,
+                )
+                .build())
         } else {
             let vault =
                 Vault::create_with_persistent_storage_path(self.vault_file_path().as_path())
@@ -68,9 +84,7 @@ impl VaultState {
     }
 
     pub async fn identities_vault(&self) -> Result<Arc<dyn IdentitiesVault>> {
-        let path = self.vault_file_path().clone();
-        let vault = Vault::create_with_persistent_storage_path(path.as_path()).await?;
-        Ok(vault)
+        Ok(self.get().await?)
     }
 
     pub fn name(&self) -> &str {
@@ -81,14 +95,7 @@ impl VaultState {
 impl Display for VaultState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
-        writeln!(
-            f,
-            "Type: {}",
-            match self.config.is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(f, "Type: {}", self.config.kind())?;
         Ok(())
     }
 }
@@ -97,16 +104,73 @@ impl Display for VaultState {
 pub struct VaultConfig {
     #[serde(default)]
     aws_kms: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pkcs11: Option<Pkcs11VaultConfig>,
 }
 
 impl VaultConfig {
     pub fn new(aws_kms: bool) -> Result<Self> {
-        Ok(Self { aws_kms })
+        Ok(Self {
+            aws_kms,
+            pkcs11: None,
+        })
+    }
+
+    pub fn new_pkcs11(pkcs11: Pkcs11VaultConfig) -> Result<Self> {
+        Ok(Self {
+            aws_kms: false,
+            pkcs11: Some(pkcs11),
+        })
     }
 
     pub fn is_aws(&self) -> bool {
         self.aws_kms
     }
+
+    pub fn is_pkcs11(&self) -> bool {
+        self.pkcs11.is_some()
+    }
+
+    /// Return a description of the type of the vault
+    pub fn kind(&self) -> &'static str {
+        if self.is_aws() {
+            "AWS KMS"
+        } else if self.is_pkcs11() {
+            "PKCS#11"
+        } else {
+            "OCKAM"
+        }
+    }
+}
+
+/// Token used by a PKCS#11 vault.
+///
+/// The PIN of the token is not stored, it is read from the
+/// `OCKAM_PKCS11_PIN` environment variable when the vault is used.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Pkcs11VaultConfig {
+    /// Path of the PKCS#11 library
+    pub module: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_label: Option<String>,
+}
+
+impl Pkcs11VaultConfig {
+    fn to_pkcs11_config(&self) -> Result<Pkcs11Config> {
+        let mut config = Pkcs11Config::new(&self.module);
+        if let Some(slot) = self.slot {
+            config = config.slot(slot);
+        }
+        if let Some(label) = &self.token_label {
+            config = config.token_label(label);
+        }
+        if let Some(pin) = get_env::<String>("OCKAM_PKCS11_PIN")? {
+            config = config.pin(pin);
+        }
+        Ok(config)
+    }
 }
 
 mod traits {
@@ -1,6 +1,9 @@
 //! Configuration files used by the ockam CLI
 
-use crate::cli_state::{CliStateError, CredentialState, StateItemTrait};
+use crate::cli_state::{
+    CliStateError, CredentialState, CredentialsState, StateItemTrait, StoredCredentialsRetriever,
+    DEFAULT_CREDENTIAL_RENEWAL_MARGIN,
+};
 use crate::cloud::project::Project;
 use crate::config::{lookup::ConfigLookup, ConfigValues};
 use crate::error::ApiError;
@@ -110,30 +113,52 @@ impl TrustContextConfig {
             .ok_or_else(|| ApiError::generic("Missing authority on trust context config"))
     }
 
+    /// Create a trust context from this configuration.
+    ///
+    /// When a credentials state is given, the credentials retrieved from a credential issuer
+    /// are stored there and only retrieved again when they are about to expire
     pub async fn to_trust_context(
         &self,
         secure_channels: Arc<SecureChannels>,
         tcp_transport: Option<TcpTransport>,
+        credentials_state: Option<CredentialsState>,
     ) -> Result<TrustContext> {
         let authority = if let Some(authority_config) = self.authority.as_ref() {
             let identity = authority_config.identity().await?;
             let credential_retriever =
                 if let Some(retriever_type) = &authority_config.own_credential {
-                    Some(
-                        retriever_type
-                            .to_credential_retriever(secure_channels.clone(), tcp_transport)
-                            .await?,
-                    )
+                    let retriever = retriever_type
+                        .to_credential_retriever(secure_channels.clone(), tcp_transport)
+                        .await?;
+                    match (retriever_type, credentials_state) {
+                        (
+                            CredentialRetrieverConfig::FromCredentialIssuer(_),
+                            Some(credentials_state),
+                        ) => Some(Arc::new(StoredCredentialsRetriever::new(
+                            credentials_state,
+                            secure_channels.identities().credentials(),
+                            identity.clone(),
+                            retriever,
+                        )) as Arc<dyn CredentialsRetriever>),
+                        _ => Some(retriever),
+                    }
                 } else {
                     None
                 };
 
-            Some(AuthorityService::new(
+            let authority = AuthorityService::new(
                 secure_channels.identities().identities_reader(),
                 secure_channels.identities().credentials(),
                 identity.identifier(),
                 credential_retriever,
-            ))
+            );
+            // a credential coming from an issuer is renewed at the same time as a stored one
+            Some(match &authority_config.own_credential {
+                Some(CredentialRetrieverConfig::FromCredentialIssuer(_)) => {
+                    authority.with_renewal_margin_secs(DEFAULT_CREDENTIAL_RENEWAL_MARGIN.as_secs())
+                }
+                _ => authority,
+            })
         } else {
             None
         };
@@ -15,7 +15,7 @@ use ockam_node::Context;
 
 use crate::kafka::kafka_outlet_address;
 use crate::nodes::models::portal::{CreateInlet, InletStatus};
-use crate::nodes::NODEMANAGER_ADDR;
+use crate::nodes::send_to_node_manager;
 use crate::port_range::PortRange;
 
 type BrokerId = i32;
@@ -114,20 +114,19 @@ impl KafkaInletController {
         prefix: Route,
         suffix: Route,
     ) -> Result<SocketAddr> {
-        let buffer: Vec<u8> = context
-            .send_and_receive(
-                route![NODEMANAGER_ADDR],
-                Request::post("/node/inlet")
-                    .body(CreateInlet::to_node(
-                        socket_address,
-                        to,
-                        prefix,
-                        suffix,
-                        None,
-                    ))
-                    .to_vec()?,
-            )
-            .await?;
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::post("/node/inlet")
+                .body(CreateInlet::to_node(
+                    socket_address,
+                    to,
+                    prefix,
+                    suffix,
+                    None,
+                ))
+                .to_vec()?,
+        )
+        .await?;
 
         let mut decoder = Decoder::new(&buffer);
         let response: Response = decoder.decode()?;
@@ -40,6 +40,7 @@ mod test {
     use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions};
 
     use crate::hop::Hop;
+    use crate::kafka::outlet_service::consumer_discovery::is_consumer_forwarder;
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
     use crate::kafka::secure_channel_map::ForwarderCreator;
     use crate::kafka::{
@@ -50,31 +51,58 @@ mod test {
     //TODO: upgrade to 13 by adding a metadata request to map uuid<=>topic_name
     const TEST_KAFKA_API_VERSION: i16 = 12;
 
-    struct HopForwarderCreator {}
+    /// Forwarders created by every kafka service of a test
+    type MockForwarders = Arc<std::sync::Mutex<Vec<String>>>;
+
+    struct HopForwarderCreator {
+        forwarders: MockForwarders,
+    }
 
     #[async_trait]
     impl ForwarderCreator for HopForwarderCreator {
         async fn create_forwarder(&self, context: &Context, alias: String) -> ockam::Result<()> {
             trace!("creating mock forwarder for: {alias}");
             //replicating the same logic of the orchestrator by adding consumer__
+            let address = format!("consumer__{alias}");
             context
-                .start_worker(Address::from_string(format!("consumer__{alias}")), Hop)
+                .start_worker(Address::from_string(address.clone()), Hop)
                 .await?;
+            self.forwarders.lock().unwrap().push(address);
             Ok(())
         }
+
+        async fn list_forwarders(
+            &self,
+            _context: &Context,
+            topic_partition: &str,
+        ) -> ockam::Result<Vec<String>> {
+            Ok(self
+                .forwarders
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|address| is_consumer_forwarder(address, topic_partition))
+                .cloned()
+                .collect())
+        }
     }
 
     async fn create_kafka_service(
         context: &Context,
         handler: &NodeManagerHandle,
+        forwarders: &MockForwarders,
         listener_address: Address,
         outlet_address: Address,
     ) -> ockam::Result<u16> {
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new_extended(
             handler.secure_channels.clone(),
             MultiAddr::try_from("/service/api")?,
-            HopForwarderCreator {},
+            HopForwarderCreator {
+                forwarders: forwarders.clone(),
+            },
             "test_trust_context_id".to_string(),
+            // consumer identifiers cannot contain '_', see is_consumer_forwarder
+            listener_address.address().replace('_', "-"),
         );
 
         let mut interceptor_multiaddr = MultiAddr::default();
@@ -102,6 +130,7 @@ mod test {
             inlet_controller,
             secure_channel_controller.into_trait(),
             listener_address,
+            Default::default(),
         )
         .await?;
 
@@ -114,10 +143,12 @@ mod test {
         context: &mut Context,
     ) -> ockam::Result<()> {
         let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let forwarders = MockForwarders::default();
 
         let consumer_bootstrap_port = create_kafka_service(
             context,
             &handler,
+            &forwarders,
             "kafka_consumer_listener".into(),
             "kafka_consumer_outlet".into(),
         )
@@ -126,6 +157,7 @@ mod test {
         let producer_bootstrap_port = create_kafka_service(
             context,
             &handler,
+            &forwarders,
             "kafka_producer_listener".into(),
             "kafka_producer_outlet".into(),
         )
@@ -133,26 +165,13 @@ mod test {
 
         //before produce a new key, the consumer has to issue a Fetch request
         // so the sidecar can react by creating the forwarder for the partition 1 of 'my-topic'
-        {
-            let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
-            handler
-                .tcp
-                .create_outlet(
-                    "kafka_consumer_outlet",
-                    format!("127.0.0.1:{}", consumer_mock_kafka.port),
-                    TcpOutletOptions::new(),
-                )
-                .await?;
-
-            simulate_first_kafka_consumer_empty_reply_and_ignore_result(
-                consumer_bootstrap_port,
-                &mut consumer_mock_kafka,
-            )
-            .await;
-            drop(consumer_mock_kafka);
-            //drop the outlet and re-create it when we need it later
-            context.stop_worker("kafka_consumer_outlet").await?;
-        }
+        start_consumer_forwarders(
+            context,
+            &handler,
+            consumer_bootstrap_port,
+            "kafka_consumer_outlet",
+        )
+        .await?;
 
         let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
         handler
@@ -191,23 +210,250 @@ mod test {
             "hello world!".as_bytes()
         );
 
+        // give the secure channel between producer and consumer to finish initialization
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let (plain_value, consumer_mock_kafka) = consume_first_value(
+            &handler,
+            consumer_bootstrap_port,
+            "kafka_consumer_outlet",
+            &request,
+        )
+        .await?;
+        assert_eq!(plain_value, "hello world!".as_bytes());
+
+        context.stop().await?;
+        consumer_mock_kafka.destroy_and_wait().await;
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn producer__flow_with_two_consumers__each_consumer_decrypts(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let forwarders = MockForwarders::default();
+
+        let mut consumer_bootstrap_ports = vec![];
+        for consumer in ["first", "second"] {
+            let outlet = format!("kafka_{consumer}_consumer_outlet");
+            let port = create_kafka_service(
+                context,
+                &handler,
+                &forwarders,
+                format!("kafka_{consumer}_consumer_listener").into(),
+                outlet.clone().into(),
+            )
+            .await?;
+            start_consumer_forwarders(context, &handler, port, &outlet).await?;
+            consumer_bootstrap_ports.push((port, outlet));
+        }
+        assert_eq!(
+            forwarders
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|address| is_consumer_forwarder(address, "my-topic-name_1"))
+                .count(),
+            2
+        );
+
+        let producer_bootstrap_port = create_kafka_service(
+            context,
+            &handler,
+            &forwarders,
+            "kafka_producer_listener".into(),
+            "kafka_producer_outlet".into(),
+        )
+        .await?;
+
+        let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        let request = simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut producer_mock_kafka,
+        )
+        .await;
+
+        // give the secure channels between producer and consumers to finish initialization
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut consumer_mock_kafkas = vec![];
+        for (port, outlet) in consumer_bootstrap_ports {
+            let (plain_value, consumer_mock_kafka) =
+                consume_first_value(&handler, port, &outlet, &request).await?;
+            assert_eq!(plain_value, "hello world!".as_bytes());
+            consumer_mock_kafkas.push(consumer_mock_kafka);
+        }
+
+        context.stop().await?;
+        for consumer_mock_kafka in consumer_mock_kafkas {
+            consumer_mock_kafka.destroy_and_wait().await;
+        }
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn producer__consumer_joining_after_a_batch__decrypts_the_next_batches(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let forwarders = MockForwarders::default();
+
+        let first_consumer_port = create_kafka_service(
+            context,
+            &handler,
+            &forwarders,
+            "kafka_first_consumer_listener".into(),
+            "kafka_first_consumer_outlet".into(),
+        )
+        .await?;
+        start_consumer_forwarders(
+            context,
+            &handler,
+            first_consumer_port,
+            "kafka_first_consumer_outlet",
+        )
+        .await?;
+
+        let producer_bootstrap_port = create_kafka_service(
+            context,
+            &handler,
+            &forwarders,
+            "kafka_producer_listener".into(),
+            "kafka_producer_outlet".into(),
+        )
+        .await?;
+
+        let mut first_producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", first_producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut first_producer_mock_kafka,
+        )
+        .await;
+        // the connection to the first mock kafka is only closed when the node stops
+        context.stop_worker("kafka_producer_outlet").await?;
+
+        // the second consumer joins once the first batch was produced
+        let second_consumer_port = create_kafka_service(
+            context,
+            &handler,
+            &forwarders,
+            "kafka_second_consumer_listener".into(),
+            "kafka_second_consumer_outlet".into(),
+        )
+        .await?;
+        start_consumer_forwarders(
+            context,
+            &handler,
+            second_consumer_port,
+            "kafka_second_consumer_outlet",
+        )
+        .await?;
+
+        let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        let request = simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut producer_mock_kafka,
+        )
+        .await;
+
+        // give the secure channels between producer and consumers to finish initialization
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let (plain_value, consumer_mock_kafka) = consume_first_value(
+            &handler,
+            second_consumer_port,
+            "kafka_second_consumer_outlet",
+            &request,
+        )
+        .await?;
+        assert_eq!(plain_value, "hello world!".as_bytes());
+
+        context.stop().await?;
+        consumer_mock_kafka.destroy_and_wait().await;
+        first_producer_mock_kafka.destroy_and_wait().await;
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
+    /// Issue a first fetch request through a consumer kafka service, so that it creates
+    /// the forwarders of the fetched partitions
+    async fn start_consumer_forwarders(
+        context: &Context,
+        handler: &NodeManagerHandle,
+        consumer_bootstrap_port: u16,
+        outlet_address: &str,
+    ) -> ockam::Result<()> {
         let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
         handler
             .tcp
             .create_outlet(
-                "kafka_consumer_outlet",
+                outlet_address,
                 format!("127.0.0.1:{}", consumer_mock_kafka.port),
                 TcpOutletOptions::new(),
             )
             .await?;
 
-        // give the secure channel between producer and consumer to finish initialization
-        tokio::time::sleep(Duration::from_millis(100)).await;
+        simulate_first_kafka_consumer_empty_reply_and_ignore_result(
+            consumer_bootstrap_port,
+            &mut consumer_mock_kafka,
+        )
+        .await;
+        drop(consumer_mock_kafka);
+        //drop the outlet and re-create it when we need it later
+        context.stop_worker(outlet_address).await
+    }
+
+    /// Fetch the records of a produce request through a consumer kafka service, and
+    /// return the value of the first record
+    async fn consume_first_value(
+        handler: &NodeManagerHandle,
+        consumer_bootstrap_port: u16,
+        outlet_address: &str,
+        producer_request: &ProduceRequest,
+    ) -> ockam::Result<(Vec<u8>, TcpServerSimulator)> {
+        let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                outlet_address,
+                format!("127.0.0.1:{}", consumer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
 
         let plain_fetch_response = simulate_kafka_consumer_and_read_response(
             consumer_bootstrap_port,
             &mut consumer_mock_kafka,
-            &request,
+            producer_request,
         )
         .await;
 
@@ -226,16 +472,8 @@ mod test {
 
         let mut plain_content = BytesMut::from(plain_content.as_ref());
         let records = RecordBatchDecoder::decode(&mut plain_content).unwrap();
-
-        assert_eq!(
-            records.get(0).as_ref().unwrap().value.as_ref().unwrap(),
-            "hello world!".as_bytes()
-        );
-
-        context.stop().await?;
-        consumer_mock_kafka.destroy_and_wait().await;
-        producer_mock_kafka.destroy_and_wait().await;
-        Ok(())
+        let value = records.first().unwrap().value.as_ref().unwrap().to_vec();
+        Ok((value, consumer_mock_kafka))
     }
 
     async fn simulate_kafka_producer_and_read_request(
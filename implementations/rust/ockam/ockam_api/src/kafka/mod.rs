@@ -12,12 +12,14 @@ mod protocol_aware;
 mod secure_channel_map;
 
 pub(crate) use inlet_controller::KafkaInletController;
+pub(crate) use outlet_service::consumer_discovery::ConsumerDiscoveryService;
 pub(crate) use outlet_service::prefix_forwarder::PrefixForwarderService;
 pub(crate) use outlet_service::OutletManagerService;
 pub(crate) use portal_listener::KafkaPortalListener;
 pub(crate) use secure_channel_map::KafkaSecureChannelControllerImpl;
 
 pub const KAFKA_OUTLET_CONSUMERS: &str = "kafka_consumers";
+pub const KAFKA_OUTLET_CONSUMERS_DISCOVERY: &str = "kafka_consumers_discovery";
 pub const KAFKA_OUTLET_INTERCEPTOR_ADDRESS: &str = "kafka_interceptor";
 pub const KAFKA_OUTLET_BOOTSTRAP_ADDRESS: &str = "kafka_bootstrap";
 
@@ -1,13 +1,13 @@
 use crate::kafka::kafka_outlet_address;
 use crate::nodes::models::portal::{CreateOutlet, OutletStatus};
-use crate::nodes::NODEMANAGER_ADDR;
+use crate::nodes::send_to_node_manager;
 use minicbor::Decoder;
 use ockam::compat::tokio::sync::Mutex;
 use ockam_core::api::{Request, Response, Status};
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{route, Error};
+use ockam_core::Error;
 use ockam_core::{Address, Result};
 use ockam_node::Context;
 
@@ -63,14 +63,13 @@ impl KafkaOutletController {
         tcp_address: String,
         worker_address: String,
     ) -> Result<String> {
-        let buffer: Vec<u8> = context
-            .send_and_receive(
-                route![NODEMANAGER_ADDR],
-                Request::post("/node/outlet")
-                    .body(CreateOutlet::new(tcp_address, worker_address, None, false))
-                    .to_vec()?,
-            )
-            .await?;
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::post("/node/outlet")
+                .body(CreateOutlet::new(tcp_address, worker_address, None, false))
+                .to_vec()?,
+        )
+        .await?;
 
         let mut decoder = Decoder::new(&buffer);
         let response: Response = decoder.decode()?;
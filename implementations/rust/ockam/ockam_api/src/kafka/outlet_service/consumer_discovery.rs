@@ -0,0 +1,145 @@
+use crate::kafka::KAFKA_OUTLET_CONSUMERS_DISCOVERY;
+
+use minicbor::Decoder;
+use ockam::{Context, ForwardersRegistry, Result, Routed, Worker};
+use ockam_abac::AbacAccessControl;
+use ockam_core::api::{self, Error, Id, Method, Request, Response};
+use ockam_core::compat::sync::Arc;
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, AllowAll};
+use ockam_identity::{SecureChannels, TRUST_CONTEXT_ID};
+
+/// Prefix of the forwarders created for the consumers by the
+/// [`PrefixForwarderService`](super::prefix_forwarder::PrefixForwarderService)
+pub(crate) const CONSUMER_FORWARDER_PREFIX: &str = "consumer__";
+
+/// This service lists the forwarders of the consumers of a topic partition, so that the
+/// producers can encrypt their records for each of them.
+/// Only the members of the trust context, which the consumers are authorized against,
+/// can list them.
+pub struct ConsumerDiscoveryService {
+    forwarders: ForwardersRegistry,
+}
+
+impl ConsumerDiscoveryService {
+    pub async fn create(
+        context: &Context,
+        secure_channels: Arc<SecureChannels>,
+        trust_context_id: &str,
+        secure_channel_listener_flow_control_id: FlowControlId,
+        forwarders: ForwardersRegistry,
+    ) -> Result<()> {
+        let worker_address = Address::from_string(KAFKA_OUTLET_CONSUMERS_DISCOVERY);
+        context.flow_controls().add_consumer(
+            worker_address.clone(),
+            &secure_channel_listener_flow_control_id,
+        );
+
+        let incoming_access_control = AbacAccessControl::create(
+            secure_channels.identities().repository(),
+            TRUST_CONTEXT_ID,
+            trust_context_id,
+        );
+
+        context
+            .start_worker_with_access_control(
+                worker_address,
+                Self { forwarders },
+                incoming_access_control,
+                AllowAll,
+            )
+            .await
+    }
+
+    fn on_request(&self, msg: &Routed<Vec<u8>>) -> Result<Vec<u8>> {
+        let mut dec = Decoder::new(msg.as_body());
+
+        let req: Request = match dec.decode() {
+            Ok(rq) => rq,
+            Err(e) => {
+                let err = Error::default().with_message(e.to_string());
+                return Ok(Response::bad_request(Id::default()).body(err).to_vec()?);
+            }
+        };
+
+        let res = match req.method() {
+            Some(Method::Get) => match req.path_segments::<3>().as_slice() {
+                ["consumers", topic_partition] => {
+                    let forwarders: Vec<String> = self
+                        .forwarders
+                        .list()
+                        .into_iter()
+                        .map(|status| status.address.address().to_string())
+                        .filter(|address| is_consumer_forwarder(address, topic_partition))
+                        .collect();
+                    Response::ok(req.id()).body(forwarders).to_vec()?
+                }
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            _ => api::invalid_method(&req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+}
+
+#[ockam::worker]
+impl Worker for ConsumerDiscoveryService {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let res = self.on_request(&msg)?;
+        ctx.send(msg.return_route(), res).await
+    }
+}
+
+/// Whether a forwarder was created for a consumer of the topic partition, with the alias
+/// `{topic}_{partition}`, or `{topic}_{partition}.{consumer}` when each consumer registers
+/// its own forwarder
+pub(crate) fn is_consumer_forwarder(address: &str, topic_partition: &str) -> bool {
+    match address
+        .strip_prefix(CONSUMER_FORWARDER_PREFIX)
+        .and_then(|alias| alias.strip_prefix(topic_partition))
+    {
+        Some("") => true,
+        // the partitions of the other topics starting with the same name end with `_{partition}`
+        Some(suffix) => suffix
+            .strip_prefix('.')
+            .map(|consumer| !consumer.is_empty() && !consumer.contains('_'))
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_consumer_forwarder;
+
+    #[test]
+    fn consumer_forwarders_of_a_topic_partition() {
+        assert!(is_consumer_forwarder("consumer__my-topic_1", "my-topic_1"));
+        assert!(is_consumer_forwarder(
+            "consumer__my-topic_1.P6c20e8",
+            "my-topic_1"
+        ));
+
+        assert!(!is_consumer_forwarder(
+            "consumer__my-topic_10",
+            "my-topic_1"
+        ));
+        assert!(!is_consumer_forwarder(
+            "consumer__my-topic_1.b_2",
+            "my-topic_1"
+        ));
+        assert!(!is_consumer_forwarder(
+            "consumer__my-topic_1.",
+            "my-topic_1"
+        ));
+        assert!(!is_consumer_forwarder("my-topic_1", "my-topic_1"));
+    }
+}
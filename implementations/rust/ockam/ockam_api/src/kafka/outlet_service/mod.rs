@@ -1,3 +1,4 @@
+pub(crate) mod consumer_discovery;
 mod interceptor_listener;
 pub(crate) mod prefix_forwarder;
 
@@ -7,6 +7,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::KafkaPortalWorker;
 use crate::kafka::protocol_aware::TopicUuidMap;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::nodes::models::services::KafkaRecordEncryption;
 
 ///First point of ingress of kafka connections, at the first message it spawns new stateful workers
 /// to take care of the connection.
@@ -14,6 +15,7 @@ pub(crate) struct KafkaPortalListener {
     inlet_controller: KafkaInletController,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     uuid_to_name: TopicUuidMap,
+    record_encryption: KafkaRecordEncryption,
 }
 
 #[ockam::worker]
@@ -51,6 +53,7 @@ impl Worker for KafkaPortalListener {
             None,
             flow_control_id,
             route![inlet_responder_address],
+            self.record_encryption.clone(),
         )
         .await?;
 
@@ -79,6 +82,7 @@ impl KafkaPortalListener {
         inlet_controller: KafkaInletController,
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         listener_address: Address,
+        record_encryption: KafkaRecordEncryption,
     ) -> ockam_core::Result<()> {
         context
             .start_worker(
@@ -87,6 +91,7 @@ impl KafkaPortalListener {
                     inlet_controller,
                     secure_channel_controller,
                     uuid_to_name: Default::default(),
+                    record_encryption,
                 },
             )
             .await
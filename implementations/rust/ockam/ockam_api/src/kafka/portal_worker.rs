@@ -16,6 +16,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::length_delimited::{length_encode, KafkaMessageDecoder};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, KafkaMessageInterceptor, TopicUuidMap};
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::nodes::models::services::KafkaRecordEncryption;
 
 ///by default kafka supports up to 1MB messages, 16MB is the maximum suggested
 pub(crate) const MAX_KAFKA_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
@@ -126,7 +127,9 @@ impl Worker for KafkaPortalWorker {
                     context.stop_worker(context.address()).await?;
                 }
             }
-            PortalMessage::Ping => self.forward(context, routed_message).await?,
+            PortalMessage::Ping | PortalMessage::ConnectTo(_) => {
+                self.forward(context, routed_message).await?
+            }
 
             PortalMessage::Pong => {
                 match self.receiving {
@@ -393,11 +396,13 @@ impl KafkaPortalWorker {
         max_kafka_message_size: Option<u32>,
         flow_control_id: Option<FlowControlId>,
         inlet_responder_route: Route,
+        record_encryption: KafkaRecordEncryption,
     ) -> ockam_core::Result<Address> {
         let shared_protocol_state = Arc::new(InletInterceptorImpl::new(
             secure_channel_controller,
             uuid_to_name,
             inlet_map,
+            record_encryption,
         ));
 
         let requests_worker_address = Address::random_tagged("KafkaPortalWorker.requests");
@@ -744,6 +749,7 @@ mod test {
             secure_channels,
             MultiAddr::default(),
             "test_trust_context_id".to_string(),
+            "test_consumer_id".to_string(),
         )
         .into_trait();
 
@@ -755,6 +761,7 @@ mod test {
             Some(TEST_MAX_KAFKA_MESSAGE_SIZE),
             None,
             route![context.address()],
+            Default::default(),
         )
         .await
         .unwrap()
@@ -799,6 +806,7 @@ mod test {
             handler.secure_channels.clone(),
             MultiAddr::default(),
             "test_trust_context_id".to_string(),
+            "test_consumer_id".to_string(),
         )
         .into_trait();
 
@@ -817,6 +825,7 @@ mod test {
             None,
             None,
             route![context.address()],
+            Default::default(),
         )
         .await?;
 
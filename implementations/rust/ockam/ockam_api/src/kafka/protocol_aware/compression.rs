@@ -0,0 +1,271 @@
+use crate::kafka::portal_worker::{InterceptError, MAX_KAFKA_MESSAGE_SIZE};
+use bytes::{BufMut, Bytes, BytesMut};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use kafka_protocol::records::{
+    Compression, Record, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions, CASTAGNOLI,
+};
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+use std::io::{Error, ErrorKind, Read, Write};
+use tracing::warn;
+
+// Layout of a record batch, see https://kafka.apache.org/documentation/#recordbatch
+const BATCH_LENGTH_OFFSET: usize = 8;
+const PARTITION_LEADER_EPOCH_OFFSET: usize = 12;
+const MAGIC_OFFSET: usize = 16;
+const CRC_OFFSET: usize = 17;
+const ATTRIBUTES_OFFSET: usize = 21;
+const RECORDS_OFFSET: usize = 61;
+const COMPRESSION_MASK: i16 = 0x7;
+
+// Legacy message sets (magic 0 and 1) only have a one byte attribute
+const LEGACY_ATTRIBUTES_OFFSET: usize = 17;
+
+/// Records of a record batch, with the compression and the version of the batch
+pub(super) struct RecordBatch {
+    pub(super) records: Vec<Record>,
+    pub(super) compression: Compression,
+    /// Magic byte of the batch: 0 and 1 for legacy message sets, 2 for record batches
+    pub(super) version: i8,
+}
+
+/// Decode the record batches of a produce request or a fetch response, keeping the
+/// compression and the version of each batch so that they can be used when encoding
+/// them back.
+///
+/// Compressed batches are decompressed here rather than by `kafka_protocol`, which
+/// doesn't handle lz4 and zstd, so that the size of their records can be limited.
+/// Compressed legacy message sets, which `kafka_protocol` would decompress without
+/// any limit, are rejected
+pub(super) fn decode_record_batches(content: Bytes) -> Result<Vec<RecordBatch>, InterceptError> {
+    let mut batches = vec![];
+    let mut decompressed_size = 0;
+
+    let mut remaining = content.as_ref();
+    while !remaining.is_empty() {
+        if remaining.len() < PARTITION_LEADER_EPOCH_OFFSET {
+            return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+        }
+        let batch_end = PARTITION_LEADER_EPOCH_OFFSET + batch_length(remaining)?;
+        if batch_end > remaining.len() || batch_end <= LEGACY_ATTRIBUTES_OFFSET {
+            return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+        }
+        let (batch, rest) = remaining.split_at(batch_end);
+        remaining = rest;
+
+        let version = batch[MAGIC_OFFSET] as i8;
+        let (mut batch, compression) = if version < 2 {
+            let compression = codec((batch[LEGACY_ATTRIBUTES_OFFSET] as i16) & COMPRESSION_MASK)?;
+            if compression != Compression::None {
+                warn!("compressed legacy message sets are not supported");
+                return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+            }
+            (BytesMut::from(batch), compression)
+        } else {
+            if batch.len() < RECORDS_OFFSET {
+                return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+            }
+            let attributes = i16::from_be_bytes(read_array(batch, ATTRIBUTES_OFFSET));
+            let compression = codec(attributes & COMPRESSION_MASK)?;
+            if compression == Compression::None {
+                (BytesMut::from(batch), compression)
+            } else {
+                let crc = u32::from_be_bytes(read_array(batch, CRC_OFFSET));
+                if CASTAGNOLI.checksum(&batch[ATTRIBUTES_OFFSET..]) != crc {
+                    warn!("invalid kafka record batch checksum");
+                    return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+                }
+                let records = decompress(compression, &batch[RECORDS_OFFSET..])?;
+                let mut decompressed = BytesMut::with_capacity(RECORDS_OFFSET + records.len());
+                write_batch(
+                    &mut decompressed,
+                    batch,
+                    attributes & !COMPRESSION_MASK,
+                    &records,
+                )?;
+                (decompressed, compression)
+            }
+        };
+
+        decompressed_size += batch.len();
+        if decompressed_size > MAX_KAFKA_MESSAGE_SIZE as usize {
+            warn!("decompressed kafka records exceed {MAX_KAFKA_MESSAGE_SIZE} bytes");
+            return Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)));
+        }
+
+        let records = RecordBatchDecoder::decode(&mut batch)
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+        batches.push(RecordBatch {
+            records,
+            compression,
+            version,
+        });
+    }
+    Ok(batches)
+}
+
+/// Encode record batches, each one with its own version and compressed with its own
+/// compression
+pub(super) fn encode_record_batches(batches: &[RecordBatch]) -> Result<Bytes, InterceptError> {
+    let mut encoded = BytesMut::new();
+    for batch in batches {
+        let start = encoded.len();
+        RecordBatchEncoder::encode(
+            &mut encoded,
+            batch.records.iter(),
+            &RecordEncodeOptions {
+                version: batch.version,
+                compression: Compression::None,
+            },
+        )
+        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        if batch.compression != Compression::None {
+            let uncompressed = encoded.split_off(start);
+            let mut remaining = uncompressed.as_ref();
+            while !remaining.is_empty() {
+                let batch_end = PARTITION_LEADER_EPOCH_OFFSET + batch_length(remaining)?;
+                let (uncompressed_batch, rest) = remaining.split_at(batch_end);
+                remaining = rest;
+
+                let attributes =
+                    i16::from_be_bytes(read_array(uncompressed_batch, ATTRIBUTES_OFFSET));
+                let records = compress(batch.compression, &uncompressed_batch[RECORDS_OFFSET..])?;
+                write_batch(
+                    &mut encoded,
+                    uncompressed_batch,
+                    attributes | codec_id(batch.compression),
+                    &records,
+                )?;
+            }
+        }
+    }
+    Ok(encoded.freeze())
+}
+
+/// Compress a payload with the given compression
+pub(super) fn compress(compression: Compression, data: &[u8]) -> Result<Vec<u8>, InterceptError> {
+    let compressed = match compression {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+        Compression::Snappy => snap::raw::Encoder::new()
+            .compress_vec(data)
+            .map_err(Error::from),
+        Compression::Lz4 => {
+            let mut encoder = FrameEncoder::new(Vec::new());
+            encoder
+                .write_all(data)
+                .and_then(|_| encoder.finish().map_err(Error::from))
+        }
+        Compression::Zstd => zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+    };
+    compressed.map_err(|err| {
+        warn!("cannot compress kafka records: {err}");
+        InterceptError::Io(Error::from(ErrorKind::InvalidData))
+    })
+}
+
+/// Decompress a payload compressed with the given compression. Payloads decompressing
+/// to more than [`MAX_KAFKA_MESSAGE_SIZE`] bytes are rejected
+pub(super) fn decompress(compression: Compression, data: &[u8]) -> Result<Vec<u8>, InterceptError> {
+    let decompressed = match compression {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Gzip => read_to_limit(GzDecoder::new(data)),
+        Compression::Snappy => snap::raw::decompress_len(data)
+            .map_err(Error::from)
+            .and_then(|len| {
+                if len > MAX_KAFKA_MESSAGE_SIZE as usize {
+                    Err(Error::new(ErrorKind::InvalidData, "too large"))
+                } else {
+                    snap::raw::Decoder::new()
+                        .decompress_vec(data)
+                        .map_err(Error::from)
+                }
+            }),
+        Compression::Lz4 => read_to_limit(FrameDecoder::new(data)),
+        Compression::Zstd => zstd::stream::read::Decoder::new(data).and_then(read_to_limit),
+    };
+    decompressed.map_err(|err| {
+        warn!("cannot decompress kafka records: {err}");
+        InterceptError::Io(Error::from(ErrorKind::InvalidData))
+    })
+}
+
+/// Read a decompressed payload, failing when it is larger than [`MAX_KAFKA_MESSAGE_SIZE`]
+fn read_to_limit(reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    reader
+        .take(MAX_KAFKA_MESSAGE_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() > MAX_KAFKA_MESSAGE_SIZE as usize {
+        return Err(Error::new(ErrorKind::InvalidData, "too large"));
+    }
+    Ok(decompressed)
+}
+
+/// Identifier of a compression, as used in the attributes of a record batch
+pub(super) fn codec_id(compression: Compression) -> i16 {
+    match compression {
+        Compression::None => 0,
+        Compression::Gzip => 1,
+        Compression::Snappy => 2,
+        Compression::Lz4 => 3,
+        Compression::Zstd => 4,
+    }
+}
+
+/// Compression from its identifier in the attributes of a record batch
+pub(super) fn codec(id: i16) -> Result<Compression, InterceptError> {
+    match id {
+        0 => Ok(Compression::None),
+        1 => Ok(Compression::Gzip),
+        2 => Ok(Compression::Snappy),
+        3 => Ok(Compression::Lz4),
+        4 => Ok(Compression::Zstd),
+        _ => {
+            warn!("unknown kafka compression: {id}");
+            Err(InterceptError::Io(Error::from(ErrorKind::InvalidData)))
+        }
+    }
+}
+
+/// Write a copy of a record batch with new attributes and records, updating
+/// its length and its checksum
+fn write_batch(
+    buffer: &mut BytesMut,
+    batch: &[u8],
+    attributes: i16,
+    records: &[u8],
+) -> Result<(), InterceptError> {
+    let batch_length =
+        i32::try_from(RECORDS_OFFSET - PARTITION_LEADER_EPOCH_OFFSET + records.len())
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+    let start = buffer.len();
+    buffer.put_slice(&batch[..BATCH_LENGTH_OFFSET]);
+    buffer.put_i32(batch_length);
+    buffer.put_slice(&batch[PARTITION_LEADER_EPOCH_OFFSET..CRC_OFFSET]);
+    buffer.put_u32(0);
+    buffer.put_i16(attributes);
+    buffer.put_slice(&batch[ATTRIBUTES_OFFSET + 2..RECORDS_OFFSET]);
+    buffer.put_slice(records);
+
+    let crc = CASTAGNOLI.checksum(&buffer[start + ATTRIBUTES_OFFSET..]);
+    buffer[start + CRC_OFFSET..start + ATTRIBUTES_OFFSET].copy_from_slice(&crc.to_be_bytes());
+    Ok(())
+}
+
+/// Length of a record batch, not including its base offset and the length itself
+fn batch_length(batch: &[u8]) -> Result<usize, InterceptError> {
+    usize::try_from(i32::from_be_bytes(read_array(batch, BATCH_LENGTH_OFFSET)))
+        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))
+}
+
+fn read_array<const N: usize>(buffer: &[u8], offset: usize) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(&buffer[offset..offset + N]);
+    array
+}
@@ -1,8 +1,11 @@
 use crate::kafka::portal_worker::InterceptError;
-use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::secure_channel_map::{KafkaEncryptedKey, KafkaSecureChannelController};
 use crate::kafka::KafkaInletController;
+use crate::nodes::models::services::KafkaRecordEncryption;
 use bytes::BytesMut;
+use compression::{codec, codec_id};
 use kafka_protocol::messages::ApiKey;
+use kafka_protocol::records::Compression;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::{
     collections::HashMap,
@@ -14,7 +17,9 @@ use ockam_core::{async_trait, Address};
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 use ockam_node::Context;
+use std::io::{Error, ErrorKind};
 
+mod compression;
 mod metadata_interceptor;
 mod request;
 mod response;
@@ -56,6 +61,7 @@ pub(crate) struct InletInterceptorImpl {
     uuid_to_name: TopicUuidMap,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     inlet_map: KafkaInletController,
+    record_encryption: KafkaRecordEncryption,
 }
 
 #[async_trait]
@@ -82,10 +88,94 @@ impl KafkaMessageInterceptor for InletInterceptorImpl {
 #[cbor(map)]
 ///Wraps the content within every record batch
 struct MessageWrapper {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4710923>,
+    /// Content encrypted with the data key of the record batch, when it isn't compressed
+    #[b(2)] content: Option<Vec<u8>>,
+    /// Content compressed before its encryption. It isn't stored in `content`, which
+    /// consumers unaware of compression require, so that they refuse the record instead
+    /// of returning its compressed value
+    #[b(3)] compressed_content: Option<CompressedContent>,
+    /// Data key of the record batch, encrypted for each of its consumers
+    #[b(4)] encrypted_keys: Vec<KafkaEncryptedKey>,
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Wraps the content of the records produced before they were encrypted with data keys,
+///for a single consumer. It can't be decoded as a [`MessageWrapper`], which requires
+///`encrypted_keys`, and consumers only expecting it refuse a [`MessageWrapper`], which
+///doesn't have a `consumer_decryptor_address`
+struct LegacyMessageWrapper {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<1652221>,
     #[b(1)] consumer_decryptor_address: Address,
-    #[b(2)] content: Vec<u8>
+    #[b(2)] content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Content compressed with the codec of its record batch, then encrypted
+struct CompressedContent {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5218736>,
+    /// Identifier of the compression, as used in the attributes of a record batch
+    #[n(1)] codec: i16,
+    #[b(2)] content: Vec<u8>,
+}
+
+impl MessageWrapper {
+    /// Wrap content encrypted with a data key, after its compression with `compression`
+    fn new(
+        encrypted_content: Vec<u8>,
+        compression: Compression,
+        encrypted_keys: Vec<KafkaEncryptedKey>,
+    ) -> Self {
+        let (content, compressed_content) = if compression == Compression::None {
+            (Some(encrypted_content), None)
+        } else {
+            let compressed_content = CompressedContent {
+                #[cfg(feature = "tag")]
+                tag: TypeTag,
+                codec: codec_id(compression),
+                content: encrypted_content,
+            };
+            (None, Some(compressed_content))
+        };
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            content,
+            compressed_content,
+            encrypted_keys,
+        }
+    }
+
+    /// Encrypted content, with the compression to undo once it is decrypted
+    fn encrypted_content(&self) -> Result<(&[u8], Compression), InterceptError> {
+        match (&self.content, &self.compressed_content) {
+            (Some(content), None) => Ok((content, Compression::None)),
+            (None, Some(compressed)) => Ok((&compressed.content, codec(compressed.codec)?)),
+            _ => Err(InterceptError::Io(Error::from(ErrorKind::InvalidData))),
+        }
+    }
+}
+
+/// Name of the record header containing the encrypted key and headers of a record,
+/// as a [`MessageWrapper`] of its [`RecordFields`]
+const ENCRYPTED_FIELDS_HEADER: &str = "ockam.encrypted_fields";
+
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Key and headers of a record, when they are encrypted
+struct RecordFields {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7360491>,
+    #[n(1)] key: Option<Vec<u8>>,
+    #[n(2)] headers: Option<Vec<(String, Option<Vec<u8>>)>>,
 }
 
 impl InletInterceptorImpl {
@@ -93,12 +183,14 @@ impl InletInterceptorImpl {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        record_encryption: KafkaRecordEncryption,
     ) -> InletInterceptorImpl {
         Self {
             request_map: Arc::new(Mutex::new(Default::default())),
             uuid_to_name,
             secure_channel_controller,
             inlet_map,
+            record_encryption,
         }
     }
 }
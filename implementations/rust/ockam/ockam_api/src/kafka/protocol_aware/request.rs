@@ -5,9 +5,7 @@ use kafka_protocol::messages::request_header::RequestHeader;
 use kafka_protocol::messages::ApiKey;
 use kafka_protocol::protocol::buf::ByteBuf;
 use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
+use kafka_protocol::records::{Compression, Record};
 use minicbor::encode::Encoder;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -17,8 +15,16 @@ use std::io::{Error, ErrorKind};
 use tracing::warn;
 
 use crate::kafka::portal_worker::InterceptError;
-use crate::kafka::protocol_aware::utils::{decode_body, encode_request};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::compression::{
+    compress, decode_record_batches, encode_record_batches,
+};
+use crate::kafka::protocol_aware::utils::{decode_body, encode_request, string_to_str_bytes};
+use crate::kafka::protocol_aware::{
+    InletInterceptorImpl, MessageWrapper, RecordFields, RequestInfo, ENCRYPTED_FIELDS_HEADER,
+};
+use crate::kafka::secure_channel_map::{encrypt_with_data_key, KafkaDataKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 
 impl InletInterceptorImpl {
     ///Parse request and map request <=> response
@@ -166,55 +172,58 @@ impl InletInterceptorImpl {
         for (topic_name, topic) in request.topic_data.iter_mut() {
             for data in &mut topic.partition_data {
                 if let Some(content) = data.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-
-                    for record in records.iter_mut() {
-                        if let Some(record_value) = record.value.take() {
-                            let encrypted_content = self
-                                .secure_channel_controller
-                                .encrypt_content_for(
+                    let mut batches = decode_record_batches(content)?;
+                    //every record sent to the partition is encrypted with the same data key,
+                    //which is encrypted for each consumer of the partition
+                    let mut batch_key = None;
+
+                    for batch in batches.iter_mut() {
+                        for record in batch.records.iter_mut() {
+                            if let Some(record_value) = record.value.take() {
+                                let data_key = self
+                                    .data_key_for(context, &mut batch_key, topic_name, data.index)
+                                    .await?;
+
+                                //the encrypted content can't be compressed anymore, so the
+                                //value is compressed beforehand with the codec of its batch
+                                let encrypted_content = encrypt_with_data_key(
+                                    &data_key.key,
+                                    &compress(batch.compression, &record_value)?,
+                                )
+                                .map_err(InterceptError::Ockam)?;
+
+                                let wrapper = MessageWrapper::new(
+                                    encrypted_content,
+                                    batch.compression,
+                                    data_key.encrypted_keys.clone(),
+                                );
+
+                                let mut write_buffer = Vec::with_capacity(1024);
+                                let mut encoder = Encoder::new(&mut write_buffer);
+                                encoder.encode(wrapper).map_err(|_err| {
+                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
+                                })?;
+
+                                record.value = Some(write_buffer.into());
+                            }
+
+                            if !record.control {
+                                self.encrypt_record_fields(
                                     context,
+                                    &mut batch_key,
                                     topic_name,
                                     data.index,
-                                    record_value.to_vec(),
+                                    record,
                                 )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
-                            //TODO: to target multiple consumers we could duplicate
-                            // the content with a dedicated encryption for each consumer
-                            let wrapper = MessageWrapper {
-                                #[cfg(feature = "tag")]
-                                tag: TypeTag,
-                                consumer_decryptor_address: encrypted_content
-                                    .consumer_decryptor_address,
-                                content: encrypted_content.content,
-                            };
-
-                            let mut write_buffer = Vec::with_capacity(1024);
-                            let mut encoder = Encoder::new(&mut write_buffer);
-                            encoder.encode(wrapper).map_err(|_err| {
-                                InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                            })?;
-
-                            record.value = Some(write_buffer.into());
+                                .await?;
+                            }
                         }
+                        //the values are already compressed, the batch isn't compressed
+                        //a second time
+                        batch.compression = Compression::None;
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-
-                    data.records = Some(encoded.freeze());
+                    data.records = Some(encode_record_batches(&batches)?);
                 }
             }
         }
@@ -226,4 +235,89 @@ impl InletInterceptorImpl {
             ApiKey::ProduceKey,
         )
     }
+
+    /// Data key of the record batch being encrypted, created when first needed
+    async fn data_key_for<'a>(
+        &self,
+        context: &mut Context,
+        batch_key: &'a mut Option<KafkaDataKey>,
+        topic_name: &str,
+        partition_id: i32,
+    ) -> Result<&'a KafkaDataKey, InterceptError> {
+        let data_key = match batch_key.take() {
+            Some(data_key) => data_key,
+            None => self
+                .secure_channel_controller
+                .create_data_key_for(context, topic_name, partition_id)
+                .await
+                .map_err(InterceptError::Ockam)?,
+        };
+        Ok(batch_key.insert(data_key))
+    }
+
+    /// Move the key and the headers of a record to an encrypted header, depending on the
+    /// record encryption options. The key is either removed, or replaced with a keyed hash
+    async fn encrypt_record_fields(
+        &self,
+        context: &mut Context,
+        batch_key: &mut Option<KafkaDataKey>,
+        topic_name: &str,
+        partition_id: i32,
+        record: &mut Record,
+    ) -> Result<(), InterceptError> {
+        let key = if self.record_encryption.keys {
+            record.key.take()
+        } else {
+            None
+        };
+        let headers = if self.record_encryption.headers && !record.headers.is_empty() {
+            Some(
+                record
+                    .headers
+                    .drain(..)
+                    .map(|(name, value)| (name.to_string(), value.map(|v| v.to_vec())))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        if key.is_none() && headers.is_none() {
+            return Ok(());
+        }
+
+        if let (Some(key), Some(secret)) = (&key, &self.record_encryption.deterministic_key_secret)
+        {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+                .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidInput)))?;
+            mac.update(key);
+            record.key = Some(mac.finalize().into_bytes().to_vec().into());
+        }
+
+        let fields = RecordFields {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key: key.map(|key| key.to_vec()),
+            headers,
+        };
+        let content = minicbor::to_vec(&fields)
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        let data_key = self
+            .data_key_for(context, batch_key, topic_name, partition_id)
+            .await?;
+
+        let wrapper = MessageWrapper::new(
+            encrypt_with_data_key(&data_key.key, &content).map_err(InterceptError::Ockam)?,
+            Compression::None,
+            data_key.encrypted_keys.clone(),
+        );
+        let wrapper = minicbor::to_vec(&wrapper)
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        record.headers.insert(
+            string_to_str_bytes(ENCRYPTED_FIELDS_HEADER.to_string()),
+            Some(wrapper.into()),
+        );
+        Ok(())
+    }
 }
@@ -9,17 +9,22 @@ use kafka_protocol::messages::response_header::ResponseHeader;
 use kafka_protocol::messages::ApiKey;
 use kafka_protocol::protocol::buf::ByteBuf;
 use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
+use kafka_protocol::records::{Compression, Record};
 use minicbor::decode::Decoder;
 use ockam_node::Context;
 use tracing::{trace, warn};
 
 use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::InterceptError;
+use crate::kafka::protocol_aware::compression::{
+    decode_record_batches, decompress, encode_record_batches,
+};
 use crate::kafka::protocol_aware::utils::{decode_body, encode_response, string_to_str_bytes};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::{
+    InletInterceptorImpl, LegacyMessageWrapper, MessageWrapper, RecordFields, RequestInfo,
+    ENCRYPTED_FIELDS_HEADER,
+};
+use crate::kafka::secure_channel_map::decrypt_with_data_key;
 
 impl InletInterceptorImpl {
     pub(crate) async fn intercept_response_impl(
@@ -224,42 +229,22 @@ impl InletInterceptorImpl {
         for response in response.responses.iter_mut() {
             for partition in response.partitions.iter_mut() {
                 if let Some(content) = partition.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                    let mut batches = decode_record_batches(content)?;
 
-                    for record in records.iter_mut() {
+                    for record in batches
+                        .iter_mut()
+                        .flat_map(|batch| batch.records.iter_mut())
+                    {
                         if let Some(record_value) = record.value.take() {
-                            let message_wrapper: MessageWrapper =
-                                Decoder::new(record_value.as_ref()).decode().map_err(|_| {
-                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                                })?;
-
-                            let decrypted_content = self
-                                .secure_channel_controller
-                                .decrypt_content_for(
-                                    context,
-                                    &message_wrapper.consumer_decryptor_address,
-                                    message_wrapper.content,
-                                )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
+                            let decrypted_content =
+                                self.decrypt_record_value(context, &record_value).await?;
                             record.value = Some(decrypted_content.into());
                         }
+
+                        self.decrypt_record_fields(context, record).await?;
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-                    partition.records = Some(encoded.freeze());
+                    partition.records = Some(encode_record_batches(&batches)?);
                 }
             }
         }
@@ -271,4 +256,92 @@ impl InletInterceptorImpl {
             ApiKey::FetchKey,
         )
     }
+
+    /// Decrypt the value of a record, wrapped either in a [`MessageWrapper`], or in a
+    /// [`LegacyMessageWrapper`] when it was produced before the records were encrypted
+    /// with data keys
+    async fn decrypt_record_value(
+        &self,
+        context: &mut Context,
+        record_value: &[u8],
+    ) -> Result<Vec<u8>, InterceptError> {
+        let message_wrapper: MessageWrapper = match Decoder::new(record_value).decode() {
+            Ok(message_wrapper) => message_wrapper,
+            Err(_) => {
+                let legacy_wrapper: LegacyMessageWrapper = Decoder::new(record_value)
+                    .decode()
+                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                return self
+                    .secure_channel_controller
+                    .decrypt_content_for(
+                        context,
+                        &legacy_wrapper.consumer_decryptor_address,
+                        legacy_wrapper.content,
+                    )
+                    .await
+                    .map_err(InterceptError::Ockam);
+            }
+        };
+        let (encrypted_content, compression) = message_wrapper.encrypted_content()?;
+
+        let data_key = self
+            .secure_channel_controller
+            .decrypt_data_key(context, &message_wrapper.encrypted_keys)
+            .await
+            .map_err(InterceptError::Ockam)?;
+
+        let decrypted_content =
+            decrypt_with_data_key(&data_key, encrypted_content).map_err(InterceptError::Ockam)?;
+
+        match compression {
+            Compression::None => Ok(decrypted_content),
+            compression => decompress(compression, &decrypted_content),
+        }
+    }
+
+    /// Restore the key and the headers of a record from its encrypted header, if any
+    async fn decrypt_record_fields(
+        &self,
+        context: &mut Context,
+        record: &mut Record,
+    ) -> Result<(), InterceptError> {
+        let wrapper = match record
+            .headers
+            .shift_remove(&string_to_str_bytes(ENCRYPTED_FIELDS_HEADER.to_string()))
+        {
+            Some(Some(wrapper)) => wrapper,
+            _ => return Ok(()),
+        };
+
+        let message_wrapper: MessageWrapper = Decoder::new(wrapper.as_ref())
+            .decode()
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        let data_key = self
+            .secure_channel_controller
+            .decrypt_data_key(context, &message_wrapper.encrypted_keys)
+            .await
+            .map_err(InterceptError::Ockam)?;
+
+        let (encrypted_content, _) = message_wrapper.encrypted_content()?;
+        let decrypted_content =
+            decrypt_with_data_key(&data_key, encrypted_content).map_err(InterceptError::Ockam)?;
+
+        let fields: RecordFields = Decoder::new(&decrypted_content)
+            .decode()
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        if let Some(key) = fields.key {
+            record.key = Some(key.into());
+        }
+        if let Some(headers) = fields.headers {
+            record.headers.clear();
+            for (name, value) in headers {
+                record
+                    .headers
+                    .insert(string_to_str_bytes(name), value.map(|v| v.into()));
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,39 +1,74 @@
 #[cfg(test)]
 mod test {
     use crate::kafka::inlet_controller::KafkaInletController;
+    use crate::kafka::portal_worker::MAX_KAFKA_MESSAGE_SIZE;
+    use crate::kafka::protocol_aware::compression::{
+        compress, decode_record_batches, decompress, encode_record_batches, RecordBatch,
+    };
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
-    use crate::kafka::protocol_aware::InletInterceptorImpl;
     use crate::kafka::protocol_aware::KafkaMessageInterceptor;
-    use crate::kafka::secure_channel_map::{KafkaEncryptedContent, KafkaSecureChannelController};
+    use crate::kafka::protocol_aware::{
+        InletInterceptorImpl, LegacyMessageWrapper, MessageWrapper,
+    };
+    use crate::kafka::secure_channel_map::{
+        decrypt_with_data_key, KafkaDataKey, KafkaEncryptedKey, KafkaSecureChannelController,
+    };
+    use crate::nodes::models::services::KafkaRecordEncryption;
     use crate::port_range::PortRange;
+    use bytes::{Bytes, BytesMut};
+    use indexmap::IndexMap;
+    use kafka_protocol::messages::fetch_request::{FetchPartition, FetchTopic};
+    use kafka_protocol::messages::fetch_response::{FetchableTopicResponse, PartitionData};
+    use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
     use kafka_protocol::messages::ApiKey;
     use kafka_protocol::messages::BrokerId;
     use kafka_protocol::messages::{ApiVersionsRequest, MetadataRequest, MetadataResponse};
     use kafka_protocol::messages::{ApiVersionsResponse, RequestHeader, ResponseHeader};
-    use kafka_protocol::protocol::{Builder, StrBytes};
+    use kafka_protocol::messages::{FetchRequest, FetchResponse, ProduceRequest, TopicName};
+    use kafka_protocol::protocol::{Builder, Decodable, StrBytes};
+    use kafka_protocol::records::{
+        Compression, Record, RecordBatchEncoder, RecordEncodeOptions, TimestampType,
+    };
+    use minicbor::Decode;
     use ockam_core::compat::sync::Arc;
     use ockam_core::route;
+    #[cfg(feature = "tag")]
+    use ockam_core::TypeTag;
     use ockam_core::{async_trait, Address};
     use ockam_multiaddr::MultiAddr;
     use ockam_node::Context;
 
+    const TEST_DATA_KEY: [u8; 32] = [1; 32];
+
     struct DummySecureChannelController;
 
     #[async_trait]
     impl KafkaSecureChannelController for DummySecureChannelController {
-        async fn encrypt_content_for(
+        async fn create_data_key_for(
             &self,
             _context: &mut Context,
             _topic_name: &str,
             _partition_id: i32,
-            content: Vec<u8>,
-        ) -> ockam_core::Result<KafkaEncryptedContent> {
-            Ok(KafkaEncryptedContent {
-                content,
-                consumer_decryptor_address: Address::from_string("arbitrary string"),
+        ) -> ockam_core::Result<KafkaDataKey> {
+            Ok(KafkaDataKey {
+                key: TEST_DATA_KEY.to_vec(),
+                encrypted_keys: vec![KafkaEncryptedKey {
+                    #[cfg(feature = "tag")]
+                    tag: TypeTag,
+                    consumer_decryptor_address: Address::from_string("arbitrary string"),
+                    content: TEST_DATA_KEY.to_vec(),
+                }],
             })
         }
 
+        async fn decrypt_data_key(
+            &self,
+            _context: &mut Context,
+            encrypted_keys: &[KafkaEncryptedKey],
+        ) -> ockam_core::Result<Vec<u8>> {
+            Ok(encrypted_keys[0].content.clone())
+        }
+
         async fn decrypt_content_for(
             &self,
             _context: &mut Context,
@@ -70,6 +105,7 @@ mod test {
             Arc::new(DummySecureChannelController {}),
             Default::default(),
             inlet_map,
+            Default::default(),
         );
 
         let mut correlation_id = 0;
@@ -204,4 +240,572 @@ mod test {
 
         context.stop().await
     }
+
+    const TEST_KAFKA_API_VERSION: i16 = 12;
+
+    fn interceptor(record_encryption: KafkaRecordEncryption) -> InletInterceptorImpl {
+        let inlet_map = KafkaInletController::new(
+            MultiAddr::default(),
+            route![],
+            route![],
+            [127, 0, 0, 1].into(),
+            PortRange::new(0, 0).unwrap(),
+        );
+
+        InletInterceptorImpl::new(
+            Arc::new(DummySecureChannelController {}),
+            Default::default(),
+            inlet_map,
+            record_encryption,
+        )
+    }
+
+    fn topic_name() -> TopicName {
+        TopicName::from(StrBytes::from_str("my-topic-name"))
+    }
+
+    fn encode_records(records: Vec<Record>, compression: Compression) -> Bytes {
+        encode_record_batches(&[RecordBatch {
+            records,
+            compression,
+            version: 2,
+        }])
+        .unwrap()
+    }
+
+    fn decode_records(records: &Option<Bytes>) -> Vec<Record> {
+        decode_record_batches(records.clone().unwrap())
+            .unwrap()
+            .into_iter()
+            .flat_map(|batch| batch.records)
+            .collect()
+    }
+
+    fn record(key: &'static str, headers: Vec<(&'static str, &'static str)>) -> Record {
+        let mut record_headers = IndexMap::new();
+        for (name, value) in headers {
+            record_headers.insert(
+                StrBytes::from_str(name),
+                Some(Bytes::from_static(value.as_bytes())),
+            );
+        }
+        Record {
+            transactional: false,
+            control: false,
+            partition_leader_epoch: 0,
+            producer_id: 0,
+            producer_epoch: 0,
+            timestamp_type: TimestampType::Creation,
+            offset: 0,
+            sequence: 0,
+            timestamp: 0,
+            key: Some(Bytes::from_static(key.as_bytes())),
+            value: Some(Bytes::from_static(b"hello world!")),
+            headers: record_headers,
+        }
+    }
+
+    fn request_header(api_key: ApiKey, correlation_id: i32) -> RequestHeader {
+        RequestHeader::builder()
+            .request_api_version(TEST_KAFKA_API_VERSION)
+            .correlation_id(correlation_id)
+            .request_api_key(api_key as i16)
+            .unknown_tagged_fields(Default::default())
+            .client_id(None)
+            .build()
+            .unwrap()
+    }
+
+    /// Send the records through the interceptor in a produce request,
+    /// and return the records of the intercepted request
+    async fn produce(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Bytes {
+        let mut topic_data = IndexMap::new();
+        topic_data.insert(
+            topic_name(),
+            TopicProduceData::builder()
+                .partition_data(vec![PartitionProduceData::builder()
+                    .index(1)
+                    .records(Some(records))
+                    .unknown_tagged_fields(Default::default())
+                    .build()
+                    .unwrap()])
+                .unknown_tagged_fields(Default::default())
+                .build()
+                .unwrap(),
+        );
+        let request = ProduceRequest::builder()
+            .transactional_id(None)
+            .acks(0)
+            .timeout_ms(0)
+            .topic_data(topic_data)
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .unwrap();
+
+        let intercepted = interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &request_header(ApiKey::ProduceKey, 0),
+                    &request,
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::ProduceKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut buffer = intercepted.freeze();
+        RequestHeader::decode(
+            &mut buffer,
+            ApiKey::ProduceKey.request_header_version(TEST_KAFKA_API_VERSION),
+        )
+        .unwrap();
+        let request = ProduceRequest::decode(&mut buffer, TEST_KAFKA_API_VERSION).unwrap();
+        request.topic_data[&topic_name()].partition_data[0]
+            .records
+            .clone()
+            .unwrap()
+    }
+
+    /// Send the records through the interceptor in a fetch response,
+    /// and return the records of the intercepted response
+    async fn fetch(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Vec<Record> {
+        decode_records(&Some(fetch_batches(context, interceptor, records).await))
+    }
+
+    /// Send the records through the interceptor in a fetch response,
+    /// and return the record batches of the intercepted response
+    async fn fetch_batches(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Bytes {
+        let request = FetchRequest::builder()
+            .cluster_id(None)
+            .replica_id(BrokerId::default())
+            .max_wait_ms(0)
+            .min_bytes(0)
+            .max_bytes(0)
+            .isolation_level(0)
+            .session_id(0)
+            .session_epoch(0)
+            .topics(vec![FetchTopic::builder()
+                .topic(topic_name())
+                .topic_id(Default::default())
+                .partitions(vec![FetchPartition::builder()
+                    .partition(1)
+                    .current_leader_epoch(0)
+                    .fetch_offset(0)
+                    .last_fetched_epoch(0)
+                    .log_start_offset(0)
+                    .partition_max_bytes(0)
+                    .unknown_tagged_fields(Default::default())
+                    .build()
+                    .unwrap()])
+                .unknown_tagged_fields(Default::default())
+                .build()
+                .unwrap()])
+            .forgotten_topics_data(Default::default())
+            .rack_id(Default::default())
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .unwrap();
+        interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &request_header(ApiKey::FetchKey, 1),
+                    &request,
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let response = FetchResponse::builder()
+            .throttle_time_ms(Default::default())
+            .error_code(Default::default())
+            .session_id(Default::default())
+            .responses(vec![FetchableTopicResponse::builder()
+                .topic(topic_name())
+                .topic_id(Default::default())
+                .partitions(vec![PartitionData::builder()
+                    .partition_index(1)
+                    .error_code(Default::default())
+                    .high_watermark(Default::default())
+                    .last_stable_offset(Default::default())
+                    .log_start_offset(Default::default())
+                    .diverging_epoch(Default::default())
+                    .current_leader(Default::default())
+                    .snapshot_id(Default::default())
+                    .aborted_transactions(Default::default())
+                    .preferred_read_replica(Default::default())
+                    .records(Some(records))
+                    .unknown_tagged_fields(Default::default())
+                    .build()
+                    .unwrap()])
+                .unknown_tagged_fields(Default::default())
+                .build()
+                .unwrap()])
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .unwrap();
+        let intercepted = interceptor
+            .intercept_response(
+                context,
+                encode_response(
+                    &ResponseHeader::builder()
+                        .correlation_id(1)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    &response,
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut buffer = intercepted.freeze();
+        ResponseHeader::decode(
+            &mut buffer,
+            ApiKey::FetchKey.response_header_version(TEST_KAFKA_API_VERSION),
+        )
+        .unwrap();
+        let response = FetchResponse::decode(&mut buffer, TEST_KAFKA_API_VERSION).unwrap();
+        response.responses[0].partitions[0].records.clone().unwrap()
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__encrypted_keys_and_headers__restored_on_fetch(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(KafkaRecordEncryption {
+            headers: true,
+            keys: true,
+            deterministic_key_secret: Some("secret".to_string()),
+        });
+
+        let produced = produce(
+            context,
+            &interceptor,
+            encode_records(
+                vec![
+                    record("user-1", vec![("trace-id", "abc")]),
+                    record("user-1", vec![]),
+                    record("user-2", vec![]),
+                ],
+                Compression::None,
+            ),
+        )
+        .await;
+
+        // The broker only sees keyed hashes of the keys, and the encrypted fields
+        let records = decode_records(&Some(produced.clone()));
+        let header_names: Vec<String> = records[0]
+            .headers
+            .keys()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(header_names, vec!["ockam.encrypted_fields".to_string()]);
+        let hashed_key = records[0].key.clone().unwrap();
+        assert_eq!(hashed_key.len(), 32);
+        assert_ne!(hashed_key.as_ref(), b"user-1");
+        assert_eq!(records[1].key, Some(hashed_key.clone()));
+        assert_ne!(records[2].key, Some(hashed_key));
+
+        let records = fetch(context, &interceptor, produced).await;
+        assert_eq!(records[0].key.as_deref(), Some(b"user-1".as_ref()));
+        assert_eq!(records[0].headers.len(), 1);
+        assert_eq!(
+            records[0].headers[&StrBytes::from_str("trace-id")].as_deref(),
+            Some(b"abc".as_ref())
+        );
+        assert_eq!(records[0].value.as_deref(), Some(b"hello world!".as_ref()));
+        assert_eq!(records[1].key.as_deref(), Some(b"user-1".as_ref()));
+        assert!(records[1].headers.is_empty());
+        assert_eq!(records[2].key.as_deref(), Some(b"user-2".as_ref()));
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__randomized_keys__removed_from_the_broker(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(KafkaRecordEncryption {
+            headers: false,
+            keys: true,
+            deterministic_key_secret: None,
+        });
+
+        let produced = produce(
+            context,
+            &interceptor,
+            encode_records(
+                vec![record("user-1", vec![("trace-id", "abc")])],
+                Compression::None,
+            ),
+        )
+        .await;
+
+        // Headers are left in cleartext, next to the encrypted key
+        let records = decode_records(&Some(produced.clone()));
+        assert_eq!(records[0].key, None);
+        assert_eq!(records[0].headers.len(), 2);
+        assert_eq!(
+            records[0].headers[&StrBytes::from_str("trace-id")].as_deref(),
+            Some(b"abc".as_ref())
+        );
+
+        let records = fetch(context, &interceptor, produced).await;
+        assert_eq!(records[0].key.as_deref(), Some(b"user-1".as_ref()));
+        assert_eq!(records[0].headers.len(), 1);
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__without_record_encryption__keys_and_headers_unchanged(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(Default::default());
+
+        let produced = produce(
+            context,
+            &interceptor,
+            encode_records(
+                vec![record("user-1", vec![("trace-id", "abc")])],
+                Compression::None,
+            ),
+        )
+        .await;
+
+        let records = decode_records(&Some(produced));
+        assert_eq!(records[0].key.as_deref(), Some(b"user-1".as_ref()));
+        assert_eq!(records[0].headers.len(), 1);
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__compressed_batches__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(Default::default());
+
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ] {
+            let produced = produce(
+                context,
+                &interceptor,
+                encode_records(
+                    vec![record("user-1", vec![]), record("user-2", vec![])],
+                    compression,
+                ),
+            )
+            .await;
+
+            // Values are compressed before being encrypted, and the batch
+            // isn't compressed a second time
+            let batches = decode_record_batches(produced.clone()).unwrap();
+            assert_eq!(batches.len(), 1);
+            assert_eq!(batches[0].compression, Compression::None);
+            let value = batches[0].records[0].value.clone().unwrap();
+            let wrapper: MessageWrapper = minicbor::decode(&value).unwrap();
+            let (encrypted_content, value_compression) = wrapper.encrypted_content().unwrap();
+            assert_eq!(value_compression, compression);
+            let content = decrypt_with_data_key(&TEST_DATA_KEY, encrypted_content).unwrap();
+            assert_eq!(decompress(compression, &content).unwrap(), b"hello world!");
+
+            // Consumers unaware of compression can't decode compressed values
+            let unaware: Result<CompressionUnawareMessageWrapper, _> = minicbor::decode(&value);
+            assert_eq!(unaware.is_ok(), compression == Compression::None);
+
+            // The broker may compress the batches again before they are fetched
+            let stored = encode_records(
+                batches
+                    .into_iter()
+                    .flat_map(|batch| batch.records)
+                    .collect(),
+                compression,
+            );
+            let fetched = fetch_batches(context, &interceptor, stored).await;
+            let batches = decode_record_batches(fetched).unwrap();
+            assert_eq!(batches.len(), 1);
+            assert_eq!(batches[0].compression, compression);
+            assert_eq!(batches[0].records.len(), 2);
+            for (record, key) in batches[0].records.iter().zip(["user-1", "user-2"]) {
+                assert_eq!(record.key.as_deref(), Some(key.as_bytes()));
+                assert_eq!(record.value.as_deref(), Some(b"hello world!".as_ref()));
+            }
+        }
+
+        context.stop().await
+    }
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__batches_with_different_compressions__compression_kept_per_batch(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(Default::default());
+
+        let stored = encode_record_batches(&[
+            RecordBatch {
+                records: vec![record("user-1", vec![])],
+                compression: Compression::Gzip,
+                version: 2,
+            },
+            RecordBatch {
+                records: vec![record("user-2", vec![])],
+                compression: Compression::Zstd,
+                version: 2,
+            },
+        ])
+        .unwrap();
+        let produced = produce(context, &interceptor, stored).await;
+        let produced = decode_record_batches(produced).unwrap();
+        let stored = encode_record_batches(&[
+            RecordBatch {
+                records: produced[0].records.clone(),
+                compression: Compression::Gzip,
+                version: 2,
+            },
+            RecordBatch {
+                records: produced[1].records.clone(),
+                compression: Compression::Zstd,
+                version: 2,
+            },
+        ])
+        .unwrap();
+
+        let fetched = fetch_batches(context, &interceptor, stored).await;
+        let batches = decode_record_batches(fetched).unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].compression, Compression::Gzip);
+        assert_eq!(batches[1].compression, Compression::Zstd);
+        assert_eq!(
+            batches[1].records[0].key.as_deref(),
+            Some(b"user-2".as_ref())
+        );
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn legacy_message_sets__version_kept__compressed_ones_rejected() {
+        let mut legacy = BytesMut::new();
+        RecordBatchEncoder::encode(
+            &mut legacy,
+            [record("user-1", vec![])].iter(),
+            &RecordEncodeOptions {
+                version: 1,
+                compression: Compression::None,
+            },
+        )
+        .unwrap();
+        let batches = decode_record_batches(legacy.freeze()).unwrap();
+        assert_eq!(batches[0].version, 1);
+        let encoded = encode_record_batches(&batches).unwrap();
+        assert_eq!(encoded[16], 1);
+
+        let mut compressed = BytesMut::new();
+        RecordBatchEncoder::encode(
+            &mut compressed,
+            [record("user-1", vec![])].iter(),
+            &RecordEncodeOptions {
+                version: 1,
+                compression: Compression::Gzip,
+            },
+        )
+        .unwrap();
+        assert!(decode_record_batches(compressed.freeze()).is_err());
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn decompress__output_larger_than_the_limit__rejected() {
+        let data = vec![0u8; MAX_KAFKA_MESSAGE_SIZE as usize + 1];
+        for compression in [
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ] {
+            let compressed = compress(compression, &data).unwrap();
+            assert!(decompress(compression, &compressed).is_err());
+            let compressed = compress(compression, &data[1..]).unwrap();
+            assert!(decompress(compression, &compressed).is_ok());
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__records_produced_before_data_keys__decrypted_with_the_secure_channel(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let interceptor = interceptor(Default::default());
+
+        let mut legacy_record = record("user-1", vec![]);
+        let legacy_wrapper = LegacyMessageWrapper {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            consumer_decryptor_address: Address::from_string("arbitrary string"),
+            content: b"hello world!".to_vec(),
+        };
+        legacy_record.value = Some(minicbor::to_vec(&legacy_wrapper).unwrap().into());
+
+        let fetched = fetch_batches(
+            context,
+            &interceptor,
+            encode_records(vec![legacy_record], Compression::None),
+        )
+        .await;
+        let records = decode_records(&Some(fetched));
+        assert_eq!(records[0].value.as_deref(), Some(b"hello world!".as_ref()));
+
+        // consumers only expecting legacy wrappers refuse the current ones
+        let produced = produce(
+            context,
+            &interceptor,
+            encode_records(vec![record("user-1", vec![])], Compression::None),
+        )
+        .await;
+        let value = decode_records(&Some(produced))[0].value.clone().unwrap();
+        assert!(minicbor::decode::<LegacyMessageWrapper>(&value).is_err());
+
+        context.stop().await
+    }
+
+    /// Message wrapper as decoded by consumers unaware of compression
+    #[derive(Decode)]
+    #[cbor(map)]
+    struct CompressionUnawareMessageWrapper {
+        #[b(2)]
+        _content: Vec<u8>,
+    }
 }
@@ -1,17 +1,22 @@
-use crate::kafka::KAFKA_OUTLET_CONSUMERS;
+use crate::kafka::{KAFKA_OUTLET_CONSUMERS, KAFKA_OUTLET_CONSUMERS_DISCOVERY};
 use crate::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelRequest, CreateSecureChannelResponse, CredentialExchangeMode,
     DeleteSecureChannelRequest, DeleteSecureChannelResponse,
 };
-use crate::nodes::NODEMANAGER_ADDR;
+use crate::nodes::send_to_node_manager;
+use crate::nodes::service::message::SendMessage;
 use crate::DefaultAddress;
-use minicbor::Decoder;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use minicbor::{Decode, Decoder, Encode};
 use ockam_abac::AbacAccessControl;
 use ockam_core::api::{Request, Response, Status};
-use ockam_core::compat::collections::{HashMap, HashSet};
+use ockam_core::compat::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
 use ockam_core::{async_trait, route, Address, Error, Result};
 use ockam_identity::{
     DecryptionRequest, DecryptionResponse, EncryptionRequest, EncryptionResponse,
@@ -20,41 +25,120 @@ use ockam_identity::{
 use ockam_multiaddr::proto::Service;
 use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::tokio::sync::Mutex;
-use ockam_node::compat::tokio::sync::MutexGuard;
 use ockam_node::Context;
+use rand::{thread_rng, RngCore};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Length of the keys encrypting the records of a batch
+const DATA_KEY_LENGTH: usize = 32;
+/// Length of the random nonce prepended to the content encrypted with a data key
+const DATA_KEY_NONCE_LENGTH: usize = 12;
+/// Maximum number of decrypted data keys kept by a consumer.
+///
+/// A data key can only be decrypted once with the secure channel of the consumer, which
+/// rejects replayed messages. The records encrypted with a data key which was evicted
+/// from the cache can't be decrypted anymore, for instance when they are fetched again
+/// after a seek or a rebalance
+const MAX_DATA_KEYS: usize = 10_000;
+/// Maximum number of evicted data keys remembered by a consumer, to report why they
+/// can't be decrypted anymore
+const MAX_EVICTED_DATA_KEYS: usize = 10 * MAX_DATA_KEYS;
+
+/// Key encrypting the records of a batch, along with its encryption for each consumer
+pub(crate) struct KafkaDataKey {
+    /// The key, used to encrypt the content of the records
+    pub(crate) key: Vec<u8>,
+    /// The key encrypted with the secure channel of each authorized consumer
+    pub(crate) encrypted_keys: Vec<KafkaEncryptedKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Data key encrypted for a single consumer
+pub(crate) struct KafkaEncryptedKey {
+    #[cfg(feature = "tag")]
+    #[n(0)] pub(crate) tag: TypeTag<3818450>,
+    /// The secure channel identifier used to encrypt the key
+    #[b(1)] pub(crate) consumer_decryptor_address: Address,
+    /// The encrypted key
+    #[b(2)] pub(crate) content: Vec<u8>,
+}
+
+/// Encrypts the content with a data key, the random nonce is prepended to the result
+pub(crate) fn encrypt_with_data_key(key: &[u8], content: &[u8]) -> Result<Vec<u8>> {
+    if key.len() != DATA_KEY_LENGTH {
+        return Err(Error::new(
+            Origin::Channel,
+            Kind::Invalid,
+            "invalid data key length",
+        ));
+    }
+    let mut nonce = [0u8; DATA_KEY_NONCE_LENGTH];
+    thread_rng().fill_bytes(&mut nonce);
+
+    let encrypted_content = Aes256Gcm::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), content)
+        .map_err(|_| Error::new(Origin::Channel, Kind::Invalid, "cannot encrypt content"))?;
 
-pub(crate) struct KafkaEncryptedContent {
-    /// The encrypted content
-    pub(crate) content: Vec<u8>,
-    /// The secure channel identifier used to encrypt the content
-    pub(crate) consumer_decryptor_address: Address,
+    let mut result = nonce.to_vec();
+    result.extend(encrypted_content);
+    Ok(result)
+}
+
+/// Decrypts the content encrypted by [`encrypt_with_data_key`]
+pub(crate) fn decrypt_with_data_key(key: &[u8], encrypted_content: &[u8]) -> Result<Vec<u8>> {
+    if key.len() != DATA_KEY_LENGTH || encrypted_content.len() < DATA_KEY_NONCE_LENGTH {
+        return Err(Error::new(
+            Origin::Channel,
+            Kind::Invalid,
+            "invalid encrypted content",
+        ));
+    }
+    let (nonce, encrypted_content) = encrypted_content.split_at(DATA_KEY_NONCE_LENGTH);
+
+    Aes256Gcm::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), encrypted_content)
+        .map_err(|_| Error::new(Origin::Channel, Kind::Invalid, "cannot decrypt content"))
 }
 
 /// Offer simple APIs to encrypt and decrypt kafka messages.
-/// Underneath it creates secure channels for each topic/partition
-/// and uses them to encrypt the content.
-/// Multiple secure channels may be created for the same topic/partition
-/// but each will be explicitly labelled.
+/// The records of each batch are encrypted with a new data key, and that key is
+/// encrypted for every consumer of the topic/partition.
+/// Underneath it creates a secure channel for each consumer and uses it to
+/// encrypt the data keys.
 /// It's the same for both producer and consumer although it could be split
 /// into two distinct implementations.
 /// This is a proxy trait to avoid propagating the vault implementation.
 #[async_trait]
 pub(crate) trait KafkaSecureChannelController: Send + Sync {
-    /// Encrypts the content specifically for the consumer waiting for that topic name and
-    /// partition.
-    /// To do so it'll create a secure channel which will be used for key exchange only.
-    /// The secure channel will be created only once and then re-used, hence the first time will
-    /// be slower, and may take up to few seconds.
-    async fn encrypt_content_for(
+    /// Creates a data key for a batch of records of that topic name and partition, and
+    /// encrypts it for every authorized consumer waiting for them.
+    /// To do so it'll create a secure channel to each consumer which will be used for
+    /// key exchange only.
+    /// The secure channels will be created only once and then re-used, hence the first time
+    /// will be slower, and may take up to few seconds.
+    /// Fails when an authorized consumer can't be reached, rather than producing records
+    /// it would not be able to decrypt.
+    async fn create_data_key_for(
         &self,
         context: &mut Context,
         topic_name: &str,
         partition_id: i32,
-        content: Vec<u8>,
-    ) -> Result<KafkaEncryptedContent>;
+    ) -> Result<KafkaDataKey>;
 
-    /// Decrypts the content based on the consumer decryptor address
-    /// the secure channel is expected to be already initialized.
+    /// Decrypts a data key with the secure channel of this consumer, based on the consumer
+    /// decryptor addresses. The secure channel is expected to be already initialized.
+    async fn decrypt_data_key(
+        &self,
+        context: &mut Context,
+        encrypted_keys: &[KafkaEncryptedKey],
+    ) -> Result<Vec<u8>>;
+
+    /// Decrypts the content of a record produced before the records were encrypted with
+    /// data keys, based on the consumer decryptor address of its secure channel.
+    /// The secure channel is expected to be already initialized.
     async fn decrypt_content_for(
         &self,
         context: &mut Context,
@@ -76,10 +160,18 @@ pub(crate) trait KafkaSecureChannelController: Send + Sync {
 #[async_trait]
 pub(crate) trait ForwarderCreator: Send + Sync + 'static {
     async fn create_forwarder(&self, context: &Context, alias: String) -> Result<()>;
+
+    /// Lists the addresses of the forwarders of the consumers of a topic/partition
+    async fn list_forwarders(
+        &self,
+        context: &Context,
+        topic_partition: &str,
+    ) -> Result<Vec<String>>;
 }
 
 pub(crate) struct NodeManagerForwarderCreator {
     orchestrator_multiaddr: MultiAddr,
+    discovery_multiaddr: MultiAddr,
 }
 
 impl NodeManagerForwarderCreator {
@@ -93,19 +185,18 @@ impl NodeManagerForwarderCreator {
             .map(|value| value.cast::<ockam_multiaddr::proto::Project>().is_none())
             .unwrap_or(true);
 
-        let buffer: Vec<u8> = context
-            .send_and_receive(
-                route![NODEMANAGER_ADDR],
-                Request::post("/node/forwarder")
-                    .body(CreateForwarder::at_node(
-                        forwarder_service,
-                        Some(alias),
-                        is_rust,
-                        None,
-                    ))
-                    .to_vec()?,
-            )
-            .await?;
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::post("/node/forwarder")
+                .body(CreateForwarder::at_node(
+                    forwarder_service,
+                    Some(alias),
+                    is_rust,
+                    None,
+                ))
+                .to_vec()?,
+        )
+        .await?;
 
         let mut decoder = Decoder::new(&buffer);
         let response: Response = decoder.decode()?;
@@ -130,6 +221,49 @@ impl NodeManagerForwarderCreator {
             Ok(())
         }
     }
+
+    async fn request_forwarders_list(
+        context: &Context,
+        discovery_service: MultiAddr,
+        topic_partition: &str,
+    ) -> Result<Vec<String>> {
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::post("/v0/message")
+                .body(SendMessage::new(
+                    &discovery_service,
+                    Request::get(format!("/consumers/{topic_partition}")).to_vec()?,
+                ))
+                .to_vec()?,
+        )
+        .await?;
+
+        let mut decoder = Decoder::new(&buffer);
+        let response: Response = decoder.decode()?;
+
+        let status = response.status().unwrap_or(Status::InternalServerError);
+        if status != Status::Ok || !response.has_body() {
+            return Err(Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                format!("cannot reach the consumers discovery service: {}", status),
+            ));
+        }
+        let buffer: Vec<u8> = decoder.decode()?;
+
+        let mut decoder = Decoder::new(&buffer);
+        let response: Response = decoder.decode()?;
+
+        let status = response.status().unwrap_or(Status::InternalServerError);
+        if status != Status::Ok || !response.has_body() {
+            return Err(Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                format!("cannot list the consumer forwarders: {}", status),
+            ));
+        }
+        Ok(decoder.decode()?)
+    }
 }
 
 #[async_trait]
@@ -140,10 +274,25 @@ impl ForwarderCreator for NodeManagerForwarderCreator {
             .await?;
         Ok(())
     }
+
+    async fn list_forwarders(
+        &self,
+        context: &Context,
+        topic_partition: &str,
+    ) -> Result<Vec<String>> {
+        trace!("listing remote forwarders for: {topic_partition}");
+        Self::request_forwarders_list(context, self.discovery_multiaddr.clone(), topic_partition)
+            .await
+    }
 }
 
 pub(crate) struct KafkaSecureChannelControllerImpl<F: ForwarderCreator> {
-    inner: Arc<Mutex<InnerSecureChannelControllerImpl<F>>>,
+    inner: Arc<Mutex<InnerSecureChannelControllerImpl>>,
+    outlet_node_multiaddr: MultiAddr,
+    consumer_id: String,
+    forwarder_creator: Arc<F>,
+    secure_channels: Arc<SecureChannels>,
+    access_control: Arc<AbacAccessControl>,
 }
 
 //had to manually implement since #[derive(Clone)] doesn't work well in this situation
@@ -151,20 +300,92 @@ impl<F: ForwarderCreator> Clone for KafkaSecureChannelControllerImpl<F> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            outlet_node_multiaddr: self.outlet_node_multiaddr.clone(),
+            consumer_id: self.consumer_id.clone(),
+            forwarder_creator: self.forwarder_creator.clone(),
+            secure_channels: self.secure_channels.clone(),
+            access_control: self.access_control.clone(),
         }
     }
 }
 
 type TopicPartition = (String, i32);
-struct InnerSecureChannelControllerImpl<F: ForwarderCreator> {
+
+/// State shared by the producers and the consumers of a kafka service. The lock is only
+/// held to read and update it, never while waiting for another node
+#[derive(Default)]
+struct InnerSecureChannelControllerImpl {
     // we identity the secure channel instance by using the decryptor of the consumer
     // which is known to both parties
-    topic_encryptor_map: HashMap<TopicPartition, Address>,
-    outlet_node_multiaddr: MultiAddr,
+    consumer_encryptor_map: HashMap<String, Address>,
+    // forwarders of the consumers of a topic/partition, as last listed by the producer
+    topic_consumers_map: HashMap<TopicPartition, Vec<String>>,
+    // data keys are decrypted only once, the secure channel rejects replayed messages
+    data_keys: DataKeyCache,
     topic_forwarder_set: HashSet<TopicPartition>,
-    forwarder_creator: F,
-    secure_channels: Arc<SecureChannels>,
-    access_control: AbacAccessControl,
+}
+
+/// Decrypted data keys, indexed by their encryption for the consumer.
+///
+/// The least recently used keys are evicted when the cache is full, and the fingerprints
+/// of the evicted keys are kept so that the consumer can tell why they can't be
+/// decrypted anymore
+#[derive(Default)]
+struct DataKeyCache {
+    // encrypted data key -> (data key, last use)
+    keys: HashMap<Vec<u8>, (Vec<u8>, u64)>,
+    // last use -> encrypted data key
+    uses: BTreeMap<u64, Vec<u8>>,
+    next_use: u64,
+    evicted: HashSet<u64>,
+    evicted_order: VecDeque<u64>,
+    fingerprints: RandomState,
+}
+
+impl DataKeyCache {
+    fn get(&mut self, encrypted_key: &[u8]) -> Option<Vec<u8>> {
+        let now = self.next_use;
+        let (key, last_use) = self.keys.get_mut(encrypted_key)?;
+        let previous_use = core::mem::replace(last_use, now);
+        let key = key.clone();
+        if let Some(encrypted_key) = self.uses.remove(&previous_use) {
+            self.uses.insert(now, encrypted_key);
+        }
+        self.next_use += 1;
+        Some(key)
+    }
+
+    fn insert(&mut self, encrypted_key: Vec<u8>, key: Vec<u8>) {
+        if self.keys.len() >= MAX_DATA_KEYS {
+            if let Some((_, evicted_key)) = self.uses.pop_first() {
+                self.keys.remove(&evicted_key);
+                self.remember_evicted(&evicted_key);
+            }
+        }
+        let now = self.next_use;
+        self.next_use += 1;
+        self.uses.insert(now, encrypted_key.clone());
+        if let Some((_, previous_use)) = self.keys.insert(encrypted_key, (key, now)) {
+            self.uses.remove(&previous_use);
+        }
+    }
+
+    fn was_evicted(&self, encrypted_key: &[u8]) -> bool {
+        self.evicted
+            .contains(&self.fingerprints.hash_one(encrypted_key))
+    }
+
+    fn remember_evicted(&mut self, encrypted_key: &[u8]) {
+        if self.evicted_order.len() >= MAX_EVICTED_DATA_KEYS {
+            if let Some(oldest) = self.evicted_order.pop_front() {
+                self.evicted.remove(&oldest);
+            }
+        }
+        let fingerprint = self.fingerprints.hash_one(encrypted_key);
+        if self.evicted.insert(fingerprint) {
+            self.evicted_order.push_back(fingerprint);
+        }
+    }
 }
 
 impl KafkaSecureChannelControllerImpl<NodeManagerForwarderCreator> {
@@ -172,18 +393,27 @@ impl KafkaSecureChannelControllerImpl<NodeManagerForwarderCreator> {
         secure_channels: Arc<SecureChannels>,
         outlet_node_multiaddr: MultiAddr,
         trust_context_id: String,
+        consumer_id: String,
     ) -> KafkaSecureChannelControllerImpl<NodeManagerForwarderCreator> {
         let mut orchestrator_multiaddr = outlet_node_multiaddr.clone();
         orchestrator_multiaddr
             .push_back(Service::new(KAFKA_OUTLET_CONSUMERS))
             .unwrap();
+
+        let mut discovery_multiaddr = outlet_node_multiaddr.clone();
+        discovery_multiaddr
+            .push_back(Service::new(KAFKA_OUTLET_CONSUMERS_DISCOVERY))
+            .unwrap();
+
         Self::new_extended(
             secure_channels,
             outlet_node_multiaddr,
             NodeManagerForwarderCreator {
                 orchestrator_multiaddr,
+                discovery_multiaddr,
             },
             trust_context_id,
+            consumer_id,
         )
     }
 }
@@ -195,6 +425,7 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         outlet_node_multiaddr: MultiAddr,
         forwarder_creator: F,
         trust_context_id: String,
+        consumer_id: String,
     ) -> KafkaSecureChannelControllerImpl<F> {
         let access_control = AbacAccessControl::create(
             secure_channels.identities().repository(),
@@ -203,14 +434,12 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         );
 
         Self {
-            inner: Arc::new(Mutex::new(InnerSecureChannelControllerImpl {
-                topic_encryptor_map: Default::default(),
-                topic_forwarder_set: Default::default(),
-                secure_channels,
-                forwarder_creator,
-                outlet_node_multiaddr,
-                access_control,
-            })),
+            inner: Default::default(),
+            outlet_node_multiaddr,
+            consumer_id,
+            forwarder_creator: Arc::new(forwarder_creator),
+            secure_channels,
+            access_control: Arc::new(access_control),
         }
     }
 
@@ -224,20 +453,19 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         context: &Context,
         destination: MultiAddr,
     ) -> Result<Address> {
-        let buffer: Vec<u8> = context
-            .send_and_receive(
-                route![NODEMANAGER_ADDR],
-                Request::post("/node/secure_channel")
-                    .body(CreateSecureChannelRequest::new(
-                        &destination,
-                        None,
-                        CredentialExchangeMode::Mutual,
-                        None,
-                        None,
-                    ))
-                    .to_vec()?,
-            )
-            .await?;
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::post("/node/secure_channel")
+                .body(CreateSecureChannelRequest::new(
+                    &destination,
+                    None,
+                    CredentialExchangeMode::Mutual,
+                    None,
+                    None,
+                ))
+                .to_vec()?,
+        )
+        .await?;
 
         let mut decoder = Decoder::new(&buffer);
         let response: Response = decoder.decode()?;
@@ -266,14 +494,13 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         context: &Context,
         encryptor_address: &Address,
     ) -> Result<()> {
-        let buffer: Vec<u8> = context
-            .send_and_receive(
-                route![NODEMANAGER_ADDR],
-                Request::delete("/node/secure_channel")
-                    .body(DeleteSecureChannelRequest::new(encryptor_address))
-                    .to_vec()?,
-            )
-            .await?;
+        let buffer: Vec<u8> = send_to_node_manager(
+            context,
+            Request::delete("/node/secure_channel")
+                .body(DeleteSecureChannelRequest::new(encryptor_address))
+                .to_vec()?,
+        )
+        .await?;
 
         let mut decoder = Decoder::new(&buffer);
         let response: Response = decoder.decode()?;
@@ -298,58 +525,74 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         }
     }
 
-    ///returns encryptor api address
+    /// Returns the secure channel to a consumer, or `None` when the consumer is not
+    /// authorized to decrypt the records
     async fn get_or_create_secure_channel_for(
         &self,
         context: &mut Context,
-        topic_name: &str,
-        partition: i32,
-    ) -> Result<SecureChannelRegistryEntry> {
-        // here we should have the orchestrator address and expect forwarders to be
-        // present in the orchestrator with the format "consumer__{topic_name}_{partition}"
-
-        let topic_partition_key = (topic_name.to_string(), partition);
-        //consumer__ prefix is added by the orchestrator
-        let topic_partition_address = format!("consumer__{topic_name}_{partition}");
-
-        let mut inner = self.inner.lock().await;
-
-        let encryptor_address = {
-            if let Some(encryptor_address) = inner.topic_encryptor_map.get(&topic_partition_key) {
-                encryptor_address.clone()
-            } else {
-                debug!("creating new secure channel to {topic_partition_address}");
-
-                let mut destination = inner.outlet_node_multiaddr.clone();
-                destination.push_back(Service::new(topic_partition_address.clone()))?;
+        consumer_forwarder: &str,
+    ) -> Result<Option<SecureChannelRegistryEntry>> {
+        let encryptor_address = self
+            .inner
+            .lock()
+            .await
+            .consumer_encryptor_map
+            .get(consumer_forwarder)
+            .cloned();
+
+        let encryptor_address = match encryptor_address {
+            Some(encryptor_address) => encryptor_address,
+            None => {
+                debug!("creating new secure channel to {consumer_forwarder}");
+
+                let mut destination = self.outlet_node_multiaddr.clone();
+                destination.push_back(Service::new(consumer_forwarder))?;
                 destination.push_back(Service::new(DefaultAddress::SECURE_CHANNEL_LISTENER))?;
 
                 let producer_encryptor_address =
                     Self::request_secure_channel_creation(context, destination).await?;
 
-                match Self::validate_consumer_credentials(&inner, &producer_encryptor_address).await
-                {
-                    Ok(producer_encryptor_address) => producer_encryptor_address,
-                    Err(error) => {
+                let authorized = self
+                    .validate_consumer_credentials(&producer_encryptor_address)
+                    .await;
+                if !matches!(authorized, Ok(true)) {
+                    Self::request_secure_channel_deletion(context, &producer_encryptor_address)
+                        .await?;
+                    return authorized.map(|_| None);
+                }
+
+                // another batch may have created a secure channel to this consumer meanwhile
+                let existing = {
+                    let mut inner = self.inner.lock().await;
+                    match inner.consumer_encryptor_map.get(consumer_forwarder) {
+                        Some(existing) => Some(existing.clone()),
+                        None => {
+                            inner.consumer_encryptor_map.insert(
+                                consumer_forwarder.to_string(),
+                                producer_encryptor_address.clone(),
+                            );
+                            None
+                        }
+                    }
+                };
+                match existing {
+                    Some(existing) => {
                         Self::request_secure_channel_deletion(context, &producer_encryptor_address)
                             .await?;
-                        return Err(error);
+                        existing
                     }
-                };
-
-                inner
-                    .topic_encryptor_map
-                    .insert(topic_partition_key, producer_encryptor_address.clone());
-
-                debug!("created secure channel to {topic_partition_address}");
-                producer_encryptor_address
+                    None => {
+                        debug!("created secure channel to {consumer_forwarder}");
+                        producer_encryptor_address
+                    }
+                }
             }
         };
 
-        inner
-            .secure_channels
+        self.secure_channels
             .secure_channel_registry()
             .get_channel_by_encryptor_address(&encryptor_address)
+            .map(Some)
             .ok_or_else(|| {
                 Error::new(
                     Origin::Channel,
@@ -359,30 +602,68 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
             })
     }
 
+    /// Returns the forwarders of the consumers of a topic/partition. They are listed for
+    /// every data key, so that a consumer can decrypt all the records produced after the
+    /// creation of its forwarder
+    async fn get_consumer_forwarders_for(
+        &self,
+        context: &mut Context,
+        topic_name: &str,
+        partition: i32,
+    ) -> Result<Vec<String>> {
+        // here we should have the orchestrator address and expect forwarders to be
+        // present in the orchestrator with the format "consumer__{topic_name}_{partition}"
+        // or "consumer__{topic_name}_{partition}.{consumer_id}"
+        let topic_partition = format!("{topic_name}_{partition}");
+        let forwarders = self
+            .forwarder_creator
+            .list_forwarders(context, &topic_partition)
+            .await?;
+        debug!("consumers of {topic_partition}: {forwarders:?}");
+
+        // the secure channels of the consumers which are gone are not needed anymore
+        let gone: Vec<(String, Address)> = {
+            let mut inner = self.inner.lock().await;
+            let previous = inner
+                .topic_consumers_map
+                .insert((topic_name.to_string(), partition), forwarders.clone())
+                .unwrap_or_default();
+            previous
+                .into_iter()
+                .filter(|forwarder| !forwarders.contains(forwarder))
+                .filter_map(|forwarder| {
+                    inner
+                        .consumer_encryptor_map
+                        .remove(&forwarder)
+                        .map(|encryptor_address| (forwarder, encryptor_address))
+                })
+                .collect()
+        };
+        for (forwarder, encryptor_address) in gone {
+            if let Err(error) =
+                Self::request_secure_channel_deletion(context, &encryptor_address).await
+            {
+                warn!("cannot delete the secure channel to {forwarder}: {error}");
+            }
+        }
+
+        Ok(forwarders)
+    }
+
+    /// Whether the consumer at the other end of a secure channel is authorized
     async fn validate_consumer_credentials(
-        inner: &MutexGuard<'_, InnerSecureChannelControllerImpl<F>>,
+        &self,
         producer_encryptor_address: &Address,
-    ) -> Result<Address> {
-        let record = inner
+    ) -> Result<bool> {
+        let record = self
             .secure_channels
             .secure_channel_registry()
             .get_channel_by_encryptor_address(producer_encryptor_address);
 
         if let Some(entry) = record {
-            let authorized = inner
-                .access_control
+            self.access_control
                 .is_identity_authorized(entry.their_id())
-                .await?;
-
-            if authorized {
-                Ok(producer_encryptor_address.clone())
-            } else {
-                Err(Error::new(
-                    Origin::Transport,
-                    Kind::Invalid,
-                    "unauthorized secure channel for consumer",
-                ))
-            }
+                .await
         } else {
             Err(Error::new(
                 Origin::Transport,
@@ -397,8 +678,7 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
         &self,
         consumer_decryptor_address: &Address,
     ) -> Result<SecureChannelRegistryEntry> {
-        let inner = self.inner.lock().await;
-        let entry = inner
+        let entry = self
             .secure_channels
             .secure_channel_registry()
             .get_channel_by_decryptor_address(consumer_decryptor_address)
@@ -413,7 +693,7 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
                 )
             })?;
 
-        let authorized = inner
+        let authorized = self
             .access_control
             .is_identity_authorized(entry.their_id())
             .await?;
@@ -428,46 +708,175 @@ impl<F: ForwarderCreator> KafkaSecureChannelControllerImpl<F> {
             ))
         }
     }
-}
 
-#[async_trait]
-impl<F: ForwarderCreator> KafkaSecureChannelController for KafkaSecureChannelControllerImpl<F> {
-    async fn encrypt_content_for(
+    /// Encrypts a data key for a consumer, returns `None` when the consumer is not
+    /// authorized to decrypt the records
+    async fn encrypt_key_for(
         &self,
         context: &mut Context,
-        topic_name: &str,
-        partition_id: i32,
-        content: Vec<u8>,
-    ) -> Result<KafkaEncryptedContent> {
-        let secure_channel_entry = self
-            .get_or_create_secure_channel_for(context, topic_name, partition_id)
-            .await?;
+        consumer_forwarder: &str,
+        key: &[u8],
+    ) -> Result<Option<KafkaEncryptedKey>> {
+        let secure_channel_entry = match self
+            .get_or_create_secure_channel_for(context, consumer_forwarder)
+            .await?
+        {
+            Some(secure_channel_entry) => secure_channel_entry,
+            None => return Ok(None),
+        };
 
         let consumer_decryptor_address = secure_channel_entry.their_decryptor_address();
 
-        trace!("encrypting content with {consumer_decryptor_address}");
+        trace!("encrypting data key with {consumer_decryptor_address}");
         let encryption_response: EncryptionResponse = context
             .send_and_receive(
                 route![secure_channel_entry.encryptor_api_address().clone()],
-                EncryptionRequest(content),
+                EncryptionRequest(key.to_vec()),
             )
             .await?;
 
-        let encrypted_content = match encryption_response {
+        let encrypted_key = match encryption_response {
             EncryptionResponse::Ok(p) => p,
             EncryptionResponse::Err(cause) => {
-                warn!("cannot encrypt kafka message");
+                warn!("cannot encrypt kafka data key");
                 return Err(cause);
             }
         };
 
-        trace!("encrypted content with {consumer_decryptor_address}");
-        Ok(KafkaEncryptedContent {
-            content: encrypted_content,
+        trace!("encrypted data key with {consumer_decryptor_address}");
+        Ok(Some(KafkaEncryptedKey {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
             consumer_decryptor_address,
+            content: encrypted_key,
+        }))
+    }
+}
+
+#[async_trait]
+impl<F: ForwarderCreator> KafkaSecureChannelController for KafkaSecureChannelControllerImpl<F> {
+    async fn create_data_key_for(
+        &self,
+        context: &mut Context,
+        topic_name: &str,
+        partition_id: i32,
+    ) -> Result<KafkaDataKey> {
+        let consumer_forwarders = self
+            .get_consumer_forwarders_for(context, topic_name, partition_id)
+            .await?;
+
+        let mut key = vec![0u8; DATA_KEY_LENGTH];
+        thread_rng().fill_bytes(&mut key);
+
+        let mut encrypted_keys = Vec::with_capacity(consumer_forwarders.len());
+        for consumer_forwarder in consumer_forwarders {
+            // the records are not produced when an authorized consumer can't be reached,
+            // since it would never be able to decrypt them
+            match self
+                .encrypt_key_for(context, &consumer_forwarder, &key)
+                .await
+            {
+                Ok(Some(encrypted_key)) => encrypted_keys.push(encrypted_key),
+                Ok(None) => debug!("{consumer_forwarder} is not authorized to consume"),
+                Err(error) => {
+                    warn!("cannot encrypt data key for {consumer_forwarder}: {error}");
+                    self.inner
+                        .lock()
+                        .await
+                        .consumer_encryptor_map
+                        .remove(&consumer_forwarder);
+                    return Err(error);
+                }
+            }
+        }
+
+        if encrypted_keys.is_empty() {
+            return Err(Error::new(
+                Origin::Channel,
+                Kind::NotFound,
+                format!("no authorized consumer for {topic_name}_{partition_id}"),
+            ));
+        }
+
+        Ok(KafkaDataKey {
+            key,
+            encrypted_keys,
         })
     }
 
+    async fn decrypt_data_key(
+        &self,
+        context: &mut Context,
+        encrypted_keys: &[KafkaEncryptedKey],
+    ) -> Result<Vec<u8>> {
+        // the lock is held until the key is stored, since it can be decrypted only once
+        let mut inner = self.inner.lock().await;
+
+        if let Some(key) = encrypted_keys
+            .iter()
+            .find_map(|encrypted_key| inner.data_keys.get(&encrypted_key.content))
+        {
+            return Ok(key);
+        }
+        if encrypted_keys
+            .iter()
+            .any(|encrypted_key| inner.data_keys.was_evicted(&encrypted_key.content))
+        {
+            return Err(Error::new(
+                Origin::Channel,
+                Kind::NotFound,
+                format!(
+                    "the data key was evicted from the cache of the {MAX_DATA_KEYS} most recently \
+                     used data keys and can't be decrypted again by this consumer"
+                ),
+            ));
+        }
+
+        // only the keys encrypted for this consumer have a local secure channel, but
+        // several consumers can share a node, and each key can be decrypted only once
+        for encrypted_key in encrypted_keys {
+            if self
+                .secure_channels
+                .secure_channel_registry()
+                .get_channel_by_decryptor_address(&encrypted_key.consumer_decryptor_address)
+                .is_none()
+            {
+                continue;
+            }
+
+            let secure_channel_entry = self
+                .get_secure_channel_for(&encrypted_key.consumer_decryptor_address)
+                .await?;
+
+            let decrypt_response = context
+                .send_and_receive(
+                    route![secure_channel_entry.decryptor_api_address().clone()],
+                    DecryptionRequest(encrypted_key.content.clone()),
+                )
+                .await?;
+
+            let key = match decrypt_response {
+                DecryptionResponse::Ok(p) => p,
+                DecryptionResponse::Err(cause) => {
+                    warn!("cannot decrypt kafka data key: {cause}");
+                    continue;
+                }
+            };
+
+            inner
+                .data_keys
+                .insert(encrypted_key.content.clone(), key.clone());
+
+            return Ok(key);
+        }
+
+        Err(Error::new(
+            Origin::Channel,
+            Kind::NotFound,
+            "the data key was not encrypted for this consumer",
+        ))
+    }
+
     async fn decrypt_content_for(
         &self,
         context: &mut Context,
@@ -509,9 +918,9 @@ impl<F: ForwarderCreator> KafkaSecureChannelController for KafkaSecureChannelCon
             if inner.topic_forwarder_set.contains(&topic_key) {
                 continue;
             }
-            let alias = format!("{topic_name}_{partition}");
-            inner
-                .forwarder_creator
+            // each consumer has its own forwarder, listed by the producers
+            let alias = format!("{topic_name}_{partition}.{}", self.consumer_id);
+            self.forwarder_creator
                 .create_forwarder(context, alias)
                 .await?;
             inner.topic_forwarder_set.insert(topic_key);
@@ -520,3 +929,26 @@ impl<F: ForwarderCreator> KafkaSecureChannelController for KafkaSecureChannelCon
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn data_key_cache_evicts_the_least_recently_used_keys() {
+        let mut cache = DataKeyCache::default();
+        for i in 0..MAX_DATA_KEYS as u32 {
+            cache.insert(i.to_be_bytes().to_vec(), vec![1]);
+        }
+        // the first key is used again, the second one becomes the least recently used
+        assert_eq!(cache.get(&0u32.to_be_bytes()), Some(vec![1]));
+        cache.insert(b"new".to_vec(), vec![2]);
+
+        assert_eq!(cache.keys.len(), MAX_DATA_KEYS);
+        assert_eq!(cache.get(&0u32.to_be_bytes()), Some(vec![1]));
+        assert_eq!(cache.get(b"new"), Some(vec![2]));
+        assert_eq!(cache.get(&1u32.to_be_bytes()), None);
+        assert!(cache.was_evicted(&1u32.to_be_bytes()));
+        assert!(!cache.was_evicted(&2u32.to_be_bytes()));
+    }
+}
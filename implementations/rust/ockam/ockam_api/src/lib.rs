@@ -29,9 +29,11 @@
 //! │  │  ├─ default_vault -> ...
 //! │  │  ├─ policies-storage.lmdb
 //! │  │  ├─ policies-storage.lmdb-lock
+//! │  │  ├─ relay_buffers
 //! │  │  ├─ setup.json
 //! │  │  ├─ stderr.log
 //! │  │  ├─ stdout.log
+//! │  │  ├─ streams
 //! │  │  └─ version.log
 //! │  ├─ node2
 //! │  └─ ...
@@ -54,7 +56,9 @@
 //!
 //! Each file stored under the `credentials` directory contains the credential for a given identity.
 //! Those files are created with the `ockam credential store` command. They are then read during the creation of
-//! a secure channel to send the credentials to the other party
+//! a secure channel to send the credentials to the other party.
+//! The credentials retrieved by a node from its project authority are stored in the `credentials/retrieved`
+//! sub-directory, so that they can be reused when the node restarts. They are renewed before they expire
 //!
 //! # `defaults`
 //!
@@ -135,9 +139,12 @@ pub mod hop;
 pub mod identity;
 pub mod kafka;
 pub mod nodes;
+pub mod oidc;
 pub mod okta;
 pub mod port_range;
 pub mod rpc_proxy_service;
+pub mod signer;
+pub mod stream;
 pub mod uppercase;
 pub mod verifier;
 
@@ -168,10 +175,16 @@ impl DefaultAddress {
     pub const ENROLLMENT_TOKEN_ACCEPTOR: &'static str = "enrollment_token_acceptor";
     pub const VERIFIER: &'static str = "verifier";
     pub const OKTA_IDENTITY_PROVIDER: &'static str = "okta";
+    pub const OIDC_IDENTITY_PROVIDER: &'static str = "oidc";
     pub const KAFKA_OUTLET: &'static str = "kafka_outlet";
     pub const KAFKA_CONSUMER: &'static str = "kafka_consumer";
     pub const KAFKA_PRODUCER: &'static str = "kafka_producer";
     pub const RPC_PROXY: &'static str = "rpc_proxy_service";
+    pub const STREAM_SERVICE: &'static str = "stream";
+    pub const STREAM_INDEX_SERVICE: &'static str = "stream_index";
+    pub const SIGNER: &'static str = "signer";
+    pub const DIRECT_PATH_SERVICE: &'static str = "direct_path";
+    pub const RENDEZVOUS_SERVICE: &'static str = "rendezvous";
 
     pub fn is_valid(name: &str) -> bool {
         matches!(
@@ -190,9 +203,15 @@ impl DefaultAddress {
                 | Self::ENROLLMENT_TOKEN_ACCEPTOR
                 | Self::VERIFIER
                 | Self::OKTA_IDENTITY_PROVIDER
+                | Self::OIDC_IDENTITY_PROVIDER
                 | Self::KAFKA_CONSUMER
                 | Self::KAFKA_PRODUCER
                 | Self::RPC_PROXY
+                | Self::STREAM_SERVICE
+                | Self::STREAM_INDEX_SERVICE
+                | Self::SIGNER
+                | Self::DIRECT_PATH_SERVICE
+                | Self::RENDEZVOUS_SERVICE
         )
     }
 }
@@ -201,6 +220,9 @@ pub mod actions {
     use ockam_abac::Action;
 
     pub const HANDLE_MESSAGE: Action = Action::assert_inline("handle_message");
+    pub const CONNECT: Action = Action::assert_inline("connect");
+    pub const QUERY: Action = Action::assert_inline("query");
+    pub const LIST: Action = Action::assert_inline("list");
 }
 
 pub mod resources {
@@ -300,8 +322,20 @@ mod test {
         assert!(DefaultAddress::is_valid(
             DefaultAddress::OKTA_IDENTITY_PROVIDER
         ));
+        assert!(DefaultAddress::is_valid(
+            DefaultAddress::OIDC_IDENTITY_PROVIDER
+        ));
         assert!(DefaultAddress::is_valid(DefaultAddress::KAFKA_CONSUMER));
         assert!(DefaultAddress::is_valid(DefaultAddress::KAFKA_PRODUCER));
         assert!(DefaultAddress::is_valid(DefaultAddress::RPC_PROXY));
+        assert!(DefaultAddress::is_valid(DefaultAddress::STREAM_SERVICE));
+        assert!(DefaultAddress::is_valid(
+            DefaultAddress::STREAM_INDEX_SERVICE
+        ));
+        assert!(DefaultAddress::is_valid(DefaultAddress::SIGNER));
+        assert!(DefaultAddress::is_valid(
+            DefaultAddress::DIRECT_PATH_SERVICE
+        ));
+        assert!(DefaultAddress::is_valid(DefaultAddress::RENDEZVOUS_SERVICE));
     }
 }
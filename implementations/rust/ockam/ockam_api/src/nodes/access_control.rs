@@ -0,0 +1,286 @@
+use crate::nodes::NODEMANAGER_ADDR;
+use ockam::identity::IdentitiesRepository;
+use ockam::identity::{
+    IdentityIdAccessControl, IdentityIdentifier, IdentitySecureChannelLocalInfo,
+};
+use ockam_abac::expr::{int, str};
+use ockam_abac::{Action, Env, PolicyAccessControl, PolicyStorage, Resource};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, Address, AllowAll, AllowOnwardAddress, Decodable, IncomingAccessControl,
+    LocalInfo, LocalMessage, RelayMessage, Result,
+};
+use ockam_node::{Context, MessageReceiveOptions};
+use ockam_transport_tcp::PortalMessage;
+use ockam_transport_uds::UdsLocalInfo;
+use std::fmt::{self, Debug, Formatter};
+
+/// LocalInfo unique Identifier of the requests sent to the node manager by the workers of its node
+pub const LOCAL_REQUEST_IDENTIFIER: &str = "LOCAL_REQUEST_IDENTIFIER";
+
+/// LocalInfo marking a request sent to the node manager by a worker of the same node.
+///
+/// A LocalInfo can't be set by another node, so the mark can't be forged by a remote peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalRequestInfo;
+
+impl LocalRequestInfo {
+    /// Encode `LocalRequestInfo` to general `LocalInfo`
+    pub fn to_local_info(&self) -> LocalInfo {
+        LocalInfo::new(LOCAL_REQUEST_IDENTIFIER.into(), vec![])
+    }
+
+    /// Find `LocalRequestInfo` in the list of general `LocalInfo` of that `LocalMessage`
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        local_msg
+            .local_info()
+            .iter()
+            .find(|x| x.type_identifier() == LOCAL_REQUEST_IDENTIFIER)
+            .map(|_| LocalRequestInfo)
+    }
+}
+
+/// Send a request to the node manager of this node and wait for its response.
+///
+/// The request is marked with a [`LocalRequestInfo`], which the node manager requires from
+/// the requests which are neither sent by an admin nor received on the socket of the node.
+pub async fn send_to_node_manager(ctx: &Context, request: Vec<u8>) -> Result<Vec<u8>> {
+    send_to_node_manager_with_options(ctx, request, MessageReceiveOptions::new()).await
+}
+
+/// Send a request to the node manager of this node and wait for its response,
+/// with the given receive options
+pub async fn send_to_node_manager_with_options(
+    ctx: &Context,
+    request: Vec<u8>,
+    options: MessageReceiveOptions,
+) -> Result<Vec<u8>> {
+    let mut child_ctx = ctx
+        .new_detached(
+            Address::random_tagged("NodeManager.request.detached"),
+            AllowAll,
+            AllowOnwardAddress(NODEMANAGER_ADDR.into()),
+        )
+        .await?;
+    child_ctx
+        .send_with_local_info(
+            NODEMANAGER_ADDR,
+            request,
+            vec![LocalRequestInfo.to_local_info()],
+        )
+        .await?;
+    Ok(child_ctx.receive_extended::<Vec<u8>>(options).await?.body())
+}
+
+/// Access control of the node manager.
+///
+/// Requests are denied unless they are:
+///  - received through a secure channel created by one of the admins of the node
+///  - received on the Unix domain socket of the node, which only its owner can use
+///  - sent by a worker of the node itself, with a [`LocalRequestInfo`]
+#[derive(Clone, Debug)]
+pub struct NodeManagerAccessControl {
+    admins: IdentityIdAccessControl,
+}
+
+impl NodeManagerAccessControl {
+    pub fn new(admins: Vec<IdentityIdentifier>) -> Self {
+        Self {
+            admins: IdentityIdAccessControl::new(admins),
+        }
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for NodeManagerAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let local_msg = relay_msg.local_message();
+        if IdentitySecureChannelLocalInfo::find_info(local_msg).is_ok() {
+            self.admins.is_authorized(relay_msg).await
+        } else if UdsLocalInfo::find_info(local_msg).is_some()
+            || LocalRequestInfo::find_info(local_msg).is_some()
+        {
+            ockam_core::allow()
+        } else {
+            ockam_core::deny()
+        }
+    }
+}
+
+/// Access control of the connection requests received by a dynamic outlet.
+///
+/// The destination requested by a SOCKS5 inlet is added to the environment of the policy
+/// as `resource.destination`, `resource.destination.host` and `resource.destination.port`,
+/// so that a policy can restrict which destinations a subject can connect to.
+/// The host is lowercased and stripped of its trailing dot, so that a policy can't be
+/// bypassed by writing the same host differently.
+/// Any message which is not a connection request is denied.
+pub struct DestinationAccessControl {
+    policies: Arc<dyn PolicyStorage>,
+    repository: Arc<dyn IdentitiesRepository>,
+    resource: Resource,
+    action: Action,
+    environment: Env,
+}
+
+impl Debug for DestinationAccessControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DestinationAccessControl")
+            .field("resource", &self.resource)
+            .field("action", &self.action)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+impl DestinationAccessControl {
+    pub fn new(
+        policies: Arc<dyn PolicyStorage>,
+        repository: Arc<dyn IdentitiesRepository>,
+        resource: Resource,
+        action: Action,
+        environment: Env,
+    ) -> Self {
+        Self {
+            policies,
+            repository,
+            resource,
+            action,
+            environment,
+        }
+    }
+}
+
+#[async_trait]
+impl IncomingAccessControl for DestinationAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let payload = &relay_msg.local_message().transport().payload;
+        let destination = match PortalMessage::decode(payload) {
+            Ok(PortalMessage::ConnectTo(destination)) => destination,
+            _ => return ockam_core::deny(),
+        };
+        let (host, port) = match destination
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        {
+            Some((host, port)) => (host.trim_start_matches('[').trim_end_matches(']'), port),
+            None => return ockam_core::deny(),
+        };
+        let host = host.trim_end_matches('.').to_lowercase();
+        let destination = if host.contains(':') {
+            format!("[{host}]:{port}")
+        } else {
+            format!("{host}:{port}")
+        };
+
+        let mut environment = self.environment.clone();
+        environment.put("resource.destination", str(destination));
+        environment.put("resource.destination.host", str(host));
+        environment.put("resource.destination.port", int(port));
+
+        PolicyAccessControl::new(
+            self.policies.clone(),
+            self.repository.clone(),
+            self.resource.clone(),
+            self.action.clone(),
+            environment,
+        )
+        .is_authorized(relay_msg)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::IdentitiesStorage;
+    use ockam_core::{route, Encodable, TransportMessage};
+
+    #[tokio::test]
+    async fn test_node_manager_requests_are_denied_by_default() -> Result<()> {
+        let ac = NodeManagerAccessControl::new(vec![]);
+
+        let request = |local_info: Vec<LocalInfo>| {
+            let onward: Address = NODEMANAGER_ADDR.into();
+            let msg = LocalMessage::new(
+                TransportMessage::v1(onward.clone(), route!["client"], vec![]),
+                local_info,
+            );
+            RelayMessage::new("client".into(), onward, msg)
+        };
+
+        assert!(!ac.is_authorized(&request(vec![])).await?);
+        assert!(
+            ac.is_authorized(&request(vec![LocalRequestInfo.to_local_info()]))
+                .await?
+        );
+        assert!(
+            ac.is_authorized(&request(vec![UdsLocalInfo.to_local_info()]))
+                .await?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_destination_policy() -> Result<()> {
+        let policies = Arc::new(ockam_abac::mem::Memory::new());
+        let resource = Resource::new("socks5_outlet");
+        let action = Action::new("handle_message");
+        let ac = DestinationAccessControl::new(
+            policies.clone(),
+            IdentitiesStorage::create(),
+            resource.clone(),
+            action.clone(),
+            Env::new(),
+        );
+        policies
+            .set_policy(
+                &resource,
+                &action,
+                &ockam_abac::parse(
+                    r#"(and (= resource.destination.host "db.internal")
+                            (> resource.destination.port 5000)
+                            (< resource.destination.port 6000))"#,
+                )?
+                .unwrap(),
+            )
+            .await?;
+
+        let identifier = IdentityIdentifier::try_from(
+            "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a",
+        )?;
+        let request = |message: PortalMessage| -> Result<RelayMessage> {
+            let onward: Address = "outlet".into();
+            let msg = LocalMessage::new(
+                TransportMessage::v1(onward.clone(), route!["inlet"], message.encode()?),
+                IdentitySecureChannelLocalInfo::mark(vec![], identifier.clone())?,
+            );
+            Ok(RelayMessage::new("inlet".into(), onward, msg))
+        };
+        let connect_to = |destination: &str| request(PortalMessage::ConnectTo(destination.into()));
+
+        assert!(ac.is_authorized(&connect_to("db.internal:5432")?).await?);
+        assert!(!ac.is_authorized(&connect_to("db.internal:22")?).await?);
+        assert!(!ac.is_authorized(&connect_to("web.internal:5432")?).await?);
+        assert!(!ac.is_authorized(&connect_to("db.internal")?).await?);
+        assert!(!ac.is_authorized(&request(PortalMessage::Ping)?).await?);
+
+        // The host is compared in lowercase, without its trailing dot
+        assert!(ac.is_authorized(&connect_to("DB.Internal.:5432")?).await?);
+        assert!(!ac.is_authorized(&connect_to("WEB.internal:5432")?).await?);
+
+        // The destination is also available as a whole
+        policies
+            .set_policy(
+                &resource,
+                &action,
+                &ockam_abac::parse(r#"(= resource.destination "[fd00::1]:22")"#)?.unwrap(),
+            )
+            .await?;
+        assert!(ac.is_authorized(&connect_to("[fd00::1]:22")?).await?);
+        assert!(ac.is_authorized(&connect_to("[FD00::1]:22")?).await?);
+        assert!(!ac.is_authorized(&connect_to("db.internal:5432")?).await?);
+        Ok(())
+    }
+}
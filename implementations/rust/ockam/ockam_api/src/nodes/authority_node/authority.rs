@@ -1,28 +1,34 @@
 use std::path::Path;
+use std::str::FromStr;
 
 use tracing::info;
 
 use ockam::identity::{
     Identities, IdentitiesRepository, IdentitiesStorage, IdentitiesVault, IdentityAttributesReader,
-    IdentityAttributesWriter, SecureChannelListenerOptions, SecureChannels, TrustEveryonePolicy,
+    IdentityAttributesWriter, SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
+    Storage, TrustEveryonePolicy, TrustIdentifierPolicy,
 };
 use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{AbacAccessControl, Env};
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::{Error, Result, Worker};
+use ockam_core::{route, Error, Result, Worker};
 use ockam_identity::{CredentialsIssuer, IdentityIdentifier, LmdbStorage};
+use ockam_multiaddr::MultiAddr;
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_tcp::{TcpListenerOptions, TcpTransport};
 use ockam_vault::Vault;
 
 use crate::authenticator::direct::EnrollmentTokenAuthenticator;
+use crate::authenticator::expiry::{AttributesSweeper, DEFAULT_SWEEP_INTERVAL};
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::echoer::Echoer;
 use crate::nodes::authority_node::authority::EnrollerCheck::{AnyMember, EnrollerOnly};
 use crate::nodes::authority_node::Configuration;
-use crate::{actions, DefaultAddress};
+use crate::oidc::OidcProvider;
+use crate::signer::RemoteSecurityModule;
+use crate::{actions, multiaddr_to_route, DefaultAddress};
 
 /// This struct represents an Authority, which is an
 /// Identity which other identities trust to authenticate attributes
@@ -31,9 +37,11 @@ use crate::{actions, DefaultAddress};
 //   - a credential issuer
 //   - an enrollment token issuer
 //   - an enrollment token acceptor
+//   - a sweeper for expired member attributes
 pub struct Authority {
     identifier: IdentityIdentifier,
     secure_channels: Arc<SecureChannels>,
+    storage: Arc<dyn Storage>,
 }
 
 /// Public functions to:
@@ -51,7 +59,8 @@ impl Authority {
     pub async fn create(configuration: &Configuration) -> Result<Authority> {
         debug!(?configuration, "creating the authority");
         let vault = Self::create_secure_channels_vault(configuration).await?;
-        let repository = Self::create_identities_repository(configuration).await?;
+        let storage = Self::create_storage(configuration).await?;
+        let repository = Self::create_identities_repository(storage.clone(), configuration);
         let secure_channels = SecureChannels::builder()
             .with_identities_vault(vault)
             .with_identities_repository(repository)
@@ -63,6 +72,62 @@ impl Authority {
         Ok(Authority {
             identifier,
             secure_channels,
+            storage,
+        })
+    }
+
+    /// Sign with the keys of a remote signer node when one is configured.
+    ///
+    /// A secure channel is created to the signer node with the configured client identity,
+    /// whose keys are in the local vault. Then the credentials and the secure channels of
+    /// the authority identity are signed through that channel. The secure channel keys
+    /// are ephemeral and still created in the local vault
+    pub async fn with_remote_signer(
+        self,
+        ctx: &Context,
+        configuration: &Configuration,
+    ) -> Result<Authority> {
+        let signer = match &configuration.remote_signer {
+            Some(signer) => signer,
+            None => return Ok(self),
+        };
+        let address = MultiAddr::from_str(&signer.address)
+            .map_err(|e| Error::new(Origin::Node, Kind::Invalid, e))?;
+        let tcp = TcpTransport::create(ctx).await?;
+        let listener_route = multiaddr_to_route(&address, &tcp)
+            .await
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Node,
+                    Kind::Invalid,
+                    format!("invalid signer address {address}"),
+                )
+            })?
+            .route;
+        let options = SecureChannelOptions::new()
+            .with_trust_policy(TrustIdentifierPolicy::new(signer.identifier.clone()));
+        let channel = self
+            .secure_channels
+            .create_secure_channel(ctx, &signer.client_identifier, listener_route, options)
+            .await?;
+        let security_module =
+            RemoteSecurityModule::create(route![channel, signer.service.clone()], ctx).await?;
+        info!(
+            "signing with the signer at {address}/service/{}",
+            signer.service
+        );
+
+        let secure_channels = SecureChannels::builder()
+            .with_identities_vault(
+                Vault::builder()
+                    .with_security_module(security_module)
+                    .build(),
+            )
+            .with_identities_repository(self.secure_channels.identities().repository())
+            .build();
+        Ok(Authority {
+            secure_channels,
+            ..self
         })
     }
 
@@ -144,6 +209,7 @@ impl Authority {
         let (issuer, acceptor) = EnrollmentTokenAuthenticator::new_worker_pair(
             configuration.trust_context_identifier(),
             self.attributes_writer(),
+            self.storage.clone(),
         );
 
         // start an enrollment token issuer with an abac policy checking that
@@ -190,7 +256,8 @@ impl Authority {
             self.identifier(),
             configuration.trust_context_identifier(),
         )
-        .await?;
+        .await?
+        .with_disclosable_attributes(&configuration.disclosable_attributes());
 
         let address = DefaultAddress::CREDENTIAL_ISSUER.to_string();
         ctx.flow_controls()
@@ -203,6 +270,14 @@ impl Authority {
         Ok(())
     }
 
+    /// Start a background worker purging the attributes of expired members
+    pub async fn start_attributes_sweeper(&self, ctx: &Context) -> Result<()> {
+        let address =
+            AttributesSweeper::start(ctx, self.attributes_writer(), DEFAULT_SWEEP_INTERVAL).await?;
+        info!("started an attributes sweeper at '{address}'");
+        Ok(())
+    }
+
     /// Start the Okta service to retrieve attributes authenticated by Okta
     pub async fn start_okta(
         &self,
@@ -227,6 +302,38 @@ impl Authority {
         Ok(())
     }
 
+    /// Start the OpenID Connect service to retrieve attributes from ID tokens
+    pub async fn start_oidc(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        if let Some(oidc) = configuration.clone().oidc {
+            let provider = OidcProvider::new(
+                &oidc.issuer_url,
+                &oidc.client_id,
+                oidc.certificate.as_deref(),
+            )?;
+            let oidc_worker = crate::oidc::Server::new(
+                self.attributes_writer(),
+                configuration.project_identifier(),
+                provider,
+                oidc.claims,
+            );
+
+            ctx.flow_controls()
+                .add_consumer(oidc.address.clone(), secure_channel_flow_control_id);
+
+            ctx.start_worker(oidc.address.clone(), oidc_worker).await?;
+            info!(
+                "started an OpenID Connect authenticator at '{}'",
+                oidc.address
+            );
+        }
+        Ok(())
+    }
+
     /// Start an echo service
     pub async fn start_echo_service(
         &self,
@@ -274,15 +381,20 @@ impl Authority {
         Ok(vault)
     }
 
-    /// Create an authenticated storage backed by a Lmdb database
-    async fn create_identities_repository(
-        configuration: &Configuration,
-    ) -> Result<Arc<dyn IdentitiesRepository>> {
+    /// Create a storage backed by a Lmdb database, for identity attributes and enrollment tokens
+    async fn create_storage(configuration: &Configuration) -> Result<Arc<dyn Storage>> {
         let storage_path = &configuration.storage_path;
         Self::create_ockam_directory_if_necessary(storage_path)?;
-        let storage = Arc::new(LmdbStorage::new(&storage_path).await?);
+        Ok(Arc::new(LmdbStorage::new(&storage_path).await?))
+    }
+
+    /// Create an authenticated storage backed by the authority storage
+    fn create_identities_repository(
+        storage: Arc<dyn Storage>,
+        configuration: &Configuration,
+    ) -> Arc<dyn IdentitiesRepository> {
         let repository = Arc::new(IdentitiesStorage::new(storage));
-        Ok(Self::bootstrap_repository(repository, configuration))
+        Self::bootstrap_repository(repository, configuration)
     }
 
     /// Create a directory to save storage files if they haven't been  created before
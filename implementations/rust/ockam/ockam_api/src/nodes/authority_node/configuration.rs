@@ -1,4 +1,5 @@
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
+use crate::oidc::ClaimMapping;
 use crate::DefaultAddress;
 use ockam::identity::credential::Timestamp;
 use ockam::identity::{AttributesEntry, IdentityIdentifier};
@@ -49,6 +50,15 @@ pub struct Configuration {
 
     /// optional configuration for the okta service
     pub okta: Option<OktaConfiguration>,
+
+    /// optional configuration for the OpenID Connect service
+    pub oidc: Option<OidcConfiguration>,
+
+    /// attributes which are selectively disclosable in the issued credentials
+    pub disclosable_attributes: Vec<String>,
+
+    /// optional configuration of a signer node keeping the keys of the authority identity
+    pub remote_signer: Option<RemoteSignerConfiguration>,
 }
 
 /// Local and private functions for the authority configuration
@@ -68,6 +78,14 @@ impl Configuration {
         self.trust_context_identifier.clone()
     }
 
+    /// Return the attributes which are selectively disclosable in the issued credentials
+    pub(crate) fn disclosable_attributes(&self) -> Vec<&str> {
+        self.disclosable_attributes
+            .iter()
+            .map(|n| n.as_str())
+            .collect()
+    }
+
     /// Return the address for the TCP listener
     pub(crate) fn tcp_listener_address(&self) -> String {
         self.tcp_listener_address.clone()
@@ -116,6 +134,44 @@ impl OktaConfiguration {
     }
 }
 
+/// Configuration for the OpenID Connect service
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct OidcConfiguration {
+    pub address: String,
+
+    /// URL of the OpenID Connect provider, used to retrieve its discovery document
+    pub issuer_url: String,
+
+    /// Client identifier registered with the provider, expected as the ID tokens audience
+    pub client_id: String,
+
+    /// optional pem certificate used to access the provider
+    pub certificate: Option<String>,
+
+    /// list of claims which are stored as attributes
+    pub claims: Vec<ClaimMapping>,
+}
+
+/// Configuration for signing with the keys of a remote signer node, so that the secret
+/// keys of the authority identity never need to be stored on the authority node
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfiguration {
+    /// address of the secure channel listener of the signer node,
+    /// for example /dnsaddr/signer.example.com/tcp/4000/service/api
+    pub address: String,
+
+    /// name of the signer service on the signer node
+    pub service: String,
+
+    /// identifier of the signer node, which is the only identity trusted
+    /// when creating the secure channel
+    pub identifier: IdentityIdentifier,
+
+    /// identity used to authenticate to the signer node. Its keys are stored in the
+    /// vault of the authority node
+    pub client_identifier: IdentityIdentifier,
+}
+
 /// This struct represents an identity that the Authority accepts
 /// as having all its attributes fully authenticated
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
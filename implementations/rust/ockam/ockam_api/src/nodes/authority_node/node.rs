@@ -10,7 +10,10 @@ pub async fn start_node(ctx: &Context, configuration: &Configuration) -> Result<
     // or retrieve it from disk if the node has already been started before
     // The trusted identities in the configuration are used to pre-populate an attribute storage
     // containing those identities and their attributes
-    let authority = Authority::create(configuration).await?;
+    let authority = Authority::create(configuration)
+        .await?
+        .with_remote_signer(ctx, configuration)
+        .await?;
 
     debug!("starting services");
     // start a secure channel listener (this also starts a TCP transport)
@@ -35,12 +38,22 @@ pub async fn start_node(ctx: &Context, configuration: &Configuration) -> Result<
         .await?;
     debug!("credential issuer started");
 
+    // purge the attributes of the members which have expired
+    authority.start_attributes_sweeper(ctx).await?;
+    debug!("attributes sweeper started");
+
     // start the Okta service (if the optional configuration has been provided)
     authority
         .start_okta(ctx, &secure_channel_flow_control_id, configuration)
         .await?;
     debug!("okta service started");
 
+    // start the OpenID Connect service (if the optional configuration has been provided)
+    authority
+        .start_oidc(ctx, &secure_channel_flow_control_id, configuration)
+        .await?;
+    debug!("oidc service started");
+
     // start an echo service so that the node can be queried as healthy
     authority
         .start_echo_service(ctx, &secure_channel_flow_control_id)
@@ -0,0 +1,518 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{host_match, host_port};
+use crate::nodes::NodeManager;
+use crate::DefaultAddress;
+
+use minicbor::{Decode, Decoder, Encode};
+use ockam::compat::tokio::sync::RwLock;
+use ockam_core::api::{self, Error, Id, Method, Request, Response};
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{
+    route, Address, AllowSourceAddress, AsyncTryClone, DenyAll, Result, Route, Routed, Worker,
+    LOCAL,
+};
+use ockam_identity::{
+    IdentityIdentifier, IdentitySecureChannelLocalInfo, SecureChannelOptions, SecureChannels,
+};
+use ockam_multiaddr::proto::Udp;
+use ockam_multiaddr::{MultiAddr, Protocol};
+use ockam_node::{tokio, Context, DelayedEvent, RpcClient, WorkerBuilder};
+use ockam_transport_udp::{UdpHolePuncher, UdpHolePuncherOptions, UDP};
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Interval between two checks of the hole to the other node
+const CHECK_EVERY: Duration = Duration::from_secs(1);
+
+/// Timeout of the requests sent while setting up a direct path
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time after which the hole is considered closed, and the channel moved back
+/// to its relay, if nothing was received from the other puncher. Punchers
+/// ping each other every second while the hole is open
+const HOLE_OPEN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Addresses of the direct path workers started for the other nodes, by identity
+type DirectPaths = Arc<Mutex<HashMap<IdentityIdentifier, Address>>>;
+
+/// Request sent through a secure channel to the direct path service of the other
+/// node, asking it to punch a hole to our puncher
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DirectPathRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2650781>,
+    #[n(1)] puncher_name: String,
+}
+
+impl DirectPathRequest {
+    pub fn new(puncher_name: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            puncher_name: puncher_name.into(),
+        }
+    }
+
+    pub fn puncher_name(&self) -> &str {
+        &self.puncher_name
+    }
+}
+
+/// Response of the direct path service to a [`DirectPathRequest`]
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DirectPathResponse {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8190372>,
+    #[n(1)] puncher_name: String,
+    /// Address of the node running the Rendezvous service used by both punchers
+    #[n(2)] rendezvous: String,
+}
+
+impl DirectPathResponse {
+    pub fn new(puncher_name: impl Into<String>, rendezvous: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            puncher_name: puncher_name.into(),
+            rendezvous: rendezvous.into(),
+        }
+    }
+
+    pub fn puncher_name(&self) -> &str {
+        &self.puncher_name
+    }
+
+    /// Address of the node running the Rendezvous service used by both punchers
+    pub fn rendezvous(&self) -> &str {
+        &self.rendezvous
+    }
+}
+
+/// Service accepting to move the secure channels created with this node to a
+/// direct UDP path.
+///
+/// Requests must be sent through the secure channel to move. The service punches
+/// a hole to the puncher of the other node, using the Rendezvous service of the
+/// node at the `rendezvous` address, and sends the messages of the channel
+/// through that hole while it is open.
+///
+/// A single direct path is kept for each identity: a new request replaces the
+/// direct path previously set up for the same identity.
+pub struct DirectPathService {
+    node_manager: Arc<RwLock<NodeManager>>,
+    rendezvous: MultiAddr,
+    direct_paths: DirectPaths,
+}
+
+#[ockam_core::worker]
+impl Worker for DirectPathService {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let r = self.on_request(c, &m).await?;
+        c.send(m.return_route(), r).await
+    }
+}
+
+impl DirectPathService {
+    pub(crate) fn new(
+        node_manager: Arc<RwLock<NodeManager>>,
+        rendezvous: MultiAddr,
+    ) -> Result<Self> {
+        // Fail early on addresses which can't be used by the punchers
+        rendezvous_peer(&rendezvous)?;
+        Ok(Self {
+            node_manager,
+            rendezvous,
+            direct_paths: Default::default(),
+        })
+    }
+
+    async fn on_request(&mut self, ctx: &Context, msg: &Routed<Vec<u8>>) -> Result<Vec<u8>> {
+        let mut dec = Decoder::new(msg.as_body());
+
+        let req: Request = match dec.decode() {
+            Ok(rq) => rq,
+            Err(e) => {
+                let err = Error::default().with_message(e.to_string());
+                return Ok(Response::bad_request(Id::default()).body(err).to_vec()?);
+            }
+        };
+
+        trace! {
+            target: "ockam_api::direct_path",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match req.method() {
+            Some(Method::Post) => match req.path_segments::<2>().as_slice() {
+                ["punchers"] => {
+                    let body: DirectPathRequest = dec.decode()?;
+                    self.start_puncher(ctx, &req, msg, body).await?
+                }
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            _ => api::invalid_method(&req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+
+    async fn start_puncher(
+        &self,
+        ctx: &Context,
+        req: &Request<'_>,
+        msg: &Routed<Vec<u8>>,
+        body: DirectPathRequest,
+    ) -> Result<Vec<u8>> {
+        // The request must come from the other side of the channel to move
+        let secure_channels = self.node_manager.read().await.secure_channels.clone();
+        let channel = msg.return_route().next().cloned()?;
+        let entry = secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(&channel);
+        let their_identity = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .map(|info| info.their_identity_id())
+            .ok();
+        let their_identity = match (entry, their_identity) {
+            (Some(entry), Some(their_identity)) if entry.their_id() == their_identity => {
+                their_identity
+            }
+            _ => {
+                let err = Error::new(req.path())
+                    .with_message("the request must be sent through the secure channel to move");
+                return Ok(Response::unauthorized(req.id()).body(err).to_vec()?);
+            }
+        };
+
+        // Replace the direct path previously set up for that identity, if any
+        let address = Address::random_tagged("DirectPathWorker");
+        let previous = self
+            .direct_paths
+            .lock()
+            .unwrap()
+            .insert(their_identity.clone(), address.clone());
+        if let Some(previous) = previous {
+            debug!(identity = %their_identity, "replacing a direct path");
+            let _ = ctx.stop_worker(previous).await;
+        }
+
+        let puncher_name = random_puncher_name();
+        let peer_puncher_name = body.puncher_name().to_string();
+        let node_manager = self.node_manager.clone();
+        let rendezvous = self.rendezvous.clone();
+        let mut ctx = ctx.async_try_clone().await?;
+        let this_puncher_name = puncher_name.clone();
+        let direct_paths = self.direct_paths.clone();
+        tokio::spawn(async move {
+            let started = DirectPathWorker::start(
+                &mut ctx,
+                node_manager,
+                address.clone(),
+                channel,
+                &rendezvous,
+                &this_puncher_name,
+                &peer_puncher_name,
+                Some((direct_paths.clone(), their_identity.clone())),
+            )
+            .await;
+            if let Err(e) = started {
+                warn!("failed to set up a direct path: {e}");
+                forget_direct_path(&direct_paths, &their_identity, &address);
+            }
+        });
+
+        Ok(Response::ok(req.id())
+            .body(DirectPathResponse::new(
+                puncher_name,
+                self.rendezvous.to_string(),
+            ))
+            .to_vec()?)
+    }
+}
+
+/// Ask the other side of a secure channel to punch a hole to this node, then
+/// move the channel to that hole while it is open.
+///
+/// The secure channel keeps its original route, usually through a relay, until
+/// the hole is open, and goes back to it whenever the hole closes.
+///
+/// The Rendezvous node is the one of the direct path service of this node, and
+/// the other node must use the same one. A trust context is required, so that the
+/// Rendezvous node is authenticated before the channel leaves its relay.
+pub(crate) async fn upgrade_to_direct_path(
+    mut ctx: Context,
+    node_manager: Arc<RwLock<NodeManager>>,
+    channel: Address,
+) -> Result<()> {
+    let rendezvous = {
+        let node_manager = node_manager.read().await;
+        node_manager.trust_context()?;
+        node_manager
+            .registry
+            .direct_path_services
+            .values()
+            .next()
+            .map(|info| info.rendezvous().clone())
+            .ok_or_else(|| {
+                ApiError::message("a direct path requires a direct path service on this node")
+            })?
+    };
+
+    let puncher_name = random_puncher_name();
+    let client = RpcClient::new(
+        route![channel.clone(), DefaultAddress::DIRECT_PATH_SERVICE],
+        &ctx,
+    )
+    .await?
+    .with_timeout(REQUEST_TIMEOUT);
+    let res: DirectPathResponse = client
+        .request(&Request::post("punchers").body(DirectPathRequest::new(&puncher_name)))
+        .await?;
+    let their_rendezvous = MultiAddr::from_str(res.rendezvous())
+        .map_err(|e| ApiError::message(format!("invalid rendezvous address: {e}")))?;
+    if their_rendezvous != rendezvous {
+        return Err(ApiError::message(format!(
+            "the other node uses the rendezvous {their_rendezvous} instead of {rendezvous}"
+        )));
+    }
+
+    DirectPathWorker::start(
+        &mut ctx,
+        node_manager,
+        Address::random_tagged("DirectPathWorker"),
+        channel,
+        &rendezvous,
+        &puncher_name,
+        res.puncher_name(),
+        None,
+    )
+    .await
+}
+
+/// Remove a direct path worker from the direct paths of a service, unless it
+/// was already replaced
+fn forget_direct_path(
+    direct_paths: &DirectPaths,
+    identity: &IdentityIdentifier,
+    address: &Address,
+) {
+    let mut direct_paths = direct_paths.lock().unwrap();
+    if direct_paths.get(identity) == Some(address) {
+        direct_paths.remove(identity);
+    }
+}
+
+/// Moves a secure channel to the hole punched to the other node while that hole
+/// is open, and back to the original route of the channel when it closes
+struct DirectPathWorker {
+    node_manager: Arc<RwLock<NodeManager>>,
+    secure_channels: Arc<SecureChannels>,
+    /// Encryptor address of the moved channel
+    channel: Address,
+    /// Original route of the channel, usually through a relay
+    relayed_route: Route,
+    /// Route of the channel through the puncher
+    direct_route: Route,
+    is_direct: bool,
+    puncher: UdpHolePuncher,
+    /// Secure channel used by the puncher to register with the Rendezvous service
+    rendezvous_channel: Address,
+    heartbeat: DelayedEvent<Vec<u8>>,
+    /// Direct paths of the service which started this worker, with the
+    /// identity of the other node
+    direct_paths: Option<(DirectPaths, IdentityIdentifier)>,
+}
+
+impl DirectPathWorker {
+    #[allow(clippy::too_many_arguments)]
+    async fn start(
+        ctx: &mut Context,
+        node_manager: Arc<RwLock<NodeManager>>,
+        address: Address,
+        channel: Address,
+        rendezvous: &MultiAddr,
+        puncher_name: &str,
+        peer_puncher_name: &str,
+        direct_paths: Option<(DirectPaths, IdentityIdentifier)>,
+    ) -> Result<()> {
+        let peer = rendezvous_peer(rendezvous)?;
+        let (secure_channels, identifier, trust_context) = {
+            let node_manager = node_manager.read().await;
+            node_manager.udp_transport(ctx).await?;
+            (
+                node_manager.secure_channels.clone(),
+                node_manager.identifier(),
+                node_manager.trust_context()?.clone(),
+            )
+        };
+        let entry = secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(&channel)
+            .ok_or_else(|| ApiError::message(format!("secure channel {channel} not found")))?;
+
+        // The puncher must be reachable by the Rendezvous service, from the
+        // socket used to punch the hole, hence the plain UDP routes
+        let options = SecureChannelOptions::new()
+            .with_timeout(REQUEST_TIMEOUT)
+            .with_trust_context(trust_context);
+        let rendezvous_channel = secure_channels
+            .create_secure_channel(
+                ctx,
+                &identifier,
+                route![(UDP, peer.clone()), DefaultAddress::SECURE_CHANNEL_LISTENER],
+                options,
+            )
+            .await?
+            .encryptor_address()
+            .clone();
+        let options = UdpHolePuncherOptions::new()
+            .with_secure_rendezvous_route(route![
+                rendezvous_channel.clone(),
+                DefaultAddress::RENDEZVOUS_SERVICE
+            ])
+            .with_hole_open_timeout(HOLE_OPEN_TIMEOUT);
+        let puncher = match UdpHolePuncher::create_with_options(
+            ctx,
+            puncher_name,
+            peer_puncher_name,
+            route![(UDP, peer), DefaultAddress::RENDEZVOUS_SERVICE],
+            options,
+        )
+        .await
+        {
+            Ok(puncher) => puncher,
+            Err(e) => {
+                let _ = secure_channels
+                    .stop_secure_channel(ctx, &rendezvous_channel)
+                    .await;
+                return Err(e);
+            }
+        };
+
+        let mut heartbeat = DelayedEvent::create(ctx, address.clone(), vec![]).await?;
+        heartbeat.schedule(CHECK_EVERY).await?;
+        let heartbeat_address = heartbeat.address();
+
+        let worker = Self {
+            node_manager,
+            secure_channels,
+            channel,
+            relayed_route: entry.remote_route(),
+            direct_route: route![puncher.address(), entry.their_decryptor_address()],
+            is_direct: false,
+            puncher,
+            rendezvous_channel,
+            heartbeat,
+            direct_paths,
+        };
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control(AllowSourceAddress(heartbeat_address))
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)
+            .await
+    }
+
+    /// Enable reliable delivery for the messages sent to the other puncher
+    async fn enable_reliable_delivery(&self, ctx: &Context) -> Result<()> {
+        let peer = self
+            .puncher
+            .peer_address()
+            .ok_or_else(|| ApiError::message("the address of the other puncher is unknown"))?;
+        let node_manager = self.node_manager.read().await;
+        node_manager
+            .udp_transport(ctx)
+            .await?
+            .enable_reliable_delivery(peer)
+            .await
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for DirectPathWorker {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.heartbeat.cancel();
+        let _ = self.puncher.stop().await;
+        let _ = self
+            .secure_channels
+            .stop_secure_channel(ctx, &self.rendezvous_channel)
+            .await;
+        if let Some((direct_paths, identity)) = &self.direct_paths {
+            forget_direct_path(direct_paths, identity, &ctx.address());
+        }
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, _m: Routed<Self::Message>) -> Result<()> {
+        let entry = match self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(&self.channel)
+        {
+            Some(entry) => entry,
+            None => {
+                debug!(channel = %self.channel, "secure channel closed, stopping its direct path");
+                return ctx.stop_worker(ctx.address()).await;
+            }
+        };
+
+        let hole_open = self.puncher.is_hole_open();
+        if hole_open != self.is_direct {
+            if hole_open {
+                // Lost datagrams are retransmitted once the channel goes through the hole
+                if let Err(e) = self.enable_reliable_delivery(ctx).await {
+                    warn!(channel = %self.channel, "keeping the relay: {e}");
+                    return self.heartbeat.schedule(CHECK_EVERY).await;
+                }
+                info!(channel = %self.channel, "moving secure channel to a direct path");
+                entry.update_remote_route(self.direct_route.clone())?;
+            } else {
+                info!(channel = %self.channel, "direct path closed, moving secure channel back to its relay");
+                entry.update_remote_route(self.relayed_route.clone())?;
+            }
+            self.is_direct = hole_open;
+        }
+
+        self.heartbeat.schedule(CHECK_EVERY).await
+    }
+}
+
+/// Return the `host:port` of a Rendezvous node given as `/ip4/<host>/udp/<port>`
+///
+/// That node is expected to run a Rendezvous service at
+/// [`DefaultAddress::RENDEZVOUS_SERVICE`], reachable through its secure channel
+/// listener at [`DefaultAddress::SECURE_CHANNEL_LISTENER`].
+fn rendezvous_peer(addr: &MultiAddr) -> Result<String> {
+    let invalid = || ApiError::message(format!("invalid rendezvous address: {addr}"));
+    if !addr.matches(0, &[host_match(), Udp::CODE.into()]) || addr.iter().count() != 2 {
+        return Err(invalid());
+    }
+    let mut protocols = addr.iter();
+    let host = protocols.next().ok_or_else(invalid)?;
+    let port = protocols
+        .next()
+        .and_then(|p| p.cast::<Udp>())
+        .ok_or_else(invalid)?;
+    host_port(&host, *port)
+}
+
+fn random_puncher_name() -> String {
+    Address::random(LOCAL).address().to_string()
+}
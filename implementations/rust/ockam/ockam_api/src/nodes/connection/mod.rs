@@ -1,20 +1,30 @@
+mod direct_path;
 mod plain_tcp;
+mod plain_udp;
 mod project;
 mod secure;
+mod unix;
+mod websocket;
 
+use crate::error::ApiError;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{async_trait, route, Address, CowStr, Route, LOCAL};
 use ockam_identity::IdentityIdentifier;
-use ockam_multiaddr::proto::Service;
-use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Service};
+use ockam_multiaddr::{Match, MultiAddr, ProtoValue, Protocol};
 use ockam_node::Context;
 use std::fmt::{Debug, Formatter};
+use std::net::{SocketAddrV4, SocketAddrV6};
 use std::time::Duration;
 
+pub(crate) use direct_path::{upgrade_to_direct_path, DirectPathService};
 pub(crate) use plain_tcp::PlainTcpInstantiator;
+pub(crate) use plain_udp::PlainUdpInstantiator;
 pub(crate) use project::ProjectInstantiator;
 pub(crate) use secure::SecureChannelInstantiator;
+pub(crate) use unix::UnixInstantiator;
+pub(crate) use websocket::WebSocketInstantiator;
 
 pub struct Connection<'a> {
     pub ctx: &'a Context,
@@ -24,6 +34,7 @@ pub struct Connection<'a> {
     pub authorized_identities: Option<Vec<IdentityIdentifier>>,
     pub timeout: Option<Duration>,
     pub add_default_consumers: bool,
+    pub direct_path: bool,
 }
 
 impl<'a> Connection<'a> {
@@ -36,6 +47,7 @@ impl<'a> Connection<'a> {
             authorized_identities: None,
             timeout: None,
             add_default_consumers: false,
+            direct_path: false,
         }
     }
 
@@ -62,6 +74,16 @@ impl<'a> Connection<'a> {
         self.add_default_consumers = true;
         self
     }
+
+    /// Once connected, try to move the last secure channel of the connection to a
+    /// hole punched between both nodes, see [`DirectPathService`].
+    ///
+    /// The connection is usable immediately through its original route, usually a
+    /// relay, and falls back to it whenever the hole closes.
+    pub fn with_direct_path(mut self) -> Self {
+        self.direct_path = true;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -312,3 +334,231 @@ impl ConnectionInstanceBuilder {
         Ok(new_multiaddr)
     }
 }
+
+/// Matches the host part of a transport [`MultiAddr`]
+pub(crate) fn host_match() -> Match {
+    Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE])
+}
+
+/// Returns the `host:port` string of a host protocol value and a port
+pub(crate) fn host_port(host: &ProtoValue, port: u16) -> Result<String, ockam_core::Error> {
+    let invalid = || ApiError::message(format!("invalid host protocol: {}", host.code()));
+    let peer = match host.code() {
+        Ip4::CODE => SocketAddrV4::new(*host.cast::<Ip4>().ok_or_else(invalid)?, port).to_string(),
+        Ip6::CODE => {
+            SocketAddrV6::new(*host.cast::<Ip6>().ok_or_else(invalid)?, port, 0, 0).to_string()
+        }
+        DnsAddr::CODE => format!("{}:{port}", &*host.cast::<DnsAddr>().ok_or_else(invalid)?),
+        _ => return Err(invalid()),
+    };
+    Ok(peer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::echoer::Echoer;
+    use crate::hop::Hop;
+    use crate::local_multiaddr_to_route;
+    use crate::nodes::registry::DirectPathServiceInfo;
+    use crate::nodes::NodeManager;
+    use crate::test_utils::start_manager_for_tests;
+    use crate::DefaultAddress;
+    use ockam_transport_udp::{UdpRendezvousService, UdpRendezvousServiceOptions};
+    use std::str::FromStr;
+
+    #[ockam_macros::test]
+    async fn connect_over_udp_websocket_and_unix_socket(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handle = start_manager_for_tests(context).await?;
+        context.start_worker("echoer", Echoer).await?;
+
+        let udp_port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let socket_dir = tempfile::tempdir().unwrap();
+        let socket_path = socket_dir.path().join("node.sock");
+        let ws_addr = {
+            let node_manager = handle.node_manager.read().await;
+            node_manager
+                .udp_transport(context)
+                .await?
+                .listen(format!("127.0.0.1:{udp_port}"))
+                .await?;
+            node_manager
+                .uds_transport(context)
+                .await?
+                .listen(socket_path.to_str().unwrap())
+                .await?;
+            node_manager
+                .ws_transport(context)
+                .await?
+                .listen("127.0.0.1:0")
+                .await?
+        };
+
+        let mut unix = MultiAddr::default();
+        unix.push_back(ockam_multiaddr::proto::Unix::new(
+            socket_path.to_str().unwrap(),
+        ))
+        .unwrap();
+        unix.push_back(Service::new("echoer")).unwrap();
+
+        for addr in [
+            MultiAddr::from_str(&format!("/ip4/127.0.0.1/udp/{udp_port}/service/echoer")).unwrap(),
+            MultiAddr::from_str(&format!(
+                "/ip4/127.0.0.1/tcp/{}/ws/service/echoer",
+                ws_addr.port()
+            ))
+            .unwrap(),
+            unix,
+        ] {
+            let connection_instance =
+                NodeManager::connect(handle.node_manager.clone(), Connection::new(context, &addr))
+                    .await?;
+
+            // the transport protocols are replaced by a local worker address
+            let normalized = &connection_instance.normalized_addr;
+            assert_eq!(normalized.iter().count(), 2, "{normalized}");
+            assert!(normalized.iter().all(|p| p.code() == Service::CODE));
+
+            let route = local_multiaddr_to_route(normalized).unwrap();
+            let reply: String = context.send_and_receive(route, "Hello".to_string()).await?;
+            assert_eq!(reply, "Hello");
+        }
+
+        let wss = MultiAddr::from_str("/ip4/127.0.0.1/tcp/443/wss/service/echoer").unwrap();
+        let res =
+            NodeManager::connect(handle.node_manager.clone(), Connection::new(context, &wss)).await;
+        assert!(res.is_err());
+
+        context.stop().await
+    }
+
+    #[ockam_macros::test(timeout = 60000)]
+    async fn relayed_secure_channel_moves_to_a_direct_path(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handle = start_manager_for_tests(context).await?;
+        // The default secure channel listener is started when the node manager worker initializes
+        let mut listener_flow_control_id = None;
+        for _ in 0..100 {
+            listener_flow_control_id = context
+                .flow_controls()
+                .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into());
+            if listener_flow_control_id.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let listener_flow_control_id = listener_flow_control_id.unwrap();
+
+        // The same node runs the Rendezvous service, the relay and both ends of the channel
+        let udp_port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        handle
+            .node_manager
+            .read()
+            .await
+            .udp_transport(context)
+            .await?
+            .listen(format!("127.0.0.1:{udp_port}"))
+            .await?;
+        let identities_repository = handle
+            .node_manager
+            .read()
+            .await
+            .secure_channels
+            .identities()
+            .repository();
+        UdpRendezvousService::start(
+            context,
+            DefaultAddress::RENDEZVOUS_SERVICE,
+            UdpRendezvousServiceOptions::new(identities_repository),
+        )
+        .await?;
+        context.flow_controls().add_consumer(
+            DefaultAddress::RENDEZVOUS_SERVICE,
+            &listener_flow_control_id,
+        );
+        let rendezvous = MultiAddr::from_str(&format!("/ip4/127.0.0.1/udp/{udp_port}")).unwrap();
+        context
+            .start_worker(
+                DefaultAddress::DIRECT_PATH_SERVICE,
+                DirectPathService::new(handle.node_manager.clone(), rendezvous.clone())?,
+            )
+            .await?;
+        handle
+            .node_manager
+            .write()
+            .await
+            .registry
+            .direct_path_services
+            .insert(
+                DefaultAddress::DIRECT_PATH_SERVICE.into(),
+                DirectPathServiceInfo::new(rendezvous),
+            );
+        context.flow_controls().add_consumer(
+            DefaultAddress::DIRECT_PATH_SERVICE,
+            &listener_flow_control_id,
+        );
+        context.start_worker("relay", Hop).await?;
+        context.start_worker("echoer", Echoer).await?;
+        context
+            .flow_controls()
+            .add_consumer("echoer", &listener_flow_control_id);
+
+        let addr = MultiAddr::from_str("/service/relay/secure/api/service/echoer").unwrap();
+        let connection_instance = NodeManager::connect(
+            handle.node_manager.clone(),
+            Connection::new(context, &addr).with_direct_path(),
+        )
+        .await?;
+        let channel = connection_instance.secure_channel_encryptors[0].clone();
+        connection_instance.add_consumer(context, &context.address());
+
+        // The channel is usable over the relay right away
+        let route = local_multiaddr_to_route(&connection_instance.normalized_addr).unwrap();
+        let reply: String = context
+            .send_and_receive(route.clone(), "Hello".to_string())
+            .await?;
+        assert_eq!(reply, "Hello");
+
+        // Both sides of the channel are moved to their puncher once the hole is open
+        let registry = handle.secure_channels.secure_channel_registry();
+        let initiator = registry.get_channel_by_encryptor_address(&channel).unwrap();
+        let relay = Address::from("relay");
+        let is_direct = |route: Route| route.next().map(|a| a != &relay).unwrap_or(false);
+        let mut moved = false;
+        for _ in 0..300 {
+            let responder = registry.get_channel_list().into_iter().find(|e| {
+                !e.is_initiator()
+                    && e.their_decryptor_address()
+                        == initiator.decryptor_messaging_address().clone()
+            });
+            if is_direct(initiator.remote_route())
+                && responder
+                    .map(|r| is_direct(r.remote_route()))
+                    .unwrap_or(false)
+            {
+                moved = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(moved, "the secure channel wasn't moved to a direct path");
+
+        let reply: String = context
+            .send_and_receive(route, "Hello again".to_string())
+            .await?;
+        assert_eq!(reply, "Hello again");
+
+        context.stop().await
+    }
+}
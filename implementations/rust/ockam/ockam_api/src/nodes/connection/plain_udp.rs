@@ -0,0 +1,84 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{
+    host_match, host_port, Changes, ConnectionInstanceBuilder, Instantiator,
+};
+use crate::nodes::NodeManager;
+use crate::try_address_to_multiaddr;
+
+use ockam::compat::tokio::sync::RwLock;
+use ockam_core::{async_trait, Error};
+use ockam_multiaddr::proto::Udp;
+use ockam_multiaddr::{Match, Protocol};
+use ockam_node::Context;
+use ockam_transport_udp::UdpConnectionOptions;
+
+use std::sync::Arc;
+
+/// Creates a udp socket dedicated to the peer.
+///
+/// Messages are sent with reliable delivery so that secure channels
+/// and portals can be used over that socket.
+pub(crate) struct PlainUdpInstantiator {
+    node_manager: Arc<RwLock<NodeManager>>,
+    context: Arc<Context>,
+}
+
+impl PlainUdpInstantiator {
+    pub(crate) fn new(context: Arc<Context>, node_manager: Arc<RwLock<NodeManager>>) -> Self {
+        Self {
+            context,
+            node_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl Instantiator for PlainUdpInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        vec![
+            // matches any host address followed by a udp protocol
+            host_match(),
+            Udp::CODE.into(),
+        ]
+    }
+
+    async fn instantiate(
+        &self,
+        builder: &ConnectionInstanceBuilder,
+        match_start: usize,
+    ) -> Result<Changes, Error> {
+        let (before, udp_piece, after) =
+            ConnectionInstanceBuilder::extract(&builder.current_multiaddr, match_start, 2);
+
+        let mut protocols = udp_piece.iter();
+        let host = protocols
+            .next()
+            .ok_or_else(|| ApiError::generic("missing host in udp multiaddr"))?;
+        let port = protocols
+            .next()
+            .and_then(|p| p.cast::<Udp>())
+            .ok_or_else(|| ApiError::generic("missing udp port in multiaddr"))?;
+        let peer = host_port(&host, *port)?;
+
+        let options = UdpConnectionOptions::new().with_reliable_delivery();
+        let flow_control_id = options.flow_control_id();
+        let sender = self
+            .node_manager
+            .read()
+            .await
+            .udp_transport(&self.context)
+            .await?
+            .connect_with_options(peer, options)
+            .await?;
+
+        let current_multiaddr =
+            ConnectionInstanceBuilder::combine(before, try_address_to_multiaddr(&sender)?, after)?;
+
+        Ok(Changes {
+            current_multiaddr,
+            flow_control_id: Some(flow_control_id),
+            secure_channel_encryptors: vec![],
+            tcp_worker: None,
+        })
+    }
+}
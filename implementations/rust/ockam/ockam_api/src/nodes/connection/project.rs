@@ -5,6 +5,7 @@ use crate::nodes::NodeManager;
 use crate::{multiaddr_to_route, try_address_to_multiaddr};
 
 use ockam::compat::tokio::sync::RwLock;
+use ockam::identity::XXCurve;
 use ockam_core::{async_trait, Error};
 use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::{Match, Protocol};
@@ -79,6 +80,8 @@ impl Instantiator for ProjectInstantiator {
                 self.identity_name.clone(),
                 &self.context,
                 self.credential_name.clone(),
+                XXCurve::default(),
+                None,
             )
             .await?;
 
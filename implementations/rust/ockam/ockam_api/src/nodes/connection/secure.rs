@@ -6,7 +6,7 @@ use crate::{local_multiaddr_to_route, try_address_to_multiaddr};
 
 use ockam::compat::tokio::sync::RwLock;
 use ockam_core::{async_trait, route, Error};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, XXCurve};
 use ockam_multiaddr::proto::Secure;
 use ockam_multiaddr::{Match, Protocol};
 use ockam_node::Context;
@@ -70,6 +70,8 @@ impl Instantiator for SecureChannelInstantiator {
                 None,
                 &self.context,
                 None,
+                XXCurve::default(),
+                None,
             )
             .await?;
 
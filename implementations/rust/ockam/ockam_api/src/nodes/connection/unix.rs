@@ -0,0 +1,70 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{Changes, ConnectionInstanceBuilder, Instantiator};
+use crate::nodes::NodeManager;
+use crate::try_address_to_multiaddr;
+
+use ockam::compat::tokio::sync::RwLock;
+use ockam_core::{async_trait, Error};
+use ockam_multiaddr::proto::Unix;
+use ockam_multiaddr::{Match, Protocol};
+use ockam_node::Context;
+use ockam_transport_uds::UdsConnectionOptions;
+
+use std::sync::Arc;
+
+/// Creates the unix domain socket connection.
+pub(crate) struct UnixInstantiator {
+    node_manager: Arc<RwLock<NodeManager>>,
+    context: Arc<Context>,
+}
+
+impl UnixInstantiator {
+    pub(crate) fn new(context: Arc<Context>, node_manager: Arc<RwLock<NodeManager>>) -> Self {
+        Self {
+            context,
+            node_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl Instantiator for UnixInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        vec![Unix::CODE.into()]
+    }
+
+    async fn instantiate(
+        &self,
+        builder: &ConnectionInstanceBuilder,
+        match_start: usize,
+    ) -> Result<Changes, Error> {
+        let (before, unix_piece, after) =
+            ConnectionInstanceBuilder::extract(&builder.current_multiaddr, match_start, 1);
+
+        let path = unix_piece
+            .first()
+            .and_then(|p| p.cast::<Unix>().map(|p| p.to_string()))
+            .ok_or_else(|| ApiError::generic("missing unix socket path in multiaddr"))?;
+
+        let options = UdsConnectionOptions::new();
+        let flow_control_id = options.flow_control_id();
+        let sender = self
+            .node_manager
+            .read()
+            .await
+            .uds_transport(&self.context)
+            .await?
+            .connect_with_options(path, options)
+            .await?;
+
+        let current_multiaddr =
+            ConnectionInstanceBuilder::combine(before, try_address_to_multiaddr(&sender)?, after)?;
+
+        Ok(Changes {
+            current_multiaddr,
+            flow_control_id: Some(flow_control_id),
+            secure_channel_encryptors: vec![],
+            tcp_worker: None,
+        })
+    }
+}
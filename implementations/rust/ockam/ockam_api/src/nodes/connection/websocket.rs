@@ -0,0 +1,101 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{
+    host_match, host_port, Changes, ConnectionInstanceBuilder, Instantiator,
+};
+use crate::nodes::NodeManager;
+use crate::try_address_to_multiaddr;
+
+use ockam::compat::tokio::sync::RwLock;
+use ockam_core::env::get_env;
+use ockam_core::{async_trait, Error};
+use ockam_multiaddr::proto::{Tcp, Ws, Wss};
+use ockam_multiaddr::{Match, Protocol};
+use ockam_node::Context;
+use ockam_transport_websocket::{TlsClientConfig, WebSocketConnectionOptions};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Environment variable containing the path of the PEM encoded CA certificates
+/// trusted by `wss` connections
+const OCKAM_WSS_CA_FILE: &str = "OCKAM_WSS_CA_FILE";
+
+/// Creates the websocket connection.
+///
+/// Must be used before the [`PlainTcpInstantiator`](super::PlainTcpInstantiator),
+/// which would otherwise consume the host and tcp port of the websocket address.
+pub(crate) struct WebSocketInstantiator {
+    node_manager: Arc<RwLock<NodeManager>>,
+    context: Arc<Context>,
+}
+
+impl WebSocketInstantiator {
+    pub(crate) fn new(context: Arc<Context>, node_manager: Arc<RwLock<NodeManager>>) -> Self {
+        Self {
+            context,
+            node_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl Instantiator for WebSocketInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        vec![
+            // matches any host address followed by a tcp port and a websocket protocol
+            host_match(),
+            Tcp::CODE.into(),
+            Match::any([Ws::CODE, Wss::CODE]),
+        ]
+    }
+
+    async fn instantiate(
+        &self,
+        builder: &ConnectionInstanceBuilder,
+        match_start: usize,
+    ) -> Result<Changes, Error> {
+        let (before, ws_piece, after) =
+            ConnectionInstanceBuilder::extract(&builder.current_multiaddr, match_start, 3);
+
+        let mut protocols = ws_piece.iter();
+        let host = protocols
+            .next()
+            .ok_or_else(|| ApiError::generic("missing host in websocket multiaddr"))?;
+        let port = protocols
+            .next()
+            .and_then(|p| p.cast::<Tcp>())
+            .ok_or_else(|| ApiError::generic("missing tcp port in websocket multiaddr"))?;
+        let peer = host_port(&host, *port)?;
+        // Secure websockets verify the server certificate with the trusted roots of the platform,
+        // unless a CA bundle is configured with the OCKAM_WSS_CA_FILE environment variable
+        let options = if protocols.next().map(|p| p.code()) == Some(Wss::CODE) {
+            let tls = match get_env::<PathBuf>(OCKAM_WSS_CA_FILE)? {
+                Some(ca_file) => TlsClientConfig::from_ca_file(ca_file)?,
+                None => TlsClientConfig::with_native_roots()?,
+            };
+            WebSocketConnectionOptions::new().with_tls(tls)
+        } else {
+            WebSocketConnectionOptions::new()
+        };
+
+        let flow_control_id = options.flow_control_id();
+        let sender = self
+            .node_manager
+            .read()
+            .await
+            .ws_transport(&self.context)
+            .await?
+            .connect_with_options(peer, options)
+            .await?;
+
+        let current_multiaddr =
+            ConnectionInstanceBuilder::combine(before, try_address_to_multiaddr(&sender)?, after)?;
+
+        Ok(Changes {
+            current_multiaddr,
+            flow_control_id: Some(flow_control_id),
+            secure_channel_encryptors: vec![],
+            tcp_worker: None,
+        })
+    }
+}
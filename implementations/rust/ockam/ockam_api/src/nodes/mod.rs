@@ -1,3 +1,4 @@
+mod access_control;
 pub mod authority_node;
 pub mod config;
 pub(crate) mod connection;
@@ -8,5 +9,14 @@ pub mod service;
 /// A const address to bind and send messages to
 pub const NODEMANAGER_ADDR: &str = "_internal.nodemanager";
 
+/// Address of the secure channel listener used by the admins of a node
+/// to reach its node manager
+pub const NODEMANAGER_LISTENER_ADDR: &str = "_internal.nodemanager.listener";
+
+pub use access_control::{
+    send_to_node_manager, send_to_node_manager_with_options, DestinationAccessControl,
+    LocalRequestInfo, NodeManagerAccessControl,
+};
+
 /// The main node-manager service running on remote nodes
 pub use service::{IdentityOverride, NodeManager, NodeManagerWorker};
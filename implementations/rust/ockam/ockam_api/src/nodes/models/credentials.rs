@@ -2,6 +2,8 @@
 
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
+use ockam_identity::CredentialsCacheStats;
+use serde::Serialize;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -52,3 +54,37 @@ impl<'a> PresentCredentialRequest<'a> {
         }
     }
 }
+
+/// Response body when asking a node for the state of its cache of verified credentials
+#[derive(Clone, Debug, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialsCacheStatus {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<5304618>,
+    #[n(1)] pub entries: u64,
+    #[n(2)] pub hits: u64,
+    #[n(3)] pub misses: u64,
+}
+
+impl CredentialsCacheStatus {
+    pub fn new(entries: usize, stats: CredentialsCacheStats) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            entries: entries as u64,
+            hits: stats.hits as u64,
+            misses: stats.misses as u64,
+        }
+    }
+
+    /// Return the ratio of lookups which were served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        CredentialsCacheStats {
+            hits: self.hits as usize,
+            misses: self.misses as usize,
+        }
+        .hit_rate()
+    }
+}
@@ -2,7 +2,7 @@ use minicbor::{Decode, Encode};
 
 use ockam::identity::IdentityIdentifier;
 use ockam::remote::RemoteForwarderInfo;
-use ockam::route;
+use ockam::{route, ForwarderBufferStats, ForwarderStatus};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::CowStr;
 use ockam_multiaddr::MultiAddr;
@@ -89,6 +89,9 @@ pub struct ForwarderInfo<'a> {
     #[b(2)] remote_address: CowStr<'a>,
     #[b(3)] worker_address: CowStr<'a>,
     #[n(4)] flow_control_id: Option<FlowControlId>,
+    /// Only set for the buffered forwarders hosted by the node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[n(5)] buffer: Option<ForwarderBufferInfo>,
 }
 
 impl<'a> ForwarderInfo<'a> {
@@ -104,6 +107,10 @@ impl<'a> ForwarderInfo<'a> {
         &self.flow_control_id
     }
 
+    pub fn buffer(&'a self) -> Option<&'a ForwarderBufferInfo> {
+        self.buffer.as_ref()
+    }
+
     pub fn remote_address_ma(&'a self) -> Result<MultiAddr, ockam_core::Error> {
         route_to_multiaddr(&route![self.remote_address.to_string()])
             .ok_or_else(|| ApiError::generic("Invalid Remote Address"))
@@ -124,6 +131,52 @@ impl<'a> From<RemoteForwarderInfo> for ForwarderInfo<'a> {
             remote_address: inner.remote_address().to_string().into(),
             worker_address: inner.worker_address().to_string().into(),
             flow_control_id: inner.flow_control_id().clone(),
+            buffer: None,
+        }
+    }
+}
+
+impl<'a> From<ForwarderStatus> for ForwarderInfo<'a> {
+    fn from(inner: ForwarderStatus) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: Default::default(),
+            forwarding_route: inner.forward_route.to_string().into(),
+            remote_address: inner.address.address().to_string().into(),
+            worker_address: inner.address.to_string().into(),
+            flow_control_id: None,
+            buffer: inner.stats.map(ForwarderBufferInfo::from),
+        }
+    }
+}
+
+/// Statistics of the buffer of a forwarder hosted by the node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ForwarderBufferInfo {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4183562>,
+    /// Number of messages waiting for the remote node to register again
+    #[n(1)] pub buffered: u64,
+    /// Number of buffered messages delivered after a new registration
+    #[n(2)] pub redelivered: u64,
+    /// Number of messages dropped because the buffer was full
+    #[n(3)] pub dropped_overflow: u64,
+    /// Number of messages dropped because they were buffered for too long
+    #[n(4)] pub dropped_expired: u64,
+}
+
+impl From<ForwarderBufferStats> for ForwarderBufferInfo {
+    fn from(stats: ForwarderBufferStats) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: Default::default(),
+            buffered: stats.buffered,
+            redelivered: stats.redelivered,
+            dropped_overflow: stats.dropped_overflow,
+            dropped_expired: stats.dropped_expired,
         }
     }
 }
@@ -15,6 +15,7 @@ use serde::ser::SerializeStruct;
 use serde::Serialize;
 
 use crate::error::ApiError;
+use crate::nodes::models::session::{RetryPolicy, SessionStatus};
 use crate::route_to_multiaddr;
 
 /// Request body to create an inlet
@@ -44,6 +45,13 @@ pub struct CreateInlet<'a> {
     #[n(6)] suffix_route: Route,
     /// The maximum duration to wait for an outlet to be available
     #[n(7)] wait_for_outlet_duration: Option<Duration>,
+    /// How the session to the outlet is replaced when the connection is lost
+    #[n(8)] retry_policy: Option<RetryPolicy>,
+    /// Move the secure channel to the outlet to a hole-punched UDP path when possible
+    #[n(9)] direct_path: bool,
+    /// Speak SOCKS5 to the clients of the inlet, and let them request their destination
+    /// to a dynamic outlet
+    #[n(10)] socks5: bool,
 }
 
 impl<'a> CreateInlet<'a> {
@@ -63,6 +71,9 @@ impl<'a> CreateInlet<'a> {
             prefix_route,
             suffix_route,
             wait_for_outlet_duration: None,
+            retry_policy: None,
+            direct_path: false,
+            socks5: false,
         }
     }
 
@@ -83,6 +94,9 @@ impl<'a> CreateInlet<'a> {
             prefix_route,
             suffix_route,
             wait_for_outlet_duration: None,
+            retry_policy: None,
+            direct_path: false,
+            socks5: false,
         }
     }
 
@@ -94,6 +108,18 @@ impl<'a> CreateInlet<'a> {
         self.wait_for_outlet_duration = Some(Duration::from_millis(ms))
     }
 
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = Some(retry_policy)
+    }
+
+    pub fn set_direct_path(&mut self) {
+        self.direct_path = true
+    }
+
+    pub fn set_socks5(&mut self) {
+        self.socks5 = true
+    }
+
     pub fn listen_addr(&self) -> SocketAddr {
         self.listen_addr
     }
@@ -121,6 +147,18 @@ impl<'a> CreateInlet<'a> {
     pub fn wait_for_outlet_duration(&self) -> Option<Duration> {
         self.wait_for_outlet_duration
     }
+
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy
+    }
+
+    pub fn direct_path(&self) -> bool {
+        self.direct_path
+    }
+
+    pub fn socks5(&self) -> bool {
+        self.socks5
+    }
 }
 
 /// Request body to create an outlet
@@ -139,6 +177,9 @@ pub struct CreateOutlet<'a> {
     /// Allow the outlet to be reachable from the default secure channel, useful when we want to
     /// tighten the flow control
     #[b(4)] pub reachable_from_default_secure_channel: bool,
+    /// Make the outlet dynamic: instead of connecting to `tcp_addr`, it connects to the
+    /// destinations requested by SOCKS5 inlets which are allowed by this allowlist
+    #[n(5)] pub allowlist: Option<OutletAllowlist>,
 }
 
 impl<'a> CreateOutlet<'a> {
@@ -155,6 +196,39 @@ impl<'a> CreateOutlet<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             reachable_from_default_secure_channel,
+            allowlist: None,
+        }
+    }
+
+    pub fn with_allowlist(mut self, allowlist: OutletAllowlist) -> Self {
+        self.allowlist = Some(allowlist);
+        self
+    }
+}
+
+/// Destinations that a dynamic outlet is allowed to connect to
+#[derive(Clone, Debug, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletAllowlist {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2694318>,
+    /// IP addresses or CIDR ranges, like `10.0.0.0/8`
+    #[n(1)] pub networks: Vec<String>,
+    /// Host names, including their subdomains when they start with a `.`
+    #[n(2)] pub hosts: Vec<String>,
+    /// Inclusive port ranges. All ports are allowed when empty
+    #[n(3)] pub ports: Vec<(u16, u16)>,
+}
+
+impl OutletAllowlist {
+    pub fn new(networks: Vec<String>, hosts: Vec<String>, ports: Vec<(u16, u16)>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            networks,
+            hosts,
+            ports,
         }
     }
 }
@@ -172,6 +246,8 @@ pub struct InletStatus<'a> {
     /// An optional status payload
     #[b(4)] pub payload: Option<CowStr<'a>>,
     #[b(5)] pub outlet_route: CowStr<'a>,
+    /// The session monitoring the connection to the outlet, if any
+    #[n(6)] pub session: Option<SessionStatus>,
 }
 
 impl<'a> Serialize for InletStatus<'a> {
@@ -179,12 +255,13 @@ impl<'a> Serialize for InletStatus<'a> {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("InletStatus", 5)?;
+        let mut state = serializer.serialize_struct("InletStatus", 6)?;
         state.serialize_field("bind_addr", &self.bind_addr)?;
         state.serialize_field("worker_addr", &self.worker_addr)?;
         state.serialize_field("alias", &self.alias)?;
         state.serialize_field("payload", &self.payload)?;
         state.serialize_field("outlet_route", &self.outlet_route)?;
+        state.serialize_field("session", &self.session)?;
         state.end()
     }
 }
@@ -199,6 +276,7 @@ impl<'a> InletStatus<'a> {
             alias: "".into(),
             payload: Some(reason.into()),
             outlet_route: "".into(),
+            session: None,
         }
     }
 
@@ -217,8 +295,14 @@ impl<'a> InletStatus<'a> {
             alias: alias.into(),
             payload: payload.into(),
             outlet_route: outlet_route.into(),
+            session: None,
         }
     }
+
+    pub fn with_session(mut self, session: Option<SessionStatus>) -> Self {
+        self.session = session;
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
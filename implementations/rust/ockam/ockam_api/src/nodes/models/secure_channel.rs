@@ -3,7 +3,7 @@ use std::time::Duration;
 use minicbor::{Decode, Encode};
 
 use crate::nodes::registry::{SecureChannelInfo, SecureChannelListenerInfo};
-use ockam::identity::IdentityIdentifier;
+use ockam::identity::{IdentityIdentifier, XXCurve};
 use ockam_core::compat::borrow::Cow;
 use ockam_core::flow_control::FlowControlId;
 #[cfg(feature = "tag")]
@@ -24,6 +24,24 @@ pub enum CredentialExchangeMode {
     #[n(2)] Mutual,
 }
 
+/// Curve used by the key exchange of a Secure Channel
+#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum KeyExchangeCurve {
+    #[n(0)] Curve25519,
+    #[n(1)] P256,
+}
+
+impl From<KeyExchangeCurve> for XXCurve {
+    fn from(curve: KeyExchangeCurve) -> Self {
+        match curve {
+            KeyExchangeCurve::Curve25519 => XXCurve::Curve25519,
+            KeyExchangeCurve::P256 => XXCurve::P256,
+        }
+    }
+}
+
 /// Request body when instructing a node to create a Secure Channel
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -37,6 +55,8 @@ pub struct CreateSecureChannelRequest<'a> {
     #[n(4)] pub timeout: Option<Duration>,
     #[b(5)] pub identity_name: Option<CowStr<'a>>,
     #[b(6)] pub credential_name: Option<CowStr<'a>>,
+    #[n(7)] pub key_exchange_curve: Option<KeyExchangeCurve>,
+    #[b(8)] pub disclosed_attributes: Option<Vec<CowStr<'a>>>,
 }
 
 impl<'a> CreateSecureChannelRequest<'a> {
@@ -57,8 +77,21 @@ impl<'a> CreateSecureChannelRequest<'a> {
             timeout: None,
             identity_name: identity_name.map(|x| x.into()),
             credential_name: credential_name.map(|x| x.into()),
+            key_exchange_curve: None,
+            disclosed_attributes: None,
         }
     }
+
+    pub fn with_key_exchange_curve(mut self, curve: KeyExchangeCurve) -> Self {
+        self.key_exchange_curve = Some(curve);
+        self
+    }
+
+    /// Only reveal the given selectively disclosable attributes of the presented credential
+    pub fn with_disclosed_attributes(mut self, names: Vec<String>) -> Self {
+        self.disclosed_attributes = Some(names.into_iter().map(|n| n.into()).collect());
+        self
+    }
 }
 
 /// Response body when instructing a node to create a Secure Channel
@@ -103,6 +136,7 @@ pub struct CreateSecureChannelListenerRequest<'a> {
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
     #[b(3)] pub vault: Option<CowStr<'a>>,
     #[b(4)] pub identity: Option<CowStr<'a>>,
+    #[n(5)] pub key_exchange_curve: Option<KeyExchangeCurve>,
 }
 
 impl<'a> CreateSecureChannelListenerRequest<'a> {
@@ -120,8 +154,14 @@ impl<'a> CreateSecureChannelListenerRequest<'a> {
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
             vault: vault.map(|x| x.into()),
             identity: identity.map(|x| x.into()),
+            key_exchange_curve: None,
         }
     }
+
+    pub fn with_key_exchange_curve(mut self, curve: KeyExchangeCurve) -> Self {
+        self.key_exchange_curve = Some(curve);
+        self
+    }
 }
 
 /// Request body when deleting a Secure Channel Listener
@@ -1,6 +1,9 @@
+use core::fmt::{Debug, Formatter};
+use core::time::Duration;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, CowBytes, CowStr};
+use ockam_identity::IdentityIdentifier;
 
 use serde::Serialize;
 
@@ -112,6 +115,37 @@ impl<'a> StartKafkaConsumerRequest<'a> {
     }
 }
 
+/// Encryption of the keys and headers of the records sent by a Kafka producer.
+/// The values of the records are always encrypted.
+#[derive(Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KafkaRecordEncryption {
+    /// Encrypt the headers of the records
+    #[n(1)] pub headers: bool,
+    /// Encrypt the keys of the records. The encrypted keys are removed from the records
+    /// sent to the broker, unless `deterministic_key_secret` is set. Brokers reject records
+    /// without a key on compacted topics, so those topics require `deterministic_key_secret`
+    #[n(2)] pub keys: bool,
+    /// Replace the encrypted keys with a hash keyed by this secret, so that the same key
+    /// always lands in the same partition and log compaction keeps working. Every producer
+    /// of a topic must use the same secret
+    #[n(3)] pub deterministic_key_secret: Option<String>,
+}
+
+impl Debug for KafkaRecordEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KafkaRecordEncryption")
+            .field("headers", &self.headers)
+            .field("keys", &self.keys)
+            .field(
+                "deterministic_key_secret",
+                &self.deterministic_key_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -119,6 +153,7 @@ pub struct StartKafkaProducerRequest<'a> {
     #[b(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[b(3)] project_route: CowStr<'a>,
+    #[n(4)] record_encryption: Option<KafkaRecordEncryption>,
 }
 
 impl<'a> StartKafkaProducerRequest<'a> {
@@ -131,9 +166,15 @@ impl<'a> StartKafkaProducerRequest<'a> {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string().into(),
+            record_encryption: None,
         }
     }
 
+    pub fn with_record_encryption(mut self, record_encryption: KafkaRecordEncryption) -> Self {
+        self.record_encryption = Some(record_encryption);
+        self
+    }
+
     pub fn bootstrap_server_addr(&self) -> SocketAddr {
         self.bootstrap_server_addr
     }
@@ -143,6 +184,9 @@ impl<'a> StartKafkaProducerRequest<'a> {
     pub fn project_route(&self) -> &CowStr<'a> {
         &self.project_route
     }
+    pub fn record_encryption(&self) -> KafkaRecordEncryption {
+        self.record_encryption.clone().unwrap_or_default()
+    }
 }
 
 /// Request body when instructing a node to start an Identity service
@@ -245,6 +289,62 @@ impl<'a> StartHopServiceRequest<'a> {
     }
 }
 
+/// Request body when instructing a node to start a Stream service, together with its
+/// Stream index service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartStreamServiceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5612408>,
+    #[b(1)] pub addr: CowStr<'a>,
+    #[b(2)] pub index_addr: CowStr<'a>,
+    #[b(3)] pub path: Option<CowStr<'a>>,
+    #[n(4)] pub max_segment_size: Option<u64>,
+    #[n(5)] pub retention_size: Option<u64>,
+    #[n(6)] pub retention_secs: Option<u64>,
+}
+
+impl<'a> StartStreamServiceRequest<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>, index_addr: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            index_addr: index_addr.into(),
+            path: None,
+            max_segment_size: None,
+            retention_size: None,
+            retention_secs: None,
+        }
+    }
+
+    /// Directory where the streams are stored. By default they are stored in the node directory
+    pub fn with_path(mut self, path: impl Into<CowStr<'a>>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_max_segment_size(mut self, max_segment_size: Option<u64>) -> Self {
+        self.max_segment_size = max_segment_size;
+        self
+    }
+
+    pub fn with_retention_size(mut self, retention_size: Option<u64>) -> Self {
+        self.retention_size = retention_size;
+        self
+    }
+
+    pub fn with_retention_duration(mut self, retention: Option<Duration>) -> Self {
+        self.retention_secs = retention.map(|d| d.as_secs());
+        self
+    }
+
+    pub fn retention_duration(&self) -> Option<Duration> {
+        self.retention_secs.map(Duration::from_secs)
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -298,6 +398,187 @@ impl<'a> StartVerifierService<'a> {
     }
 }
 
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartSignerService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4060283>,
+    #[b(1)] addr: CowStr<'a>,
+    #[n(2)] authorized: Vec<IdentityIdentifier>,
+}
+
+impl<'a> StartSignerService<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>, authorized: Vec<IdentityIdentifier>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            authorized,
+        }
+    }
+
+    pub fn address(&'a self) -> &'a str {
+        &self.addr
+    }
+
+    /// Identities allowed to use the signer
+    pub fn authorized(&self) -> &[IdentityIdentifier] {
+        &self.authorized
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartDirectPathService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5518042>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(2)] rendezvous: CowStr<'a>,
+}
+
+impl<'a> StartDirectPathService<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>, rendezvous: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            rendezvous: rendezvous.into(),
+        }
+    }
+
+    pub fn address(&'a self) -> &'a str {
+        &self.addr
+    }
+
+    /// Address of the node running the Rendezvous service, e.g. `/ip4/10.0.0.1/udp/4000`
+    pub fn rendezvous(&'a self) -> &'a str {
+        &self.rendezvous
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartRendezvousService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4471902>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(2)] udp_address: CowStr<'a>,
+    #[n(3)] anonymous_registrations: bool,
+}
+
+impl<'a> StartRendezvousService<'a> {
+    pub fn new(
+        addr: impl Into<CowStr<'a>>,
+        udp_address: impl Into<CowStr<'a>>,
+        anonymous_registrations: bool,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            udp_address: udp_address.into(),
+            anonymous_registrations,
+        }
+    }
+
+    pub fn address(&'a self) -> &'a str {
+        &self.addr
+    }
+
+    /// Local UDP address the node listens on for the punchers, e.g. `0.0.0.0:4000`
+    pub fn udp_address(&'a self) -> &'a str {
+        &self.udp_address
+    }
+
+    pub fn anonymous_registrations(&self) -> bool {
+        self.anonymous_registrations
+    }
+}
+
+/// Request body to list the punchers registered with a Rendezvous service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ListRendezvousRegistrations<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3092817>,
+    #[b(1)] rendezvous: CowStr<'a>,
+}
+
+impl<'a> ListRendezvousRegistrations<'a> {
+    pub fn new(rendezvous: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            rendezvous: rendezvous.into(),
+        }
+    }
+
+    /// Address of the Rendezvous service, through a secure channel, e.g.
+    /// `/ip4/10.0.0.1/udp/4000/secure/api/service/rendezvous`
+    pub fn rendezvous(&'a self) -> &'a str {
+        &self.rendezvous
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RendezvousRegistration {
+    #[cfg(feature = "tag")]
+    #[serde(skip_serializing)]
+    #[n(0)] tag: TypeTag<6120458>,
+    #[n(1)] pub puncher_name: String,
+    /// Identity which registered the puncher, if it wasn't registered anonymously
+    #[n(2)] pub identifier: Option<String>,
+    /// Public route to the puncher
+    #[n(3)] pub route: String,
+    /// Time before the registration expires, in seconds
+    #[n(4)] pub expires_in: u64,
+}
+
+impl RendezvousRegistration {
+    pub fn new(
+        puncher_name: impl Into<String>,
+        identifier: Option<String>,
+        route: impl Into<String>,
+        expires_in: u64,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            puncher_name: puncher_name.into(),
+            identifier,
+            route: route.into(),
+            expires_in,
+        }
+    }
+}
+
+/// Response body for listing the punchers registered with a Rendezvous service
+#[derive(Debug, Clone, Serialize, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RendezvousRegistrationList {
+    #[cfg(feature = "tag")]
+    #[serde(skip_serializing)]
+    #[n(0)] tag: TypeTag<2716593>,
+    #[n(1)] pub list: Vec<RendezvousRegistration>
+}
+
+impl RendezvousRegistrationList {
+    pub fn new(list: Vec<RendezvousRegistration>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
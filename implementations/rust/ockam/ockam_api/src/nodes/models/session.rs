@@ -0,0 +1,223 @@
+//! Session monitoring request/response types
+
+use std::fmt;
+use std::time::Duration;
+
+use minicbor::{Decode, Encode};
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+use serde::Serialize;
+
+/// Health of a session, as seen by the session Medic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum Status {
+    /// The last attempt to replace the session failed
+    #[n(0)] Down,
+    /// The session stopped answering pings and is being replaced
+    #[n(1)] Degraded,
+    /// The session answers pings
+    #[n(2)] Up,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Down => write!(f, "down"),
+            Status::Degraded => write!(f, "degraded"),
+            Status::Up => write!(f, "up"),
+        }
+    }
+}
+
+/// How a session is replaced when it stops answering pings.
+///
+/// The delay before a replacement attempt grows exponentially with the number of
+/// consecutive failed attempts, up to `max_delay`. A random part of the delay, up to
+/// `jitter` times the delay, is removed so that sessions failing at the same time are
+/// not all replaced at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RetryPolicy {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6913457>,
+    #[n(1)] initial_delay: Duration,
+    #[n(2)] max_delay: Duration,
+    #[n(3)] multiplier: u32,
+    #[n(4)] jitter: f64,
+    #[n(5)] max_failures: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(5 * 60),
+            multiplier: 2,
+            jitter: 0.5,
+            max_failures: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the first replacement attempt
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Maximum delay between two replacement attempts
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Factor applied to the delay after each failed replacement attempt
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    /// Fraction of the delay, between 0 and 1, which can be randomly removed
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Number of unanswered pings after which the session is replaced
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Check that the policy can be used by the session Medic.
+    ///
+    /// Policies decoded from a request don't go through the `with_*` functions,
+    /// so their values must be checked before being used.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.initial_delay.is_zero() {
+            return Err("the session retry delay must be greater than 0");
+        }
+        if self.multiplier == 0 {
+            return Err("the session retry multiplier must be at least 1");
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("the session retry jitter must be a number between 0 and 1");
+        }
+        if self.max_failures == 0 {
+            return Err("the session maximum number of failures must be at least 1");
+        }
+        Ok(())
+    }
+
+    pub fn initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        self.multiplier
+    }
+
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    pub fn max_failures(&self) -> u32 {
+        self.max_failures
+    }
+}
+
+/// Change of the status of a session
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionEvent {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2834519>,
+    #[n(1)] pub from: Status,
+    #[n(2)] pub to: Status,
+    /// Time of the change, in seconds since the UNIX epoch
+    #[n(3)] pub at: u64,
+    #[n(4)] pub error: Option<String>,
+}
+
+impl SessionEvent {
+    pub fn new(from: Status, to: Status, at: u64, error: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            from,
+            to,
+            at,
+            error,
+        }
+    }
+}
+
+/// Response body when showing a session
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionStatus {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<8047361>,
+    #[n(1)] pub key: String,
+    #[n(2)] pub ping_route: String,
+    #[n(3)] pub status: Status,
+    #[n(4)] pub last_error: Option<String>,
+    /// Time of the next replacement attempt, in seconds since the UNIX epoch
+    #[n(5)] pub next_retry: Option<u64>,
+    /// Number of consecutive failed replacement attempts
+    #[n(6)] pub failed_attempts: u32,
+    /// Most recent status changes, oldest first
+    #[n(7)] pub events: Vec<SessionEvent>,
+}
+
+impl SessionStatus {
+    pub fn new(key: impl Into<String>, ping_route: impl Into<String>, status: Status) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key: key.into(),
+            ping_route: ping_route.into(),
+            status,
+            last_error: None,
+            next_retry: None,
+            failed_attempts: 0,
+            events: vec![],
+        }
+    }
+}
+
+/// Response body when listing the sessions of a node
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionList {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<1359602>,
+    #[n(1)] pub list: Vec<SessionStatus>,
+}
+
+impl SessionList {
+    pub fn new(list: Vec<SessionStatus>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
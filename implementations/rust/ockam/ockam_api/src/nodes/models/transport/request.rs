@@ -44,6 +44,40 @@ impl CreateTcpListener {
     }
 }
 
+/// Request body when instructing a node to create a WebSocket listener
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateWebSocketListener {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2896217>,
+    /// The address payload for the transport
+    #[n(1)] pub addr: String,
+    /// Path of the PEM encoded certificate chain used to accept `wss://` connections
+    #[n(2)] pub tls_certificate_file: Option<String>,
+    /// Path of the PEM encoded private key of the certificate
+    #[n(3)] pub tls_private_key_file: Option<String>,
+}
+
+impl CreateWebSocketListener {
+    pub fn new(addr: String) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            tls_certificate_file: None,
+            tls_private_key_file: None,
+        }
+    }
+
+    /// Accept `wss://` connections with the given certificate chain and private key files
+    pub fn with_tls(mut self, certificate_file: String, private_key_file: String) -> Self {
+        self.tls_certificate_file = Some(certificate_file);
+        self.tls_private_key_file = Some(private_key_file);
+        self
+    }
+}
+
 /// Request to delete a transport
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
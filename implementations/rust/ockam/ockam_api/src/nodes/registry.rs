@@ -1,9 +1,12 @@
 use crate::nodes::service::Alias;
+use crate::session::sessions::Key;
 use ockam::identity::IdentityIdentifier;
 use ockam::remote::RemoteForwarderInfo;
+use ockam::ForwardersRegistry;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
 use ockam_identity::{SecureChannel, SecureChannelListener};
+use ockam_multiaddr::MultiAddr;
 use std::fmt::Display;
 
 #[derive(Default)]
@@ -107,6 +110,31 @@ pub(crate) struct HopServiceInfo {}
 #[derive(Default)]
 pub(crate) struct VerifierServiceInfo {}
 
+#[derive(Default)]
+pub(crate) struct SignerServiceInfo {}
+
+#[derive(Clone)]
+pub(crate) struct DirectPathServiceInfo {
+    rendezvous: MultiAddr,
+}
+
+impl DirectPathServiceInfo {
+    pub fn new(rendezvous: MultiAddr) -> Self {
+        Self { rendezvous }
+    }
+
+    /// Address of the node running the Rendezvous service used by the punchers
+    pub fn rendezvous(&self) -> &MultiAddr {
+        &self.rendezvous
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct RendezvousServiceInfo {}
+
+#[derive(Default)]
+pub(crate) struct StreamServiceInfo {}
+
 #[derive(Default)]
 pub(crate) struct CredentialsServiceInfo {}
 
@@ -147,6 +175,7 @@ pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
     pub(crate) outlet_route: Route,
+    pub(crate) session: Option<Key>,
 }
 
 impl InletInfo {
@@ -163,8 +192,15 @@ impl InletInfo {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
             outlet_route: outlet_route.to_owned(),
+            session: None,
         }
     }
+
+    /// Set the key of the session monitoring the connection to the outlet
+    pub(crate) fn with_session(mut self, session: Option<Key>) -> Self {
+        self.session = session;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -198,12 +234,19 @@ pub(crate) struct Registry {
     pub(crate) kafka_services: BTreeMap<Address, KafkaServiceInfo>,
     pub(crate) hop_services: BTreeMap<Address, HopServiceInfo>,
     pub(crate) verifier_services: BTreeMap<Address, VerifierServiceInfo>,
+    pub(crate) signer_services: BTreeMap<Address, SignerServiceInfo>,
+    pub(crate) direct_path_services: BTreeMap<Address, DirectPathServiceInfo>,
+    pub(crate) rendezvous_services: BTreeMap<Address, RendezvousServiceInfo>,
+    pub(crate) stream_services: BTreeMap<Address, StreamServiceInfo>,
+    pub(crate) stream_index_services: BTreeMap<Address, StreamServiceInfo>,
     pub(crate) credentials_services: BTreeMap<Address, CredentialsServiceInfo>,
     #[cfg(feature = "direct-authenticator")]
     pub(crate) authenticator_service: BTreeMap<Address, AuthenticatorServiceInfo>,
 
     // FIXME: wow this is a terrible way to store data
     pub(crate) forwarders: BTreeMap<String, RemoteForwarderInfo>,
+    /// Forwarders created on this node by remote nodes
+    pub(crate) hosted_forwarders: ForwardersRegistry,
     pub(crate) inlets: BTreeMap<Alias, InletInfo>,
     pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
 }
@@ -3,6 +3,7 @@
 use std::collections::BTreeMap;
 use std::error::Error as _;
 use std::net::SocketAddr;
+use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 
 use minicbor::Decoder;
@@ -12,10 +13,10 @@ use ockam::identity::{
     Credentials, CredentialsServer, CredentialsServerModule, Identities, IdentitiesRepository,
     IdentitiesVault, IdentityAttributesReader, IdentityAttributesWriter,
 };
-use ockam::identity::{IdentityIdentifier, SecureChannels};
+use ockam::identity::{IdentityIdentifier, SecureChannels, XXCurve};
 use ockam::{
-    Address, Context, ForwardingService, ForwardingServiceOptions, Result, Routed, TcpTransport,
-    Worker,
+    Address, Context, ForwarderBufferOptions, ForwardingService, ForwardingServiceOptions, Result,
+    Routed, TcpTransport, Worker,
 };
 use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{Action, Env, Expr, PolicyAccessControl, PolicyStorage, Resource};
@@ -34,28 +35,37 @@ use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::asynchronous::RwLock;
 use ockam_node::tokio;
 use ockam_node::tokio::task::JoinHandle;
+use ockam_transport_udp::UdpTransport;
+use ockam_transport_uds::UdsTransport;
+use ockam_transport_websocket::WebSocketTransport;
 
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
-use crate::cli_state::{CliState, StateDirTrait, StateItemTrait};
-use crate::config::cli::TrustContextConfig;
+use crate::cli_state::{
+    CliState, StateDirTrait, StateItemTrait, DEFAULT_CREDENTIAL_RENEWAL_MARGIN,
+};
+use crate::config::cli::{CredentialRetrieverConfig, TrustContextConfig};
 use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
 use crate::nodes::connection::{
-    Connection, ConnectionInstance, ConnectionInstanceBuilder, PlainTcpInstantiator,
-    ProjectInstantiator, SecureChannelInstantiator,
+    upgrade_to_direct_path, Connection, ConnectionInstance, ConnectionInstanceBuilder,
+    PlainTcpInstantiator, PlainUdpInstantiator, ProjectInstantiator, SecureChannelInstantiator,
+    UnixInstantiator, WebSocketInstantiator,
 };
 use crate::nodes::models::base::NodeStatus;
 use crate::nodes::models::transport::{TransportMode, TransportType};
 use crate::nodes::models::workers::{WorkerList, WorkerStatus};
 use crate::nodes::registry::KafkaServiceKind;
+use crate::nodes::{DestinationAccessControl, NodeManagerAccessControl, NODEMANAGER_ADDR};
 use crate::session::sessions::Sessions;
 use crate::session::Medic;
 use crate::DefaultAddress;
 use crate::RpcProxyService;
 
 use super::registry::Registry;
+use credential_renewal::CredentialRenewal;
 
+mod credential_renewal;
 mod credentials;
 mod flow_controls;
 mod forwarder;
@@ -65,6 +75,7 @@ mod node_services;
 mod policy;
 mod portals;
 mod secure_channel;
+mod sessions;
 mod transport;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
@@ -93,10 +104,15 @@ pub struct NodeManager {
     node_name: String,
     api_transport_flow_control_id: FlowControlId,
     pub(crate) tcp_transport: TcpTransport,
+    udp_transport: tokio::sync::OnceCell<UdpTransport>,
+    uds_transport: tokio::sync::OnceCell<UdsTransport>,
+    ws_transport: tokio::sync::OnceCell<WebSocketTransport>,
     pub(crate) controller_identity_id: IdentityIdentifier,
     skip_defaults: bool,
+    forwarder_buffer: Option<ForwarderBufferOptions>,
     enable_credential_checks: bool,
     identifier: IdentityIdentifier,
+    admins: Vec<IdentityIdentifier>,
     pub(crate) secure_channels: Arc<SecureChannels>,
     projects: Arc<BTreeMap<String, ProjectLookup>>,
     trust_context: Option<TrustContext>,
@@ -111,6 +127,48 @@ impl NodeManager {
         self.identifier.clone()
     }
 
+    /// Return the UDP transport of the node, creating it on first use
+    pub(crate) async fn udp_transport(&self, ctx: &Context) -> Result<&UdpTransport> {
+        self.udp_transport
+            .get_or_try_init(|| UdpTransport::create(ctx))
+            .await
+    }
+
+    /// Return the Unix domain socket transport of the node, creating it on first use
+    pub(crate) async fn uds_transport(&self, ctx: &Context) -> Result<&UdsTransport> {
+        self.uds_transport
+            .get_or_try_init(|| UdsTransport::create(ctx))
+            .await
+    }
+
+    /// Listen for the requests of the CLI on the Unix domain socket of the node.
+    /// Only the owner of the node can connect to that socket
+    async fn listen_on_api_socket(&self, ctx: &Context) -> Result<()> {
+        let path = self.cli_state.nodes.get(&self.node_name)?.api_socket();
+        // the socket is bound in the directory of the node, which only its owner can
+        // access, so that the socket is never reachable with the default permissions
+        if let Some(dir) = path.parent() {
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .map_err(|e| ApiError::generic(&e.to_string()))?;
+        }
+        // remove the socket left by a previous run of the node
+        let _ = std::fs::remove_file(&path);
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| ApiError::generic("invalid path for the socket of the node"))?;
+        self.uds_transport(ctx).await?.listen(path_str).await?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| ApiError::generic(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// Return the WebSocket transport of the node, creating it on first use
+    pub(crate) async fn ws_transport(&self, ctx: &Context) -> Result<&WebSocketTransport> {
+        self.ws_transport
+            .get_or_try_init(|| WebSocketTransport::create(ctx))
+            .await
+    }
+
     pub(super) fn identities(&self) -> Arc<Identities> {
         self.secure_channels.identities()
     }
@@ -154,6 +212,25 @@ impl NodeManagerWorker {
     pub fn get(&mut self) -> &mut Arc<RwLock<NodeManager>> {
         &mut self.node_manager
     }
+
+    /// Start the node manager at [`NODEMANAGER_ADDR`]. Besides the workers of the node,
+    /// only its admins and the users of its Unix domain socket can send requests to it
+    pub async fn start(self, ctx: &Context) -> Result<()> {
+        let admins = {
+            let node_manager = self.node_manager.read().await;
+            if let Err(e) = node_manager.listen_on_api_socket(ctx).await {
+                warn!("The node manager can't be reached with a Unix domain socket: {e}");
+            }
+            node_manager.admins.clone()
+        };
+        ctx.start_worker_with_access_control(
+            NODEMANAGER_ADDR,
+            self,
+            NodeManagerAccessControl::new(admins),
+            AllowAll,
+        )
+        .await
+    }
 }
 
 pub struct IdentityOverride {
@@ -170,36 +247,9 @@ impl NodeManager {
         custom_default: Option<&Expr>,
     ) -> Result<Arc<dyn IncomingAccessControl>> {
         if let Some(tcid) = trust_context_id {
-            // Populate environment with known attributes:
-            let mut env = Env::new();
-            env.put("resource.id", str(r.as_str()));
-            env.put("action.id", str(a.as_str()));
-            env.put("resource.project_id", str(tcid.to_string()));
-            env.put("resource.trust_context_id", str(tcid));
-
-            // Check if a policy exists for (resource, action) and if not, then
-            // create or use a default entry:
-            if self.policies.get_policy(r, a).await?.is_none() {
-                let fallback = match custom_default {
-                    Some(e) => e.clone(),
-                    None => and([
-                        eq([ident("resource.project_id"), ident("subject.project_id")]), // TODO: DEPRECATE - Removing PROJECT_ID attribute in favor of TRUST_CONTEXT_ID
-                                                                                         /*
-                                                                                         * TODO: replace the project_id check for trust_context_id.  For now the
-                                                                                         * existing authority deployed doesn't know about trust_context so this is to
-                                                                                         * be done after updating deployed authorities.
-                                                                                         eq([
-                                                                                             ident("resource.trust_context_id"),
-                                                                                             ident("subject.trust_context_id"),
-                                                                                         ]),
-                                                                                         */
-                    ]),
-                };
-                self.policies.set_policy(r, a, &fallback).await?
-            }
-            let policies = self.policies.clone();
+            let env = self.policy_environment(r, a, tcid, custom_default).await?;
             Ok(Arc::new(PolicyAccessControl::new(
-                policies,
+                self.policies.clone(),
                 self.identities_repository(),
                 r.clone(),
                 a.clone(),
@@ -210,6 +260,67 @@ impl NodeManager {
         }
     }
 
+    /// Access control of the connection requests of a dynamic outlet, evaluating the policy
+    /// of (resource, action) with the requested destination
+    async fn destination_access_control(
+        &self,
+        r: &Resource,
+        a: &Action,
+        trust_context_id: Option<&str>,
+    ) -> Result<Arc<dyn IncomingAccessControl>> {
+        if let Some(tcid) = trust_context_id {
+            let env = self.policy_environment(r, a, tcid, None).await?;
+            Ok(Arc::new(DestinationAccessControl::new(
+                self.policies.clone(),
+                self.identities_repository(),
+                r.clone(),
+                a.clone(),
+                env,
+            )))
+        } else {
+            Ok(Arc::new(AllowAll))
+        }
+    }
+
+    /// Return the environment of the policy of (resource, action), and set a default
+    /// policy if there is none
+    async fn policy_environment(
+        &self,
+        r: &Resource,
+        a: &Action,
+        tcid: &str,
+        custom_default: Option<&Expr>,
+    ) -> Result<Env> {
+        // Populate environment with known attributes:
+        let mut env = Env::new();
+        env.put("resource.id", str(r.as_str()));
+        env.put("action.id", str(a.as_str()));
+        env.put("resource.project_id", str(tcid.to_string()));
+        env.put("resource.trust_context_id", str(tcid));
+
+        // Check if a policy exists for (resource, action) and if not, then
+        // create or use a default entry:
+        if self.policies.get_policy(r, a).await?.is_none() {
+            let fallback = match custom_default {
+                Some(e) => e.clone(),
+                None => and([
+                    eq([ident("resource.project_id"), ident("subject.project_id")]), // TODO: DEPRECATE - Removing PROJECT_ID attribute in favor of TRUST_CONTEXT_ID
+                                                                                     /*
+                                                                                     * TODO: replace the project_id check for trust_context_id.  For now the
+                                                                                     * existing authority deployed doesn't know about trust_context so this is to
+                                                                                     * be done after updating deployed authorities.
+                                                                                     eq([
+                                                                                         ident("resource.trust_context_id"),
+                                                                                         ident("subject.trust_context_id"),
+                                                                                     ]),
+                                                                                     */
+                ]),
+            };
+            self.policies.set_policy(r, a, &fallback).await?
+        }
+        Ok(env)
+    }
+
     pub(crate) fn trust_context(&self) -> Result<&TrustContext> {
         self.trust_context
             .as_ref()
@@ -222,6 +333,7 @@ pub struct NodeManagerGeneralOptions {
     node_name: String,
     skip_defaults: bool,
     pre_trusted_identities: Option<PreTrustedIdentities>,
+    forwarder_buffer: Option<ForwarderBufferOptions>,
 }
 
 impl NodeManagerGeneralOptions {
@@ -236,8 +348,16 @@ impl NodeManagerGeneralOptions {
             node_name,
             skip_defaults,
             pre_trusted_identities,
+            forwarder_buffer: None,
         }
     }
+
+    /// Buffer the messages of the forwarders hosted by this node while the nodes which
+    /// registered them are unreachable
+    pub fn with_forwarder_buffer(mut self, forwarder_buffer: ForwarderBufferOptions) -> Self {
+        self.forwarder_buffer = Some(forwarder_buffer);
+        self
+    }
 }
 
 pub struct NodeManagerProjectsOptions {
@@ -337,13 +457,26 @@ impl NodeManager {
         let medic = Medic::new();
         let sessions = medic.sessions();
 
+        // The identity of the node is always allowed to use its node manager
+        let identifier = node_state.config().identifier().await?;
+        let mut admins = vec![identifier.clone()];
+        for admin in node_state.config().setup().admins() {
+            if !admins.contains(admin) {
+                admins.push(admin.clone());
+            }
+        }
+
         let mut s = Self {
             cli_state,
             node_name: general_options.node_name,
             api_transport_flow_control_id: transport_options.api_transport_flow_control_id,
             tcp_transport: transport_options.tcp_transport,
+            udp_transport: Default::default(),
+            uds_transport: Default::default(),
+            ws_transport: Default::default(),
             controller_identity_id: Self::load_controller_identity_id()?,
             skip_defaults: general_options.skip_defaults,
+            forwarder_buffer: general_options.forwarder_buffer,
             enable_credential_checks: trust_options.trust_context_config.is_some()
                 && trust_options
                     .trust_context_config
@@ -351,7 +484,8 @@ impl NodeManager {
                     .unwrap()
                     .authority()
                     .is_ok(),
-            identifier: node_state.config().identifier().await?,
+            identifier,
+            admins,
             secure_channels,
             projects: Arc::new(projects_options.projects),
             trust_context: None,
@@ -369,21 +503,39 @@ impl NodeManager {
             info!("NodeManager::create: starting default services");
             if let Some(tc) = trust_options.trust_context_config {
                 info!("NodeManager::create: configuring trust context");
-                s.configure_trust_context(&tc).await?;
+                s.configure_trust_context(ctx, &tc).await?;
             }
         }
 
         Ok(s)
     }
 
-    async fn configure_trust_context(&mut self, tc: &TrustContextConfig) -> Result<()> {
-        self.trust_context = Some(
-            tc.to_trust_context(
+    async fn configure_trust_context(
+        &mut self,
+        ctx: &Context,
+        tc: &TrustContextConfig,
+    ) -> Result<()> {
+        let trust_context = tc
+            .to_trust_context(
                 self.secure_channels.clone(),
                 Some(self.tcp_transport.async_try_clone().await?),
+                Some(self.cli_state.credentials.retrieved()?),
             )
-            .await?,
-        );
+            .await?;
+
+        // A credential issued by a credential issuer is renewed before it expires
+        if let Ok(CredentialRetrieverConfig::FromCredentialIssuer(_)) =
+            tc.authority().and_then(|a| a.own_credential())
+        {
+            CredentialRenewal::start(
+                ctx,
+                trust_context.authority()?.clone(),
+                self.identifier(),
+                DEFAULT_CREDENTIAL_RENEWAL_MARGIN,
+            )
+            .await?;
+        }
+        self.trust_context = Some(trust_context);
 
         info!("NodeManager::configure_trust_context: trust context configured");
 
@@ -401,12 +553,18 @@ impl NodeManager {
         self.start_uppercase_service_impl(ctx, DefaultAddress::UPPERCASE_SERVICE.into())
             .await?;
 
+        let mut forwarding_service_options = ForwardingServiceOptions::new()
+            .service_as_consumer(api_flow_control_id)
+            .forwarder_as_consumer(api_flow_control_id);
+        if let Some(forwarder_buffer) = &self.forwarder_buffer {
+            forwarding_service_options =
+                forwarding_service_options.with_buffer(forwarder_buffer.clone());
+        }
+        self.registry.hosted_forwarders = forwarding_service_options.registry();
         ForwardingService::create(
             ctx,
             DefaultAddress::FORWARDING_SERVICE,
-            ForwardingServiceOptions::new()
-                .service_as_consumer(api_flow_control_id)
-                .forwarder_as_consumer(api_flow_control_id),
+            forwarding_service_options,
         )
         .await?;
 
@@ -415,6 +573,7 @@ impl NodeManager {
             None, // Not checking identifiers here in favor of credential check
             None,
             None,
+            XXCurve::default(),
             ctx,
         )
         .await?;
@@ -433,7 +592,8 @@ impl NodeManager {
         Ok(())
     }
 
-    /// Resolve project ID (if any), create secure channel (if needed) and create a tcp connection
+    /// Resolve project ID (if any), create secure channel (if needed) and create a transport
+    /// connection (tcp, udp, websocket or unix socket)
     /// Returns [`ConnectionInstance`]
     pub(crate) async fn connect(
         node_manager: Arc<RwLock<NodeManager>>,
@@ -459,8 +619,20 @@ impl NodeManager {
                 connection.identity_name.map(|x| x.to_string()),
             ))
             .await?
+            .instantiate(WebSocketInstantiator::new(
+                context.clone(),
+                node_manager.clone(),
+            ))
+            .await?
             .instantiate(PlainTcpInstantiator::new(tcp_transport))
             .await?
+            .instantiate(PlainUdpInstantiator::new(
+                context.clone(),
+                node_manager.clone(),
+            ))
+            .await?
+            .instantiate(UnixInstantiator::new(context.clone(), node_manager.clone()))
+            .await?
             .instantiate(SecureChannelInstantiator::new(
                 context.clone(),
                 node_manager.clone(),
@@ -472,6 +644,23 @@ impl NodeManager {
 
         debug!("connected to {connection_instance:?}");
 
+        if connection.direct_path {
+            match connection_instance.secure_channel_encryptors.last() {
+                Some(channel) => {
+                    let ctx = connection.ctx.async_try_clone().await?;
+                    let channel = channel.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) =
+                            upgrade_to_direct_path(ctx, node_manager, channel.clone()).await
+                        {
+                            warn!(%channel, "failed to set up a direct path: {e}");
+                        }
+                    });
+                }
+                None => warn!("a direct path can only be set up for a secure channel"),
+            }
+        }
+
         if connection.add_default_consumers {
             connection_instance
                 .add_consumer(&context, &DefaultAddress::SECURE_CHANNEL_LISTENER.into());
@@ -578,6 +767,11 @@ impl NodeManagerWorker {
                 self.delete_tcp_listener(req, dec).await?.to_vec()?
             }
 
+            // ==*== WebSocket Listeners ==*==
+            (Post, ["node", "ws", "listener"]) => {
+                self.create_ws_listener(req, dec, ctx).await?.to_vec()?
+            }
+
             // ==*== Credential ==*==
             (Post, ["node", "credentials", "actions", "get"]) => self
                 .get_credential(req, dec, ctx)
@@ -586,6 +780,9 @@ impl NodeManagerWorker {
             (Post, ["node", "credentials", "actions", "present"]) => {
                 self.present_credential(req, dec, ctx).await?.to_vec()?
             }
+            (Get, ["node", "credentials", "cache"]) => {
+                self.credentials_cache_status(req).await.to_vec()?
+            }
 
             // ==*== Secure channels ==*==
             // TODO: Change to RequestBuilder format
@@ -638,6 +835,9 @@ impl NodeManagerWorker {
             (Post, ["node", "services", DefaultAddress::HOP_SERVICE]) => {
                 self.start_hop_service(ctx, req, dec).await?.to_vec()?
             }
+            (Post, ["node", "services", DefaultAddress::STREAM_SERVICE]) => {
+                self.start_stream_service(ctx, req, dec).await?.to_vec()?
+            }
             (Post, ["node", "services", DefaultAddress::DIRECT_AUTHENTICATOR]) => self
                 .start_authenticator_service(ctx, req, dec)
                 .await?
@@ -645,6 +845,17 @@ impl NodeManagerWorker {
             (Post, ["node", "services", DefaultAddress::VERIFIER]) => {
                 self.start_verifier_service(ctx, req, dec).await?.to_vec()?
             }
+            (Post, ["node", "services", DefaultAddress::SIGNER]) => {
+                self.start_signer_service(ctx, req, dec).await?.to_vec()?
+            }
+            (Post, ["node", "services", DefaultAddress::DIRECT_PATH_SERVICE]) => self
+                .start_direct_path_service(ctx, req, dec)
+                .await?
+                .to_vec()?,
+            (Post, ["node", "services", DefaultAddress::RENDEZVOUS_SERVICE]) => self
+                .start_rendezvous_service(ctx, req, dec)
+                .await?
+                .to_vec()?,
             (Post, ["node", "services", DefaultAddress::CREDENTIALS_SERVICE]) => self
                 .start_credentials_service(ctx, req, dec)
                 .await?
@@ -671,6 +882,9 @@ impl NodeManagerWorker {
                 .await?
                 .to_vec()?,
             (Get, ["node", "services"]) => self.list_services(req).await?,
+            (Get, ["node", "rendezvous", "registrations"]) => {
+                self.list_rendezvous_registrations(ctx, req, dec).await?
+            }
             (Get, ["node", "services", service_type]) => {
                 self.list_services_of_type(req, service_type).await?
             }
@@ -680,11 +894,14 @@ impl NodeManagerWorker {
                 self.show_forwarder(req, remote_address).await?.to_vec()?
             }
             (Get, ["node", "forwarder"]) => {
-                let forwarder_registry = {
+                let (forwarder_registry, hosted_forwarders) = {
                     let node_manager = self.node_manager.read().await;
-                    &node_manager.registry.forwarders.clone()
+                    (
+                        &node_manager.registry.forwarders.clone(),
+                        node_manager.registry.hosted_forwarders.list(),
+                    )
                 };
-                self.get_forwarders(req, forwarder_registry)
+                self.get_forwarders(req, forwarder_registry, hosted_forwarders)
                     .await
                     .to_vec()?
             }
@@ -719,6 +936,9 @@ impl NodeManagerWorker {
             (Delete, ["node", "inlet", alias]) => self.delete_inlet(req, alias).await?.to_vec()?,
             (Delete, ["node", "portal"]) => todo!(),
 
+            // ==*== Sessions ==*==
+            (Get, ["node", "sessions"]) => self.list_sessions(req).await.to_vec()?,
+
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 self.add_consumer(ctx, req, dec)?.to_vec()?
@@ -854,8 +1074,9 @@ impl Worker for NodeManagerWorker {
             .start_echoer_service_impl(ctx, DefaultAddress::ECHO_SERVICE.into())
             .await?;
 
-        ctx.flow_controls()
-            .add_consumer(DefaultAddress::RPC_PROXY, &api_flow_control_id);
+        // Remote requests to the node manager and the RPC proxy service must come
+        // from the admins of the node, through a secure channel
+        node_manager.create_node_manager_listener(ctx).await?;
         ctx.start_worker(DefaultAddress::RPC_PROXY, RpcProxyService::new())
             .await?;
 
@@ -0,0 +1,84 @@
+use ockam::identity::{AuthorityService, IdentityIdentifier, Timestamp};
+use ockam_core::{Address, AllowSourceAddress, DenyAll, Result, Routed, Worker};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Delay before trying again to renew a credential when the authority could not be reached
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// This worker renews the credential of a node before it expires.
+///
+/// Without it, a credential is only renewed when it is needed, for example to create a
+/// secure channel, so that the first connection made after the expiry has to wait for
+/// the authority, and fails if the authority can't be reached at that moment.
+pub(crate) struct CredentialRenewal {
+    authority: AuthorityService,
+    identifier: IdentityIdentifier,
+    renewal_margin: Duration,
+    heartbeat: DelayedEvent<Vec<u8>>,
+}
+
+impl CredentialRenewal {
+    /// Start renewing the credential of `identifier` when it expires in less than
+    /// `renewal_margin`
+    pub(crate) async fn start(
+        ctx: &Context,
+        authority: AuthorityService,
+        identifier: IdentityIdentifier,
+        renewal_margin: Duration,
+    ) -> Result<Address> {
+        let address = Address::random_tagged("CredentialRenewal");
+        let mut heartbeat = DelayedEvent::create(ctx, address.clone(), vec![]).await?;
+        // the first credential is retrieved as soon as the node starts
+        heartbeat.schedule(Duration::ZERO).await?;
+        let heartbeat_address = heartbeat.address();
+
+        let renewal = Self {
+            authority,
+            identifier,
+            renewal_margin,
+            heartbeat,
+        };
+        WorkerBuilder::new(renewal)
+            .with_address(address.clone())
+            .with_incoming_access_control(AllowSourceAddress(heartbeat_address))
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)
+            .await?;
+        Ok(address)
+    }
+
+    /// Return the delay before the next renewal of the current credential
+    fn next_renewal(&self) -> Duration {
+        let (Some(now), Some(expires)) = (Timestamp::now(), self.authority.credential_expires_at())
+        else {
+            return RETRY_INTERVAL;
+        };
+        expires
+            .elapsed(now.add_seconds(self.renewal_margin.as_secs()))
+            .unwrap_or_default()
+            .max(RETRY_INTERVAL)
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for CredentialRenewal {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, ctx: &mut Context, _m: Routed<Self::Message>) -> Result<()> {
+        let delay = match self.authority.credential(ctx, &self.identifier).await {
+            Ok(_) => self.next_renewal(),
+            Err(e) => {
+                warn!("failed to renew the credential of {}: {e}", self.identifier);
+                RETRY_INTERVAL
+            }
+        };
+        debug!(
+            "the credential of {} is renewed in {delay:?}",
+            self.identifier
+        );
+        self.heartbeat.schedule(delay).await
+    }
+}
@@ -12,7 +12,9 @@ use ockam_node::Context;
 use crate::cli_state::traits::StateDirTrait;
 use crate::error::ApiError;
 use crate::local_multiaddr_to_route;
-use crate::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
+use crate::nodes::models::credentials::{
+    CredentialsCacheStatus, GetCredentialRequest, PresentCredentialRequest,
+};
 use crate::nodes::service::map_multiaddr_err;
 
 use super::NodeManagerWorker;
@@ -50,6 +52,17 @@ impl NodeManagerWorker {
         }
     }
 
+    /// Return the number of verified credentials cached by the node and how often
+    /// the cache spared a verification
+    pub(super) async fn credentials_cache_status(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<CredentialsCacheStatus> {
+        let node_manager = self.node_manager.read().await;
+        let cache = node_manager.identities().credentials_cache();
+        Response::ok(req.id()).body(CredentialsCacheStatus::new(cache.len(), cache.stats()))
+    }
+
     pub(super) async fn present_credential(
         &self,
         req: &Request<'_>,
@@ -6,7 +6,7 @@ use ockam::compat::asynchronous::RwLock;
 use ockam::compat::sync::Mutex;
 use ockam::identity::IdentityIdentifier;
 use ockam::remote::{RemoteForwarder, RemoteForwarderInfo, RemoteForwarderOptions};
-use ockam::Result;
+use ockam::{ForwarderStatus, Result};
 use ockam_core::api::{Id, Request, Response, ResponseBuilder, Status};
 use ockam_core::AsyncTryClone;
 use ockam_multiaddr::MultiAddr;
@@ -166,12 +166,14 @@ impl NodeManagerWorker {
         &mut self,
         req: &Request<'a>,
         registry: &'a BTreeMap<String, RemoteForwarderInfo>,
+        hosted_forwarders: Vec<ForwarderStatus>,
     ) -> ResponseBuilder<Vec<ForwarderInfo<'a>>> {
         debug!("Handling ListForwarders request");
         Response::ok(req.id()).body(
             registry
                 .iter()
                 .map(|(_, registry_info)| ForwarderInfo::from(registry_info.to_owned()))
+                .chain(hosted_forwarders.into_iter().map(ForwarderInfo::from))
                 .collect(),
         )
     }
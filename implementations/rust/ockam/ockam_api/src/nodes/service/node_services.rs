@@ -1,45 +1,60 @@
 use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use minicbor::Decoder;
 
+use ockam::compat::tokio::sync::RwLock;
 use ockam::{Address, Context, Result};
-use ockam_abac::expr::{and, eq, ident, str};
+use ockam_abac::expr::{and, eq, ident, or, str};
 
 use ockam_abac::{Action, Env, Expr, PolicyAccessControl, Resource};
 use ockam_core::api::{Request, Response, ResponseBuilder};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{route, IncomingAccessControl};
-use ockam_identity::{identities, AuthorityService, CredentialsIssuer, TrustContext};
+use ockam_identity::{
+    identities, AuthorityService, CredentialsIssuer, IdentityIdentifier, InMemoryStorage,
+    LmdbStorage, Storage, TrustContext,
+};
 
 use ockam_multiaddr::MultiAddr;
 use ockam_node::WorkerBuilder;
+use ockam_transport_udp::{UdpRendezvousClient, UdpRendezvousService, UdpRendezvousServiceOptions};
 
 use crate::auth::Server;
 use crate::authenticator::direct::EnrollmentTokenAuthenticator;
+use crate::cli_state::StateDirTrait;
 use crate::echoer::Echoer;
 use crate::error::ApiError;
 use crate::hop::Hop;
 use crate::identity::IdentityService;
+use crate::kafka::{ConsumerDiscoveryService, OutletManagerService, PrefixForwarderService};
 use crate::kafka::{
     KafkaInletController, KafkaPortalListener, KafkaSecureChannelControllerImpl,
     KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
 };
-use crate::kafka::{OutletManagerService, PrefixForwarderService};
+use crate::local_multiaddr_to_route;
+use crate::nodes::connection::{Connection, DirectPathService};
 use crate::nodes::models::portal::CreateInlet;
 use crate::nodes::models::services::{
-    DeleteServiceRequest, ServiceList, ServiceStatus, StartAuthenticatedServiceRequest,
-    StartAuthenticatorRequest, StartCredentialsService, StartEchoerServiceRequest,
-    StartHopServiceRequest, StartIdentityServiceRequest, StartKafkaConsumerRequest,
-    StartKafkaOutletRequest, StartKafkaProducerRequest, StartOktaIdentityProviderRequest,
-    StartServiceRequest, StartUppercaseServiceRequest, StartVerifierService,
+    DeleteServiceRequest, KafkaRecordEncryption, ListRendezvousRegistrations,
+    RendezvousRegistration, RendezvousRegistrationList, ServiceList, ServiceStatus,
+    StartAuthenticatedServiceRequest, StartAuthenticatorRequest, StartCredentialsService,
+    StartDirectPathService, StartEchoerServiceRequest, StartHopServiceRequest,
+    StartIdentityServiceRequest, StartKafkaConsumerRequest, StartKafkaOutletRequest,
+    StartKafkaProducerRequest, StartOktaIdentityProviderRequest, StartRendezvousService,
+    StartServiceRequest, StartSignerService, StartStreamServiceRequest,
+    StartUppercaseServiceRequest, StartVerifierService,
 };
 use crate::nodes::registry::{
-    AuthenticatorServiceInfo, CredentialsServiceInfo, KafkaServiceInfo, KafkaServiceKind, Registry,
+    AuthenticatorServiceInfo, CredentialsServiceInfo, DirectPathServiceInfo, KafkaServiceInfo,
+    KafkaServiceKind, Registry, RendezvousServiceInfo, SignerServiceInfo, StreamServiceInfo,
     VerifierServiceInfo,
 };
 use crate::nodes::NodeManager;
 use crate::port_range::PortRange;
+use crate::stream::{StreamIndexService, StreamLogOptions, StreamService};
 use crate::uppercase::Uppercase;
 use crate::DefaultAddress;
 use crate::{actions, resources};
@@ -187,6 +202,214 @@ impl NodeManager {
         Ok(())
     }
 
+    pub(super) async fn start_stream_service_impl(
+        &mut self,
+        ctx: &Context,
+        addr: Address,
+        index_addr: Address,
+        path: Option<PathBuf>,
+        options: StreamLogOptions,
+    ) -> Result<()> {
+        options.validate()?;
+        if self.registry.stream_services.contains_key(&addr) {
+            return Err(ApiError::generic("Stream service exists at this address"));
+        }
+        if self
+            .registry
+            .stream_index_services
+            .contains_key(&index_addr)
+        {
+            return Err(ApiError::generic(
+                "Stream index service exists at this address",
+            ));
+        }
+
+        let path = match path {
+            Some(path) => path,
+            None => self.cli_state.nodes.get(&self.node_name)?.streams_dir(),
+        };
+        std::fs::create_dir_all(&path)
+            .map_err(|e| ApiError::generic(&format!("Unable to create {path:?}: {e}")))?;
+        let storage: Arc<dyn Storage> = Arc::new(LmdbStorage::new(path.join("index.lmdb")).await?);
+
+        let service = StreamService::new(
+            path.join("logs"),
+            options,
+            Some(self.api_transport_flow_control_id.clone()),
+        );
+        ctx.flow_controls()
+            .add_consumer(addr.clone(), &self.api_transport_flow_control_id);
+        ctx.start_worker(addr.clone(), service).await?;
+
+        ctx.flow_controls()
+            .add_consumer(index_addr.clone(), &self.api_transport_flow_control_id);
+        ctx.start_worker(index_addr.clone(), StreamIndexService::new(storage))
+            .await?;
+
+        self.registry
+            .stream_services
+            .insert(addr, StreamServiceInfo::default());
+        self.registry
+            .stream_index_services
+            .insert(index_addr, StreamServiceInfo::default());
+
+        Ok(())
+    }
+
+    pub(super) async fn start_signer_service_impl(
+        &mut self,
+        ctx: &Context,
+        addr: Address,
+        authorized: &[IdentityIdentifier],
+    ) -> Result<()> {
+        if self.registry.signer_services.contains_key(&addr) {
+            return Err(ApiError::generic("Signer service exists at this address"));
+        }
+        if authorized.is_empty() {
+            return Err(ApiError::generic(
+                "At least one identity must be authorized to use the signer service",
+            ));
+        }
+
+        // The signer is only reachable via the default secure channel listener,
+        // never directly from the transport
+        let flow_control_id = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .ok_or_else(|| {
+                ApiError::generic("Unable to get flow control for secure channel listener")
+            })?;
+        ctx.flow_controls()
+            .add_consumer(addr.clone(), &flow_control_id);
+
+        // The policy is always replaced so that only the identities given when
+        // starting the service are allowed to sign
+        let action = actions::HANDLE_MESSAGE;
+        let resource = Resource::new(&addr.to_string());
+        let rule = or(authorized
+            .iter()
+            .map(|id| eq([ident("subject.identifier"), str(id.to_string())])));
+        self.policies.set_policy(&resource, &action, &rule).await?;
+        let mut env = Env::new();
+        env.put("resource.id", str(resource.as_str()));
+        env.put("action.id", str(action.as_str()));
+        let abac = Arc::new(PolicyAccessControl::new(
+            self.policies.clone(),
+            self.identities_repository(),
+            resource,
+            action,
+            env,
+        ));
+
+        let signer = crate::signer::Server::new(self.identities().vault());
+        WorkerBuilder::new(signer)
+            .with_address(addr.clone())
+            .with_incoming_access_control_arc(abac)
+            .start(ctx)
+            .await?;
+
+        self.registry
+            .signer_services
+            .insert(addr, SignerServiceInfo::default());
+
+        Ok(())
+    }
+
+    pub(super) async fn start_direct_path_service_impl(
+        &mut self,
+        ctx: &Context,
+        addr: Address,
+        rendezvous: MultiAddr,
+        node_manager: Arc<RwLock<NodeManager>>,
+    ) -> Result<()> {
+        if self.registry.direct_path_services.contains_key(&addr) {
+            return Err(ApiError::generic(
+                "Direct path service exists at this address",
+            ));
+        }
+
+        // Requests must be sent through the secure channel to move
+        let flow_control_id = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .ok_or_else(|| {
+                ApiError::generic("Unable to get flow control for secure channel listener")
+            })?;
+        ctx.flow_controls()
+            .add_consumer(addr.clone(), &flow_control_id);
+
+        let service = DirectPathService::new(node_manager, rendezvous.clone())?;
+        ctx.start_worker(addr.clone(), service).await?;
+
+        self.registry
+            .direct_path_services
+            .insert(addr, DirectPathServiceInfo::new(rendezvous));
+
+        Ok(())
+    }
+
+    pub(super) async fn start_rendezvous_service_impl(
+        &mut self,
+        ctx: &Context,
+        addr: Address,
+        udp_address: SocketAddr,
+        anonymous_registrations: bool,
+    ) -> Result<()> {
+        if self.registry.rendezvous_services.contains_key(&addr) {
+            return Err(ApiError::generic(
+                "Rendezvous service exists at this address",
+            ));
+        }
+
+        // Queries and listing follow the policies of the node for this service
+        let resource = Resource::new(addr.address());
+        let query_policy = self
+            .get_or_set_policy(&resource, &actions::QUERY, &Expr::Bool(true))
+            .await?;
+        let list_policy = self
+            .get_or_set_policy(&resource, &actions::LIST, &Expr::Bool(false))
+            .await?;
+        let mut options = UdpRendezvousServiceOptions::new(self.identities_repository())
+            .with_query_policy(query_policy)
+            .with_list_policy(list_policy);
+        if anonymous_registrations {
+            options = options.with_anonymous_registrations();
+        }
+
+        // Registrations are sent through secure channels, announcements over UDP
+        let flow_control_id = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .ok_or_else(|| {
+                ApiError::generic("Unable to get flow control for secure channel listener")
+            })?;
+        self.udp_transport(ctx)
+            .await?
+            .listen(udp_address.to_string())
+            .await?;
+        ctx.flow_controls()
+            .add_consumer(addr.clone(), &flow_control_id);
+        UdpRendezvousService::start(ctx, addr.clone(), options).await?;
+
+        self.registry
+            .rendezvous_services
+            .insert(addr, RendezvousServiceInfo::default());
+
+        Ok(())
+    }
+
+    /// Return the policy of the node for a resource and an action, storing the
+    /// given default policy if there is none
+    async fn get_or_set_policy(&self, r: &Resource, a: &Action, default: &Expr) -> Result<Expr> {
+        match self.policies.get_policy(r, a).await? {
+            Some(policy) => Ok(policy),
+            None => {
+                self.policies.set_policy(r, a, default).await?;
+                Ok(default.clone())
+            }
+        }
+    }
+
     async fn build_access_control(
         &self,
         r: &Resource,
@@ -310,6 +533,7 @@ impl NodeManager {
         let (issuer, acceptor) = EnrollmentTokenAuthenticator::new_worker_pair(
             project.clone(),
             self.attributes_writer(),
+            InMemoryStorage::create(),
         );
         let rule = and([
             eq([ident("resource.project_id"), ident("subject.project_id")]),
@@ -436,6 +660,32 @@ impl NodeManagerWorker {
         Ok(Response::ok(req.id()))
     }
 
+    pub(super) async fn start_stream_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let req_body: StartStreamServiceRequest = dec.decode()?;
+        let mut options = StreamLogOptions::default();
+        if let Some(max_segment_size) = req_body.max_segment_size {
+            options.max_segment_size = max_segment_size;
+        }
+        options.retention_size = req_body.retention_size;
+        options.retention_age = req_body.retention_duration();
+        node_manager
+            .start_stream_service_impl(
+                ctx,
+                req_body.addr.to_string().into(),
+                req_body.index_addr.to_string().into(),
+                req_body.path.as_ref().map(|p| PathBuf::from(p.as_ref())),
+                options,
+            )
+            .await?;
+        Ok(Response::ok(req.id()))
+    }
+
     //TODO: split this into the different services it really starts
     pub(super) async fn start_authenticator_service<'a>(
         &mut self,
@@ -529,6 +779,109 @@ impl NodeManagerWorker {
         Ok(Response::ok(req.id()))
     }
 
+    pub(super) async fn start_signer_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let body: StartSignerService = dec.decode()?;
+        let addr: Address = body.address().into();
+
+        node_manager
+            .start_signer_service_impl(ctx, addr, body.authorized())
+            .await?;
+
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn start_direct_path_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let body: StartDirectPathService = dec.decode()?;
+        let addr: Address = body.address().into();
+        let rendezvous = MultiAddr::from_str(body.rendezvous())
+            .map_err(|e| ApiError::message(format!("invalid rendezvous address: {e}")))?;
+
+        let node_manager = self.node_manager.clone();
+        self.node_manager
+            .write()
+            .await
+            .start_direct_path_service_impl(ctx, addr, rendezvous, node_manager)
+            .await?;
+
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn start_rendezvous_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let body: StartRendezvousService = dec.decode()?;
+        let addr: Address = body.address().into();
+        let udp_address = SocketAddr::from_str(body.udp_address())
+            .map_err(|e| ApiError::message(format!("invalid UDP address: {e}")))?;
+
+        self.node_manager
+            .write()
+            .await
+            .start_rendezvous_service_impl(ctx, addr, udp_address, body.anonymous_registrations())
+            .await?;
+
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn list_rendezvous_registrations(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: ListRendezvousRegistrations = dec.decode()?;
+        let rendezvous = MultiAddr::from_str(body.rendezvous())
+            .map_err(|e| ApiError::message(format!("invalid rendezvous address: {e}")))?;
+
+        // Listing is restricted by the service to some identities, hence the secure channel
+        let connection = Connection::new(ctx, &rendezvous);
+        let connection_instance =
+            NodeManager::connect(self.node_manager.clone(), connection).await?;
+        let route = local_multiaddr_to_route(&connection_instance.normalized_addr)
+            .ok_or_else(|| ApiError::generic("Invalid rendezvous route"))?;
+        let entries = match UdpRendezvousClient::new(ctx, route).await {
+            Ok(client) => client.list().await,
+            Err(e) => Err(e),
+        };
+
+        let mut node_manager = self.node_manager.write().await;
+        for encryptor in &connection_instance.secure_channel_encryptors {
+            if let Err(error) = node_manager.delete_secure_channel(ctx, encryptor).await {
+                debug!("cannot delete secure channel `{encryptor}`: {error}");
+            }
+        }
+        drop(node_manager);
+
+        let list = entries?
+            .into_iter()
+            .map(|entry| {
+                RendezvousRegistration::new(
+                    entry.puncher_name(),
+                    entry.identifier().map(|i| i.to_string()),
+                    entry.route().to_string(),
+                    entry.expires_in(),
+                )
+            })
+            .collect();
+        Ok(Response::ok(req.id())
+            .body(RendezvousRegistrationList::new(list))
+            .to_vec()?)
+    }
+
     pub(super) async fn start_credentials_service<'a>(
         &mut self,
         ctx: &Context,
@@ -588,6 +941,15 @@ impl NodeManagerWorker {
 
         {
             let node_manager = self.node_manager.write().await;
+            ConsumerDiscoveryService::create(
+                context,
+                node_manager.secure_channels.clone(),
+                node_manager.trust_context()?.id(),
+                default_secure_channel_listener_flow_control_id.clone(),
+                node_manager.registry.hosted_forwarders.clone(),
+            )
+            .await?;
+
             OutletManagerService::create(
                 context,
                 node_manager.secure_channels.clone(),
@@ -604,6 +966,7 @@ impl NodeManagerWorker {
             KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string(),
             Some(KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string()),
             false,
+            None,
         )
         .await?;
 
@@ -630,6 +993,7 @@ impl NodeManagerWorker {
             body_req.brokers_port_range(),
             outlet_node_multiaddr,
             KafkaServiceKind::Consumer,
+            Default::default(),
         )
         .await?;
 
@@ -646,6 +1010,13 @@ impl NodeManagerWorker {
         let listener_address: Address = body.address().into();
         let body_req = body.request();
         let outlet_node_multiaddr = body_req.project_route().to_string().parse()?;
+        let record_encryption = body_req.record_encryption();
+        if record_encryption.keys && record_encryption.deterministic_key_secret.is_none() {
+            warn!(
+                "the keys of the records are removed, records sent to compacted topics \
+                will be rejected by the brokers unless a deterministic key secret is set"
+            );
+        }
 
         self.start_kafka_service_impl(
             context,
@@ -656,6 +1027,7 @@ impl NodeManagerWorker {
             body_req.brokers_port_range(),
             outlet_node_multiaddr,
             KafkaServiceKind::Producer,
+            record_encryption,
         )
         .await?;
 
@@ -673,6 +1045,7 @@ impl NodeManagerWorker {
         brokers_port_range: (u16, u16),
         outlet_node_multiaddr: MultiAddr,
         kind: KafkaServiceKind,
+        record_encryption: KafkaRecordEncryption,
     ) -> Result<()> {
         debug!(
             "outlet_node_multiaddr: {}",
@@ -681,10 +1054,18 @@ impl NodeManagerWorker {
 
         let trust_context_id;
         let secure_channels;
+        let consumer_id;
         {
             let node_manager = self.node_manager.read().await;
             trust_context_id = node_manager.trust_context()?.id().to_string();
             secure_channels = node_manager.secure_channels.clone();
+            // the consumer identifier is unique for each kafka service of the node, and
+            // can't contain '_', see is_consumer_forwarder
+            consumer_id = format!(
+                "{}-{}",
+                node_manager.identifier(),
+                hex::encode(local_interceptor_address.address())
+            );
 
             if let Some(project) = outlet_node_multiaddr.first().and_then(|value| {
                 value
@@ -709,6 +1090,7 @@ impl NodeManagerWorker {
             secure_channels,
             outlet_node_multiaddr.clone(),
             trust_context_id,
+            consumer_id,
         );
 
         let inlet_controller = KafkaInletController::new(
@@ -743,6 +1125,7 @@ impl NodeManagerWorker {
             inlet_controller,
             secure_channel_controller.into_trait(),
             local_interceptor_address.clone(),
+            record_encryption,
         )
         .await?;
 
@@ -847,6 +1230,34 @@ impl NodeManagerWorker {
         registry.verifier_services.keys().for_each(|addr| {
             list.push(ServiceStatus::new(addr.address(), DefaultAddress::VERIFIER))
         });
+        registry
+            .signer_services
+            .keys()
+            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), DefaultAddress::SIGNER)));
+        registry.direct_path_services.keys().for_each(|addr| {
+            list.push(ServiceStatus::new(
+                addr.address(),
+                DefaultAddress::DIRECT_PATH_SERVICE,
+            ))
+        });
+        registry.rendezvous_services.keys().for_each(|addr| {
+            list.push(ServiceStatus::new(
+                addr.address(),
+                DefaultAddress::RENDEZVOUS_SERVICE,
+            ))
+        });
+        registry.stream_services.keys().for_each(|addr| {
+            list.push(ServiceStatus::new(
+                addr.address(),
+                DefaultAddress::STREAM_SERVICE,
+            ))
+        });
+        registry.stream_index_services.keys().for_each(|addr| {
+            list.push(ServiceStatus::new(
+                addr.address(),
+                DefaultAddress::STREAM_INDEX_SERVICE,
+            ))
+        });
         registry.credentials_services.keys().for_each(|addr| {
             list.push(ServiceStatus::new(
                 addr.address(),
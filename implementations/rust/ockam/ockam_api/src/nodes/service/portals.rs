@@ -2,7 +2,7 @@ use crate::error::ApiError;
 use crate::local_multiaddr_to_route;
 use crate::nodes::connection::{Connection, ConnectionInstance};
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, InletList, InletStatus, OutletAllowlist, OutletList, OutletStatus,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
 use crate::nodes::service::random_alias;
@@ -21,7 +21,7 @@ use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::asynchronous::RwLock;
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions};
+use ockam_transport_tcp::{TcpInletOptions, TcpOutletAllowlist, TcpOutletOptions};
 use std::collections::BTreeMap;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -92,6 +92,10 @@ impl NodeManagerWorker {
 
         info!("Handling request to create inlet portal");
 
+        if let Some(Err(reason)) = req.retry_policy().map(|p| p.validate()) {
+            return Ok(Response::bad_request(rid).body(InletStatus::bad_request(reason)));
+        }
+
         debug! {
             prefix = %req.prefix_route(),
             suffix = %req.suffix_route(),
@@ -112,9 +116,12 @@ impl NodeManagerWorker {
                 .wait_for_outlet_duration()
                 .unwrap_or(Duration::from_secs(5));
 
-            let connection = Connection::new(ctx, req.outlet_addr())
+            let mut connection = Connection::new(ctx, req.outlet_addr())
                 .with_authorized_identity(req.authorized())
                 .with_timeout(duration);
+            if req.direct_path() {
+                connection = connection.with_direct_path();
+            }
 
             NodeManager::connect(manager.clone(), connection).await?
         };
@@ -163,10 +170,17 @@ impl NodeManagerWorker {
 
         let options = TcpInletOptions::new().with_incoming_access_control(access_control.clone());
 
-        let res = node_manager
-            .tcp_transport
-            .create_inlet(listen_addr.clone(), outlet_route.clone(), options)
-            .await;
+        let res = if req.socks5() {
+            node_manager
+                .tcp_transport
+                .create_socks5_inlet(listen_addr.clone(), outlet_route.clone(), options)
+                .await
+        } else {
+            node_manager
+                .tcp_transport
+                .create_inlet(listen_addr.clone(), outlet_route.clone(), options)
+                .await
+        };
 
         Ok(match res {
             Ok((socket_address, worker_addr)) => {
@@ -174,13 +188,11 @@ impl NodeManagerWorker {
                 //in the returned socket address
                 let listen_addr = socket_address.to_string();
 
-                // TODO: Use better way to store inlets?
-                node_manager.registry.inlets.insert(
-                    alias.clone(),
-                    InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
-                );
-                if !connection_instance.normalized_addr.is_empty() {
+                let session = if !connection_instance.normalized_addr.is_empty() {
                     let mut session = Session::new(connection_instance.transport_route.clone());
+                    if let Some(retry_policy) = req.retry_policy() {
+                        session.set_retry_policy(retry_policy);
+                    }
 
                     let ctx = Arc::new(ctx.async_try_clone().await?);
                     let repl = replacer(
@@ -193,11 +205,22 @@ impl NodeManagerWorker {
                         req.suffix_route().clone(),
                         req.authorized(),
                         access_control.clone(),
+                        req.direct_path(),
+                        req.socks5(),
                         ctx,
                     );
                     session.set_replacer(repl);
-                    node_manager.sessions.lock().unwrap().add(session);
-                }
+                    Some(node_manager.sessions.lock().unwrap().add(session))
+                } else {
+                    None
+                };
+
+                // TODO: Use better way to store inlets?
+                node_manager.registry.inlets.insert(
+                    alias.clone(),
+                    InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route)
+                        .with_session(session),
+                );
 
                 Response::ok(rid).body(InletStatus::new(
                     listen_addr,
@@ -282,13 +305,16 @@ impl NodeManagerWorker {
         info!(%alias, "Handling request to show inlet portal");
         if let Some(inlet_to_show) = node_manager.registry.inlets.get(alias) {
             debug!(%alias, "Inlet not found in node registry");
-            Ok(Response::ok(req.id()).body(InletStatus::new(
-                inlet_to_show.bind_addr.to_string(),
-                inlet_to_show.worker_addr.to_string(),
-                alias,
-                None,
-                inlet_to_show.outlet_route.to_string(),
-            )))
+            Ok(Response::ok(req.id()).body(
+                InletStatus::new(
+                    inlet_to_show.bind_addr.to_string(),
+                    inlet_to_show.worker_addr.to_string(),
+                    alias,
+                    None,
+                    inlet_to_show.outlet_route.to_string(),
+                )
+                .with_session(node_manager.session_status(inlet_to_show.session.as_ref())),
+            ))
         } else {
             error!(%alias, "Inlet not found in the node registry");
             Ok(Response::not_found(req.id()).body(InletStatus::new(
@@ -312,6 +338,7 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
+            allowlist,
             ..
         } = dec.decode()?;
 
@@ -324,10 +351,12 @@ impl NodeManagerWorker {
             worker_addr.into(),
             alias.map(|a| a.0.into()),
             reachable_from_default_secure_channel,
+            allowlist,
         )
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_outlet_impl<'a>(
         &mut self,
         ctx: &Context,
@@ -336,6 +365,7 @@ impl NodeManagerWorker {
         worker_addr: String,
         alias: Option<String>,
         reachable_from_default_secure_channel: bool,
+        allowlist: Option<OutletAllowlist>,
     ) -> Result<ResponseBuilder<OutletStatus<'a>>> {
         let mut node_manager = self.node_manager.write().await;
         let resource = alias
@@ -360,6 +390,14 @@ impl NodeManagerWorker {
             .await?;
 
         let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
+        let options = if allowlist.is_some() {
+            let connection_access_control = node_manager
+                .destination_access_control(&resource, &actions::CONNECT, trust_context_id)
+                .await?;
+            options.with_connection_access_control(connection_access_control)
+        } else {
+            options
+        };
         let options = if !check_credential {
             options.as_consumer(&node_manager.api_transport_flow_control_id)
         } else {
@@ -380,10 +418,23 @@ impl NodeManagerWorker {
             options
         };
 
-        let res = node_manager
-            .tcp_transport
-            .create_outlet(worker_addr.clone(), tcp_addr.clone(), options)
-            .await;
+        let res = match allowlist {
+            Some(allowlist) => match tcp_outlet_allowlist(allowlist) {
+                Ok(allowlist) => {
+                    node_manager
+                        .tcp_transport
+                        .create_dynamic_outlet(worker_addr.clone(), allowlist, options)
+                        .await
+                }
+                Err(e) => Err(e),
+            },
+            None => {
+                node_manager
+                    .tcp_transport
+                    .create_outlet(worker_addr.clone(), tcp_addr.clone(), options)
+                    .await
+            }
+        };
 
         Ok(match res {
             Ok(_) => {
@@ -488,6 +539,21 @@ impl NodeManagerWorker {
     }
 }
 
+/// Convert the allowlist of a request to the allowlist of a dynamic outlet
+fn tcp_outlet_allowlist(allowlist: OutletAllowlist) -> Result<TcpOutletAllowlist> {
+    let mut tcp_allowlist = TcpOutletAllowlist::new();
+    for network in &allowlist.networks {
+        tcp_allowlist = tcp_allowlist.allow_network(network)?;
+    }
+    for host in allowlist.hosts {
+        tcp_allowlist = tcp_allowlist.allow_host(host);
+    }
+    for (start, end) in allowlist.ports {
+        tcp_allowlist = tcp_allowlist.allow_port_range(start..=end);
+    }
+    Ok(tcp_allowlist)
+}
+
 /// Create a session replacer.
 ///
 /// This returns a function that accepts the previous ping address (e.g.
@@ -504,6 +570,8 @@ fn replacer(
     suffix_route: Route,
     auth: Option<IdentityIdentifier>,
     access: Arc<dyn IncomingAccessControl>,
+    direct_path: bool,
+    socks5: bool,
     ctx: Arc<Context>,
 ) -> Replacer {
     let connection_instance_arc = Arc::new(Mutex::new(connection_instance));
@@ -553,9 +621,12 @@ fn replacer(
                 drop(node_manager);
 
                 // Now a connection attempt is made:
-                let connection = Connection::new(ctx.as_ref(), &addr)
+                let mut connection = Connection::new(ctx.as_ref(), &addr)
                     .with_authorized_identity(auth)
                     .with_timeout(MAX_CONNECT_TIME);
+                if direct_path {
+                    connection = connection.with_direct_path();
+                }
 
                 let new_connection_instance =
                     NodeManager::connect(node_manager_arc.clone(), connection).await?;
@@ -575,11 +646,19 @@ fn replacer(
                 let options = TcpInletOptions::new().with_incoming_access_control(access);
 
                 // Finally attempt to create a new inlet using the new route:
-                let new_inlet_address = node_manager
-                    .tcp_transport
-                    .create_inlet(bind, normalized_route, options)
-                    .await?
-                    .1;
+                let new_inlet_address = if socks5 {
+                    node_manager
+                        .tcp_transport
+                        .create_socks5_inlet(bind, normalized_route, options)
+                        .await?
+                        .1
+                } else {
+                    node_manager
+                        .tcp_transport
+                        .create_inlet(bind, normalized_route, options)
+                        .await?
+                        .1
+                };
                 *inlet_address_arc.lock().unwrap() = new_inlet_address;
 
                 Ok(new_connection_instance.transport_route.clone())
@@ -600,3 +679,42 @@ fn replacer(
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::models::session::RetryPolicy;
+    use crate::nodes::send_to_node_manager;
+    use crate::test_utils::start_manager_for_tests;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn create_inlet_with_an_invalid_retry_policy(context: &mut Context) -> Result<()> {
+        let _handle = start_manager_for_tests(context).await?;
+
+        let mut payload = CreateInlet::to_node(
+            "127.0.0.1:0".parse().unwrap(),
+            "/service/outlet".parse().unwrap(),
+            Route::new().into(),
+            Route::new().into(),
+            None,
+        );
+        payload.set_retry_policy(RetryPolicy::default().with_jitter(f64::NAN));
+
+        let response = send_to_node_manager(
+            context,
+            Request::post("/node/inlet").body(payload).to_vec()?,
+        )
+        .await?;
+
+        let mut decoder = Decoder::new(&response);
+        let header: Response = decoder.decode()?;
+        assert_eq!(header.status(), Some(Status::BadRequest));
+        let status: InletStatus = decoder.decode()?;
+        assert_eq!(
+            status.payload.as_deref(),
+            Some("the session retry jitter must be a number between 0 and 1")
+        );
+        context.stop().await
+    }
+}
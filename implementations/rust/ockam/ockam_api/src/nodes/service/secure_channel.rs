@@ -5,7 +5,7 @@ use minicbor::Decoder;
 use ockam::identity::TrustEveryonePolicy;
 use ockam::identity::{
     Identities, IdentitiesVault, IdentityIdentifier, SecureChannelListenerOptions,
-    SecureChannelOptions, SecureChannels, TrustMultiIdentifiersPolicy,
+    SecureChannelOptions, SecureChannels, TrustMultiIdentifiersPolicy, XXCurve,
 };
 use ockam::{Address, Result, Route};
 use ockam_core::api::{Request, Response, ResponseBuilder};
@@ -28,12 +28,13 @@ use crate::nodes::models::secure_channel::{
 use crate::nodes::registry::{Registry, SecureChannelListenerInfo};
 use crate::nodes::service::invalid_multiaddr_error;
 use crate::nodes::service::NodeIdentities;
-use crate::nodes::NodeManager;
+use crate::nodes::{NodeManager, NODEMANAGER_ADDR, NODEMANAGER_LISTENER_ADDR};
 use crate::{multiaddr_to_route, DefaultAddress};
 
 use super::{map_multiaddr_err, NodeManagerWorker};
 
 impl NodeManager {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create_secure_channel_internal(
         &mut self,
         identifier: &IdentityIdentifier,
@@ -42,9 +43,11 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         timeout: Option<Duration>,
         credential: Option<Credential>,
+        curve: XXCurve,
+        disclosed_attributes: Option<Vec<String>>,
     ) -> Result<SecureChannel> {
         debug!(%sc_route, "Creating secure channel");
-        let options = SecureChannelOptions::new();
+        let options = SecureChannelOptions::new().with_key_exchange_curve(curve);
 
         let options = if let Some(timeout) = timeout {
             options.with_timeout(timeout)
@@ -58,6 +61,13 @@ impl NodeManager {
             options
         };
 
+        let options = if let Some(names) = disclosed_attributes {
+            let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+            options.with_disclosed_attributes(&names)
+        } else {
+            options
+        };
+
         let options = match authorized_identifiers.clone() {
             Some(ids) => options.with_trust_policy(TrustMultiIdentifiersPolicy::new(ids)),
             None => options.with_trust_policy(TrustEveryonePolicy),
@@ -92,6 +102,8 @@ impl NodeManager {
         identity_name: Option<String>,
         ctx: &Context,
         credential_name: Option<String>,
+        curve: XXCurve,
+        disclosed_attributes: Option<Vec<String>>,
     ) -> Result<SecureChannel> {
         let identifier = self.get_identifier(identity_name.clone()).await?;
         let provided_credential = if let Some(credential_name) = credential_name {
@@ -143,6 +155,8 @@ impl NodeManager {
                 authorized_identifiers,
                 timeout,
                 credential,
+                curve,
+                disclosed_attributes,
             )
             .await?;
 
@@ -156,6 +170,7 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         vault_name: Option<String>,
         identity_name: Option<String>,
+        curve: XXCurve,
         ctx: &Context,
     ) -> Result<SecureChannelListener> {
         debug!(
@@ -166,8 +181,9 @@ impl NodeManager {
         let secure_channels = self.build_secure_channels(vault_name.clone()).await?;
         let identifier = self.get_identifier(identity_name.clone()).await?;
 
-        let options =
-            SecureChannelListenerOptions::new().as_consumer(&self.api_transport_flow_control_id);
+        let options = SecureChannelListenerOptions::new()
+            .as_consumer(&self.api_transport_flow_control_id)
+            .with_key_exchange_curve(curve);
 
         let options = match authorized_identifiers {
             Some(ids) => options.with_trust_policy(TrustMultiIdentifiersPolicy::new(ids)),
@@ -207,6 +223,31 @@ impl NodeManager {
         Ok(listener)
     }
 
+    /// Create the secure channel listener through which the admins of the node
+    /// reach its node manager and its RPC proxy service
+    pub(super) async fn create_node_manager_listener(&self, ctx: &Context) -> Result<()> {
+        let options = SecureChannelListenerOptions::new()
+            .as_consumer(&self.api_transport_flow_control_id)
+            .with_trust_policy(TrustMultiIdentifiersPolicy::new(self.admins.clone()));
+
+        let listener = self
+            .secure_channels
+            .create_secure_channel_listener(
+                ctx,
+                &self.identifier,
+                NODEMANAGER_LISTENER_ADDR,
+                options,
+            )
+            .await?;
+
+        ctx.flow_controls()
+            .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
+        ctx.flow_controls()
+            .add_consumer(DefaultAddress::RPC_PROXY, listener.flow_control_id());
+
+        Ok(())
+    }
+
     /// Build a SecureChannels struct for a specific vault if one is specified
     /// Otherwise return the shared SecureChannels
     pub(crate) async fn build_secure_channels(
@@ -321,6 +362,8 @@ impl NodeManagerWorker {
             timeout,
             identity_name: identity,
             credential_name,
+            key_exchange_curve,
+            disclosed_attributes,
             ..
         } = dec.decode()?;
 
@@ -363,6 +406,9 @@ impl NodeManagerWorker {
                 identity.map(|i| i.to_string()),
                 ctx,
                 credential_name.map(|c| c.to_string()),
+                key_exchange_curve.map(XXCurve::from).unwrap_or_default(),
+                disclosed_attributes
+                    .map(|names| names.into_iter().map(|n| n.to_string()).collect()),
             )
             .await?;
 
@@ -429,6 +475,7 @@ impl NodeManagerWorker {
             authorized_identifiers,
             vault,
             identity,
+            key_exchange_curve,
             ..
         } = dec.decode()?;
 
@@ -455,6 +502,7 @@ impl NodeManagerWorker {
                 authorized_identifiers,
                 vault.map(|v| v.to_string()),
                 identity.map(|v| v.to_string()),
+                key_exchange_curve.map(XXCurve::from).unwrap_or_default(),
                 ctx,
             )
             .await?;
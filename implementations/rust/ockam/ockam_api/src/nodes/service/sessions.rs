@@ -0,0 +1,23 @@
+use crate::nodes::models::session::{SessionList, SessionStatus};
+use crate::session::sessions::Key;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Status of a session monitored by the Medic
+    pub(super) fn session_status(&self, key: Option<&Key>) -> Option<SessionStatus> {
+        let sessions = self.sessions.lock().unwrap();
+        key.and_then(|k| sessions.session(k)).map(|s| s.to_status())
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn list_sessions(&self, req: &Request<'_>) -> ResponseBuilder<SessionList> {
+        let node_manager = self.node_manager.read().await;
+        let sessions = node_manager.sessions.lock().unwrap();
+        Response::ok(req.id()).body(SessionList::new(
+            sessions.iter().map(|(_, s)| s.to_status()).collect(),
+        ))
+    }
+}
@@ -1,6 +1,7 @@
+use crate::error::ApiError;
 use crate::nodes::models::transport::{
-    CreateTcpConnection, CreateTcpListener, DeleteTransport, TransportList, TransportMode,
-    TransportStatus, TransportType,
+    CreateTcpConnection, CreateTcpListener, CreateWebSocketListener, DeleteTransport,
+    TransportList, TransportMode, TransportStatus, TransportType,
 };
 use crate::nodes::service::ApiTransport;
 use minicbor::Decoder;
@@ -12,6 +13,7 @@ use ockam_node::Context;
 use ockam_transport_tcp::{
     TcpConnectionOptions, TcpListenerInfo, TcpListenerOptions, TcpSenderInfo, TcpTransport,
 };
+use ockam_transport_websocket::{TlsServerConfig, WebSocketListenerOptions};
 use std::net::SocketAddr;
 
 use super::NodeManagerWorker;
@@ -278,6 +280,54 @@ impl NodeManagerWorker {
         Ok(response)
     }
 
+    pub(super) async fn create_ws_listener(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        ctx: &Context,
+    ) -> Result<ResponseBuilder<TransportStatus>> {
+        let node_manager = self.node_manager.read().await;
+        let CreateWebSocketListener {
+            addr,
+            tls_certificate_file,
+            tls_private_key_file,
+            ..
+        } = dec.decode()?;
+
+        info!(
+            "Handling request to create a new websocket listener: {}",
+            addr
+        );
+
+        let options = match (tls_certificate_file, tls_private_key_file) {
+            (Some(certificate), Some(private_key)) => WebSocketListenerOptions::new()
+                .with_tls(TlsServerConfig::from_pem_files(certificate, private_key)?),
+            (None, None) => WebSocketListenerOptions::new(),
+            _ => {
+                return Err(ApiError::generic(
+                    "both a certificate and a private key are needed to accept wss connections",
+                ))
+            }
+        };
+        let socket_address = node_manager
+            .ws_transport(ctx)
+            .await?
+            .listen_with_options(&addr, options)
+            .await?;
+
+        Ok(
+            Response::ok(req.id()).body(TransportStatus::new(ApiTransport {
+                tt: TransportType::WebSocket,
+                tm: TransportMode::Listen,
+                socket_address,
+                worker_address: "<none>".into(),
+                processor_address: "<none>".into(),
+                // WebSocket listeners don't restrict the messages of their connections
+                flow_control_id: FlowControls::generate_flow_control_id(),
+            })),
+        )
+    }
+
     pub(super) async fn delete_tcp_connection(
         &self,
         req: &Request<'_>,
@@ -0,0 +1,165 @@
+use ockam::identity::AttributeValue;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Separator used to access nested claims, for example `realm_access.roles`
+const PATH_SEPARATOR: char = '.';
+
+/// Separator used to join the values of an array claim into a single attribute value
+const VALUES_SEPARATOR: &str = ",";
+
+/// This struct maps a claim of an ID token to an identity attribute.
+///
+/// The claim is given as a path where each segment is the name of a nested JSON member,
+/// for example `address.country` or `realm_access.roles`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimMapping {
+    /// Path of the claim in the ID token
+    pub claim: String,
+
+    /// Name of the identity attribute receiving the claim value
+    pub attribute: String,
+}
+
+impl ClaimMapping {
+    pub fn new(claim: &str, attribute: &str) -> Self {
+        ClaimMapping {
+            claim: claim.to_string(),
+            attribute: attribute.to_string(),
+        }
+    }
+
+    /// Return the value of the mapped claim, if it is present in the claims.
+    ///
+    /// Strings are returned as they are, numbers and booleans are converted to strings and
+    /// arrays of such values are joined with a comma. Objects and null values are ignored.
+    pub fn extract(&self, claims: &Value) -> Option<String> {
+        let value = self
+            .claim
+            .split(PATH_SEPARATOR)
+            .try_fold(claims, |value, segment| value.get(segment))?;
+        match value {
+            Value::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(scalar_to_string)
+                    .collect::<Option<Vec<_>>>()?;
+                Some(values.join(VALUES_SEPARATOR))
+            }
+            value => scalar_to_string(value),
+        }
+    }
+
+    /// Return the mapped claim as a list of values, if it is an array.
+    ///
+    /// The joined value returned by [`ClaimMapping::extract`] is kept for the nodes which
+    /// don't support typed values, while the list lets a policy check for example if
+    /// one of the groups of a member is `admin`. Scalar claims only have a plain value so
+    /// that the existing policies comparing them as strings keep working.
+    pub fn extract_value(&self, claims: &Value) -> Option<AttributeValue> {
+        let value = self
+            .claim
+            .split(PATH_SEPARATOR)
+            .try_fold(claims, |value, segment| value.get(segment))?;
+        match value {
+            Value::Array(values) => values
+                .iter()
+                .map(scalar_to_value)
+                .collect::<Option<Vec<_>>>()
+                .map(AttributeValue::List),
+            _ => None,
+        }
+    }
+}
+
+/// Return the identity attributes corresponding to a set of claims
+pub fn extract_attributes(claims: &Value, mappings: &[ClaimMapping]) -> HashMap<String, String> {
+    mappings
+        .iter()
+        .filter_map(|m| m.extract(claims).map(|v| (m.attribute.clone(), v)))
+        .collect()
+}
+
+/// Return the typed identity attributes corresponding to a set of claims
+pub fn extract_attribute_values(
+    claims: &Value,
+    mappings: &[ClaimMapping],
+) -> BTreeMap<String, AttributeValue> {
+    mappings
+        .iter()
+        .filter_map(|m| m.extract_value(claims).map(|v| (m.attribute.clone(), v)))
+        .collect()
+}
+
+fn scalar_to_value(value: &Value) -> Option<AttributeValue> {
+    match value {
+        Value::String(s) => Some(AttributeValue::Str(s.clone())),
+        Value::Number(n) => n.as_i64().map(AttributeValue::Int),
+        Value::Bool(b) => Some(AttributeValue::Bool(*b)),
+        _ => None,
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_attributes() {
+        let claims = json!({
+            "email": "alice@example.com",
+            "email_verified": true,
+            "level": 3,
+            "groups": ["admin", "dev"],
+            "realm_access": { "roles": ["operator"], "tenant": "acme" },
+            "address": { "country": null },
+            "mixed": ["a", { "b": "c" }]
+        });
+        let mappings = vec![
+            ClaimMapping::new("email", "email"),
+            ClaimMapping::new("email_verified", "email_verified"),
+            ClaimMapping::new("level", "level"),
+            ClaimMapping::new("groups", "groups"),
+            ClaimMapping::new("realm_access.roles", "roles"),
+            ClaimMapping::new("realm_access.tenant", "tenant"),
+            ClaimMapping::new("address.country", "country"),
+            ClaimMapping::new("mixed", "mixed"),
+            ClaimMapping::new("missing.claim", "missing"),
+        ];
+
+        let attributes = extract_attributes(&claims, &mappings);
+        let expected: HashMap<String, String> = [
+            ("email", "alice@example.com"),
+            ("email_verified", "true"),
+            ("level", "3"),
+            ("groups", "admin,dev"),
+            ("roles", "operator"),
+            ("tenant", "acme"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(attributes, expected);
+
+        let values = extract_attribute_values(&claims, &mappings);
+        let expected: BTreeMap<String, AttributeValue> = [
+            ("groups", AttributeValue::from(vec!["admin", "dev"])),
+            ("roles", AttributeValue::from(vec!["operator"])),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        assert_eq!(values, expected);
+    }
+}
@@ -0,0 +1,163 @@
+//! Generic OpenID Connect authenticator.
+//!
+//! Identities can enroll by presenting an ID token issued by an OpenID Connect provider.
+//! The token is validated with the provider keys and some of its claims are stored as
+//! attributes of the enrolling identity.
+
+mod claims;
+mod provider;
+
+pub use claims::*;
+pub use provider::*;
+
+use core::str;
+use minicbor::{Decode, Decoder, Encode};
+use ockam::identity::credential::Timestamp;
+use ockam::identity::{
+    AttributesEntry, IdentityAttributesWriter, IdentityIdentifier, IdentitySecureChannelLocalInfo,
+};
+use ockam_core::api::{self, Method, Request, Response};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{self, CowStr, Result, Routed, Worker};
+use ockam_identity::{secure_channel_required, LEGACY_ID, TRUST_CONTEXT_ID};
+use ockam_node::{Context, RpcClient};
+use tracing::trace;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request sent to the OIDC authenticator to enroll with an ID token
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthenticateOidcToken<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1904416>,
+    #[b(1)] id_token: CowStr<'a>,
+}
+
+impl<'a> AuthenticateOidcToken<'a> {
+    pub fn new(id_token: impl Into<CowStr<'a>>) -> Self {
+        AuthenticateOidcToken {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id_token: id_token.into(),
+        }
+    }
+
+    pub fn id_token(&self) -> &str {
+        &self.id_token
+    }
+}
+
+/// Worker enrolling identities presenting an ID token issued by an OpenID Connect provider
+pub struct Server {
+    attributes_writer: Arc<dyn IdentityAttributesWriter>,
+    project: String,
+    provider: OidcProvider,
+    claims: Vec<ClaimMapping>,
+}
+
+#[ockam_core::worker]
+impl Worker for Server {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        if let Ok(i) = IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            let r = self.on_request(&i.their_identity_id(), m.as_body()).await?;
+            c.send(m.return_route(), r).await
+        } else {
+            secure_channel_required(c, m).await
+        }
+    }
+}
+
+impl Server {
+    pub fn new(
+        attributes_writer: Arc<dyn IdentityAttributesWriter>,
+        project: String,
+        provider: OidcProvider,
+        claims: Vec<ClaimMapping>,
+    ) -> Self {
+        Server {
+            attributes_writer,
+            project,
+            provider,
+            claims,
+        }
+    }
+
+    async fn on_request(&mut self, from: &IdentityIdentifier, data: &[u8]) -> Result<Vec<u8>> {
+        let mut dec = Decoder::new(data);
+        let req: Request = dec.decode()?;
+
+        trace! {
+            target: "ockam_api::oidc::server",
+            from   = %from,
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+        let res = match req.method() {
+            Some(Method::Post) => match req.path_segments::<2>().as_slice() {
+                ["v0", "enroll"] => {
+                    let token: AuthenticateOidcToken = dec.decode()?;
+                    match self.provider.validate_id_token(token.id_token()).await {
+                        Ok(claims) => {
+                            let attributes = extract_attributes(&claims, &self.claims);
+                            let values = extract_attribute_values(&claims, &self.claims);
+                            debug!(%from, ?attributes, "ID token validated");
+                            let entry = AttributesEntry::new(
+                                attributes
+                                    .into_iter()
+                                    .map(|(k, v)| (k, v.as_bytes().to_vec()))
+                                    .chain([
+                                        (LEGACY_ID.to_owned(), self.project.as_bytes().to_vec()),
+                                        (
+                                            TRUST_CONTEXT_ID.to_owned(),
+                                            self.project.as_bytes().to_vec(),
+                                        ),
+                                    ])
+                                    .collect(),
+                                Timestamp::now().unwrap(),
+                                None,
+                                None,
+                            )
+                            .with_values(values);
+                            self.attributes_writer.put_attributes(from, entry).await?;
+                            Response::ok(req.id()).to_vec()?
+                        }
+                        Err(e) => {
+                            warn!(%from, %e, "ID token rejected");
+                            api::forbidden(&req, "invalid ID token").to_vec()?
+                        }
+                    }
+                }
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            _ => api::invalid_method(&req).to_vec()?,
+        };
+        Ok(res)
+    }
+}
+
+/// Client for the OIDC authenticator
+pub struct OidcAuthenticatorClient(RpcClient);
+
+impl OidcAuthenticatorClient {
+    pub fn new(client: RpcClient) -> Self {
+        OidcAuthenticatorClient(client)
+    }
+
+    /// Enroll the identity at the origin of the secure channel with an ID token
+    pub async fn enroll(&self, id_token: &str) -> Result<()> {
+        self.0
+            .request_no_resp_body(
+                &Request::post("/v0/enroll").body(AuthenticateOidcToken::new(id_token)),
+            )
+            .await
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::ApiError;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use ockam_core::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Path of the discovery document, relative to the issuer URL
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// Minimum interval between two retrievals of the provider keys, so that tokens
+/// signed with unknown keys can't be used to flood the provider with requests
+const MIN_JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Signature algorithms accepted for ID tokens.
+/// Symmetric algorithms are excluded since the provider keys are retrieved from a public JWKS
+const ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+/// Subset of the OpenID Connect discovery document used to validate ID tokens
+#[derive(Debug, Clone, Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    jwks_uri: String,
+}
+
+/// An OpenID Connect provider.
+///
+/// The discovery document and the provider keys are retrieved lazily, when the first
+/// ID token is validated. The keys are fetched again when a token is signed with an
+/// unknown key, in order to support key rotation, but at most once per minute.
+pub struct OidcProvider {
+    issuer_url: String,
+    client_id: String,
+    client: reqwest::Client,
+    discovery: Option<DiscoveryDocument>,
+    jwks: JwkSet,
+    jwks_retrieved_at: Option<Instant>,
+}
+
+impl OidcProvider {
+    /// Create a new provider for a given issuer and client id (the expected ID token audience).
+    /// If a PEM certificate is given, it is used as the only trusted root certificate to access
+    /// the provider.
+    pub fn new(issuer_url: &str, client_id: &str, certificate: Option<&str>) -> Result<Self> {
+        let mut builder = reqwest::ClientBuilder::new();
+        if let Some(certificate) = certificate {
+            let certificate = reqwest::Certificate::from_pem(certificate.as_bytes())
+                .map_err(|err| ApiError::generic(&err.to_string()))?;
+            builder = builder
+                .tls_built_in_root_certs(false)
+                .add_root_certificate(certificate);
+        }
+        let client = builder
+            .build()
+            .map_err(|err| ApiError::generic(&err.to_string()))?;
+        Ok(OidcProvider {
+            issuer_url: issuer_url.trim_end_matches('/').to_string(),
+            client_id: client_id.to_string(),
+            client,
+            discovery: None,
+            jwks: JwkSet { keys: vec![] },
+            jwks_retrieved_at: None,
+        })
+    }
+
+    /// Validate an ID token and return its claims.
+    ///
+    /// The token signature is checked with the provider keys and the issuer, audience
+    /// and expiration claims are checked as well.
+    pub async fn validate_id_token(&mut self, id_token: &str) -> Result<Value> {
+        let header = decode_header(id_token)
+            .map_err(|e| ApiError::generic(&format!("invalid ID token header: {e}")))?;
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(ApiError::generic(&format!(
+                "unsupported ID token algorithm {:?}",
+                header.alg
+            )));
+        }
+        let discovery = self.discovery().await?;
+        let key = self.decoding_key(header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[discovery.issuer]);
+        validation.set_audience(&[self.client_id.as_str()]);
+        let token = decode::<Value>(id_token, &key, &validation)
+            .map_err(|e| ApiError::generic(&format!("invalid ID token: {e}")))?;
+        Ok(token.claims)
+    }
+
+    /// Return the discovery document, retrieving it if necessary
+    async fn discovery(&mut self) -> Result<DiscoveryDocument> {
+        if let Some(discovery) = &self.discovery {
+            return Ok(discovery.clone());
+        }
+        let url = format!("{}{}", self.issuer_url, DISCOVERY_PATH);
+        debug!(%url, "retrieving the OIDC discovery document");
+        let discovery: DiscoveryDocument = self.get_json(&url).await?;
+        // the issuer of the discovery document must be the configured issuer,
+        // see https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfigurationValidation
+        if discovery.issuer.trim_end_matches('/') != self.issuer_url {
+            return Err(ApiError::generic(&format!(
+                "the OIDC discovery document issuer {} doesn't match the issuer {}",
+                discovery.issuer, self.issuer_url
+            )));
+        }
+        self.discovery = Some(discovery.clone());
+        Ok(discovery)
+    }
+
+    /// Return the key used to verify a token signature.
+    /// The provider keys are retrieved again if the key is not found
+    async fn decoding_key(&mut self, kid: Option<&str>) -> Result<DecodingKey> {
+        if let Some(key) = self.find_key(kid)? {
+            return Ok(key);
+        }
+        if let Some(retrieved_at) = self.jwks_retrieved_at {
+            if retrieved_at.elapsed() < MIN_JWKS_REFETCH_INTERVAL {
+                return Err(ApiError::generic("unknown ID token signing key"));
+            }
+        }
+        let jwks_uri = self.discovery().await?.jwks_uri;
+        debug!(%jwks_uri, "retrieving the OIDC provider keys");
+        self.jwks_retrieved_at = Some(Instant::now());
+        self.jwks = self.get_json(&jwks_uri).await?;
+        self.find_key(kid)?
+            .ok_or_else(|| ApiError::generic("unknown ID token signing key"))
+    }
+
+    /// Find a key by id. If no key id is given, the provider must have a single key
+    fn find_key(&self, kid: Option<&str>) -> Result<Option<DecodingKey>> {
+        let jwk = match kid {
+            Some(kid) => self.jwks.find(kid),
+            None if self.jwks.keys.len() == 1 => self.jwks.keys.first(),
+            None => None,
+        };
+        jwk.map(|jwk| {
+            DecodingKey::from_jwk(jwk).map_err(|e| ApiError::generic(&format!("invalid JWK: {e}")))
+        })
+        .transpose()
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ApiError::generic(&format!("failed to access {url}: {e}")))?
+            .json()
+            .await
+            .map_err(|e| ApiError::generic(&format!("failed to decode {url}: {e}")))
+    }
+}
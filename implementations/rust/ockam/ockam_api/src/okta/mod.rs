@@ -3,7 +3,8 @@ use core::str;
 use minicbor::Decoder;
 use ockam::identity::credential::Timestamp;
 use ockam::identity::{
-    AttributesEntry, IdentityAttributesWriter, IdentityIdentifier, IdentitySecureChannelLocalInfo,
+    AttributeValue, AttributesEntry, IdentityAttributesWriter, IdentityIdentifier,
+    IdentitySecureChannelLocalInfo,
 };
 use ockam_core::api;
 use ockam_core::api::{Method, Request, Response};
@@ -12,8 +13,8 @@ use ockam_core::{self, Result, Routed, Worker};
 use ockam_identity::{LEGACY_ID, TRUST_CONTEXT_ID};
 use ockam_node::Context;
 use reqwest::StatusCode;
-use std::collections::HashMap;
-use tracing::trace;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{trace, warn};
 
 pub struct Server {
     attributes_writer: Arc<dyn IdentityAttributesWriter>,
@@ -83,7 +84,7 @@ impl Server {
                     let token: crate::cloud::enroll::auth0::AuthenticateAuth0Token =
                         dec.decode()?;
                     debug!("device code received: {token:#?}");
-                    if let Some(attrs) = self.check_token(&token.access_token.0).await? {
+                    if let Some((attrs, values)) = self.check_token(&token.access_token.0).await? {
                         //TODO in some future, we will want to track that this entry
                         //     was added by the okta addon.
                         //     But for that we would need to give a separate identity to this
@@ -106,7 +107,8 @@ impl Server {
                             Timestamp::now().unwrap(),
                             None,
                             None,
-                        );
+                        )
+                        .with_values(values);
                         self.attributes_writer.put_attributes(from, entry).await?;
                         Response::ok(req.id()).to_vec()?
                     } else {
@@ -120,7 +122,12 @@ impl Server {
         Ok(res)
     }
 
-    async fn check_token(&mut self, token: &str) -> Result<Option<HashMap<String, String>>> {
+    /// Return the configured claims of the user, as plain attributes for the string claims
+    /// and as typed attributes for the other ones, like a list of groups
+    async fn check_token(
+        &mut self,
+        token: &str,
+    ) -> Result<Option<(HashMap<String, String>, BTreeMap<String, AttributeValue>)>> {
         let client = reqwest::ClientBuilder::new()
             .tls_built_in_root_certs(false)
             .add_root_certificate(self.certificate.clone())
@@ -142,12 +149,22 @@ impl Server {
                         .map_err(|_err| ApiError::generic("Failed to authenticate with Okta"))?;
                     debug!("userinfo received: {doc:?}");
                     let mut custom_attrs = HashMap::new();
+                    let mut custom_values = BTreeMap::new();
                     for a in self.attributes.iter() {
-                        if let Some(v) = doc.get(a).and_then(|v| v.as_str()) {
-                            custom_attrs.insert(a.to_owned(), v.to_string());
+                        match doc.get(a) {
+                            Some(serde_json::Value::String(v)) => {
+                                custom_attrs.insert(a.to_owned(), v.to_string());
+                            }
+                            Some(v) => match serde_json::from_value::<AttributeValue>(v.clone()) {
+                                Ok(v) => {
+                                    custom_values.insert(a.to_owned(), v);
+                                }
+                                Err(_) => warn!("ignoring the unsupported value of the claim {a}"),
+                            },
+                            None => {}
                         }
                     }
-                    Ok(Some(custom_attrs))
+                    Ok(Some((custom_attrs, custom_values)))
                 }
                 _ => Ok(None),
             }
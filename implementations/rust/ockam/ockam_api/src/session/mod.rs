@@ -13,13 +13,10 @@ use ockam_node::Context;
 use ockam_node::{tokio, WorkerBuilder};
 use tracing as log;
 
-const MAX_FAILURES: usize = 3;
-const RETRY_DELAY: Duration = Duration::from_secs(5);
 const DELAY: Duration = Duration::from_secs(3);
 
 #[derive(Debug)]
 pub struct Medic {
-    retry_delay: Duration,
     delay: Duration,
     sessions: Arc<Mutex<Sessions>>,
     pings: JoinSet<(Key, Result<(), Error>)>,
@@ -36,7 +33,6 @@ pub struct Message {
 impl Medic {
     pub fn new() -> Self {
         Self {
-            retry_delay: RETRY_DELAY,
             delay: DELAY,
             sessions: Arc::new(Mutex::new(Sessions::new())),
             pings: JoinSet::new(),
@@ -64,7 +60,8 @@ impl Medic {
     /// Continuously check all sessions.
     ///
     /// This method never returns. It will ping all healthy sessions and
-    /// trigger replacements for the unhealthy ones.
+    /// trigger replacements for the unhealthy ones, after a delay given
+    /// by the retry policy of each session.
     async fn go(mut self, ctx: Context, mut rx: mpsc::Receiver<Message>) -> ! {
         let ctx = Arc::new(ctx);
         loop {
@@ -72,7 +69,7 @@ impl Medic {
             {
                 let mut sessions = self.sessions.lock().unwrap();
                 for (&key, session) in sessions.iter_mut() {
-                    if session.pings().len() < MAX_FAILURES {
+                    if session.pings().len() < session.retry_policy().max_failures() as usize {
                         let m = Message::new(session.key());
                         session.add_ping(m.ping);
                         let l = {
@@ -114,11 +111,18 @@ impl Medic {
                         match session.status() {
                             Status::Up | Status::Down => {
                                 log::warn!(%key, "session unresponsive");
+                                if session.status() == Status::Up {
+                                    session.set_last_error(format!(
+                                        "no response to {} pings",
+                                        session.pings().len()
+                                    ));
+                                }
                                 let f = session.replacement(session.ping_route().clone());
                                 session.set_status(Status::Degraded);
-                                log::info!(%key, "replacing session");
+                                let delay = session.schedule_retry();
+                                log::info!(%key, ?delay, "replacing session");
                                 self.replacements.spawn(async move {
-                                    sleep(self.retry_delay).await;
+                                    sleep(delay).await;
                                     (key, f.await)
                                 });
                             }
@@ -150,16 +154,14 @@ impl Medic {
                         log::warn!(key = %k, err = %e, "replacing session failed");
                         let mut sessions = self.sessions.lock().unwrap();
                         if let Some(s) = sessions.session_mut(&k) {
-                           s.set_status(Status::Down);
+                            s.replacement_failed(&e);
                         }
                     }
                     Some(Ok((k, Ok(ping_route)))) => {
                         let mut sessions = self.sessions.lock().unwrap();
                         if let Some(s) = sessions.session_mut(&k) {
                             log::info!(key = %k, ping_route = %ping_route, "replacement is up");
-                            s.set_status(Status::Up);
-                            s.set_ping_address(ping_route);
-                            s.clear_pings();
+                            s.replacement_succeeded(ping_route);
                         }
                     }
                 },
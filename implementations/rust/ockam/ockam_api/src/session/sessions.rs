@@ -1,7 +1,8 @@
 use core::fmt;
 use core::future::Future;
 use core::pin::Pin;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use minicbor::bytes::ByteArray;
 use minicbor::{Decode, Encode};
@@ -11,12 +12,17 @@ use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::rand;
 use ockam_core::{Error, Route};
 
+pub use crate::nodes::models::session::{RetryPolicy, SessionEvent, SessionStatus, Status};
+
 //most sessions replacer are dependent on the node manager, if many session
 //fails concurrently, which is the common scenario we need extra time
 //to account for the lock contention
 pub const MAX_RECOVERY_TIME: Duration = Duration::from_secs(30);
 pub const MAX_CONNECT_TIME: Duration = Duration::from_secs(5);
 
+/// Number of status changes kept for each session
+const MAX_EVENTS: usize = 10;
+
 pub type Replacement = Pin<Box<dyn Future<Output = Result<Route, Error>> + Send>>;
 pub type Replacer = Box<dyn FnMut(Route) -> Replacement + Send>;
 
@@ -31,13 +37,11 @@ pub struct Session {
     status: Status,
     replace: Replacer,
     pings: Vec<Ping>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Status {
-    Down,
-    Degraded,
-    Up,
+    retry_policy: RetryPolicy,
+    failed_attempts: u32,
+    last_error: Option<String>,
+    next_retry: Option<SystemTime>,
+    events: VecDeque<SessionEvent>,
 }
 
 impl fmt::Debug for Session {
@@ -47,6 +51,9 @@ impl fmt::Debug for Session {
             .field("ping_route", &self.ping_route)
             .field("status", &self.status)
             .field("pings", &self.pings)
+            .field("retry_policy", &self.retry_policy)
+            .field("failed_attempts", &self.failed_attempts)
+            .field("last_error", &self.last_error)
             .finish()
     }
 }
@@ -70,7 +77,6 @@ impl Sessions {
         k
     }
 
-    #[allow(unused)]
     pub fn session(&self, k: &Key) -> Option<&Session> {
         self.map.get(k)
     }
@@ -79,7 +85,6 @@ impl Sessions {
         self.map.get_mut(k)
     }
 
-    #[allow(unused)]
     pub fn iter(&self) -> impl Iterator<Item = (&Key, &Session)> + '_ {
         self.map.iter()
     }
@@ -97,6 +102,11 @@ impl Session {
             status: Status::Up,
             replace: Box::new(move |r| Box::pin(async move { Ok(r) })),
             pings: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+            failed_attempts: 0,
+            last_error: None,
+            next_retry: None,
+            events: VecDeque::new(),
         }
     }
 
@@ -116,10 +126,81 @@ impl Session {
         self.status
     }
 
+    /// Change the status of the session, recording the change if the status is different
     pub fn set_status(&mut self, s: Status) {
+        if self.status == s {
+            return;
+        }
+        log::info! {
+            target: "ockam_api::session",
+            key = %self.key,
+            from = %self.status,
+            to = %s,
+            error = ?self.last_error,
+            "session status changed"
+        }
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(SessionEvent::new(
+            self.status,
+            s,
+            unix_time(SystemTime::now()),
+            self.last_error.clone(),
+        ));
         self.status = s
     }
 
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    pub fn set_retry_policy(&mut self, p: RetryPolicy) {
+        self.retry_policy = p
+    }
+
+    pub fn set_last_error(&mut self, e: impl Into<String>) {
+        self.last_error = Some(e.into())
+    }
+
+    /// Compute the delay before the next replacement attempt
+    pub fn schedule_retry(&mut self) -> Duration {
+        let delay = backoff(&self.retry_policy, self.failed_attempts, rand::random());
+        self.next_retry = Some(SystemTime::now() + delay);
+        delay
+    }
+
+    /// Record a failed replacement attempt
+    pub fn replacement_failed(&mut self, e: &Error) {
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        self.next_retry = None;
+        self.set_last_error(e.to_string());
+        self.set_status(Status::Down)
+    }
+
+    /// Record a successful replacement, using the new route to ping the session
+    pub fn replacement_succeeded(&mut self, ping_route: Route) {
+        self.failed_attempts = 0;
+        self.next_retry = None;
+        self.set_ping_address(ping_route);
+        self.clear_pings();
+        self.set_status(Status::Up);
+        self.last_error = None
+    }
+
+    pub fn to_status(&self) -> SessionStatus {
+        let mut status = SessionStatus::new(
+            self.key.to_string(),
+            self.ping_route.to_string(),
+            self.status,
+        );
+        status.last_error = self.last_error.clone();
+        status.next_retry = self.next_retry.map(unix_time);
+        status.failed_attempts = self.failed_attempts;
+        status.events = self.events.iter().cloned().collect();
+        status
+    }
+
     pub fn replacement(&mut self, ping_route: Route) -> Replacement {
         (self.replace)(ping_route)
     }
@@ -141,6 +222,26 @@ impl Session {
     }
 }
 
+/// Delay before a replacement attempt, after a number of failed attempts.
+///
+/// `random` is a number between 0 and 1 used to remove a random part of the delay.
+fn backoff(policy: &RetryPolicy, failed_attempts: u32, random: f64) -> Duration {
+    let factor = policy
+        .multiplier()
+        .checked_pow(failed_attempts)
+        .unwrap_or(u32::MAX);
+    let delay = policy
+        .initial_delay()
+        .checked_mul(factor)
+        .unwrap_or(Duration::MAX)
+        .min(policy.max_delay());
+    delay.mul_f64(1.0 - policy.jitter() * random.clamp(0.0, 1.0))
+}
+
+fn unix_time(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Encode, Decode)]
 #[rustfmt::skip]
 pub struct Key(#[n(0)] ByteArray<24>);
@@ -172,3 +273,57 @@ impl fmt::Display for Ping {
         write!(f, "{:x}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_maximum_delay() {
+        let policy = RetryPolicy::default()
+            .with_initial_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(10))
+            .with_multiplier(2)
+            .with_jitter(0.5);
+
+        assert_eq!(backoff(&policy, 0, 0.0), Duration::from_secs(1));
+        assert_eq!(backoff(&policy, 2, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff(&policy, 4, 0.0), Duration::from_secs(10));
+        assert_eq!(backoff(&policy, 100, 0.0), Duration::from_secs(10));
+
+        // the jitter removes at most half of the delay
+        assert_eq!(backoff(&policy, 2, 1.0), Duration::from_secs(2));
+        let delay = backoff(&policy, 2, 0.3);
+        assert!(delay > Duration::from_secs(2) && delay < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn status_changes_are_recorded() {
+        let mut session = Session::new(Route::new().into());
+        session.set_status(Status::Degraded);
+        session.replacement_failed(&Error::new(
+            ockam_core::errcode::Origin::Api,
+            ockam_core::errcode::Kind::Io,
+            "unreachable",
+        ));
+        session.replacement_succeeded(Route::new().into());
+
+        let status = session.to_status();
+        assert_eq!(status.status, Status::Up);
+        assert_eq!(status.failed_attempts, 0);
+        let events: Vec<_> = status.events.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(
+            events,
+            vec![
+                (Status::Up, Status::Degraded),
+                (Status::Degraded, Status::Down),
+                (Status::Down, Status::Up)
+            ]
+        );
+        assert!(status.events[1]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("unreachable"));
+    }
+}
@@ -0,0 +1,225 @@
+pub mod types;
+
+use core::fmt;
+use minicbor::Decoder;
+use ockam::identity::IdentitiesVault;
+use ockam::vault::{
+    PublicKey, SecretAttributes, SecretType, SecurityModule, Signature, VaultError,
+    VaultSecurityModule,
+};
+use ockam_core::api::{self, decode_option, Id};
+use ockam_core::api::{Error, Method, Request, Response};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{self, async_trait, Address, DenyAll, KeyId, Result, Route, Routed, Worker};
+use ockam_node::api::request;
+use ockam_node::Context;
+use tracing::trace;
+
+use self::types::{KeyIdRequest, PublicKeyRequest, SignRequest, SignResponse};
+
+/// Signer API server.
+///
+/// Exposes the signing operations of a vault so that the secret keys of an identity
+/// can be kept on a dedicated node. This worker must only be reachable via a secure
+/// channel and protected by an access control restricting the identities allowed to use it.
+pub struct Server {
+    vault: Arc<dyn IdentitiesVault>,
+}
+
+#[ockam_core::worker]
+impl Worker for Server {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let r = self.on_request(m.as_body()).await?;
+        c.send(m.return_route(), r).await
+    }
+}
+
+impl Server {
+    pub fn new(vault: Arc<dyn IdentitiesVault>) -> Self {
+        Server { vault }
+    }
+
+    async fn on_request(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut dec = Decoder::new(data);
+
+        let req: Request = match dec.decode() {
+            Ok(rq) => rq,
+            Err(e) => {
+                let err = Error::default().with_message(e.to_string());
+                return Ok(Response::bad_request(Id::default()).body(err).to_vec()?);
+            }
+        };
+
+        trace! {
+            target: "ockam_api::signer::server",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match req.method() {
+            Some(Method::Post) => match req.path_segments::<2>().as_slice() {
+                ["sign"] => {
+                    let sr: SignRequest = dec.decode()?;
+                    let key_id = sr.key_id().to_string();
+                    match self.vault.sign(&key_id, sr.data()).await {
+                        Ok(signature) => Response::ok(req.id())
+                            .body(SignResponse::new(signature.as_ref()))
+                            .to_vec()?,
+                        Err(err) => Self::error(&req, err)?,
+                    }
+                }
+                ["public_key"] => {
+                    let pr: PublicKeyRequest = dec.decode()?;
+                    let key_id = pr.key_id().to_string();
+                    match self.vault.get_public_key(&key_id).await {
+                        Ok(public_key) => Response::ok(req.id()).body(public_key).to_vec()?,
+                        Err(err) => Self::error(&req, err)?,
+                    }
+                }
+                ["key_id"] => {
+                    let kr: KeyIdRequest = dec.decode()?;
+                    match self.vault.get_key_id(kr.public_key()).await {
+                        Ok(key_id) => Response::ok(req.id()).body(key_id).to_vec()?,
+                        Err(err) => Self::error(&req, err)?,
+                    }
+                }
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            _ => api::invalid_method(&req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+
+    fn error(req: &Request, err: ockam_core::Error) -> Result<Vec<u8>> {
+        let body = Error::new(req.path()).with_message(err.to_string());
+        let res = if err.code().kind == Kind::NotFound {
+            Response::not_found(req.id()).body(body).to_vec()?
+        } else {
+            Response::internal_error(req.id()).body(body).to_vec()?
+        };
+        Ok(res)
+    }
+}
+
+/// Security module forwarding the signing operations to a remote signer [`Server`].
+///
+/// Secrets can't be created, imported or exported with this security module, they
+/// stay on the signer node. Signatures are verified locally.
+pub struct RemoteSecurityModule {
+    ctx: Context,
+    route: Route,
+    verifier: Arc<dyn SecurityModule>,
+}
+
+impl fmt::Debug for RemoteSecurityModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSecurityModule")
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl RemoteSecurityModule {
+    /// Create a security module sending its requests to the signer at the end of `route`.
+    /// The route is expected to go through a secure channel
+    pub async fn new(route: Route, ctx: &Context) -> Result<Self> {
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("RemoteSecurityModule.detached"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        Ok(RemoteSecurityModule {
+            ctx,
+            route,
+            verifier: VaultSecurityModule::create(),
+        })
+    }
+
+    /// Create a security module and return it as a trait object
+    pub async fn create(route: Route, ctx: &Context) -> Result<Arc<dyn SecurityModule>> {
+        Ok(Arc::new(Self::new(route, ctx).await?))
+    }
+
+    fn not_found(what: &str) -> ockam_core::Error {
+        ockam_core::Error::new(
+            Origin::Vault,
+            Kind::NotFound,
+            format!("{what} not found on the remote signer"),
+        )
+    }
+}
+
+#[async_trait]
+impl SecurityModule for RemoteSecurityModule {
+    /// Secrets are only created on the signer node
+    async fn create_secret(&self, _attributes: SecretAttributes) -> Result<KeyId> {
+        Err(ockam_core::Error::new(
+            Origin::Vault,
+            Kind::Unsupported,
+            "secrets can not be created with a remote signer",
+        ))
+    }
+
+    async fn get_public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
+        let label = "get public key";
+        let req = Request::post("/public_key").body(PublicKeyRequest::new(key_id.as_str()));
+        let buf = request(&self.ctx, label, None, self.route.clone(), req).await?;
+        let public_key: Option<PublicKey> = decode_option(label, "public_key", &buf)?;
+        public_key.ok_or_else(|| Self::not_found("secret"))
+    }
+
+    async fn get_key_id(&self, public_key: &PublicKey) -> Result<KeyId> {
+        let label = "get key id";
+        let req = Request::post("/key_id").body(KeyIdRequest::new(public_key.clone()));
+        let buf = request(&self.ctx, label, None, self.route.clone(), req).await?;
+        let key_id: Option<String> = decode_option(label, "key_id", &buf)?;
+        key_id.ok_or_else(|| Self::not_found("key id"))
+    }
+
+    /// The attributes are deduced from the type of the public key
+    async fn get_attributes(&self, key_id: &KeyId) -> Result<SecretAttributes> {
+        match self.get_public_key(key_id).await?.stype() {
+            SecretType::NistP256 => Ok(SecretAttributes::NistP256),
+            SecretType::Ed25519 => Ok(SecretAttributes::Ed25519),
+            SecretType::X25519 => Ok(SecretAttributes::X25519),
+            _ => Err(VaultError::InvalidKeyType.into()),
+        }
+    }
+
+    async fn delete_secret(&self, _key_id: KeyId) -> Result<bool> {
+        Err(ockam_core::Error::new(
+            Origin::Vault,
+            Kind::Unsupported,
+            "secrets can not be deleted with a remote signer",
+        ))
+    }
+
+    async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
+        let label = "sign";
+        let req = Request::post("/sign").body(SignRequest::new(key_id.as_str(), message));
+        let buf = request(&self.ctx, label, None, self.route.clone(), req).await?;
+        let res: Option<SignResponse> = decode_option(label, "sign_response", &buf)?;
+        let res = res.ok_or_else(|| Self::not_found("signing secret"))?;
+        Ok(Signature::new(res.signature().to_vec()))
+    }
+
+    async fn verify(
+        &self,
+        public_key: &PublicKey,
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<bool> {
+        self.verifier.verify(public_key, message, signature).await
+    }
+}
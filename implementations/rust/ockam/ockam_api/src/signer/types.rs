@@ -0,0 +1,104 @@
+use minicbor::{Decode, Encode};
+use ockam::vault::PublicKey;
+use ockam_core::{CowBytes, CowStr};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SignRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3417821>,
+    #[b(1)] key_id: CowStr<'a>,
+    #[b(2)] data: CowBytes<'a>,
+}
+
+impl<'a> SignRequest<'a> {
+    pub fn new(key_id: impl Into<CowStr<'a>>, data: impl Into<CowBytes<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key_id: key_id.into(),
+            data: data.into(),
+        }
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SignResponse<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5523017>,
+    #[b(1)] signature: CowBytes<'a>,
+}
+
+impl<'a> SignResponse<'a> {
+    pub fn new(signature: impl Into<CowBytes<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            signature: signature.into(),
+        }
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PublicKeyRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8806432>,
+    #[b(1)] key_id: CowStr<'a>,
+}
+
+impl<'a> PublicKeyRequest<'a> {
+    pub fn new(key_id: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key_id: key_id.into(),
+        }
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KeyIdRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1290774>,
+    #[n(1)] public_key: PublicKey,
+}
+
+impl KeyIdRequest {
+    pub fn new(public_key: PublicKey) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            public_key,
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+}
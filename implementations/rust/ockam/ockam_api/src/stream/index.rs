@@ -0,0 +1,83 @@
+use ockam::identity::Storage;
+use ockam::protocols::stream::requests::{IndexRequest, Request};
+use ockam::protocols::stream::responses::IndexResponse;
+use ockam::protocols::{ProtocolParser, ProtocolPayload};
+use ockam::{Any, Context, Result, Routed, Worker};
+use ockam_core::compat::sync::Arc;
+use ockam_core::Decodable;
+
+/// Prefix of the namespace used to store the indices of the consumers of a stream
+const STREAM_INDEX_KEY: &str = "STREAM_INDEX";
+
+/// Worker keeping track of the position of each consumer in a stream.
+///
+/// The consumers save the index of the next message they want to read, per stream and
+/// client id, so that they can resume reading a stream after a restart.
+pub struct StreamIndexService {
+    storage: Arc<dyn Storage>,
+}
+
+impl StreamIndexService {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    async fn get_index(&self, stream_name: &str, client_id: &str) -> Result<Option<u64>> {
+        let value = self.storage.get(client_id, &namespace(stream_name)).await?;
+        Ok(value.and_then(|v| v.try_into().ok().map(u64::from_le_bytes)))
+    }
+
+    async fn save_index(&self, stream_name: &str, client_id: &str, index: u64) -> Result<()> {
+        self.storage
+            .set(
+                client_id,
+                namespace(stream_name),
+                index.to_le_bytes().to_vec(),
+            )
+            .await
+    }
+}
+
+#[ockam::worker]
+impl Worker for StreamIndexService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        if !Request::check_id(pp.protocol.as_str()) {
+            warn!(protocol = %pp.protocol.as_str(), "unexpected stream index request");
+            return Ok(());
+        }
+        match Request::parse(pp)? {
+            Request::Index(IndexRequest::Get {
+                client_id,
+                stream_name,
+            }) => {
+                let index = self.get_index(&stream_name, &client_id).await?;
+                trace!(%stream_name, %client_id, ?index, "get stream index");
+                ctx.send(
+                    msg.return_route(),
+                    IndexResponse::new(client_id, stream_name, index),
+                )
+                .await
+            }
+            Request::Index(IndexRequest::Save {
+                client_id,
+                stream_name,
+                index,
+            }) => {
+                trace!(%stream_name, %client_id, index = index.u64(), "save stream index");
+                self.save_index(&stream_name, &client_id, index.u64()).await
+            }
+            _ => {
+                warn!("unexpected stream request sent to the stream index service");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn namespace(stream_name: &str) -> String {
+    format!("{STREAM_INDEX_KEY}/{stream_name}")
+}
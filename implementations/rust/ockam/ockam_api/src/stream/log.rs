@@ -0,0 +1,417 @@
+use crate::error::ApiError;
+use ockam_core::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Extension of the segment files
+const SEGMENT_EXTENSION: &str = "log";
+
+/// Size of the header preceding each record: the length of the record data
+const RECORD_HEADER_SIZE: u64 = 4;
+
+/// Default maximum size of a segment: 16 MiB
+pub const DEFAULT_MAX_SEGMENT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Options for a stream log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamLogOptions {
+    /// Size in bytes after which a new segment is started
+    pub max_segment_size: u64,
+
+    /// Maximum size in bytes of the log. The oldest segments are removed when it is exceeded
+    pub retention_size: Option<u64>,
+
+    /// Maximum age of a segment, based on the time of its last write.
+    /// Older segments are removed
+    pub retention_age: Option<Duration>,
+}
+
+impl StreamLogOptions {
+    /// Check that the options can be used to store messages
+    pub fn validate(&self) -> Result<()> {
+        if self.max_segment_size == 0 {
+            return Err(ApiError::generic(
+                "the maximum segment size of a stream must be greater than 0",
+            ));
+        }
+        if self.retention_age == Some(Duration::ZERO) {
+            return Err(ApiError::generic(
+                "the retention duration of a stream must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for StreamLogOptions {
+    fn default() -> Self {
+        Self {
+            max_segment_size: DEFAULT_MAX_SEGMENT_SIZE,
+            retention_size: None,
+            retention_age: None,
+        }
+    }
+}
+
+/// A segment file of the log.
+///
+/// The segment is named after the index of its first record, and contains a sequence of
+/// records, each one being prefixed with its length as a 32 bits little-endian integer.
+#[derive(Debug)]
+struct Segment {
+    base: u64,
+    path: PathBuf,
+    offsets: Vec<u64>,
+    size: u64,
+}
+
+impl Segment {
+    fn path(dir: &Path, base: u64) -> PathBuf {
+        dir.join(format!("{base:020}.{SEGMENT_EXTENSION}"))
+    }
+
+    /// Load an existing segment and compute the offset of each record.
+    /// A record truncated by a crash is removed from the segment file
+    fn load(path: PathBuf, base: u64) -> Result<Self> {
+        let bytes = fs::read(&path).map_err(io_error)?;
+        let mut offsets = vec![];
+        let mut offset = 0u64;
+        while offset + RECORD_HEADER_SIZE <= bytes.len() as u64 {
+            let start = offset as usize;
+            let header: [u8; 4] = bytes[start..start + 4].try_into().unwrap();
+            let end = offset + RECORD_HEADER_SIZE + u32::from_le_bytes(header) as u64;
+            if end > bytes.len() as u64 {
+                break;
+            }
+            offsets.push(offset);
+            offset = end;
+        }
+        if offset < bytes.len() as u64 {
+            warn!(path = %path.display(), "truncating an incomplete stream record");
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .map_err(io_error)?;
+            file.set_len(offset).map_err(io_error)?;
+        }
+        Ok(Self {
+            base,
+            path,
+            offsets,
+            size: offset,
+        })
+    }
+
+    /// Index of the record following the last record of this segment
+    fn next_index(&self) -> u64 {
+        self.base + self.offsets.len() as u64
+    }
+
+    fn age(&self) -> Result<Duration> {
+        let modified = fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .map_err(io_error)?;
+        Ok(SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default())
+    }
+}
+
+/// Append-only log storing the messages of a stream on disk.
+///
+/// The log is split in segments so that old messages can be removed according to the
+/// retention limits, without rewriting the rest of the log. Each message is identified by
+/// its index, which is never reused, even when messages are removed.
+#[derive(Debug)]
+pub struct StreamLog {
+    dir: PathBuf,
+    options: StreamLogOptions,
+    segments: Vec<Segment>,
+    active: File,
+}
+
+impl StreamLog {
+    /// Open the log stored in a directory, creating it if necessary
+    pub fn open(dir: impl AsRef<Path>, options: StreamLogOptions) -> Result<Self> {
+        options.validate()?;
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error)?;
+
+        let mut segments = vec![];
+        for entry in fs::read_dir(&dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            if let Some(base) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                segments.push(Segment::load(path, base)?);
+            }
+        }
+        segments.sort_by_key(|s| s.base);
+        if segments.is_empty() {
+            let path = Segment::path(&dir, 0);
+            File::create(&path).map_err(io_error)?;
+            segments.push(Segment::load(path, 0)?);
+        }
+
+        let active = open_for_append(&segments[segments.len() - 1].path)?;
+        let mut log = Self {
+            dir,
+            options,
+            segments,
+            active,
+        };
+        log.apply_retention()?;
+        Ok(log)
+    }
+
+    /// Index of the oldest message still available
+    pub fn first_index(&self) -> u64 {
+        self.segments[0].base
+    }
+
+    /// Index which will be given to the next appended message
+    pub fn next_index(&self) -> u64 {
+        self.active_segment().next_index()
+    }
+
+    /// Append a message and return its index
+    pub fn append(&mut self, data: &[u8]) -> Result<u64> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| ApiError::generic("stream message is too large"))?;
+        if self.active_segment().size >= self.options.max_segment_size {
+            self.roll()?;
+        }
+
+        let index = self.next_index();
+        let mut record = Vec::with_capacity(RECORD_HEADER_SIZE as usize + data.len());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(data);
+        self.active.write_all(&record).map_err(io_error)?;
+        self.active.sync_data().map_err(io_error)?;
+
+        let segment = self.active_segment_mut();
+        segment.offsets.push(segment.size);
+        segment.size += record.len() as u64;
+        Ok(index)
+    }
+
+    /// Read at most `limit` messages starting at `index`. All the available messages are
+    /// returned if `limit` is 0. Messages removed by the retention limits are skipped
+    pub fn read(&self, index: u64, limit: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        let mut messages = vec![];
+        let mut index = index.max(self.first_index());
+        for segment in &self.segments {
+            if index >= segment.next_index() {
+                continue;
+            }
+            let mut file = File::open(&segment.path).map_err(io_error)?;
+            file.seek(SeekFrom::Start(
+                segment.offsets[(index - segment.base) as usize],
+            ))
+            .map_err(io_error)?;
+            while index < segment.next_index() {
+                if limit != 0 && messages.len() as u64 >= limit {
+                    return Ok(messages);
+                }
+                let mut header = [0u8; RECORD_HEADER_SIZE as usize];
+                file.read_exact(&mut header).map_err(io_error)?;
+                let mut data = vec![0u8; u32::from_le_bytes(header) as usize];
+                file.read_exact(&mut data).map_err(io_error)?;
+                messages.push((index, data));
+                index += 1;
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Maximum age of the messages, if any
+    pub fn retention_age(&self) -> Option<Duration> {
+        self.options.retention_age
+    }
+
+    /// Remove the messages exceeding the retention limits.
+    ///
+    /// Messages are otherwise only removed when a new segment is started, so this must be
+    /// called periodically for the messages of a quiet stream to expire. The active segment
+    /// is rolled if it is too old, so that it can be removed as well
+    pub fn expire(&mut self) -> Result<()> {
+        let active_expired = match self.options.retention_age {
+            Some(max) => {
+                !self.active_segment().offsets.is_empty() && self.active_segment().age()? > max
+            }
+            None => false,
+        };
+        if active_expired {
+            self.roll()
+        } else {
+            self.apply_retention()
+        }
+    }
+
+    /// Start a new segment and remove the segments exceeding the retention limits
+    fn roll(&mut self) -> Result<()> {
+        let base = self.next_index();
+        let path = Segment::path(&self.dir, base);
+        debug!(path = %path.display(), "starting a new stream segment");
+        self.active = open_for_append(&path)?;
+        self.segments.push(Segment {
+            base,
+            path,
+            offsets: vec![],
+            size: 0,
+        });
+        self.apply_retention()
+    }
+
+    /// Remove the oldest segments while the log exceeds its retention limits.
+    /// The active segment is never removed
+    fn apply_retention(&mut self) -> Result<()> {
+        while self.segments.len() > 1 {
+            let oldest = &self.segments[0];
+            let total_size: u64 = self.segments.iter().map(|s| s.size).sum();
+            let too_large = self
+                .options
+                .retention_size
+                .map(|max| total_size > max)
+                .unwrap_or(false);
+            let too_old = match self.options.retention_age {
+                Some(max) => oldest.age()? > max,
+                None => false,
+            };
+            if !too_large && !too_old {
+                break;
+            }
+            debug!(path = %oldest.path.display(), "removing an expired stream segment");
+            fs::remove_file(&oldest.path).map_err(io_error)?;
+            self.segments.remove(0);
+        }
+        Ok(())
+    }
+
+    fn active_segment(&self) -> &Segment {
+        &self.segments[self.segments.len() - 1]
+    }
+
+    fn active_segment_mut(&mut self) -> &mut Segment {
+        let last = self.segments.len() - 1;
+        &mut self.segments[last]
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error)
+}
+
+fn io_error(e: std::io::Error) -> ockam_core::Error {
+    ApiError::generic(&format!("stream log error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(max_segment_size: u64) -> StreamLogOptions {
+        StreamLogOptions {
+            max_segment_size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_append_read_and_reopen() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = StreamLog::open(dir.path(), options(10))?;
+        for i in 0..5u8 {
+            assert_eq!(log.append(&[i; 8])?, i as u64);
+        }
+        assert_eq!(log.read(1, 2)?, vec![(1, vec![1; 8]), (2, vec![2; 8])]);
+        assert_eq!(log.read(3, 0)?.len(), 2);
+        assert!(log.read(5, 0)?.is_empty());
+
+        // simulate a crash in the middle of a write
+        let last = Segment::path(dir.path(), 4);
+        OpenOptions::new()
+            .append(true)
+            .open(&last)
+            .unwrap()
+            .write_all(&[8, 0, 0, 0, 1])
+            .unwrap();
+
+        let mut log = StreamLog::open(dir.path(), options(10))?;
+        assert_eq!(log.next_index(), 5);
+        assert_eq!(log.append(b"after")?, 5);
+        assert_eq!(
+            log.read(4, 0)?,
+            vec![(4, vec![4; 8]), (5, b"after".to_vec())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_retention_size() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = StreamLog::open(
+            dir.path(),
+            StreamLogOptions {
+                max_segment_size: 10,
+                retention_size: Some(30),
+                retention_age: None,
+            },
+        )?;
+        for i in 0..6u8 {
+            log.append(&[i; 8])?;
+        }
+        // each message is stored in its own 12 bytes segment
+        assert_eq!(log.first_index(), 3);
+        assert_eq!(log.read(0, 1)?, vec![(3, vec![3; 8])]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retention_age() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = StreamLog::open(
+            dir.path(),
+            StreamLogOptions {
+                max_segment_size: 1024,
+                retention_size: None,
+                retention_age: Some(Duration::from_millis(50)),
+            },
+        )?;
+        log.append(b"old")?;
+        log.expire()?;
+        assert_eq!(log.read(0, 0)?, vec![(0, b"old".to_vec())]);
+
+        // the messages of the active segment expire even if nothing else is written
+        std::thread::sleep(Duration::from_millis(100));
+        log.expire()?;
+        assert_eq!(log.first_index(), 1);
+        assert!(log.read(0, 0)?.is_empty());
+        assert_eq!(log.append(b"new")?, 1);
+        assert_eq!(log.read(0, 0)?, vec![(1, b"new".to_vec())]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(StreamLog::open(dir.path(), options(0)).is_err());
+
+        let zero_retention = StreamLogOptions {
+            retention_age: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(StreamLog::open(dir.path(), zero_retention).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Durable stream service.
+//!
+//! This module implements the node side of the stream protocol used by the
+//! [`ockam::stream::Stream`] client: a stream service creating streams, one worker per
+//! stream storing its messages in an append-only log on disk, and an index service
+//! storing the position of each stream consumer.
+
+mod index;
+mod log;
+mod service;
+
+pub use self::log::*;
+pub use index::*;
+pub use service::*;
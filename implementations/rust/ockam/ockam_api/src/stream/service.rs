@@ -0,0 +1,212 @@
+use crate::stream::{StreamLog, StreamLogOptions};
+use ockam::protocols::stream::requests::{CreateStreamRequest, PullRequest, PushRequest, Request};
+use ockam::protocols::stream::responses::{InitResponse, PullResponse, PushConfirm, StreamMessage};
+use ockam::protocols::{ProtocolParser, ProtocolPayload};
+use ockam::{Address, Any, Context, DelayedEvent, Result, Routed, Worker};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{self, Rng};
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::Decodable;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Maximum interval between two removals of the expired messages of a stream
+const RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Worker creating streams and dispatching the creation requests to their workers.
+///
+/// Each stream is handled by its own worker, which stores the stream messages in a
+/// [`StreamLog`] located in a sub-directory of the service directory. The stream workers
+/// answer the creation requests themselves so that clients can then send their push and
+/// pull requests directly to them.
+pub struct StreamService {
+    dir: PathBuf,
+    options: StreamLogOptions,
+    flow_control_id: Option<FlowControlId>,
+    streams: BTreeMap<String, Address>,
+}
+
+impl StreamService {
+    /// Create a new stream service storing its streams in a directory.
+    /// If a flow control id is given, the stream workers are added as consumers for it
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        options: StreamLogOptions,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            options,
+            flow_control_id,
+            streams: BTreeMap::new(),
+        }
+    }
+
+    /// Return the address of the worker of a stream, starting it if necessary
+    async fn stream_worker(&mut self, ctx: &Context, name: &str) -> Result<Address> {
+        if let Some(address) = self.streams.get(name) {
+            return Ok(address.clone());
+        }
+
+        let log = StreamLog::open(self.dir.join(name), self.options.clone())?;
+        let address = Address::random_tagged("StreamWorker");
+        if let Some(flow_control_id) = &self.flow_control_id {
+            ctx.flow_controls()
+                .add_consumer(address.clone(), flow_control_id);
+        }
+        ctx.start_worker(address.clone(), StreamWorker::new(name, log))
+            .await?;
+        info!(%name, %address, "started stream");
+        self.streams.insert(name.to_string(), address.clone());
+        Ok(address)
+    }
+}
+
+#[ockam::worker]
+impl Worker for StreamService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        for address in self.streams.values() {
+            let _ = ctx.stop_worker(address.clone()).await;
+        }
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        let name = match Request::check_id(pp.protocol.as_str()) {
+            true => match Request::parse(pp)? {
+                Request::Create(CreateStreamRequest { stream_name }) => {
+                    stream_name.unwrap_or_else(random_stream_name)
+                }
+                _ => {
+                    warn!("stream requests must be sent to the stream workers");
+                    return Ok(());
+                }
+            },
+            false => {
+                warn!(protocol = %pp.protocol.as_str(), "unexpected stream service request");
+                return Ok(());
+            }
+        };
+        if !is_valid_stream_name(&name) {
+            warn!(%name, "invalid stream name");
+            return Ok(());
+        }
+
+        // Let the stream worker answer, so that the client learns its address
+        let address = self.stream_worker(ctx, &name).await?;
+        let mut message = msg.into_local_message();
+        let transport_message = message.transport_mut();
+        transport_message.onward_route.step()?;
+        transport_message.onward_route.modify().prepend(address);
+        ctx.forward(message).await
+    }
+}
+
+/// Worker appending messages to the log of a stream and serving them to consumers.
+///
+/// When the messages have a maximum age, the worker periodically removes the expired
+/// messages, even if no new messages are appended to the stream.
+pub struct StreamWorker {
+    name: String,
+    log: StreamLog,
+    heartbeat: Option<DelayedEvent<Vec<u8>>>,
+}
+
+impl StreamWorker {
+    fn new(name: &str, log: StreamLog) -> Self {
+        Self {
+            name: name.to_string(),
+            log,
+            heartbeat: None,
+        }
+    }
+
+    fn sweep_interval(&self) -> Option<Duration> {
+        self.log
+            .retention_age()
+            .map(|age| age.min(RETENTION_SWEEP_INTERVAL))
+    }
+}
+
+#[ockam::worker]
+impl Worker for StreamWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        if let Some(interval) = self.sweep_interval() {
+            let mut heartbeat = DelayedEvent::create(ctx, ctx.address(), vec![]).await?;
+            heartbeat.schedule(interval).await?;
+            self.heartbeat = Some(heartbeat);
+        }
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let interval = self.sweep_interval();
+        if let (Some(heartbeat), Some(interval)) = (&mut self.heartbeat, interval) {
+            if msg.src_addr() == heartbeat.address() {
+                // a failed removal is retried at the next tick
+                if let Err(e) = self.log.expire() {
+                    warn!(stream = %self.name, %e, "failed to remove the expired stream messages");
+                }
+                return heartbeat.schedule(interval).await;
+            }
+        }
+
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        if !Request::check_id(pp.protocol.as_str()) {
+            warn!(protocol = %pp.protocol.as_str(), stream = %self.name, "unexpected stream request");
+            return Ok(());
+        }
+        let response = match Request::parse(pp)? {
+            Request::Create(_) => InitResponse::new(self.name.clone()),
+            Request::Push(PushRequest { request_id, data }) => match self.log.append(&data) {
+                Ok(index) => PushConfirm::new(request_id.u64(), true, index),
+                Err(e) => {
+                    error!(stream = %self.name, %e, "failed to append a stream message");
+                    PushConfirm::new(request_id.u64(), false, 0)
+                }
+            },
+            Request::Pull(PullRequest {
+                request_id,
+                index,
+                limit,
+            }) => {
+                let messages = self
+                    .log
+                    .read(index.u64(), limit.u64())?
+                    .into_iter()
+                    .map(|(index, data)| StreamMessage {
+                        index: index.into(),
+                        data,
+                    })
+                    .collect::<Vec<_>>();
+                PullResponse::new(request_id.u64(), messages)
+            }
+            Request::Index(_) => {
+                warn!(stream = %self.name, "index requests must be sent to the stream index service");
+                return Ok(());
+            }
+        };
+        ctx.send(msg.return_route(), response).await
+    }
+}
+
+fn random_stream_name() -> String {
+    let random: [u8; 16] = rand::thread_rng().gen();
+    hex::encode(random)
+}
+
+/// Stream names are used as directory names
+fn is_valid_stream_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
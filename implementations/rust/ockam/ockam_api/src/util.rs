@@ -6,7 +6,7 @@ use ockam::TcpTransport;
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Error, Result, Route, TransportType, LOCAL};
 use ockam_multiaddr::proto::{
-    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker,
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Udp, Unix, Worker, Ws, Wss,
 };
 use ockam_multiaddr::{Code, MultiAddr, Protocol};
 use ockam_transport_tcp::{TcpConnectionOptions, TCP};
@@ -309,6 +309,10 @@ pub fn is_local_node(ma: &MultiAddr) -> anyhow::Result<bool> {
                     .map(|ip6| ip6.is_loopback())
                     .ok_or_else(|| anyhow!("Invalid \"ip6\" value"))?;
             }
+            // A "/unix" socket is always on the local machine
+            Unix::CODE => {
+                at_rust_node = true;
+            }
             // A MultiAddr starting with "/service" could reference both local and remote nodes.
             _ => {
                 return Err(anyhow!("Invalid address, protocol not supported"));
@@ -330,6 +334,10 @@ pub fn local_worker(code: &Code) -> Result<bool> {
         | Ip4::CODE
         | Ip6::CODE
         | Tcp::CODE
+        | Udp::CODE
+        | Ws::CODE
+        | Wss::CODE
+        | Unix::CODE
         | Secure::CODE => Ok(false),
         Worker::CODE | Service::CODE => Ok(true),
 
@@ -358,7 +366,7 @@ pub mod test_utils {
         NodeManagerGeneralOptions, NodeManagerProjectsOptions, NodeManagerTransportOptions,
         NodeManagerTrustOptions,
     };
-    use crate::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+    use crate::nodes::{NodeManager, NodeManagerWorker};
 
     /// This struct is used by tests, it has two responsibilities:
     /// - guard to delete the cli state at the end of the test, the cli state
@@ -459,9 +467,7 @@ pub mod test_utils {
         // since we re-created secure-channels, we rewrite the identity in the LMDB storage
         create_identity_zero(&secure_channels).await?;
 
-        context
-            .start_worker(NODEMANAGER_ADDR, node_manager_worker)
-            .await?;
+        node_manager_worker.start(context).await?;
 
         Ok(NodeManagerHandle {
             cli_state,
@@ -1,4 +1,4 @@
-use ockam::identity::IdentityAttributesReader;
+use ockam::identity::{AttributeValue, IdentityAttributesReader};
 use ockam_api::auth;
 use ockam_api::bootstrapped_identities_store::PreTrustedIdentities;
 use ockam_core::Result;
@@ -9,7 +9,7 @@ use std::sync::Arc;
 async fn auth_smoke(ctx: &mut Context) -> Result<()> {
     let s = PreTrustedIdentities::new_from_string(
         r#"{"P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a":{"attr":"value"},
-            "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357b":{"attr":"value2"}
+            "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357b":{"attr":"value2","groups":["admins","devs"],"level":3}
            }"#,
     )?;
     let s: Arc<dyn IdentityAttributesReader> = Arc::new(s);
@@ -26,6 +26,18 @@ async fn auth_smoke(ctx: &mut Context) -> Result<()> {
     assert_eq!(None, entry.attested_by());
     assert_eq!(None, entry.expires());
 
+    // Retrieve one with typed values
+    let entry = client
+        .get("P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357b")
+        .await?
+        .expect("found");
+    assert_eq!(Some(&b"value2"[..].to_vec()), entry.attrs().get("attr"));
+    assert_eq!(
+        Some(&AttributeValue::from(vec!["admins", "devs"])),
+        entry.value("groups")
+    );
+    assert_eq!(Some(&AttributeValue::from(3)), entry.value("level"));
+
     // Try to retrieve non-existing one
     assert_eq!(
         None,
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use ockam::identity::identities;
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, Result, Worker};
+use ockam_identity::{
+    IdentityIdentifier, SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
+};
+use ockam_node::Context;
+
+/// Authority identity listening for secure channels, in front of which
+/// the tests start their authenticator services
+pub struct Authority {
+    pub secure_channels: Arc<SecureChannels>,
+    listener: Address,
+    flow_control_id: FlowControlId,
+}
+
+impl Authority {
+    pub async fn create(ctx: &Context) -> Result<Authority> {
+        let listener = Address::random_local();
+        let secure_channels = SecureChannels::builder()
+            .with_identities(identities())
+            .build();
+        let authority = secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+
+        let options = SecureChannelListenerOptions::new();
+        let flow_control_id = options.spawner_flow_control_id();
+        secure_channels
+            .create_secure_channel_listener(ctx, &authority.identifier(), listener.clone(), options)
+            .await?;
+
+        Ok(Authority {
+            secure_channels,
+            listener,
+            flow_control_id,
+        })
+    }
+
+    /// Start a service reachable through the secure channels of the authority
+    /// and return its address
+    pub async fn start_service<W>(&self, ctx: &Context, worker: W) -> Result<Address>
+    where
+        W: Worker<Context = Context>,
+    {
+        let address = Address::random_local();
+        ctx.flow_controls()
+            .add_consumer(address.clone(), &self.flow_control_id);
+        ctx.start_worker(address.clone(), worker).await?;
+        Ok(address)
+    }
+
+    pub async fn create_identity(&self) -> Result<IdentityIdentifier> {
+        Ok(self
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?
+            .identifier())
+    }
+
+    /// Create a secure channel from the given identity to the authority
+    pub async fn secure_channel(
+        &self,
+        ctx: &Context,
+        identifier: &IdentityIdentifier,
+    ) -> Result<Address> {
+        Ok(self
+            .secure_channels
+            .create_secure_channel(
+                ctx,
+                identifier,
+                self.listener.clone(),
+                SecureChannelOptions::new(),
+            )
+            .await?
+            .into())
+    }
+}
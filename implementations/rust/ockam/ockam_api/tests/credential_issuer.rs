@@ -1,5 +1,5 @@
 use ockam::identity::credential::Timestamp;
-use ockam::identity::{identities, AttributesEntry};
+use ockam::identity::{identities, AttributeValue, AttributesEntry};
 use ockam::route;
 use ockam_api::bootstrapped_identities_store::{BootstrapedIdentityStore, PreTrustedIdentities};
 use ockam_core::compat::collections::{BTreeMap, HashMap};
@@ -30,7 +30,11 @@ async fn credential(ctx: &mut Context) -> Result<()> {
             now,
             None,
             None,
-        ),
+        )
+        .with_values(BTreeMap::from([(
+            "groups".to_string(),
+            AttributeValue::from(vec!["admins", "devs"]),
+        )])),
     )]);
 
     let boostrapped = BootstrapedIdentityStore::new(
@@ -68,7 +72,8 @@ async fn credential(ctx: &mut Context) -> Result<()> {
         auth_identity.identifier(),
         "project42".into(),
     )
-    .await?;
+    .await?
+    .with_disclosable_attributes(&["attr"]);
     ctx.start_worker(auth_worker_addr.clone(), auth).await?;
 
     // Connect to the API channel from the member:
@@ -90,14 +95,34 @@ async fn credential(ctx: &mut Context) -> Result<()> {
         .decode_identity(&exported)
         .await
         .unwrap();
+    let undisclosed = credential.disclose(&[]);
     let data = identities
         .credentials()
-        .verify_credential(&imported.identifier(), &[auth_identity], credential)
+        .verify_credential(
+            &imported.identifier(),
+            std::slice::from_ref(&auth_identity),
+            credential,
+        )
         .await?;
     assert_eq!(
         Some(b"project42".as_slice()),
         data.attributes().get("project_id")
     );
     assert_eq!(Some(b"value".as_slice()), data.attributes().get("attr"));
+    assert_eq!(
+        Some(&AttributeValue::from(vec!["admins", "devs"])),
+        data.attributes().get_value("groups")
+    );
+
+    // the member can choose not to reveal a disclosable attribute
+    let data = identities
+        .credentials()
+        .verify_credential(&imported.identifier(), &[auth_identity], undisclosed)
+        .await?;
+    assert_eq!(None, data.attributes().get("attr"));
+    assert_eq!(
+        Some(b"project42".as_slice()),
+        data.attributes().get("project_id")
+    );
     ctx.stop().await
 }
@@ -0,0 +1,95 @@
+use ockam::identity::{
+    identities, Credential, CredentialData, CredentialsRetriever, IdentityIdentifier,
+};
+use ockam_api::cli_state::{CredentialsState, StateDirTrait, StoredCredentialsRetriever};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use ockam_node::Context;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Retriever returning the same credential and counting how many times it was called
+struct CountingRetriever {
+    credential: Credential,
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl CredentialsRetriever for CountingRetriever {
+    async fn retrieve(
+        &self,
+        _ctx: &Context,
+        _for_identity: &IdentityIdentifier,
+    ) -> Result<Credential> {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        Ok(self.credential.clone())
+    }
+}
+
+#[ockam_macros::test]
+async fn stored_credential_is_reused_until_renewal(ctx: &mut Context) -> Result<()> {
+    let identities = identities();
+    let authority = identities.identities_creation().create_identity().await?;
+    let member = identities.identities_creation().create_identity().await?;
+    let credential = identities
+        .credentials()
+        .issue_credential(
+            &authority.identifier(),
+            CredentialData::builder(member.identifier(), authority.identifier())
+                .with_attribute("role", b"member")
+                .valid_for(Duration::from_secs(3600))
+                .build()?,
+        )
+        .await?;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("defaults")).unwrap();
+    let credentials_state = CredentialsState::load(dir.path())?;
+    let count = Arc::new(AtomicUsize::new(0));
+    let retriever = |count: &Arc<AtomicUsize>| {
+        Arc::new(CountingRetriever {
+            credential: credential.clone(),
+            count: count.clone(),
+        })
+    };
+
+    // the first node start retrieves the credential and stores it
+    let stored = StoredCredentialsRetriever::new(
+        credentials_state.clone(),
+        identities.credentials(),
+        authority.clone(),
+        retriever(&count),
+    );
+    assert_eq!(
+        stored.retrieve(ctx, &member.identifier()).await?,
+        credential
+    );
+    assert_eq!(count.load(Ordering::Relaxed), 1);
+    assert!(credentials_state.exists(stored.credential_name(&member.identifier())));
+
+    // the next ones use the stored credential
+    let stored = StoredCredentialsRetriever::new(
+        credentials_state.clone(),
+        identities.credentials(),
+        authority.clone(),
+        retriever(&count),
+    );
+    assert_eq!(
+        stored.retrieve(ctx, &member.identifier()).await?,
+        credential
+    );
+    assert_eq!(count.load(Ordering::Relaxed), 1);
+
+    // a credential expiring within the renewal margin is retrieved again
+    let stored = StoredCredentialsRetriever::new(
+        credentials_state,
+        identities.credentials(),
+        authority,
+        retriever(&count),
+    )
+    .with_renewal_margin(Duration::from_secs(7200));
+    stored.retrieve(ctx, &member.identifier()).await?;
+    assert_eq!(count.load(Ordering::Relaxed), 2);
+
+    ctx.stop().await
+}
@@ -0,0 +1,250 @@
+use ockam::identity::{AttributeValue, InMemoryStorage};
+use ockam::route;
+use ockam_api::authenticator::direct::types::CreateToken;
+use ockam_api::authenticator::direct::{
+    EnrollmentTokenAuthenticator, TokenAcceptorClient, TokenIssuerClient,
+};
+use ockam_core::{Address, Result};
+use ockam_identity::IdentityIdentifier;
+use ockam_node::{Context, RpcClient};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::common::Authority;
+
+mod common;
+
+#[ockam_macros::test]
+async fn enrollment_token_can_be_used_several_times(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+    let member1 = setup.authority.create_identity().await?;
+    let member2 = setup.authority.create_identity().await?;
+    let member3 = setup.authority.create_identity().await?;
+
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    let token = issuer
+        .create_token(
+            CreateToken::new()
+                .with_attributes(HashMap::from([("component", "edge")]))
+                .with_duration(Some(Duration::from_secs(3600)))
+                .with_ttl_count(Some(2)),
+        )
+        .await?;
+
+    assert!(setup.present_token(ctx, &member1, &token).await.is_ok());
+    assert!(setup.present_token(ctx, &member2, &token).await.is_ok());
+    assert!(setup.present_token(ctx, &member3, &token).await.is_err());
+
+    let attributes = setup
+        .authority
+        .secure_channels
+        .identities()
+        .repository()
+        .get_attributes(&member2)
+        .await?
+        .unwrap();
+    assert_eq!(
+        Some(b"edge".as_slice()),
+        attributes.attrs().get("component").map(|v| v.as_slice())
+    );
+    assert_eq!(Some(enroller), attributes.attested_by());
+    assert!(setup
+        .authority
+        .secure_channels
+        .identities()
+        .repository()
+        .get_attributes(&member3)
+        .await?
+        .is_none());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enrollment_token_gives_typed_attributes(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+    let member = setup.authority.create_identity().await?;
+
+    let groups = AttributeValue::from(vec!["admins", "devs"]);
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    let token = issuer
+        .create_token(
+            CreateToken::new()
+                .with_attribute_values(BTreeMap::from([("groups".to_string(), groups.clone())])),
+        )
+        .await?;
+    setup.present_token(ctx, &member, &token).await?;
+
+    let attributes = setup
+        .authority
+        .secure_channels
+        .identities()
+        .repository()
+        .get_attributes(&member)
+        .await?
+        .unwrap();
+    assert_eq!(Some(&groups), attributes.value("groups"));
+    assert!(attributes.has_attribute_value("groups", b"devs"));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enrollment_token_can_be_bound_to_an_identity(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+    let member = setup.authority.create_identity().await?;
+    let other = setup.authority.create_identity().await?;
+
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    let token = issuer
+        .create_token(CreateToken::new().with_required_identifier(Some(member.clone())))
+        .await?;
+
+    assert!(setup.present_token(ctx, &other, &token).await.is_err());
+    assert!(setup.present_token(ctx, &member, &token).await.is_ok());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enrollment_token_can_set_an_expiry_for_members(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+    let member = setup.authority.create_identity().await?;
+
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    let token = issuer
+        .create_token(CreateToken::new().with_member_duration(Some(Duration::from_secs(3600))))
+        .await?;
+    assert_eq!(issuer.list_tokens().await?[0].member_ttl_secs(), Some(3600));
+    assert!(setup.present_token(ctx, &member, &token).await.is_ok());
+
+    let attributes = setup
+        .authority
+        .secure_channels
+        .identities()
+        .repository()
+        .get_attributes(&member)
+        .await?
+        .unwrap();
+    assert_eq!(
+        attributes.expires(),
+        Some(attributes.added().add_seconds(3600))
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enrollment_token_can_be_listed_and_revoked(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+    let other_enroller = setup.authority.create_identity().await?;
+    let member = setup.authority.create_identity().await?;
+
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    let token1 = issuer
+        .create_token(CreateToken::new().with_ttl_count(Some(10)))
+        .await?;
+    let token2 = issuer.create_token(CreateToken::new()).await?;
+
+    let other_issuer = setup.issuer_client(ctx, &other_enroller).await?;
+    other_issuer.create_token(CreateToken::new()).await?;
+
+    let tokens = issuer.list_tokens().await?;
+    assert_eq!(tokens.len(), 2);
+    let listed = tokens
+        .iter()
+        .find(|t| t.one_time_code() == &token1)
+        .unwrap();
+    assert_eq!(listed.ttl_count(), 10);
+    assert_eq!(listed.usage_count(), 0);
+
+    // a token can only be revoked by the enroller who created it
+    assert!(other_issuer.revoke_token(&token1).await.is_err());
+    issuer.revoke_token(&token1).await?;
+    assert!(setup.present_token(ctx, &member, &token1).await.is_err());
+
+    let tokens = issuer.list_tokens().await?;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].one_time_code(), &token2);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enrollment_token_must_be_usable(ctx: &mut Context) -> Result<()> {
+    let setup = Setup::create(ctx).await?;
+    let enroller = setup.authority.create_identity().await?;
+
+    let issuer = setup.issuer_client(ctx, &enroller).await?;
+    assert!(issuer
+        .create_token(CreateToken::new().with_ttl_count(Some(0)))
+        .await
+        .is_err());
+    assert!(issuer
+        .create_token(
+            CreateToken::new().with_duration(Some(Duration::from_secs(365 * 24 * 60 * 60)))
+        )
+        .await
+        .is_err());
+    assert!(issuer.list_tokens().await?.is_empty());
+
+    ctx.stop().await
+}
+
+/// Authority running the enrollment token services used in the tests
+struct Setup {
+    authority: Authority,
+    issuer: Address,
+    acceptor: Address,
+}
+
+impl Setup {
+    async fn create(ctx: &Context) -> Result<Setup> {
+        let authority = Authority::create(ctx).await?;
+        let (issuer_worker, acceptor_worker) = EnrollmentTokenAuthenticator::new_worker_pair(
+            "project42".into(),
+            authority
+                .secure_channels
+                .identities()
+                .repository()
+                .as_attributes_writer(),
+            InMemoryStorage::create(),
+        );
+        let issuer = authority.start_service(ctx, issuer_worker).await?;
+        let acceptor = authority.start_service(ctx, acceptor_worker).await?;
+
+        Ok(Setup {
+            authority,
+            issuer,
+            acceptor,
+        })
+    }
+
+    async fn issuer_client(
+        &self,
+        ctx: &Context,
+        enroller: &IdentityIdentifier,
+    ) -> Result<TokenIssuerClient> {
+        let channel = self.authority.secure_channel(ctx, enroller).await?;
+        Ok(TokenIssuerClient::new(
+            RpcClient::new(route![channel, self.issuer.clone()], ctx).await?,
+        ))
+    }
+
+    async fn present_token(
+        &self,
+        ctx: &Context,
+        member: &IdentityIdentifier,
+        token: &ockam::identity::OneTimeCode,
+    ) -> Result<()> {
+        let channel = self.authority.secure_channel(ctx, member).await?;
+        TokenAcceptorClient::new(RpcClient::new(route![channel, self.acceptor.clone()], ctx).await?)
+            .present_token(token)
+            .await
+    }
+}
@@ -0,0 +1,180 @@
+use ockam::identity::credential::Timestamp;
+use ockam::identity::{
+    identities, AttributesEntry, IdentitiesStorage, IdentityAttributesWriter, InMemoryStorage,
+    Storage,
+};
+use ockam::route;
+use ockam_api::authenticator::direct::{DirectAuthenticator, DirectAuthenticatorClient};
+use ockam_api::authenticator::expiry::{AttributesExpired, AttributesSweeper};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Address, AllowAll, Result};
+use ockam_identity::{
+    CredentialsIssuer, CredentialsIssuerClient, SecureChannelListenerOptions, SecureChannelOptions,
+    SecureChannels,
+};
+use ockam_node::{Context, RpcClient};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[ockam_macros::test]
+async fn direct_authenticator_sets_member_expiry(ctx: &mut Context) -> Result<()> {
+    let secure_channels = SecureChannels::builder()
+        .with_identities(identities())
+        .build();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let authority = identities_creation.create_identity().await?;
+    let enroller = identities_creation.create_identity().await?;
+    let member = identities_creation.create_identity().await?;
+
+    let listener = Address::random_local();
+    let authenticator = Address::random_local();
+    let options = SecureChannelListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer(authenticator.clone(), &options.spawner_flow_control_id());
+    secure_channels
+        .create_secure_channel_listener(ctx, &authority.identifier(), listener.clone(), options)
+        .await?;
+
+    let repository = secure_channels.identities().repository();
+    let direct = DirectAuthenticator::new(
+        "project42".into(),
+        repository.as_attributes_writer(),
+        repository.as_attributes_reader(),
+    )
+    .await?;
+    ctx.start_worker(authenticator.clone(), direct).await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &enroller.identifier(),
+            listener,
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let client =
+        DirectAuthenticatorClient::new(RpcClient::new(route![channel, authenticator], ctx).await?);
+    client
+        .add_member(
+            member.identifier(),
+            HashMap::from([("role", "member")]),
+            Some(Duration::from_secs(3600)),
+        )
+        .await?;
+
+    let entry = repository
+        .get_attributes(&member.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(entry.expires(), Some(entry.added().add_seconds(3600)));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn credential_expiry_is_capped_by_member_expiry(ctx: &mut Context) -> Result<()> {
+    let secure_channels = SecureChannels::builder()
+        .with_identities(identities())
+        .build();
+    let identities = secure_channels.identities();
+    let authority = identities.identities_creation().create_identity().await?;
+    let member = identities.identities_creation().create_identity().await?;
+
+    let now = Timestamp::now().unwrap();
+    let member_expiry = now.add_seconds(60);
+    identities
+        .repository()
+        .put_attributes(
+            &member.identifier(),
+            AttributesEntry::new(
+                BTreeMap::from([("attr".to_string(), b"value".to_vec())]),
+                now,
+                Some(member_expiry),
+                None,
+            ),
+        )
+        .await?;
+
+    let listener = Address::random_local();
+    let issuer = Address::random_local();
+    let options = SecureChannelListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer(issuer.clone(), &options.spawner_flow_control_id());
+    secure_channels
+        .create_secure_channel_listener(ctx, &authority.identifier(), listener.clone(), options)
+        .await?;
+    let worker = CredentialsIssuer::new(
+        identities.clone(),
+        authority.identifier(),
+        "project42".into(),
+    )
+    .await?;
+    ctx.start_worker(issuer.clone(), worker).await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &member.identifier(),
+            listener,
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let credential = CredentialsIssuerClient::new(route![channel, issuer], ctx)
+        .await?
+        .credential()
+        .await?;
+    let data = identities
+        .credentials()
+        .verify_credential(&member.identifier(), &[authority], credential)
+        .await?;
+    assert_eq!(data.expires_at(), member_expiry);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn sweeper_purges_expired_attributes(ctx: &mut Context) -> Result<()> {
+    let storage = InMemoryStorage::create();
+    let repository = Arc::new(IdentitiesStorage::new(storage.clone()));
+    let identities_creation = identities().identities_creation();
+    let expired = identities_creation.create_identity().await?.identifier();
+    let valid = identities_creation.create_identity().await?.identifier();
+
+    let now = Timestamp::now().unwrap();
+    for (identifier, expires) in [(&expired, now), (&valid, now.add_seconds(3600))] {
+        repository
+            .put_attributes(
+                identifier,
+                AttributesEntry::new(BTreeMap::new(), now, Some(expires), None),
+            )
+            .await?;
+    }
+
+    let mut listener = ctx
+        .new_detached(Address::random_local(), AllowAll, AllowAll)
+        .await?;
+    AttributesSweeper::start_with_listeners(
+        ctx,
+        repository,
+        Duration::from_millis(100),
+        vec![listener.address()],
+    )
+    .await?;
+    let event = listener.receive::<AttributesExpired>().await?.body();
+    assert_eq!(event.member, expired);
+    assert_eq!(event.expired_at, Some(now));
+
+    // the storage is read directly since reading expired attributes from the repository
+    // would delete them anyway
+    assert!(storage
+        .get(&expired.to_string(), "ATTRIBUTES")
+        .await?
+        .is_none());
+    assert!(storage
+        .get(&valid.to_string(), "ATTRIBUTES")
+        .await?
+        .is_some());
+
+    ctx.stop().await
+}
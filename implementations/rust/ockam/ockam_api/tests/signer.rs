@@ -0,0 +1,92 @@
+use core::time::Duration;
+use ockam::identity::secure_channels::secure_channels;
+use ockam::identity::{
+    Identities, IdentityIdAccessControl, SecureChannelListenerOptions, SecureChannelOptions,
+};
+use ockam::vault::{SecurityModule, Vault};
+use ockam_api::signer::{RemoteSecurityModule, Server};
+use ockam_core::{route, Result};
+use ockam_node::{Context, WorkerBuilder};
+
+#[ockam_macros::test]
+async fn sign_with_a_remote_signer(ctx: &mut Context) -> Result<()> {
+    // the signer node holds the authority key
+    let signer_channels = secure_channels();
+    let signer_creation = signer_channels.identities().identities_creation();
+    let authority = signer_creation.create_identity().await?;
+    let signer_node = signer_creation.create_identity().await?;
+
+    // the client node only has its own identity
+    let client_channels = secure_channels();
+    let client_creation = client_channels.identities().identities_creation();
+    let client = client_creation.create_identity().await?;
+    let intruder = client_creation.create_identity().await?;
+
+    let listener = signer_channels
+        .create_secure_channel_listener(
+            ctx,
+            &signer_node.identifier(),
+            "signer_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("signer", listener.flow_control_id());
+    WorkerBuilder::new(Server::new(signer_channels.vault()))
+        .with_address("signer")
+        .with_incoming_access_control(IdentityIdAccessControl::new(vec![client.identifier()]))
+        .start(ctx)
+        .await?;
+
+    let channel = client_channels
+        .create_secure_channel(
+            ctx,
+            &client.identifier(),
+            route!["signer_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let remote = RemoteSecurityModule::create(route![channel, "signer"], ctx).await?;
+    let vault = Vault::builder().with_security_module(remote).build();
+    let identities = Identities::builder().with_identities_vault(vault).build();
+
+    // the signature is produced by the signer node
+    let signature = identities
+        .identities_keys()
+        .create_signature(&authority, b"hello", None)
+        .await?;
+    assert!(
+        signer_channels
+            .identities()
+            .identities_keys()
+            .verify_signature(&authority, &signature, b"hello", None)
+            .await?
+    );
+
+    // no secret can be created remotely
+    assert!(identities
+        .identities_creation()
+        .create_identity()
+        .await
+        .is_err());
+
+    // an identity which is not authorized doesn't get any response
+    let channel = client_channels
+        .create_secure_channel(
+            ctx,
+            &intruder.identifier(),
+            route!["signer_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let remote = RemoteSecurityModule::new(route![channel, "signer"], ctx).await?;
+    let key_id = signer_channels
+        .identities()
+        .identities_keys()
+        .get_secret_key(&authority, None)
+        .await?;
+    let result = tokio::time::timeout(Duration::from_secs(1), remote.sign(&key_id, b"hello")).await;
+    assert!(!matches!(result, Ok(Ok(_))));
+
+    ctx.stop().await
+}
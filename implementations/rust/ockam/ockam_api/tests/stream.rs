@@ -0,0 +1,151 @@
+use ockam::identity::{InMemoryStorage, Storage};
+use ockam::protocols::stream::requests::{CreateStreamRequest, PullRequest, PushRequest};
+use ockam::protocols::stream::responses::{PullResponse, Response};
+use ockam::protocols::{ProtocolParser, ProtocolPayload};
+use ockam::stream::Stream;
+use ockam::{route, Address, Context, Result};
+use ockam_api::stream::{StreamIndexService, StreamLogOptions, StreamService};
+use ockam_core::compat::sync::Arc;
+use std::path::Path;
+use std::time::Duration;
+
+#[ockam_macros::test]
+async fn stream_messages_are_delivered(ctx: &mut Context) -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = InMemoryStorage::create();
+    start_services(ctx, dir.path(), storage, "stream", "stream_index").await?;
+
+    let (tx, mut rx) = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .client_id("consumer")
+        .connect(route![], "messages", "messages")
+        .await?;
+
+    for message in ["a", "b", "c"] {
+        ctx.send(route![tx.to_route()], message.to_string()).await?;
+    }
+    for expected in ["a", "b", "c"] {
+        let message = rx.next::<String>().await?;
+        assert_eq!(message.as_body(), expected);
+    }
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn streams_survive_a_restart(ctx: &mut Context) -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = InMemoryStorage::create();
+    start_services(ctx, dir.path(), storage.clone(), "stream1", "index1").await?;
+
+    let (tx, mut rx) = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .stream_service("stream1")
+        .index_service("index1")
+        .client_id("consumer")
+        .connect(route![], "messages", "messages")
+        .await?;
+    for message in ["a", "b"] {
+        ctx.send(route![tx.to_route()], message.to_string()).await?;
+        assert_eq!(rx.next::<String>().await?.as_body(), message);
+    }
+
+    // restart the services on the same storage
+    ctx.stop_worker("stream1").await?;
+    ctx.stop_worker("index1").await?;
+    start_services(ctx, dir.path(), storage, "stream2", "index2").await?;
+
+    // all the messages are still available
+    let mut child = ctx
+        .new_detached(
+            Address::random_local(),
+            ockam_core::AllowAll,
+            ockam_core::AllowAll,
+        )
+        .await?;
+    child
+        .send(
+            route!["stream2"],
+            CreateStreamRequest::new("messages".to_string()),
+        )
+        .await?;
+    let init = child.receive::<ProtocolPayload>().await?;
+    let stream_worker = init.return_route();
+    child.send(stream_worker, PullRequest::new(1, 0, 0)).await?;
+    match Response::parse(child.receive::<ProtocolPayload>().await?.body())? {
+        Response::PullResponse(PullResponse { messages, .. }) => assert_eq!(messages.len(), 2),
+        _ => panic!("unexpected response"),
+    }
+
+    // a consumer with the same client id resumes after the messages it already received
+    let (tx, mut rx) = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .stream_service("stream2")
+        .index_service("index2")
+        .client_id("consumer")
+        .connect(route![], "messages", "messages")
+        .await?;
+    ctx.send(route![tx.to_route()], "c".to_string()).await?;
+    assert_eq!(rx.next::<String>().await?.as_body(), "c");
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn messages_of_a_quiet_stream_expire(ctx: &mut Context) -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let options = StreamLogOptions {
+        retention_age: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    ctx.start_worker("stream", StreamService::new(dir.path(), options, None))
+        .await?;
+
+    let mut child = ctx
+        .new_detached(
+            Address::random_local(),
+            ockam_core::AllowAll,
+            ockam_core::AllowAll,
+        )
+        .await?;
+    child
+        .send(
+            route!["stream"],
+            CreateStreamRequest::new("messages".to_string()),
+        )
+        .await?;
+    let stream_worker = child.receive::<ProtocolPayload>().await?.return_route();
+    child
+        .send(stream_worker.clone(), PushRequest::new(1, b"old".to_vec()))
+        .await?;
+    child.receive::<ProtocolPayload>().await?;
+
+    // no message is appended, the message is removed by the periodic sweep
+    ctx.sleep(Duration::from_millis(600)).await;
+    child.send(stream_worker, PullRequest::new(2, 0, 0)).await?;
+    match Response::parse(child.receive::<ProtocolPayload>().await?.body())? {
+        Response::PullResponse(PullResponse { messages, .. }) => assert!(messages.is_empty()),
+        _ => panic!("unexpected response"),
+    }
+
+    ctx.stop().await
+}
+
+async fn start_services(
+    ctx: &Context,
+    dir: &Path,
+    storage: Arc<dyn Storage>,
+    stream_address: &str,
+    index_address: &str,
+) -> Result<()> {
+    ctx.start_worker(
+        stream_address,
+        StreamService::new(dir, StreamLogOptions::default(), None),
+    )
+    .await?;
+    ctx.start_worker(index_address, StreamIndexService::new(storage))
+        .await
+}
@@ -89,11 +89,16 @@ fn print_entries(entries: &[(IdentityIdentifier, AttributesEntry)]) {
     let model: Vec<_> = entries
         .iter()
         .map(|(identifier, entry)| {
-            let attrs: HashMap<String, String> = entry
+            let mut attrs: HashMap<String, serde_json::Value> = entry
                 .attrs()
                 .iter()
-                .map(|(k, v)| (k.to_string(), String::from_utf8(v.clone()).unwrap()))
+                .map(|(k, v)| (k.to_string(), String::from_utf8(v.clone()).unwrap().into()))
                 .collect();
+            attrs.extend(
+                entry
+                    .values()
+                    .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap())),
+            );
             (
                 String::from(identifier),
                 serde_json::to_string(&attrs).unwrap(),
@@ -10,12 +10,16 @@ use ockam::Context;
 use ockam_api::bootstrapped_identities_store::PreTrustedIdentities;
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
 use ockam_api::nodes::authority_node;
-use ockam_api::nodes::authority_node::{OktaConfiguration, TrustedIdentity};
+use ockam_api::nodes::authority_node::{
+    OidcConfiguration, OktaConfiguration, RemoteSignerConfiguration, TrustedIdentity,
+};
 use ockam_api::nodes::models::transport::{CreateTransportJson, TransportMode, TransportType};
+use ockam_api::oidc::ClaimMapping;
 use ockam_api::DefaultAddress;
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::fmt;
 use ockam_identity::{AttributesEntry, IdentityIdentifier};
+use ockam_multiaddr::MultiAddr;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
@@ -82,6 +86,46 @@ pub struct CreateCommand {
     #[arg(long, value_name = "ATTRIBUTE_NAMES", default_value = None)]
     attributes: Option<Vec<String>>,
 
+    /// OIDC: URL of the OpenID Connect provider issuing ID tokens (optional)
+    #[arg(long, value_name = "URL", default_value = None, requires = "oidc_client_id")]
+    oidc_issuer_url: Option<String>,
+
+    /// OIDC: client identifier registered with the OpenID Connect provider (optional)
+    #[arg(long, value_name = "CLIENT_ID", default_value = None, requires = "oidc_issuer_url")]
+    oidc_client_id: Option<String>,
+
+    /// OIDC: pem certificate used to access the OpenID Connect provider (optional)
+    #[arg(long, value_name = "STRING", default_value = None, requires = "oidc_issuer_url")]
+    oidc_certificate: Option<String>,
+
+    /// OIDC: claim to store as an attribute, in the `claim=attribute` format.
+    /// Nested claims are accessed with a dot, for example `realm_access.roles=roles`
+    #[arg(long = "oidc-claim", value_name = "CLAIM=ATTRIBUTE", value_parser = parse_claim_mapping, requires = "oidc_issuer_url")]
+    oidc_claims: Vec<ClaimMapping>,
+
+    /// Attribute made selectively disclosable in the issued credentials, so that members
+    /// decide if they reveal it when presenting their credential. This argument can be repeated
+    #[arg(long = "disclosable-attribute", value_name = "ATTRIBUTE")]
+    disclosable_attributes: Vec<String>,
+
+    /// Signer: address of the secure channel listener of a node running a signer service
+    /// which holds the keys of the authority identity (optional).
+    /// For example /dnsaddr/signer.example.com/tcp/4000/service/api
+    #[arg(long, value_name = "MULTIADDR", requires_all = ["signer_identity", "signer_client_identity"])]
+    signer: Option<MultiAddr>,
+
+    /// Signer: name of the signer service on the signer node
+    #[arg(long, value_name = "ADDRESS", default_value_t = DefaultAddress::SIGNER.to_string(), requires = "signer")]
+    signer_service: String,
+
+    /// Signer: identifier of the signer node
+    #[arg(long, value_name = "IDENTIFIER", requires = "signer")]
+    signer_identity: Option<IdentityIdentifier>,
+
+    /// Signer: name of the local identity authenticating the authority to the signer node
+    #[arg(long, value_name = "IDENTITY_NAME", requires = "signer")]
+    signer_client_identity: Option<String>,
+
     /// Run the node in foreground.
     #[arg(long, short, value_name = "BOOL", default_value_t = false)]
     foreground: bool,
@@ -163,6 +207,48 @@ async fn spawn_background_node(opts: &CommandGlobalOpts, cmd: &CreateCommand) ->
         });
     }
 
+    if let Some(oidc_issuer_url) = &cmd.oidc_issuer_url {
+        args.push("--oidc-issuer-url".to_string());
+        args.push(oidc_issuer_url.clone());
+    }
+
+    if let Some(oidc_client_id) = &cmd.oidc_client_id {
+        args.push("--oidc-client-id".to_string());
+        args.push(oidc_client_id.clone());
+    }
+
+    if let Some(oidc_certificate) = &cmd.oidc_certificate {
+        args.push("--oidc-certificate".to_string());
+        args.push(oidc_certificate.clone());
+    }
+
+    cmd.oidc_claims.iter().for_each(|mapping| {
+        args.push("--oidc-claim".to_string());
+        args.push(format!("{}={}", mapping.claim, mapping.attribute));
+    });
+
+    cmd.disclosable_attributes.iter().for_each(|name| {
+        args.push("--disclosable-attribute".to_string());
+        args.push(name.clone());
+    });
+
+    if let Some(signer) = &cmd.signer {
+        args.push("--signer".to_string());
+        args.push(signer.to_string());
+        args.push("--signer-service".to_string());
+        args.push(cmd.signer_service.clone());
+    }
+
+    if let Some(signer_identity) = &cmd.signer_identity {
+        args.push("--signer-identity".to_string());
+        args.push(signer_identity.to_string());
+    }
+
+    if let Some(signer_client_identity) = &cmd.signer_client_identity {
+        args.push("--signer-client-identity".to_string());
+        args.push(signer_client_identity.clone());
+    }
+
     if let Some(vault) = &cmd.vault {
         args.push("--vault".to_string());
         args.push(vault.clone());
@@ -283,6 +369,39 @@ async fn start_authority_node(
         _ => None,
     };
 
+    let oidc_configuration = match (&cmd.oidc_issuer_url, &cmd.oidc_client_id) {
+        (Some(issuer_url), Some(client_id)) => Some(OidcConfiguration {
+            address: DefaultAddress::OIDC_IDENTITY_PROVIDER.to_string(),
+            issuer_url: issuer_url.clone(),
+            client_id: client_id.clone(),
+            certificate: cmd.oidc_certificate.clone(),
+            claims: cmd.oidc_claims.clone(),
+        }),
+        _ => None,
+    };
+
+    let remote_signer = match (
+        &cmd.signer,
+        &cmd.signer_identity,
+        &cmd.signer_client_identity,
+    ) {
+        (Some(address), Some(identifier), Some(client_identity)) => {
+            Some(RemoteSignerConfiguration {
+                address: address.to_string(),
+                service: cmd.signer_service.clone(),
+                identifier: identifier.clone(),
+                client_identifier: opts
+                    .state
+                    .identities
+                    .get(client_identity)
+                    .context("Signer client identity not found")?
+                    .config()
+                    .identifier(),
+            })
+        }
+        _ => None,
+    };
+
     // persist the node state and mark it as an authority node
     // That flag allows the node to be seen as UP when listing the nodes with the
     // the `ockam node list` command, without having to send a TCP query to open a connection
@@ -317,12 +436,28 @@ async fn start_authority_node(
         no_direct_authentication: cmd.no_direct_authentication,
         no_token_enrollment: cmd.no_token_enrollment,
         okta: okta_configuration,
+        oidc: oidc_configuration,
+        disclosable_attributes: cmd.disclosable_attributes,
+        remote_signer,
     };
     authority_node::start_node(&ctx, &configuration).await?;
 
     Ok(())
 }
 
+/// Return a claim mapping passed as `claim=attribute` on the command line
+fn parse_claim_mapping(value: &str) -> Result<ClaimMapping> {
+    match value.split_once('=') {
+        Some((claim, attribute)) if !claim.is_empty() && !attribute.is_empty() => {
+            Ok(ClaimMapping::new(claim, attribute))
+        }
+        _ => Err(crate::Error::new(
+            exitcode::CONFIG,
+            miette!("Invalid claim mapping {value}, expected claim=attribute"),
+        )),
+    }
+}
+
 /// Return a list of trusted identities passed as a JSON string on the command line
 fn parse_trusted_identities(values: &str) -> Result<TrustedIdentities> {
     serde_json::from_str::<TrustedIdentities>(values).map_err(|e| {
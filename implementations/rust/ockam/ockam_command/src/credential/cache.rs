@@ -0,0 +1,71 @@
+use clap::Args;
+use colorful::Colorful;
+use ockam::Context;
+use ockam_api::nodes::models::credentials::CredentialsCacheStatus;
+use ockam_core::api::Request;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::terminal::OckamColor;
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Show how many verified credentials are cached by a node, and how often the cache
+/// spared a verification
+#[derive(Clone, Debug, Args)]
+pub struct CacheCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+}
+
+impl CacheCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CacheCommand),
+) -> crate::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    let is_finished: Mutex<bool> = Mutex::new(false);
+
+    let send_req = async {
+        rpc.request(Request::get("/node/credentials/cache")).await?;
+
+        *is_finished.lock().await = true;
+        rpc.parse_response::<CredentialsCacheStatus>()
+    };
+
+    let output_messages = vec![format!(
+        "Retrieving the credentials cache of {}...\n",
+        node_name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    )];
+
+    let progress_output = opts
+        .terminal
+        .progress_output(&output_messages, &is_finished);
+
+    let (status, _) = try_join!(send_req, progress_output)?;
+
+    let plain = format!(
+        "Cached credentials: {}\nHits: {}\nMisses: {}\nHit rate: {:.1}%",
+        status.entries,
+        status.hits,
+        status.misses,
+        status.hit_rate() * 100.0
+    );
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(serde_json::to_string_pretty(&status)?)
+        .write_line()?;
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+pub(crate) mod cache;
 pub(crate) mod get;
 pub(crate) mod issue;
 pub(crate) mod list;
@@ -6,6 +7,7 @@ pub(crate) mod show;
 pub(crate) mod store;
 pub(crate) mod verify;
 
+pub(crate) use cache::CacheCommand;
 use colorful::Colorful;
 pub(crate) use get::GetCommand;
 pub(crate) use issue::IssueCommand;
@@ -36,6 +38,7 @@ pub struct CredentialCommand {
 pub enum CredentialSubcommand {
     #[command(display_order = 900)]
     Get(GetCommand),
+    Cache(CacheCommand),
     Issue(IssueCommand),
     List(ListCommand),
     Present(PresentCommand),
@@ -48,6 +51,7 @@ impl CredentialCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             CredentialSubcommand::Get(c) => c.run(options),
+            CredentialSubcommand::Cache(c) => c.run(options),
             CredentialSubcommand::Issue(c) => c.run(options),
             CredentialSubcommand::List(c) => c.run(options),
             CredentialSubcommand::Present(c) => c.run(options),
@@ -1,11 +1,12 @@
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
 use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use colorful::Colorful;
 use ockam::Context;
 use ockam_api::cli_state::traits::StateDirTrait;
 use ockam_identity::IdentityIdentifier;
+use ockam_vault::SecretAttributes;
 use rand::prelude::random;
 use tokio::sync::Mutex;
 use tokio::try_join;
@@ -26,11 +27,36 @@ pub struct CreateCommand {
     /// Vault name to store the identity key
     #[arg(long, value_name = "VAULT_NAME", global = true)]
     vault: Option<String>,
+
+    /// Type of the identity root key
+    #[arg(long, value_enum, default_value_t = KeyType::Ed25519)]
+    key_type: KeyType,
+}
+
+/// Type of the root key of an identity
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    /// ECDSA with the NIST P-256 curve
+    P256,
+}
+
+impl From<KeyType> for SecretAttributes {
+    fn from(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => SecretAttributes::Ed25519,
+            KeyType::P256 => SecretAttributes::NistP256,
+        }
+    }
 }
 
 impl CreateCommand {
     pub fn new(name: String, vault: Option<String>) -> CreateCommand {
-        CreateCommand { name, vault }
+        CreateCommand {
+            name,
+            vault,
+            key_type: KeyType::Ed25519,
+        }
     }
 
     pub fn run(self, options: CommandGlobalOpts) {
@@ -79,7 +105,7 @@ impl CreateCommand {
                 .get_identities(vault)
                 .await?
                 .identities_creation()
-                .create_identity()
+                .create_identity_with_secret_attributes(self.key_type.into())
                 .await?;
 
             opts.state
@@ -0,0 +1,89 @@
+use crate::identity::{
+    get_identity_name, identity_bundle_password, initialize_identity_if_default,
+};
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::vault::default_vault_name;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use ockam::Context;
+use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
+use std::path::PathBuf;
+
+const LONG_ABOUT: &str = include_str!("./static/export/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/export/after_long_help.txt");
+
+/// Export an identity to a file
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ExportCommand {
+    #[arg()]
+    name: Option<String>,
+
+    /// Path of the file where the identity bundle is written
+    #[arg(short, long, value_name = "FILE")]
+    file: PathBuf,
+
+    /// Export the secret keys of the identity, encrypted with a password
+    #[arg(long)]
+    encrypt: bool,
+
+    /// Name of the vault storing the secret keys of the identity
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl ExportCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_identity_if_default(&opts, &self.name);
+        node_rpc(Self::run_impl, (opts, self))
+    }
+
+    async fn run_impl(
+        _ctx: Context,
+        (opts, cmd): (CommandGlobalOpts, ExportCommand),
+    ) -> crate::Result<()> {
+        let name = get_identity_name(&opts.state, &cmd.name);
+        let identifier = opts.state.identities.get(&name)?.config().identifier();
+
+        let vault_name = cmd
+            .vault
+            .clone()
+            .unwrap_or_else(|| default_vault_name(&opts.state));
+        let vault = opts.state.vaults.get(&vault_name)?.get().await?;
+        let identities = opts.state.get_identities(vault).await?;
+        let identity = identities.repository().get_identity(&identifier).await?;
+
+        let password = if cmd.encrypt {
+            Some(identity_bundle_password(&opts, true)?)
+        } else {
+            None
+        };
+        let bundle = identities
+            .identities_creation()
+            .export_identity_bundle(&identity, password.as_deref())
+            .await?;
+        std::fs::write(&cmd.file, bundle.export()?)?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Identity {} exported to {}",
+                identifier
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color()),
+                cmd.file
+                    .display()
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ))
+            .machine(identifier.clone())
+            .json(serde_json::json!({ "identity": { "identifier": &identifier } }))
+            .write_line()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::identity::identity_bundle_password;
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+use ockam::identity::IdentityBundle;
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+use rand::prelude::random;
+use std::path::PathBuf;
+
+const LONG_ABOUT: &str = include_str!("./static/import/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/import/after_long_help.txt");
+
+/// Import an identity from a file
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ImportCommand {
+    /// Path of the identity bundle created with `ockam identity export`
+    #[arg(value_name = "FILE")]
+    input: PathBuf,
+
+    /// Name of the imported identity
+    #[arg(long, hide_default_value = true, default_value_t = hex::encode(& random::< [u8; 4] > ()))]
+    name: String,
+
+    /// Name of the vault where the secret keys of the identity are stored
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl ImportCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(Self::run_impl, (opts, self))
+    }
+
+    async fn run_impl(
+        _ctx: Context,
+        (opts, cmd): (CommandGlobalOpts, ImportCommand),
+    ) -> crate::Result<()> {
+        if opts.state.identities.exists(&cmd.name) {
+            return Err(miette!("An identity named {} already exists", cmd.name).into());
+        }
+        let bundle = IdentityBundle::import(&std::fs::read(&cmd.input)?)?;
+        let password = if bundle.has_secrets() {
+            Some(identity_bundle_password(&opts, false)?)
+        } else {
+            None
+        };
+
+        let vault = opts.state.create_vault_state(cmd.vault.as_deref()).await?;
+        let identity = opts
+            .state
+            .get_identities(vault.get().await?)
+            .await?
+            .identities_creation()
+            .import_identity_bundle(&bundle, password.as_deref())
+            .await?;
+        let identifier = identity.identifier();
+        opts.state
+            .create_identity_state(&identifier, Some(&cmd.name))
+            .await?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Identity {} imported as {}",
+                identifier
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color()),
+                cmd.name
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ))
+            .machine(identifier.clone())
+            .json(serde_json::json!({ "identity": { "identifier": &identifier } }))
+            .write_line()?;
+        Ok(())
+    }
+}
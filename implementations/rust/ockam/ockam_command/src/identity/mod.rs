@@ -1,12 +1,16 @@
 mod create;
 mod default;
 mod delete;
+mod export;
+mod import;
 mod list;
 mod show;
 
 use colorful::Colorful;
 pub(crate) use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
+pub(crate) use export::ExportCommand;
+pub(crate) use import::ImportCommand;
 pub(crate) use list::ListCommand;
 pub(crate) use show::ShowCommand;
 
@@ -14,8 +18,10 @@ use crate::identity::default::DefaultCommand;
 use crate::terminal::OckamColor;
 use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts, PARSER_LOGS};
 use clap::{Args, Subcommand};
+use miette::miette;
 use ockam_api::cli_state::traits::StateDirTrait;
 use ockam_api::cli_state::CliState;
+use ockam_core::env::get_env;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 
@@ -38,6 +44,8 @@ pub enum IdentitySubcommand {
     List(ListCommand),
     Default(DefaultCommand),
     Delete(DeleteCommand),
+    Export(ExportCommand),
+    Import(ImportCommand),
 }
 
 impl IdentityCommand {
@@ -48,6 +56,8 @@ impl IdentityCommand {
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::Export(c) => c.run(options),
+            IdentitySubcommand::Import(c) => c.run(options),
         }
     }
 }
@@ -77,6 +87,19 @@ pub fn get_default_identity_name(cli_state: &CliState) -> String {
         .unwrap_or_else(|_| "default".to_string())
 }
 
+/// Return the password protecting the secret keys of an identity bundle, either from the
+/// `OCKAM_IDENTITY_PASSWORD` environment variable or by asking the user
+fn identity_bundle_password(opts: &CommandGlobalOpts, confirm: bool) -> crate::Result<String> {
+    if let Some(password) = get_env::<String>("OCKAM_IDENTITY_PASSWORD")? {
+        return Ok(password);
+    }
+    opts.terminal
+        .password("Identity password", confirm)?
+        .ok_or_else(|| {
+            miette!("A password is required, please set the OCKAM_IDENTITY_PASSWORD environment variable").into()
+        })
+}
+
 /// Create the default identity
 fn create_default_identity(opts: &CommandGlobalOpts) {
     let default = "default";
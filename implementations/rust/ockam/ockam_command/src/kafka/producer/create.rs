@@ -1,13 +1,18 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use clap::{command, Args};
 use colorful::Colorful;
+use miette::miette;
 use ockam::{Context, TcpTransport};
 use ockam_api::{
-    nodes::models::services::{StartKafkaProducerRequest, StartServiceRequest},
+    nodes::models::services::{
+        KafkaRecordEncryption, StartKafkaProducerRequest, StartServiceRequest,
+    },
     port_range::PortRange,
 };
 use ockam_core::api::Request;
+use ockam_core::env::get_env;
 use ockam_multiaddr::MultiAddr;
 use tokio::{sync::Mutex, try_join};
 
@@ -22,7 +27,7 @@ use crate::{
     node::NodeOpts,
     service::start::start_service_impl,
     terminal::OckamColor,
-    util::{node_rpc, parsers::socket_addr_parser},
+    util::{exitcode, node_rpc, parsers::socket_addr_parser},
     CommandGlobalOpts,
 };
 
@@ -46,6 +51,20 @@ pub struct CreateCommand {
     /// The route to the project in ockam orchestrator, expected something like /project/<name>
     #[arg(long, default_value_t = kafka_default_project_route())]
     project_route: MultiAddr,
+    /// Encrypt the headers of the records, in addition to their values
+    #[arg(long)]
+    encrypt_headers: bool,
+    /// Encrypt the keys of the records, in addition to their values.
+    /// The keys are removed from the records stored by the brokers, which reject them on
+    /// compacted topics, unless a deterministic key secret is set
+    #[arg(long)]
+    encrypt_keys: bool,
+    /// File containing the secret used to replace the encrypted keys with a keyed hash, so that
+    /// records with the same key keep landing in the same partition and compacted topics keep
+    /// working. The secret can also be set with the OCKAM_KAFKA_DETERMINISTIC_KEY_SECRET
+    /// environment variable. All the producers of a topic must use the same secret
+    #[arg(long, value_name = "PATH", requires = "encrypt_keys")]
+    deterministic_key_secret_file: Option<PathBuf>,
 }
 
 impl CreateCommand {
@@ -67,8 +86,17 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> c
         bootstrap_server,
         brokers_port_range,
         project_route,
+        encrypt_headers,
+        encrypt_keys,
+        deterministic_key_secret_file,
     } = cmd;
 
+    let deterministic_key_secret = if encrypt_keys {
+        deterministic_key_secret(deterministic_key_secret_file)?
+    } else {
+        None
+    };
+
     let project_route = process_nodes_multiaddr(&project_route, &opts.state)?;
 
     let is_finished = Mutex::new(false);
@@ -77,7 +105,12 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> c
         let node_name = get_node_name(&opts.state, &node_opts.at_node);
 
         let payload =
-            StartKafkaProducerRequest::new(bootstrap_server, brokers_port_range, project_route);
+            StartKafkaProducerRequest::new(bootstrap_server, brokers_port_range, project_route)
+                .with_record_encryption(KafkaRecordEncryption {
+                    headers: encrypt_headers,
+                    keys: encrypt_keys,
+                    deterministic_key_secret,
+                });
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post("/node/services/kafka_producer").body(payload);
         start_service_impl(&ctx, &opts, &node_name, "KafkaProducer", req, Some(&tcp)).await?;
@@ -127,3 +160,24 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> c
 
     Ok(())
 }
+
+/// Read the secret used to hash the record keys, from a file or from the
+/// `OCKAM_KAFKA_DETERMINISTIC_KEY_SECRET` environment variable
+fn deterministic_key_secret(file: Option<PathBuf>) -> crate::Result<Option<String>> {
+    let secret = match file {
+        Some(path) => Some(std::fs::read_to_string(&path).map_err(|e| {
+            crate::Error::new(
+                exitcode::IOERR,
+                miette!("Failed to read the deterministic key secret from {path:?}: {e}"),
+            )
+        })?),
+        None => get_env::<String>("OCKAM_KAFKA_DETERMINISTIC_KEY_SECRET")?,
+    };
+    match secret.as_deref().map(str::trim_end) {
+        Some("") => Err(crate::Error::new(
+            exitcode::USAGE,
+            miette!("The deterministic key secret must not be empty"),
+        )),
+        secret => Ok(secret.map(str::to_string)),
+    }
+}
@@ -43,6 +43,7 @@ mod util;
 mod vault;
 mod version;
 mod worker;
+mod ws;
 
 use crate::admin::AdminCommand;
 use crate::authority::AuthorityCommand;
@@ -94,6 +95,7 @@ use util::{exitcode, exitcode::ExitCode};
 use vault::VaultCommand;
 use version::Version;
 use worker::WorkerCommand;
+use ws::listener::WebSocketListenerCommand;
 
 const ABOUT: &str = include_str!("./static/about.txt");
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -273,6 +275,7 @@ pub enum OckamSubcommand {
     TcpConnection(TcpConnectionCommand),
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
+    WsListener(WebSocketListenerCommand),
 
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
@@ -388,6 +391,7 @@ impl OckamCommand {
             OckamSubcommand::TcpConnection(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
+            OckamSubcommand::WsListener(c) => c.run(options),
 
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
@@ -16,19 +16,24 @@ use std::{
 use tokio::try_join;
 use tracing::error;
 
-use crate::node::util::{add_project_info_to_node_state, init_node_state, spawn_node};
+use crate::node::util::{
+    add_admins_to_node_state, add_project_info_to_node_state, init_node_state, spawn_node,
+};
 use crate::secure_channel::listener::create as secure_channel_listener;
 use crate::service::config::Config;
 use crate::terminal::OckamColor;
 use crate::util::api::{parse_trust_context, TrustContextConfigBuilder, TrustContextOpts};
 use crate::util::node_rpc;
-use crate::util::{api, parse_node_name, RpcBuilder};
+use crate::util::parsers::duration_parser;
+use crate::util::{api, parse_node_name, proxy_settings_from_env, RpcBuilder};
 use crate::util::{bind_to_port_check, embedded_node_that_is_not_stopped, exitcode};
 use crate::{docs, identity, util::find_available_port, CommandGlobalOpts, Result};
 use crate::{fmt_log, fmt_ok};
-use ockam::{Address, AsyncTryClone, TcpListenerOptions};
+use ockam::identity::IdentityIdentifier;
+use ockam::{Address, AsyncTryClone, ForwarderBufferOptions, TcpListenerOptions};
 use ockam::{Context, TcpTransport};
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
+use ockam_api::cli_state::{NodeState, RelayBufferConfig};
 use ockam_api::config::lookup::ProjectLookup;
 use ockam_api::nodes::authority_node;
 use ockam_api::nodes::models::transport::CreateTransportJson;
@@ -37,14 +42,15 @@ use ockam_api::{
     bootstrapped_identities_store::PreTrustedIdentities,
     nodes::models::transport::{TransportMode, TransportType},
     nodes::{
+        send_to_node_manager,
         service::{
             NodeManagerGeneralOptions, NodeManagerProjectsOptions, NodeManagerTransportOptions,
         },
-        NodeManager, NodeManagerWorker, NODEMANAGER_ADDR,
+        NodeManager, NodeManagerWorker,
     },
 };
 use ockam_core::api::{RequestBuilder, Response, Status};
-use ockam_core::{route, LOCAL};
+use ockam_core::LOCAL;
 
 use super::show::is_node_up;
 
@@ -111,8 +117,83 @@ pub struct CreateCommand {
     #[arg(long = "credential", value_name = "CREDENTIAL_NAME")]
     pub credential: Option<String>,
 
+    /// Identifier of an identity allowed to manage the node, in addition to the default identity.
+    /// Can be repeated
+    #[arg(long = "admin", value_name = "IDENTIFIER")]
+    pub admins: Vec<IdentityIdentifier>,
+
     #[command(flatten)]
     pub trust_context_opts: TrustContextOpts,
+
+    #[command(flatten)]
+    pub relay_buffer_opts: RelayBufferOpts,
+}
+
+/// Options to buffer the messages sent to the relays hosted by the node
+#[derive(Clone, Debug, Args, Default)]
+pub struct RelayBufferOpts {
+    /// Buffer at most this number of messages per relay, while the node which created the relay is disconnected
+    #[arg(long, value_name = "MESSAGES")]
+    pub relay_buffer_size: Option<usize>,
+
+    /// Drop the buffered relay messages which are older than this duration
+    #[arg(long, value_name = "DURATION", requires = "relay_buffer_size", value_parser = duration_parser)]
+    pub relay_buffer_ttl: Option<Duration>,
+
+    /// Store the buffered relay messages on disk, so that they survive a restart of the node
+    #[arg(long, requires = "relay_buffer_size")]
+    pub relay_buffer_on_disk: bool,
+}
+
+impl RelayBufferOpts {
+    fn forwarder_buffer_options(&self, node_state: &NodeState) -> Option<ForwarderBufferOptions> {
+        let mut options = ForwarderBufferOptions::new(self.relay_buffer_size?);
+        if let Some(ttl) = self.relay_buffer_ttl {
+            options = options.with_ttl(ttl);
+        }
+        if self.relay_buffer_on_disk {
+            options = options.on_disk(node_state.relay_buffers_dir());
+        }
+        Some(options)
+    }
+
+    /// Options persisted in the node state
+    fn config(&self) -> Option<RelayBufferConfig> {
+        Some(RelayBufferConfig {
+            size: self.relay_buffer_size?,
+            ttl_secs: self.relay_buffer_ttl.map(|ttl| ttl.as_secs()),
+            on_disk: self.relay_buffer_on_disk,
+        })
+    }
+
+    /// Options used when restarting a node with its persisted state
+    pub(crate) fn from_config(config: Option<&RelayBufferConfig>) -> Self {
+        match config {
+            Some(config) => Self {
+                relay_buffer_size: Some(config.size),
+                relay_buffer_ttl: config.ttl_secs.map(Duration::from_secs),
+                relay_buffer_on_disk: config.on_disk,
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Arguments passed to the process running the node
+    pub(crate) fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(size) = self.relay_buffer_size {
+            args.push("--relay-buffer-size".to_string());
+            args.push(size.to_string());
+        }
+        if let Some(ttl) = self.relay_buffer_ttl {
+            args.push("--relay-buffer-ttl".to_string());
+            args.push(format!("{}s", ttl.as_secs()));
+        }
+        if self.relay_buffer_on_disk {
+            args.push("--relay-buffer-on-disk".to_string());
+        }
+        args
+    }
 }
 
 impl Default for CreateCommand {
@@ -131,7 +212,9 @@ impl Default for CreateCommand {
             reload_from_trusted_identities_file: None,
             authority_identity: None,
             credential: None,
+            admins: vec![],
             trust_context_opts: TrustContextOpts::default(),
+            relay_buffer_opts: RelayBufferOpts::default(),
         }
     }
 }
@@ -271,6 +354,9 @@ async fn run_foreground_node(
         )
         .await?;
     }
+    if !cmd.child_process {
+        add_admins_to_node_state(&opts, &node_name, &cmd.admins)?;
+    }
 
     add_project_info_to_node_state(&node_name, &opts, &cmd.trust_context_opts).await?;
 
@@ -281,6 +367,7 @@ async fn run_foreground_node(
             .build();
 
     let tcp = TcpTransport::create(&ctx).await?;
+    tcp.set_proxy_settings(proxy_settings_from_env()?);
     let bind = &cmd.tcp_listener_address;
 
     let options = TcpListenerOptions::new();
@@ -292,6 +379,7 @@ async fn run_foreground_node(
             .config()
             .setup_mut()
             .set_verbose(opts.global_args.verbose)
+            .set_relay_buffer(cmd.relay_buffer_opts.config())
             .add_transport(CreateTransportJson::new(
                 TransportType::Tcp,
                 TransportMode::Listen,
@@ -302,14 +390,19 @@ async fn run_foreground_node(
     let projects = ProjectLookup::from_state(opts.state.projects.list()?).await?;
     let pre_trusted_identities = load_pre_trusted_identities(&cmd)?;
 
+    let mut general_options = NodeManagerGeneralOptions::new(
+        opts.state.clone(),
+        cmd.node_name.clone(),
+        cmd.launch_config.is_some(),
+        pre_trusted_identities,
+    );
+    if let Some(forwarder_buffer) = cmd.relay_buffer_opts.forwarder_buffer_options(&node_state) {
+        general_options = general_options.with_forwarder_buffer(forwarder_buffer);
+    }
+
     let node_man = NodeManager::create(
         &ctx,
-        NodeManagerGeneralOptions::new(
-            opts.state.clone(),
-            cmd.node_name.clone(),
-            cmd.launch_config.is_some(),
-            pre_trusted_identities,
-        ),
+        general_options,
         NodeManagerProjectsOptions::new(projects),
         NodeManagerTransportOptions::new(
             listener.flow_control_id().clone(),
@@ -320,10 +413,7 @@ async fn run_foreground_node(
     .await?;
     let node_manager_worker = NodeManagerWorker::new(node_man);
 
-    ctx.flow_controls()
-        .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
-    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
-        .await?;
+    node_manager_worker.start(&ctx).await?;
 
     if let Some(config) = &cmd.launch_config {
         if start_services(&ctx, config).await.is_err() {
@@ -421,7 +511,7 @@ async fn start_services(ctx: &Context, cfg: &Config) -> Result<()> {
             let ids = cfg.authorized_identifiers;
             let identity = cfg.identity;
             println!("starting secure-channel listener ...");
-            secure_channel_listener::create_listener(ctx, adr, ids, identity, route![]).await?;
+            secure_channel_listener::create_listener(ctx, adr, ids, identity).await?;
         }
     }
     if let Some(cfg) = config.verifier {
@@ -453,9 +543,7 @@ async fn send_req_to_node_manager<T>(ctx: &Context, req: RequestBuilder<'_, T>)
 where
     T: Encode<()>,
 {
-    let buf: Vec<u8> = ctx
-        .send_and_receive(NODEMANAGER_ADDR, req.to_vec()?)
-        .await?;
+    let buf: Vec<u8> = send_to_node_manager(ctx, req.to_vec()?).await?;
     let mut dec = Decoder::new(&buf);
     let hdr = dec.decode::<Response>()?;
     if hdr.status() != Some(Status::Ok) {
@@ -487,6 +575,7 @@ async fn spawn_background_node(
         cmd.identity.as_deref(),
     )
     .await?;
+    add_admins_to_node_state(opts, &node_name, &cmd.admins)?;
 
     let trust_context_path = match cmd.trust_context_opts.trust_context.clone() {
         Some(tc) => {
@@ -514,6 +603,7 @@ async fn spawn_background_node(
         cmd.credential.as_ref(),
         trust_context_path.as_ref(),
         cmd.trust_context_opts.project.as_ref(),
+        &cmd.relay_buffer_opts,
     )?;
 
     Ok(())
@@ -565,6 +655,9 @@ async fn start_authority_node(
             no_direct_authentication: true,
             no_token_enrollment: true,
             okta: None,
+            oidc: None,
+            disclosable_attributes: authenticator_config.disclosable_attributes,
+            remote_signer: None,
         };
         authority_node::start_node(&ctx, &configuration).await?;
     }
@@ -7,6 +7,7 @@ use delete::DeleteCommand;
 use list::ListCommand;
 use logs::LogCommand;
 use ockam_api::cli_state::{CliState, StateDirTrait};
+use sessions::SessionsCommand;
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
@@ -18,6 +19,7 @@ mod default;
 mod delete;
 mod list;
 mod logs;
+mod sessions;
 mod show;
 mod start;
 mod stop;
@@ -52,6 +54,8 @@ pub enum NodeSubcommand {
     Logs(LogCommand),
     Show(ShowCommand),
     #[command(display_order = 800)]
+    Sessions(SessionsCommand),
+    #[command(display_order = 800)]
     Start(StartCommand),
     #[command(display_order = 800)]
     Stop(StopCommand),
@@ -66,6 +70,7 @@ impl NodeCommand {
             NodeSubcommand::Delete(c) => c.run(options),
             NodeSubcommand::List(c) => c.run(options),
             NodeSubcommand::Show(c) => c.run(options),
+            NodeSubcommand::Sessions(c) => c.run(options),
             NodeSubcommand::Start(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
             NodeSubcommand::Logs(c) => c.run(options),
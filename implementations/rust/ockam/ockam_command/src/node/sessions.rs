@@ -0,0 +1,72 @@
+use crate::node::{get_node_name, initialize_node_if_default};
+use crate::terminal::OckamColor;
+use crate::util::{node_rpc, Rpc};
+use crate::{docs, CommandGlobalOpts};
+
+use clap::Args;
+use colorful::Colorful;
+use ockam_api::nodes::models::session::SessionList;
+use ockam_core::api::Request;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+const LONG_ABOUT: &str = include_str!("./static/sessions/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/sessions/after_long_help.txt");
+
+/// List the sessions monitored by a node
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct SessionsCommand {
+    /// Name of the node.
+    #[arg()]
+    node_name: Option<String>,
+}
+
+impl SessionsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_name);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: ockam::Context,
+    (opts, cmd): (CommandGlobalOpts, SessionsCommand),
+) -> crate::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    let is_finished: Mutex<bool> = Mutex::new(false);
+
+    let send_req = async {
+        rpc.request(Request::get("/node/sessions")).await?;
+
+        *is_finished.lock().await = true;
+        rpc.parse_response::<SessionList>()
+    };
+
+    let output_messages = vec![format!(
+        "Listing Sessions on {}...\n",
+        node_name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    )];
+
+    let progress_output = opts
+        .terminal
+        .progress_output(&output_messages, &is_finished);
+
+    let (sessions, _) = try_join!(send_req, progress_output)?;
+
+    let list = opts.terminal.build_list(
+        &sessions.list,
+        "Sessions",
+        &format!("No Sessions found on {node_name}"),
+    )?;
+    opts.terminal.stdout().plain(list).write_line()?;
+
+    Ok(())
+}
@@ -6,7 +6,7 @@ use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 
 use crate::node::show::print_query_status;
 use crate::node::util::{check_default, spawn_node};
-use crate::node::{get_node_name, initialize_node_if_default};
+use crate::node::{get_node_name, initialize_node_if_default, RelayBufferOpts};
 use crate::util::{node_rpc, RpcBuilder};
 use crate::{docs, fmt_err, CommandGlobalOpts};
 
@@ -72,6 +72,7 @@ async fn run_impl(
         None,               // Credential
         None,               // Trust Context
         None,               // Project Name
+        &RelayBufferOpts::from_config(node_setup.relay_buffer()), // Previously chosen relay buffer
     )?;
 
     // Print node status
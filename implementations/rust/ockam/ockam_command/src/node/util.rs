@@ -1,6 +1,7 @@
 use anyhow::Context as _;
 use miette::miette;
 
+use ockam::identity::IdentityIdentifier;
 use ockam::{Context, TcpListenerOptions, TcpTransport};
 use ockam_api::cli_state;
 use ockam_api::cli_state::traits::StateItemTrait;
@@ -11,16 +12,17 @@ use ockam_api::nodes::service::{
     NodeManagerGeneralOptions, NodeManagerProjectsOptions, NodeManagerTransportOptions,
     NodeManagerTrustOptions,
 };
-use ockam_api::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+use ockam_api::nodes::{NodeManager, NodeManagerWorker};
 use std::env::current_exe;
 use std::fs::OpenOptions;
 use std::path::PathBuf;
 use std::process::Command;
 use tracing::{debug, info};
 
-use crate::node::CreateCommand;
+use crate::node::{CreateCommand, RelayBufferOpts};
 use crate::project::ProjectInfo;
 use crate::util::api::{TrustContextConfigBuilder, TrustContextOpts};
+use crate::util::proxy_settings_from_env;
 use crate::{CommandGlobalOpts, Result};
 
 pub async fn start_embedded_node(
@@ -57,6 +59,7 @@ pub async fn start_embedded_node_with_vault_and_identity(
     };
 
     let tcp = TcpTransport::create(ctx).await?;
+    tcp.set_proxy_settings(proxy_settings_from_env()?);
     let bind = cmd.tcp_listener_address;
 
     let options = TcpListenerOptions::new();
@@ -80,11 +83,7 @@ pub async fn start_embedded_node_with_vault_and_identity(
 
     let node_manager_worker = NodeManagerWorker::new(node_man);
 
-    ctx.flow_controls()
-        .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
-
-    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
-        .await?;
+    node_manager_worker.start(ctx).await?;
 
     Ok(cmd.node_name.clone())
 }
@@ -154,6 +153,23 @@ pub(crate) async fn init_node_state(
     Ok(())
 }
 
+/// Allow the default identity and the given identities to use the node manager of a node
+pub(crate) fn add_admins_to_node_state(
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    admins: &[IdentityIdentifier],
+) -> Result<()> {
+    let default_identity = opts.state.identities.default().map(|i| i.identifier());
+    let node_state = opts.state.nodes.get(node_name)?;
+    node_state.set_setup(
+        &node_state
+            .config()
+            .setup_mut()
+            .add_admins(default_identity.into_iter().chain(admins.iter().cloned())),
+    )?;
+    Ok(())
+}
+
 pub async fn delete_embedded_node(opts: &CommandGlobalOpts, name: &str) {
     let _ = delete_node(opts, name, false);
 }
@@ -205,6 +221,7 @@ pub fn spawn_node(
     credential: Option<&String>,
     trust_context: Option<&PathBuf>,
     project_name: Option<&String>,
+    relay_buffer_opts: &RelayBufferOpts,
 ) -> crate::Result<()> {
     let mut args = vec![
         match verbose {
@@ -277,6 +294,8 @@ pub fn spawn_node(
         args.push(project_name.to_string());
     }
 
+    args.extend(relay_buffer_opts.args());
+
     args.push(name.to_owned());
 
     run_ockam(opts, name, args)
@@ -1,14 +1,19 @@
 use clap::Args;
+use colorful::Colorful;
 use ockam_api::cloud::ORCHESTRATOR_RESTART_TIMEOUT;
 use ockam_api::config::cli::TrustContextConfig;
 use ockam_api::identity::EnrollmentTicket;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
 use std::time::Duration;
+use time::OffsetDateTime;
 
 use anyhow::Context as _;
 use miette::miette;
-use ockam::identity::IdentityIdentifier;
+use ockam::identity::{AttributeValue, IdentityIdentifier, OneTimeCode};
 use ockam::Context;
+use ockam_api::authenticator::direct::types::CreateToken;
+use ockam_api::authenticator::direct::types::EnrollmentToken;
 use ockam_api::authenticator::direct::{DirectAuthenticatorClient, TokenIssuerClient};
 use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
 use ockam_api::config::lookup::{ProjectAuthority, ProjectLookup};
@@ -20,13 +25,27 @@ use ockam_node::RpcClient;
 use crate::identity::{get_identity_name, initialize_identity_if_default};
 use crate::node::util::{delete_embedded_node, start_embedded_node};
 use crate::project::util::create_secure_channel_to_authority;
+use crate::terminal::OckamColor;
 use crate::util::api::{parse_trust_context, CloudOpts, TrustContextOpts};
 use crate::util::node_rpc;
+use crate::util::output::Output;
+use crate::util::parsers::duration_parser;
 use crate::{docs, CommandGlobalOpts, Result};
 
 const LONG_ABOUT: &str = include_str!("./static/ticket/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/ticket/after_long_help.txt");
 
+/// Arguments which only make sense when adding a member or issuing a ticket
+const ISSUING_ARGS: [&str; 7] = [
+    "member",
+    "attributes",
+    "attribute_values",
+    "expires_in",
+    "usage_count",
+    "for_identifier",
+    "member_expires_in",
+];
+
 /// Add members to a project as an authorised enroller.
 #[derive(Clone, Debug, Args)]
 #[command(
@@ -50,6 +69,38 @@ pub struct TicketCommand {
     /// Attributes in `key=value` format to be attached to the member
     #[arg(short, long = "attribute", value_name = "ATTRIBUTE")]
     attributes: Vec<String>,
+
+    /// Typed attributes in `key=value` format, where the value is a JSON string, integer,
+    /// boolean or list, for example `groups=["admins","devs"]`
+    #[arg(long = "attribute-value", value_name = "ATTRIBUTE")]
+    attribute_values: Vec<String>,
+
+    /// Duration for which the enrollment ticket can be used, for example `30m`, `12h` or `7d`.
+    /// The default is 10 minutes, and the maximum is 30 days
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser, conflicts_with = "member")]
+    expires_in: Option<Duration>,
+
+    /// Number of times the enrollment ticket can be used. The default is 1
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "member")]
+    usage_count: Option<u64>,
+
+    /// Only allow the given identity to use the enrollment ticket
+    #[arg(long, value_name = "IDENTIFIER", conflicts_with = "member")]
+    for_identifier: Option<IdentityIdentifier>,
+
+    /// Duration after which the attributes of the member expire, for example `12h` or `30d`.
+    /// This applies to a member added with `--member` as well as to the members enrolled with
+    /// the ticket. By default the attributes never expire
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    member_expires_in: Option<Duration>,
+
+    /// List the enrollment tickets created by this identity which can still be used
+    #[arg(long, conflicts_with_all = ISSUING_ARGS, conflicts_with = "revoke")]
+    list: bool,
+
+    /// Revoke the enrollment ticket with the given one-time code
+    #[arg(long, value_name = "CODE", conflicts_with_all = ISSUING_ARGS)]
+    revoke: Option<OneTimeCode>,
 }
 
 impl TicketCommand {
@@ -71,6 +122,19 @@ impl TicketCommand {
         }
         Ok(attributes)
     }
+
+    fn attribute_values(&self) -> Result<BTreeMap<String, AttributeValue>> {
+        let mut values = BTreeMap::new();
+        for attr in &self.attribute_values {
+            let mut parts = attr.splitn(2, '=');
+            let key = parts.next().context("key expected")?;
+            let value = parts.next().context("value expected")?;
+            let value: AttributeValue = serde_json::from_str(value)
+                .context(format!("invalid value for the attribute {key}"))?;
+            values.insert(key.to_string(), value);
+        }
+        Ok(values)
+    }
 }
 
 struct Runner {
@@ -160,7 +224,12 @@ impl Runner {
                 .with_timeout(Duration::from_secs(ORCHESTRATOR_RESTART_TIMEOUT)),
             );
             client
-                .add_member(id.clone(), self.cmd.attributes()?)
+                .add_member_with_attribute_values(
+                    id.clone(),
+                    self.cmd.attributes()?,
+                    self.cmd.attribute_values()?,
+                    self.cmd.member_expires_in,
+                )
                 .await?
         } else {
             let token_issuer_route = {
@@ -182,11 +251,41 @@ impl Runner {
                 .await?
                 .with_timeout(Duration::from_secs(ORCHESTRATOR_RESTART_TIMEOUT)),
             );
-            let token = client.create_token(self.cmd.attributes()?).await?;
 
-            let ticket = EnrollmentTicket::new(token, project, trust_context);
-            let ticket_serialized = hex::encode(serde_json::to_vec(&ticket)?);
-            print!("{}", ticket_serialized)
+            if self.cmd.list {
+                let tokens = client.list_tokens().await?;
+                let plain = self.opts.terminal.build_list(
+                    &tokens,
+                    "Enrollment tickets",
+                    "No enrollment tickets found.",
+                )?;
+                let json = serde_json::to_string_pretty(&tokens)?;
+                self.opts
+                    .terminal
+                    .clone()
+                    .stdout()
+                    .plain(plain)
+                    .json(json)
+                    .write_line()?;
+            } else if let Some(code) = &self.cmd.revoke {
+                client.revoke_token(code).await?;
+            } else {
+                let token = client
+                    .create_token(
+                        CreateToken::new()
+                            .with_attributes(self.cmd.attributes()?)
+                            .with_attribute_values(self.cmd.attribute_values()?)
+                            .with_duration(self.cmd.expires_in)
+                            .with_ttl_count(self.cmd.usage_count)
+                            .with_required_identifier(self.cmd.for_identifier.clone())
+                            .with_member_duration(self.cmd.member_expires_in),
+                    )
+                    .await?;
+
+                let ticket = EnrollmentTicket::new(token, project, trust_context);
+                let ticket_serialized = hex::encode(serde_json::to_vec(&ticket)?);
+                print!("{}", ticket_serialized)
+            }
         }
 
         delete_embedded_node(&self.opts, &node_name).await;
@@ -194,6 +293,36 @@ impl Runner {
     }
 }
 
+impl Output for EnrollmentToken {
+    fn output(&self) -> Result<String> {
+        let mut output = String::new();
+        let expires_at = OffsetDateTime::from_unix_timestamp(self.expires_at().unix_time() as i64)
+            .map(|t| t.to_string())
+            .unwrap_or_else(|_| self.expires_at().unix_time().to_string());
+        writeln!(
+            output,
+            "Ticket {}",
+            self.one_time_code()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(output, "Expires {expires_at}")?;
+        write!(
+            output,
+            "Used {} of {} times",
+            self.usage_count(),
+            self.ttl_count()
+        )?;
+        if let Some(identifier) = self.required_identifier() {
+            write!(output, "\nFor {identifier}")?;
+        }
+        if let Some(ttl) = self.member_ttl_secs() {
+            write!(output, "\nMembers expire after {ttl} seconds")?;
+        }
+        Ok(output)
+    }
+}
+
 /// Get the project authority from the first address protocol.
 ///
 /// If the first protocol is a `/project`, look up the project's config.
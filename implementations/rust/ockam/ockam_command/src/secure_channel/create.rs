@@ -16,6 +16,7 @@ use tokio::{sync::Mutex, try_join};
 
 use crate::docs;
 use crate::identity::{get_identity_name, initialize_identity_if_default};
+use crate::secure_channel::KeyExchange;
 use crate::util::api::CloudOpts;
 use crate::util::{clean_nodes_multiaddr, RpcBuilder};
 use ockam::{identity::IdentityIdentifier, route, Context, TcpTransport};
@@ -56,6 +57,15 @@ pub struct CreateCommand {
     /// Name of a stored Credential to use within this Secure Channel
     #[arg(short, long)]
     pub credential: Option<String>,
+
+    /// Curve used by the key exchange, it must match the one of the listener
+    #[arg(long, value_enum, default_value_t = KeyExchange::Curve25519)]
+    pub key_exchange: KeyExchange,
+
+    /// Only reveal this selectively disclosable attribute of the presented credential.
+    /// This argument can be repeated. By default all the attributes are revealed
+    #[arg(long = "disclose", value_name = "ATTRIBUTE")]
+    pub disclosed_attributes: Option<Vec<String>>,
 }
 
 impl CreateCommand {
@@ -117,7 +127,12 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
             CredentialExchangeMode::Mutual,
             Some(identity),
             cmd.credential.clone(),
-        );
+        )
+        .with_key_exchange_curve(cmd.key_exchange.into());
+        let payload = match cmd.disclosed_attributes.clone() {
+            Some(names) => payload.with_disclosed_attributes(names),
+            None => payload,
+        };
         let request = Request::post("/node/secure_channel").body(payload);
 
         rpc.request(request).await?;
@@ -4,11 +4,12 @@ use miette::miette;
 use ockam::identity::IdentityIdentifier;
 use ockam::Context;
 use ockam_api::nodes::models::secure_channel::CreateSecureChannelListenerRequest;
-use ockam_api::nodes::NODEMANAGER_ADDR;
+use ockam_api::nodes::send_to_node_manager;
 use ockam_core::api::{Request, Status};
-use ockam_core::{Address, Route};
+use ockam_core::Address;
 
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::secure_channel::KeyExchange;
 use crate::util::{api, exitcode, extract_address_value, node_rpc, Rpc};
 use crate::{docs, CommandGlobalOpts, Result};
 
@@ -38,6 +39,10 @@ pub struct CreateCommand {
 
     #[arg(value_name = "IDENTITY", long)]
     identity: Option<String>,
+
+    /// Curve used by the key exchange, initiators must use the same curve
+    #[arg(long, value_enum, default_value_t = KeyExchange::Curve25519)]
+    key_exchange: KeyExchange,
 }
 
 impl CreateCommand {
@@ -64,7 +69,8 @@ async fn run_impl(
             cmd.authorized,
             cmd.vault,
             cmd.identity,
-        ),
+        )
+        .with_key_exchange_curve(cmd.key_exchange.into()),
     );
     rpc.request(req).await?;
     match rpc.is_ok() {
@@ -84,14 +90,12 @@ pub async fn create_listener(
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
     identity: Option<String>,
-    mut base_route: Route,
 ) -> Result<()> {
-    let resp: Vec<u8> = ctx
-        .send_and_receive(
-            base_route.modify().append(NODEMANAGER_ADDR),
-            api::create_secure_channel_listener(&addr, authorized_identifiers, identity)?,
-        )
-        .await?;
+    let resp: Vec<u8> = send_to_node_manager(
+        ctx,
+        api::create_secure_channel_listener(&addr, authorized_identifiers, identity)?,
+    )
+    .await?;
 
     let response = api::parse_create_secure_channel_listener_response(&resp)?;
 
@@ -11,7 +11,8 @@ pub use list::ListCommand;
 pub use show::ShowCommand;
 
 use crate::{docs, CommandGlobalOpts};
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+use ockam_api::nodes::models::secure_channel::KeyExchangeCurve;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
@@ -51,3 +52,20 @@ impl SecureChannelCommand {
         }
     }
 }
+
+/// Curve used by the key exchange of a secure channel.
+/// The initiator and the listener must use the same curve
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum KeyExchange {
+    Curve25519,
+    P256,
+}
+
+impl From<KeyExchange> for KeyExchangeCurve {
+    fn from(key_exchange: KeyExchange) -> Self {
+        match key_exchange {
+            KeyExchange::Curve25519 => KeyExchangeCurve::Curve25519,
+            KeyExchange::P256 => KeyExchangeCurve::P256,
+        }
+    }
+}
@@ -50,6 +50,10 @@ pub struct AuthenticatorConfig {
 
     #[serde(default)]
     pub(crate) disabled: bool,
+
+    /// Attributes which are selectively disclosable in the issued credentials
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) disclosable_attributes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
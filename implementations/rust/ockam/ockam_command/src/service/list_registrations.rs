@@ -0,0 +1,123 @@
+use std::fmt::Write;
+
+use clap::Args;
+use colorful::Colorful;
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::services::{RendezvousRegistration, RendezvousRegistrationList};
+use ockam_multiaddr::MultiAddr;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::terminal::OckamColor;
+use crate::util::output::Output;
+use crate::util::{api, extract_address_value, node_rpc, RpcBuilder};
+use crate::CommandGlobalOpts;
+
+/// List the punchers registered with a Rendezvous service
+#[derive(Clone, Debug, Args)]
+pub struct ListRegistrationsCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Address of the Rendezvous service, reached through a secure channel
+    /// created by the node, e.g. /ip4/10.0.0.1/udp/4000/secure/api/service/rendezvous
+    #[arg(long, value_name = "MULTIADDR")]
+    pub rendezvous: MultiAddr,
+}
+
+impl ListRegistrationsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListRegistrationsCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: ListRegistrationsCommand,
+) -> crate::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = extract_address_value(&node_name)?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let mut rpc = RpcBuilder::new(ctx, &opts, &node_name).tcp(&tcp)?.build();
+    let is_finished: Mutex<bool> = Mutex::new(false);
+
+    let send_req = async {
+        rpc.request(api::list_rendezvous_registrations(&cmd.rendezvous))
+            .await?;
+        let r = rpc.parse_response::<RendezvousRegistrationList>()?;
+
+        *is_finished.lock().await = true;
+        crate::Result::Ok(r)
+    };
+
+    let output_messages = vec![format!(
+        "Listing the registrations of {}...\n",
+        cmd.rendezvous
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    )];
+
+    let progress_output = opts
+        .terminal
+        .progress_output(&output_messages, &is_finished);
+
+    let (registrations, _) = try_join!(send_req, progress_output)?;
+
+    let plain = opts.terminal.build_list(
+        &registrations.list,
+        &format!("Registrations of {}", cmd.rendezvous),
+        &format!("No registrations found on {}", cmd.rendezvous),
+    )?;
+    let json = serde_json::to_string_pretty(&registrations.list)?;
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json)
+        .write_line()?;
+
+    Ok(())
+}
+
+impl Output for RendezvousRegistration {
+    fn output(&self) -> crate::Result<String> {
+        let mut output = String::new();
+
+        writeln!(
+            output,
+            "Puncher {}",
+            self.puncher_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(
+            output,
+            "Identity {}",
+            self.identifier
+                .as_deref()
+                .unwrap_or("anonymous")
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(
+            output,
+            "Route {}",
+            self.route
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        write!(output, "Expires in {}s", self.expires_in)?;
+
+        Ok(output)
+    }
+}
@@ -1,5 +1,6 @@
 pub(crate) mod config;
 pub(crate) mod list;
+pub(crate) mod list_registrations;
 pub(crate) mod start;
 pub(crate) mod util;
 
@@ -10,6 +11,7 @@ use crate::CommandGlobalOpts;
 use clap::{Args, Subcommand};
 
 use list::ListCommand;
+use list_registrations::ListRegistrationsCommand;
 
 #[derive(Clone, Debug, Args)]
 #[command(hide = docs::hide())]
@@ -24,6 +26,8 @@ pub enum ServiceSubcommand {
     Start(StartCommand),
     #[command(display_order = 901)]
     List(ListCommand),
+    #[command(display_order = 902)]
+    ListRegistrations(ListRegistrationsCommand),
 }
 
 impl ServiceCommand {
@@ -31,6 +35,7 @@ impl ServiceCommand {
         match self.subcommand {
             ServiceSubcommand::Start(c) => c.run(options),
             ServiceSubcommand::List(c) => c.run(options),
+            ServiceSubcommand::ListRegistrations(c) => c.run(options),
         }
     }
 }
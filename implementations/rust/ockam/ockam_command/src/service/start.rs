@@ -1,5 +1,6 @@
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
 use crate::terminal::OckamColor;
+use crate::util::parsers::duration_parser;
 use crate::util::{api, node_rpc, RpcBuilder};
 use crate::{fmt_ok, CommandGlobalOpts};
 use crate::{fmt_warn, Result};
@@ -8,10 +9,15 @@ use miette::miette;
 
 use colorful::Colorful;
 use minicbor::Encode;
+use ockam::identity::IdentityIdentifier;
 use ockam::{Context, TcpTransport};
 
+use ockam_api::nodes::models::services::StartStreamServiceRequest;
 use ockam_api::DefaultAddress;
 use ockam_core::api::{RequestBuilder, Status};
+use ockam_multiaddr::MultiAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Start a specified service
 #[derive(Clone, Debug, Args)]
@@ -40,6 +46,43 @@ pub enum StartSubCommand {
         #[arg(long, default_value_t = verifier_default_addr())]
         addr: String,
     },
+    /// Start a signer service, using the identity keys of the node vault to sign
+    /// data on behalf of the authorized identities. The service is only reachable
+    /// via the node secure channel listener
+    Signer {
+        #[arg(long, default_value_t = signer_default_addr())]
+        addr: String,
+
+        /// Identifier of an identity allowed to use the signer. Can be repeated
+        #[arg(long = "authorized", value_name = "IDENTIFIER", required = true)]
+        authorized: Vec<IdentityIdentifier>,
+    },
+    /// Start a direct-path service, letting the inlets connected to this node through a
+    /// relay move their secure channel to a hole-punched UDP path
+    DirectPath {
+        #[arg(long, default_value_t = direct_path_default_addr())]
+        addr: String,
+
+        /// Address of the node running the Rendezvous service, e.g. /ip4/10.0.0.1/udp/4000
+        #[arg(long, value_name = "MULTIADDR")]
+        rendezvous: MultiAddr,
+    },
+    /// Start a Rendezvous service, letting the punchers of other nodes find each
+    /// other's public UDP address. Punchers register through the node secure channel
+    /// listener. Queries and listing follow the `query` and `list` policies of the
+    /// service address, which by default allow every query and no listing
+    Rendezvous {
+        #[arg(long, default_value_t = rendezvous_default_addr())]
+        addr: String,
+
+        /// Local UDP address to listen on for the punchers
+        #[arg(long, value_name = "SOCKET_ADDRESS", default_value = "0.0.0.0:4000")]
+        udp_address: String,
+
+        /// Also accept anonymous registrations, sent over plain UDP
+        #[arg(long)]
+        anonymous_registrations: bool,
+    },
     Credentials {
         #[arg(long)]
         identity: String,
@@ -57,6 +100,31 @@ pub enum StartSubCommand {
         #[arg(long)]
         project: String,
     },
+    /// Start a durable stream service, storing the stream messages on disk
+    Stream {
+        #[arg(long, default_value_t = stream_default_addr())]
+        addr: String,
+
+        /// Address of the service storing the stream consumers indices
+        #[arg(long, default_value_t = stream_index_default_addr())]
+        index_addr: String,
+
+        /// Directory where the streams are stored. Defaults to the node directory
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Size in bytes after which a new segment file is started for a stream
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        max_segment_size: Option<u64>,
+
+        /// Maximum size in bytes of a stream. The oldest messages are removed when it is exceeded
+        #[arg(long)]
+        retention_size: Option<u64>,
+
+        /// Maximum age of the messages of a stream, for example 2h or 7d
+        #[arg(long, value_parser = duration_parser)]
+        retention: Option<Duration>,
+    },
 }
 
 fn hop_default_addr() -> String {
@@ -75,6 +143,18 @@ fn verifier_default_addr() -> String {
     DefaultAddress::VERIFIER.to_string()
 }
 
+fn signer_default_addr() -> String {
+    DefaultAddress::SIGNER.to_string()
+}
+
+fn direct_path_default_addr() -> String {
+    DefaultAddress::DIRECT_PATH_SERVICE.to_string()
+}
+
+fn rendezvous_default_addr() -> String {
+    DefaultAddress::RENDEZVOUS_SERVICE.to_string()
+}
+
 fn credentials_default_addr() -> String {
     DefaultAddress::CREDENTIALS_SERVICE.to_string()
 }
@@ -83,6 +163,14 @@ fn authenticator_default_addr() -> String {
     DefaultAddress::DIRECT_AUTHENTICATOR.to_string()
 }
 
+fn stream_default_addr() -> String {
+    DefaultAddress::STREAM_SERVICE.to_string()
+}
+
+fn stream_index_default_addr() -> String {
+    DefaultAddress::STREAM_INDEX_SERVICE.to_string()
+}
+
 impl StartCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
         initialize_node_if_default(&opts, &self.node_opts.at_node);
@@ -124,6 +212,26 @@ async fn run_impl(
             start_verifier_service(ctx, &opts, &node_name, &addr, Some(&tcp)).await?;
             addr
         }
+        StartSubCommand::Signer { addr, authorized } => {
+            let req = api::start_signer_service(&addr, authorized);
+            start_service_impl(ctx, &opts, &node_name, "Signer", req, Some(&tcp)).await?;
+            addr
+        }
+        StartSubCommand::DirectPath { addr, rendezvous } => {
+            let rendezvous = rendezvous.to_string();
+            let req = api::start_direct_path_service(&addr, &rendezvous);
+            start_service_impl(ctx, &opts, &node_name, "DirectPath", req, Some(&tcp)).await?;
+            addr
+        }
+        StartSubCommand::Rendezvous {
+            addr,
+            udp_address,
+            anonymous_registrations,
+        } => {
+            let req = api::start_rendezvous_service(&addr, &udp_address, anonymous_registrations);
+            start_service_impl(ctx, &opts, &node_name, "Rendezvous", req, Some(&tcp)).await?;
+            addr
+        }
         StartSubCommand::Credentials {
             identity,
             addr,
@@ -139,6 +247,25 @@ async fn run_impl(
                 .await?;
             addr
         }
+        StartSubCommand::Stream {
+            addr,
+            index_addr,
+            path,
+            max_segment_size,
+            retention_size,
+            retention,
+        } => {
+            let mut payload = StartStreamServiceRequest::new(&addr, &index_addr)
+                .with_max_segment_size(max_segment_size)
+                .with_retention_size(retention_size)
+                .with_retention_duration(retention);
+            if let Some(path) = &path {
+                payload = payload.with_path(path.to_string_lossy().to_string());
+            }
+            let req = api::start_stream_service(payload);
+            start_service_impl(ctx, &opts, &node_name, "Stream", req, Some(&tcp)).await?;
+            addr
+        }
     };
 
     opts.terminal.write_line(&fmt_ok!(
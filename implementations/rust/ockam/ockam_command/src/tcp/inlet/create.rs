@@ -2,7 +2,7 @@ use crate::node::{get_node_name, initialize_node_if_default};
 use crate::policy::{add_default_project_policy, has_policy};
 use crate::tcp::util::alias_parser;
 use crate::terminal::OckamColor;
-use crate::util::parsers::socket_addr_parser;
+use crate::util::parsers::{duration_parser, socket_addr_parser};
 use crate::util::{
     bind_to_port_check, exitcode, extract_address_value, find_available_port, node_rpc,
     process_nodes_multiaddr, RpcBuilder,
@@ -17,6 +17,7 @@ use ockam_abac::Resource;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
 use ockam_api::nodes::models::portal::CreateInlet;
 use ockam_api::nodes::models::portal::InletStatus;
+use ockam_api::nodes::models::session::RetryPolicy;
 use ockam_core::api::Request;
 use ockam_core::route;
 use ockam_multiaddr::proto::Project;
@@ -61,6 +62,44 @@ pub struct CreateCommand {
     /// Time to wait before retrying to connect to outlet (ms).
     #[arg(long, display_order = 900, id = "RETRY", default_value = "20000")]
     retry_wait_ms: u64,
+
+    /// Delay before the first attempt to replace the session to the outlet once it is down, e.g. 5s.
+    #[arg(long, display_order = 900, id = "SESSION_RETRY_DELAY", value_parser = duration_parser)]
+    session_retry_delay: Option<Duration>,
+
+    /// Maximum delay between two attempts to replace the session to the outlet, e.g. 5m.
+    #[arg(long, display_order = 900, id = "SESSION_MAX_RETRY_DELAY", value_parser = duration_parser)]
+    session_max_retry_delay: Option<Duration>,
+
+    /// Factor applied to the delay after each failed attempt to replace the session to the outlet.
+    #[arg(long, display_order = 900, id = "SESSION_RETRY_MULTIPLIER", value_parser = clap::value_parser!(u32).range(1..))]
+    session_retry_multiplier: Option<u32>,
+
+    /// Fraction of the delay, between 0 and 1, randomly removed before replacing the session to the outlet.
+    #[arg(long, display_order = 900, id = "SESSION_RETRY_JITTER", value_parser = jitter_parser)]
+    session_retry_jitter: Option<f64>,
+
+    /// Number of unanswered pings after which the session to the outlet is replaced.
+    #[arg(long, display_order = 900, id = "SESSION_MAX_FAILURES", value_parser = clap::value_parser!(u32).range(1..))]
+    session_max_failures: Option<u32>,
+
+    /// Once connected through a relay, try to move the secure channel to the outlet to a
+    /// hole-punched UDP path. Both nodes must run a direct-path service with the same
+    /// rendezvous node, and belong to a trust context.
+    #[arg(long, display_order = 900)]
+    direct_path: bool,
+
+    /// Speak SOCKS5 to the clients of the inlet, and let them request their destination
+    /// to a dynamic outlet.
+    #[arg(long, display_order = 900)]
+    socks5: bool,
+}
+
+fn jitter_parser(arg: &str) -> std::result::Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(jitter) if (0.0..=1.0).contains(&jitter) => Ok(jitter),
+        _ => Err("the jitter must be a number between 0 and 1".to_string()),
+    }
 }
 
 fn default_from_addr() -> SocketAddr {
@@ -78,6 +117,35 @@ impl CreateCommand {
         initialize_node_if_default(&opts, &self.at);
         node_rpc(rpc, (opts, self));
     }
+
+    /// Policy used to replace the session to the outlet, if any of its options is set
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        if self.session_retry_delay.is_none()
+            && self.session_max_retry_delay.is_none()
+            && self.session_retry_multiplier.is_none()
+            && self.session_retry_jitter.is_none()
+            && self.session_max_failures.is_none()
+        {
+            return None;
+        }
+        let mut policy = RetryPolicy::default();
+        if let Some(d) = self.session_retry_delay {
+            policy = policy.with_initial_delay(d)
+        }
+        if let Some(d) = self.session_max_retry_delay {
+            policy = policy.with_max_delay(d)
+        }
+        if let Some(m) = self.session_retry_multiplier {
+            policy = policy.with_multiplier(m)
+        }
+        if let Some(j) = self.session_retry_jitter {
+            policy = policy.with_jitter(j)
+        }
+        if let Some(f) = self.session_max_failures {
+            policy = policy.with_max_failures(f)
+        }
+        Some(policy)
+    }
 }
 
 async fn rpc(ctx: Context, (opts, mut cmd): (CommandGlobalOpts, CreateCommand)) -> Result<()> {
@@ -148,6 +216,15 @@ async fn rpc(ctx: Context, (opts, mut cmd): (CommandGlobalOpts, CreateCommand))
                     payload.set_alias(a)
                 }
                 payload.set_wait_ms(cmd.connection_wait_ms);
+                if let Some(policy) = cmd.retry_policy() {
+                    payload.set_retry_policy(policy)
+                }
+                if cmd.direct_path {
+                    payload.set_direct_path()
+                }
+                if cmd.socks5 {
+                    payload.set_socks5()
+                }
 
                 Request::post("/node/inlet").body(payload)
             };
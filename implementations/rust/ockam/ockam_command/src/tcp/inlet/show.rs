@@ -1,5 +1,6 @@
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
 use crate::tcp::util::alias_parser;
+use crate::util::output::format_unix_time;
 use crate::util::{extract_address_value, node_rpc, Rpc};
 use crate::Result;
 use crate::{docs, CommandGlobalOpts};
@@ -51,6 +52,33 @@ pub async fn run_impl(
             println!("  To Outlet Address: {ma}");
         }
     }
+    if let Some(session) = inlet_to_show.session {
+        println!("Session:");
+        println!("  Status: {}", session.status);
+        println!("  Failed Attempts: {}", session.failed_attempts);
+        if let Some(e) = session.last_error {
+            println!("  Last Error: {e}");
+        }
+        if let Some(t) = session.next_retry {
+            println!("  Next Retry: {}", format_unix_time(t));
+        }
+        if !session.events.is_empty() {
+            println!("  Events:");
+            for e in session.events {
+                match e.error {
+                    Some(err) => {
+                        println!(
+                            "    {} {} -> {} ({err})",
+                            format_unix_time(e.at),
+                            e.from,
+                            e.to
+                        )
+                    }
+                    None => println!("    {} {} -> {}", format_unix_time(e.at), e.from, e.to),
+                }
+            }
+        }
+    }
     Ok(())
 }
 
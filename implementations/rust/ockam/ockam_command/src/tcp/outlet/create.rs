@@ -3,7 +3,7 @@ use crate::policy::{add_default_project_policy, has_policy};
 use crate::tcp::util::alias_parser;
 use crate::terminal::OckamColor;
 
-use crate::util::parsers::socket_addr_parser;
+use crate::util::parsers::{port_range_parser, socket_addr_parser};
 use crate::util::{extract_address_value, node_rpc, Rpc};
 use crate::{display_parse_logs, fmt_log};
 use crate::{docs, fmt_ok, CommandGlobalOpts};
@@ -13,7 +13,7 @@ use colorful::Colorful;
 use ockam::Context;
 use ockam_abac::Resource;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
-use ockam_api::nodes::models::portal::{CreateOutlet, OutletStatus};
+use ockam_api::nodes::models::portal::{CreateOutlet, OutletAllowlist, OutletStatus};
 use ockam_core::api::{Request, RequestBuilder};
 use std::net::SocketAddr;
 use tokio::sync::Mutex;
@@ -34,12 +34,29 @@ pub struct CreateCommand {
     from: String,
 
     /// TCP address to send raw tcp traffic.
-    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
-    to: SocketAddr,
+    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser,
+        required_unless_present_any = ["ALLOW_NETWORK", "ALLOW_HOST"],
+        conflicts_with_all = ["ALLOW_NETWORK", "ALLOW_HOST", "ALLOW_PORT"])]
+    to: Option<SocketAddr>,
 
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Create a dynamic outlet, connecting to the destinations requested by SOCKS5 inlets
+    /// which are in this IP address or range, e.g. 10.0.0.0/8. Can be repeated.
+    #[arg(long, display_order = 903, id = "ALLOW_NETWORK")]
+    allow_network: Vec<String>,
+
+    /// Create a dynamic outlet, connecting to the destinations requested by SOCKS5 inlets
+    /// with this host name, or its subdomains when it starts with a '.'. Can be repeated.
+    #[arg(long, display_order = 903, id = "ALLOW_HOST")]
+    allow_host: Vec<String>,
+
+    /// Restrict the destinations of a dynamic outlet to this port or port range,
+    /// e.g. 5432 or 5432-5439. Can be repeated.
+    #[arg(long, display_order = 903, id = "ALLOW_PORT", value_parser = port_range_parser)]
+    allow_port: Vec<(u16, u16)>,
 }
 
 impl CreateCommand {
@@ -53,15 +70,23 @@ fn default_from_addr() -> String {
     "/service/outlet".to_string()
 }
 
+impl CreateCommand {
+    /// Destination of the outlet, as displayed to the user
+    fn destination(&self) -> String {
+        match self.to {
+            Some(to) => to.to_string(),
+            None => "the destinations requested by SOCKS5 inlets".to_string(),
+        }
+    }
+}
+
 pub async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> crate::Result<()> {
     opts.terminal.write_line(&fmt_log!(
         "Creating TCP Outlet to {}...\n",
-        &cmd.to
-            .to_string()
-            .color(OckamColor::PrimaryResource.color())
+        &cmd.destination().color(OckamColor::PrimaryResource.color())
     ))?;
     display_parse_logs(&opts);
 
@@ -88,7 +113,7 @@ pub async fn run_impl(
     let send_req = async {
         let new_cmd = CreateCommand {
             from: extract_address_value(&cmd.from)?,
-            ..cmd
+            ..cmd.clone()
         };
 
         rpc.request(make_api_request(new_cmd)?).await?;
@@ -126,9 +151,7 @@ pub async fn run_impl(
             &node.to_string().color(OckamColor::PrimaryResource.color()),
             format!("/service/{}", extract_address_value(&cmd.from)?)
                 .color(OckamColor::PrimaryResource.color()),
-            &cmd.to
-                .to_string()
-                .color(OckamColor::PrimaryResource.color())
+            &cmd.destination().color(OckamColor::PrimaryResource.color())
         ))
         .machine(machine)
         .json(json)
@@ -139,10 +162,14 @@ pub async fn run_impl(
 
 /// Construct a request to create a tcp outlet
 fn make_api_request<'a>(cmd: CreateCommand) -> crate::Result<RequestBuilder<'a, CreateOutlet<'a>>> {
-    let tcp_addr = cmd.to.to_string();
     let worker_addr = cmd.from;
     let alias = cmd.alias.map(|a| a.into());
-    let payload = CreateOutlet::new(tcp_addr, worker_addr, alias, true);
+    let payload = match cmd.to {
+        Some(to) => CreateOutlet::new(to.to_string(), worker_addr, alias, true),
+        None => CreateOutlet::new("dynamic", worker_addr, alias, true).with_allowlist(
+            OutletAllowlist::new(cmd.allow_network, cmd.allow_host, cmd.allow_port),
+        ),
+    };
     let request = Request::post("/node/outlet").body(payload);
     Ok(request)
 }
@@ -218,6 +218,20 @@ impl<W: TerminalWriter> Terminal<W> {
         ))
     }
 
+    /// Prompt the user for a password, which is entered twice when `confirm` is true.
+    /// Return None if the user can't be asked for input.
+    pub fn password(&self, msg: &str, confirm: bool) -> Result<Option<String>> {
+        if !self.can_ask_for_user_input() {
+            return Ok(None);
+        }
+        let mut prompt = dialoguer::Password::new();
+        prompt.with_prompt(msg);
+        if confirm {
+            prompt.with_confirmation("Confirm password", "The passwords don't match");
+        }
+        Ok(Some(prompt.interact()?))
+    }
+
     fn can_ask_for_user_input(&self) -> bool {
         !self.no_input && self.stderr.is_tty()
     }
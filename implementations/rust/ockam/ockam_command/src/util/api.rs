@@ -17,9 +17,10 @@ use ockam_api::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
 use ockam_api::config::cli::TrustContextConfig;
 use ockam_api::nodes::models::flow_controls::AddConsumer;
 use ockam_api::nodes::models::services::{
-    StartAuthenticatedServiceRequest, StartAuthenticatorRequest, StartCredentialsService,
-    StartHopServiceRequest, StartIdentityServiceRequest, StartOktaIdentityProviderRequest,
-    StartVerifierService,
+    ListRendezvousRegistrations, StartAuthenticatedServiceRequest, StartAuthenticatorRequest,
+    StartCredentialsService, StartDirectPathService, StartHopServiceRequest,
+    StartIdentityServiceRequest, StartOktaIdentityProviderRequest, StartRendezvousService,
+    StartSignerService, StartStreamServiceRequest, StartVerifierService,
 };
 use ockam_api::nodes::*;
 use ockam_api::DefaultAddress;
@@ -61,6 +62,14 @@ pub(crate) fn list_services() -> RequestBuilder<'static, ()> {
     Request::get("/node/services")
 }
 
+/// Construct a request to list the punchers registered with a Rendezvous service
+pub(crate) fn list_rendezvous_registrations(
+    rendezvous: &MultiAddr,
+) -> RequestBuilder<'static, ListRendezvousRegistrations<'static>> {
+    let payload = ListRendezvousRegistrations::new(rendezvous.to_string());
+    Request::get("/node/rendezvous/registrations").body(payload)
+}
+
 /// Construct a request to print a list of inlets for the given node
 pub(crate) fn list_inlets() -> RequestBuilder<'static, ()> {
     Request::get("/node/inlet")
@@ -163,6 +172,34 @@ pub(crate) fn start_verifier_service(addr: &str) -> RequestBuilder<'static, Star
     Request::post(node_service(DefaultAddress::VERIFIER)).body(payload)
 }
 
+/// Construct a request to start a Signer Service
+pub(crate) fn start_signer_service(
+    addr: &str,
+    authorized: Vec<IdentityIdentifier>,
+) -> RequestBuilder<'static, StartSignerService<'_>> {
+    let payload = StartSignerService::new(addr, authorized);
+    Request::post(node_service(DefaultAddress::SIGNER)).body(payload)
+}
+
+/// Construct a request to start a Direct Path Service
+pub(crate) fn start_direct_path_service<'a>(
+    addr: &'a str,
+    rendezvous: &'a str,
+) -> RequestBuilder<'static, StartDirectPathService<'a>> {
+    let payload = StartDirectPathService::new(addr, rendezvous);
+    Request::post(node_service(DefaultAddress::DIRECT_PATH_SERVICE)).body(payload)
+}
+
+/// Construct a request to start a Rendezvous Service
+pub(crate) fn start_rendezvous_service<'a>(
+    addr: &'a str,
+    udp_address: &'a str,
+    anonymous_registrations: bool,
+) -> RequestBuilder<'static, StartRendezvousService<'a>> {
+    let payload = StartRendezvousService::new(addr, udp_address, anonymous_registrations);
+    Request::post(node_service(DefaultAddress::RENDEZVOUS_SERVICE)).body(payload)
+}
+
 /// Construct a request to start a Credential Service
 pub(crate) fn start_credentials_service<'a>(
     public_identity: &'a str,
@@ -182,6 +219,13 @@ pub(crate) fn start_authenticator_service<'a>(
     Request::post(node_service(DefaultAddress::DIRECT_AUTHENTICATOR)).body(payload)
 }
 
+/// Construct a request to start a Stream Service and its Stream Index Service
+pub(crate) fn start_stream_service(
+    payload: StartStreamServiceRequest,
+) -> RequestBuilder<'static, StartStreamServiceRequest> {
+    Request::post(node_service(DefaultAddress::STREAM_SERVICE)).body(payload)
+}
+
 pub(crate) fn add_consumer(
     id: FlowControlId,
     address: MultiAddr,
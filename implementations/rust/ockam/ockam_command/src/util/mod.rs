@@ -9,22 +9,27 @@ use std::{
 use anyhow::Context as _;
 use miette::{miette, ErrReport as Report};
 use minicbor::{data::Type, Decode, Decoder, Encode};
-use tracing::{debug, error, trace};
+use tracing::{debug, error, trace, warn};
 
+use ockam::identity::{SecureChannelOptions, SecureChannels, TrustIdentifierPolicy};
 use ockam::{
-    Address, Context, MessageSendReceiveOptions, NodeBuilder, Route, TcpConnectionOptions,
-    TcpTransport,
+    Address, Context, MessageReceiveOptions, MessageSendReceiveOptions, NodeBuilder, Route,
+    TcpConnectionOptions, TcpProxy, TcpProxySettings, TcpTransport,
 };
-use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
+use ockam_api::cli_state::{CliState, NodeState, StateDirTrait, StateItemTrait};
 use ockam_api::config::lookup::{InternetAddress, LookupMeta};
-use ockam_api::nodes::NODEMANAGER_ADDR;
+use ockam_api::nodes::{
+    send_to_node_manager_with_options, NODEMANAGER_ADDR, NODEMANAGER_LISTENER_ADDR,
+};
 use ockam_core::api::{RequestBuilder, Response, Status};
-use ockam_core::DenyAll;
+use ockam_core::{route, DenyAll};
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Project, Service, Space, Tcp};
 use ockam_multiaddr::{
     proto::{self, Node},
     MultiAddr, Protocol,
 };
+use ockam_transport_uds::{UdsTransport, UDS};
+use tokio::sync::OnceCell;
 
 use crate::util::output::Output;
 use crate::{node::util::start_embedded_node, EncodeFormat};
@@ -39,6 +44,12 @@ pub(crate) mod output;
 
 pub const DEFAULT_CONTROLLER_ADDRESS: &str = "/dnsaddr/orchestrator.ockam.io/tcp/6252/service/api";
 
+/// Maximum duration of the handshake of the secure channel to a background node
+const SECURE_CHANNEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport used to reach the Unix domain sockets of the background nodes
+static UDS_TRANSPORT: OnceCell<UdsTransport> = OnceCell::const_new();
+
 #[derive(Clone)]
 pub enum RpcMode<'a> {
     Embedded,
@@ -140,15 +151,7 @@ impl<'a> Rpc<'a> {
     where
         T: Encode<()>,
     {
-        let route = self.route_impl(self.ctx).await?;
-        self.buf = self
-            .ctx
-            .send_and_receive(route.clone(), req.to_vec()?)
-            .await
-            .map_err(|_err| {
-                // Overwrite error to swallow inner cause and hide it from end-user
-                miette!("The request timed out, please make sure the command's arguments are correct or try again")
-            })?;
+        self.buf = self.send_and_receive(req.to_vec()?, None).await?;
         Ok(())
     }
 
@@ -160,49 +163,71 @@ impl<'a> Rpc<'a> {
     where
         T: Encode<()>,
     {
-        let route = self.route_impl(self.ctx).await?;
-        let options = MessageSendReceiveOptions::new().with_timeout(timeout);
-        self.buf = self
-            .ctx
-            .send_and_receive_extended::<Vec<u8>>(route.clone(), req.to_vec()?, options)
-            .await
-            .map_err(|_err| {
-                // Overwrite error to swallow inner cause and hide it from end-user
-                miette!("The request timed out, please make sure the command's arguments are correct or try again")
-            })?.body();
+        self.buf = self.send_and_receive(req.to_vec()?, Some(timeout)).await?;
         Ok(())
     }
 
-    async fn route_impl(&self, ctx: &Context) -> Result<Route> {
+    async fn send_and_receive(&self, req: Vec<u8>, timeout: Option<Duration>) -> Result<Vec<u8>> {
+        let route = self
+            .route_impl(self.ctx, timeout.unwrap_or(SECURE_CHANNEL_TIMEOUT))
+            .await?;
+        let response = if route == route![NODEMANAGER_ADDR] {
+            // The node manager of an embedded node only accepts the requests marked as local
+            let mut options = MessageReceiveOptions::new();
+            if let Some(timeout) = timeout {
+                options = options.with_timeout(timeout);
+            }
+            send_to_node_manager_with_options(self.ctx, req, options).await
+        } else {
+            let mut options = MessageSendReceiveOptions::new();
+            if let Some(timeout) = timeout {
+                options = options.with_timeout(timeout);
+            }
+            self.ctx
+                .send_and_receive_extended::<Vec<u8>>(route, req, options)
+                .await
+                .map(|r| r.body())
+        };
+        // Overwrite error to swallow inner cause and hide it from end-user
+        Ok(response.map_err(|_err| self.timeout_error())?)
+    }
+
+    async fn route_impl(&self, ctx: &Context, timeout: Duration) -> Result<Route> {
         let mut to = self.to.clone();
         let route = match self.mode {
             RpcMode::Embedded => to,
             RpcMode::Background { ref tcp } => {
                 let node_state = self.opts.state.nodes.get(&self.node_name)?;
-                let port = node_state
-                    .config()
-                    .setup()
-                    .default_tcp_listener()?
-                    .addr
-                    .port();
-                let addr_str = format!("localhost:{port}");
-                let addr = match tcp {
+                let next = match self.api_socket(ctx, &node_state).await {
+                    Some(socket) => socket,
                     None => {
-                        let tcp = TcpTransport::create(ctx).await?;
-                        tcp.connect(addr_str, TcpConnectionOptions::new())
-                            .await?
-                            .sender_address()
-                            .clone()
-                    }
-                    Some(tcp) => {
-                        // Create a new connection anyway
-                        tcp.connect(addr_str, TcpConnectionOptions::new())
-                            .await?
-                            .sender_address()
-                            .clone()
+                        let port = node_state
+                            .config()
+                            .setup()
+                            .default_tcp_listener()?
+                            .addr
+                            .port();
+                        let addr_str = format!("localhost:{port}");
+                        let addr = match tcp {
+                            None => {
+                                let tcp = TcpTransport::create(ctx).await?;
+                                tcp.connect(addr_str, TcpConnectionOptions::new())
+                                    .await?
+                                    .sender_address()
+                                    .clone()
+                            }
+                            Some(tcp) => {
+                                // Create a new connection anyway
+                                tcp.connect(addr_str, TcpConnectionOptions::new())
+                                    .await?
+                                    .sender_address()
+                                    .clone()
+                            }
+                        };
+                        self.secure_channel(ctx, &node_state, addr, timeout).await?
                     }
                 };
-                to.modify().prepend(addr);
+                to.modify().prepend(next);
                 to
             }
         };
@@ -210,6 +235,96 @@ impl<'a> Rpc<'a> {
         Ok(route)
     }
 
+    fn timeout_error(&self) -> Report {
+        match self.mode {
+            RpcMode::Embedded => miette!("The request timed out, please make sure the command's arguments are correct or try again"),
+            // A node drops the secure channels created by identities which are not its admins
+            RpcMode::Background { .. } => miette!("The request timed out, please make sure the command's arguments are correct, that the default identity is an admin of the node {}, or try again", self.node_name),
+        }
+    }
+
+    /// Return the address of the Unix domain socket of a running background node.
+    ///
+    /// Only the owner of the node can use that socket, so the requests sent
+    /// through it don't need a secure channel.
+    async fn api_socket(&self, ctx: &Context, node_state: &NodeState) -> Option<Address> {
+        let path = node_state.api_socket();
+        if !node_state.is_running() || !path.exists() {
+            return None;
+        }
+        // A single UDS transport can be created by the command
+        if let Err(e) = UDS_TRANSPORT
+            .get_or_try_init(|| UdsTransport::create(ctx))
+            .await
+        {
+            debug!(%e, "Can't create a UDS transport");
+            return None;
+        }
+        Some(Address::new(UDS, path.to_str()?))
+    }
+
+    /// Create a secure channel to the node manager of a background node.
+    ///
+    /// The channel is authenticated with the default identity when it is one of the admins
+    /// of the node. Otherwise, for example for a node created before its admins were
+    /// recorded, or with another identity, the channel is authenticated with the identity
+    /// of the node itself, which the node always accepts.
+    async fn secure_channel(
+        &self,
+        ctx: &Context,
+        node_state: &NodeState,
+        tcp_address: Address,
+        timeout: Duration,
+    ) -> Result<Address> {
+        let node_config = node_state.config();
+        let node_identifier = node_config.identifier().await?;
+        let default_identifier = self
+            .opts
+            .state
+            .identities
+            .default()
+            .ok()
+            .map(|i| i.identifier());
+        let (identifier, secure_channels) = match default_identifier {
+            Some(identifier)
+                if identifier == node_identifier
+                    || node_config.setup().admins().contains(&identifier) =>
+            {
+                (identifier, self.opts.state.default_secure_channels().await?)
+            }
+            _ => {
+                let secure_channels = SecureChannels::builder()
+                    .with_identities_vault(node_config.vault().await?)
+                    .with_identities_repository(
+                        self.opts.state.identities.identities_repository().await?,
+                    )
+                    .build();
+                (node_identifier.clone(), secure_channels)
+            }
+        };
+        let options = SecureChannelOptions::new()
+            .with_trust_policy(TrustIdentifierPolicy::new(node_identifier))
+            .with_timeout(timeout);
+        let channel = secure_channels
+            .create_secure_channel(
+                ctx,
+                &identifier,
+                route![tcp_address, NODEMANAGER_LISTENER_ADDR],
+                options,
+            )
+            .await
+            .map_err(|e| {
+                miette!(
+                    "Could not authenticate to the node {} with the identity {}: {}. \
+                    Make sure that this identity is an admin of the node",
+                    self.node_name,
+                    identifier,
+                    e
+                )
+            })?;
+        Ok(channel.encryptor_address().clone())
+    }
+
     /// Parse the response body and return it.
     pub fn parse_response<T>(&'a self) -> Result<T>
     where
@@ -607,6 +722,32 @@ pub fn random_name() -> String {
     hex::encode(rand::random::<[u8; 4]>())
 }
 
+/// Return the proxy settings of the outgoing TCP connections of a node, set with the
+/// `HTTPS_PROXY` and `NO_PROXY` environment variables, or their lowercase variants
+pub fn proxy_settings_from_env() -> Result<Option<TcpProxySettings>> {
+    proxy_settings(|name| std::env::var(name).ok())
+}
+
+fn proxy_settings(var: impl Fn(&str) -> Option<String>) -> Result<Option<TcpProxySettings>> {
+    let var = |name: &str| {
+        var(name)
+            .or_else(|| var(&name.to_lowercase()))
+            .filter(|v| !v.trim().is_empty())
+    };
+    let proxy = match var("HTTPS_PROXY") {
+        Some(url) if url.trim().to_lowercase().starts_with("https://") => {
+            warn!("Ignoring HTTPS_PROXY, TLS connections to a proxy are not supported");
+            return Ok(None);
+        }
+        Some(url) => TcpProxy::from_url(&url)?,
+        None => return Ok(None),
+    };
+    let no_proxy = var("NO_PROXY").unwrap_or_default();
+    Ok(Some(
+        TcpProxySettings::new(proxy).with_no_proxy(no_proxy.split(',')),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -642,6 +783,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_proxy_settings() {
+        let env = |vars: Vec<(&'static str, &'static str)>| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert!(proxy_settings(env(vec![])).unwrap().is_none());
+        assert!(proxy_settings(env(vec![("HTTPS_PROXY", "ftp://proxy")])).is_err());
+        assert!(
+            proxy_settings(env(vec![("HTTPS_PROXY", "https://proxy.corp:3128")]))
+                .unwrap()
+                .is_none()
+        );
+
+        let settings = proxy_settings(env(vec![
+            ("https_proxy", "http://proxy.corp:3128"),
+            ("NO_PROXY", "internal.corp, 10.0.0.0/8"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            settings.proxy_for("orchestrator.ockam.io:6252"),
+            Some(&TcpProxy::http("proxy.corp:3128"))
+        );
+        assert_eq!(settings.proxy_for("db.internal.corp:5432"), None);
+        assert_eq!(settings.proxy_for("10.1.1.1:4000"), None);
+    }
+
     #[ockam_macros::test(crate = "ockam")]
     async fn test_process_multi_addr(ctx: &mut Context) -> ockam::Result<()> {
         let cli_state = CliState::test()?;
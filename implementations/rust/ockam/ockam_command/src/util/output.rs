@@ -9,6 +9,7 @@ use ockam::identity::credential::Credential;
 use ockam_api::cloud::project::Project;
 
 use ockam_api::nodes::models::portal::{InletStatus, OutletStatus};
+use ockam_api::nodes::models::session::SessionStatus;
 
 use crate::project::ProjectInfo;
 use crate::terminal::OckamColor;
@@ -21,6 +22,7 @@ use ockam_api::nodes::models::secure_channel::{
 };
 use ockam_api::route_to_multiaddr;
 use ockam_core::{route, Route};
+use time::OffsetDateTime;
 
 /// Trait to control how a given type will be printed as a CLI output.
 ///
@@ -345,18 +347,71 @@ From {} to {}"#,
     }
 }
 
+impl Output for SessionStatus {
+    fn output(&self) -> Result<String> {
+        let mut output = String::new();
+        writeln!(output, "Session {}", self.key)?;
+        writeln!(output, "  Ping Route: {}", self.ping_route)?;
+        writeln!(output, "  Status: {}", self.status)?;
+        writeln!(output, "  Failed Attempts: {}", self.failed_attempts)?;
+        if let Some(e) = &self.last_error {
+            writeln!(output, "  Last Error: {e}")?;
+        }
+        if let Some(t) = self.next_retry {
+            writeln!(output, "  Next Retry: {}", format_unix_time(t))?;
+        }
+        if !self.events.is_empty() {
+            writeln!(output, "  Events:")?;
+            for e in &self.events {
+                write!(
+                    output,
+                    "    {} {} -> {}",
+                    format_unix_time(e.at),
+                    e.from,
+                    e.to
+                )?;
+                match &e.error {
+                    Some(err) => writeln!(output, " ({err})")?,
+                    None => writeln!(output)?,
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let mut output = format!(
+            "Session {} to {}\nStatus: {}",
+            self.key
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.ping_route
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.status
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+        );
+        if let Some(t) = self.next_retry {
+            write!(output, ", next retry at {}", format_unix_time(t))?;
+        }
+        Ok(output)
+    }
+}
+
+/// Format a time given in seconds since the UNIX epoch
+pub(crate) fn format_unix_time(secs: u64) -> String {
+    match OffsetDateTime::from_unix_timestamp(secs as i64) {
+        Ok(t) => t.to_string(),
+        Err(_) => secs.to_string(),
+    }
+}
+
 impl Output for VaultState {
     fn output(&self) -> Result<String> {
         let mut output = String::new();
         writeln!(output, "Name: {}", self.name())?;
-        writeln!(
-            output,
-            "Type: {}",
-            match self.config().is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(output, "Type: {}", self.config().kind())?;
         Ok(output)
     }
 
@@ -370,12 +425,10 @@ impl Output for VaultState {
         write!(
             output,
             "Type {}",
-            match self.config().is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color())
+            self.config()
+                .kind()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
         )?;
         Ok(output)
     }
@@ -1,6 +1,7 @@
 use crate::Result;
 use miette::miette;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
 
 /// Helper fn for parsing ip and port from user input
 /// It can parse a string containing either an `ip:port` pair or just a `port`
@@ -30,11 +31,47 @@ pub(crate) fn socket_addr_parser(input: &str) -> Result<SocketAddr> {
     }
 }
 
+/// Helper fn for parsing a duration from user input
+/// It can parse a number followed by an optional unit: `s` (default), `m`, `h` or `d`,
+/// for example `90`, `30m` or `2d`.
+pub(crate) fn duration_parser(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let (value, multiplier) = match input.char_indices().last() {
+        Some((i, 's')) => (&input[..i], 1),
+        Some((i, 'm')) => (&input[..i], 60),
+        Some((i, 'h')) => (&input[..i], 60 * 60),
+        Some((i, 'd')) => (&input[..i], 24 * 60 * 60),
+        _ => (input, 1),
+    };
+    let value: u64 = value
+        .parse()
+        .map_err(|_| miette!("Invalid duration {}", input))?;
+    Ok(Duration::from_secs(value.saturating_mul(multiplier)))
+}
+
+/// Helper fn for parsing a port, like `5432`, or an inclusive range of ports, like `5432-5439`
+pub(crate) fn port_range_parser(input: &str) -> Result<(u16, u16)> {
+    let parse = |port: &str| {
+        port.trim()
+            .parse::<u16>()
+            .map_err(|_| miette!("Invalid port number {}", port))
+    };
+    let (start, end) = match input.split_once('-') {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => (parse(input)?, parse(input)?),
+    };
+    if start > end {
+        return Err(miette!("Invalid port range {}", input).into());
+    }
+    Ok((start, end))
+}
+
 #[cfg(test)]
 mod tests {
     use ockam_core::compat::net::{IpAddr, Ipv4Addr, SocketAddr};
 
-    use crate::util::parsers::socket_addr_parser;
+    use crate::util::parsers::{duration_parser, port_range_parser, socket_addr_parser};
+    use std::time::Duration;
 
     #[test]
     fn test_parse_bootstrap_server() {
@@ -72,4 +109,25 @@ mod tests {
         let invalid_input = "192,166,0.1:9999";
         assert!(socket_addr_parser(invalid_input).is_err());
     }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(duration_parser("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(duration_parser("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(duration_parser("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(duration_parser("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(duration_parser("3d").unwrap(), Duration::from_secs(259200));
+        assert!(duration_parser("").is_err());
+        assert!(duration_parser("3w").is_err());
+        assert!(duration_parser("m").is_err());
+    }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(port_range_parser("5432").unwrap(), (5432, 5432));
+        assert_eq!(port_range_parser("5432-5439").unwrap(), (5432, 5439));
+        assert!(port_range_parser("5439-5432").is_err());
+        assert!(port_range_parser("5432-").is_err());
+        assert!(port_range_parser("70000").is_err());
+    }
 }
@@ -19,7 +19,7 @@ pub struct AttachKeyCommand {
     /// Name of the vault to attach the key to
     vault: String,
 
-    /// AWS KMS key to attach
+    /// AWS KMS or PKCS#11 key to attach
     #[arg(short, long)]
     key_id: String,
 }
@@ -39,8 +39,8 @@ async fn rpc(
 
 async fn run_impl(opts: CommandGlobalOpts, cmd: AttachKeyCommand) -> crate::Result<()> {
     let v_state = opts.state.vaults.get(&cmd.vault)?;
-    if !v_state.config().is_aws() {
-        return Err(miette!("Vault {} is not an AWS KMS vault", cmd.vault).into());
+    if !v_state.config().is_aws() && !v_state.config().is_pkcs11() {
+        return Err(miette!("Vault {} is not an AWS KMS or a PKCS#11 vault", cmd.vault).into());
     }
     let vault = v_state.get().await?;
     let idt = {
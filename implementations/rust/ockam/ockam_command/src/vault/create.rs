@@ -1,6 +1,7 @@
 use clap::Args;
 use colorful::Colorful;
 use rand::prelude::random;
+use std::path::PathBuf;
 
 use ockam::Context;
 use ockam_api::cli_state;
@@ -26,8 +27,20 @@ pub struct CreateCommand {
     #[arg(short, long)]
     path: Option<String>,
 
-    #[arg(long, default_value = "false")]
+    #[arg(long, default_value = "false", conflicts_with = "pkcs11_module")]
     aws_kms: bool,
+
+    /// Path of a PKCS#11 library, to create the keys of the vault on a hardware security module
+    #[arg(long, value_name = "PATH")]
+    pkcs11_module: Option<PathBuf>,
+
+    /// Slot of the PKCS#11 token, by default the first slot with an initialized token is used
+    #[arg(long, value_name = "SLOT", requires = "pkcs11_module")]
+    pkcs11_slot: Option<u64>,
+
+    /// Label of the PKCS#11 token
+    #[arg(long, value_name = "LABEL", requires = "pkcs11_module")]
+    pkcs11_token: Option<String>,
 }
 
 impl CreateCommand {
@@ -48,8 +61,22 @@ async fn run_impl(
     opts: CommandGlobalOpts,
     cmd: CreateCommand,
 ) -> crate::Result<()> {
-    let CreateCommand { name, aws_kms, .. } = cmd;
-    let config = cli_state::VaultConfig::new(aws_kms)?;
+    let CreateCommand {
+        name,
+        aws_kms,
+        pkcs11_module,
+        pkcs11_slot,
+        pkcs11_token,
+        ..
+    } = cmd;
+    let config = match pkcs11_module {
+        Some(module) => cli_state::VaultConfig::new_pkcs11(cli_state::Pkcs11VaultConfig {
+            module,
+            slot: pkcs11_slot,
+            token_label: pkcs11_token,
+        })?,
+        None => cli_state::VaultConfig::new(aws_kms)?,
+    };
     if !opts.state.vaults.is_empty()? {
         opts.terminal.write_line(&fmt_info!(
             "This is the first vault to be created in this environment. It will be set as the default vault"
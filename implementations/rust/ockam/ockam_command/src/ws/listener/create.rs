@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use crate::node::{get_node_name, initialize_node_if_default};
+use crate::util::Rpc;
+use crate::util::{exitcode, node_rpc, parse_node_name};
+use crate::{CommandGlobalOpts, Error};
+use clap::Args;
+use miette::miette;
+use ockam_api::nodes::models;
+use ockam_api::nodes::models::transport::CreateWebSocketListener;
+use ockam_core::api::Request;
+use ockam_multiaddr::proto::{DnsAddr, Tcp, Ws, Wss};
+use ockam_multiaddr::MultiAddr;
+
+/// Create a WebSocket listener
+///
+/// The listener accepts `wss` connections when it is given a certificate and its private key.
+/// Nodes verify the certificates of `wss` listeners with the trusted roots of the platform, or with
+/// the CA certificates of the file set in the OCKAM_WSS_CA_FILE environment variable.
+#[derive(Args, Clone, Debug)]
+pub struct CreateCommand {
+    /// Node at which to create the listener
+    #[arg(global = true, long, value_name = "NODE")]
+    pub at: Option<String>,
+
+    /// Address for this listener (eg. 127.0.0.1:7000)
+    pub address: String,
+
+    /// PEM file containing the certificate chain of the listener, starting with its certificate
+    #[arg(long, value_name = "PATH", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM file containing the private key of the listener certificate
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.at);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: ockam::Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> crate::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.at);
+    let node_name = parse_node_name(&node_name)?;
+
+    let mut body = CreateWebSocketListener::new(cmd.address);
+    if let (Some(cert), Some(key)) = (&cmd.tls_cert, &cmd.tls_key) {
+        // The node reads the files, which must not depend on its working directory
+        body = body.with_tls(absolute_path(cert)?, absolute_path(key)?);
+    }
+    let secure = body.tls_certificate_file.is_some();
+
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    rpc.request(Request::post("/node/ws/listener").body(body))
+        .await?;
+    let response = rpc.parse_response::<models::transport::TransportStatus>()?;
+
+    let socket = response.socket_addr()?;
+    let mut multiaddr = MultiAddr::default();
+    multiaddr.push_back(DnsAddr::new("localhost"))?;
+    multiaddr.push_back(Tcp::new(socket.port()))?;
+    if secure {
+        multiaddr.push_back(Wss)?;
+    } else {
+        multiaddr.push_back(Ws)?;
+    }
+    println!(
+        "WebSocket listener created! You can send messages to it via this route:\n`{multiaddr}`"
+    );
+
+    Ok(())
+}
+
+fn absolute_path(path: &Path) -> crate::Result<String> {
+    let path = std::fs::canonicalize(path).map_err(|e| {
+        Error::new(
+            exitcode::IOERR,
+            miette!("cannot read {}: {e}", path.display()),
+        )
+    })?;
+    Ok(path.to_string_lossy().to_string())
+}
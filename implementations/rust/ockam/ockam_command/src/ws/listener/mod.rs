@@ -0,0 +1,27 @@
+mod create;
+
+pub(crate) use create::CreateCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage WebSocket Listeners
+#[derive(Args, Clone, Debug)]
+pub struct WebSocketListenerCommand {
+    #[command(subcommand)]
+    subcommand: WebSocketListenerSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WebSocketListenerSubCommand {
+    /// Create a websocket listener on the selected node
+    Create(CreateCommand),
+}
+
+impl WebSocketListenerCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            WebSocketListenerSubCommand::Create(c) => c.run(options),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::alloc::string::ToString;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+/// A typed attribute value.
+///
+/// Plain attributes are stored as bytes, one value per name. Typed values
+/// allow attributes like groups or roles to have several values, or to be
+/// compared as numbers or booleans.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[rustfmt::skip]
+pub enum AttributeValue {
+    /// A UTF-8 string
+    #[n(1)] Str  (#[n(0)] String),
+    /// A signed integer
+    #[n(2)] Int  (#[n(0)] i64),
+    /// A boolean
+    #[n(3)] Bool (#[n(0)] bool),
+    /// A list of values
+    #[n(4)] List (#[n(0)] Vec<AttributeValue>),
+}
+
+impl AttributeValue {
+    /// Return true if this value, or one of its elements for a list, has the
+    /// given plain representation
+    pub fn matches(&self, value: &[u8]) -> bool {
+        match self {
+            AttributeValue::Str(s) => s.as_bytes() == value,
+            AttributeValue::Int(i) => i.to_string().as_bytes() == value,
+            AttributeValue::Bool(b) => b.to_string().as_bytes() == value,
+            AttributeValue::List(xs) => xs.iter().any(|x| x.matches(value)),
+        }
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(s: &str) -> Self {
+        AttributeValue::Str(s.into())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(s: String) -> Self {
+        AttributeValue::Str(s)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(i: i64) -> Self {
+        AttributeValue::Int(i)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(b: bool) -> Self {
+        AttributeValue::Bool(b)
+    }
+}
+
+impl<T: Into<AttributeValue>> From<Vec<T>> for AttributeValue {
+    fn from(xs: Vec<T>) -> Self {
+        AttributeValue::List(xs.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_matches_any_element() {
+        let groups = AttributeValue::from(vec!["admins", "devs"]);
+        assert!(groups.matches(b"devs"));
+        assert!(!groups.matches(b"ops"));
+        assert!(AttributeValue::from(42).matches(b"42"));
+        assert!(AttributeValue::from(true).matches(b"true"));
+    }
+
+    #[test]
+    fn cbor_roundtrip() {
+        let value = AttributeValue::List(vec![
+            AttributeValue::from("admins"),
+            AttributeValue::from(-1),
+            AttributeValue::from(false),
+        ]);
+        let bytes = minicbor::to_vec(&value).unwrap();
+        assert_eq!(minicbor::decode::<AttributeValue>(&bytes).unwrap(), value);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::CredentialData;
+use crate::{CredentialData, Disclosure};
 use core::fmt;
+use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::Result;
@@ -26,6 +27,9 @@ pub struct Credential {
     /// Cryptographic signature of attributes data.
     #[cbor(with = "minicbor::bytes")]
     #[b(2)] pub signature: Vec<u8>,
+    /// Encoded [`Disclosure`]s revealed by the holder of the credential.
+    /// They are not covered by the signature, only their digests are.
+    #[n(3)] pub disclosures: Option<Vec<ByteVec>>,
 }
 
 impl Credential {
@@ -45,16 +49,54 @@ impl Credential {
             tag: TypeTag,
             data,
             signature,
+            disclosures: None,
         }
     }
+
+    pub(crate) fn with_disclosures(mut self, disclosures: Vec<ByteVec>) -> Self {
+        self.disclosures = Some(disclosures).filter(|d| !d.is_empty());
+        self
+    }
+
+    /// Return the encoded disclosures attached to this credential
+    pub fn unverified_disclosures(&self) -> &[ByteVec] {
+        self.disclosures.as_deref().unwrap_or_default()
+    }
+
+    /// Return the names of the attributes which can be disclosed by the holder
+    /// of this credential
+    pub fn disclosable_attributes(&self) -> Vec<String> {
+        self.unverified_disclosures()
+            .iter()
+            .filter_map(|d| Disclosure::try_from(d.as_slice()).ok())
+            .map(|d| d.name().into())
+            .collect()
+    }
+
+    /// Return a copy of this credential only revealing the given selectively
+    /// disclosable attributes. The other attributes signed by the issuer are unchanged.
+    pub fn disclose(&self, names: &[&str]) -> Credential {
+        let disclosures = self
+            .unverified_disclosures()
+            .iter()
+            .filter(|d| {
+                Disclosure::try_from(d.as_slice())
+                    .map(|d| names.contains(&d.name()))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        self.clone().with_disclosures(disclosures)
+    }
 }
 
 impl fmt::Display for Credential {
     #[cfg(feature = "std")]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let data = CredentialData::<Unverified>::try_from(self)
-            .map_err(|_| fmt::Error)?
-            .into_verified();
+        let mut data = CredentialData::<Unverified>::try_from(self).map_err(|_| fmt::Error)?;
+        data.disclose(self.unverified_disclosures())
+            .map_err(|_| fmt::Error)?;
+        let data = data.into_verified();
         write!(f, "{}", data)?;
         writeln!(f, "Signature:  {}", hex::encode(self.signature.deref()))
     }
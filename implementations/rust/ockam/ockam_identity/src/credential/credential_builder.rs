@@ -6,7 +6,8 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 
 use crate::credential::{
-    Attributes, CredentialData, SchemaId, Timestamp, Verified, MAX_CREDENTIAL_VALIDITY,
+    AttributeValue, Attributes, CredentialData, SchemaId, Timestamp, Verified,
+    MAX_CREDENTIAL_VALIDITY,
 };
 use crate::identity::identity_change::IdentityChangeConstants;
 use crate::identity::IdentityIdentifier;
@@ -21,6 +22,7 @@ pub struct CredentialBuilder {
     pub(crate) subject: IdentityIdentifier,
     pub(crate) issuer: IdentityIdentifier,
     pub(crate) validity: Duration,
+    pub(crate) not_after: Option<Timestamp>,
 }
 
 impl CredentialBuilder {
@@ -34,6 +36,7 @@ impl CredentialBuilder {
             subject,
             issuer,
             validity: MAX_CREDENTIAL_VALIDITY,
+            not_after: None,
         }
     }
 
@@ -56,6 +59,12 @@ impl CredentialBuilder {
         self
     }
 
+    /// Add some key / typed value pair as credential attribute.
+    pub fn with_attribute_value(mut self, k: &str, v: impl Into<AttributeValue>) -> Self {
+        self.attrs.put_value(k, v.into());
+        self
+    }
+
     /// Set the schema identifier of the credential.
     pub fn with_schema(mut self, s: SchemaId) -> Self {
         self.schema = Some(s);
@@ -76,12 +85,23 @@ impl CredentialBuilder {
         self
     }
 
+    /// Make sure that the credential does not expire later than the given time,
+    /// whatever its validity duration is.
+    pub fn not_after(mut self, t: Timestamp) -> Self {
+        self.not_after = Some(t);
+        self
+    }
+
     /// Return a verified credential data, with a created timestamp
     pub fn build(self) -> Result<CredentialData<Verified>> {
         let key_label = IdentityChangeConstants::ROOT_LABEL;
         let now = Timestamp::now()
             .ok_or_else(|| Error::new(Origin::Core, Kind::Internal, "invalid system time"))?;
         let exp = Timestamp::add_seconds(&now, self.validity.as_secs());
+        let exp = match self.not_after {
+            Some(not_after) if not_after < exp => not_after,
+            _ => exp,
+        };
 
         Ok(CredentialData {
             schema: self.schema,
@@ -92,6 +112,7 @@ impl CredentialBuilder {
             created: now,
             expires: exp,
             status: None::<PhantomData<Verified>>,
+            digests: None,
         })
     }
 }
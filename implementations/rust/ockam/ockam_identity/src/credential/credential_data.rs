@@ -1,10 +1,10 @@
 use crate::alloc::string::ToString;
 use crate::identity::identity_change::IdentityChangeConstants;
 use crate::identity::IdentityIdentifier;
-use crate::CredentialBuilder;
+use crate::{AttributeValue, CredentialBuilder, DisclosedValue, Disclosure};
 use core::marker::PhantomData;
 use core::time::Duration;
-use minicbor::bytes::ByteVec;
+use minicbor::bytes::{ByteArray, ByteVec};
 use minicbor::{Decode, Encode};
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::{collections::BTreeMap, fmt, string::String, vec::Vec};
@@ -43,6 +43,8 @@ pub struct CredentialData<T> {
     #[n(7)] pub(crate) expires: Timestamp,
     /// Term to represent the verification status type.
     #[n(8)] pub(crate) status: Option<PhantomData<T>>,
+    /// Digests of the selectively disclosable attributes.
+    #[n(9)] pub(crate) digests: Option<Vec<ByteArray<32>>>,
 }
 
 impl CredentialData<Verified> {
@@ -114,8 +116,39 @@ impl CredentialData<Unverified> {
             created: self.created,
             expires: self.expires,
             status: None::<PhantomData<Verified>>,
+            digests: self.digests,
         }
     }
+
+    /// Add the attributes revealed by the holder of the credential.
+    ///
+    /// Each disclosure must have been signed by the issuer, via its digest, and must
+    /// not override an attribute which is already present.
+    pub(crate) fn disclose(&mut self, disclosures: &[ByteVec]) -> Result<()> {
+        for encoded in disclosures {
+            let digest = Disclosure::digest(encoded);
+            if !self.digests.iter().flatten().any(|d| **d == digest) {
+                return Err(Error::new(
+                    Origin::Application,
+                    Kind::Invalid,
+                    "invalid disclosure",
+                ));
+            }
+            let disclosure = Disclosure::try_from(encoded.as_slice())?;
+            if self.attributes.contains(disclosure.name()) {
+                return Err(Error::new(
+                    Origin::Application,
+                    Kind::Invalid,
+                    "duplicate attribute disclosure",
+                ));
+            }
+            match disclosure.value() {
+                DisclosedValue::Plain(v) => self.attributes.put(disclosure.name(), v),
+                DisclosedValue::Typed(v) => self.attributes.put_value(disclosure.name(), v.clone()),
+            };
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for CredentialData<Verified> {
@@ -163,6 +196,17 @@ impl fmt::Display for CredentialData<Verified> {
                     .map(|(k, v)| (k, std::str::from_utf8(v).unwrap_or("**binary**"))),
             )
             .finish()?;
+        if self.attributes.values().next().is_some() {
+            write!(f, "\nValues:     ")?;
+            f.debug_map().entries(self.attributes.values()).finish()?;
+        }
+        if self.disclosable_count() > 0 {
+            write!(
+                f,
+                "\nDisclosable: {} attribute(s)",
+                self.disclosable_count()
+            )?;
+        }
         writeln!(f)
     }
 
@@ -220,6 +264,11 @@ impl CredentialData<Verified> {
         self.expires
     }
 
+    /// Return the number of selectively disclosable attributes signed by the issuer
+    pub fn disclosable_count(&self) -> usize {
+        self.digests.iter().flatten().count()
+    }
+
     /// Return the identity attributes as a reference
     pub fn attributes(&self) -> &Attributes {
         &self.attributes
@@ -264,6 +313,7 @@ pub struct Attributes {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<4724285>,
     #[b(1)] attrs: BTreeMap<String, ByteVec>,
+    #[n(2)] values: Option<BTreeMap<String, AttributeValue>>,
 }
 
 impl Attributes {
@@ -273,17 +323,18 @@ impl Attributes {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             attrs: BTreeMap::new(),
+            values: None,
         }
     }
 
     /// Return true if this set of key / value is empty
     pub fn is_empty(&self) -> bool {
-        self.attrs.is_empty()
+        self.len() == 0
     }
 
     /// Return the number of key / values
     pub fn len(&self) -> usize {
-        self.attrs.len()
+        self.attrs.len() + self.values().count()
     }
 
     /// Add a key-value pair to the attribute set.
@@ -304,6 +355,43 @@ impl Attributes {
         self.attrs.iter()
     }
 
+    /// Add a key / typed value pair to the attribute set.
+    ///
+    /// Typed values are encoded separately from the plain ones so that they are
+    /// ignored by the nodes which don't support them.
+    pub fn put_value(&mut self, k: &str, v: AttributeValue) -> &mut Self {
+        self.values
+            .get_or_insert_with(BTreeMap::new)
+            .insert(k.into(), v);
+        self
+    }
+
+    /// Return the typed value associated to a given key
+    pub fn get_value(&self, k: &str) -> Option<&AttributeValue> {
+        self.values.as_ref().and_then(|values| values.get(k))
+    }
+
+    /// Return an iterator on the list of key / typed values
+    pub fn values(&self) -> impl Iterator<Item = (&String, &AttributeValue)> {
+        self.values.iter().flatten()
+    }
+
+    /// Return true if a plain or a typed value exists for a given key
+    pub fn contains(&self, k: &str) -> bool {
+        self.attrs.contains_key(k) || self.get_value(k).is_some()
+    }
+
+    /// Remove the plain or typed value associated to a given key
+    pub(crate) fn remove(&mut self, k: &str) -> Option<DisclosedValue> {
+        if let Some(v) = self.attrs.remove(k) {
+            return Some(DisclosedValue::Plain(v));
+        }
+        self.values
+            .as_mut()
+            .and_then(|values| values.remove(k))
+            .map(DisclosedValue::Typed)
+    }
+
     //TODO: review the credential' attributes types.   They are references and has lifetimes,
     //etc,  but in reality this is always just deserizalided (either from wire or from
     //storage), so imho all that just add to the complexity without gaining much
@@ -313,6 +401,14 @@ impl Attributes {
             .map(|(k, v)| (k.to_string(), v.to_vec()))
             .collect()
     }
+
+    pub(crate) fn values_map(&self) -> BTreeMap<String, AttributeValue> {
+        self.values
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
 }
 
 /// A Unix timestamp (seconds since 1970-01-01 00:00:00 UTC)
@@ -332,7 +428,8 @@ impl Timestamp {
             .map(|d| Timestamp(d.as_secs()))
     }
 
-    pub(crate) fn add_seconds(&self, seconds: u64) -> Self {
+    /// Return a new timestamp which is a given number of seconds after this one
+    pub fn add_seconds(&self, seconds: u64) -> Self {
         Timestamp(self.0.saturating_add(seconds))
     }
 
@@ -405,6 +502,7 @@ Attributes: {"name": "value"}
             created: Timestamp(120),
             expires: Timestamp(200),
             status: None::<PhantomData<Verified>>,
+            digests: None,
         }
     }
 }
@@ -0,0 +1,96 @@
+use crate::credential::AttributeValue;
+use minicbor::bytes::{ByteArray, ByteVec};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::rand;
+use ockam_core::compat::rand::RngCore;
+use ockam_core::compat::{string::String, vec::Vec};
+use ockam_core::Result;
+use ockam_vault::Vault;
+
+#[cfg(feature = "tag")]
+use crate::TypeTag;
+
+/// Value of an attribute which is disclosed separately from the signed credential data
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+pub enum DisclosedValue {
+    /// A plain attribute value
+    #[n(1)] Plain (#[n(0)] ByteVec),
+    /// A typed attribute value
+    #[n(2)] Typed (#[n(0)] AttributeValue),
+}
+
+/// A selectively disclosable attribute.
+///
+/// The issuer only signs the digest of the encoded disclosure, so that the holder
+/// of a credential can choose to reveal the attribute or not. The salt prevents a
+/// verifier from guessing the value of an undisclosed attribute from its digest.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Disclosure {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8137246>,
+    #[n(1)] salt: ByteArray<16>,
+    #[n(2)] name: String,
+    #[n(3)] value: DisclosedValue,
+}
+
+impl Disclosure {
+    /// Create a disclosure for an attribute, with a random salt
+    pub fn new(name: impl Into<String>, value: DisclosedValue) -> Self {
+        let mut salt = [0; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Disclosure {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            salt: salt.into(),
+            name: name.into(),
+            value,
+        }
+    }
+
+    /// Return the name of the disclosed attribute
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the value of the disclosed attribute
+    pub fn value(&self) -> &DisclosedValue {
+        &self.value
+    }
+
+    /// Encode the disclosure, the digest of the encoded bytes is signed by the issuer
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(minicbor::to_vec(self)?)
+    }
+
+    /// Return the digest of an encoded disclosure
+    pub fn digest(encoded: &[u8]) -> [u8; 32] {
+        Vault::sha256(encoded)
+    }
+}
+
+impl TryFrom<&[u8]> for Disclosure {
+    type Error = minicbor::decode::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        minicbor::decode(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disclosures_of_the_same_value_have_different_digests() {
+        let value = DisclosedValue::Plain(b"admin".to_vec().into());
+        let d1 = Disclosure::new("role", value.clone()).encode().unwrap();
+        let d2 = Disclosure::new("role", value).encode().unwrap();
+        assert_ne!(Disclosure::digest(&d1), Disclosure::digest(&d2));
+
+        let decoded = Disclosure::try_from(d1.as_slice()).unwrap();
+        assert_eq!(decoded.name(), "role");
+    }
+}
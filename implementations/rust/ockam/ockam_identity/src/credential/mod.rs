@@ -1,10 +1,14 @@
+mod attribute_value;
 #[allow(clippy::module_inception)]
 mod credential;
 mod credential_builder;
 mod credential_data;
+mod disclosure;
 mod one_time_code;
 
+pub use attribute_value::*;
 pub use credential::*;
 pub use credential_builder::*;
 pub use credential_data::*;
+pub use disclosure::*;
 pub use one_time_code::*;
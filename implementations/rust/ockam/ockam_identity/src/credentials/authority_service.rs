@@ -8,6 +8,11 @@ use ockam_core::compat::sync::RwLock;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_node::Context;
+use tracing::warn;
+
+/// Default duration before the expiry of the cached credential when a new one is retrieved.
+/// It gives a bit of leeway for clock skew
+const DEFAULT_RENEWAL_MARGIN_SECS: u64 = 60;
 
 /// An AuthorityService represents an authority which issued credentials
 #[derive(Clone)]
@@ -17,6 +22,7 @@ pub struct AuthorityService {
     identifier: IdentityIdentifier,
     own_credential: Option<Arc<dyn CredentialsRetriever>>,
     inner_cache: Arc<RwLock<Option<CachedCredential>>>,
+    renewal_margin_secs: u64,
 }
 
 #[derive(Clone)]
@@ -39,9 +45,26 @@ impl AuthorityService {
             identifier,
             own_credential,
             inner_cache: Arc::new(RwLock::new(None)),
+            renewal_margin_secs: DEFAULT_RENEWAL_MARGIN_SECS,
         }
     }
 
+    /// Retrieve a new credential when the cached one expires in less than the given
+    /// number of seconds
+    pub fn with_renewal_margin_secs(mut self, renewal_margin_secs: u64) -> Self {
+        self.renewal_margin_secs = renewal_margin_secs;
+        self
+    }
+
+    /// Return the expiry of the cached credential, if there is one
+    pub fn credential_expires_at(&self) -> Option<Timestamp> {
+        self.inner_cache
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|cache| cache.valid_until)
+    }
+
     /// Return the Public Identity of the Authority
     pub async fn identity(&self) -> Result<Identity> {
         self.identities_reader.get_identity(&self.identifier).await
@@ -53,20 +76,35 @@ impl AuthorityService {
         ctx: &Context,
         for_identity: &IdentityIdentifier,
     ) -> Result<Credential> {
-        {
-            // check if we have a valid cached credential
-            let guard = self.inner_cache.read().unwrap();
-            let now = Timestamp::now().ok_or_else(|| {
-                Error::new(Origin::Application, Kind::Invalid, "invalid system time")
-            })?;
-            if let Some(cache) = guard.as_ref() {
-                // add an extra minute to have a bit of leeway for clock skew
-                if cache.valid_until > now.add_seconds(60) {
-                    return Ok(cache.credential.clone());
-                }
+        let now = Timestamp::now()
+            .ok_or_else(|| Error::new(Origin::Application, Kind::Invalid, "invalid system time"))?;
+        // check if we have a valid cached credential
+        let cached = self.inner_cache.read().unwrap().clone();
+        if let Some(cache) = cached.as_ref() {
+            if cache.valid_until > now.add_seconds(self.renewal_margin_secs) {
+                return Ok(cache.credential.clone());
             }
         }
 
+        match self.retrieve(ctx, for_identity).await {
+            Ok(credential) => Ok(credential),
+            Err(e) => match cached {
+                // keep using the cached credential while it is still valid, the authority
+                // might only be unreachable for a while
+                Some(cache) if cache.valid_until > now.add_seconds(DEFAULT_RENEWAL_MARGIN_SECS) => {
+                    warn!("the credential of {for_identity} could not be renewed yet: {e}");
+                    Ok(cache.credential)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &IdentityIdentifier,
+    ) -> Result<Credential> {
         // in order to keep the locking schema simple, we allow multiple concurrent retrievals
         let retriever = self
             .own_credential
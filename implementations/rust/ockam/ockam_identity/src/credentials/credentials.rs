@@ -1,4 +1,5 @@
-use crate::credential::{Credential, CredentialData, Timestamp, Verified};
+use crate::credential::{Credential, CredentialData, Disclosure, Timestamp, Verified};
+use crate::credentials::CredentialsCache;
 use crate::identities::{AttributesEntry, Identities};
 use crate::identity::{Identity, IdentityError, IdentityIdentifier};
 use async_trait::async_trait;
@@ -17,6 +18,16 @@ pub trait Credentials: Send + Sync + 'static {
         credential_data: CredentialData<Verified>,
     ) -> Result<Credential>;
 
+    /// Issue a credential where the given attributes are selectively disclosable:
+    /// the issuer only signs salted digests of these attributes and the holder of the
+    /// credential decides which ones are revealed to a verifier
+    async fn issue_credential_with_disclosures(
+        &self,
+        issuer: &IdentityIdentifier,
+        credential_data: CredentialData<Verified>,
+        disclosable: &[&str],
+    ) -> Result<Credential>;
+
     /// Verify that a credential has been signed by one of the authorities
     async fn verify_credential(
         &self,
@@ -42,7 +53,16 @@ impl Credentials for Identities {
         authorities: &[Identity],
         credential: Credential,
     ) -> Result<CredentialData<Verified>> {
-        let credential_data = CredentialData::try_from(credential.data.as_slice())?;
+        let now = Timestamp::now()
+            .ok_or_else(|| Error::new(Origin::Application, Kind::Invalid, "invalid system time"))?;
+
+        // a credential which was already verified doesn't need its signature to be checked again
+        let key = CredentialsCache::key(&credential)?;
+        if let Some(credential_data) = self.credentials_cache.get(&key, subject, authorities, now) {
+            return Ok(credential_data);
+        }
+
+        let mut credential_data = CredentialData::try_from(credential.data.as_slice())?;
 
         let issuer = authorities
             .iter()
@@ -52,9 +72,6 @@ impl Credentials for Identities {
             None => return Err(IdentityError::UnknownAuthority.into()),
         };
 
-        let now = Timestamp::now()
-            .ok_or_else(|| Error::new(Origin::Application, Kind::Invalid, "invalid system time"))?;
-
         credential_data.verify(subject, &issuer.identifier(), now)?;
 
         let sig = ockam_vault::Signature::new(credential.signature().to_vec());
@@ -75,7 +92,13 @@ impl Credentials for Identities {
                 "invalid signature",
             ));
         }
-        Ok(credential_data.into_verified())
+
+        // only the attributes revealed by the holder are added to the verified data
+        credential_data.disclose(credential.unverified_disclosures())?;
+        let credential_data = credential_data.into_verified();
+        self.credentials_cache
+            .put(key, credential_data.clone(), now);
+        Ok(credential_data)
     }
 
     /// Create a signed credential based on the given values.
@@ -93,6 +116,27 @@ impl Credentials for Identities {
         Ok(Credential::new(bytes, SignatureVec::from(sig)))
     }
 
+    async fn issue_credential_with_disclosures(
+        &self,
+        issuer: &IdentityIdentifier,
+        mut credential_data: CredentialData<Verified>,
+        disclosable: &[&str],
+    ) -> Result<Credential> {
+        let mut disclosures = vec![];
+        let mut digests = vec![];
+        for name in disclosable {
+            if let Some(value) = credential_data.attributes.remove(name) {
+                let encoded = Disclosure::new(*name, value).encode()?;
+                digests.push(Disclosure::digest(&encoded).into());
+                disclosures.push(encoded.into());
+            }
+        }
+        credential_data.digests = Some(digests).filter(|d: &Vec<_>| !d.is_empty());
+
+        let credential = self.issue_credential(issuer, credential_data).await?;
+        Ok(credential.with_disclosures(disclosures))
+    }
+
     async fn receive_presented_credential(
         &self,
         sender: &IdentityIdentifier,
@@ -111,7 +155,8 @@ impl Credentials for Identities {
                     Timestamp::now().unwrap(),
                     Some(credential_data.expires),
                     Some(credential_data.issuer),
-                ),
+                )
+                .with_values(credential_data.attributes.values_map()),
             )
             .await?;
 
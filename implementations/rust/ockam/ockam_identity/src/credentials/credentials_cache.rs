@@ -0,0 +1,115 @@
+use crate::credential::{Credential, CredentialData, Timestamp, Verified};
+use crate::identity::{Identity, IdentityIdentifier};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::RwLock;
+use ockam_core::Result;
+use ockam_vault::Vault;
+
+/// Maximum number of verified credentials kept in the cache
+pub const MAX_CACHED_CREDENTIALS: usize = 1000;
+
+/// Cache of the credentials which have already been verified.
+///
+/// Credentials are keyed by the hash of their encoding, so that a peer presenting the
+/// same credential again during a secure channel handshake doesn't require its signature
+/// to be checked again. The subject, the issuer and the expiration date of a cached
+/// credential are still checked on each lookup.
+#[derive(Default)]
+pub struct CredentialsCache {
+    entries: RwLock<BTreeMap<[u8; 32], CredentialData<Verified>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// Number of hits and misses of a [`CredentialsCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CredentialsCacheStats {
+    /// Number of credentials which didn't need to be verified again
+    pub hits: usize,
+    /// Number of credentials which had to be verified
+    pub misses: usize,
+}
+
+impl CredentialsCacheStats {
+    /// Return the ratio of lookups which were served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl CredentialsCache {
+    /// Return the key of a credential in the cache
+    pub fn key(credential: &Credential) -> Result<[u8; 32]> {
+        Ok(Vault::sha256(&minicbor::to_vec(credential)?))
+    }
+
+    /// Return the verified data of a credential if it is cached and still valid
+    /// for the given subject and authorities
+    pub(crate) fn get(
+        &self,
+        key: &[u8; 32],
+        subject: &IdentityIdentifier,
+        authorities: &[Identity],
+        now: Timestamp,
+    ) -> Option<CredentialData<Verified>> {
+        let entries = self.entries.read().unwrap();
+        let found = entries.get(key).filter(|data| {
+            &data.subject == subject
+                && data.expires > now
+                && authorities.iter().any(|a| a.identifier() == data.issuer)
+        });
+        match found {
+            Some(data) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(data.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Cache the data of a verified credential
+    pub(crate) fn put(&self, key: [u8; 32], data: CredentialData<Verified>, now: Timestamp) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= MAX_CACHED_CREDENTIALS {
+            entries.retain(|_, d| d.expires > now);
+        }
+        if entries.len() >= MAX_CACHED_CREDENTIALS {
+            // evict the credential which expires first
+            if let Some(k) = entries
+                .iter()
+                .min_by_key(|(_, d)| d.expires)
+                .map(|(k, _)| *k)
+            {
+                entries.remove(&k);
+            }
+        }
+        entries.insert(key, data);
+    }
+
+    /// Return the number of cached credentials
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Return true if no credential is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of hits and misses since the creation of the cache
+    pub fn stats(&self) -> CredentialsCacheStats {
+        CredentialsCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -26,6 +26,7 @@ pub struct CredentialsIssuer {
     identities: Arc<Identities>,
     issuer: IdentityIdentifier,
     trust_context: String,
+    disclosable_attributes: Vec<String>,
 }
 
 impl CredentialsIssuer {
@@ -39,9 +40,17 @@ impl CredentialsIssuer {
             identities,
             issuer,
             trust_context,
+            disclosable_attributes: vec![],
         })
     }
 
+    /// Make the given attributes selectively disclosable in the issued credentials,
+    /// so that their holders decide which ones are revealed to a verifier
+    pub fn with_disclosable_attributes(mut self, names: &[&str]) -> Self {
+        self.disclosable_attributes = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
     async fn issue_credential(&self, from: &IdentityIdentifier) -> Result<Option<Credential>> {
         match self
             .identities
@@ -51,7 +60,7 @@ impl CredentialsIssuer {
             .await?
         {
             Some(entry) => {
-                let crd = entry
+                let mut crd = entry
                     .attrs()
                     .iter()
                     .fold(
@@ -61,12 +70,29 @@ impl CredentialsIssuer {
                     )
                     .with_attribute(LEGACY_ID, self.trust_context.as_bytes()) // TODO: DEPRECATE - Removing PROJECT_ID attribute in favor of TRUST_CONTEXT_ID
                     .with_attribute(TRUST_CONTEXT_ID, self.trust_context.as_bytes());
-                Ok(Some(
-                    self.identities
-                        .credentials()
+                for (name, value) in entry.values() {
+                    crd = crd.with_attribute_value(name, value.clone());
+                }
+                // A member can not keep a valid credential after its own membership expired
+                if let Some(expires) = entry.expires() {
+                    crd = crd.not_after(expires);
+                }
+                let credentials = self.identities.credentials();
+                let credential = if self.disclosable_attributes.is_empty() {
+                    credentials
                         .issue_credential(&self.issuer, crd.build()?)
-                        .await?,
-                ))
+                        .await?
+                } else {
+                    let names: Vec<&str> = self
+                        .disclosable_attributes
+                        .iter()
+                        .map(|n| n.as_str())
+                        .collect();
+                    credentials
+                        .issue_credential_with_disclosures(&self.issuer, crd.build()?, &names)
+                        .await?
+                };
+                Ok(Some(credential))
             }
             None => Ok(None),
         }
@@ -1,6 +1,7 @@
 mod authority_service;
 #[allow(clippy::module_inception)]
 mod credentials;
+mod credentials_cache;
 mod credentials_issuer;
 mod credentials_retriever;
 mod credentials_server;
@@ -9,6 +10,7 @@ mod trust_context;
 
 pub use authority_service::*;
 pub use credentials::*;
+pub use credentials_cache::*;
 pub use credentials_issuer::*;
 pub use credentials_retriever::*;
 pub use credentials_server::*;
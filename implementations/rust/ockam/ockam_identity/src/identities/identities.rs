@@ -1,7 +1,7 @@
 use crate::identities::{IdentitiesKeys, IdentitiesRepository, IdentitiesVault};
 use crate::{
-    Credentials, CredentialsServer, CredentialsServerModule, IdentitiesBuilder, IdentitiesCreation,
-    IdentitiesReader, IdentitiesStorage,
+    Credentials, CredentialsCache, CredentialsServer, CredentialsServerModule, IdentitiesBuilder,
+    IdentitiesCreation, IdentitiesReader, IdentitiesStorage,
 };
 use ockam_core::compat::sync::Arc;
 use ockam_vault::Vault;
@@ -11,6 +11,7 @@ use ockam_vault::Vault;
 pub struct Identities {
     pub(crate) vault: Arc<dyn IdentitiesVault>,
     pub(crate) identities_repository: Arc<dyn IdentitiesRepository>,
+    pub(crate) credentials_cache: Arc<CredentialsCache>,
 }
 
 impl Identities {
@@ -47,6 +48,11 @@ impl Identities {
         Arc::new(self.clone())
     }
 
+    /// Return the cache of the verified credentials
+    pub fn credentials_cache(&self) -> Arc<CredentialsCache> {
+        self.credentials_cache.clone()
+    }
+
     /// Return the identities credentials server
     pub fn credentials_server(&self) -> Arc<dyn CredentialsServer> {
         Arc::new(CredentialsServerModule::new(self.credentials()))
@@ -62,6 +68,7 @@ impl Identities {
         Identities {
             vault,
             identities_repository,
+            credentials_cache: Arc::new(CredentialsCache::default()),
         }
     }
 
@@ -1,13 +1,20 @@
+use ockam_core::compat::collections::BTreeSet;
+use ockam_core::compat::rand;
+use ockam_core::compat::rand::RngCore;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
-use ockam_core::{KeyId, Result};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, KeyId, Result};
 use ockam_vault::{Secret, SecretAttributes};
+use tracing::warn;
 
 use crate::alloc::string::ToString;
+use crate::identities::identity_bundle::{BundledSecret, EncryptedSecrets, PasswordKdf};
 use crate::identity::IdentityError;
 use crate::{
-    IdentitiesKeys, IdentitiesRepository, IdentitiesVault, Identity, IdentityChangeConstants,
-    IdentityChangeHistory, IdentityIdentifier, KeyAttributes,
+    IdentitiesKeys, IdentitiesRepository, IdentitiesVault, Identity, IdentityBundle,
+    IdentityChangeConstants, IdentityChangeHistory, IdentityIdentifier, KeyAttributes,
 };
 
 /// This struct supports functions for the creation and import of identities using an IdentityVault
@@ -71,6 +78,191 @@ impl IdentitiesCreation {
         Ok(identity)
     }
 
+    /// Export an identity as a portable bundle.
+    ///
+    /// When a password is given, the secret keys of the identity are exported from the vault
+    /// and encrypted in the bundle, so that the identity can be restored on another machine
+    pub async fn export_identity_bundle(
+        &self,
+        identity: &Identity,
+        password: Option<&str>,
+    ) -> Result<IdentityBundle> {
+        let change_history = identity.change_history();
+        let bundle = IdentityBundle::new(&change_history)?;
+        let password = match password {
+            Some(password) => password,
+            None => return Ok(bundle),
+        };
+
+        let labels: BTreeSet<String> = change_history
+            .as_ref()
+            .iter()
+            .map(|c| c.change().label().to_string())
+            .collect();
+        let identity_keys = IdentitiesKeys::new(self.vault.clone());
+        let mut secrets = vec![];
+        for label in labels {
+            let key_id = identity_keys.get_secret_key(identity, Some(&label)).await?;
+            let stored_secret = self.vault.export_persistent_secret(&key_id).await?;
+            secrets.push(BundledSecret {
+                label,
+                secret_type: stored_secret.attributes().secret_type(),
+                secret: stored_secret.secret().clone(),
+            });
+        }
+        let plaintext = minicbor::to_vec(&secrets)?;
+
+        let kdf = PasswordKdf::generate();
+        let mut nonce = [0; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key_id = self.import_bundle_key(&kdf, password).await?;
+        let ciphertext = self
+            .vault
+            .aead_aes_gcm_encrypt(&key_id, &plaintext, &nonce, bundle.change_history_data())
+            .await;
+        self.vault.delete_ephemeral_secret(key_id).await?;
+
+        Ok(bundle.with_secrets(EncryptedSecrets::new(kdf, nonce, ciphertext?.to_vec())))
+    }
+
+    /// Import an identity from a bundle and persist it.
+    ///
+    /// The change history of the identity is fully verified. If the bundle contains the
+    /// secret keys of the identity, they are decrypted with the password and stored in the
+    /// vault, after checking that they match the public keys of the change history
+    pub async fn import_identity_bundle(
+        &self,
+        bundle: &IdentityBundle,
+        password: Option<&str>,
+    ) -> Result<Identity> {
+        bundle.change_history()?;
+        let identity = self.decode_identity(bundle.change_history_data()).await?;
+
+        if let Some(secrets) = bundle.encrypted_secrets() {
+            let password = password.ok_or_else(|| {
+                Error::new(
+                    Origin::Identity,
+                    Kind::Invalid,
+                    "a password is required to import the secrets of this identity bundle",
+                )
+            })?;
+            let key_id = self.import_bundle_key(secrets.kdf(), password).await?;
+            let plaintext = self
+                .vault
+                .aead_aes_gcm_decrypt(
+                    &key_id,
+                    secrets.ciphertext(),
+                    secrets.nonce(),
+                    bundle.change_history_data(),
+                )
+                .await;
+            self.vault.delete_ephemeral_secret(key_id).await?;
+            let plaintext = plaintext.map_err(|_| {
+                Error::new(
+                    Origin::Identity,
+                    Kind::Invalid,
+                    "invalid password or corrupted identity bundle",
+                )
+            })?;
+
+            let secrets: Vec<BundledSecret> = minicbor::decode(&plaintext)?;
+            self.check_bundled_secrets(&identity, &secrets).await?;
+
+            // the identity is only stored if all its secrets could be imported
+            let imported = self.import_bundled_secrets(&identity, &secrets).await?;
+            if let Err(e) = self.repository.update_identity(&identity).await {
+                self.delete_secrets(imported).await;
+                return Err(e);
+            }
+            return Ok(identity);
+        }
+
+        self.repository.update_identity(&identity).await?;
+        Ok(identity)
+    }
+
+    /// Check that the bundled secrets match the public keys of the change history, before
+    /// anything is persisted in the vault
+    async fn check_bundled_secrets(
+        &self,
+        identity: &Identity,
+        secrets: &[BundledSecret],
+    ) -> Result<()> {
+        for bundled in secrets {
+            let expected = identity.change_history().get_public_key(&bundled.label)?;
+            let key_id = self
+                .vault
+                .import_ephemeral_secret(bundled.secret.clone(), bundled.attributes()?)
+                .await?;
+            let public_key = self.vault.get_public_key(&key_id).await;
+            self.vault.delete_ephemeral_secret(key_id).await?;
+            if public_key? != expected {
+                return Err(IdentityError::ConsistencyError.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Persist the bundled secrets and return the key ids of the secrets which were not
+    /// already in the vault.
+    ///
+    /// If a secret can't be persisted, the secrets imported before it are deleted so that
+    /// a failed import doesn't leave a partial identity in the vault
+    async fn import_bundled_secrets(
+        &self,
+        identity: &Identity,
+        secrets: &[BundledSecret],
+    ) -> Result<Vec<KeyId>> {
+        let mut imported = vec![];
+        for bundled in secrets {
+            match self.is_persisted(identity, bundled).await {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(e) => {
+                    self.delete_secrets(imported).await;
+                    return Err(e);
+                }
+            }
+            match self
+                .vault
+                .import_persistent_secret(bundled.secret.clone(), bundled.attributes()?)
+                .await
+            {
+                Ok(key_id) => imported.push(key_id),
+                Err(e) => {
+                    self.delete_secrets(imported).await;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Return true if the key of a bundled secret is already persisted in the vault.
+    /// The bundled secrets must have been checked against the change history first
+    async fn is_persisted(&self, identity: &Identity, bundled: &BundledSecret) -> Result<bool> {
+        let public_key = identity.change_history().get_public_key(&bundled.label)?;
+        let key_id = self.vault.get_key_id(&public_key).await?;
+        Ok(self.vault.get_secret_attributes(&key_id).await.is_ok())
+    }
+
+    /// Delete the secrets of a failed import
+    async fn delete_secrets(&self, key_ids: Vec<KeyId>) {
+        for key_id in key_ids {
+            if let Err(e) = self.vault.delete_persistent_secret(key_id.clone()).await {
+                warn!("could not delete the imported secret {key_id}: {e}");
+            }
+        }
+    }
+
+    /// Import the AES key used to encrypt the secrets of an identity bundle
+    async fn import_bundle_key(&self, kdf: &PasswordKdf, password: &str) -> Result<KeyId> {
+        let key = kdf.derive_key(password)?;
+        self.vault
+            .import_ephemeral_secret(Secret::new(key), SecretAttributes::Aes256)
+            .await
+    }
+
     /// Cryptographically compute `IdentityIdentifier`
     pub(super) async fn compute_identity_identifier(
         &self,
@@ -91,10 +283,16 @@ impl IdentitiesCreation {
 
     /// Create an Identity
     pub async fn create_identity(&self) -> Result<Identity> {
-        let attrs = KeyAttributes::new(
-            IdentityChangeConstants::ROOT_LABEL.to_string(),
-            SecretAttributes::Ed25519,
-        );
+        self.create_identity_with_secret_attributes(SecretAttributes::Ed25519)
+            .await
+    }
+
+    /// Create an Identity with a root key of the given type, Ed25519 or NistP256
+    pub async fn create_identity_with_secret_attributes(
+        &self,
+        attributes: SecretAttributes,
+    ) -> Result<Identity> {
+        let attrs = KeyAttributes::new(IdentityChangeConstants::ROOT_LABEL.to_string(), attributes);
         self.make_and_persist_identity(None, attrs).await
     }
 }
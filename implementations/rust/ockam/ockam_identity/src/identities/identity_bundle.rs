@@ -0,0 +1,206 @@
+use crate::identity::IdentityChangeHistory;
+use argon2::{Algorithm, Argon2, Params, Version};
+use minicbor::bytes::{ByteArray, ByteVec};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::rand;
+use ockam_core::compat::rand::RngCore;
+use ockam_core::compat::{string::String, vec::Vec};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_vault::{Secret, SecretAttributes, SecretType};
+
+/// Version of the bundle format produced by this library
+pub const IDENTITY_BUNDLE_VERSION: u8 = 1;
+
+/// Maximum memory cost of the key derivation accepted when importing a bundle (1 GiB).
+/// The costs are read from the bundle, so they must be bounded to prevent a crafted
+/// bundle from exhausting the memory or the CPU of the importing machine
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Maximum number of iterations of the key derivation accepted when importing a bundle
+const MAX_ITERATIONS: u32 = 64;
+
+/// Maximum degree of parallelism of the key derivation accepted when importing a bundle
+const MAX_PARALLELISM: u32 = 16;
+
+/// A portable bundle containing an identity change history and, optionally, the secret
+/// keys of that identity, encrypted with a password.
+///
+/// The secrets are encrypted with AES-GCM using a key derived from the password with
+/// Argon2id. The change history is used as additional authenticated data so that the
+/// secrets can't be moved to the bundle of another identity.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct IdentityBundle {
+    #[n(1)] version: u8,
+    #[n(2)] change_history: ByteVec,
+    #[n(3)] secrets: Option<EncryptedSecrets>,
+}
+
+/// Secret keys of an identity, encrypted with a password
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EncryptedSecrets {
+    #[n(1)] kdf: PasswordKdf,
+    #[n(2)] nonce: ByteArray<12>,
+    #[n(3)] ciphertext: ByteVec,
+}
+
+/// Parameters of the Argon2id function used to derive the encryption key from a password
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PasswordKdf {
+    #[n(1)] salt: ByteArray<16>,
+    #[n(2)] memory_kib: u32,
+    #[n(3)] iterations: u32,
+    #[n(4)] parallelism: u32,
+}
+
+/// A secret key of an identity, along with the label of that key in the change history
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct BundledSecret {
+    #[n(1)] pub(crate) label: String,
+    #[n(2)] pub(crate) secret_type: SecretType,
+    #[n(3)] pub(crate) secret: Secret,
+}
+
+impl IdentityBundle {
+    /// Create a bundle containing only the public change history of an identity
+    pub fn new(change_history: &IdentityChangeHistory) -> Result<Self> {
+        Ok(IdentityBundle {
+            version: IDENTITY_BUNDLE_VERSION,
+            change_history: change_history.export()?.into(),
+            secrets: None,
+        })
+    }
+
+    /// Return the change history of the bundled identity
+    ///
+    /// The consistency of the change history is checked but its signatures are only
+    /// verified when the bundle is imported
+    pub fn change_history(&self) -> Result<IdentityChangeHistory> {
+        let change_history = IdentityChangeHistory::import(&self.change_history)?;
+        change_history.check_entire_consistency()?;
+        Ok(change_history)
+    }
+
+    /// Return true if this bundle contains the secret keys of the identity
+    pub fn has_secrets(&self) -> bool {
+        self.secrets.is_some()
+    }
+
+    /// Encode the bundle as CBOR
+    pub fn export(&self) -> Result<Vec<u8>> {
+        Ok(minicbor::to_vec(self)?)
+    }
+
+    /// Decode a bundle and check that its version is supported
+    pub fn import(data: &[u8]) -> Result<Self> {
+        let bundle: IdentityBundle = minicbor::decode(data)?;
+        if bundle.version != IDENTITY_BUNDLE_VERSION {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Unsupported,
+                format!("unsupported identity bundle version {}", bundle.version),
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub(crate) fn change_history_data(&self) -> &[u8] {
+        &self.change_history
+    }
+
+    pub(crate) fn encrypted_secrets(&self) -> Option<&EncryptedSecrets> {
+        self.secrets.as_ref()
+    }
+
+    pub(crate) fn with_secrets(mut self, secrets: EncryptedSecrets) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+}
+
+impl EncryptedSecrets {
+    pub(crate) fn new(kdf: PasswordKdf, nonce: [u8; 12], ciphertext: Vec<u8>) -> Self {
+        EncryptedSecrets {
+            kdf,
+            nonce: nonce.into(),
+            ciphertext: ciphertext.into(),
+        }
+    }
+
+    pub(crate) fn kdf(&self) -> &PasswordKdf {
+        &self.kdf
+    }
+
+    pub(crate) fn nonce(&self) -> &[u8] {
+        &*self.nonce
+    }
+
+    pub(crate) fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+}
+
+impl PasswordKdf {
+    /// Create Argon2id parameters with a random salt and the default costs
+    pub(crate) fn generate() -> Self {
+        let mut salt = [0; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        PasswordKdf {
+            salt: salt.into(),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+
+    /// Derive a 256 bits AES key from a password
+    pub(crate) fn derive_key(&self, password: &str) -> Result<Vec<u8>> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                "the key derivation costs of the identity bundle are too high",
+            ));
+        }
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| Error::new(Origin::Identity, Kind::Invalid, format!("{e}")))?;
+        let mut key = vec![0; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &*self.salt, &mut key)
+            .map_err(|e| Error::new(Origin::Identity, Kind::Invalid, format!("{e}")))?;
+        Ok(key)
+    }
+}
+
+impl BundledSecret {
+    /// Return the attributes of the secret, which are not encoded as such
+    pub(crate) fn attributes(&self) -> Result<SecretAttributes> {
+        let attributes = match (self.secret_type, self.secret.length()) {
+            (SecretType::Ed25519, _) => SecretAttributes::Ed25519,
+            (SecretType::X25519, _) => SecretAttributes::X25519,
+            (SecretType::NistP256, _) => SecretAttributes::NistP256,
+            (SecretType::Aes, 16) => SecretAttributes::Aes128,
+            (SecretType::Aes, 32) => SecretAttributes::Aes256,
+            (SecretType::Buffer, n) => SecretAttributes::Buffer(n as u32),
+            _ => {
+                return Err(Error::new(
+                    Origin::Identity,
+                    Kind::Invalid,
+                    "invalid secret in the identity bundle",
+                ))
+            }
+        };
+        Ok(attributes)
+    }
+}
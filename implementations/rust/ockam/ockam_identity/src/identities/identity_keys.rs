@@ -138,27 +138,30 @@ impl IdentitiesKeys {
         Ok(key)
     }
 
-    /// Rotate this `Identity` root key
+    /// Rotate this `Identity` root key. The new key has the same type as the current one
     pub async fn rotate_root_key(&self, identity: &mut Identity) -> Result<()> {
+        let key_attributes = IdentityChangeHistory::find_last_key_change(
+            identity.change_history().as_ref(),
+            IdentityChangeConstants::ROOT_LABEL,
+        )?
+        .change()
+        .key_attributes()
+        .clone();
         let change = self
-            .make_rotate_key_change(
-                identity,
-                KeyAttributes::default_with_label(IdentityChangeConstants::ROOT_LABEL.to_string()),
-            )
+            .make_rotate_key_change(identity, key_attributes)
             .await?;
 
         identity.add_change(change)
     }
 
-    /// Creates a signed static key to use for 'xx' key exchange
+    /// Creates a signed static key to use for 'xx' key exchange,
+    /// X25519 or NistP256 depending on the curve used by the key exchange
     pub async fn create_signed_static_key(
         &self,
         identity: &Identity,
+        attributes: SecretAttributes,
     ) -> Result<(KeyId, ockam_vault::Signature)> {
-        let static_key_id = self
-            .vault
-            .create_ephemeral_secret(SecretAttributes::X25519)
-            .await?;
+        let static_key_id = self.vault.create_ephemeral_secret(attributes).await?;
 
         let public_static_key = self.vault.get_public_key(&static_key_id).await?;
 
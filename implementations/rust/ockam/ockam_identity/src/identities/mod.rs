@@ -3,6 +3,7 @@ mod identities;
 mod identities_builder;
 mod identities_creation;
 mod identities_vault;
+mod identity_bundle;
 mod identity_keys;
 
 /// Identities storage functions
@@ -12,6 +13,7 @@ pub use identities::*;
 pub use identities_builder::*;
 pub use identities_creation::*;
 pub use identities_vault::*;
+pub use identity_bundle::*;
 pub use identity_keys::*;
 pub use storage::*;
 
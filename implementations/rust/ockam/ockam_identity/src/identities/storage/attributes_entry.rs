@@ -1,5 +1,5 @@
 use crate::alloc::borrow::ToOwned;
-use crate::credential::Timestamp;
+use crate::credential::{AttributeValue, Timestamp};
 use crate::identity::IdentityIdentifier;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::{collections::BTreeMap, string::String, vec::Vec};
@@ -14,6 +14,8 @@ pub struct AttributesEntry {
     #[n(2)] added: Timestamp,
     #[n(3)] expires: Option<Timestamp>,
     #[n(4)] attested_by: Option<IdentityIdentifier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(5)] values: Option<BTreeMap<String, AttributeValue>>,
 }
 
 impl AttributesEntry {
@@ -33,19 +35,52 @@ impl AttributesEntry {
             added,
             expires,
             attested_by,
+            values: None,
         }
     }
 
+    /// Set the typed attribute values of this entry
+    pub fn with_values(mut self, values: BTreeMap<String, AttributeValue>) -> Self {
+        self.values = if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        };
+        self
+    }
+
     /// The entry attributes
     pub fn attrs(&self) -> &BTreeMap<String, Vec<u8>> {
         &self.attrs
     }
 
+    /// The entry typed attribute values
+    pub fn values(&self) -> impl Iterator<Item = (&String, &AttributeValue)> {
+        self.values.iter().flatten()
+    }
+
+    /// Return the typed value of an attribute
+    pub fn value(&self, name: &str) -> Option<&AttributeValue> {
+        self.values.as_ref().and_then(|values| values.get(name))
+    }
+
+    /// Return true if the attribute has the given value, either as its plain value
+    /// or as one of its typed values
+    pub fn has_attribute_value(&self, name: &str, value: &[u8]) -> bool {
+        self.attrs.get(name).map(|v| v.as_slice()) == Some(value)
+            || self.value(name).map(|v| v.matches(value)).unwrap_or(false)
+    }
+
     /// Expiration time for this entry
     pub fn expires(&self) -> Option<Timestamp> {
         self.expires
     }
 
+    /// Return true if the entry has an expiration time and it has been reached at the given time
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        matches!(self.expires, Some(exp) if exp <= now)
+    }
+
     /// Date that the entry was added
     pub fn added(&self) -> Timestamp {
         self.added
@@ -5,6 +5,7 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Result;
 use ockam_core::{async_trait, Error};
+use tracing::warn;
 
 use crate::alloc::string::ToString;
 use crate::credential::Timestamp;
@@ -84,6 +85,13 @@ pub trait IdentityAttributesWriter: Send + Sync + 'static {
 
     /// Remove all attributes for a given identity identifier
     async fn delete(&self, identity: &IdentityIdentifier) -> Result<()>;
+
+    /// Remove all the entries which are expired at the given time
+    /// and return them with their identity identifier
+    async fn purge_expired(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(IdentityIdentifier, AttributesEntry)>>;
 }
 
 /// Trait implementing write access to identities
@@ -170,14 +178,13 @@ impl IdentityAttributesReader for IdentitiesStorage {
 
         let now = Timestamp::now()
             .ok_or_else(|| Error::new(Origin::Core, Kind::Internal, "invalid system time"))?;
-        match entry.expires() {
-            Some(exp) if exp <= now => {
-                self.storage
-                    .del(&id, IdentityChangeConstants::ATTRIBUTES_KEY)
-                    .await?;
-                Ok(None)
-            }
-            _ => Ok(Some(entry)),
+        if entry.is_expired(now) {
+            self.storage
+                .del(&id, IdentityChangeConstants::ATTRIBUTES_KEY)
+                .await?;
+            Ok(None)
+        } else {
+            Ok(Some(entry))
         }
     }
 
@@ -250,6 +257,42 @@ impl IdentityAttributesWriter for IdentitiesStorage {
             )
             .await
     }
+
+    async fn purge_expired(
+        &self,
+        now: Timestamp,
+    ) -> Result<Vec<(IdentityIdentifier, AttributesEntry)>> {
+        let mut purged = Vec::new();
+        for id in self
+            .storage
+            .keys(IdentityChangeConstants::ATTRIBUTES_KEY)
+            .await?
+        {
+            let entry = match self
+                .storage
+                .get(&id, IdentityChangeConstants::ATTRIBUTES_KEY)
+                .await?
+            {
+                Some(e) => e,
+                None => continue,
+            };
+            // a corrupt entry must not prevent the other entries from being purged
+            let entry: AttributesEntry = match minicbor::decode(&entry) {
+                Ok(e) => e,
+                Err(e) => {
+                    warn!("skipping the corrupt attributes entry of {id}: {e}");
+                    continue;
+                }
+            };
+            if entry.is_expired(now) {
+                self.storage
+                    .del(&id, IdentityChangeConstants::ATTRIBUTES_KEY)
+                    .await?;
+                purged.push((IdentityIdentifier::try_from(id)?, entry));
+            }
+        }
+        Ok(purged)
+    }
 }
 
 #[async_trait]
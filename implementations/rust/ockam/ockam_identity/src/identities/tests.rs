@@ -228,6 +228,20 @@ impl PersistentSecretsStore for CrazyVault {
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.vault.delete_persistent_secret(key_id).await
     }
+
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.vault
+            .import_persistent_secret(secret, attributes)
+            .await
+    }
+
+    async fn export_persistent_secret(&self, key_id: &KeyId) -> Result<StoredSecret> {
+        self.vault.export_persistent_secret(key_id).await
+    }
 }
 
 #[async_trait]
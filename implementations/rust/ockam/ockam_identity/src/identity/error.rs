@@ -44,6 +44,8 @@ pub enum IdentityError {
     NonceOverflow,
     /// SecureChannel was not found in the Registry
     SecureChannelNotFound,
+    /// Route doesn't lead to the other side of the SecureChannel
+    InvalidSecureChannelRoute,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
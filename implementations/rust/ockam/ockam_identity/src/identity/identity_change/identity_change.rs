@@ -1,6 +1,6 @@
 use crate::identity::identity_change::CreateKeyChangeData;
 use crate::identity::identity_change::RotateKeyChangeData;
-use crate::identity::identity_change::{ChangeIdentifier, Signature};
+use crate::identity::identity_change::{ChangeIdentifier, KeyAttributes, Signature};
 use core::fmt;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
@@ -37,6 +37,13 @@ impl IdentityChange {
         }
     }
 
+    pub(crate) fn key_attributes(&self) -> &KeyAttributes {
+        match self {
+            IdentityChange::CreateKey(data) => data.key_attributes(),
+            IdentityChange::RotateKey(data) => data.key_attributes(),
+        }
+    }
+
     pub(crate) fn public_key(&self) -> Result<PublicKey> {
         Ok(match self {
             IdentityChange::CreateKey(data) => data.public_key(),
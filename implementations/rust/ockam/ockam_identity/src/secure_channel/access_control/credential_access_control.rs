@@ -51,14 +51,10 @@ impl IncomingAccessControl for CredentialAccessControl {
                 None => return Ok(false), // No attributes for that Identity
             };
 
-            for required_attribute in self.required_attributes.iter() {
-                let attr_val = match attributes.attrs().get(&required_attribute.0) {
-                    Some(v) => v,
-                    None => return Ok(false), // No required key
-                };
-
-                if &required_attribute.1 != attr_val {
-                    return Ok(false); // Value doesn't match
+            // A multi-valued attribute is accepted as soon as one of its values is the required one
+            for (name, value) in self.required_attributes.iter() {
+                if !attributes.has_attribute_value(name, value) {
+                    return Ok(false); // Missing key or value doesn't match
                 }
             }
 
@@ -1,5 +1,3 @@
-use ockam_core::{KeyExchanger, NewKeyExchanger};
-
 #[derive(Clone)]
 pub(crate) enum Role {
     Initiator,
@@ -21,13 +19,3 @@ impl Role {
         }
     }
 }
-
-/// KeyExchanger with extra constraints
-pub trait SecureChannelKeyExchanger: KeyExchanger + Send + Sync + 'static {}
-
-impl<D> SecureChannelKeyExchanger for D where D: KeyExchanger + Send + Sync + 'static {}
-
-/// NewKeyExchanger with extra constraints
-pub trait SecureChannelNewKeyExchanger: NewKeyExchanger + Send + Sync + 'static {}
-
-impl<D> SecureChannelNewKeyExchanger for D where D: NewKeyExchanger + Send + Sync + 'static {}
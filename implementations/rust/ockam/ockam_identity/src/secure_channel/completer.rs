@@ -2,6 +2,7 @@ use crate::secure_channel::decryptor::Decryptor;
 use crate::secure_channel::decryptor_worker::DecryptorWorker;
 use crate::secure_channel::encryptor::Encryptor;
 use crate::secure_channel::encryptor_worker::EncryptorWorker;
+use crate::secure_channel::remote_route::{RemoteRoute, RemoteRouteAccessControl};
 use crate::secure_channel::{Addresses, Role};
 use crate::{
     to_xx_initialized, Credential, Credentials, Identity, IdentityError, IdentityIdentifier,
@@ -9,7 +10,7 @@ use crate::{
 };
 use alloc::vec::Vec;
 use ockam_core::compat::sync::Arc;
-use ockam_core::{AllowAll, AllowOnwardAddress, CompletedKeyExchange, Mailbox, Mailboxes, Route};
+use ockam_core::{AllowAll, CompletedKeyExchange, Mailbox, Mailboxes, Route};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_vault::Signature;
 use tracing::info;
@@ -108,11 +109,12 @@ impl ExchangeCompleter {
         );
 
         //encryptor worker
+        let remote_route = RemoteRoute::new(self.remote_route.clone());
         {
             let encryptor = EncryptorWorker::new(
                 self.role.str(),
                 self.addresses.clone(),
-                self.remote_route.clone(),
+                remote_route.clone(),
                 Encryptor::new(
                     self.keys.encrypt_key().clone(),
                     0,
@@ -120,11 +122,12 @@ impl ExchangeCompleter {
                 ),
             );
 
-            let next_hop = self.remote_route.next()?.clone();
+            // Only the next hop of the remote route can be reached, even after
+            // the channel is moved to another route
             let main_mailbox = Mailbox::new(
                 self.addresses.encryptor.clone(),
                 Arc::new(AllowAll),
-                Arc::new(AllowOnwardAddress(next_hop)),
+                Arc::new(RemoteRouteAccessControl(remote_route.clone())),
             );
             let api_mailbox = Mailbox::new(
                 self.addresses.encryptor_api.clone(),
@@ -156,6 +159,7 @@ impl ExchangeCompleter {
             self.identity_identifier,
             self.their_identity.identifier(),
             their_decryptor_address.unwrap().clone(),
+            remote_route,
         );
 
         secure_channels
@@ -2,8 +2,9 @@ use crate::identity::IdentityError;
 use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
 use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::remote_route::RemoteRoute;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{async_trait, Decodable, Encodable, Route};
+use ockam_core::{async_trait, Decodable, Encodable};
 use ockam_core::{Any, Result, Routed, TransportMessage, Worker};
 use ockam_node::Context;
 use tracing::debug;
@@ -12,7 +13,7 @@ pub(crate) struct EncryptorWorker {
     //for debug purposes only
     role: &'static str,
     addresses: Addresses,
-    remote_route: Route,
+    remote_route: RemoteRoute,
     encryptor: Encryptor,
 }
 
@@ -20,7 +21,7 @@ impl EncryptorWorker {
     pub fn new(
         role: &'static str,
         addresses: Addresses,
-        remote_route: Route,
+        remote_route: RemoteRoute,
         encryptor: Encryptor,
     ) -> Self {
         Self {
@@ -88,7 +89,7 @@ impl EncryptorWorker {
 
         // Send the message to the decryptor on the other side
         ctx.send_from_address(
-            self.remote_route.clone(),
+            self.remote_route.get(),
             encrypted_payload,
             self.addresses.encryptor.clone(),
         )
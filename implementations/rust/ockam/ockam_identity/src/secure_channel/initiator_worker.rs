@@ -17,7 +17,7 @@ use ockam_core::{
     Routed,
 };
 use ockam_core::{Decodable, Worker};
-use ockam_key_exchange_xx::XXNewKeyExchanger;
+use ockam_key_exchange_xx::{XXCurve, XXNewKeyExchanger};
 use ockam_node::callback::CallbackSender;
 use ockam_node::{Context, WorkerBuilder};
 use tracing::debug;
@@ -170,6 +170,7 @@ impl InitiatorWorker {
         credentials: Vec<Credential>,
         trust_context: Option<TrustContext>,
         remote_route: Route,
+        curve: XXCurve,
         timeout: Duration,
     ) -> ockam_core::Result<()> {
         let (mut callback_waiter, callback_sender) = ockam_node::callback::new_callback();
@@ -183,10 +184,11 @@ impl InitiatorWorker {
         let (static_key_id, signature) = secure_channels
             .identities()
             .identities_keys()
-            .create_signed_static_key(&identity)
+            .create_signed_static_key(&identity, curve.secret_attributes())
             .await?;
 
         let key_exchanger = XXNewKeyExchanger::new(to_xx_vault(secure_channels.vault()))
+            .with_curve(curve)
             .initiator(Some(static_key_id))
             .await?;
 
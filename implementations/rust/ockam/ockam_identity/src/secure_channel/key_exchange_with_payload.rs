@@ -12,7 +12,7 @@ pub(crate) struct KeyExchangeWithPayload<T> {
     _phantom: core::marker::PhantomData<T>,
 }
 
-impl<'de, T> KeyExchangeWithPayload<T>
+impl<T> KeyExchangeWithPayload<T>
 where
     T: Serialize + DeserializeOwned + Send + Sync + 'static,
 {
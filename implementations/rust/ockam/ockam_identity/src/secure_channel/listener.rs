@@ -79,6 +79,7 @@ impl Worker for IdentityChannelListener {
         } else {
             self.options.credentials.clone()
         };
+        let credentials = self.options.disclose(credentials);
 
         let decryptor_remote_address = ResponderWorker::create(
             ctx,
@@ -89,6 +90,7 @@ impl Worker for IdentityChannelListener {
             access_control.decryptor_outgoing_access_control,
             credentials,
             self.options.trust_context.clone(),
+            self.options.curve,
         )
         .await?;
 
@@ -17,6 +17,7 @@ mod nonce_tracker;
 mod options;
 mod packets;
 mod registry;
+mod remote_route;
 mod responder_state;
 mod responder_worker;
 /// List of trust policies to setup ABAC controls
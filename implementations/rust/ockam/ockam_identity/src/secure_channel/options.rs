@@ -3,19 +3,33 @@ use crate::{Credential, TrustContext, TrustEveryonePolicy, TrustPolicy};
 use core::fmt;
 use core::fmt::Formatter;
 use core::time::Duration;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, OutgoingAccessControl, Result};
+pub use ockam_key_exchange_xx::XXCurve;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 
+fn disclose(names: &Option<Vec<String>>, credentials: Vec<Credential>) -> Vec<Credential> {
+    match names {
+        Some(names) => {
+            let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+            credentials.iter().map(|c| c.disclose(&names)).collect()
+        }
+        None => credentials,
+    }
+}
+
 /// Trust options for a Secure Channel
 pub struct SecureChannelOptions {
     pub(crate) flow_control_id: FlowControlId,
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<Credential>,
+    pub(crate) disclosed_attributes: Option<Vec<String>>,
+    pub(crate) curve: XXCurve,
     pub(crate) timeout: Duration,
 }
 
@@ -38,6 +52,8 @@ impl SecureChannelOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            disclosed_attributes: None,
+            curve: XXCurve::default(),
             timeout: DEFAULT_TIMEOUT,
         }
     }
@@ -60,6 +76,13 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Only reveal the given selectively disclosable attributes of the presented credentials.
+    /// By default all of them are revealed
+    pub fn with_disclosed_attributes(mut self, names: &[&str]) -> Self {
+        self.disclosed_attributes = Some(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
     /// Sets trust context
     pub fn with_trust_context(mut self, trust_context: TrustContext) -> Self {
         self.trust_context = Some(trust_context);
@@ -72,6 +95,13 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Use a different curve for the key exchange, for example [`XXCurve::P256`].
+    /// The listener must use the same curve
+    pub fn with_key_exchange_curve(mut self, curve: XXCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn producer_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -79,6 +109,10 @@ impl SecureChannelOptions {
 }
 
 impl SecureChannelOptions {
+    pub(crate) fn disclose(&self, credentials: Vec<Credential>) -> Vec<Credential> {
+        disclose(&self.disclosed_attributes, credentials)
+    }
+
     pub(crate) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
@@ -126,6 +160,8 @@ pub struct SecureChannelListenerOptions {
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<Credential>,
+    pub(crate) disclosed_attributes: Option<Vec<String>>,
+    pub(crate) curve: XXCurve,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -146,6 +182,8 @@ impl SecureChannelListenerOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            disclosed_attributes: None,
+            curve: XXCurve::default(),
         }
     }
 
@@ -170,6 +208,13 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Only reveal the given selectively disclosable attributes of the presented credentials.
+    /// By default all of them are revealed
+    pub fn with_disclosed_attributes(mut self, names: &[&str]) -> Self {
+        self.disclosed_attributes = Some(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
     /// Sets trust context
     pub fn with_trust_context(mut self, trust_context: TrustContext) -> Self {
         self.trust_context = Some(trust_context);
@@ -182,6 +227,13 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Use a different curve for the key exchange, for example [`XXCurve::P256`].
+    /// Initiators must use the same curve
+    pub fn with_key_exchange_curve(mut self, curve: XXCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -189,6 +241,10 @@ impl SecureChannelListenerOptions {
 }
 
 impl SecureChannelListenerOptions {
+    pub(crate) fn disclose(&self, credentials: Vec<Credential>) -> Vec<Credential> {
+        disclose(&self.disclosed_attributes, credentials)
+    }
+
     pub(crate) fn setup_flow_control_for_listener(
         &self,
         flow_controls: &FlowControls,
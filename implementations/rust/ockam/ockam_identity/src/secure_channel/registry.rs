@@ -1,8 +1,9 @@
 use crate::identity::{IdentityError, IdentityIdentifier};
+use crate::secure_channel::remote_route::RemoteRoute;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
-use ockam_core::{Address, Result};
+use ockam_core::{Address, Result, Route};
 
 /// Known information about particular SecureChannel
 #[derive(Clone, Debug)]
@@ -15,12 +16,13 @@ pub struct SecureChannelRegistryEntry {
     my_id: IdentityIdentifier,
     their_id: IdentityIdentifier,
     their_decryptor_address: Address,
+    remote_route: RemoteRoute,
 }
 
 impl SecureChannelRegistryEntry {
     /// Create new registry entry
     #[allow(clippy::too_many_arguments)]
-    pub fn new(
+    pub(crate) fn new(
         encryptor_messaging_address: Address,
         encryptor_api_address: Address,
         decryptor_messaging_address: Address,
@@ -29,6 +31,7 @@ impl SecureChannelRegistryEntry {
         my_id: IdentityIdentifier,
         their_id: IdentityIdentifier,
         their_decryptor_address: Address,
+        remote_route: RemoteRoute,
     ) -> Self {
         Self {
             encryptor_messaging_address,
@@ -39,6 +42,7 @@ impl SecureChannelRegistryEntry {
             my_id,
             their_id,
             their_decryptor_address,
+            remote_route,
         }
     }
 
@@ -80,6 +84,22 @@ impl SecureChannelRegistryEntry {
     pub fn their_decryptor_address(&self) -> Address {
         self.their_decryptor_address.clone()
     }
+
+    /// Route currently used to reach their `Decryptor`
+    pub fn remote_route(&self) -> Route {
+        self.remote_route.get()
+    }
+
+    /// Send the messages of this channel to their `Decryptor` over another route
+    ///
+    /// The route must end with their `Decryptor` address.
+    pub fn update_remote_route(&self, route: Route) -> Result<()> {
+        if route.recipient()? != self.their_decryptor_address {
+            return Err(IdentityError::InvalidSecureChannelRoute.into());
+        }
+        self.remote_route.set(route);
+        Ok(())
+    }
 }
 
 /// Registry of all known Secure Channels
@@ -0,0 +1,40 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, OutgoingAccessControl, RelayMessage, Result, Route};
+
+/// Route to the decryptor on the other side of a secure channel
+///
+/// Shared between the encryptor, which sends messages over it, and the
+/// registry, so that the channel can be moved to another route.
+#[derive(Clone, Debug)]
+pub(crate) struct RemoteRoute(Arc<RwLock<Route>>);
+
+impl RemoteRoute {
+    pub(crate) fn new(route: Route) -> Self {
+        Self(Arc::new(RwLock::new(route)))
+    }
+
+    pub(crate) fn get(&self) -> Route {
+        self.0.read().unwrap().clone()
+    }
+
+    pub(crate) fn set(&self, route: Route) {
+        *self.0.write().unwrap() = route;
+    }
+}
+
+/// Allows messages to the next hop of the current [`RemoteRoute`] to go through
+#[derive(Debug)]
+pub(crate) struct RemoteRouteAccessControl(pub(crate) RemoteRoute);
+
+#[async_trait]
+impl OutgoingAccessControl for RemoteRouteAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let remote_route = self.0.get();
+        if remote_route.next()? != relay_msg.onward_route().next()? {
+            return ockam_core::deny();
+        }
+
+        ockam_core::allow()
+    }
+}
@@ -14,7 +14,7 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 use ockam_core::{Address, Any, NewKeyExchanger, OutgoingAccessControl, Routed};
 use ockam_core::{Decodable, Worker};
-use ockam_key_exchange_xx::XXNewKeyExchanger;
+use ockam_key_exchange_xx::{XXCurve, XXNewKeyExchanger};
 use ockam_node::{Context, WorkerBuilder};
 use tracing::debug;
 
@@ -144,6 +144,7 @@ impl ResponderWorker {
         decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
         credentials: Vec<Credential>,
         trust_context: Option<TrustContext>,
+        curve: XXCurve,
     ) -> ockam_core::Result<Address> {
         let identity = secure_channels
             .identities
@@ -154,10 +155,11 @@ impl ResponderWorker {
         let (static_key_id, signature) = secure_channels
             .identities()
             .identities_keys()
-            .create_signed_static_key(&identity)
+            .create_signed_static_key(&identity, curve.secret_attributes())
             .await?;
 
         let key_exchanger = XXNewKeyExchanger::new(to_xx_vault(secure_channels.vault()))
+            .with_curve(curve)
             .responder(Some(static_key_id))
             .await?;
 
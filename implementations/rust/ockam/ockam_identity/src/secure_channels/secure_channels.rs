@@ -96,6 +96,7 @@ impl SecureChannels {
         let next = route.next()?;
         options.setup_flow_control(ctx.flow_controls(), &addresses, next)?;
         let access_control = options.create_access_control(ctx.flow_controls());
+        let credentials = options.disclose(options.credentials.clone());
 
         InitiatorWorker::create(
             ctx,
@@ -104,9 +105,10 @@ impl SecureChannels {
             identifier,
             options.trust_policy,
             access_control.decryptor_outgoing_access_control,
-            options.credentials,
+            credentials,
             options.trust_context,
             route,
+            options.curve,
             options.timeout,
         )
         .await?;
@@ -142,4 +144,18 @@ impl SecureChannels {
 
         Ok(())
     }
+
+    /// Move a SecureChannel, given its encryptor address, to another route
+    ///
+    /// The route must end with the decryptor address of the other side of the
+    /// channel. Only the messages sent by this side of the channel take the new route.
+    pub fn update_secure_channel_route(&self, channel: &Address, route: Route) -> Result<()> {
+        match self
+            .secure_channel_registry
+            .get_channel_by_encryptor_address(channel)
+        {
+            Some(entry) => entry.update_remote_route(route),
+            None => Err(IdentityError::SecureChannelNotFound.into()),
+        }
+    }
 }
@@ -7,9 +7,10 @@ use ockam_identity::{
     AuthorityService, CredentialData, DecryptionResponse, EncryptionRequest, EncryptionResponse,
     IdentityAccessControlBuilder, IdentityIdentifier, IdentitySecureChannelLocalInfo,
     SecureChannelListenerOptions, SecureChannelOptions, TrustContext, TrustEveryonePolicy,
-    TrustIdentifierPolicy,
+    TrustIdentifierPolicy, XXCurve,
 };
 use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
+use ockam_vault::SecretAttributes;
 use tokio::time::sleep;
 
 #[ockam_macros::test]
@@ -81,6 +82,72 @@ async fn test_channel(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_p256(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation
+        .create_identity_with_secret_attributes(SecretAttributes::NistP256)
+        .await?;
+    let bob = identities_creation
+        .create_identity_with_secret_attributes(SecretAttributes::NistP256)
+        .await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_key_exchange_curve(XXCurve::P256),
+        )
+        .await?;
+
+    // the initiator must use the same curve as the listener
+    assert!(secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_timeout(Duration::from_millis(500)),
+        )
+        .await
+        .is_err());
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_key_exchange_curve(XXCurve::P256),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+
+    child_ctx
+        .send(
+            route![alice_channel, child_ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(local_info.their_identity_id(), alice.identifier());
+    assert_eq!("Hello, Bob!", msg.body());
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_send_credentials(context: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -209,6 +276,95 @@ async fn test_channel_send_credentials(context: &mut Context) -> Result<()> {
     context.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_send_selectively_disclosed_credential(context: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().identities_reader(),
+            secure_channels.identities().credentials(),
+            authority.identifier(),
+            None,
+        )),
+    );
+
+    let bob_credential = secure_channels
+        .identities()
+        .credentials()
+        .issue_credential(
+            &authority.identifier(),
+            CredentialData::builder(bob.identifier(), authority.identifier())
+                .with_attribute("is_bob", b"true")
+                .build()?,
+        )
+        .await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            context,
+            &bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_trust_context(trust_context.clone())
+                .with_credential(bob_credential),
+        )
+        .await?;
+
+    let alice_credential = secure_channels
+        .identities()
+        .credentials()
+        .issue_credential_with_disclosures(
+            &authority.identifier(),
+            CredentialData::builder(alice.identifier(), authority.identifier())
+                .with_attribute("project", b"p1")
+                .with_attribute("role", b"admin")
+                .with_attribute("email", b"alice@example.com")
+                .build()?,
+            &["role", "email"],
+        )
+        .await?;
+
+    let _alice_channel = secure_channels
+        .create_secure_channel(
+            context,
+            &alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential)
+                .with_disclosed_attributes(&["role"]),
+        )
+        .await?;
+
+    context.sleep(Duration::from_millis(100)).await;
+
+    let alice_attributes = secure_channels
+        .identities()
+        .repository()
+        .get_attributes(&alice.identifier())
+        .await?
+        .unwrap();
+
+    assert_eq!(
+        "p1".as_bytes(),
+        alice_attributes.attrs().get("project").unwrap()
+    );
+    assert_eq!(
+        "admin".as_bytes(),
+        alice_attributes.attrs().get("role").unwrap()
+    );
+    assert!(alice_attributes.attrs().get("email").is_none());
+
+    context.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_rejected_trust_policy(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -401,6 +557,98 @@ async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_route_update(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    let mut bob_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "bob",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("bob", bob_listener.flow_control_id());
+
+    let forwarded_count = Arc::new(AtomicU8::new(0));
+    ctx.start_worker(
+        "hop",
+        CountingHop {
+            forwarded_count: forwarded_count.clone(),
+        },
+    )
+    .await?;
+
+    let alice_channel_data = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    let their_decryptor = alice_channel_data.their_decryptor_address();
+    assert_eq!(
+        alice_channel_data.remote_route(),
+        route![their_decryptor.clone()]
+    );
+
+    // The route must lead to the other side of the channel
+    assert!(secure_channels
+        .update_secure_channel_route(alice_channel.encryptor_address(), route!["hop", "bob"])
+        .is_err());
+
+    // Move the channel to a route going through the hop
+    secure_channels.update_secure_channel_route(
+        alice_channel.encryptor_address(),
+        route!["hop", their_decryptor.clone()],
+    )?;
+    assert_eq!(
+        alice_channel_data.remote_route(),
+        route!["hop", their_decryptor]
+    );
+
+    ctx.send(
+        route![alice_channel.clone(), "bob"],
+        "Hello, Bob!".to_string(),
+    )
+    .await?;
+    let msg = bob_ctx.receive::<String>().await?;
+    let return_route = msg.return_route();
+    assert_eq!("Hello, Bob!", msg.body());
+    assert_eq!(forwarded_count.load(Ordering::Relaxed), 1);
+
+    // Only alice's side of the channel was moved
+    ctx.flow_controls()
+        .add_consumer(ctx.address(), alice_channel.flow_control_id());
+    bob_ctx
+        .send(return_route, "Hello, Alice!".to_string())
+        .await?;
+    assert_eq!("Hello, Alice!", ctx.receive::<String>().await?.body());
+    assert_eq!(forwarded_count.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_api(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -785,6 +1033,33 @@ impl Worker for Receiver {
     }
 }
 
+struct CountingHop {
+    forwarded_count: Arc<AtomicU8>,
+}
+
+#[ockam_core::async_trait]
+impl Worker for CountingHop {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        context: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        self.forwarded_count.fetch_add(1, Ordering::Relaxed);
+
+        let mut msg = msg.into_local_message();
+        let transport_message = msg.transport_mut();
+        transport_message.onward_route.step()?;
+        transport_message
+            .return_route
+            .modify()
+            .prepend(context.address());
+        context.forward(msg).await
+    }
+}
+
 #[allow(non_snake_case)]
 #[ockam_macros::test]
 async fn access_control__known_participant__should_pass_messages(ctx: &mut Context) -> Result<()> {
@@ -6,8 +6,9 @@ use ockam_core::{async_trait, Any, DenyAll};
 use ockam_core::{route, Result, Routed, Worker};
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::{
-    AuthorityService, CredentialAccessControl, CredentialData, CredentialsMemoryRetriever,
-    SecureChannelListenerOptions, SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
+    identities, AttributeValue, AuthorityService, CredentialAccessControl, CredentialData,
+    CredentialsMemoryRetriever, DisclosedValue, Disclosure, SecureChannelListenerOptions,
+    SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
 };
 use ockam_node::{Context, WorkerBuilder};
 
@@ -282,6 +283,218 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn access_control_with_multi_valued_attribute(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let identities_repository = identities.repository();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let options = SecureChannelListenerOptions::new();
+    let listener = secure_channels
+        .create_secure_channel_listener(ctx, &server.identifier(), "listener", options)
+        .await?;
+
+    let trust_context = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(AuthorityService::new(
+            identities.identities_reader(),
+            credentials.clone(),
+            authority.identifier(),
+            None,
+        )),
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.identifier(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(server.identifier().clone())),
+        )
+        .await?;
+
+    let credential_data = CredentialData::builder(client.identifier(), authority.identifier())
+        .with_attribute_value("groups", vec!["admins", "devs"])
+        .build()?;
+    let credential = credentials
+        .issue_credential(&authority.identifier(), credential_data)
+        .await?;
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel.clone(), "credential_exchange"],
+            credential,
+        )
+        .await?;
+
+    let attrs = identities_repository
+        .get_attributes(&client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        attrs.value("groups"),
+        Some(&AttributeValue::from(vec!["admins", "devs"]))
+    );
+
+    let counter = Arc::new(AtomicI8::new(0));
+    let devs_only = CredentialAccessControl::new(
+        &[("groups".to_string(), b"devs".to_vec())],
+        identities_repository.clone(),
+    );
+    let ops_only = CredentialAccessControl::new(
+        &[("groups".to_string(), b"ops".to_vec())],
+        identities_repository.clone(),
+    );
+    for (address, access_control) in [("devs", devs_only), ("ops", ops_only)] {
+        ctx.flow_controls()
+            .add_consumer(address, listener.flow_control_id());
+        WorkerBuilder::new(CountingWorker {
+            msgs_count: counter.clone(),
+        })
+        .with_address(address)
+        .with_incoming_access_control(access_control)
+        .with_outgoing_access_control(DenyAll)
+        .start(ctx)
+        .await?;
+    }
+
+    ctx.send(route![channel.clone(), "ops"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+    ctx.send(route![channel, "devs"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn selective_disclosure(ctx: &mut Context) -> Result<()> {
+    let identities = identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let credential_data = CredentialData::builder(client.identifier(), authority.identifier())
+        .with_attribute("project", b"p1")
+        .with_attribute("role", b"admin")
+        .with_attribute_value("groups", vec!["admins", "devs"])
+        .build()?;
+    let credential = credentials
+        .issue_credential_with_disclosures(
+            &authority.identifier(),
+            credential_data,
+            &["role", "groups"],
+        )
+        .await?;
+    let mut disclosable = credential.disclosable_attributes();
+    disclosable.sort();
+    assert_eq!(disclosable, vec!["groups", "role"]);
+
+    // the issuer signature is still valid when only some attributes are disclosed
+    let authorities = [authority.clone()];
+    let data = credentials
+        .verify_credential(
+            &client.identifier(),
+            &authorities,
+            credential.disclose(&["groups"]),
+        )
+        .await?;
+    assert_eq!(data.attributes().get("project"), Some(&b"p1"[..]));
+    assert_eq!(
+        data.attributes().get_value("groups"),
+        Some(&AttributeValue::from(vec!["admins", "devs"]))
+    );
+    assert!(data.attributes().get("role").is_none());
+
+    let data = credentials
+        .verify_credential(&client.identifier(), &authorities, credential.disclose(&[]))
+        .await?;
+    assert_eq!(data.attributes().len(), 1);
+
+    // a disclosure which was not signed by the issuer is rejected
+    let mut tampered = credential.disclose(&[]);
+    tampered.disclosures = Some(vec![Disclosure::new(
+        "role",
+        DisclosedValue::Plain(b"superuser".to_vec().into()),
+    )
+    .encode()?
+    .into()]);
+    assert!(credentials
+        .verify_credential(&client.identifier(), &authorities, tampered)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn verified_credentials_are_cached(ctx: &mut Context) -> Result<()> {
+    let identities = identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+    let other = identities_creation.create_identity().await?;
+
+    let credential_data = CredentialData::builder(client.identifier(), authority.identifier())
+        .with_attribute("is_superuser", b"true")
+        .build()?;
+    let credential = credentials
+        .issue_credential(&authority.identifier(), credential_data)
+        .await?;
+
+    let authorities = [authority.clone()];
+    for _ in 0..3 {
+        credentials
+            .verify_credential(&client.identifier(), &authorities, credential.clone())
+            .await?;
+    }
+    let cache = identities.credentials_cache();
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.stats().hits, 2);
+    assert_eq!(cache.stats().misses, 1);
+
+    // a cached credential is still checked against its subject and its issuer
+    assert!(credentials
+        .verify_credential(&other.identifier(), &authorities, credential.clone())
+        .await
+        .is_err());
+    assert!(credentials
+        .verify_credential(&client.identifier(), &[other], credential)
+        .await
+        .is_err());
+    assert_eq!(cache.stats().hits, 2);
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
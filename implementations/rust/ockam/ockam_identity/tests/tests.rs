@@ -1,6 +1,6 @@
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_identity::identities;
+use ockam_identity::{identities, IdentityBundle};
 use ockam_node::Context;
 use ockam_vault::SecretAttributes;
 use rand::{thread_rng, RngCore};
@@ -80,6 +80,29 @@ async fn test_key_rotation(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn test_key_rotation_p256(ctx: &mut Context) -> Result<()> {
+    let identities = identities();
+    let identities_creation = identities.identities_creation();
+    let identities_keys = identities.identities_keys();
+
+    let mut alice = identities_creation
+        .create_identity_with_secret_attributes(SecretAttributes::NistP256)
+        .await?;
+    identities_keys.rotate_root_key(&mut alice).await?;
+
+    // the rotated key keeps the type of the original root key
+    let key_id = identities_keys.get_secret_key(&alice, None).await?;
+    let attributes = identities.vault().get_secret_attributes(&key_id).await?;
+    assert_eq!(attributes, SecretAttributes::NistP256);
+
+    identities_keys.verify_changes(&alice).await?;
+
+    ctx.stop().await?;
+
+    Ok(())
+}
+
 #[ockam_macros::test]
 async fn test_update_contact_and_reprove(ctx: &mut Context) -> Result<()> {
     let identities = identities();
@@ -157,6 +180,86 @@ async fn add_key(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn export_and_import_identity_bundle(ctx: &mut Context) -> Result<()> {
+    let identities1 = identities();
+    let mut alice = identities1.identities_creation().create_identity().await?;
+    identities1
+        .identities_keys()
+        .rotate_root_key(&mut alice)
+        .await?;
+
+    let bundle = identities1
+        .identities_creation()
+        .export_identity_bundle(&alice, Some("password"))
+        .await?;
+    let bundle = IdentityBundle::import(&bundle.export()?)?;
+    assert!(bundle.has_secrets());
+
+    // the secrets can't be decrypted without the right password
+    let identities2 = identities();
+    assert!(identities2
+        .identities_creation()
+        .import_identity_bundle(&bundle, Some("wrong password"))
+        .await
+        .is_err());
+    assert!(identities2
+        .identities_creation()
+        .import_identity_bundle(&bundle, None)
+        .await
+        .is_err());
+    // a failed import leaves nothing behind
+    assert!(identities2
+        .repository()
+        .retrieve_identity(&alice.identifier())
+        .await?
+        .is_none());
+
+    // the imported identity can sign on a different vault
+    let imported = identities2
+        .identities_creation()
+        .import_identity_bundle(&bundle, Some("password"))
+        .await?;
+    assert_eq!(imported.identifier(), alice.identifier());
+    assert_eq!(
+        identities2
+            .repository()
+            .get_identity(&alice.identifier())
+            .await?,
+        imported
+    );
+    let proof = identities2
+        .identities_keys()
+        .create_signature(&imported, b"data", None)
+        .await?;
+    if !identities1
+        .identities_keys()
+        .verify_signature(&alice, &proof, b"data", None)
+        .await?
+    {
+        return test_error("the imported identity proof was invalid");
+    }
+
+    // a bundle without secrets only imports the public identity
+    let public_bundle = identities1
+        .identities_creation()
+        .export_identity_bundle(&alice, None)
+        .await?;
+    assert!(!public_bundle.has_secrets());
+    let identities3 = identities();
+    let imported = identities3
+        .identities_creation()
+        .import_identity_bundle(&public_bundle, None)
+        .await?;
+    assert!(identities3
+        .identities_keys()
+        .create_signature(&imported, b"data", None)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
 fn test_error<S: Into<String>>(error: S) -> Result<()> {
     Err(Error::new_without_cause(Origin::Identity, Kind::Unknown).context("msg", error.into()))
 }
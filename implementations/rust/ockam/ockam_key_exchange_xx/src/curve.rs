@@ -0,0 +1,84 @@
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_vault::constants::{CURVE25519_PUBLIC_LENGTH_USIZE, NISTP256_PUBLIC_LENGTH_USIZE};
+use ockam_vault::{PublicKey, SecretAttributes, SecretType};
+
+use crate::XXError;
+
+/// DER prefix of the SubjectPublicKeyInfo of a P-256 public key with an uncompressed point
+const NISTP256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+/// Elliptic curve used for the Diffie-Hellman operations of the XX handshake.
+///
+/// Both sides of a handshake must use the same curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XXCurve {
+    /// `Noise_XX_25519_AESGCM_SHA256`
+    Curve25519,
+    /// `Noise_XX_P256_AESGCM_SHA256`, for deployments restricted to NIST curves.
+    /// Public keys are exchanged as uncompressed SEC1 points
+    P256,
+}
+
+impl Default for XXCurve {
+    fn default() -> Self {
+        XXCurve::Curve25519
+    }
+}
+
+impl XXCurve {
+    /// Noise protocol name, padded with zeros to the size of a SHA256 digest
+    pub fn protocol_name(&self) -> &'static [u8; 32] {
+        match self {
+            XXCurve::Curve25519 => b"Noise_XX_25519_AESGCM_SHA256\0\0\0\0",
+            XXCurve::P256 => b"Noise_XX_P256_AESGCM_SHA256\0\0\0\0\0",
+        }
+    }
+
+    /// Attributes of the static and ephemeral secrets
+    pub fn secret_attributes(&self) -> SecretAttributes {
+        match self {
+            XXCurve::Curve25519 => SecretAttributes::X25519,
+            XXCurve::P256 => SecretAttributes::NistP256,
+        }
+    }
+
+    /// Type of the static and ephemeral secrets
+    pub fn secret_type(&self) -> SecretType {
+        self.secret_attributes().secret_type()
+    }
+
+    /// Size of a public key sent during the handshake
+    pub fn public_key_length(&self) -> usize {
+        match self {
+            XXCurve::Curve25519 => CURVE25519_PUBLIC_LENGTH_USIZE,
+            XXCurve::P256 => NISTP256_PUBLIC_LENGTH_USIZE,
+        }
+    }
+
+    /// Encode a vault public key as it is sent during the handshake
+    pub(crate) fn encode_public_key(&self, public_key: &PublicKey) -> Result<Vec<u8>> {
+        match self {
+            XXCurve::Curve25519 => Ok(public_key.data().to_vec()),
+            XXCurve::P256 => match public_key.data().strip_prefix(&NISTP256_SPKI_PREFIX[..]) {
+                Some(point) if point.len() == NISTP256_PUBLIC_LENGTH_USIZE => Ok(point.to_vec()),
+                _ => Err(XXError::InternalVaultError.into()),
+            },
+        }
+    }
+
+    /// Decode a public key received during the handshake into a vault public key
+    pub(crate) fn decode_public_key(&self, data: &[u8]) -> Result<PublicKey> {
+        if data.len() != self.public_key_length() {
+            return Err(XXError::MessageLenMismatch.into());
+        }
+        let data = match self {
+            XXCurve::Curve25519 => data.to_vec(),
+            XXCurve::P256 => [&NISTP256_SPKI_PREFIX[..], data].concat(),
+        };
+        Ok(PublicKey::new(data, self.secret_type()))
+    }
+}
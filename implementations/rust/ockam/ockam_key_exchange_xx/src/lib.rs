@@ -57,6 +57,8 @@ impl<D> XXInitializedVault for D where
 {
 }
 
+mod curve;
+pub use curve::*;
 mod initiator;
 mod state;
 pub use initiator::*;
@@ -129,4 +131,62 @@ mod tests {
 
         ctx.stop().await
     }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn full_flow__p256__keys_should_match(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let key_exchanger = XXNewKeyExchanger::new(vault.clone()).with_curve(XXCurve::P256);
+
+        let mut initiator = key_exchanger.initiator(None).await?;
+        let mut responder = key_exchanger.responder(None).await?;
+
+        let m1 = initiator.generate_request(b"1").await?;
+        // the ephemeral key is sent as an uncompressed point
+        assert_eq!(m1.len(), 65 + 1);
+        assert_eq!(responder.handle_response(&m1).await?, b"1");
+        let m2 = responder.generate_request(b"2").await?;
+        assert_eq!(initiator.handle_response(&m2).await?, b"2");
+        let m3 = initiator.generate_request(b"3").await?;
+        assert_eq!(responder.handle_response(&m3).await?, b"3");
+        assert!(initiator.is_complete().await? && responder.is_complete().await?);
+
+        let initiator = initiator.finalize().await?;
+        let responder = responder.finalize().await?;
+        assert_eq!(initiator.h(), responder.h());
+
+        let s1 = vault
+            .get_ephemeral_secret(initiator.encrypt_key(), "encrypt key")
+            .await?;
+        let s2 = vault
+            .get_ephemeral_secret(responder.decrypt_key(), "decrypt key")
+            .await?;
+        assert_eq!(s1, s2);
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn full_flow__different_curves__should_fail(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let mut initiator = XXNewKeyExchanger::new(vault.clone())
+            .initiator(None)
+            .await?;
+        let mut responder = XXNewKeyExchanger::new(vault.clone())
+            .with_curve(XXCurve::P256)
+            .responder(None)
+            .await?;
+
+        let m1 = initiator.generate_request(&[]).await?;
+        let result = match responder.handle_response(&m1).await {
+            Ok(_) => responder.generate_request(&[]).await,
+            Err(e) => Err(e),
+        };
+        assert!(result.is_err());
+
+        ctx.stop().await
+    }
 }
@@ -1,5 +1,5 @@
 use crate::state::State;
-use crate::{Initiator, Responder, XXVault};
+use crate::{Initiator, Responder, XXCurve, XXVault};
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, compat::boxed::Box, KeyId, Result};
 
@@ -8,12 +8,23 @@ use ockam_core::NewKeyExchanger;
 /// Represents an XX NewKeyExchanger
 pub struct XXNewKeyExchanger {
     vault: Arc<dyn XXVault>,
+    curve: XXCurve,
 }
 
 impl XXNewKeyExchanger {
-    /// Create a new XXNewKeyExchanger
+    /// Create a new XXNewKeyExchanger using Curve25519
     pub fn new(vault: Arc<dyn XXVault>) -> Self {
-        Self { vault }
+        Self {
+            vault,
+            curve: XXCurve::default(),
+        }
+    }
+
+    /// Use a different curve for the Diffie-Hellman operations.
+    /// A static key passed to the initiator or the responder must be a secret on that curve
+    pub fn with_curve(mut self, curve: XXCurve) -> Self {
+        self.curve = curve;
+        self
     }
 }
 
@@ -24,13 +35,13 @@ impl NewKeyExchanger for XXNewKeyExchanger {
 
     /// Create a new initiator using the provided backing vault
     async fn initiator(&self, key_id: Option<KeyId>) -> Result<Initiator> {
-        let ss = State::new(self.vault.clone(), key_id).await?;
+        let ss = State::new(self.vault.clone(), key_id, self.curve).await?;
         Ok(Initiator::new(ss))
     }
 
     /// Create a new responder using the provided backing vault
     async fn responder(&self, key_id: Option<KeyId>) -> Result<Responder> {
-        let ss = State::new(self.vault.clone(), key_id).await?;
+        let ss = State::new(self.vault.clone(), key_id, self.curve).await?;
         Ok(Responder::new(ss))
     }
 }
@@ -1,12 +1,11 @@
-use crate::{XXError, XXVault, AES_GCM_TAGSIZE_USIZE, SHA256_SIZE_USIZE};
+use crate::{XXCurve, XXError, XXVault, AES_GCM_TAGSIZE_USIZE, SHA256_SIZE_USIZE};
 use ockam_core::compat::sync::Arc;
 use ockam_core::{compat::vec::Vec, Result};
 use ockam_core::{CompletedKeyExchange, KeyId};
-use ockam_vault::{PublicKey, SecretAttributes, SecretType, Vault};
+use ockam_vault::{PublicKey, SecretAttributes, Vault};
 
 mod dh_state;
 pub(crate) use dh_state::*;
-use ockam_vault::constants::CURVE25519_PUBLIC_LENGTH_USIZE;
 
 /// Represents the XX Handshake
 #[derive(Clone)]
@@ -21,6 +20,7 @@ pub(crate) struct State {
     dh_state: DhState,
     nonce: u16,
     h: Option<[u8; SHA256_SIZE_USIZE]>,
+    curve: XXCurve,
     vault: Arc<dyn XXVault>,
 }
 
@@ -38,7 +38,11 @@ impl core::fmt::Debug for State {
 }
 
 impl State {
-    pub(crate) async fn new(vault: Arc<dyn XXVault>, key_id: Option<KeyId>) -> Result<Self> {
+    pub(crate) async fn new(
+        vault: Arc<dyn XXVault>,
+        key_id: Option<KeyId>,
+        curve: XXCurve,
+    ) -> Result<Self> {
         Ok(Self {
             run_prologue: true,
             identity_key: key_id,
@@ -50,6 +54,7 @@ impl State {
             dh_state: DhState::empty(vault.clone()),
             nonce: 0,
             h: None,
+            curve,
             vault: vault.clone(),
         })
     }
@@ -61,12 +66,12 @@ impl State {
     }
 
     fn get_protocol_name(&self) -> &'static [u8] {
-        b"Noise_XX_25519_AESGCM_SHA256\0\0\0\0"
+        self.curve.protocol_name()
     }
 
     /// Create a new `HandshakeState` starting with the prologue
     async fn prologue(&mut self) -> Result<()> {
-        let attributes = SecretAttributes::X25519;
+        let attributes = self.curve.secret_attributes();
         // 1. Generate a static key pair for this handshake and set it to `s`
         if let Some(ik) = &self.identity_key {
             self.identity_public_key = Some(self.vault.get_public_key(ik).await?);
@@ -204,17 +209,17 @@ impl State {
             .clone();
 
         let payload = payload.as_ref();
-        self.h = Some(self.mix_hash(ephemeral_public_key.data()).await?);
+        let mut output = self.curve.encode_public_key(&ephemeral_public_key)?;
+        self.h = Some(self.mix_hash(&output).await?);
         self.h = Some(self.mix_hash(payload).await?);
 
-        let mut output = ephemeral_public_key.data().to_vec();
         output.extend_from_slice(payload);
         Ok(output)
     }
 
     /// Decode the second message in the sequence, sent from the responder
     pub(crate) async fn decode_message_2<B: AsRef<[u8]>>(&mut self, message: B) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let public_key_size = self.curve.public_key_length();
         let message = message.as_ref();
         if message.len() < 2 * public_key_size + AES_GCM_TAGSIZE_USIZE {
             return Err(XXError::MessageLenMismatch.into());
@@ -224,19 +229,19 @@ impl State {
 
         let mut index_l = 0;
         let mut index_r = public_key_size;
-        let re = &message[..index_r];
-        let re = PublicKey::new(re.to_vec(), SecretType::X25519);
+        let re_bytes = &message[..index_r];
+        let re = self.curve.decode_public_key(re_bytes)?;
         index_l += public_key_size;
         index_r += public_key_size + AES_GCM_TAGSIZE_USIZE;
         let encrypted_rs_and_tag = &message[index_l..index_r];
         let encrypted_payload_and_tag = &message[index_r..];
 
-        self.h = Some(self.mix_hash(re.data()).await?);
+        self.h = Some(self.mix_hash(re_bytes).await?);
         self.dh_state.dh(&ephemeral_secret_handle, &re).await?;
         self.remote_ephemeral_public_key = Some(re);
         let (rs, h) = self.decrypt_and_mix_hash(encrypted_rs_and_tag).await?;
         self.h = Some(h);
-        let rs = PublicKey::new(rs, SecretType::X25519);
+        let rs = self.curve.decode_public_key(&rs)?;
         self.dh_state.dh(&ephemeral_secret_handle, &rs).await?;
         self.remote_static_public_key = Some(rs);
         self.nonce = 0;
@@ -261,7 +266,8 @@ impl State {
             .clone()
             .ok_or(XXError::InvalidState)?;
 
-        let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(static_public.data()).await?;
+        let static_public = self.curve.encode_public_key(&static_public)?;
+        let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(&static_public).await?;
         self.h = Some(h);
         self.dh_state
             .dh(&static_secret, &remote_ephemeral_public_key)
@@ -292,15 +298,15 @@ impl State {
         &mut self,
         message_1: B,
     ) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let public_key_size = self.curve.public_key_length();
         let message_1 = message_1.as_ref();
         if message_1.len() < public_key_size {
             return Err(XXError::MessageLenMismatch.into());
         }
 
-        let re = &message_1[..public_key_size];
-        let re = PublicKey::new(re.to_vec(), SecretType::X25519);
-        self.h = Some(self.mix_hash(re.data()).await?);
+        let re_bytes = &message_1[..public_key_size];
+        let re = self.curve.decode_public_key(re_bytes)?;
+        self.h = Some(self.mix_hash(re_bytes).await?);
         self.h = Some(self.mix_hash(&message_1[public_key_size..]).await?);
         self.remote_ephemeral_public_key = Some(re);
         Ok(message_1[public_key_size..].to_vec())
@@ -320,12 +326,14 @@ impl State {
             .clone()
             .ok_or(XXError::InvalidState)?;
 
-        self.h = Some(self.mix_hash(ephemeral_public.data()).await?);
+        let mut output = self.curve.encode_public_key(&ephemeral_public)?;
+        self.h = Some(self.mix_hash(&output).await?);
         self.dh_state
             .dh(&ephemeral_secret, &remote_ephemeral_public_key)
             .await?;
 
-        let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(static_public.data()).await?;
+        let static_public = self.curve.encode_public_key(&static_public)?;
+        let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(&static_public).await?;
         self.h = Some(h);
         self.dh_state
             .dh(&static_secret, &remote_ephemeral_public_key)
@@ -335,7 +343,6 @@ impl State {
         self.h = Some(h);
         self.nonce += 1;
 
-        let mut output = ephemeral_public.data().to_vec();
         output.append(&mut encrypted_s_and_tag);
         output.append(&mut encrypted_payload_and_tag);
         Ok(output)
@@ -346,7 +353,7 @@ impl State {
         &mut self,
         message_3: B,
     ) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let public_key_size = self.curve.public_key_length();
         let message_3 = message_3.as_ref();
         if message_3.len() < public_key_size + AES_GCM_TAGSIZE_USIZE {
             return Err(XXError::MessageLenMismatch.into());
@@ -358,7 +365,7 @@ impl State {
             .decrypt_and_mix_hash(&message_3[..public_key_size + AES_GCM_TAGSIZE_USIZE])
             .await?;
         self.h = Some(h);
-        let rs = PublicKey::new(rs, SecretType::X25519);
+        let rs = self.curve.decode_public_key(&rs)?;
         self.dh_state.dh(ephemeral_secret, &rs).await?;
         self.nonce = 0;
         let (payload, h) = self
@@ -384,7 +391,7 @@ impl State {
 #[cfg(test)]
 mod tests {
     use crate::state::{DhState, State};
-    use crate::{Initiator, Responder, XXVault};
+    use crate::{Initiator, Responder, XXCurve, XXVault};
     use hex::{decode, encode};
     use ockam_core::compat::sync::Arc;
     use ockam_core::Result;
@@ -403,7 +410,9 @@ mod tests {
         ];
 
         let vault: Arc<dyn XXVault> = vault;
-        let mut state = State::new(vault.clone(), None).await.unwrap();
+        let mut state = State::new(vault.clone(), None, XXCurve::Curve25519)
+            .await
+            .unwrap();
         let res = state.prologue().await;
         assert!(res.is_ok());
         assert_eq!(state.h.unwrap(), exp_h);
@@ -686,6 +695,7 @@ mod tests {
             },
             nonce: 0,
             h: Some(h),
+            curve: XXCurve::Curve25519,
             vault: vault.async_try_clone().await.unwrap(),
         }
     }
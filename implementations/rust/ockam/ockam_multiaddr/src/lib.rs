@@ -44,7 +44,7 @@ fn default_registry() -> &'static Registry {
 ///
 /// ```text
 /// Protocol <- Text / Binary
-/// Text     <- '/' Prefix '/' Char+
+/// Text     <- '/' Prefix ('/' Char+)?
 /// Prefix   <- Char+
 /// Binary   <- Code Byte+
 /// Code     <- UnsignedVarint
@@ -53,9 +53,12 @@ fn default_registry() -> &'static Registry {
 /// To process a protocol, one needs to know the code and prefix as they
 /// determine the protocol value.
 ///
+/// Some protocols, like `/ws`, have no value and consist of the prefix only.
+///
 /// NB: Protocol values which contain '/'s create ambiguity in the textual
 /// representation. These so called "path protocols" must be the last
-/// protocol component in a multi-address.
+/// protocol component in a multi-address, unless they encode their '/'s
+/// (see [`proto::Unix`]).
 pub trait Protocol<'a>: Sized {
     /// Registered protocol code.
     const CODE: Code;
@@ -1,6 +1,8 @@
 use super::{Buffer, Checked, Code, Protocol};
 use crate::Error;
 use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 use core::ops::Deref;
 use core::str::{self, FromStr};
@@ -153,6 +155,179 @@ impl Protocol<'_> for Tcp {
     }
 }
 
+/// A UDP port number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Udp(pub u16);
+
+impl Udp {
+    pub fn new(v: u16) -> Self {
+        Udp(v)
+    }
+}
+
+impl Deref for Udp {
+    type Target = u16;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Protocol<'_> for Udp {
+    const CODE: Code = Code::new(273);
+    const PREFIX: &'static str = "udp";
+
+    fn read_str(input: Checked<&str>) -> Result<Self, Error> {
+        u16::from_str(&input).map(Udp).map_err(Error::message)
+    }
+
+    fn read_bytes(input: Checked<&[u8]>) -> Result<Self, Error> {
+        let mut b = [0; 2];
+        b.copy_from_slice(&input);
+        Ok(Udp(u16::from_be_bytes(b)))
+    }
+
+    fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+        write!(f, "/{}/{}", Self::PREFIX, self.0)?;
+        Ok(())
+    }
+
+    fn write_bytes(&self, buf: &mut dyn Buffer) {
+        let mut b = encode::u32_buffer();
+        let uvi = encode::u32(Self::CODE.into(), &mut b);
+        buf.extend_with(uvi);
+        buf.extend_with(&self.0.to_be_bytes())
+    }
+}
+
+/// A Unix domain socket path.
+///
+/// In the textual representation '%' and '/' are percent-encoded, so that
+/// a path can be followed by other protocols, e.g.
+/// `/unix/%2Ftmp%2Fnode.sock/service/api`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unix<'a>(Cow<'a, str>);
+
+impl<'a> Unix<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(s: S) -> Self {
+        Self(s.into())
+    }
+}
+
+impl Deref for Unix<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> Protocol<'a> for Unix<'a> {
+    const CODE: Code = Code::new(400);
+    const PREFIX: &'static str = "unix";
+
+    fn read_str(input: Checked<&'a str>) -> Result<Self, Error> {
+        if input.is_empty() {
+            return Err(Error::message("empty unix socket path"));
+        }
+        if !input.contains('%') {
+            return Ok(Self(Cow::Borrowed(input.0)));
+        }
+        let mut path = Vec::with_capacity(input.len());
+        let mut bytes = input.as_bytes().iter();
+        while let Some(b) = bytes.next() {
+            if *b != b'%' {
+                path.push(*b);
+                continue;
+            }
+            let hex = [
+                *bytes
+                    .next()
+                    .ok_or_else(|| Error::message("truncated escape"))?,
+                *bytes
+                    .next()
+                    .ok_or_else(|| Error::message("truncated escape"))?,
+            ];
+            let hex = str::from_utf8(&hex).map_err(Error::message)?;
+            path.push(u8::from_str_radix(hex, 16).map_err(Error::message)?)
+        }
+        let path = String::from_utf8(path).map_err(Error::message)?;
+        Ok(Self(Cow::Owned(path)))
+    }
+
+    fn read_bytes(input: Checked<&'a [u8]>) -> Result<Self, Error> {
+        if input.is_empty() {
+            return Err(Error::message("empty unix socket path"));
+        }
+        let s = str::from_utf8(&input).map_err(Error::message)?;
+        Ok(Self(Cow::Borrowed(s)))
+    }
+
+    fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+        write!(f, "/{}/", Self::PREFIX)?;
+        for c in self.0.chars() {
+            match c {
+                '%' => f.write_str("%25")?,
+                '/' => f.write_str("%2F")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&self, buf: &mut dyn Buffer) {
+        let mut b = encode::u32_buffer();
+        let uvi = encode::u32(Self::CODE.into(), &mut b);
+        buf.extend_with(uvi);
+        let mut b = encode::usize_buffer();
+        let uvi = encode::usize(self.0.len(), &mut b);
+        buf.extend_with(uvi);
+        buf.extend_with(self.0.as_bytes())
+    }
+}
+
+macro_rules! gen_unit_proto {
+    ($t:ident, $c:literal, $p:literal) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $t;
+
+        impl Protocol<'_> for $t {
+            const CODE: Code = Code::new($c);
+            const PREFIX: &'static str = $p;
+
+            fn read_str(input: Checked<&str>) -> Result<Self, Error> {
+                if input.is_empty() {
+                    Ok($t)
+                } else {
+                    Err(Error::message(concat!("/", $p, " does not take a value")))
+                }
+            }
+
+            fn read_bytes(input: Checked<&[u8]>) -> Result<Self, Error> {
+                if input.is_empty() {
+                    Ok($t)
+                } else {
+                    Err(Error::message(concat!("/", $p, " does not take a value")))
+                }
+            }
+
+            fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+                write!(f, "/{}", Self::PREFIX)?;
+                Ok(())
+            }
+
+            fn write_bytes(&self, buf: &mut dyn Buffer) {
+                let mut b = encode::u32_buffer();
+                let uvi = encode::u32(Self::CODE.into(), &mut b);
+                buf.extend_with(uvi);
+            }
+        }
+    };
+}
+
+gen_unit_proto!(Ws, 477, "ws");
+gen_unit_proto!(Wss, 478, "wss");
+
 macro_rules! gen_str_proto {
     ($t:ident, $c:literal, $p:literal) => {
         #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1,6 +1,8 @@
 use super::{Code, Codec, Protocol};
 use crate::codec::StdCodec;
-use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker};
+use crate::proto::{
+    DnsAddr, Node, Project, Secure, Service, Space, Tcp, Udp, Unix, Worker, Ws, Wss,
+};
 use alloc::collections::btree_map::BTreeMap;
 use alloc::sync::Arc;
 use core::fmt;
@@ -27,6 +29,10 @@ impl Default for Registry {
         let mut r = RegistryBuilder::new();
         r.register(Worker::CODE, Worker::PREFIX, std_codec.clone());
         r.register(Tcp::CODE, Tcp::PREFIX, std_codec.clone());
+        r.register(Udp::CODE, Udp::PREFIX, std_codec.clone());
+        r.register(Ws::CODE, Ws::PREFIX, std_codec.clone());
+        r.register(Wss::CODE, Wss::PREFIX, std_codec.clone());
+        r.register(Unix::CODE, Unix::PREFIX, std_codec.clone());
         r.register(DnsAddr::CODE, DnsAddr::PREFIX, std_codec.clone());
         #[allow(clippy::redundant_clone)]
         r.register(Service::CODE, Service::PREFIX, std_codec.clone());
@@ -1,5 +1,7 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Udp, Unix, Ws, Wss,
+};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -132,6 +134,22 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Udp::CODE => {
+                        addr.push_back(Udp::new(0)).unwrap();
+                        prot.push_back(Udp::CODE);
+                    }
+                    Ws::CODE => {
+                        addr.push_back(Ws).unwrap();
+                        prot.push_back(Ws::CODE);
+                    }
+                    Wss::CODE => {
+                        addr.push_back(Wss).unwrap();
+                        prot.push_back(Wss::CODE);
+                    }
+                    Unix::CODE => {
+                        addr.push_back(Unix::new("/tmp/node.sock")).unwrap();
+                        prot.push_back(Unix::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -153,6 +171,10 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Udp::CODE,
+    Ws::CODE,
+    Wss::CODE,
+    Unix::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -169,6 +191,10 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Udp::CODE => a.push_back(Udp::new(u16::arbitrary(g))).unwrap(),
+                Ws::CODE => a.push_back(Ws).unwrap(),
+                Wss::CODE => a.push_back(Wss).unwrap(),
+                Unix::CODE => a.push_back(Unix::new(gen_path())).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -176,6 +202,34 @@ impl Arbitrary for Addr {
     }
 }
 
+#[test]
+fn transports() {
+    for s in [
+        "/ip4/127.0.0.1/udp/4000/service/api",
+        "/ip6/::1/udp/4000/service/api",
+        "/dnsaddr/localhost/tcp/80/ws/service/api",
+        "/dnsaddr/localhost/tcp/443/wss",
+        "/unix/%2Ftmp%2Fnode.sock/service/api",
+        "/unix/relative%25name.sock",
+    ] {
+        let a = MultiAddr::from_str(s).unwrap();
+        assert_eq!(s, a.to_string());
+        assert_eq!(a, MultiAddr::try_from(a.as_ref()).unwrap())
+    }
+
+    let a = MultiAddr::from_str("/unix/%2ftmp%2fnode.sock/ws/service/api").unwrap();
+    let mut i = a.iter();
+    let path = i.next().unwrap();
+    assert_eq!("/tmp/node.sock", &*path.cast::<Unix>().unwrap());
+    assert_eq!(Ws::CODE, i.next().unwrap().code());
+    assert_eq!("api", &*i.next().unwrap().cast::<Service>().unwrap());
+    assert!(i.next().is_none());
+
+    assert!(MultiAddr::from_str("/unix/").is_err());
+    assert!(MultiAddr::from_str("/unix/%2").is_err());
+    assert!(MultiAddr::from_str("/udp/65536").is_err());
+}
+
 /// An operation to perform on a MultiAddr.
 #[derive(Debug, Copy, Clone)]
 enum Op {
@@ -215,6 +269,17 @@ fn gen_hostname() -> String {
     v.join(".")
 }
 
+fn gen_path() -> String {
+    let mut g = rand::thread_rng();
+    let mut v = vec![String::new()];
+    for _ in 1..=g.gen_range(1..=5) {
+        v.push(gen_string())
+    }
+    // Include characters which need encoding in the textual representation
+    v.push(String::from("50%.sock"));
+    v.join("/")
+}
+
 fn gen_string() -> String {
     let mut s = Alphanumeric.sample_string(&mut rand::thread_rng(), 23);
     s.retain(|c| c != '/');
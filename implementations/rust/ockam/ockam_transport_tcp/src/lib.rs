@@ -20,12 +20,17 @@ extern crate alloc;
 
 mod options;
 mod portal;
+mod proxy;
 mod registry;
 mod transport;
 
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
 use ockam_core::TransportType;
 pub use options::*;
 pub use portal::*;
+pub use proxy::*;
 pub use registry::*;
 pub use transport::*;
 
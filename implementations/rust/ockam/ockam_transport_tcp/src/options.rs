@@ -1,4 +1,5 @@
 use crate::workers::Addresses;
+use crate::TcpProxy;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
@@ -13,6 +14,7 @@ pub(crate) struct TcpConnectionAccessControl {
 pub struct TcpConnectionOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) proxy: Option<TcpProxy>,
 }
 
 impl TcpConnectionOptions {
@@ -22,6 +24,7 @@ impl TcpConnectionOptions {
         Self {
             consumer: vec![],
             flow_control_id: FlowControls::generate_flow_control_id(),
+            proxy: None,
         }
     }
 
@@ -36,6 +39,15 @@ impl TcpConnectionOptions {
     pub fn flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
     }
+
+    /// Tunnel the connection through the given proxy, instead of the default proxy
+    /// of the [`TcpTransport`](crate::TcpTransport).
+    /// The socket address of the resulting connection is the address of the peer, with an
+    /// unspecified IP address when the peer is given as a host name, resolved by the proxy
+    pub fn with_proxy(mut self, proxy: TcpProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
 }
 
 impl TcpConnectionOptions {
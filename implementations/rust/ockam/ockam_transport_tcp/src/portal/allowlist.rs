@@ -0,0 +1,184 @@
+use crate::transport::common::{domain_matches, ip_matches, split_host_port};
+use core::ops::RangeInclusive;
+use ockam_core::compat::net::{IpAddr, SocketAddr};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_core::TransportError;
+use tokio::net::lookup_host;
+
+/// Destinations that a dynamic Outlet is allowed to connect to, on behalf of SOCKS5 Inlets
+///
+/// A destination is allowed when its port is allowed, and either its host name matches
+/// one of the allowed hosts, or its IP address belongs to one of the allowed networks.
+/// Host names are resolved by the Outlet, so a host name resolving to an address of an
+/// allowed network is allowed as well.
+///
+/// ```rust
+/// use ockam_transport_tcp::TcpOutletAllowlist;
+/// # use ockam_core::Result;
+/// # fn test() -> Result<()> {
+/// let allowlist = TcpOutletAllowlist::new()
+///     .allow_network("10.0.0.0/8")?
+///     .allow_host(".db.internal")
+///     .allow_port_range(5432..=5439);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TcpOutletAllowlist {
+    networks: Vec<String>,
+    hosts: Vec<String>,
+    ports: Vec<RangeInclusive<u16>>,
+}
+
+impl TcpOutletAllowlist {
+    /// An allowlist denying all destinations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow an IP address, or a range of IP addresses like `10.0.0.0/8` or `fd00::/8`
+    pub fn allow_network(mut self, network: &str) -> Result<Self> {
+        let (ip, prefix_len) = match network.split_once('/') {
+            Some((ip, prefix_len)) => (ip, Some(prefix_len)),
+            None => (network, None),
+        };
+        let ip: IpAddr = ip.parse().map_err(|_| invalid_network(network))?;
+        if let Some(prefix_len) = prefix_len {
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            match prefix_len.parse::<u32>() {
+                Ok(prefix_len) if prefix_len <= max => {}
+                _ => return Err(invalid_network(network)),
+            }
+        }
+        self.networks.push(network.to_string());
+        Ok(self)
+    }
+
+    /// Allow a host name, and its subdomains when it starts with a `.`
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.hosts.push(host.into().to_lowercase());
+        self
+    }
+
+    /// Allow a port. All ports are allowed if no port or port range is allowed
+    pub fn allow_port(self, port: u16) -> Self {
+        self.allow_port_range(port..=port)
+    }
+
+    /// Allow a range of ports. All ports are allowed if no port or port range is allowed
+    pub fn allow_port_range(mut self, ports: RangeInclusive<u16>) -> Self {
+        self.ports.push(ports);
+        self
+    }
+
+    /// Check that the `host:port` destination is allowed, and return its socket address.
+    ///
+    /// The port, and the host when it is an IP address or an allowed host name, are checked
+    /// before resolving the host name, so that a destination which can't be allowed is never
+    /// looked up.
+    pub(crate) async fn resolve(&self, destination: &str) -> Result<SocketAddr> {
+        let (host, port) = split_host_port(destination).ok_or_else(|| {
+            Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                format!("invalid destination {destination}"),
+            )
+        })?;
+        if !self.ports.is_empty() && !self.ports.iter().any(|ports| ports.contains(&port)) {
+            return Err(not_allowed(destination));
+        }
+
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return if self.is_allowed_ip(ip) {
+                Ok(SocketAddr::new(ip, port))
+            } else {
+                Err(not_allowed(destination))
+            };
+        }
+
+        let host = host.to_lowercase();
+        let is_allowed_host = self.hosts.iter().any(|allowed| {
+            if allowed.starts_with('.') {
+                domain_matches(&host, allowed)
+            } else {
+                &host == allowed
+            }
+        });
+        if !is_allowed_host && self.networks.is_empty() {
+            return Err(not_allowed(destination));
+        }
+
+        let addresses: Vec<SocketAddr> = lookup_host((host.as_str(), port))
+            .await
+            .map_err(|_| TransportError::InvalidAddress)?
+            .filter(|address| is_allowed_host || self.is_allowed_ip(address.ip()))
+            .collect();
+        // Prefer ip4
+        addresses
+            .iter()
+            .find(|address| address.is_ipv4())
+            .or_else(|| addresses.first())
+            .copied()
+            .ok_or_else(|| not_allowed(destination))
+    }
+
+    fn is_allowed_ip(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| ip_matches(ip, network))
+    }
+}
+
+fn invalid_network(network: &str) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("invalid network {network}"),
+    )
+}
+
+fn not_allowed(destination: &str) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("the destination {destination} is not allowed"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_allowlist() -> Result<()> {
+        let allowlist = TcpOutletAllowlist::new()
+            .allow_network("10.0.0.0/8")?
+            .allow_network("fd00::1")?
+            .allow_host(".internal")
+            .allow_host("localhost")
+            .allow_port(22)
+            .allow_port_range(5432..=5439);
+
+        assert!(allowlist.resolve("10.1.2.3:22").await.is_ok());
+        assert!(allowlist.resolve("[fd00::1]:5435").await.is_ok());
+        assert!(allowlist.resolve("localhost:5432").await.is_ok());
+        assert!(allowlist.resolve("10.1.2.3:80").await.is_err());
+        assert!(allowlist.resolve("11.1.2.3:22").await.is_err());
+        assert!(allowlist.resolve("127.0.0.1:22").await.is_err());
+        assert!(allowlist.resolve("[fd00::2]:22").await.is_err());
+        assert!(allowlist.resolve("not-localhost:22").await.is_err());
+
+        // Host names resolving to an allowed network are allowed
+        let allowlist = TcpOutletAllowlist::new().allow_network("127.0.0.0/8")?;
+        assert!(allowlist.resolve("localhost:22").await.is_ok());
+
+        assert!(TcpOutletAllowlist::new()
+            .resolve("10.1.2.3:22")
+            .await
+            .is_err());
+        assert!(TcpOutletAllowlist::new()
+            .allow_network("10.0.0.0/33")
+            .is_err());
+        assert!(TcpOutletAllowlist::new().allow_network("internal").is_err());
+        Ok(())
+    }
+}
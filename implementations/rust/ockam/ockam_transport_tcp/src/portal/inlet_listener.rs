@@ -12,12 +12,14 @@ use tracing::{debug, error};
 ///
 /// TCP Portal Inlet listen processors are created by `TcpTransport`
 /// after a call is made to
-/// [`TcpTransport::create_inlet`](crate::TcpTransport::create_inlet).
+/// [`TcpTransport::create_inlet`](crate::TcpTransport::create_inlet)
+/// or [`TcpTransport::create_socks5_inlet`](crate::TcpTransport::create_socks5_inlet).
 pub(crate) struct TcpInletListenProcessor {
     registry: TcpRegistry,
     inner: TcpListener,
     outlet_listener_route: Route,
     options: TcpInletOptions,
+    is_socks5: bool,
 }
 
 impl TcpInletListenProcessor {
@@ -26,12 +28,14 @@ impl TcpInletListenProcessor {
         inner: TcpListener,
         outlet_listener_route: Route,
         options: TcpInletOptions,
+        is_socks5: bool,
     ) -> Self {
         Self {
             registry,
             inner,
             outlet_listener_route,
             options,
+            is_socks5,
         }
     }
 
@@ -42,6 +46,7 @@ impl TcpInletListenProcessor {
         outlet_listener_route: Route,
         addr: SocketAddr,
         options: TcpInletOptions,
+        is_socks5: bool,
     ) -> Result<(SocketAddr, Address)> {
         let processor_address = Address::random_tagged("TcpInletListenProcessor");
 
@@ -54,7 +59,7 @@ impl TcpInletListenProcessor {
             }
         };
         let socket_addr = inner.local_addr().map_err(TransportError::from)?;
-        let processor = Self::new(registry, inner, outlet_listener_route, options);
+        let processor = Self::new(registry, inner, outlet_listener_route, options, is_socks5);
 
         ctx.start_processor(processor_address.clone(), processor)
             .await?;
@@ -99,6 +104,7 @@ impl Processor for TcpInletListenProcessor {
             outlet_listener_route,
             addresses,
             self.options.incoming_access_control.clone(),
+            self.is_socks5,
         )
         .await?;
 
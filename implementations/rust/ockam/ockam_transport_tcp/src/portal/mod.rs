@@ -1,11 +1,14 @@
 mod addresses;
+mod allowlist;
 mod inlet_listener;
 pub mod options;
 mod outlet_listener;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
+mod socks5;
 
+pub use allowlist::*;
 pub(crate) use inlet_listener::*;
 pub(crate) use outlet_listener::*;
 pub use portal_message::*;
@@ -62,6 +62,7 @@ impl Default for TcpInletOptions {
 pub struct TcpOutletOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(super) connection_access_control: Arc<dyn IncomingAccessControl>,
 }
 
 impl TcpOutletOptions {
@@ -70,6 +71,7 @@ impl TcpOutletOptions {
         Self {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
+            connection_access_control: Arc::new(AllowAll),
         }
     }
 
@@ -91,6 +93,18 @@ impl TcpOutletOptions {
         self
     }
 
+    /// Set the Access Control authorizing the connection requests received by a dynamic Outlet,
+    /// in addition to its Incoming Access Control. Those requests are
+    /// [`PortalMessage::ConnectTo`](crate::PortalMessage::ConnectTo) messages, containing
+    /// the destination requested by a SOCKS5 Inlet
+    pub fn with_connection_access_control(
+        mut self,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Self {
+        self.connection_access_control = access_control;
+        self
+    }
+
     /// Mark that this Outlet listener is a Consumer for to the given [`FlowControlId`]
     /// Also, in this case spawned Outlets will be marked as Consumers with [`FlowControlId`]
     /// of the message that was used to create the Outlet
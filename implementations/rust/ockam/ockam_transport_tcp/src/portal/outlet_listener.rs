@@ -1,28 +1,41 @@
 use crate::portal::addresses::{Addresses, PortalType};
-use crate::{PortalMessage, TcpOutletOptions, TcpPortalWorker, TcpRegistry};
-use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
+use crate::{PortalMessage, TcpOutletAllowlist, TcpOutletOptions, TcpPortalWorker, TcpRegistry};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, Address, Decodable, DenyAll, OutgoingAccessControl, RelayMessage, Result, Routed,
+    Worker,
+};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use std::net::SocketAddr;
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Destination of the connections of the Outlets created by a `TcpOutletListenWorker`
+pub(crate) enum OutletTarget {
+    /// Connect to a fixed peer, when pinged by an Inlet
+    Peer(SocketAddr),
+    /// Connect to the destination requested by a SOCKS5 Inlet, if it is allowed
+    Dynamic(TcpOutletAllowlist),
+}
 
 /// A TCP Portal Outlet listen worker
 ///
 /// TCP Portal Outlet listen workers are created by `TcpTransport`
 /// after a call is made to
-/// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
+/// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet)
+/// or [`TcpTransport::create_dynamic_outlet`](crate::TcpTransport::create_dynamic_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
-    peer: SocketAddr,
+    target: OutletTarget,
     options: TcpOutletOptions,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, peer: SocketAddr, options: TcpOutletOptions) -> Self {
+    fn new(registry: TcpRegistry, target: OutletTarget, options: TcpOutletOptions) -> Self {
         Self {
             registry,
-            peer,
+            target,
             options,
         }
     }
@@ -31,18 +44,24 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
-        peer: SocketAddr,
+        target: OutletTarget,
         options: TcpOutletOptions,
     ) -> Result<()> {
         let access_control = options.incoming_access_control.clone();
 
         options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, peer, options);
+        // A dynamic Outlet listener replies to the connection requests it refuses
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = match target {
+            OutletTarget::Peer(_) => Arc::new(DenyAll),
+            OutletTarget::Dynamic(_) => Arc::new(AllowDisconnect),
+        };
+
+        let worker = Self::new(registry, target, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
-            .with_outgoing_access_control(DenyAll)
+            .with_outgoing_access_control_arc(outgoing_access_control)
             .start(ctx)
             .await?;
 
@@ -50,6 +69,22 @@ impl TcpOutletListenWorker {
     }
 }
 
+/// Outgoing access control of a dynamic Outlet listener, which can only reply
+/// [`PortalMessage::Disconnect`] to the connection requests it refuses
+#[derive(Debug)]
+struct AllowDisconnect;
+
+#[async_trait]
+impl OutgoingAccessControl for AllowDisconnect {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let payload = &relay_msg.local_message().transport().payload;
+        Ok(matches!(
+            PortalMessage::decode(payload),
+            Ok(PortalMessage::Disconnect)
+        ))
+    }
+}
+
 #[async_trait]
 impl Worker for TcpOutletListenWorker {
     type Context = Context;
@@ -75,10 +110,39 @@ impl Worker for TcpOutletListenWorker {
         let return_route = msg.return_route();
         let src_addr = msg.src_addr();
 
-        if let PortalMessage::Ping = msg.body() {
-        } else {
-            return Err(TransportError::Protocol.into());
-        }
+        let peer = match (msg.as_body(), &self.target) {
+            (PortalMessage::Ping, OutletTarget::Peer(peer)) => *peer,
+            (PortalMessage::ConnectTo(destination), OutletTarget::Dynamic(allowlist)) => {
+                // The requester is authorized before the destination is resolved
+                let relay_message = RelayMessage::new(
+                    src_addr.clone(),
+                    msg.msg_addr(),
+                    msg.local_message().clone(),
+                );
+                if !self
+                    .options
+                    .connection_access_control
+                    .is_authorized(&relay_message)
+                    .await?
+                {
+                    warn!(
+                        "Refused an unauthorized connection request to {}",
+                        destination
+                    );
+                    ctx.send(return_route, PortalMessage::Disconnect).await?;
+                    return Ok(());
+                }
+                match allowlist.resolve(destination).await {
+                    Ok(peer) => peer,
+                    Err(err) => {
+                        warn!("Refused a connection request: {}", err);
+                        ctx.send(return_route, PortalMessage::Disconnect).await?;
+                        return Ok(());
+                    }
+                }
+            }
+            _ => return Err(TransportError::Protocol.into()),
+        };
 
         let addresses = Addresses::generate(PortalType::Outlet);
 
@@ -88,7 +152,7 @@ impl Worker for TcpOutletListenWorker {
         TcpPortalWorker::start_new_outlet(
             ctx,
             self.registry.clone(),
-            self.peer,
+            peer,
             return_route.clone(),
             addresses.clone(),
             self.options.incoming_access_control.clone(),
@@ -13,6 +13,9 @@ pub enum PortalMessage {
     Disconnect,
     /// Message with binary payload
     Payload(Vec<u8>),
+    /// First message that a SOCKS5 Inlet sends to a dynamic Outlet, instead of `Ping`,
+    /// with the `host:port` destination requested by the SOCKS5 client
+    ConnectTo(String),
 }
 
 /// An internal message type for a Portal
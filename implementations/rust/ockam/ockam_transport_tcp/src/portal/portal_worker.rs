@@ -1,4 +1,5 @@
 use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::socks5;
 use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor, TcpRegistry};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
@@ -20,6 +21,9 @@ use tracing::{debug, info, trace, warn};
 ///
 /// `Outlet`: `SendPong` -> `Initialized`
 /// `Inlet`: `SendPing` -> `ReceivePong` -> `Initialized`
+///
+/// A SOCKS5 `Inlet` reads the destination requested by its client in the `SendPing`
+/// state, and sends it to the `Outlet` with its ping.
 #[derive(Clone)]
 enum State {
     SendPing { ping_route: Route },
@@ -44,10 +48,12 @@ pub(crate) struct TcpPortalWorker {
     remote_route: Option<Route>,
     is_disconnecting: bool,
     portal_type: PortalType,
+    is_socks5: bool,
 }
 
 impl TcpPortalWorker {
     /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn start_new_inlet(
         ctx: &Context,
         registry: TcpRegistry,
@@ -56,6 +62,7 @@ impl TcpPortalWorker {
         ping_route: Route,
         addresses: Addresses,
         access_control: Arc<dyn IncomingAccessControl>,
+        is_socks5: bool,
     ) -> Result<()> {
         Self::start(
             ctx,
@@ -66,6 +73,7 @@ impl TcpPortalWorker {
             addresses,
             PortalType::Inlet,
             access_control,
+            is_socks5,
         )
         .await
     }
@@ -88,6 +96,7 @@ impl TcpPortalWorker {
             addresses,
             PortalType::Outlet,
             access_control,
+            false,
         )
         .await
     }
@@ -103,6 +112,7 @@ impl TcpPortalWorker {
         addresses: Addresses,
         portal_type: PortalType,
         access_control: Arc<dyn IncomingAccessControl>,
+        is_socks5: bool,
     ) -> Result<()> {
         info!(
             "Creating new {:?} at internal: {}, remote: {}",
@@ -129,6 +139,7 @@ impl TcpPortalWorker {
             remote_route: None,
             is_disconnecting: false,
             portal_type,
+            is_socks5,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -272,14 +283,32 @@ impl TcpPortalWorker {
         Ok(())
     }
 
-    async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
+    async fn handle_send_ping(&mut self, ctx: &Context, ping_route: Route) -> Result<State> {
+        let ping = if self.is_socks5 {
+            match (&mut self.read_half, &mut self.write_half) {
+                (Some(rx), Some(tx)) => {
+                    // A client which doesn't complete the handshake doesn't keep the Inlet
+                    let destination = tokio::time::timeout(
+                        socks5::HANDSHAKE_TIMEOUT,
+                        socks5::accept_request(rx, tx),
+                    )
+                    .await
+                    .map_err(|_| TransportError::ConnectionDrop)??;
+                    debug!(
+                        "Inlet at: {} received a SOCKS5 request to {}",
+                        self.addresses.internal, destination
+                    );
+                    PortalMessage::ConnectTo(destination)
+                }
+                _ => return Err(TransportError::PortalInvalidState.into()),
+            }
+        } else {
+            PortalMessage::Ping
+        };
+
         // Force creation of Outlet on the other side
-        ctx.send_from_address(
-            ping_route,
-            PortalMessage::Ping,
-            self.addresses.remote.clone(),
-        )
-        .await?;
+        ctx.send_from_address(ping_route, ping, self.addresses.remote.clone())
+            .await?;
 
         debug!("Inlet at: {} sent ping", self.addresses.internal);
 
@@ -287,30 +316,40 @@ impl TcpPortalWorker {
     }
 
     async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
-        // Respond to Inlet
-        ctx.send_from_address(
-            pong_route.clone(),
-            PortalMessage::Pong,
-            self.addresses.remote.clone(),
-        )
-        .await?;
-
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
+            let stream = match TcpStream::connect(self.peer).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    // Let the Inlet close its connection
+                    ctx.send_from_address(
+                        pong_route,
+                        PortalMessage::Disconnect,
+                        self.addresses.remote.clone(),
+                    )
+                    .await?;
+                    return Err(TransportError::from(err).into());
+                }
+            };
             let (rx, tx) = stream.into_split();
             self.write_half = Some(tx);
             self.read_half = Some(rx);
 
-            self.start_receiver(ctx, pong_route.clone()).await?;
-
             debug!(
                 "Outlet at: {} successfully connected",
                 self.addresses.internal
             );
         }
 
+        // Respond to Inlet
+        ctx.send_from_address(
+            pong_route.clone(),
+            PortalMessage::Pong,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        self.start_receiver(ctx, pong_route.clone()).await?;
+
         debug!("Outlet at: {} sent pong", self.addresses.internal);
 
         self.remote_route = Some(pong_route);
@@ -377,9 +416,26 @@ impl Worker for TcpPortalWorker {
 
                 let msg = PortalMessage::decode(msg.payload())?;
 
-                if let PortalMessage::Pong = msg {
-                } else {
-                    return Err(TransportError::Protocol.into());
+                match msg {
+                    PortalMessage::Pong => {}
+                    PortalMessage::Disconnect => {
+                        // The Outlet could not connect, or refused to connect
+                        info!(
+                            "Inlet at: {} was refused a connection by the outlet",
+                            self.addresses.internal
+                        );
+                        if let (true, Some(tx)) = (self.is_socks5, &mut self.write_half) {
+                            let _ = socks5::reply(tx, socks5::CONNECTION_NOT_ALLOWED).await;
+                        }
+                        self.is_disconnecting = true;
+                        ctx.stop_worker(self.addresses.internal.clone()).await?;
+                        return Ok(());
+                    }
+                    _ => return Err(TransportError::Protocol.into()),
+                }
+
+                if let (true, Some(tx)) = (self.is_socks5, &mut self.write_half) {
+                    socks5::reply(tx, socks5::SUCCEEDED).await?;
                 }
 
                 self.start_receiver(ctx, return_route.clone()).await?;
@@ -445,7 +501,7 @@ impl Worker for TcpPortalWorker {
                             self.start_disconnection(ctx, DisconnectionReason::Remote)
                                 .await?;
                         }
-                        PortalMessage::Ping | PortalMessage::Pong => {
+                        PortalMessage::Ping | PortalMessage::Pong | PortalMessage::ConnectTo(_) => {
                             return Err(TransportError::Protocol.into());
                         }
                     }
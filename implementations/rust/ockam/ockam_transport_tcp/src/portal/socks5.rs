@@ -0,0 +1,100 @@
+//! Server side of the SOCKS5 protocol (RFC 1928), as spoken by SOCKS5 Inlets.
+//!
+//! Only the `CONNECT` command is supported, without authentication, since the
+//! client is authorized by the Outlet.
+
+use core::time::Duration;
+use ockam_core::compat::net::{Ipv4Addr, Ipv6Addr};
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+/// Maximum duration of the handshake of a client, until its `CONNECT` request is read
+pub(super) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const NO_ACCEPTABLE_METHODS: u8 = 0xff;
+const CONNECT: u8 = 1;
+
+/// Reply code when the connection to the destination succeeded
+pub(super) const SUCCEEDED: u8 = 0;
+/// Reply code when the connection to the destination was refused by the Outlet
+pub(super) const CONNECTION_NOT_ALLOWED: u8 = 2;
+const COMMAND_NOT_SUPPORTED: u8 = 7;
+const ADDRESS_TYPE_NOT_SUPPORTED: u8 = 8;
+
+/// Negotiate the authentication method and read the `CONNECT` request of a client.
+/// Return the `host:port` destination requested by the client
+pub(super) async fn accept_request(
+    rx: &mut OwnedReadHalf,
+    tx: &mut OwnedWriteHalf,
+) -> Result<String> {
+    let mut header = [0u8; 2];
+    read(rx, &mut header).await?;
+    if header[0] != VERSION {
+        return Err(TransportError::Protocol.into());
+    }
+    let mut methods = vec![0u8; header[1] as usize];
+    read(rx, &mut methods).await?;
+    if !methods.contains(&NO_AUTHENTICATION) {
+        write(tx, &[VERSION, NO_ACCEPTABLE_METHODS]).await?;
+        return Err(TransportError::Protocol.into());
+    }
+    write(tx, &[VERSION, NO_AUTHENTICATION]).await?;
+
+    let mut request = [0u8; 4];
+    read(rx, &mut request).await?;
+    if request[0] != VERSION {
+        return Err(TransportError::Protocol.into());
+    }
+    if request[1] != CONNECT {
+        reply(tx, COMMAND_NOT_SUPPORTED).await?;
+        return Err(TransportError::Protocol.into());
+    }
+    let host = match request[3] {
+        1 => {
+            let mut ip = [0u8; 4];
+            read(rx, &mut ip).await?;
+            Ipv4Addr::from(ip).to_string()
+        }
+        3 => {
+            let mut len = [0u8; 1];
+            read(rx, &mut len).await?;
+            let mut host = vec![0u8; len[0] as usize];
+            read(rx, &mut host).await?;
+            String::from_utf8(host).map_err(|_| TransportError::InvalidAddress)?
+        }
+        4 => {
+            let mut ip = [0u8; 16];
+            read(rx, &mut ip).await?;
+            format!("[{}]", Ipv6Addr::from(ip))
+        }
+        _ => {
+            reply(tx, ADDRESS_TYPE_NOT_SUPPORTED).await?;
+            return Err(TransportError::InvalidAddress.into());
+        }
+    };
+    let mut port = [0u8; 2];
+    read(rx, &mut port).await?;
+
+    Ok(format!("{}:{}", host, u16::from_be_bytes(port)))
+}
+
+/// Reply to the `CONNECT` request of a client.
+/// The bound address is left unspecified since the connection is established by the Outlet
+pub(super) async fn reply(tx: &mut OwnedWriteHalf, code: u8) -> Result<()> {
+    write(tx, &[VERSION, code, 0, 1, 0, 0, 0, 0, 0, 0]).await
+}
+
+async fn read(rx: &mut OwnedReadHalf, buf: &mut [u8]) -> Result<()> {
+    rx.read_exact(buf)
+        .await
+        .map_err(|_| TransportError::ConnectionDrop)?;
+    Ok(())
+}
+
+async fn write(tx: &mut OwnedWriteHalf, data: &[u8]) -> Result<()> {
+    Ok(tx.write_all(data).await.map_err(TransportError::from)?)
+}
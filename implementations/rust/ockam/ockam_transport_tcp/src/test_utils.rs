@@ -0,0 +1,171 @@
+//! Proxy stand-ins, used to test the connections tunneled through a proxy.
+//!
+//! Every target host is resolved as `127.0.0.1` by the stand-ins.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A running proxy stand-in
+#[derive(Clone, Debug)]
+pub struct ProxyStandIn {
+    address: SocketAddr,
+    targets: Arc<Mutex<Vec<String>>>,
+}
+
+impl ProxyStandIn {
+    /// Address of the proxy
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// `host:port` targets of the tunnels opened by the proxy
+    pub fn targets(&self) -> Vec<String> {
+        self.targets.lock().unwrap().clone()
+    }
+}
+
+/// Start an HTTP proxy stand-in supporting the CONNECT method. When `credentials`
+/// are given, as `username:password`, the proxy requires them as basic auth
+pub async fn start_http_proxy(credentials: Option<&str>) -> ProxyStandIn {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = ProxyStandIn {
+        address: listener.local_addr().unwrap(),
+        targets: Default::default(),
+    };
+    let authorization = credentials.map(|credentials| {
+        format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            BASE64.encode(credentials)
+        )
+    });
+    let targets = proxy.targets.clone();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            let targets = targets.clone();
+            let authorization = authorization.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0u8; 1];
+                    if client.read(&mut byte).await.unwrap() == 0 {
+                        return;
+                    }
+                    request.push(byte[0]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let target = request
+                    .strip_prefix("CONNECT ")
+                    .and_then(|r| r.split(' ').next())
+                    .unwrap()
+                    .to_string();
+
+                if let Some(authorization) = authorization {
+                    if !request.contains(&authorization) {
+                        let _ = client
+                            .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                            .await;
+                        return;
+                    }
+                }
+                let mut server = connect_to_target(&target).await;
+                client
+                    .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                    .await
+                    .unwrap();
+                targets.lock().unwrap().push(target);
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            });
+        }
+    });
+    proxy
+}
+
+/// Start a SOCKS5 proxy stand-in requiring the given username and password
+pub async fn start_socks5_proxy(username: &'static str, password: &'static str) -> ProxyStandIn {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = ProxyStandIn {
+        address: listener.local_addr().unwrap(),
+        targets: Default::default(),
+    };
+    let targets = proxy.targets.clone();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            let targets = targets.clone();
+            tokio::spawn(async move {
+                // Greeting, only accepting the username/password method
+                let mut header = [0u8; 2];
+                client.read_exact(&mut header).await.unwrap();
+                let mut methods = vec![0u8; header[1] as usize];
+                client.read_exact(&mut methods).await.unwrap();
+                if !methods.contains(&2) {
+                    let _ = client.write_all(&[5, 0xff]).await;
+                    return;
+                }
+                client.write_all(&[5, 2]).await.unwrap();
+
+                // Username and password
+                let mut len = [0u8; 2];
+                client.read_exact(&mut len).await.unwrap();
+                let mut received_username = vec![0u8; len[1] as usize];
+                client.read_exact(&mut received_username).await.unwrap();
+                client.read_exact(&mut len[..1]).await.unwrap();
+                let mut received_password = vec![0u8; len[0] as usize];
+                client.read_exact(&mut received_password).await.unwrap();
+                if received_username != username.as_bytes()
+                    || received_password != password.as_bytes()
+                {
+                    let _ = client.write_all(&[1, 1]).await;
+                    return;
+                }
+                client.write_all(&[1, 0]).await.unwrap();
+
+                // Connect request, with a domain name
+                let mut request = [0u8; 5];
+                client.read_exact(&mut request).await.unwrap();
+                assert_eq!(request[3], 3, "the host name is resolved by the proxy");
+                let mut host = vec![0u8; request[4] as usize];
+                client.read_exact(&mut host).await.unwrap();
+                let mut port = [0u8; 2];
+                client.read_exact(&mut port).await.unwrap();
+                let target = format!(
+                    "{}:{}",
+                    String::from_utf8(host).unwrap(),
+                    u16::from_be_bytes(port)
+                );
+
+                let mut server = connect_to_target(&target).await;
+                client
+                    .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+                    .await
+                    .unwrap();
+                targets.lock().unwrap().push(target);
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            });
+        }
+    });
+    proxy
+}
+
+/// Start a proxy stand-in accepting connections and never answering them
+pub async fn start_silent_proxy() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut clients = vec![];
+        while let Ok((client, _)) = listener.accept().await {
+            clients.push(client);
+        }
+    });
+    address
+}
+
+async fn connect_to_target(target: &str) -> TcpStream {
+    let port = target.rsplit_once(':').unwrap().1;
+    TcpStream::connect(format!("127.0.0.1:{port}"))
+        .await
+        .unwrap()
+}
@@ -1,7 +1,7 @@
 use crate::TcpConnectionMode;
 use core::fmt;
 use core::fmt::Formatter;
-use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
+use ockam_core::compat::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Result};
 use ockam_transport_core::TransportError;
@@ -122,7 +122,7 @@ impl TcpListener {
 }
 
 /// Resolve the given peer to a [`SocketAddr`](std::net::SocketAddr)
-pub(super) fn resolve_peer(peer: String) -> Result<SocketAddr> {
+pub(crate) fn resolve_peer(peer: String) -> Result<SocketAddr> {
     // Try to parse as SocketAddr
     if let Ok(p) = parse_socket_addr(&peer) {
         return Ok(p);
@@ -147,6 +147,56 @@ pub(super) fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
     Ok(s.parse().map_err(|_| TransportError::InvalidAddress)?)
 }
 
+/// Check if an IP address matches an IP address or a CIDR range
+pub(crate) fn ip_matches(ip: IpAddr, entry: &str) -> bool {
+    let (network, prefix_len) = match entry.split_once('/') {
+        Some((network, prefix_len)) => match prefix_len.parse::<u32>() {
+            Ok(prefix_len) => (network, prefix_len),
+            Err(_) => return false,
+        },
+        None => (entry, 128),
+    };
+    match (ip, network.parse::<IpAddr>()) {
+        (IpAddr::V4(ip), Ok(IpAddr::V4(network))) => {
+            let prefix_len = prefix_len.min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), Ok(IpAddr::V6(network))) => {
+            let mask = u128::MAX
+                .checked_shl(128 - prefix_len.min(128))
+                .unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Split a `host:port` address, where an IPv6 host is enclosed in brackets
+pub(crate) fn split_host_port(address: &str) -> Option<(&str, u16)> {
+    if let Ok(socket_address) = address.parse::<SocketAddr>() {
+        let host_len = address.rfind(':')?;
+        return Some((
+            address[..host_len]
+                .trim_start_matches('[')
+                .trim_end_matches(']'),
+            socket_address.port(),
+        ));
+    }
+    let (host, port) = address.rsplit_once(':')?;
+    if host.is_empty() || host.contains(':') {
+        return None;
+    }
+    Some((host, port.parse().ok()?))
+}
+
+/// Check if a host name is a domain, or one of its subdomains.
+/// The domain can start with a `.`
+pub(crate) fn domain_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
 #[cfg(test)]
 mod test {
     use crate::transport::common::parse_socket_addr;
@@ -1,6 +1,7 @@
+use crate::proxy::target_socket_address;
 use crate::transport::common::{resolve_peer, TcpConnection};
 use crate::workers::{Addresses, TcpRecvProcessor, TcpSendWorker};
-use crate::{TcpConnectionMode, TcpConnectionOptions, TcpTransport};
+use crate::{TcpConnectionMode, TcpConnectionOptions, TcpProxySettings, TcpTransport};
 use ockam_core::{Address, Result};
 
 impl TcpTransport {
@@ -16,15 +17,35 @@ impl TcpTransport {
     /// let connection = tcp.connect("127.0.0.1:5000", TcpConnectionOptions::new()).await?; // and connect to port 5000
     /// # Ok(()) }
     /// ```
+    ///
+    /// The connection is tunneled through the proxy of the `options` if any, otherwise through
+    /// the default proxy of the transport, unless the peer must be reached directly.
     pub async fn connect(
         &self,
         peer: impl Into<String>,
         options: TcpConnectionOptions,
     ) -> Result<TcpConnection> {
-        // Resolve peer address
-        let socket = resolve_peer(peer.into())?;
+        let peer = peer.into();
+        let proxy = options.proxy.clone().or_else(|| {
+            self.proxy_settings
+                .read()
+                .unwrap()
+                .as_ref()
+                .and_then(|settings| settings.proxy_for(&peer).cloned())
+        });
 
-        let (read_half, write_half) = TcpSendWorker::connect(socket).await?;
+        let (socket, (read_half, write_half)) = match proxy {
+            Some(proxy) => {
+                // The peer host name is resolved by the proxy
+                let socket = target_socket_address(&peer)?;
+                (socket, TcpSendWorker::split(proxy.connect(&peer).await?))
+            }
+            None => {
+                // Resolve peer address
+                let socket = resolve_peer(peer)?;
+                (socket, TcpSendWorker::connect(socket).await?)
+            }
+        };
 
         let mode = TcpConnectionMode::Outgoing;
         let addresses = Addresses::generate(mode);
@@ -66,6 +87,23 @@ impl TcpTransport {
         ))
     }
 
+    /// Set the default proxy of the outgoing connections, including the connections
+    /// established when resolving the `(TCP, host:port)` addresses of a route
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpProxy, TcpProxySettings, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let proxy = TcpProxy::from_url("http://proxy.example.com:3128")?;
+    /// tcp.set_proxy_settings(Some(TcpProxySettings::new(proxy).with_no_proxy(vec![".internal"])));
+    /// # Ok(()) }
+    /// ```
+    pub fn set_proxy_settings(&self, settings: Option<TcpProxySettings>) {
+        *self.proxy_settings.write().unwrap() = settings;
+    }
+
     /// Interrupt an active TCP connection given its `Address`
     pub async fn disconnect(&self, address: &Address) -> Result<()> {
         self.ctx.stop_worker(address.clone()).await
@@ -1,4 +1,4 @@
-mod common;
+pub(crate) mod common;
 mod connection;
 mod lifecycle;
 mod listener;
@@ -6,7 +6,8 @@ mod portals;
 
 pub use crate::portal::options::*;
 
-use crate::TcpRegistry;
+use crate::{TcpProxySettings, TcpRegistry};
+use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::{async_trait, AsyncTryClone, Result};
 use ockam_node::{Context, HasContext};
 
@@ -53,6 +54,7 @@ use ockam_node::{Context, HasContext};
 pub struct TcpTransport {
     ctx: Context,
     registry: TcpRegistry,
+    proxy_settings: Arc<RwLock<Option<TcpProxySettings>>>,
 }
 
 /// This trait adds a `create_tcp_transport` method to any struct returning a Context.
@@ -1,6 +1,8 @@
-use crate::portal::TcpInletListenProcessor;
+use crate::portal::{OutletTarget, TcpInletListenProcessor};
 use crate::transport::common::{parse_socket_addr, resolve_peer};
-use crate::{TcpInletOptions, TcpOutletListenWorker, TcpOutletOptions, TcpTransport};
+use crate::{
+    TcpInletOptions, TcpOutletAllowlist, TcpOutletListenWorker, TcpOutletOptions, TcpTransport,
+};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, Result, Route};
 
@@ -35,6 +37,39 @@ impl TcpTransport {
             outlet_route.into(),
             socket_addr,
             options,
+            false,
+        )
+        .await
+    }
+
+    /// Create a SOCKS5 Inlet that listens on bind_addr. Each client of the Inlet requests
+    /// a destination with the SOCKS5 protocol, which is sent to the dynamic Outlet at
+    /// outlet_route. The dynamic Outlet connects to that destination if it is allowed,
+    /// and the connection is then streamed as with a regular Inlet.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpInletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_socks5_inlet("127.0.0.1:1080", route!["dynamic_outlet"], TcpInletOptions::new()).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_socks5_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        outlet_route: impl Into<Route>,
+        options: TcpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let socket_addr = parse_socket_addr(&bind_addr.into())?;
+        TcpInletListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            outlet_route.into(),
+            socket_addr,
+            options,
+            true,
         )
         .await
     }
@@ -88,7 +123,7 @@ impl TcpTransport {
             &self.ctx,
             self.registry.clone(),
             address.into(),
-            peer_addr,
+            OutletTarget::Peer(peer_addr),
             options,
         )
         .await?;
@@ -96,6 +131,38 @@ impl TcpTransport {
         Ok(())
     }
 
+    /// Create a dynamic Outlet Listener at address, which connects to the destinations requested
+    /// by SOCKS5 Inlets, when they are allowed by the allowlist and the connection access control
+    /// of the options. It can be stopped with [`TcpTransport::stop_outlet`].
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpOutletAllowlist, TcpOutletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let allowlist = TcpOutletAllowlist::new().allow_network("10.0.0.0/8")?.allow_port(5432);
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_dynamic_outlet("dynamic_outlet", allowlist, TcpOutletOptions::new()).await?;
+    /// # tcp.stop_outlet("dynamic_outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_dynamic_outlet(
+        &self,
+        address: impl Into<Address>,
+        allowlist: TcpOutletAllowlist,
+        options: TcpOutletOptions,
+    ) -> Result<()> {
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address.into(),
+            OutletTarget::Dynamic(allowlist),
+            options,
+        )
+        .await
+    }
+
     /// Stop outlet at addr
     /// ```rust
     /// use ockam_transport_tcp::{TcpOutletOptions, TcpTransport};
@@ -134,6 +134,11 @@ impl TcpSendWorker {
             }
         };
 
+        Ok(Self::split(connection))
+    }
+
+    /// Enable keepalive probes on an established connection and split it
+    pub(crate) fn split(connection: TcpStream) -> (OwnedReadHalf, OwnedWriteHalf) {
         let mut keepalive = TcpKeepalive::new()
             .with_time(Duration::from_secs(300))
             .with_interval(Duration::from_secs(75));
@@ -147,7 +152,7 @@ impl TcpSendWorker {
         let socket = SockRef::from(&connection);
         socket.set_tcp_keepalive(&keepalive).unwrap();
 
-        Ok(connection.into_split())
+        connection.into_split()
     }
 }
 
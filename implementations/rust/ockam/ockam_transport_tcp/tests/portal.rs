@@ -1,13 +1,15 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, Result};
+use ockam_core::{route, DenyAll, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
+    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletAllowlist,
+    TcpOutletOptions, TcpTransport,
 };
 
 const LENGTH: usize = 32;
@@ -238,3 +240,112 @@ async fn portal__tcp_connection_with_invalid_message_flow__should_not_succeed(
 
     Ok(())
 }
+
+/// Send a SOCKS5 `CONNECT` request for the destination and return the reply code
+async fn socks5_connect(stream: &mut TcpStream, host: &str, port: u16) -> u8 {
+    stream.write_all(&[5, 1, 0]).await.unwrap();
+    let mut method = [0u8; 2];
+    stream.read_exact(&mut method).await.unwrap();
+    assert_eq!(method, [5, 0]);
+
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.unwrap();
+
+    let mut reply = [0u8; 10];
+    stream.read_exact(&mut reply).await.unwrap();
+    reply[1]
+}
+
+async fn setup_socks5(ctx: &Context, options: TcpOutletOptions) -> Result<(String, TcpListener)> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let allowlist = TcpOutletAllowlist::new()
+        .allow_host("localhost")
+        .allow_port(listener.local_addr().unwrap().port());
+    tcp.create_dynamic_outlet("dynamic_outlet", allowlist, options)
+        .await?;
+
+    let (inlet_saddr, _) = tcp
+        .create_socks5_inlet(
+            "127.0.0.1:0",
+            route!["dynamic_outlet"],
+            TcpInletOptions::new(),
+        )
+        .await?;
+
+    Ok((inlet_saddr.to_string(), listener))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__socks5_allowed_destination__should_succeed(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let (inlet_addr, listener) = setup_socks5(ctx, TcpOutletOptions::new()).await?;
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "localhost", port).await, 0);
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__socks5_refused_destination__should_not_succeed(ctx: &mut Context) -> Result<()> {
+    let (inlet_addr, listener) = setup_socks5(ctx, TcpOutletOptions::new()).await?;
+    let port = listener.local_addr().unwrap().port();
+
+    // The port is not allowed
+    let mut stream = TcpStream::connect(&inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "localhost", port + 1).await, 2);
+
+    // The host is not allowed
+    let mut stream = TcpStream::connect(&inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "127.0.0.1", port).await, 2);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__socks5_unauthorized_connection__should_not_succeed(
+    ctx: &mut Context,
+) -> Result<()> {
+    let options = TcpOutletOptions::new().with_connection_access_control(Arc::new(DenyAll));
+    let (inlet_addr, listener) = setup_socks5(ctx, options).await?;
+    let port = listener.local_addr().unwrap().port();
+
+    let mut stream = TcpStream::connect(&inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "localhost", port).await, 2);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::test_utils::{start_http_proxy, start_silent_proxy, start_socks5_proxy};
+use ockam_transport_tcp::{
+    TcpConnectionOptions, TcpListenerOptions, TcpProxy, TcpProxySettings, TcpTransport, TCP,
+};
+use std::net::{Ipv4Addr, SocketAddr};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[ockam_macros::test]
+async fn http_proxy_with_basic_auth(ctx: &mut Context) -> Result<()> {
+    let (transport, port) = start_echoer(ctx).await?;
+    let proxy_stand_in = start_http_proxy(Some("user:password")).await;
+
+    let proxy =
+        TcpProxy::http(proxy_stand_in.address().to_string()).with_basic_auth("user", "password");
+    let connection = transport
+        .connect(
+            format!("echo.test:{port}"),
+            TcpConnectionOptions::new().with_proxy(proxy),
+        )
+        .await?;
+    // The target host name is resolved by the proxy
+    assert_eq!(
+        connection.socket_address(),
+        &SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))
+    );
+
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+    assert_eq!(proxy_stand_in.targets(), vec![format!("echo.test:{port}")]);
+
+    // The socket address of a target given as an IP address is known
+    let proxy =
+        TcpProxy::http(proxy_stand_in.address().to_string()).with_basic_auth("user", "password");
+    let target = SocketAddr::from(([10, 0, 0, 1], port));
+    let connection = transport
+        .connect(
+            target.to_string(),
+            TcpConnectionOptions::new().with_proxy(proxy),
+        )
+        .await?;
+    assert_eq!(connection.socket_address(), &target);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn http_proxy_refusing_the_credentials(ctx: &mut Context) -> Result<()> {
+    let (transport, port) = start_echoer(ctx).await?;
+    let proxy_stand_in = start_http_proxy(Some("user:password")).await;
+
+    let proxy =
+        TcpProxy::http(proxy_stand_in.address().to_string()).with_basic_auth("user", "wrong");
+    let res = transport
+        .connect(
+            format!("echo.test:{port}"),
+            TcpConnectionOptions::new().with_proxy(proxy),
+        )
+        .await;
+    assert!(res.is_err());
+    assert!(proxy_stand_in.targets().is_empty());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn socks5_proxy_as_the_default_proxy(ctx: &mut Context) -> Result<()> {
+    let (transport, port) = start_echoer(ctx).await?;
+    let proxy_stand_in = start_socks5_proxy("user", "password").await;
+
+    let proxy = TcpProxy::from_url(&format!(
+        "socks5://user:password@{}",
+        proxy_stand_in.address()
+    ))?;
+    transport.set_proxy_settings(Some(
+        TcpProxySettings::new(proxy).with_no_proxy(vec!["direct.test"]),
+    ));
+
+    // The connection is established when resolving the TCP address of the route
+    let route = ctx
+        .resolve_transport_route(route![(TCP, format!("echo.test:{port}")), "echoer"])
+        .await?;
+    let reply: String = ctx.send_and_receive(route, "Hello".to_string()).await?;
+    assert_eq!(reply, "Hello");
+    assert_eq!(proxy_stand_in.targets(), vec![format!("echo.test:{port}")]);
+
+    // Loopback addresses and NO_PROXY entries are reached directly
+    let route = ctx
+        .resolve_transport_route(route![(TCP, format!("127.0.0.1:{port}")), "echoer"])
+        .await?;
+    let reply: String = ctx.send_and_receive(route, "Hello".to_string()).await?;
+    assert_eq!(reply, "Hello");
+    let res = transport
+        .connect(format!("direct.test:{port}"), TcpConnectionOptions::new())
+        .await;
+    assert!(res.is_err());
+    assert_eq!(proxy_stand_in.targets().len(), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn proxy_handshake_timeout(ctx: &mut Context) -> Result<()> {
+    let (transport, port) = start_echoer(ctx).await?;
+    let proxy_address = start_silent_proxy().await;
+
+    let proxy = TcpProxy::http(proxy_address.to_string());
+    let res = transport
+        .connect(
+            format!("echo.test:{port}"),
+            TcpConnectionOptions::new().with_proxy(proxy),
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+/// Start an echoer behind a TCP listener and return the port of the listener
+async fn start_echoer(ctx: &Context) -> Result<(TcpTransport, u16)> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+    Ok((transport, listener.socket_address().port()))
+}
@@ -55,11 +55,12 @@
 
 use ockam::{
     errcode::{Kind, Origin},
+    identity::{secure_channels, SecureChannelOptions},
     workers::Echoer,
 };
 use ockam_core::{route, Error, Result};
 use ockam_node::Context;
-use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+use ockam_transport_udp::{UdpHolePuncher, UdpHolePuncherOptions, UdpTransport, UDP};
 use rand::Rng;
 use std::ops::Range;
 use tracing::{error, info};
@@ -67,6 +68,9 @@ use tracing::{error, info};
 /// Address of remote Rendezvous service
 const RENDEZVOUS: &str = "rendezvous";
 
+/// Address of remote secure channel listener
+const LISTENER: &str = "listener";
+
 /// Address of Echoer service
 const ECHOER: &str = "echoer";
 
@@ -102,8 +106,29 @@ async fn do_main(ctx: &mut Context) -> Result<()> {
     // Create transport, echoer service and puncher
     UdpTransport::create(ctx).await?;
     ctx.start_worker(ECHOER, Echoer).await?;
-    let rendezvous_route = route![(UDP, rendezvous_addr), RENDEZVOUS];
-    let mut puncher = UdpHolePuncher::create(ctx, &this_name, &that_name, rendezvous_route).await?;
+    let rendezvous_route = route![(UDP, rendezvous_addr.clone()), RENDEZVOUS];
+
+    // Create a secure channel to register our name with the Rendezvous service
+    let secure_channels = secure_channels();
+    let identity = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &identity.identifier(),
+            route![(UDP, rendezvous_addr), LISTENER],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let options =
+        UdpHolePuncherOptions::new().with_secure_rendezvous_route(route![channel, RENDEZVOUS]);
+
+    let mut puncher =
+        UdpHolePuncher::create_with_options(ctx, &this_name, &that_name, rendezvous_route, options)
+            .await?;
     info!("Puncher address = {:?}", puncher.address());
 
     // Wait for hole to open
@@ -1,6 +1,7 @@
+use ockam::identity::{secure_channels, SecureChannelListenerOptions};
 use ockam_core::Result;
 use ockam_node::Context;
-use ockam_transport_udp::{UdpRendezvousService, UdpTransport};
+use ockam_transport_udp::{UdpRendezvousService, UdpRendezvousServiceOptions, UdpTransport};
 use tracing::debug;
 
 #[ockam_macros::node]
@@ -11,7 +12,26 @@ async fn main(ctx: Context) -> Result<()> {
 
     debug!("Starting UDP Rendezvous service listening on {}", addr);
 
-    UdpRendezvousService::start(&ctx, "rendezvous").await?;
+    // Punchers register their names through a secure channel
+    let secure_channels = secure_channels();
+    let options = UdpRendezvousServiceOptions::new(secure_channels.identities().repository());
+    UdpRendezvousService::start(&ctx, "rendezvous", options).await?;
+
+    let identity = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            &ctx,
+            &identity.identifier(),
+            "listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("rendezvous", listener.flow_control_id());
 
     let udp = UdpTransport::create(&ctx).await?;
     udp.listen(addr).await?;
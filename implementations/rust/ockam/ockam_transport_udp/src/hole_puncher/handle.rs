@@ -1,7 +1,9 @@
 use super::message::PunchMessage;
-use crate::{hole_puncher::worker::UdpHolePunchWorker, PunchError};
+use crate::{hole_puncher::worker::UdpHolePunchWorker, PunchError, UdpHolePuncherOptions};
 use ockam_core::{Address, AllowOnwardAddress, AllowSourceAddress, Result, Route};
 use ockam_node::Context;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// High level management interface for UDP NAT Hole Punchers
 ///
@@ -28,15 +30,21 @@ use ockam_node::Context;
 /// ```rust
 /// # use {ockam_node::Context, ockam_core::{Result, route}};
 /// # async fn test(ctx: &mut Context) -> Result<()> {
-/// use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+/// use ockam_transport_udp::{UdpHolePuncher, UdpHolePuncherOptions, UdpTransport, UDP};
 ///
 /// // Create transport
 /// UdpTransport::create(ctx).await?;
 ///
 /// // Create a NAT hole from us 'alice' to them 'bob' using
-/// // the Rendezvous service 'zurg' at public IP address `192.168.1.10:4000`
+/// // the Rendezvous service 'zurg' at public IP address `192.168.1.10:4000`.
+/// // A secure channel to that node, here with address 'zurg_channel', is used
+/// // to register our name
 /// let rendezvous_route = route![(UDP, "192.168.1.10:4000"), "zurg"];
-/// let mut puncher = UdpHolePuncher::create(ctx, "alice", "bob", rendezvous_route).await?;
+/// let options = UdpHolePuncherOptions::new()
+///     .with_secure_rendezvous_route(route!["zurg_channel", "zurg"]);
+/// let mut puncher =
+///     UdpHolePuncher::create_with_options(ctx, "alice", "bob", rendezvous_route, options)
+///         .await?;
 ///
 /// // Note: For this to work, 'bob' will likewise need to create a hole thru to us
 ///
@@ -55,6 +63,8 @@ pub struct UdpHolePuncher {
     ctx: Context,
     worker_main_addr: Address,
     worker_local_addr: Address,
+    hole_open: Arc<AtomicBool>,
+    peer_address: Arc<Mutex<Option<String>>>,
 }
 
 // TODO: Allow app to specify how often keepalives are used - they may have
@@ -62,11 +72,33 @@ pub struct UdpHolePuncher {
 
 impl UdpHolePuncher {
     /// Create a new UDP NAT Hole Puncher
+    ///
+    /// `rendezvous_route` is a route to the Rendezvous service over UDP. Our
+    /// puncher name is registered anonymously, see [`Self::create_with_options`]
+    /// to register it through a secure channel.
     pub async fn create<S: AsRef<str>, R: Into<Route>>(
         ctx: &mut Context,
         puncher_name: S,
         peer_puncher_name: S,
         rendezvous_route: R,
+    ) -> Result<UdpHolePuncher> {
+        Self::create_with_options(
+            ctx,
+            puncher_name,
+            peer_puncher_name,
+            rendezvous_route,
+            UdpHolePuncherOptions::new(),
+        )
+        .await
+    }
+
+    /// Create a new UDP NAT Hole Puncher with the given options
+    pub async fn create_with_options<S: AsRef<str>, R: Into<Route>>(
+        ctx: &mut Context,
+        puncher_name: S,
+        peer_puncher_name: S,
+        rendezvous_route: R,
+        options: UdpHolePuncherOptions,
     ) -> Result<UdpHolePuncher> {
         // Check if we can reach the rendezvous service
         let rendezvous_route = rendezvous_route.into();
@@ -77,12 +109,17 @@ impl UdpHolePuncher {
 
         // Create worker
         let handle_addr = Address::random_tagged("UdpHolePuncher.detached");
+        let hole_open = Arc::new(AtomicBool::new(false));
+        let peer_address = Arc::new(Mutex::new(None));
         let (worker_main_addr, worker_local_addr) = UdpHolePunchWorker::create(
             ctx,
             &handle_addr,
             rendezvous_route,
+            options,
             puncher_name.as_ref(),
             peer_puncher_name.as_ref(),
+            hole_open.clone(),
+            peer_address.clone(),
         )
         .await?;
 
@@ -99,6 +136,8 @@ impl UdpHolePuncher {
             ctx: handle_ctx,
             worker_main_addr,
             worker_local_addr,
+            hole_open,
+            peer_address,
         })
     }
 
@@ -117,6 +156,25 @@ impl UdpHolePuncher {
         Ok(())
     }
 
+    /// Is the hole to the peer currently open?
+    ///
+    /// The hole is considered closed when nothing was received from the peer
+    /// for a while.
+    pub fn is_hole_open(&self) -> bool {
+        self.hole_open.load(Ordering::Relaxed)
+    }
+
+    /// UDP address of the peer's puncher, as last returned by the
+    /// Rendezvous service
+    pub fn peer_address(&self) -> Option<String> {
+        self.peer_address.lock().ok().and_then(|a| a.clone())
+    }
+
+    /// Stop this UDP NAT Hole Puncher
+    pub async fn stop(&self) -> Result<()> {
+        self.ctx.stop_worker(self.worker_main_addr.clone()).await
+    }
+
     /// Address of this UDP NAT Hole Puncher's worker.
     pub fn address(&self) -> Address {
         self.worker_local_addr.clone()
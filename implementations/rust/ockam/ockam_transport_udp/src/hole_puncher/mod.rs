@@ -1,7 +1,9 @@
 pub use error::PunchError;
 pub use handle::UdpHolePuncher;
+pub use options::{UdpHolePuncherOptions, DEFAULT_HOLE_OPEN_TIMEOUT};
 
 mod error;
 mod handle;
 mod message;
+mod options;
 mod worker;
@@ -0,0 +1,48 @@
+use ockam_core::Route;
+use std::time::Duration;
+
+/// Default time after which the hole is considered closed if nothing was
+/// received from the peer
+pub const DEFAULT_HOLE_OPEN_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Options for a [`UdpHolePuncher`](crate::UdpHolePuncher)
+#[derive(Debug, Clone)]
+pub struct UdpHolePuncherOptions {
+    pub(crate) secure_rendezvous_route: Option<Route>,
+    pub(crate) hole_open_timeout: Duration,
+}
+
+impl Default for UdpHolePuncherOptions {
+    fn default() -> Self {
+        Self {
+            secure_rendezvous_route: None,
+            hole_open_timeout: DEFAULT_HOLE_OPEN_TIMEOUT,
+        }
+    }
+}
+
+impl UdpHolePuncherOptions {
+    /// Default options: the puncher registers its name anonymously, which
+    /// requires a Rendezvous service accepting anonymous registrations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time after which the hole is considered closed if nothing was
+    /// received from the peer
+    ///
+    /// The peer is pinged every second while the hole is open.
+    pub fn with_hole_open_timeout(mut self, timeout: Duration) -> Self {
+        self.hole_open_timeout = timeout;
+        self
+    }
+
+    /// Register the name of the puncher through a secure channel, given a
+    /// route to the Rendezvous service through that channel
+    ///
+    /// The name is then bound to the identity of the channel.
+    pub fn with_secure_rendezvous_route(mut self, route: impl Into<Route>) -> Self {
+        self.secure_rendezvous_route = Some(route.into());
+        self
+    }
+}
@@ -1,17 +1,21 @@
 use crate::hole_puncher::message::PunchMessage;
-use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
-use crate::PunchError;
+use crate::rendezvous_service::{
+    AnnounceRequest, RegisterRequest, Registration, UdpRendezvousClient,
+};
+use crate::{PunchError, UdpHolePuncherOptions};
+use minicbor::Decoder;
+use ockam_core::api::{Request, Response, Status};
 use ockam_core::{
     Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox, Mailboxes, Result, Route,
     Routed, Worker,
 };
-use ockam_node::{Context, DelayedEvent, MessageSendReceiveOptions, WorkerBuilder};
-use std::sync::Arc;
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 const HEARTBEAT_EVERY: Duration = Duration::from_secs(1);
-const HOLE_OPEN_TIMEOUT: Duration = Duration::from_secs(20);
 const PING_TRIES: usize = 5;
 
 // UDP and NAT Hole Punching are unreliable protocols. Expect send and receive
@@ -64,14 +68,25 @@ pub(crate) struct UdpHolePunchWorker {
     handle_addr: Address,
     /// For generating internal heartbeat messages
     heartbeat: DelayedEvent<PunchMessage>,
-    /// Route to Rendezvous service
+    /// Route to Rendezvous service, over UDP
     rendezvous_route: Route,
+    /// Client for the Rendezvous service, over UDP
+    rendezvous: UdpRendezvousClient,
+    /// Client for the Rendezvous service, through a secure channel, unless
+    /// our name is registered anonymously
+    secure_rendezvous: Option<UdpRendezvousClient>,
+    /// Time at which our registration with the Rendezvous service must be renewed
+    renew_registration_at: Option<Instant>,
     /// Name of this puncher
     this_puncher_name: String,
     /// Name of peer node's puncher
     peer_puncher_name: String,
-    /// Is hole open to peer?
-    hole_open: bool,
+    /// Is hole open to peer? Shared with our handle
+    hole_open: Arc<AtomicBool>,
+    /// Time after which the hole is considered closed if nothing was received from peer
+    hole_open_timeout: Duration,
+    /// UDP address of peer node's puncher, shared with our handle
+    peer_address: Arc<Mutex<Option<String>>>,
     /// Route to peer node's puncher
     peer_route: Option<Route>,
     /// Timestamp of most recent message received from peer
@@ -83,65 +98,100 @@ pub(crate) struct UdpHolePunchWorker {
 
 impl UdpHolePunchWorker {
     /// Update the Rendezvous service
-    async fn rendezvous_update(&self, ctx: &mut Context) -> Result<()> {
-        let msg = RendezvousRequest::Update {
-            puncher_name: self.this_puncher_name.clone(),
+    ///
+    /// Our name is registered through the secure channel, then the
+    /// registration is announced over UDP so that the service learns our
+    /// public address and our main address.
+    ///
+    /// Anonymous registrations are sent over UDP from our main address
+    /// instead, and are renewed once their response is received.
+    async fn rendezvous_update(&mut self, ctx: &mut Context) -> Result<()> {
+        let secure_rendezvous = match &self.secure_rendezvous {
+            Some(client) => client,
+            None => {
+                let req = Request::post("registrations")
+                    .body(RegisterRequest::new(&self.this_puncher_name));
+                return ctx.send(self.rendezvous_route.clone(), req.to_vec()?).await;
+            }
         };
-        ctx.send(self.rendezvous_route.clone(), msg).await
-    }
+        let registration = secure_rendezvous.register(&self.this_puncher_name).await?;
 
-    /// Query the Rendezvous service
-    async fn rendezvous_query(&self, ctx: &mut Context) -> Result<Route> {
-        let msg = RendezvousRequest::Query {
-            puncher_name: self.peer_puncher_name.clone(),
-        };
+        // The response to the announcement is ignored
+        let req = Request::post("announcements").body(AnnounceRequest::new(registration.token()));
+        ctx.send(self.rendezvous_route.clone(), req.to_vec()?)
+            .await?;
 
-        // Send from a temporary context/address, so we can process the reply here
-        let res = ctx
-            .send_and_receive_extended::<RendezvousResponse>(
-                self.rendezvous_route.clone(),
-                msg,
-                MessageSendReceiveOptions::new().with_timeout(QUICK_TIMEOUT),
-            )
-            .await?
-            .body();
+        self.schedule_renewal(&registration);
+        Ok(())
+    }
+
+    /// Renew the registration before it expires
+    fn schedule_renewal(&mut self, registration: &Registration) {
+        let ttl = Duration::from_secs(registration.ttl());
+        self.renew_registration_at = Some(Instant::now() + ttl / 2);
+    }
 
-        match res {
-            RendezvousResponse::Query(r) => r,
-            _ => Err(PunchError::Internal.into()),
+    /// Handle the responses of the Rendezvous service received by our main
+    /// address, only those to anonymous registrations are used
+    fn handle_rendezvous_response(&mut self, payload: &[u8]) {
+        let mut dec = Decoder::new(payload);
+        let registration = match dec.decode::<Response>() {
+            Ok(res) if res.status() == Some(Status::Ok) && res.has_body() => {
+                dec.decode::<Registration>().ok()
+            }
+            _ => None,
+        };
+        if let Some(registration) = registration {
+            trace!("Anonymous registration renewed");
+            self.schedule_renewal(&registration);
         }
     }
 
+    /// Query the Rendezvous service
+    async fn rendezvous_query(&self) -> Result<Route> {
+        let entry = self
+            .secure_rendezvous
+            .as_ref()
+            .unwrap_or(&self.rendezvous)
+            .query(&self.peer_puncher_name)
+            .await?;
+        trace!(
+            "Peer puncher {} is registered by {:?}",
+            entry.puncher_name(),
+            entry.identifier()
+        );
+        Ok(entry.route().clone())
+    }
+
     /// Test to see if we can reach the Rendezvous service
-    pub(crate) async fn rendezvous_reachable(ctx: &mut Context, rendezvous_route: &Route) -> bool {
+    pub(crate) async fn rendezvous_reachable(ctx: &Context, rendezvous_route: &Route) -> bool {
+        let client = match UdpRendezvousClient::new(ctx, rendezvous_route.clone()).await {
+            Ok(client) => client.with_timeout(QUICK_TIMEOUT),
+            Err(_) => return false,
+        };
         for _ in 0..PING_TRIES {
             trace!("Start attempt to check Rendezvous service reachability");
-            let res: Result<Routed<RendezvousResponse>> = ctx
-                .send_and_receive_extended(
-                    rendezvous_route.clone(),
-                    RendezvousRequest::Ping,
-                    MessageSendReceiveOptions::new().with_timeout(QUICK_TIMEOUT),
-                )
-                .await;
 
             // Check response. Ignore all but success.
-            if let Ok(msg) = res {
-                if let RendezvousResponse::Pong = msg.body() {
-                    trace!("Success reaching Rendezvous service");
-                    return true;
-                };
+            if client.ping().await.is_ok() {
+                trace!("Success reaching Rendezvous service");
+                return true;
             }
         }
         trace!("Failed to reach Rendezvous service");
         false
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create(
         ctx: &Context,
         handle_addr: &Address,
         rendezvous_route: Route,
+        options: UdpHolePuncherOptions,
         this_puncher_name: &str,
         peer_puncher_name: &str,
+        hole_open: Arc<AtomicBool>,
+        peer_address: Arc<Mutex<Option<String>>>,
     ) -> Result<(Address, Address)> {
         // Create worker' addresses, heartbeat & mailboxes
         let main_addr =
@@ -170,6 +220,19 @@ impl UdpHolePunchWorker {
             Arc::new(AllowAll), // FIXME: @ac
         );
 
+        let rendezvous = UdpRendezvousClient::new(ctx, rendezvous_route.clone())
+            .await?
+            .with_timeout(QUICK_TIMEOUT);
+        let hole_open_timeout = options.hole_open_timeout;
+        let secure_rendezvous = match options.secure_rendezvous_route {
+            Some(route) => Some(
+                UdpRendezvousClient::new(ctx, route)
+                    .await?
+                    .with_timeout(QUICK_TIMEOUT),
+            ),
+            None => None,
+        };
+
         // Create and start worker
         let worker = Self {
             main_addr: main_addr.clone(),
@@ -177,9 +240,14 @@ impl UdpHolePunchWorker {
             handle_addr: handle_addr.clone(),
             heartbeat,
             rendezvous_route,
+            rendezvous,
+            secure_rendezvous,
+            renew_registration_at: None,
             this_puncher_name: String::from(this_puncher_name),
             peer_puncher_name: String::from(peer_puncher_name),
-            hole_open: false,
+            hole_open,
+            hole_open_timeout,
+            peer_address,
             peer_route: None,
             peer_received_at: Instant::now(),
             wait_for_hole_open_addr: None,
@@ -194,7 +262,7 @@ impl UdpHolePunchWorker {
 
     /// Update state to show the hole to peer is now open
     async fn set_hole_open(&mut self, ctx: &Context) -> Result<()> {
-        self.hole_open = true;
+        self.hole_open.store(true, Ordering::Relaxed);
 
         // Inform handle, if needed
         let addr = self.wait_for_hole_open_addr.take();
@@ -250,32 +318,48 @@ impl UdpHolePunchWorker {
         Ok(())
     }
 
+    fn is_hole_open(&self) -> bool {
+        self.hole_open.load(Ordering::Relaxed)
+    }
+
     /// Handle heartbeat messages
     async fn handle_heartbeat(&mut self, ctx: &mut Context) -> Result<()> {
         debug!(
             "Heartbeat => Puncher: hole_open = {:?}, peer_route = {:?}",
-            self.hole_open, self.peer_route
+            self.is_hole_open(),
+            self.peer_route
         );
 
         // Schedule next heartbeat here in case something below errors
         self.heartbeat.schedule(HEARTBEAT_EVERY).await?;
 
         // If we have not heard from peer for a while, consider hole as closed
-        if self.hole_open && self.peer_received_at.elapsed() >= HOLE_OPEN_TIMEOUT {
+        if self.is_hole_open() && self.peer_received_at.elapsed() >= self.hole_open_timeout {
             trace!("Not heard from peer for a while. Setting as hole closed.",);
-            self.hole_open = false;
+            self.hole_open.store(false, Ordering::Relaxed);
         }
 
-        if !self.hole_open {
+        // Update Rendezvous service when the hole is closed or our registration is about to expire
+        let renew_registration = match self.renew_registration_at {
+            Some(at) => at <= Instant::now(),
+            None => true,
+        };
+        if !self.is_hole_open() || renew_registration {
+            if let Err(e) = self.rendezvous_update(ctx).await {
+                warn!("Failed to update the Rendezvous service: {}", e);
+            }
+        }
+
+        if !self.is_hole_open() {
             // Attempt hole open if it is closed
             trace!("Hole closed. Will attempt to open hole to peer");
 
-            // Update Rendezvous service
-            self.rendezvous_update(ctx).await?;
-
             // Query Rendezvous service
-            if let Ok(peer_route) = self.rendezvous_query(ctx).await {
+            if let Ok(peer_route) = self.rendezvous_query().await {
                 self.peer_route = Some(peer_route.clone());
+                if let Ok(mut peer_address) = self.peer_address.lock() {
+                    *peer_address = peer_route.next().ok().map(|a| a.address().to_string());
+                }
 
                 // Ping peer
                 ctx.send(peer_route.clone(), PunchMessage::Ping).await?;
@@ -329,6 +413,7 @@ impl Worker for UdpHolePunchWorker {
 
     async fn shutdown(&mut self, _context: &mut Self::Context) -> Result<()> {
         self.heartbeat.cancel();
+        self.hole_open.store(false, Ordering::Relaxed);
         Ok(())
     }
 
@@ -364,6 +449,10 @@ impl Worker for UdpHolePunchWorker {
                         }
                         _ => return Err(PunchError::Internal.into()),
                     }
+                } else {
+                    // e.g. responses of the Rendezvous service to our registrations
+                    trace!("Message from {}", return_route);
+                    self.handle_rendezvous_response(msg.payload());
                 }
             }
 
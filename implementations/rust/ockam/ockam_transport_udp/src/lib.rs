@@ -9,12 +9,16 @@
 // with command `cargo run --example client`
 use ockam_core::TransportType;
 
-pub use hole_puncher::{PunchError, UdpHolePuncher};
-pub use rendezvous_service::UdpRendezvousService;
+pub use hole_puncher::{
+    PunchError, UdpHolePuncher, UdpHolePuncherOptions, DEFAULT_HOLE_OPEN_TIMEOUT,
+};
+pub use options::*;
+pub use rendezvous_service::*;
 pub use transport::UdpTransport;
 pub use transport::UdpTransportExtension;
 
 mod hole_puncher;
+mod options;
 mod rendezvous_service;
 mod router;
 mod transport;
@@ -0,0 +1,81 @@
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+use std::time::Duration;
+
+/// Default maximum size of a datagram, it fits in the minimum IPv6 MTU
+pub const DEFAULT_MAX_DATAGRAM_SIZE: usize = 1200;
+
+/// Default time after which an incomplete message is dropped
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options applied to every socket opened by a [`UdpTransport`](crate::UdpTransport)
+///
+/// Messages larger than the maximum datagram size are split into several
+/// datagrams and reassembled by the receiving transport.
+#[derive(Debug, Clone)]
+pub struct UdpTransportOptions {
+    pub(crate) max_datagram_size: usize,
+    pub(crate) reassembly_timeout: Duration,
+}
+
+impl Default for UdpTransportOptions {
+    fn default() -> Self {
+        Self {
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+            reassembly_timeout: DEFAULT_REASSEMBLY_TIMEOUT,
+        }
+    }
+}
+
+impl UdpTransportOptions {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum size of the datagrams sent by the transport, headers included
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    /// Set the time after which a message which didn't receive all its datagrams is dropped
+    pub fn with_reassembly_timeout(mut self, reassembly_timeout: Duration) -> Self {
+        self.reassembly_timeout = reassembly_timeout;
+        self
+    }
+}
+
+/// Options applied to a socket opened by [`UdpTransport::connect_with_options`](crate::UdpTransport::connect_with_options)
+///
+/// The listener of the socket is marked as a Producer, so the messages
+/// coming from the peer are only delivered to the consumers of its [`FlowControlId`].
+#[derive(Debug, Clone)]
+pub struct UdpConnectionOptions {
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) reliable_delivery: bool,
+}
+
+impl UdpConnectionOptions {
+    #[allow(clippy::new_without_default)]
+    /// Default options, messages are sent without delivery guarantees.
+    /// The listener is marked as a Producer with a random [`FlowControlId`]
+    pub fn new() -> Self {
+        Self {
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            reliable_delivery: false,
+        }
+    }
+
+    /// Getter for freshly generated [`FlowControlId`]
+    pub fn flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+
+    /// Retransmit lost datagrams and deliver messages in order
+    ///
+    /// The peer replies with reliable delivery as well.
+    pub fn with_reliable_delivery(mut self) -> Self {
+        self.reliable_delivery = true;
+        self
+    }
+}
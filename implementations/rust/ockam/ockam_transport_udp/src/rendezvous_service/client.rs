@@ -0,0 +1,62 @@
+use crate::rendezvous_service::{AnnounceRequest, RegisterRequest, Registration, RendezvousEntry};
+use ockam_core::api::Request;
+use ockam_core::{Result, Route};
+use ockam_node::{Context, RpcClient};
+use std::time::Duration;
+
+/// Client for a [`UdpRendezvousService`](crate::UdpRendezvousService)
+///
+/// Registrations, queries and listing must be sent through a secure channel,
+/// announcements and pings must be sent over UDP.
+pub struct UdpRendezvousClient {
+    client: RpcClient,
+}
+
+impl UdpRendezvousClient {
+    /// Create a client for the service at the given route
+    pub async fn new(ctx: &Context, route: impl Into<Route>) -> Result<Self> {
+        Ok(Self {
+            client: RpcClient::new(route.into(), ctx).await?,
+        })
+    }
+
+    /// Set the time after which requests fail if no response was received
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            client: self.client.with_timeout(timeout),
+        }
+    }
+
+    /// Check that the service is reachable
+    pub async fn ping(&self) -> Result<()> {
+        self.client
+            .request_no_resp_body(&Request::get("ping"))
+            .await
+    }
+
+    /// Register a puncher name, bound to the identity of the secure channel
+    pub async fn register(&self, puncher_name: &str) -> Result<Registration> {
+        self.client
+            .request(&Request::post("registrations").body(RegisterRequest::new(puncher_name)))
+            .await
+    }
+
+    /// Announce a registration token, the service records the UDP address it was sent from
+    pub async fn announce(&self, token: &str) -> Result<()> {
+        self.client
+            .request_no_resp_body(&Request::post("announcements").body(AnnounceRequest::new(token)))
+            .await
+    }
+
+    /// Return the entry of a registered puncher
+    pub async fn query(&self, puncher_name: &str) -> Result<RendezvousEntry> {
+        self.client
+            .request(&Request::get(format!("registrations/{puncher_name}")))
+            .await
+    }
+
+    /// Return the entries of all the registered punchers
+    pub async fn list(&self) -> Result<Vec<RendezvousEntry>> {
+        self.client.request(&Request::get("registrations")).await
+    }
+}
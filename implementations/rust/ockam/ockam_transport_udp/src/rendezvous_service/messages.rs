@@ -1,30 +1,128 @@
-use ockam_core::{Message, Result, Route};
-use serde::{Deserialize, Serialize};
-
-// TODO: Change this Request/Response protocol to use CBOR encoding for messages.
-
-/// Request type for UDP Hole Punching Rendezvous service
-#[derive(Serialize, Deserialize, Debug, Message)]
-pub enum RendezvousRequest {
-    /// Update service's internal table with the
-    /// details of the sending node.
-    Update {
-        /// Name of sending node's puncher
-        puncher_name: String,
-    },
-    /// Query service's internal table for the public
-    /// route to the named node.
-    Query {
-        /// Name of puncher to lookup
-        puncher_name: String,
-    },
-    /// Ping service to see if it is reachable and working.
-    Ping,
-}
-
-/// Response type for UDP Hole Punching Rendezvous service
-#[derive(Serialize, Deserialize, Debug, Message)]
-pub enum RendezvousResponse {
-    Query(Result<Route>),
-    Pong,
+use minicbor::{Decode, Encode};
+use ockam_core::Route;
+use ockam_identity::IdentityIdentifier;
+
+/// Request to register a puncher name with the Rendezvous service
+///
+/// Must be sent through a secure channel, the name is bound to the
+/// identity of the channel. Services accepting anonymous registrations also
+/// accept requests sent over UDP, from the socket used for hole punching,
+/// which don't need to be announced.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RegisterRequest {
+    #[n(1)] puncher_name: String,
+}
+
+impl RegisterRequest {
+    pub fn new(puncher_name: impl Into<String>) -> Self {
+        Self {
+            puncher_name: puncher_name.into(),
+        }
+    }
+
+    pub fn puncher_name(&self) -> &str {
+        &self.puncher_name
+    }
+}
+
+/// Response of the Rendezvous service to a [`RegisterRequest`]
+///
+/// The token must be announced over UDP, from the socket used for hole
+/// punching, for the service to learn the public address of the puncher.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Registration {
+    #[n(1)] token: String,
+    /// Lifetime of the registration, in seconds
+    #[n(2)] ttl: u64,
+}
+
+impl Registration {
+    pub fn new(token: impl Into<String>, ttl: u64) -> Self {
+        Self {
+            token: token.into(),
+            ttl,
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Lifetime of the registration, in seconds
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+}
+
+/// Request announcing a registration token over UDP
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AnnounceRequest {
+    #[n(1)] token: String,
+}
+
+impl AnnounceRequest {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// A puncher registered with the Rendezvous service
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RendezvousEntry {
+    #[n(1)] puncher_name: String,
+    /// Identity which registered the puncher, if it wasn't registered anonymously
+    #[n(2)] identifier: Option<IdentityIdentifier>,
+    /// Public route to the puncher, starting with its UDP address
+    #[n(3)] route: Route,
+    /// Time before the entry expires, in seconds
+    #[n(4)] expires_in: u64,
+}
+
+impl RendezvousEntry {
+    pub fn new(
+        puncher_name: impl Into<String>,
+        identifier: Option<IdentityIdentifier>,
+        route: Route,
+        expires_in: u64,
+    ) -> Self {
+        Self {
+            puncher_name: puncher_name.into(),
+            identifier,
+            route,
+            expires_in,
+        }
+    }
+
+    pub fn puncher_name(&self) -> &str {
+        &self.puncher_name
+    }
+
+    /// Identity which registered the puncher, if it wasn't registered anonymously
+    pub fn identifier(&self) -> Option<&IdentityIdentifier> {
+        self.identifier.as_ref()
+    }
+
+    /// Public route to the puncher, starting with its UDP address
+    pub fn route(&self) -> &Route {
+        &self.route
+    }
+
+    /// Time before the entry expires, in seconds
+    pub fn expires_in(&self) -> u64 {
+        self.expires_in
+    }
 }
@@ -1,5 +1,9 @@
-pub(crate) use messages::{RendezvousRequest, RendezvousResponse};
+pub use client::UdpRendezvousClient;
+pub use messages::{AnnounceRequest, RegisterRequest, Registration, RendezvousEntry};
+pub use options::*;
 pub use rendezvous::UdpRendezvousService;
 
+mod client;
 mod messages;
+mod options;
 mod rendezvous;
@@ -0,0 +1,71 @@
+use ockam_abac::Expr;
+use ockam_core::compat::sync::Arc;
+use ockam_identity::IdentitiesRepository;
+use std::time::Duration;
+
+/// Default lifetime of a registration with the Rendezvous service
+pub const DEFAULT_REGISTRATION_TTL: Duration = Duration::from_secs(60);
+
+/// Options for a [`UdpRendezvousService`](crate::UdpRendezvousService)
+///
+/// Policies are ABAC expressions evaluated with the attributes of the
+/// calling identity as `subject.*`, as found in the identities repository of
+/// the service.
+#[derive(Clone)]
+pub struct UdpRendezvousServiceOptions {
+    pub(crate) ttl: Duration,
+    pub(crate) query_policy: Expr,
+    pub(crate) list_policy: Expr,
+    pub(crate) anonymous_registrations: bool,
+    pub(crate) identities_repository: Arc<dyn IdentitiesRepository>,
+}
+
+impl UdpRendezvousServiceOptions {
+    /// Default options: every identity can query every puncher, no identity
+    /// can list the registered punchers, and registrations must be sent
+    /// through a secure channel
+    ///
+    /// The attributes of the calling identities are read from
+    /// `identities_repository`, usually the repository of the node running
+    /// the service.
+    pub fn new(identities_repository: Arc<dyn IdentitiesRepository>) -> Self {
+        Self {
+            ttl: DEFAULT_REGISTRATION_TTL,
+            query_policy: Expr::Bool(true),
+            list_policy: Expr::Bool(false),
+            anonymous_registrations: false,
+            identities_repository,
+        }
+    }
+
+    /// Set the time after which a registration which wasn't renewed expires
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the policy deciding if an identity can query a puncher
+    ///
+    /// The queried puncher is available as `resource.puncher_name`, and the
+    /// identity which registered it as `resource.identifier`.
+    pub fn with_query_policy(mut self, policy: Expr) -> Self {
+        self.query_policy = policy;
+        self
+    }
+
+    /// Set the policy deciding if an identity can list the registered punchers
+    pub fn with_list_policy(mut self, policy: Expr) -> Self {
+        self.list_policy = policy;
+        self
+    }
+
+    /// Accept registrations and queries sent over plain UDP
+    ///
+    /// Anonymous punchers can't register the names registered by an identity,
+    /// and can only query the other anonymous punchers. Their names can be
+    /// taken over by any other anonymous puncher.
+    pub fn with_anonymous_registrations(mut self) -> Self {
+        self.anonymous_registrations = true;
+        self
+    }
+}
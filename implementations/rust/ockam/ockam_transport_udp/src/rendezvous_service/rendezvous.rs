@@ -1,12 +1,23 @@
-use crate::{
-    rendezvous_service::{RendezvousRequest, RendezvousResponse},
-    UDP,
+use crate::rendezvous_service::{
+    AnnounceRequest, RegisterRequest, Registration, RendezvousEntry, UdpRendezvousServiceOptions,
 };
-use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{async_trait, Address, Error, Result, Route, Routed, Worker};
+use crate::UDP;
+use minicbor::Decoder;
+use ockam_abac::expr::str;
+use ockam_abac::{AbacAccessControl, Env, Expr};
+use ockam_core::api::{Error, Id, Method, Request, Response, ResponseBuilder, Status};
+use ockam_core::{async_trait, Address, Result, Route, Routed, Worker};
+use ockam_identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
 use ockam_node::Context;
 use std::collections::BTreeMap;
-use tracing::{debug, trace, warn};
+use std::time::Instant;
+use tracing::{debug, error, trace, warn};
+
+/// Maximum number of registrations, announced or not, kept by the service
+const MAX_REGISTRATIONS: usize = 4096;
+
+/// Maximum number of puncher names registered by a single identity
+const MAX_REGISTRATIONS_PER_IDENTITY: usize = 64;
 
 /// High level management interface for UDP Rendezvous Service
 ///
@@ -14,55 +25,83 @@ use tracing::{debug, trace, warn};
 ///
 /// A node could start multiple Rendezvous services, each with its own address.
 ///
-/// To work, this service requires the UDP Transport to be working.
+/// To work, this service requires the UDP Transport to be working. Punchers
+/// register their name through a secure channel, so that it is bound to
+/// their identity, then announce the registration over UDP so that the
+/// service learns their public address. Registrations expire unless they
+/// are renewed. Services can also accept anonymous registrations, sent over
+/// UDP. See [`UdpRendezvousServiceOptions`].
 ///
 /// # Example
 ///
 /// ```rust
-/// use ockam_transport_udp::{UdpTransport, UdpRendezvousService};
+/// use ockam_transport_udp::{UdpTransport, UdpRendezvousService, UdpRendezvousServiceOptions};
+/// # use ockam_identity::secure_channels::secure_channels;
 /// # use ockam_node::Context;
 /// # use ockam_core::Result;
 /// # async fn test(ctx: Context) -> Result<()> {
+/// # let secure_channels = secure_channels();
 ///
 /// // Start a Rendezvous service with address 'my_rendezvous' and listen on UDP port 4000
-/// UdpRendezvousService::start(&ctx, "my_rendezvous").await?;
+/// let options = UdpRendezvousServiceOptions::new(secure_channels.identities().repository());
+/// UdpRendezvousService::start(&ctx, "my_rendezvous", options).await?;
 /// let udp = UdpTransport::create(&ctx).await?;
 /// udp.listen("0.0.0.0:4000").await?;
+///
+/// // Secure channels to the service must be allowed to reach it, for instance:
+/// // ctx.flow_controls().add_consumer("my_rendezvous", &listener.flow_control_id());
 /// # Ok(()) }
 /// ```
 pub struct UdpRendezvousService;
 
 impl UdpRendezvousService {
     /// Start a new Rendezvous service with the given local address
-    pub async fn start(ctx: &Context, address: impl Into<Address>) -> Result<()> {
-        ctx.start_worker(address.into(), RendezvousWorker::new())
+    pub async fn start(
+        ctx: &Context,
+        address: impl Into<Address>,
+        options: UdpRendezvousServiceOptions,
+    ) -> Result<()> {
+        ctx.start_worker(address.into(), RendezvousWorker::new(options))
             .await
     }
 }
 
-// TODO: Implement mechanism for removing entries from internal map, possibly by
-// deleting 'old' entries on heartbeat events.
+/// A puncher name registered by an identity, or anonymously
+#[derive(Debug)]
+struct RegisteredPuncher {
+    identifier: Option<IdentityIdentifier>,
+    /// Public route to the puncher, known once the registration was announced
+    route: Option<Route>,
+    expires_at: Instant,
+}
+
+/// A registration token which wasn't announced yet
+#[derive(Debug)]
+struct PendingToken {
+    puncher_name: String,
+    identifier: Option<IdentityIdentifier>,
+    expires_at: Instant,
+}
 
 /// Worker for the UDP NAT Hole Punching Rendezvous service
 ///
 /// Maintains an internal map for remote nodes and the public IP address
 /// from which they send UDP datagrams.
 ///
-/// Remote nodes can send requests to update and query the map.
+/// Remote nodes can send requests to register, announce, query and list
+/// the entries of the map.
 struct RendezvousWorker {
-    map: BTreeMap<String, Route>,
-}
-
-impl Default for RendezvousWorker {
-    fn default() -> Self {
-        Self::new()
-    }
+    options: UdpRendezvousServiceOptions,
+    punchers: BTreeMap<String, RegisteredPuncher>,
+    tokens: BTreeMap<String, PendingToken>,
 }
 
 impl RendezvousWorker {
-    fn new() -> Self {
+    fn new(options: UdpRendezvousServiceOptions) -> Self {
         Self {
-            map: BTreeMap::new(),
+            options,
+            punchers: BTreeMap::new(),
+            tokens: BTreeMap::new(),
         }
     }
 
@@ -81,33 +120,305 @@ impl RendezvousWorker {
         res.into()
     }
 
-    // Handle Update request
-    fn handle_update(&mut self, puncher_name: &str, return_route: &Route) {
-        let r = Self::parse_route(return_route);
-        if !r.is_empty() {
-            self.map.insert(puncher_name.to_owned(), r);
-        } else {
+    /// Remove the expired registrations and tokens
+    fn remove_expired(&mut self, now: Instant) {
+        self.punchers.retain(|_, p| p.expires_at > now);
+        self.tokens.retain(|_, t| t.expires_at > now);
+    }
+
+    /// Evaluate a policy for the given identity, and optionally a registered puncher
+    async fn is_authorized(
+        &self,
+        policy: &Expr,
+        identifier: &IdentityIdentifier,
+        resource: Option<(&str, &RegisteredPuncher)>,
+    ) -> Result<bool> {
+        let mut env = Env::new();
+        if let Some((puncher_name, puncher)) = resource {
+            env.put("resource.puncher_name", str(puncher_name));
+            if let Some(identifier) = &puncher.identifier {
+                env.put("resource.identifier", str(identifier.to_string()));
+            }
+        }
+        AbacAccessControl::new(
+            self.options.identities_repository.clone(),
+            policy.clone(),
+            env,
+        )
+        .is_identity_authorized(identifier.clone())
+        .await
+    }
+
+    fn entry(
+        puncher_name: &str,
+        puncher: &RegisteredPuncher,
+        now: Instant,
+    ) -> Option<RendezvousEntry> {
+        let route = puncher.route.clone()?;
+        Some(RendezvousEntry::new(
+            puncher_name,
+            puncher.identifier.clone(),
+            route,
+            puncher.expires_at.saturating_duration_since(now).as_secs(),
+        ))
+    }
+
+    // Handle Register request
+    fn handle_register(
+        &mut self,
+        req: &Request<'_>,
+        body: RegisterRequest,
+        caller: Option<IdentityIdentifier>,
+        return_route: &Route,
+        now: Instant,
+    ) -> Result<Vec<u8>> {
+        // Anonymous registrations are sent over UDP, and announce themselves
+        let route = match &caller {
+            Some(_) => None,
+            None if self.options.anonymous_registrations => {
+                let route = Self::parse_route(return_route);
+                if route.is_empty() {
+                    return Self::error(
+                        req,
+                        Status::BadRequest,
+                        "anonymous registrations must be sent over UDP",
+                    );
+                }
+                Some(route)
+            }
+            None => return Self::error(req, Status::Unauthorized, "a secure channel is required"),
+        };
+        let puncher_name = body.puncher_name().to_string();
+
+        match self.punchers.get_mut(&puncher_name) {
+            Some(puncher) if puncher.identifier != caller => {
+                warn!(?caller, %puncher_name, "Puncher name is registered by another identity");
+                return Self::error(
+                    req,
+                    Status::Conflict,
+                    "the puncher name is registered by another identity",
+                );
+            }
+            Some(puncher) => {
+                puncher.expires_at = now + self.options.ttl;
+                if route.is_some() {
+                    puncher.route = route;
+                }
+            }
+            None => {
+                if self.punchers.len() >= MAX_REGISTRATIONS {
+                    return Self::error(req, Status::InternalServerError, "too many registrations");
+                }
+                // Anonymous punchers are only bounded by the total number of registrations
+                if caller.is_some()
+                    && self
+                        .punchers
+                        .values()
+                        .filter(|p| p.identifier == caller)
+                        .count()
+                        >= MAX_REGISTRATIONS_PER_IDENTITY
+                {
+                    warn!(?caller, "Too many registrations for this identity");
+                    return Self::error(
+                        req,
+                        Status::Forbidden,
+                        "too many registrations for this identity",
+                    );
+                }
+                self.punchers.insert(
+                    puncher_name.clone(),
+                    RegisteredPuncher {
+                        identifier: caller.clone(),
+                        route,
+                        expires_at: now + self.options.ttl,
+                    },
+                );
+            }
+        }
+
+        // Only the latest token of a registration can be announced
+        self.tokens
+            .retain(|_, t| t.puncher_name != puncher_name || t.identifier != caller);
+        if self.tokens.len() >= MAX_REGISTRATIONS {
+            return Self::error(req, Status::InternalServerError, "too many registrations");
+        }
+        let token = format!("{:032x}", rand::random::<u128>());
+        self.tokens.insert(
+            token.clone(),
+            PendingToken {
+                puncher_name,
+                identifier: caller,
+                expires_at: now + self.options.ttl,
+            },
+        );
+
+        let registration = Registration::new(token, self.options.ttl.as_secs().max(1));
+        Ok(Response::ok(req.id()).body(registration).to_vec()?)
+    }
+
+    // Handle Announce request
+    fn handle_announce(
+        &mut self,
+        req: &Request<'_>,
+        body: AnnounceRequest,
+        return_route: &Route,
+    ) -> Result<Vec<u8>> {
+        let route = Self::parse_route(return_route);
+        if route.is_empty() {
             // This could happen if a client erroneously contacts this service over TCP not UDP, for example
             warn!(
                 "Return route has no UDP part, will not update map: {:?}",
                 return_route
             );
-            // Ignore issue. There's no (current) way to inform sender.
+            return Self::error(
+                req,
+                Status::BadRequest,
+                "announcements must be sent over UDP",
+            );
+        }
+
+        // Tokens can only be used once
+        let token = match self.tokens.remove(body.token()) {
+            Some(token) => token,
+            None => return Self::error(req, Status::NotFound, "unknown registration token"),
+        };
+        match self.punchers.get_mut(&token.puncher_name) {
+            Some(puncher) if puncher.identifier == token.identifier => {
+                debug!(puncher_name = %token.puncher_name, %route, "Puncher announced");
+                puncher.route = Some(route);
+                Ok(Response::ok(req.id()).to_vec()?)
+            }
+            _ => Self::error(req, Status::NotFound, "unknown registration token"),
         }
     }
 
     // Handle Query request
-    fn handle_query(&self, puncher_name: &String) -> Result<Route> {
-        match self.map.get(puncher_name) {
-            Some(route) => Ok(route.clone()),
-            None => Err(Error::new_without_cause(Origin::Other, Kind::NotFound)),
+    async fn handle_query(
+        &self,
+        req: &Request<'_>,
+        puncher_name: &str,
+        caller: Option<IdentityIdentifier>,
+        now: Instant,
+    ) -> Result<Vec<u8>> {
+        if caller.is_none() && !self.options.anonymous_registrations {
+            return Self::error(req, Status::Unauthorized, "a secure channel is required");
+        }
+        let puncher = match self.punchers.get(puncher_name) {
+            Some(puncher) => puncher,
+            None => return Self::error(req, Status::NotFound, "unknown puncher"),
+        };
+        let authorized = match &caller {
+            Some(caller) => {
+                self.is_authorized(
+                    &self.options.query_policy,
+                    caller,
+                    Some((puncher_name, puncher)),
+                )
+                .await?
+            }
+            // Anonymous punchers can only find each other
+            None => puncher.identifier.is_none(),
+        };
+        if !authorized {
+            warn!(?caller, %puncher_name, "Query denied by policy");
+            return Self::error(req, Status::Forbidden, "query denied by policy");
+        }
+        match Self::entry(puncher_name, puncher, now) {
+            Some(entry) => Ok(Response::ok(req.id()).body(entry).to_vec()?),
+            None => Self::error(req, Status::NotFound, "the puncher was not announced"),
+        }
+    }
+
+    // Handle List request
+    async fn handle_list(
+        &self,
+        req: &Request<'_>,
+        caller: Option<IdentityIdentifier>,
+        now: Instant,
+    ) -> Result<Vec<u8>> {
+        let caller = match caller {
+            Some(caller) => caller,
+            None => return Self::error(req, Status::Unauthorized, "a secure channel is required"),
+        };
+        if !self
+            .is_authorized(&self.options.list_policy, &caller, None)
+            .await?
+        {
+            warn!(%caller, "List denied by policy");
+            return Self::error(req, Status::Forbidden, "list denied by policy");
+        }
+        let entries: Vec<RendezvousEntry> = self
+            .punchers
+            .iter()
+            .filter_map(|(name, puncher)| Self::entry(name, puncher, now))
+            .collect();
+        Ok(Response::ok(req.id()).body(entries).to_vec()?)
+    }
+
+    async fn handle_request(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        caller: Option<IdentityIdentifier>,
+        return_route: &Route,
+    ) -> Result<Vec<u8>> {
+        trace! {
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let now = Instant::now();
+        self.remove_expired(now);
+
+        use Method::*;
+        let path = req.path();
+        let path_segments = req.path_segments::<3>();
+        let method = match req.method() {
+            Some(m) => m,
+            None => return Self::error(req, Status::BadRequest, "invalid method"),
+        };
+
+        match (method, path_segments.as_slice()) {
+            (Get, ["ping"]) => Ok(Response::ok(req.id()).to_vec()?),
+            (Post, ["registrations"]) => {
+                self.handle_register(req, dec.decode()?, caller, return_route, now)
+            }
+            (Post, ["announcements"]) => self.handle_announce(req, dec.decode()?, return_route),
+            (Get, ["registrations"]) => self.handle_list(req, caller, now).await,
+            (Get, ["registrations", puncher_name]) => {
+                self.handle_query(req, puncher_name, caller, now).await
+            }
+            _ => {
+                warn!(%method, %path, "Called invalid endpoint");
+                Self::error(
+                    req,
+                    Status::BadRequest,
+                    &format!("invalid endpoint: {}", path),
+                )
+            }
         }
     }
+
+    fn error(req: &Request<'_>, status: Status, msg: &str) -> Result<Vec<u8>> {
+        Ok(Self::error_response(req.id(), req.path(), status, msg).to_vec()?)
+    }
+
+    fn error_response<'a>(
+        id: Id,
+        path: &'a str,
+        status: Status,
+        msg: &'a str,
+    ) -> ResponseBuilder<Error<'a>> {
+        Response::builder(id, status).body(Error::new(path).with_message(msg))
+    }
 }
 
 #[async_trait]
 impl Worker for RendezvousWorker {
-    type Message = RendezvousRequest;
+    type Message = Vec<u8>;
     type Context = Context;
 
     async fn handle_message(
@@ -115,39 +426,56 @@ impl Worker for RendezvousWorker {
         ctx: &mut Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        debug!(
-            "Received message: {:?} from {}",
-            msg,
-            Self::parse_route(&msg.return_route())
-        );
         let return_route = msg.return_route();
-        match msg.as_body() {
-            RendezvousRequest::Update { puncher_name } => {
-                self.handle_update(puncher_name, &return_route);
-            }
-            RendezvousRequest::Query { puncher_name } => {
-                let res = self.handle_query(puncher_name);
-                ctx.send(return_route, RendezvousResponse::Query(res))
-                    .await?;
+        debug!("Received message from {}", Self::parse_route(&return_route));
+
+        let mut dec = Decoder::new(msg.as_body());
+        let req: Request = match dec.decode() {
+            Ok(r) => r,
+            Err(e) => {
+                error!("failed to decode request: {:?}", e);
+                return Ok(());
             }
-            RendezvousRequest::Ping => {
-                ctx.send(return_route, RendezvousResponse::Pong).await?;
+        };
+
+        // Registrations are bound to the identity of the secure channel they were received from
+        let caller = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id());
+
+        let r = match self
+            .handle_request(&req, &mut dec, caller, &return_route)
+            .await
+        {
+            Ok(r) => r,
+            Err(err) => {
+                error!(?err, "Failed to handle message");
+                Response::builder(req.id(), Status::InternalServerError)
+                    .body(err.to_string())
+                    .to_vec()?
             }
-        }
-        trace!("Map: {:?}", self.map);
-        Ok(())
+        };
+        trace!("Registered punchers: {:?}", self.punchers);
+        ctx.send(return_route, r).await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RendezvousWorker;
-    use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
+    use super::{RendezvousWorker, MAX_REGISTRATIONS_PER_IDENTITY};
+    use crate::rendezvous_service::{UdpRendezvousClient, UdpRendezvousServiceOptions};
     use crate::{UdpRendezvousService, UdpTransport, UDP};
+    use ockam_abac::parse;
     use ockam_core::errcode::Origin;
-    use ockam_core::{route, Error, Result, Route, Routed, TransportType, Worker};
+    use ockam_core::{route, Address, Error, Result, Route, Routed, TransportType, Worker};
+    use ockam_identity::secure_channels::secure_channels;
+    use ockam_identity::{
+        IdentityIdentifier, SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
+    };
     use ockam_node::Context;
     use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
     use tokio::net::UdpSocket;
     use tracing::debug;
 
@@ -179,34 +507,29 @@ mod tests {
 
     #[ockam_macros::test]
     async fn update_and_query(ctx: &mut Context) -> Result<()> {
-        let (rendezvous_route, send_addr) = test_setup(ctx).await?;
-
-        let our_public_route = route![(UDP, send_addr.to_string()), ctx.address()];
+        let setup = test_setup(ctx, |options| options).await?;
+        let (alice, alice_client) = setup.client(ctx).await?;
+        let (bob, bob_client) = setup.client(ctx).await?;
 
-        // Update service, should work
+        // Register and announce, should work
         //
-        // Use Alice and Bob with the same address to check the service can
+        // Use Alice and Bob with the same UDP address to check the service can
         // handle multiple internal mappings and that multiple map values
         // can be for the same node.
-        update_operation("Alice", ctx, &rendezvous_route)
-            .await
-            .unwrap();
-        update_operation("Bob", ctx, &rendezvous_route)
-            .await
-            .unwrap();
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
+        update_operation("Bob", ctx, &bob_client, &setup.rendezvous_route).await?;
 
         // Query service, should work
-        let res = query_operation("Alice", ctx, &rendezvous_route)
-            .await
-            .unwrap();
-        assert_eq!(res, our_public_route);
-        let res = query_operation("Bob", ctx, &rendezvous_route)
-            .await
-            .unwrap();
-        assert_eq!(res, our_public_route);
+        let our_public_addr = (UDP, setup.send_addr.to_string()).into();
+        let entry = bob_client.query("Alice").await?;
+        assert_eq!(entry.route().next()?, &our_public_addr);
+        assert_eq!(entry.identifier(), Some(&alice));
+        let entry = alice_client.query("Bob").await?;
+        assert_eq!(entry.route().next()?, &our_public_addr);
+        assert_eq!(entry.identifier(), Some(&bob));
 
         // Query service for non-existant node, should error
-        let res = query_operation("DoesNotExist", ctx, &rendezvous_route).await;
+        let res = alice_client.query("DoesNotExist").await;
         assert!(res.is_err(), "Query operation should have failed");
 
         // Shutdown
@@ -215,60 +538,301 @@ mod tests {
     }
 
     #[ockam_macros::test]
-    async fn ping(ctx: &mut Context) -> Result<()> {
-        let (rendezvous_route, _) = test_setup(ctx).await?;
+    async fn registrations_are_bound_to_identities(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options).await?;
+        let (alice, alice_client) = setup.client(ctx).await?;
+        let (_, bob_client) = setup.client(ctx).await?;
+
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
 
-        let res: RendezvousResponse = ctx
-            .send_and_receive(rendezvous_route, RendezvousRequest::Ping)
+        // Bob can't take over Alice's name
+        assert!(bob_client.register("Alice").await.is_err());
+
+        // Names can't be registered or queried without a secure channel
+        let plain_client = UdpRendezvousClient::new(ctx, setup.rendezvous_route.clone()).await?;
+        assert!(plain_client.register("Alice").await.is_err());
+        assert!(plain_client.query("Alice").await.is_err());
+
+        // Alice can renew her registration
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
+        assert_eq!(bob_client.query("Alice").await?.identifier(), Some(&alice));
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn announcements_require_a_valid_token(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options).await?;
+        let (_, alice_client) = setup.client(ctx).await?;
+        let plain_client = UdpRendezvousClient::new(ctx, setup.rendezvous_route.clone()).await?;
+
+        // A registration which wasn't announced can't be queried
+        let registration = alice_client.register("Alice").await?;
+        assert!(alice_client.query("Alice").await.is_err());
+
+        // Unknown tokens are rejected
+        assert!(plain_client.announce("not a token").await.is_err());
+
+        // Announcements must be sent over UDP, not through the secure channel
+        assert!(alice_client.announce(registration.token()).await.is_err());
+
+        // Tokens can only be used once
+        plain_client.announce(registration.token()).await?;
+        assert!(plain_client.announce(registration.token()).await.is_err());
+        assert!(alice_client.query("Alice").await.is_ok());
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn registrations_expire(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options.with_ttl(Duration::from_secs(1))).await?;
+        let (_, alice_client) = setup.client(ctx).await?;
+        let (bob, bob_client) = setup.client(ctx).await?;
+
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
+        assert_eq!(alice_client.register("Alice").await?.ttl(), 1);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        // The registration expired, the name can be registered by another identity
+        assert!(bob_client.query("Alice").await.is_err());
+        update_operation("Alice", ctx, &bob_client, &setup.rendezvous_route).await?;
+        assert_eq!(alice_client.query("Alice").await?.identifier(), Some(&bob));
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn queries_and_listing_follow_policies(ctx: &mut Context) -> Result<()> {
+        let secure_channels = secure_channels();
+        let identities = secure_channels.identities();
+        let operator = identities
+            .identities_creation()
+            .create_identity()
+            .await?
+            .identifier();
+        identities
+            .repository()
+            .put_attribute_value(&operator, "role", "operator")
             .await?;
-        assert!(matches!(res, RendezvousResponse::Pong));
+
+        // Punchers can only query the punchers registered by their own identity
+        let options = UdpRendezvousServiceOptions::new(identities.repository())
+            .with_query_policy(parse("(= subject.identifier resource.identifier)")?.unwrap())
+            .with_list_policy(parse("(= subject.role \"operator\")")?.unwrap());
+        let setup = test_setup_with_secure_channels(ctx, options, secure_channels).await?;
+        let (_, alice_client) = setup.client(ctx).await?;
+        let (_, bob_client) = setup.client(ctx).await?;
+        let operator_client = setup.client_for(ctx, &operator).await?;
+
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
+        update_operation(
+            "Alice's laptop",
+            ctx,
+            &alice_client,
+            &setup.rendezvous_route,
+        )
+        .await?;
+        update_operation("Bob", ctx, &bob_client, &setup.rendezvous_route).await?;
+
+        assert!(alice_client.query("Alice's laptop").await.is_ok());
+        assert!(alice_client.query("Bob").await.is_err());
+        assert!(bob_client.query("Alice").await.is_err());
+
+        // Only the operator can list the registered punchers
+        assert!(alice_client.list().await.is_err());
+        let names: Vec<String> = operator_client
+            .list()
+            .await?
+            .iter()
+            .map(|e| e.puncher_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["Alice", "Alice's laptop", "Bob"]);
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn registrations_are_limited_per_identity(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options).await?;
+        let (_, alice_client) = setup.client(ctx).await?;
+        let (_, bob_client) = setup.client(ctx).await?;
+
+        for i in 0..MAX_REGISTRATIONS_PER_IDENTITY {
+            alice_client.register(&format!("Alice {i}")).await?;
+        }
+        assert!(alice_client.register("One too many").await.is_err());
+
+        // Existing registrations can still be renewed, and other identities are not limited
+        alice_client.register("Alice 0").await?;
+        bob_client.register("Bob").await?;
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn anonymous_registrations(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options.with_anonymous_registrations()).await?;
+        let (alice, alice_client) = setup.client(ctx).await?;
+        let plain_client = UdpRendezvousClient::new(ctx, setup.rendezvous_route.clone()).await?;
+
+        // Anonymous registrations sent over UDP don't need to be announced
+        plain_client.register("Anonymous").await?;
+        let entry = plain_client.query("Anonymous").await?;
+        assert_eq!(entry.identifier(), None);
+        assert_eq!(
+            entry.route().next()?,
+            &(UDP, setup.send_addr.to_string()).into()
+        );
+
+        // Names registered by an identity can't be taken over or queried anonymously
+        update_operation("Alice", ctx, &alice_client, &setup.rendezvous_route).await?;
+        assert!(plain_client.register("Alice").await.is_err());
+        assert!(plain_client.query("Alice").await.is_err());
+        assert_eq!(
+            alice_client.query("Alice").await?.identifier(),
+            Some(&alice)
+        );
+        assert!(alice_client.query("Anonymous").await.is_ok());
+
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn ping(ctx: &mut Context) -> Result<()> {
+        let setup = test_setup(ctx, |options| options).await?;
+
+        let client = UdpRendezvousClient::new(ctx, setup.rendezvous_route).await?;
+        client.ping().await?;
 
         // Shutdown
         ctx.stop().await?;
         Ok(())
     }
 
+    struct TestSetup {
+        secure_channels: Arc<SecureChannels>,
+        /// Route to the service over UDP
+        rendezvous_route: Route,
+        /// Route to the secure channel listener of the service's node
+        listener_route: Route,
+        /// Our UDP sending address
+        send_addr: SocketAddr,
+    }
+
+    impl TestSetup {
+        /// Create an identity and a client using a secure channel for it
+        async fn client(&self, ctx: &Context) -> Result<(IdentityIdentifier, UdpRendezvousClient)> {
+            let identifier = self
+                .secure_channels
+                .identities()
+                .identities_creation()
+                .create_identity()
+                .await?
+                .identifier();
+            let client = self.client_for(ctx, &identifier).await?;
+            Ok((identifier, client))
+        }
+
+        /// Create a client using a secure channel for the given identity
+        async fn client_for(
+            &self,
+            ctx: &Context,
+            identifier: &IdentityIdentifier,
+        ) -> Result<UdpRendezvousClient> {
+            let channel = self
+                .secure_channels
+                .create_secure_channel(
+                    ctx,
+                    identifier,
+                    self.listener_route.clone(),
+                    SecureChannelOptions::new(),
+                )
+                .await?;
+            UdpRendezvousClient::new(ctx, route![channel, "rendezvous"]).await
+        }
+    }
+
+    /// Helper
+    async fn test_setup(
+        ctx: &mut Context,
+        options: impl FnOnce(UdpRendezvousServiceOptions) -> UdpRendezvousServiceOptions,
+    ) -> Result<TestSetup> {
+        let secure_channels = secure_channels();
+        let options = options(UdpRendezvousServiceOptions::new(
+            secure_channels.identities().repository(),
+        ));
+        test_setup_with_secure_channels(ctx, options, secure_channels).await
+    }
+
     /// Helper
-    async fn test_setup(ctx: &mut Context) -> Result<(Route, SocketAddr)> {
+    async fn test_setup_with_secure_channels(
+        ctx: &mut Context,
+        options: UdpRendezvousServiceOptions,
+        secure_channels: Arc<SecureChannels>,
+    ) -> Result<TestSetup> {
         // Find an available port
         let bind_addr = *available_local_ports(1).await?.first().unwrap();
         debug!("bind_addr = {:?}", bind_addr);
 
         // Create transport, start rendezvous service, start echo service and listen
         let transport = UdpTransport::create(ctx).await?;
-        UdpRendezvousService::start(ctx, "rendezvous").await?;
+        UdpRendezvousService::start(ctx, "rendezvous", options).await?;
         let rendezvous_route = route![(UDP, bind_addr.to_string()), "rendezvous"];
         ctx.start_worker("echo", EchoUDPAddress).await?;
         let route_echo = route![(UDP, bind_addr.to_string()), "echo"];
         transport.listen(bind_addr.to_string()).await?;
 
+        // Start a secure channel listener whose channels can reach the rendezvous service
+        let service_identity = secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+        let listener = secure_channels
+            .create_secure_channel_listener(
+                ctx,
+                &service_identity.identifier(),
+                "listener",
+                SecureChannelListenerOptions::new(),
+            )
+            .await?;
+        ctx.flow_controls()
+            .add_consumer(Address::from("rendezvous"), listener.flow_control_id());
+        let listener_route = route![(UDP, bind_addr.to_string()), "listener"];
+
         // Use echo service to find out our UDP sending address
         let send_addr: String = ctx.send_and_receive(route_echo, String::new()).await?;
         let send_addr = send_addr.parse::<SocketAddr>().unwrap();
 
-        Ok((rendezvous_route, send_addr))
+        Ok(TestSetup {
+            secure_channels,
+            rendezvous_route,
+            listener_route,
+            send_addr,
+        })
     }
 
     /// Helper
-    async fn update_operation(puncher_name: &str, ctx: &mut Context, route: &Route) -> Result<()> {
-        let msg = RendezvousRequest::Update {
-            puncher_name: String::from(puncher_name),
-        };
-
-        // Send from our context's main address
-        ctx.send(route.clone(), msg).await
-    }
+    async fn update_operation(
+        puncher_name: &str,
+        ctx: &mut Context,
+        client: &UdpRendezvousClient,
+        route: &Route,
+    ) -> Result<()> {
+        let registration = client.register(puncher_name).await?;
 
-    /// Helper
-    async fn query_operation(puncher_name: &str, ctx: &Context, route: &Route) -> Result<Route> {
-        let msg = RendezvousRequest::Query {
-            puncher_name: String::from(puncher_name),
-        };
-        let res: RendezvousResponse = ctx.send_and_receive(route.clone(), msg).await?;
-        match res {
-            RendezvousResponse::Query(r) => r,
-            r => panic!("Unexpected response: {:?}", r),
-        }
+        // Announce from our context's main address
+        let req = ockam_core::api::Request::post("announcements")
+            .body(super::AnnounceRequest::new(registration.token()));
+        let res: Vec<u8> = ctx.send_and_receive(route.clone(), req.to_vec()?).await?;
+        ockam_core::api::is_ok("announce", &res)
     }
 
     /// Echo service that allows us to find out the UDP address the tests are sending from
@@ -1,6 +1,8 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, AllowAll, Result};
 use ockam_node::Context;
+use ockam_transport_core::TransportError;
 use std::net::SocketAddr;
 
 /// A handle to connect to a UdpRouter
@@ -33,10 +35,53 @@ impl UdpRouterHandle {
     /// so the local node can act as a server to other nodes
     pub async fn listen(&self, local_addr: SocketAddr) -> Result<()> {
         let msg = UdpRouterRequest::Listen { local_addr };
-        let UdpRouterResponse::Listen(res) = self
+        let response = self
             .ctx
             .send_and_receive(self.api_addr.clone(), msg)
             .await?;
-        res
+        if let UdpRouterResponse::Listen(res) = response {
+            res
+        } else {
+            Err(TransportError::InvalidRouterResponseType.into())
+        }
+    }
+
+    /// Request router to open a local UDP socket dedicated to the given peer
+    ///
+    /// Returns the address of the sender worker for that peer
+    pub async fn connect(
+        &self,
+        peer: SocketAddr,
+        flow_control_id: Option<FlowControlId>,
+        reliable_delivery: bool,
+    ) -> Result<Address> {
+        let msg = UdpRouterRequest::Connect {
+            peer,
+            flow_control_id,
+            reliable_delivery,
+        };
+        let response = self
+            .ctx
+            .send_and_receive(self.api_addr.clone(), msg)
+            .await?;
+        if let UdpRouterResponse::Connect(res) = response {
+            res
+        } else {
+            Err(TransportError::InvalidRouterResponseType.into())
+        }
+    }
+
+    /// Request router to use reliable delivery for the 'client' messages sent to the given peer
+    pub async fn enable_reliable_delivery(&self, peer: SocketAddr) -> Result<()> {
+        let msg = UdpRouterRequest::EnableReliableDelivery { peer };
+        let response = self
+            .ctx
+            .send_and_receive(self.api_addr.clone(), msg)
+            .await?;
+        if let UdpRouterResponse::EnableReliableDelivery(res) = response {
+            res
+        } else {
+            Err(TransportError::InvalidRouterResponseType.into())
+        }
     }
 }
@@ -1,4 +1,5 @@
-use ockam_core::{Message, Result};
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, Message, Result};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -7,9 +8,20 @@ pub enum UdpRouterRequest {
     /// Listen on a local UDP port so the local node can
     /// act as a server to other nodes
     Listen { local_addr: SocketAddr },
+    /// Open a local UDP socket dedicated to a single peer
+    Connect {
+        peer: SocketAddr,
+        /// Mark the listener of the socket as a Producer with this id
+        flow_control_id: Option<FlowControlId>,
+        reliable_delivery: bool,
+    },
+    /// Use reliable delivery for the 'client' messages sent to a peer
+    EnableReliableDelivery { peer: SocketAddr },
 }
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 pub enum UdpRouterResponse {
     Listen(Result<()>),
+    Connect(Result<Address>),
+    EnableReliableDelivery(Result<()>),
 }
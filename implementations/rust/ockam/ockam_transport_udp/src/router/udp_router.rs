@@ -1,27 +1,37 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
 use crate::router::UdpRouterHandle;
-use crate::workers::{TransportMessageCodec, UdpListenProcessor, UdpSendWorker};
+use crate::workers::{
+    PacketCodec, UdpListenProcessor, UdpSendWorker, UdpSenderAddresses, UdpSenderEvent,
+};
+use crate::UdpTransportOptions;
 use futures_util::StreamExt;
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, Address, AllowAll, Any, Decodable, DenyAll, LocalMessage, Mailbox, Mailboxes,
     Result, Routed, Worker,
 };
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
 use std::sync::Arc;
 use tokio::net::UdpSocket;
 use tokio_util::udp::UdpFramed;
-use tracing::{debug, error, trace};
+use tracing::{debug, trace};
 
 /// The router for the UDP transport
 ///
-/// The router opens a single 'client' local socket for messages which were
-/// initiaited by an entity within the local node.
+/// The router opens a 'client' local socket for messages which were
+/// initiaited by an entity within the local node. An IPv4 socket is opened
+/// when the router is created, an IPv6 socket is opened the first time a
+/// message is sent to an IPv6 peer.
 ///
 /// The router opens a 'server' local socket whenever a user calls
 /// [`listen()`](crate::UdpTransport::listen) on the transport.
 ///
+/// The router opens a 'connected' local socket whenever a user calls
+/// [`connect()`](crate::UdpTransport::connect) on the transport. Messages
+/// sent to the sender of that socket are always delivered to the same peer.
+///
 /// For each open local socket, the router creates a 'sender'
 /// ([`UdpSendWorker`](UdpSendWorker)) and a 'listener'
 /// ([`UdpListenProcessor`](UdpListenProcessor)) to handle messages
@@ -30,19 +40,23 @@ use tracing::{debug, error, trace};
 /// The router only expects to have to route 'client' messages to the 'client'
 /// sender. 'server' messages bypass the router as listeners inject the
 /// sender's address into the return route of received messages.
-///
-/// This transport only supports IPv4.
 pub(crate) struct UdpRouter {
     ctx: Context,
     main_addr: Address,
     api_addr: Address,
-    /// Sender for 'client' messages
-    client_sender: Address,
+    /// Sender for 'client' messages sent to IPv4 peers
+    client_sender: UdpSenderAddresses,
+    /// Sender for 'client' messages sent to IPv6 peers
+    client_sender_v6: Option<UdpSenderAddresses>,
+    options: UdpTransportOptions,
 }
 
 impl UdpRouter {
     /// Create and register a new UDP router with the node context
-    pub(crate) async fn register(ctx: &Context) -> Result<UdpRouterHandle> {
+    pub(crate) async fn register(
+        ctx: &Context,
+        options: UdpTransportOptions,
+    ) -> Result<UdpRouterHandle> {
         // This context is only used to start workers, doesn't need to send nor receive messages
         let child_ctx = ctx
             .new_detached(
@@ -61,7 +75,11 @@ impl UdpRouter {
         // Create sender, listener pair for 'client' messages
         let client_sender = Self::create_sender_listener(
             &child_ctx,
-            SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0),
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            None,
+            None,
+            false,
+            &options,
         )
         .await?;
 
@@ -70,6 +88,8 @@ impl UdpRouter {
             main_addr: main_addr.clone(),
             api_addr: api_addr.clone(),
             client_sender,
+            client_sender_v6: None,
+            options,
         };
 
         let main_mailbox = Mailbox::new(
@@ -95,42 +115,120 @@ impl UdpRouter {
 
     /// Handle the routing of 'client' messages
     async fn handle_route(&mut self, ctx: &Context, mut msg: LocalMessage) -> Result<()> {
-        // Forward message to sender for 'client' messages
-        let addr = self.client_sender.clone();
+        // Forward message to the sender for 'client' messages
+        // matching the IP version of the peer
+        let peer = msg.transport().onward_route.next()?.address().to_string();
+        let addr = self.client_sender_for(&peer).await?.main;
         msg.transport_mut().onward_route.modify().prepend(addr);
         ctx.forward(msg).await
     }
 
-    /// Create a sender, listener pair for the given socket address.
-    ///
-    /// Returns the address of the created sender.
-    async fn create_sender_listener(ctx: &Context, local_addr: SocketAddr) -> Result<Address> {
-        // This transport only supports IPv4
-        if !local_addr.is_ipv4() {
-            error!(local_addr = %local_addr, "This transport only supprts IPv4");
-            return Err(TransportError::InvalidAddress.into());
+    /// Return true if the peer address only resolves to IPv6 addresses
+    fn is_ipv6_peer(peer: &str) -> Result<bool> {
+        let mut peer_addrs = peer
+            .to_socket_addrs()
+            .map_err(|_| TransportError::InvalidAddress)?;
+        Ok(peer_addrs.all(|a| a.is_ipv6()))
+    }
+
+    /// Return the sender for 'client' messages sent to the given peer
+    async fn client_sender_for(&mut self, peer: &str) -> Result<UdpSenderAddresses> {
+        if Self::is_ipv6_peer(peer)? {
+            self.client_sender_v6().await
+        } else {
+            Ok(self.client_sender.clone())
+        }
+    }
+
+    /// Return the sender for 'client' messages sent to IPv6 peers,
+    /// opening its socket if necessary
+    async fn client_sender_v6(&mut self) -> Result<UdpSenderAddresses> {
+        if let Some(sender) = &self.client_sender_v6 {
+            return Ok(sender.clone());
         }
 
+        let sender = Self::create_sender_listener(
+            &self.ctx,
+            SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+            None,
+            None,
+            false,
+            &self.options,
+        )
+        .await?;
+        self.client_sender_v6 = Some(sender.clone());
+        Ok(sender)
+    }
+
+    /// Create a sender, listener pair for the given socket address.
+    ///
+    /// If a `peer` is given, the sender only sends to, and the listener only
+    /// accepts datagrams from, that peer. `reliable_delivery` enables reliable
+    /// delivery for the messages sent to that peer. If a `flow_control_id` is
+    /// given, the listener is marked as a Producer with that id.
+    ///
+    /// Returns the addresses of the created sender.
+    async fn create_sender_listener(
+        ctx: &Context,
+        local_addr: SocketAddr,
+        peer: Option<SocketAddr>,
+        flow_control_id: Option<FlowControlId>,
+        reliable_delivery: bool,
+        options: &UdpTransportOptions,
+    ) -> Result<UdpSenderAddresses> {
         // Bind new socket
         let socket = UdpSocket::bind(local_addr)
             .await
             .map_err(|_| TransportError::InvalidAddress)?;
 
         // Split socket into sink and stream
-        let (sink, stream) = UdpFramed::new(socket, TransportMessageCodec).split();
+        let (sink, stream) = UdpFramed::new(socket, PacketCodec).split();
 
         debug!("Creating new sender and listener for {}", local_addr);
 
         // Create sender
-        let sender_addr = Address::random_tagged("UdpSendWorker");
-        let sender = UdpSendWorker::new(sink);
-        // FIXME: @ac
-        ctx.start_worker(sender_addr.clone(), sender).await?;
+        let sender_addrs = UdpSenderAddresses {
+            main: Address::random_tagged("UdpSendWorker.main"),
+            internal: Address::random_tagged("UdpSendWorker.internal"),
+        };
+        let listener_addr = Address::random_tagged("UdpListenProcessor");
+        let sender = UdpSendWorker::new(
+            sink,
+            peer,
+            local_addr.is_ipv6(),
+            options.max_datagram_size,
+            sender_addrs.internal.clone(),
+            listener_addr.clone(),
+            reliable_delivery,
+        );
+        let main_mailbox = Mailbox::new(
+            sender_addrs.main.clone(),
+            Arc::new(AllowAll), // FIXME: @ac
+            Arc::new(AllowAll), // FIXME: @ac
+        );
+        let internal_mailbox = Mailbox::new(
+            sender_addrs.internal.clone(),
+            Arc::new(AllowAll), // FIXME: @ac
+            Arc::new(AllowAll), // FIXME: @ac
+        );
+        WorkerBuilder::new(sender)
+            .with_mailboxes(Mailboxes::new(main_mailbox, vec![internal_mailbox]))
+            .start(ctx)
+            .await?;
 
         // Create listener
-        UdpListenProcessor::start(ctx, stream, sender_addr.clone()).await?;
+        UdpListenProcessor::start(
+            ctx,
+            listener_addr,
+            stream,
+            sender_addrs.clone(),
+            peer,
+            flow_control_id,
+            options.reassembly_timeout,
+        )
+        .await?;
 
-        Ok(sender_addr)
+        Ok(sender_addrs)
     }
 }
 
@@ -163,11 +261,60 @@ impl Worker for UdpRouter {
             trace!("handle_message() API_ADDR: msg = {:?}", msg);
             match msg {
                 UdpRouterRequest::Listen { local_addr } => {
-                    let res = Self::create_sender_listener(&self.ctx, local_addr).await;
+                    let res = Self::create_sender_listener(
+                        &self.ctx,
+                        local_addr,
+                        None,
+                        None,
+                        false,
+                        &self.options,
+                    )
+                    .await;
                     let res = res.map(|_| ());
                     ctx.send_from_address(return_route, UdpRouterResponse::Listen(res), msg_addr)
                         .await?;
                 }
+                UdpRouterRequest::Connect {
+                    peer,
+                    flow_control_id,
+                    reliable_delivery,
+                } => {
+                    let unspecified = if peer.is_ipv6() {
+                        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+                    } else {
+                        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+                    };
+                    let res = Self::create_sender_listener(
+                        &self.ctx,
+                        SocketAddr::new(unspecified, 0),
+                        Some(peer),
+                        flow_control_id,
+                        reliable_delivery,
+                        &self.options,
+                    )
+                    .await;
+                    let res = res.map(|addrs| addrs.main);
+                    ctx.send_from_address(return_route, UdpRouterResponse::Connect(res), msg_addr)
+                        .await?;
+                }
+                UdpRouterRequest::EnableReliableDelivery { peer } => {
+                    let res = match self.client_sender_for(&peer.to_string()).await {
+                        Ok(sender) => {
+                            ctx.send(
+                                sender.internal,
+                                UdpSenderEvent::EnableReliableDelivery { peer },
+                            )
+                            .await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    ctx.send_from_address(
+                        return_route,
+                        UdpRouterResponse::EnableReliableDelivery(res),
+                        msg_addr,
+                    )
+                    .await?;
+                }
             };
         } else {
             return Err(TransportError::Protocol.into());
@@ -1,13 +1,21 @@
 use crate::router::{UdpRouter, UdpRouterHandle};
-use ockam_core::{async_trait, Result};
+use crate::{UdpConnectionOptions, UdpTransportOptions};
+use ockam_core::{async_trait, Address, Result};
 use ockam_node::{Context, HasContext};
 use ockam_transport_core::TransportError;
+use std::net::{SocketAddr, ToSocketAddrs};
 
 /// High level management interface for UDP transport
 ///
 /// A node will have, at most, one UDP transport running.
 ///
-/// This transport only supports IPv4.
+/// Both IPv4 and IPv6 peers are supported. Messages which don't fit in a
+/// single datagram are fragmented, see [`UdpTransportOptions`].
+///
+/// Messages are sent without delivery guarantees, unless reliable delivery
+/// is enabled for a peer. Lost datagrams sent to that peer are then
+/// retransmitted and messages are delivered in order, which allows running
+/// secure channels and portals over UDP.
 pub struct UdpTransport {
     router_handle: UdpRouterHandle,
 }
@@ -15,7 +23,15 @@ pub struct UdpTransport {
 impl UdpTransport {
     /// Create a new UDP transport for the current node
     pub async fn create(ctx: &Context) -> Result<UdpTransport> {
-        let router_handle = UdpRouter::register(ctx).await?;
+        Self::create_with_options(ctx, UdpTransportOptions::new()).await
+    }
+
+    /// Create a new UDP transport for the current node with specific options
+    pub async fn create_with_options(
+        ctx: &Context,
+        options: UdpTransportOptions,
+    ) -> Result<UdpTransport> {
+        let router_handle = UdpRouter::register(ctx, options).await?;
         Ok(Self { router_handle })
     }
 
@@ -27,6 +43,67 @@ impl UdpTransport {
             .map_err(|_| TransportError::InvalidAddress)?;
         self.router_handle.listen(bind_addr).await
     }
+
+    /// Open a local socket dedicated to the given peer
+    ///
+    /// Returns the address of a local worker which sends every message it
+    /// receives to that peer. Replies from the peer are routed back through
+    /// the same worker.
+    ///
+    /// IPv4 addresses are preferred when the peer resolves to both IP versions.
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        let peer = Self::resolve_peer(peer.as_ref())?;
+        self.router_handle.connect(peer, None, false).await
+    }
+
+    /// Open a local socket dedicated to the given peer with specific options
+    ///
+    /// See [`connect`](Self::connect). The messages received from the peer
+    /// are only delivered to the consumers of the flow control id of the options.
+    ///
+    /// With reliable delivery, the socket is closed if the peer stops acknowledging packets.
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: UdpConnectionOptions,
+    ) -> Result<Address> {
+        let peer = Self::resolve_peer(peer.as_ref())?;
+        self.router_handle
+            .connect(
+                peer,
+                Some(options.flow_control_id),
+                options.reliable_delivery,
+            )
+            .await
+    }
+
+    /// Use reliable delivery for the messages sent to the given peer
+    /// through the UDP router, i.e. with a `UDP` address in their route
+    ///
+    /// This is typically used once a hole was punched to the peer, as
+    /// messages keep being sent from the same local socket.
+    ///
+    /// If the peer stops acknowledging packets, the messages sent to it are
+    /// rejected until reliable delivery is enabled again.
+    pub async fn enable_reliable_delivery<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        let peer = Self::resolve_peer(peer.as_ref())?;
+        self.router_handle.enable_reliable_delivery(peer).await
+    }
+
+    /// Resolve a peer address, preferring IPv4 addresses
+    fn resolve_peer(peer: &str) -> Result<SocketAddr> {
+        let peer_addrs: Vec<SocketAddr> = peer
+            .to_socket_addrs()
+            .map_err(|_| TransportError::InvalidAddress)?
+            .collect();
+        let peer = peer_addrs
+            .iter()
+            .find(|a| a.is_ipv4())
+            .or_else(|| peer_addrs.first())
+            .copied()
+            .ok_or(TransportError::InvalidAddress)?;
+        Ok(peer)
+    }
 }
 
 /// This trait adds a `create_udp_transport` method to any struct returning a Context.
@@ -1,34 +1,211 @@
+use super::{Ack, Fragment, FRAGMENT_HEADER_LEN};
 use bytes::{Buf, BufMut, BytesMut};
-use ockam_core::TransportMessage;
-use ockam_core::{Decodable, Encodable};
 use ockam_transport_core::TransportError;
 use tokio_util::codec::{Decoder, Encoder};
 
-pub(crate) struct TransportMessageCodec;
+/// Size of the protocol version and packet type prepended to every datagram
+pub(crate) const PACKET_HEADER_LEN: usize = 2;
 
-impl Encoder<TransportMessage> for TransportMessageCodec {
+/// Version of the datagram format, datagrams using another version are dropped
+const PROTOCOL_VERSION: u8 = 1;
+
+/// Size of the session and sequence number of a [`Packet::Data`]
+pub(crate) const DATA_HEADER_LEN: usize = 8;
+
+const FRAGMENT: u8 = 0;
+const DATA: u8 = 1;
+const ACK: u8 = 2;
+
+/// Content of a single datagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Packet {
+    /// A fragment sent without delivery guarantees
+    Fragment(Fragment),
+    /// A fragment sent with reliable delivery
+    Data {
+        session: u32,
+        seq: u32,
+        fragment: Fragment,
+    },
+    /// Acknowledgement of reliably delivered fragments
+    Ack(Ack),
+}
+
+/// Codec writing a single [`Packet`] per datagram
+pub(crate) struct PacketCodec;
+
+impl PacketCodec {
+    fn encode_fragment(fragment: Fragment, dst: &mut BytesMut) {
+        dst.reserve(FRAGMENT_HEADER_LEN + fragment.payload.len());
+        dst.put_u32(fragment.message_id);
+        dst.put_u16(fragment.index);
+        dst.put_u16(fragment.count);
+        dst.put(fragment.payload);
+    }
+
+    fn decode_fragment(datagram: &mut BytesMut) -> Result<Fragment, TransportError> {
+        if datagram.len() < FRAGMENT_HEADER_LEN {
+            return Err(TransportError::RecvBadMessage);
+        }
+        let message_id = datagram.get_u32();
+        let index = datagram.get_u16();
+        let count = datagram.get_u16();
+        Ok(Fragment {
+            message_id,
+            index,
+            count,
+            payload: datagram.split().freeze(),
+        })
+    }
+}
+
+impl Encoder<Packet> for PacketCodec {
     type Error = TransportError;
-    fn encode(&mut self, item: TransportMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let msg_buf = item.encode().map_err(|_| TransportError::SendBadMessage)?;
-        let len = msg_buf.len();
-        dst.put_u16(len as u16);
-        dst.put(&msg_buf[..]);
+    fn encode(&mut self, item: Packet, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.put_u8(PROTOCOL_VERSION);
+        match item {
+            Packet::Fragment(fragment) => {
+                dst.put_u8(FRAGMENT);
+                Self::encode_fragment(fragment, dst);
+            }
+            Packet::Data {
+                session,
+                seq,
+                fragment,
+            } => {
+                dst.put_u8(DATA);
+                dst.put_u32(session);
+                dst.put_u32(seq);
+                Self::encode_fragment(fragment, dst);
+            }
+            Packet::Ack(ack) => {
+                let ranges =
+                    u8::try_from(ack.ranges.len()).map_err(|_| TransportError::Capacity)?;
+                dst.put_u8(ACK);
+                dst.put_u32(ack.session);
+                dst.put_u32(ack.next_expected);
+                dst.put_u8(ranges);
+                for (start, end) in ack.ranges {
+                    dst.put_u32(start);
+                    dst.put_u32(end);
+                }
+            }
+        }
         Ok(())
     }
 }
 
-impl Decoder for TransportMessageCodec {
-    type Item = TransportMessage;
+impl Decoder for PacketCodec {
+    type Item = Packet;
     type Error = TransportError;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         if src.is_empty() {
             return Ok(None);
         }
 
-        let len = src.get_u16() as usize;
-        let msg = TransportMessage::decode(&src.split_to(len)[..])
-            .map_err(|_| TransportError::RecvBadMessage)?;
+        // Consume the whole datagram, even if it is invalid
+        let mut datagram = src.split();
+        if datagram.len() < PACKET_HEADER_LEN || datagram.get_u8() != PROTOCOL_VERSION {
+            return Err(TransportError::RecvBadMessage);
+        }
+        let packet = match datagram.get_u8() {
+            FRAGMENT => Packet::Fragment(Self::decode_fragment(&mut datagram)?),
+            DATA => {
+                if datagram.len() < DATA_HEADER_LEN {
+                    return Err(TransportError::RecvBadMessage);
+                }
+                let session = datagram.get_u32();
+                let seq = datagram.get_u32();
+                let fragment = Self::decode_fragment(&mut datagram)?;
+                Packet::Data {
+                    session,
+                    seq,
+                    fragment,
+                }
+            }
+            ACK => {
+                if datagram.len() < 9 {
+                    return Err(TransportError::RecvBadMessage);
+                }
+                let session = datagram.get_u32();
+                let next_expected = datagram.get_u32();
+                let count = datagram.get_u8() as usize;
+                if datagram.len() != count * 8 {
+                    return Err(TransportError::RecvBadMessage);
+                }
+                let ranges = (0..count)
+                    .map(|_| (datagram.get_u32(), datagram.get_u32()))
+                    .collect();
+                Packet::Ack(Ack {
+                    session,
+                    next_expected,
+                    ranges,
+                })
+            }
+            _ => return Err(TransportError::RecvBadMessage),
+        };
+        Ok(Some(packet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn packets_roundtrip() {
+        let fragment = Fragment {
+            message_id: 7,
+            index: 1,
+            count: 2,
+            payload: Bytes::from_static(b"hello"),
+        };
+        let packets = vec![
+            Packet::Fragment(fragment.clone()),
+            Packet::Data {
+                session: 3,
+                seq: 42,
+                fragment,
+            },
+            Packet::Ack(Ack {
+                session: 3,
+                next_expected: 40,
+                ranges: vec![(42, 43), (45, 50)],
+            }),
+        ];
+
+        for packet in packets {
+            let mut buf = BytesMut::new();
+            PacketCodec.encode(packet.clone(), &mut buf).unwrap();
+            assert_eq!(PacketCodec.decode(&mut buf).unwrap(), Some(packet));
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn invalid_datagrams_are_consumed() {
+        let mut buf = BytesMut::from(&[PROTOCOL_VERSION, DATA, 0, 0][..]);
+        assert!(PacketCodec.decode(&mut buf).is_err());
+        assert!(buf.is_empty());
+    }
 
-        Ok(Some(msg))
+    #[test]
+    fn datagrams_with_another_version_are_rejected() {
+        let mut buf = BytesMut::new();
+        PacketCodec
+            .encode(
+                Packet::Fragment(Fragment {
+                    message_id: 1,
+                    index: 0,
+                    count: 1,
+                    payload: Bytes::from_static(b"hello"),
+                }),
+                &mut buf,
+            )
+            .unwrap();
+        buf[0] = PROTOCOL_VERSION + 1;
+        assert!(PacketCodec.decode(&mut buf).is_err());
+        assert!(buf.is_empty());
     }
 }
@@ -0,0 +1,329 @@
+use bytes::Bytes;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Size of the header of a fragment:
+/// message id (4 bytes), fragment index (2 bytes), fragment count (2 bytes)
+pub(crate) const FRAGMENT_HEADER_LEN: usize = 8;
+
+/// Maximum size of a transport message sent over UDP
+pub(crate) const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Maximum number of fragments of a single message
+pub(crate) const MAX_FRAGMENT_COUNT: u16 = 1024;
+
+/// Maximum number of partially received messages kept by a [`Reassembler`]
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// Maximum number of bytes buffered by a [`Reassembler`] for incomplete messages
+const MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+
+/// A piece of a transport message which fits in a single datagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Fragment {
+    /// Identifier of the message, shared by all its fragments
+    pub(crate) message_id: u32,
+    /// Position of this fragment in the message
+    pub(crate) index: u16,
+    /// Total number of fragments of the message
+    pub(crate) count: u16,
+    pub(crate) payload: Bytes,
+}
+
+/// Splits encoded transport messages into [`Fragment`]s
+pub(crate) struct Fragmenter {
+    next_message_id: u32,
+}
+
+impl Fragmenter {
+    pub(crate) fn new() -> Self {
+        Self {
+            next_message_id: rand::random(),
+        }
+    }
+
+    /// Split a message into fragments which, once encoded, are not larger
+    /// than `max_fragment_size`. Every call uses a new message id
+    ///
+    /// Messages larger than [`MAX_MESSAGE_SIZE`], or needing more than
+    /// [`MAX_FRAGMENT_COUNT`] fragments, are rejected since the peer would drop them.
+    pub(crate) fn fragment(
+        &mut self,
+        message: Bytes,
+        max_fragment_size: usize,
+    ) -> Result<Vec<Fragment>> {
+        if message.len() > MAX_MESSAGE_SIZE {
+            return Err(TransportError::Capacity.into());
+        }
+        let max_payload_size = max_fragment_size.saturating_sub(FRAGMENT_HEADER_LEN).max(1);
+        let count = (message.len().max(1) + max_payload_size - 1) / max_payload_size;
+        let count = u16::try_from(count)
+            .ok()
+            .filter(|count| *count <= MAX_FRAGMENT_COUNT)
+            .ok_or(TransportError::Capacity)?;
+
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        Ok((0..count)
+            .map(|index| {
+                let start = index as usize * max_payload_size;
+                let end = (start + max_payload_size).min(message.len());
+                Fragment {
+                    message_id,
+                    index,
+                    count,
+                    payload: message.slice(start..end),
+                }
+            })
+            .collect())
+    }
+}
+
+struct PendingMessage {
+    started_at: Instant,
+    count: u16,
+    size: usize,
+    fragments: BTreeMap<u16, Bytes>,
+}
+
+/// Collects the [`Fragment`]s received from peers and returns
+/// the messages once all their fragments have been received
+///
+/// Messages which are not complete after the reassembly timeout are dropped,
+/// as well as messages which are larger than [`MAX_MESSAGE_SIZE`].
+pub(crate) struct Reassembler {
+    timeout: Duration,
+    pending: HashMap<(SocketAddr, u32), PendingMessage>,
+    /// Keys of the pending messages, in the order in which they were started
+    started: VecDeque<(Instant, (SocketAddr, u32))>,
+    /// Number of bytes buffered for all the pending messages
+    pending_bytes: usize,
+}
+
+impl Reassembler {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+            started: VecDeque::new(),
+            pending_bytes: 0,
+        }
+    }
+
+    /// Add a fragment received from `peer`.
+    /// Return the full message if this was its last missing fragment
+    pub(crate) fn add(&mut self, peer: SocketAddr, fragment: Fragment) -> Option<Vec<u8>> {
+        if fragment.index >= fragment.count || fragment.count > MAX_FRAGMENT_COUNT {
+            warn!(%peer, "Dropping fragment with an invalid index or count");
+            return None;
+        }
+
+        let now = Instant::now();
+        self.remove_expired(now);
+
+        // Single datagram messages don't need to be stored
+        if fragment.count == 1 {
+            return Some(fragment.payload.to_vec());
+        }
+
+        let key = (peer, fragment.message_id);
+        if !self.pending.contains_key(&key) {
+            if self.pending.len() >= MAX_PENDING_MESSAGES {
+                warn!(%peer, "Too many messages being reassembled, dropping fragment");
+                return None;
+            }
+            self.pending.insert(
+                key,
+                PendingMessage {
+                    started_at: now,
+                    count: fragment.count,
+                    size: 0,
+                    fragments: BTreeMap::new(),
+                },
+            );
+            self.started.push_back((now, key));
+            self.compact_started();
+        }
+
+        let pending = self.pending.get_mut(&key)?;
+        if pending.count != fragment.count {
+            warn!(%peer, "Dropping fragment with an inconsistent fragment count");
+            return None;
+        }
+        if pending.fragments.contains_key(&fragment.index) {
+            return None;
+        }
+
+        let fragment_size = fragment.payload.len();
+        if pending.size + fragment_size > MAX_MESSAGE_SIZE
+            || self.pending_bytes + fragment_size > MAX_PENDING_BYTES
+        {
+            warn!(%peer, "Message too large to be reassembled, dropping it");
+            self.remove(&key);
+            return None;
+        }
+        pending.size += fragment_size;
+        pending.fragments.insert(fragment.index, fragment.payload);
+        self.pending_bytes += fragment_size;
+
+        if pending.fragments.len() < pending.count as usize {
+            return None;
+        }
+
+        let pending = self.remove(&key)?;
+        Some(pending.fragments.into_values().flatten().collect())
+    }
+
+    /// Remove a pending message. Its key is left in the `started` queue
+    /// and skipped when it expires
+    fn remove(&mut self, key: &(SocketAddr, u32)) -> Option<PendingMessage> {
+        let pending = self.pending.remove(key)?;
+        self.pending_bytes -= pending.size;
+        Some(pending)
+    }
+
+    /// Forget the keys of the completed messages once they outnumber the pending ones,
+    /// so that the `started` queue stays bounded when messages never expire
+    fn compact_started(&mut self) {
+        if self.started.len() > 2 * MAX_PENDING_MESSAGES {
+            let pending = &self.pending;
+            self.started.retain(|(started_at, key)| {
+                pending
+                    .get(key)
+                    .map_or(false, |pending| pending.started_at == *started_at)
+            });
+        }
+    }
+
+    /// Drop the messages started more than `timeout` ago, oldest first
+    fn remove_expired(&mut self, now: Instant) {
+        while let Some((started_at, key)) = self.started.front().copied() {
+            if now.saturating_duration_since(started_at) <= self.timeout {
+                break;
+            }
+            self.started.pop_front();
+            // the key may belong to a message which was completed, or replaced by a
+            // more recent message using the same id
+            if self
+                .pending
+                .get(&key)
+                .map_or(false, |pending| pending.started_at == started_at)
+            {
+                warn!(peer = %key.0, "Reassembly timeout, dropping incomplete message");
+                self.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer() -> SocketAddr {
+        "127.0.0.1:4000".parse().unwrap()
+    }
+
+    #[test]
+    fn fragments_are_reassembled_in_any_order() {
+        let message: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+        let mut fragmenter = Fragmenter::new();
+        let mut fragments = fragmenter
+            .fragment(Bytes::from(message.clone()), 1000)
+            .unwrap();
+        assert_eq!(fragments.len(), 6);
+        fragments.reverse();
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(5));
+        let last = fragments.pop().unwrap();
+        for fragment in fragments {
+            // duplicates are ignored
+            assert!(reassembler.add(peer(), fragment.clone()).is_none());
+            assert!(reassembler.add(peer(), fragment).is_none());
+        }
+        assert_eq!(reassembler.add(peer(), last), Some(message));
+        assert!(reassembler.pending.is_empty());
+    }
+
+    #[test]
+    fn incomplete_messages_expire() {
+        let mut fragmenter = Fragmenter::new();
+        let size = FRAGMENT_HEADER_LEN + 1;
+        let first = fragmenter
+            .fragment(Bytes::from_static(b"ab"), size)
+            .unwrap();
+        let second = fragmenter
+            .fragment(Bytes::from_static(b"cd"), size)
+            .unwrap();
+        assert_ne!(first[0].message_id, second[0].message_id);
+
+        let mut reassembler = Reassembler::new(Duration::from_millis(10));
+        assert!(reassembler.add(peer(), first[0].clone()).is_none());
+        std::thread::sleep(Duration::from_millis(20));
+
+        // receiving another fragment drops the expired message
+        assert!(reassembler.add(peer(), second[0].clone()).is_none());
+        assert!(reassembler.add(peer(), first[1].clone()).is_none());
+        assert_eq!(
+            reassembler.add(peer(), second[1].clone()),
+            Some(b"cd".to_vec())
+        );
+    }
+
+    #[test]
+    fn messages_from_different_peers_are_not_mixed() {
+        let other: SocketAddr = "[::1]:4000".parse().unwrap();
+        let mut fragmenter = Fragmenter::new();
+        let fragments = fragmenter
+            .fragment(Bytes::from_static(b"ab"), FRAGMENT_HEADER_LEN + 1)
+            .unwrap();
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(5));
+        assert!(reassembler.add(peer(), fragments[0].clone()).is_none());
+        assert!(reassembler.add(other, fragments[1].clone()).is_none());
+        assert_eq!(
+            reassembler.add(peer(), fragments[1].clone()),
+            Some(b"ab".to_vec())
+        );
+    }
+
+    #[test]
+    fn oversized_messages_are_rejected() {
+        let mut fragmenter = Fragmenter::new();
+        let message = Bytes::from(vec![0u8; MAX_MESSAGE_SIZE + 1]);
+        assert!(fragmenter.fragment(message, 1200).is_err());
+        let message = Bytes::from(vec![0u8; MAX_FRAGMENT_COUNT as usize + 1]);
+        assert!(fragmenter
+            .fragment(message, FRAGMENT_HEADER_LEN + 1)
+            .is_err());
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(5));
+        let too_many_fragments = Fragment {
+            message_id: 1,
+            index: 0,
+            count: MAX_FRAGMENT_COUNT + 1,
+            payload: Bytes::from_static(b"a"),
+        };
+        assert!(reassembler.add(peer(), too_many_fragments).is_none());
+        assert!(reassembler.pending.is_empty());
+
+        // the message is dropped as soon as its fragments exceed the maximum size
+        let large_payload = Bytes::from(vec![0u8; MAX_MESSAGE_SIZE / 2 + 1]);
+        for index in 0..2 {
+            let fragment = Fragment {
+                message_id: 2,
+                index,
+                count: 3,
+                payload: large_payload.clone(),
+            };
+            assert!(reassembler.add(peer(), fragment).is_none());
+        }
+        assert!(reassembler.pending.is_empty());
+        assert_eq!(reassembler.pending_bytes, 0);
+    }
+}
@@ -1,12 +1,45 @@
-use super::TransportMessageCodec;
+use super::{
+    Ack, Fragment, Packet, PacketCodec, Reassembler, ReliableReceiver, UdpSenderAddresses,
+    UdpSenderEvent, MAX_RELIABLE_PEERS, SESSION_IDLE_TIMEOUT,
+};
 use crate::UDP;
 use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
-use ockam_core::{async_trait, route, Address, AllowAll, LocalMessage, Processor, Result};
-use ockam_node::Context;
+use ockam_core::compat::sync::Arc;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl};
+use ockam_core::{
+    async_trait, route, Address, AllowAll, Decodable, LocalMessage, OutgoingAccessControl,
+    Processor, Result, TransportMessage,
+};
+use ockam_node::{Context, ProcessorBuilder};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use tokio_util::udp::UdpFramed;
 use tracing::{debug, warn};
 
+/// State of the reliable session started by a peer
+struct ReliablePeer {
+    receiver: ReliableReceiver,
+    /// Fragments are received in order, so messages are never left incomplete
+    reassembler: Reassembler,
+    last_received: Instant,
+}
+
+impl ReliablePeer {
+    fn new(session: u32, now: Instant) -> Self {
+        Self {
+            receiver: ReliableReceiver::new(session),
+            reassembler: Reassembler::new(Duration::MAX),
+            last_received: now,
+        }
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_received) >= SESSION_IDLE_TIMEOUT
+    }
+}
+
 /// A listener for the UDP transport
 ///
 /// This processor handles the reception of messages on a
@@ -15,31 +48,158 @@ use tracing::{debug, warn};
 /// When a message is received, the address of the paired sender
 /// ([`UdpSendWorker`](crate::workers::UdpSendWorker)) is injected into the message's
 /// return route so that replies are sent to the sender.
+///
+/// If the socket is dedicated to a single peer, datagrams from other peers
+/// are dropped and the peer's UDP address is not added to the return route.
+///
+/// Messages are only forwarded once all their fragments have been received.
+/// Fragments sent with reliable delivery are forwarded in order, and
+/// acknowledged by the paired sender. A peer can only start a new reliable
+/// session from its first packet, once its current session is idle, so that
+/// datagrams from a previous session can't reset the current one.
 pub(crate) struct UdpListenProcessor {
     /// The read half of the udnerlying UDP socket.
-    stream: SplitStream<UdpFramed<TransportMessageCodec>>,
-    /// Address of our sender counterpart
-    sender_addr: Address,
+    stream: SplitStream<UdpFramed<PacketCodec>>,
+    /// Addresses of our sender counterpart
+    sender: UdpSenderAddresses,
+    /// The peer this socket is dedicated to, if any
+    peer: Option<SocketAddr>,
+    reassembler: Reassembler,
+    reliable_peers: HashMap<SocketAddr, ReliablePeer>,
 }
 
 impl UdpListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
-        stream: SplitStream<UdpFramed<TransportMessageCodec>>,
-        sender_addr: Address,
+        addr: Address,
+        stream: SplitStream<UdpFramed<PacketCodec>>,
+        sender: UdpSenderAddresses,
+        peer: Option<SocketAddr>,
+        flow_control_id: Option<FlowControlId>,
+        reassembly_timeout: Duration,
     ) -> Result<()> {
         let processor = Self {
             stream,
-            sender_addr,
+            sender,
+            peer,
+            reassembler: Reassembler::new(reassembly_timeout),
+            reliable_peers: HashMap::new(),
+        };
+
+        // When the socket is a Producer, the messages received from the peer are only
+        // delivered to the consumers of its flow control id
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = match flow_control_id {
+            Some(flow_control_id) => {
+                let flow_controls = ctx.flow_controls();
+                flow_controls.add_producer(
+                    addr.clone(),
+                    &flow_control_id,
+                    None,
+                    vec![processor.sender.main.clone()],
+                );
+                // Acknowledgements are sent to the paired sender
+                flow_controls.add_consumer(processor.sender.internal.clone(), &flow_control_id);
+                Arc::new(FlowControlOutgoingAccessControl::new(
+                    flow_controls,
+                    flow_control_id,
+                    None,
+                ))
+            }
+            None => Arc::new(AllowAll),
         };
-        let addr = Address::random_tagged("UdpListenProcessor");
 
         // FIXME: @ac
-        ctx.start_processor_with_access_control(addr.clone(), processor, AllowAll, AllowAll)
+        ProcessorBuilder::new(processor)
+            .with_address(addr)
+            .with_incoming_access_control(AllowAll)
+            .with_outgoing_access_control_arc(outgoing_access_control)
+            .start(ctx)
             .await?;
 
         Ok(())
     }
+
+    /// Decode a reassembled message and forward it
+    async fn forward(&self, ctx: &Context, addr: SocketAddr, data: Vec<u8>) -> Result<()> {
+        let mut msg = match TransportMessage::decode(&data) {
+            Ok(msg) => msg,
+            Err(e) => {
+                warn!(%addr, "Failed to decode message, will wait for next message: {:?}", e);
+                return Ok(());
+            }
+        };
+
+        // Only the listener can notify the sender of acknowledgements
+        if msg.onward_route.next().ok() == Some(&self.sender.internal) {
+            warn!(%addr, "Dropping message sent to the internal address of the sender");
+            return Ok(());
+        }
+
+        // Set return route to go directly to paired sender, skipping the UDP router
+        msg.return_route = match self.peer {
+            Some(_) => route![self.sender.main.clone(), msg.return_route],
+            None => route![
+                self.sender.main.clone(),
+                Address::new(UDP, addr.to_string()),
+                msg.return_route
+            ],
+        };
+
+        debug!(onward_route = %msg.onward_route,
+            return_route = %msg.return_route,
+            "Forwarding UDP message");
+        ctx.forward(LocalMessage::new(msg, vec![])).await
+    }
+
+    /// Return the messages completed by a reliably delivered fragment
+    fn receive_reliable(
+        &mut self,
+        addr: SocketAddr,
+        session: u32,
+        seq: u32,
+        fragment: Fragment,
+    ) -> Option<(Vec<Vec<u8>>, Ack)> {
+        let now = Instant::now();
+        match self.reliable_peers.get(&addr) {
+            Some(reliable_peer) if reliable_peer.receiver.session() == session => {}
+            // A new session replaces the previous one once it is idle
+            Some(reliable_peer) if seq == 0 && reliable_peer.is_idle(now) => {
+                debug!(%addr, "Peer started a new reliable session");
+                self.reliable_peers
+                    .insert(addr, ReliablePeer::new(session, now));
+            }
+            Some(_) => {
+                debug!(%addr, "Dropping datagram from another reliable session");
+                return None;
+            }
+            None if seq != 0 => {
+                debug!(%addr, "Dropping datagram from an unknown reliable session");
+                return None;
+            }
+            None => {
+                if self.reliable_peers.len() >= MAX_RELIABLE_PEERS {
+                    self.reliable_peers
+                        .retain(|_, reliable_peer| !reliable_peer.is_idle(now));
+                }
+                if self.reliable_peers.len() >= MAX_RELIABLE_PEERS {
+                    warn!(%addr, "Too many peers using reliable delivery, dropping datagram");
+                    return None;
+                }
+                self.reliable_peers
+                    .insert(addr, ReliablePeer::new(session, now));
+            }
+        }
+        let reliable_peer = self.reliable_peers.get_mut(&addr)?;
+        reliable_peer.last_received = now;
+
+        let messages = reliable_peer
+            .receiver
+            .receive(seq, fragment)
+            .into_iter()
+            .filter_map(|fragment| reliable_peer.reassembler.add(addr, fragment))
+            .collect();
+        Some((messages, reliable_peer.receiver.ack()))
+    }
 }
 
 #[async_trait]
@@ -52,9 +212,9 @@ impl Processor for UdpListenProcessor {
 
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming UDP datagram...");
-        let (mut msg, addr) = match self.stream.next().await {
+        let (packet, addr) = match self.stream.next().await {
             Some(res) => match res {
-                Ok((msg, addr)) => (msg, addr),
+                Ok((packet, addr)) => (packet, addr),
                 Err(e) => {
                     warn!(
                         "Failed to read message, will wait for next message: {:?}",
@@ -69,17 +229,43 @@ impl Processor for UdpListenProcessor {
             }
         };
 
-        // Set return route to go directly to paired sender, skipping the UDP router
-        msg.return_route = route![
-            self.sender_addr.clone(),
-            Address::new(UDP, addr.to_string()),
-            msg.return_route
-        ];
+        if let Some(peer) = self.peer {
+            if peer != addr {
+                warn!(%addr, %peer, "Dropping datagram from unexpected peer");
+                return Ok(true);
+            }
+        }
 
-        debug!(onward_route = %msg.onward_route,
-            return_route = %msg.return_route,
-            "Forwarding UDP message");
-        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+        match packet {
+            Packet::Fragment(fragment) => {
+                if let Some(data) = self.reassembler.add(addr, fragment) {
+                    self.forward(ctx, addr, data).await?;
+                }
+            }
+            Packet::Data {
+                session,
+                seq,
+                fragment,
+            } => {
+                if let Some((messages, ack)) = self.receive_reliable(addr, session, seq, fragment) {
+                    ctx.send(
+                        self.sender.internal.clone(),
+                        UdpSenderEvent::SendAck { peer: addr, ack },
+                    )
+                    .await?;
+                    for data in messages {
+                        self.forward(ctx, addr, data).await?;
+                    }
+                }
+            }
+            Packet::Ack(ack) => {
+                ctx.send(
+                    self.sender.internal.clone(),
+                    UdpSenderEvent::AckReceived { peer: addr, ack },
+                )
+                .await?;
+            }
+        }
 
         Ok(true)
     }
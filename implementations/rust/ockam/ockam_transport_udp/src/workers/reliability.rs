@@ -0,0 +1,616 @@
+use super::Fragment;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Maximum number of packets which can be sent ahead of the first unacknowledged packet
+pub(crate) const RECEIVE_WINDOW: u32 = 1024;
+
+/// Maximum number of peers with which a socket keeps reliable sessions
+pub(crate) const MAX_RELIABLE_PEERS: usize = 1024;
+
+/// Maximum number of consecutive retransmission timeouts before giving up
+pub(crate) const MAX_RETRANSMISSIONS: u32 = 8;
+
+/// Time without receiving anything from a peer after which its reliable session
+/// can be replaced by a new one
+pub(crate) const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of selective acknowledgement ranges sent in an [`Ack`]
+const MAX_ACK_RANGES: usize = 32;
+
+/// Number of packets transmitted and acknowledged after a packet before it is considered lost
+const DUPLICATE_THRESHOLD: u64 = 3;
+
+const INITIAL_WINDOW: f64 = 4.0;
+const MIN_WINDOW: f64 = 2.0;
+
+const INITIAL_RTO: Duration = Duration::from_secs(1);
+const MIN_RTO: Duration = Duration::from_millis(200);
+const MAX_RTO: Duration = Duration::from_secs(10);
+
+/// Sequence numbers are sent as 32 bits integers which wrap around.
+/// Return the 64 bits sequence number closest to `reference` having the given
+/// 32 lower bits, using serial number arithmetic (RFC 1982)
+fn extend_seq(seq: u32, reference: u64) -> Option<u64> {
+    let delta = seq.wrapping_sub(reference as u32) as i32;
+    if delta >= 0 {
+        reference.checked_add(delta as u64)
+    } else {
+        reference.checked_sub(delta.unsigned_abs() as u64)
+    }
+}
+
+/// Acknowledgement of the packets received by a [`ReliableReceiver`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Ack {
+    /// Session of the acknowledged packets
+    pub(crate) session: u32,
+    /// Every packet before this sequence number was received
+    pub(crate) next_expected: u32,
+    /// Ranges `[start, end)` of packets received after `next_expected`
+    pub(crate) ranges: Vec<(u32, u32)>,
+}
+
+/// Receiving side of a reliable session
+///
+/// Fragments are returned in the order of their sequence numbers,
+/// duplicates are dropped.
+pub(crate) struct ReliableReceiver {
+    session: u32,
+    next_expected: u64,
+    out_of_order: BTreeMap<u64, Fragment>,
+}
+
+impl ReliableReceiver {
+    pub(crate) fn new(session: u32) -> Self {
+        Self {
+            session,
+            next_expected: 0,
+            out_of_order: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn session(&self) -> u32 {
+        self.session
+    }
+
+    /// Add a received packet and return the fragments which can now be delivered in order
+    pub(crate) fn receive(&mut self, seq: u32, fragment: Fragment) -> Vec<Fragment> {
+        let seq = match extend_seq(seq, self.next_expected) {
+            Some(seq)
+                if seq >= self.next_expected
+                    && seq - self.next_expected < RECEIVE_WINDOW as u64 =>
+            {
+                seq
+            }
+            _ => return vec![],
+        };
+        self.out_of_order.insert(seq, fragment);
+
+        let mut delivered = vec![];
+        while let Some(fragment) = self.out_of_order.remove(&self.next_expected) {
+            delivered.push(fragment);
+            self.next_expected += 1;
+        }
+        delivered
+    }
+
+    /// Acknowledgement of all the packets received so far
+    pub(crate) fn ack(&self) -> Ack {
+        let mut ranges: Vec<(u64, u64)> = vec![];
+        for seq in self.out_of_order.keys() {
+            if let Some((_, end)) = ranges.last_mut() {
+                if *end == *seq {
+                    *end += 1;
+                    continue;
+                }
+            }
+            if ranges.len() == MAX_ACK_RANGES {
+                break;
+            }
+            ranges.push((*seq, *seq + 1));
+        }
+        Ack {
+            session: self.session,
+            next_expected: self.next_expected as u32,
+            ranges: ranges
+                .into_iter()
+                .map(|(start, end)| (start as u32, end as u32))
+                .collect(),
+        }
+    }
+}
+
+/// Retransmission timeout computation, as specified in RFC 6298
+pub(crate) struct RttEstimator {
+    srtt: Option<Duration>,
+    rttvar: Duration,
+    rto: Duration,
+}
+
+impl Default for RttEstimator {
+    fn default() -> Self {
+        Self {
+            srtt: None,
+            rttvar: Duration::ZERO,
+            rto: INITIAL_RTO,
+        }
+    }
+}
+
+impl RttEstimator {
+    /// Update the estimation with the round-trip time of a packet which was not retransmitted
+    pub(crate) fn update(&mut self, sample: Duration) {
+        let srtt = match self.srtt {
+            None => {
+                self.rttvar = sample / 2;
+                sample
+            }
+            Some(srtt) => {
+                let delta = if srtt > sample {
+                    srtt - sample
+                } else {
+                    sample - srtt
+                };
+                self.rttvar = self.rttvar * 3 / 4 + delta / 4;
+                srtt * 7 / 8 + sample / 8
+            }
+        };
+        self.srtt = Some(srtt);
+        self.rto = (srtt + self.rttvar * 4).max(MIN_RTO).min(MAX_RTO);
+    }
+
+    /// Double the timeout after a retransmission timeout
+    pub(crate) fn backoff(&mut self) {
+        self.rto = (self.rto * 2).min(MAX_RTO);
+    }
+
+    pub(crate) fn rto(&self) -> Duration {
+        self.rto
+    }
+}
+
+struct InFlight {
+    fragment: Fragment,
+    sent_at: Instant,
+    /// Order of the last transmission of the packet, among all transmissions
+    sent_order: u64,
+    retransmitted: bool,
+}
+
+/// Sending side of a reliable session
+///
+/// Every packet gets a sequence number and is kept until it is acknowledged.
+/// Packets are retransmitted when the receiver acknowledges packets
+/// transmitted later, or after a retransmission timeout. The number of packets
+/// in flight is limited by a congestion window which grows with
+/// acknowledgements and shrinks on losses.
+pub(crate) struct ReliableSender {
+    session: u32,
+    next_seq: u64,
+    queue: VecDeque<Fragment>,
+    in_flight: BTreeMap<u64, InFlight>,
+    /// Packets in flight which must be retransmitted
+    lost: BTreeSet<u64>,
+    /// Transmission order of the next packet sent
+    next_order: u64,
+    /// Highest transmission order of the acknowledged packets
+    highest_acked: Option<u64>,
+    rtt: RttEstimator,
+    cwnd: f64,
+    ssthresh: f64,
+    /// Losses are not reported again until this sequence number is acknowledged
+    recovery_until: Option<u64>,
+    timeouts: u32,
+}
+
+impl ReliableSender {
+    pub(crate) fn new() -> Self {
+        Self {
+            session: rand::random(),
+            next_seq: 0,
+            queue: VecDeque::new(),
+            in_flight: BTreeMap::new(),
+            lost: BTreeSet::new(),
+            next_order: 0,
+            highest_acked: None,
+            rtt: RttEstimator::default(),
+            cwnd: INITIAL_WINDOW,
+            ssthresh: RECEIVE_WINDOW as f64,
+            recovery_until: None,
+            timeouts: 0,
+        }
+    }
+
+    pub(crate) fn session(&self) -> u32 {
+        self.session
+    }
+
+    /// Queue a fragment for sending
+    pub(crate) fn push(&mut self, fragment: Fragment) {
+        self.queue.push_back(fragment)
+    }
+
+    fn pipe(&self) -> usize {
+        self.in_flight.len() - self.lost.len()
+    }
+
+    fn window(&self) -> usize {
+        (self.cwnd as usize).max(1)
+    }
+
+    /// Return the packets which can be sent now, retransmissions first
+    pub(crate) fn transmit(&mut self, now: Instant) -> Vec<(u32, Fragment)> {
+        let mut packets = vec![];
+        while self.pipe() < self.window() {
+            if let Some(seq) = self.lost.iter().next().copied() {
+                self.lost.remove(&seq);
+                if let Some(packet) = self.in_flight.get_mut(&seq) {
+                    packet.sent_at = now;
+                    packet.sent_order = self.next_order;
+                    packet.retransmitted = true;
+                    self.next_order += 1;
+                    packets.push((seq as u32, packet.fragment.clone()));
+                }
+                continue;
+            }
+
+            let first_unacked = self.in_flight.keys().next().copied();
+            if self.next_seq - first_unacked.unwrap_or(self.next_seq) >= RECEIVE_WINDOW as u64 {
+                break;
+            }
+            let fragment = match self.queue.pop_front() {
+                Some(fragment) => fragment,
+                None => break,
+            };
+            self.in_flight.insert(
+                self.next_seq,
+                InFlight {
+                    fragment: fragment.clone(),
+                    sent_at: now,
+                    sent_order: self.next_order,
+                    retransmitted: false,
+                },
+            );
+            self.next_order += 1;
+            packets.push((self.next_seq as u32, fragment));
+            self.next_seq += 1;
+        }
+        packets
+    }
+
+    /// Remove the acknowledged packets and detect the lost ones
+    pub(crate) fn on_ack(&mut self, ack: &Ack, now: Instant) {
+        if ack.session != self.session {
+            return;
+        }
+
+        // The acknowledged sequence numbers are close to the first unacknowledged packet
+        let reference = self
+            .in_flight
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(self.next_seq);
+        let next_expected = extend_seq(ack.next_expected, reference).unwrap_or(0);
+        let ranges: Vec<(u64, u64)> = ack
+            .ranges
+            .iter()
+            .filter_map(|(start, end)| {
+                let len = end.wrapping_sub(*start) as u64;
+                let start = extend_seq(*start, reference)?;
+                Some((start, start + len))
+            })
+            .collect();
+
+        let acked: Vec<u64> = self
+            .in_flight
+            .keys()
+            .filter(|seq| {
+                **seq < next_expected
+                    || ranges
+                        .iter()
+                        .any(|(start, end)| *start <= **seq && **seq < *end)
+            })
+            .copied()
+            .collect();
+        if acked.is_empty() {
+            return;
+        }
+        // Early retransmit (RFC 5827): with few packets outstanding,
+        // fewer packets can be acknowledged after a lost one
+        let threshold = DUPLICATE_THRESHOLD
+            .min(self.in_flight.len() as u64 - 1)
+            .max(1);
+
+        let mut sample = None;
+        for seq in &acked {
+            if let Some(packet) = self.in_flight.remove(seq) {
+                // Karn's algorithm: retransmitted packets give ambiguous samples
+                if !packet.retransmitted {
+                    sample = Some(now.saturating_duration_since(packet.sent_at));
+                }
+                self.highest_acked = self.highest_acked.max(Some(packet.sent_order));
+            }
+            self.lost.remove(seq);
+        }
+        if let Some(sample) = sample {
+            self.rtt.update(sample);
+        }
+        self.timeouts = 0;
+
+        if matches!(self.recovery_until, Some(seq) if next_expected >= seq) {
+            self.recovery_until = None;
+        }
+        if self.recovery_until.is_none() {
+            let acked = acked.len() as f64;
+            self.cwnd += if self.cwnd < self.ssthresh {
+                acked
+            } else {
+                acked / self.cwnd
+            };
+            self.cwnd = self.cwnd.min(RECEIVE_WINDOW as f64);
+        }
+
+        // A packet is lost when enough packets transmitted after it were acknowledged
+        let highest_acked = match self.highest_acked {
+            Some(order) => order,
+            None => return,
+        };
+        let lost: Vec<u64> = self
+            .in_flight
+            .iter()
+            .filter(|(seq, packet)| {
+                packet.sent_order + threshold <= highest_acked && !self.lost.contains(seq)
+            })
+            .map(|(seq, _)| *seq)
+            .collect();
+        if !lost.is_empty() {
+            self.lost.extend(lost);
+            if self.recovery_until.is_none() {
+                self.ssthresh = (self.cwnd / 2.0).max(MIN_WINDOW);
+                self.cwnd = self.ssthresh;
+                self.recovery_until = Some(self.next_seq);
+            }
+        }
+    }
+
+    /// Time at which the oldest packet in flight must be retransmitted
+    pub(crate) fn next_timeout(&self) -> Option<Instant> {
+        self.in_flight
+            .iter()
+            .filter(|(seq, _)| !self.lost.contains(seq))
+            .map(|(_, packet)| packet.sent_at)
+            .min()
+            .map(|sent_at| sent_at + self.rtt.rto())
+    }
+
+    /// Handle a retransmission timeout: every packet in flight is retransmitted
+    /// and the congestion window restarts from one packet.
+    ///
+    /// Return an error when too many retransmissions failed in a row
+    pub(crate) fn on_timeout(&mut self, now: Instant) -> Result<()> {
+        match self.next_timeout() {
+            Some(deadline) if deadline <= now => {}
+            _ => return Ok(()),
+        }
+
+        self.timeouts += 1;
+        if self.timeouts > MAX_RETRANSMISSIONS {
+            return Err(TransportError::ConnectionDrop.into());
+        }
+
+        self.rtt.backoff();
+        self.ssthresh = (self.cwnd / 2.0).max(MIN_WINDOW);
+        self.cwnd = 1.0;
+        self.recovery_until = None;
+        self.lost.extend(self.in_flight.keys().copied());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    fn fragment(i: u32) -> Fragment {
+        Fragment {
+            message_id: i,
+            index: 0,
+            count: 1,
+            payload: Bytes::from(i.to_be_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn receiver_delivers_in_order() {
+        let mut receiver = ReliableReceiver::new(1);
+        assert!(receiver.receive(1, fragment(1)).is_empty());
+        assert!(receiver.receive(3, fragment(3)).is_empty());
+        assert!(receiver.receive(4, fragment(4)).is_empty());
+        assert_eq!(
+            receiver.ack(),
+            Ack {
+                session: 1,
+                next_expected: 0,
+                ranges: vec![(1, 2), (3, 5)]
+            }
+        );
+
+        assert_eq!(
+            receiver.receive(0, fragment(0)),
+            vec![fragment(0), fragment(1)]
+        );
+        // duplicates are dropped
+        assert!(receiver.receive(0, fragment(0)).is_empty());
+        assert_eq!(
+            receiver.receive(2, fragment(2)),
+            vec![fragment(2), fragment(3), fragment(4)]
+        );
+        assert_eq!(receiver.ack().next_expected, 5);
+        assert!(receiver.ack().ranges.is_empty());
+    }
+
+    #[test]
+    fn rto_follows_the_rtt() {
+        let mut rtt = RttEstimator::default();
+        assert_eq!(rtt.rto(), INITIAL_RTO);
+
+        rtt.update(Duration::from_millis(100));
+        assert_eq!(rtt.rto(), Duration::from_millis(300));
+        rtt.backoff();
+        assert_eq!(rtt.rto(), Duration::from_millis(600));
+
+        for _ in 0..100 {
+            rtt.update(Duration::from_millis(10));
+        }
+        assert_eq!(rtt.rto(), MIN_RTO);
+    }
+
+    #[test]
+    fn sender_respects_the_congestion_window() {
+        let now = Instant::now();
+        let mut sender = ReliableSender::new();
+        for i in 0..100 {
+            sender.push(fragment(i));
+        }
+
+        let sent = sender.transmit(now);
+        assert_eq!(sent.len(), INITIAL_WINDOW as usize);
+        assert!(sender.transmit(now).is_empty());
+
+        // slow start: every acknowledged packet allows two more packets
+        sender.on_ack(
+            &Ack {
+                session: sender.session(),
+                next_expected: 4,
+                ranges: vec![],
+            },
+            now,
+        );
+        assert_eq!(sender.transmit(now).len(), 8);
+    }
+
+    #[test]
+    fn sender_retransmits_lost_packets() {
+        let now = Instant::now();
+        let mut sender = ReliableSender::new();
+        for i in 0..4 {
+            sender.push(fragment(i));
+        }
+        assert_eq!(sender.transmit(now).len(), 4);
+
+        // packet 0 is missing while the 3 next ones are received
+        sender.on_ack(
+            &Ack {
+                session: sender.session(),
+                next_expected: 0,
+                ranges: vec![(1, 4)],
+            },
+            now,
+        );
+        assert_eq!(sender.transmit(now), vec![(0, fragment(0))]);
+        // the window grew to 7 packets with the acknowledgements, then was halved
+        assert_eq!(sender.cwnd, 3.5);
+
+        // if the retransmission is lost too, the timeout triggers another one
+        let later = now + INITIAL_RTO;
+        sender.on_timeout(later).unwrap();
+        assert_eq!(sender.transmit(later), vec![(0, fragment(0))]);
+        assert_eq!(sender.cwnd, 1.0);
+    }
+
+    #[test]
+    fn sender_gives_up_after_too_many_timeouts() {
+        let mut now = Instant::now();
+        let mut sender = ReliableSender::new();
+        sender.push(fragment(0));
+        sender.transmit(now);
+
+        for _ in 0..MAX_RETRANSMISSIONS {
+            now = sender.next_timeout().unwrap();
+            sender.on_timeout(now).unwrap();
+            assert_eq!(sender.transmit(now).len(), 1);
+        }
+        now = sender.next_timeout().unwrap();
+        assert!(sender.on_timeout(now).is_err());
+    }
+
+    #[test]
+    fn lossy_transfer_is_complete_and_ordered() {
+        let mut now = Instant::now();
+        let mut sender = ReliableSender::new();
+        let mut receiver = ReliableReceiver::new(sender.session());
+        for i in 0..500 {
+            sender.push(fragment(i));
+        }
+
+        let mut delivered = vec![];
+        let mut datagrams = 0;
+        while delivered.len() < 500 {
+            let mut packets = sender.transmit(now);
+            if packets.is_empty() {
+                now = sender.next_timeout().unwrap();
+                sender.on_timeout(now).unwrap();
+                continue;
+            }
+            // deliver the packets in reverse order and lose one out of 7
+            packets.reverse();
+            for (seq, fragment) in packets {
+                datagrams += 1;
+                if datagrams % 7 == 0 {
+                    continue;
+                }
+                delivered.extend(receiver.receive(seq, fragment));
+                now += Duration::from_millis(1);
+                sender.on_ack(&receiver.ack(), now);
+            }
+        }
+
+        assert_eq!(delivered, (0..500).map(fragment).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        let mut now = Instant::now();
+        let mut sender = ReliableSender::new();
+        let mut receiver = ReliableReceiver::new(sender.session());
+        sender.next_seq = u32::MAX as u64 - 2;
+        receiver.next_expected = u32::MAX as u64 - 2;
+        for i in 0..10 {
+            sender.push(fragment(i));
+        }
+
+        let mut delivered = vec![];
+        let mut seqs = vec![];
+        let mut lost_once = false;
+        while delivered.len() < 10 {
+            let packets = sender.transmit(now);
+            if packets.is_empty() {
+                now = sender.next_timeout().unwrap();
+                sender.on_timeout(now).unwrap();
+                continue;
+            }
+            for (seq, fragment) in packets {
+                seqs.push(seq);
+                // the first packet after the wrap around is lost once
+                if seq == 0 && !lost_once {
+                    lost_once = true;
+                    continue;
+                }
+                delivered.extend(receiver.receive(seq, fragment));
+                now += Duration::from_millis(1);
+                sender.on_ack(&receiver.ack(), now);
+            }
+        }
+
+        assert_eq!(delivered, (0..10).map(fragment).collect::<Vec<_>>());
+        assert!(seqs.contains(&u32::MAX) && seqs.iter().filter(|s| **s == 0).count() == 2);
+        // packets from before the wrap around are old packets
+        assert!(receiver.receive(u32::MAX, fragment(0)).is_empty());
+    }
+}
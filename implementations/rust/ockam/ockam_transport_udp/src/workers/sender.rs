@@ -1,28 +1,280 @@
-use super::TransportMessageCodec;
+use super::{
+    Ack, Fragmenter, Packet, PacketCodec, ReliableSender, DATA_HEADER_LEN, MAX_RELIABLE_PEERS,
+    PACKET_HEADER_LEN,
+};
 use crate::UDP;
 use futures_util::{stream::SplitSink, SinkExt};
-use ockam_core::{async_trait, Any, Result, Routed, TransportMessage, Worker};
-use ockam_node::Context;
+use ockam_core::{
+    async_trait, Address, Any, Decodable, Encodable, Message, Result, Routed, TransportMessage,
+    Worker,
+};
+use ockam_node::{Context, DelayedEvent};
 use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Instant;
 use tokio_util::udp::UdpFramed;
-use tracing::{error, trace, warn};
+use tracing::{debug, error, trace, warn};
+
+/// Events handled on the internal address of a [`UdpSendWorker`]
+#[derive(Serialize, Deserialize, Debug, Clone, Message)]
+pub(crate) enum UdpSenderEvent {
+    /// An acknowledgement was received from a peer
+    AckReceived { peer: SocketAddr, ack: Ack },
+    /// Reliable data was received from a peer, send it an acknowledgement
+    SendAck { peer: SocketAddr, ack: Ack },
+    /// Use reliable delivery for the messages sent to a peer
+    EnableReliableDelivery { peer: SocketAddr },
+    /// Check the retransmission timeouts
+    Timeout,
+}
+
+/// Addresses of a [`UdpSendWorker`]
+#[derive(Debug, Clone)]
+pub(crate) struct UdpSenderAddresses {
+    /// Address receiving the messages to send
+    pub(crate) main: Address,
+    /// Address receiving [`UdpSenderEvent`]s
+    pub(crate) internal: Address,
+}
 
 /// A sender for the UDP transport
 ///
 /// This worker handles the sending of messages on a
 /// local socket. See [`UdpRouter`](crate::router::UdpRouter) for more details.
+///
+/// A sender created for a single peer sends every message to that peer,
+/// otherwise the next hop of the onward route must be a UDP address.
+///
+/// Messages are split into fragments which fit in a single datagram.
+///
+/// Fragments sent to a peer using reliable delivery are numbered and
+/// retransmitted until they are acknowledged. Reliable delivery is used when
+/// it was enabled for the peer, or when the peer itself uses reliable delivery.
+///
+/// When a peer stops acknowledging packets, a socket dedicated to that peer is
+/// closed. Otherwise the messages sent to the peer are rejected until reliable
+/// delivery is enabled again, or sent without reliable delivery if the session
+/// was only used to reply to the peer.
 pub(crate) struct UdpSendWorker {
     /// The read half of the udnerlying UDP socket.
-    sink: SplitSink<UdpFramed<TransportMessageCodec>, (TransportMessage, SocketAddr)>,
+    sink: SplitSink<UdpFramed<PacketCodec>, (Packet, SocketAddr)>,
+    /// The peer this sender is dedicated to, if any
+    peer: Option<SocketAddr>,
+    /// Whether the underlying socket is an IPv6 socket
+    ipv6: bool,
+    max_datagram_size: usize,
+    fragmenter: Fragmenter,
+    internal_addr: Address,
+    /// Address of the paired listener, stopped with this worker
+    listener_addr: Address,
+    /// Reliable sessions, by peer
+    sessions: HashMap<SocketAddr, PeerSession>,
+    /// Peers which stopped acknowledging the packets of a session enabled locally
+    failed_peers: HashSet<SocketAddr>,
+    timeout: Option<DelayedEvent<UdpSenderEvent>>,
+}
+
+/// Reliable session used to send messages to a peer
+struct PeerSession {
+    sender: ReliableSender,
+    /// False if the session is only used to reply to a peer using reliable delivery
+    enabled: bool,
+}
+
+impl PeerSession {
+    fn new(enabled: bool) -> Self {
+        Self {
+            sender: ReliableSender::new(),
+            enabled,
+        }
+    }
 }
 
 impl UdpSendWorker {
     /// Create a new `UdpSendWorker`
     pub(crate) fn new(
-        sink: SplitSink<UdpFramed<TransportMessageCodec>, (TransportMessage, SocketAddr)>,
+        sink: SplitSink<UdpFramed<PacketCodec>, (Packet, SocketAddr)>,
+        peer: Option<SocketAddr>,
+        ipv6: bool,
+        max_datagram_size: usize,
+        internal_addr: Address,
+        listener_addr: Address,
+        reliable_delivery: bool,
     ) -> Self {
-        Self { sink }
+        let mut sessions = HashMap::new();
+        if let (Some(peer), true) = (peer, reliable_delivery) {
+            sessions.insert(peer, PeerSession::new(true));
+        }
+        Self {
+            sink,
+            peer,
+            ipv6,
+            max_datagram_size,
+            fragmenter: Fragmenter::new(),
+            internal_addr,
+            listener_addr,
+            sessions,
+            failed_peers: HashSet::new(),
+            timeout: None,
+        }
+    }
+
+    /// Remove the UDP address of the peer from the onward route
+    /// and resolve it to a `SocketAddr` of the same IP version as the socket
+    fn resolve_next_hop(&self, msg: &mut TransportMessage) -> Result<SocketAddr> {
+        let peer_addr = msg.onward_route.step()?;
+
+        if peer_addr.transport_type() != UDP {
+            error!(addr = %peer_addr,
+                "Destination address is not UDP");
+            return Err(TransportError::UnknownRoute.into());
+        }
+
+        let peer_addr = peer_addr.address();
+        let mut peer_addrs = peer_addr
+            .to_socket_addrs()
+            .map_err(|_| TransportError::InvalidAddress)?;
+
+        // Try to send to first SocketAddr
+        match peer_addrs.find(|a| a.is_ipv6() == self.ipv6) {
+            Some(a) => Ok(a),
+            None => {
+                warn!(
+                    "No IPv{} address resolved for peer {:?}",
+                    if self.ipv6 { 6 } else { 4 },
+                    peer_addr
+                );
+                Err(TransportError::UnknownRoute.into())
+            }
+        }
+    }
+
+    async fn send(&mut self, packet: Packet, addr: SocketAddr) -> Result<()> {
+        match self.sink.send((packet, addr)).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Failed send to {}: {:?}", addr, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Send the packets of the reliable session with `peer` allowed by its window
+    async fn flush(&mut self, peer: SocketAddr) -> Result<()> {
+        let packets: Vec<Packet> = match self.sessions.get_mut(&peer) {
+            Some(PeerSession { sender, .. }) => {
+                let session = sender.session();
+                sender
+                    .transmit(Instant::now())
+                    .into_iter()
+                    .map(|(seq, fragment)| Packet::Data {
+                        session,
+                        seq,
+                        fragment,
+                    })
+                    .collect()
+            }
+            None => return Ok(()),
+        };
+        for packet in packets {
+            self.send(packet, peer).await?;
+        }
+        Ok(())
+    }
+
+    /// Schedule the next retransmission timeout, if any packet is in flight
+    async fn schedule_timeout(&mut self) -> Result<()> {
+        let next_timeout = self
+            .sessions
+            .values()
+            .filter_map(|s| s.sender.next_timeout())
+            .min();
+        if let Some(timeout) = self.timeout.as_mut() {
+            match next_timeout {
+                Some(at) => {
+                    timeout
+                        .schedule(at.saturating_duration_since(Instant::now()))
+                        .await?
+                }
+                None => timeout.cancel(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the reliable session with a peer which doesn't acknowledge packets anymore
+    async fn give_up(&mut self, ctx: &Context, peer: SocketAddr) -> Result<()> {
+        let session = match self.sessions.remove(&peer) {
+            Some(session) => session,
+            None => return Ok(()),
+        };
+        if self.peer == Some(peer) {
+            warn!(%peer, "Peer doesn't acknowledge packets, closing the socket");
+            return ctx.stop_worker(ctx.address()).await;
+        }
+        if session.enabled {
+            warn!(%peer, "Peer doesn't acknowledge packets, rejecting the messages sent to it");
+            if self.failed_peers.len() < MAX_RELIABLE_PEERS {
+                self.failed_peers.insert(peer);
+            }
+        } else {
+            warn!(%peer, "Peer doesn't acknowledge packets, replying without reliable delivery");
+        }
+        Ok(())
+    }
+
+    async fn handle_event(&mut self, ctx: &Context, event: UdpSenderEvent) -> Result<()> {
+        match event {
+            UdpSenderEvent::AckReceived { peer, ack } => {
+                if let Some(session) = self.sessions.get_mut(&peer) {
+                    session.sender.on_ack(&ack, Instant::now());
+                }
+                self.flush(peer).await?;
+            }
+            UdpSenderEvent::SendAck { peer, ack } => {
+                // Reply with reliable delivery to a peer using reliable delivery, once its
+                // session delivered its first packet, so that a stray datagram can't
+                // switch the replies to reliable delivery
+                let reply_reliably = ack.next_expected != 0;
+                self.send(Packet::Ack(ack), peer).await?;
+                if reply_reliably
+                    && !self.sessions.contains_key(&peer)
+                    && !self.failed_peers.contains(&peer)
+                    && self.sessions.len() < MAX_RELIABLE_PEERS
+                {
+                    debug!(%peer, "Replying with reliable delivery");
+                    self.sessions.insert(peer, PeerSession::new(false));
+                }
+            }
+            UdpSenderEvent::EnableReliableDelivery { peer } => {
+                debug!(%peer, "Using reliable delivery");
+                self.failed_peers.remove(&peer);
+                self.sessions
+                    .entry(peer)
+                    .or_insert_with(|| PeerSession::new(true))
+                    .enabled = true;
+            }
+            UdpSenderEvent::Timeout => {
+                let now = Instant::now();
+                let given_up: Vec<SocketAddr> = self
+                    .sessions
+                    .iter_mut()
+                    .filter_map(|(peer, session)| {
+                        session.sender.on_timeout(now).err().map(|_| *peer)
+                    })
+                    .collect();
+                for peer in given_up {
+                    self.give_up(ctx, peer).await?;
+                }
+                let peers: Vec<SocketAddr> = self.sessions.keys().copied().collect();
+                for peer in peers {
+                    self.flush(peer).await?;
+                }
+            }
+        }
+        self.schedule_timeout().await
     }
 }
 
@@ -31,58 +283,79 @@ impl Worker for UdpSendWorker {
     type Message = Any;
     type Context = Context;
 
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        self.timeout = Some(
+            DelayedEvent::create(ctx, self.internal_addr.clone(), UdpSenderEvent::Timeout).await?,
+        );
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // the listener may already be stopped when the node shuts down
+        let _ = ctx.stop_processor(self.listener_addr.clone()).await;
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if msg.msg_addr() == self.internal_addr {
+            let event = UdpSenderEvent::decode(msg.payload())?;
+            return self.handle_event(ctx, event).await;
+        }
+
         // Parse message and remove our address from its routing
         let mut msg = msg.into_transport_message();
         msg.onward_route.step()?;
 
         trace!("Sending message to {:?}", msg.onward_route);
 
-        // Resolve peer address to IPv4 SocketAddr(s).
-        let peer_addr = msg.onward_route.step()?;
-
-        if peer_addr.transport_type() != UDP {
-            error!(addr = %peer_addr,
-                "Destination address is not UDP");
-            return Err(TransportError::UnknownRoute.into());
-        }
-
-        let peer_addr = peer_addr.address();
-        let peer_addrs = peer_addr
-            .to_socket_addrs()
-            .map_err(|_| TransportError::InvalidAddress)?;
-        let peer_addrs: Vec<_> = peer_addrs.filter(SocketAddr::is_ipv4).collect();
-
-        // Try to send to first SocketAddr
-        let addr = match peer_addrs.first() {
-            Some(a) => *a,
-            None => {
-                warn!("No IPv4 address resolved for peer {:?}", peer_addr);
-                return Err(TransportError::UnknownRoute.into());
-            }
+        // Resolve peer address to a SocketAddr
+        let addr = match self.peer {
+            Some(peer) => peer,
+            None => self.resolve_next_hop(&mut msg)?,
         };
 
         // Error on conditions that _might_ put the sink
         // into an error state
         if addr.port() == 0 {
-            warn!(peer_addr = %peer_addr, "Will not send to address");
+            warn!(peer_addr = %addr, "Will not send to address");
             return Err(TransportError::InvalidAddress.into());
         }
+        if self.failed_peers.contains(&addr) {
+            warn!(peer_addr = %addr, "Peer doesn't acknowledge packets, will not send to address");
+            return Err(TransportError::ConnectionDrop.into());
+        }
+
+        let msg_buf = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
 
         // Send
-        match self.sink.send((msg.clone(), addr)).await {
-            Ok(()) => {
-                trace!("Successful send to {}", addr);
-                Ok(())
+        if self.sessions.contains_key(&addr) {
+            let max_fragment_size = self
+                .max_datagram_size
+                .saturating_sub(PACKET_HEADER_LEN + DATA_HEADER_LEN);
+            let fragments = self
+                .fragmenter
+                .fragment(msg_buf.into(), max_fragment_size)?;
+            if let Some(session) = self.sessions.get_mut(&addr) {
+                fragments.into_iter().for_each(|f| session.sender.push(f));
             }
-            Err(e) => {
-                error!("Failed send to {}: {:?}", addr, e);
-                Err(e.into())
+            self.flush(addr).await?;
+            self.schedule_timeout().await?;
+        } else {
+            let max_fragment_size = self.max_datagram_size.saturating_sub(PACKET_HEADER_LEN);
+            let fragments = self
+                .fragmenter
+                .fragment(msg_buf.into(), max_fragment_size)?;
+            trace!("Sending {} datagram(s) to {}", fragments.len(), addr);
+            for fragment in fragments {
+                self.send(Packet::Fragment(fragment), addr).await?;
             }
         }
+
+        trace!("Successful send to {}", addr);
+        Ok(())
     }
 }
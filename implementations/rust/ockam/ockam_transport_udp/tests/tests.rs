@@ -1,7 +1,7 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
 use ockam_node::{Context, MessageReceiveOptions, MessageSendReceiveOptions};
-use ockam_transport_udp::{UdpTransport, UDP};
+use ockam_transport_udp::{UdpConnectionOptions, UdpTransport, UdpTransportOptions, UDP};
 use std::net::SocketAddr;
 use std::time::Duration;
 use tracing::{debug, error, trace};
@@ -192,6 +192,231 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// A connected sender should deliver messages to its peer
+/// without needing a UDP address in the onward route.
+#[ockam_macros::test]
+async fn send_receive_connected(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+    debug!("bind_addr = {:?}", bind_addr);
+
+    // Transport
+    let transport = UdpTransport::create(ctx).await?;
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.clone()).await?;
+
+    // Connected sender
+    let sender = transport.connect(bind_addr).await?;
+    for _ in 0..3 {
+        let msg = String::from("Ockam. Testing. 1, 2, 3...");
+        let reply = ctx
+            .send_and_receive_extended::<String>(
+                route![sender.clone(), "echoer"],
+                msg.clone(),
+                MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+            )
+            .await?;
+
+        assert_eq!(reply.return_route().next()?, &sender);
+        assert_eq!(reply.body(), msg, "Should receive the same message");
+    }
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// Messages larger than a datagram should be fragmented and reassembled
+#[ockam_macros::test]
+async fn send_receive_large_messages(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+    debug!("bind_addr = {:?}", bind_addr);
+
+    // Transport
+    let transport = UdpTransport::create(ctx).await?;
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.clone()).await?;
+
+    // Sender
+    for size in [1_000, 8_000, 60_000] {
+        let msg = random_string(size);
+        let r = route![(UDP, bind_addr.clone()), "echoer"];
+        let reply = ctx
+            .send_and_receive_extended::<String>(
+                r,
+                msg.clone(),
+                MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+            )
+            .await?
+            .body();
+
+        assert_eq!(reply, msg, "Should receive the same message");
+    }
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// The maximum size of the datagrams can be configured
+#[ockam_macros::test]
+async fn send_receive_small_datagrams(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+    debug!("bind_addr = {:?}", bind_addr);
+
+    // Transport
+    let options = UdpTransportOptions::new().with_max_datagram_size(64);
+    let transport = UdpTransport::create_with_options(ctx, options).await?;
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.clone()).await?;
+
+    // Connected sender
+    let sender = transport.connect(bind_addr).await?;
+    let msg = random_string(4_096);
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            route![sender, "echoer"],
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn send_receive_ipv6(ctx: &mut Context) -> Result<()> {
+    // Skip the test if IPv6 is not available
+    let bind_addr = match utils::available_local_ipv6_port().await {
+        Some(addr) => addr.to_string(),
+        None => return ctx.stop().await,
+    };
+    debug!("bind_addr = {:?}", bind_addr);
+
+    // Transport
+    let transport = UdpTransport::create(ctx).await?;
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.clone()).await?;
+
+    // Sender
+    let msg = random_string(4_096);
+    let r = route![(UDP, bind_addr.clone()), "echoer"];
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            r,
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?;
+
+    let src_addr = reply
+        .return_route()
+        .iter()
+        .find(|x| x.transport_type() == UDP)
+        .map(|x| x.address().parse::<SocketAddr>().unwrap())
+        .unwrap();
+    assert!(src_addr.is_ipv6());
+    assert_eq!(reply.body(), msg, "Should receive the same message");
+
+    // Connected sender, it uses another local socket
+    ctx.start_worker("connected_echoer", Echoer::new()).await?;
+    let sender = transport.connect(bind_addr).await?;
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            route![sender, "connected_echoer"],
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+#[ockam_macros::test(timeout = 30000)]
+async fn send_receive_reliable_over_lossy_path(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = *utils::available_local_ports(1).await?.first().unwrap();
+    debug!("bind_addr = {:?}", bind_addr);
+
+    // Drop one datagram out of 5 in each direction
+    let proxy_addr = utils::start_lossy_proxy(bind_addr, 5).await?;
+
+    // Transport
+    let transport = UdpTransport::create(ctx).await?;
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.to_string()).await?;
+
+    // Sender, the echoer replies with reliable delivery as well
+    let options = UdpConnectionOptions::new().with_reliable_delivery();
+    let flow_control_id = options.flow_control_id();
+    let sender = transport
+        .connect_with_options(proxy_addr.to_string(), options)
+        .await?;
+    let mut child_ctx = ctx
+        .new_detached(Address::random_tagged("App.detached"), AllowAll, AllowAll)
+        .await?;
+    ctx.flow_controls()
+        .add_consumer(child_ctx.address(), &flow_control_id);
+
+    let messages: Vec<String> = (0..100)
+        .map(|i| random_string(if i % 10 == 0 { 5_000 } else { 100 }))
+        .collect();
+    for msg in &messages {
+        child_ctx
+            .send(route![sender.clone(), "echoer"], msg.clone())
+            .await?;
+    }
+
+    // Every message is echoed back, in order
+    for msg in &messages {
+        let reply = child_ctx
+            .receive_extended::<String>(MessageReceiveOptions::new().with_timeout(TIMEOUT))
+            .await?
+            .body();
+        assert_eq!(&reply, msg, "Should receive the messages in order");
+    }
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+fn random_string(size: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(size)
+        .map(char::from)
+        .collect()
+}
+
 pub struct Echoer {
     prev_src_addr: Option<String>,
 }
@@ -4,6 +4,7 @@ use std::net::SocketAddr;
 use tokio::net::UdpSocket;
 
 const AVAILABLE_LOCAL_PORTS_ADDR: &str = "127.0.0.1:0";
+const AVAILABLE_LOCAL_IPV6_PORT_ADDR: &str = "[::1]:0";
 
 /// Helper function. Try to find numbers of available local UDP ports.
 pub async fn available_local_ports(count: usize) -> Result<Vec<SocketAddr>> {
@@ -26,3 +27,58 @@ pub async fn available_local_ports(count: usize) -> Result<Vec<SocketAddr>> {
 
     Ok(addrs)
 }
+
+/// Helper function. Try to find an available local IPv6 UDP port,
+/// return `None` if IPv6 is not available.
+pub async fn available_local_ipv6_port() -> Option<SocketAddr> {
+    let s = UdpSocket::bind(AVAILABLE_LOCAL_IPV6_PORT_ADDR).await.ok()?;
+    s.local_addr().ok()
+}
+
+/// Helper function. Start a UDP proxy for a single client, forwarding
+/// datagrams to `target` and back, and dropping one datagram out of
+/// `drop_every` in each direction.
+///
+/// Returns the address the client should send to.
+pub async fn start_lossy_proxy(target: SocketAddr, drop_every: usize) -> Result<SocketAddr> {
+    let client_side = UdpSocket::bind(AVAILABLE_LOCAL_PORTS_ADDR)
+        .await
+        .map_err(|e| Error::new_unknown(Origin::Unknown, e))?;
+    let target_side = UdpSocket::bind(AVAILABLE_LOCAL_PORTS_ADDR)
+        .await
+        .map_err(|e| Error::new_unknown(Origin::Unknown, e))?;
+    target_side
+        .connect(target)
+        .await
+        .map_err(|e| Error::new_unknown(Origin::Unknown, e))?;
+    let proxy_addr = client_side
+        .local_addr()
+        .map_err(|e| Error::new_unknown(Origin::Unknown, e))?;
+
+    tokio::spawn(async move {
+        let mut client = None;
+        let mut to_target = 0;
+        let mut to_client = 0;
+        let mut buf = vec![0; 65_536];
+        let mut buf_back = vec![0; 65_536];
+        loop {
+            tokio::select! {
+                Ok((len, addr)) = client_side.recv_from(&mut buf) => {
+                    client = Some(addr);
+                    to_target += 1;
+                    if to_target % drop_every != 0 {
+                        let _ = target_side.send(&buf[..len]).await;
+                    }
+                }
+                Ok(len) = target_side.recv(&mut buf_back) => {
+                    to_client += 1;
+                    if let (Some(client), true) = (client, to_client % drop_every != 0) {
+                        let _ = client_side.send_to(&buf_back[..len], client).await;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(proxy_addr)
+}
@@ -8,9 +8,13 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate core;
 
+mod local_info;
+mod options;
 mod router;
 mod transport;
 mod workers;
+pub use local_info::*;
+pub use options::*;
 use tokio::net::unix::SocketAddr as TokioSocketAddr;
 use tracing::error;
 pub use transport::*;
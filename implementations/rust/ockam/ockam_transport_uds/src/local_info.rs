@@ -0,0 +1,27 @@
+use ockam_core::{LocalInfo, LocalMessage};
+
+/// Unix domain socket LocalInfo unique Identifier
+pub const UDS_LOCAL_INFO_IDENTIFIER: &str = "UDS_LOCAL_INFO_IDENTIFIER";
+
+/// LocalInfo added to the messages received over a Unix domain socket.
+///
+/// Only the processes allowed by the permissions of the socket file can send
+/// these messages, which lets workers trust them as much as local messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdsLocalInfo;
+
+impl UdsLocalInfo {
+    /// Encode `UdsLocalInfo` to general `LocalInfo`
+    pub fn to_local_info(&self) -> LocalInfo {
+        LocalInfo::new(UDS_LOCAL_INFO_IDENTIFIER.into(), vec![])
+    }
+
+    /// Find `UdsLocalInfo` in the list of general `LocalInfo` of that `LocalMessage`
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        local_msg
+            .local_info()
+            .iter()
+            .find(|x| x.type_identifier() == UDS_LOCAL_INFO_IDENTIFIER)
+            .map(|_| UdsLocalInfo)
+    }
+}
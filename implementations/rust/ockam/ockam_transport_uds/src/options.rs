@@ -0,0 +1,25 @@
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+
+/// Trust Options for a UDS connection
+///
+/// The receiver of the connection is marked as a Producer, so the messages
+/// coming from the peer are only delivered to the consumers of its [`FlowControlId`].
+#[derive(Debug)]
+pub struct UdsConnectionOptions {
+    pub(crate) flow_control_id: FlowControlId,
+}
+
+impl UdsConnectionOptions {
+    #[allow(clippy::new_without_default)]
+    /// Mark this Uds Receiver as a Producer with a random [`FlowControlId`]
+    pub fn new() -> Self {
+        Self {
+            flow_control_id: FlowControls::generate_flow_control_id(),
+        }
+    }
+
+    /// Getter for freshly generated [`FlowControlId`]
+    pub fn flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
@@ -1,5 +1,6 @@
 use std::os::unix::net::SocketAddr;
 
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AsyncTryClone, DenyAll, Mailbox, Mailboxes, Result,
 };
@@ -7,7 +8,7 @@ use ockam_node::Context;
 use ockam_transport_core::TransportError;
 
 use crate::{
-    address_from_socket_addr, parse_socket_addr,
+    parse_socket_addr,
     workers::{UdsListenProcessor, WorkerPair},
     UDS,
 };
@@ -91,6 +92,30 @@ impl UdsRouterHandle {
         }
     }
 
+    /// Establish a dedicated outgoing UDS connection on an existing transport
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        flow_control_id: FlowControlId,
+    ) -> Result<Address> {
+        let response = self
+            .ctx
+            .send_and_receive(
+                self.api_addr.clone(),
+                UdsRouterRequest::ConnectWithOptions {
+                    peer: peer.as_ref().to_string(),
+                    flow_control_id,
+                },
+            )
+            .await?;
+
+        if let UdsRouterResponse::ConnectWithOptions(res) = response {
+            res
+        } else {
+            Err(TransportError::InvalidRouterResponseType.into())
+        }
+    }
+
     /// Disconnect an outgoing UDS connection on an existing transport
     pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
         let response = self
@@ -112,9 +137,7 @@ impl UdsRouterHandle {
 
     /// Register a new connection worker with this router
     pub async fn register(&self, pair: &WorkerPair) -> Result<()> {
-        let uds_address: Address = address_from_socket_addr(pair.peer())?;
-
-        let mut accepts = vec![uds_address];
+        let mut accepts = vec![pair.address().clone()];
         accepts.extend(
             pair.paths()
                 .iter()
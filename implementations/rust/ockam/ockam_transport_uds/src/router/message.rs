@@ -1,3 +1,4 @@
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Message, Result};
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +13,11 @@ pub enum UdsRouterRequest {
     },
     /// Connect to a UDS Peer
     Connect { peer: String },
+    /// Connect to a UDS Peer with a dedicated connection, marked as a Producer
+    ConnectWithOptions {
+        peer: String,
+        flow_control_id: FlowControlId,
+    },
     /// Disconnect from a UDS Peer
     Disconnect { peer: String },
     /// Unregister (usually, after disconnection)
@@ -27,6 +33,8 @@ pub enum UdsRouterResponse {
     Register(Result<()>),
     /// Response containing an [`Address`] on succesful connection to a peer
     Connect(Result<Address>),
+    /// Response containing an [`Address`] on succesful dedicated connection to a peer
+    ConnectWithOptions(Result<Address>),
     /// Response containing a result when attempting to disconnect from a peer
     Disconnect(Result<()>),
     /// Resposne containing a result when attempt to unregister
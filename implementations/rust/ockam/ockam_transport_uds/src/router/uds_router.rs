@@ -1,4 +1,5 @@
 use core::ops::Deref;
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AllowAll, Any, Decodable, LocalMessage, Mailbox,
     Mailboxes, Result, Routed, Worker,
@@ -9,7 +10,11 @@ use std::collections::BTreeMap;
 use tracing::{debug, error, trace};
 
 use super::{UdsRouterHandle, UdsRouterRequest, UdsRouterResponse};
-use crate::{address_from_socket_addr, workers::UdsSendWorker, UDS};
+use crate::{
+    address_from_socket_addr,
+    workers::{UdsSendWorker, WorkerPair},
+    UDS,
+};
 
 /// A UDS address router and connection listener
 ///
@@ -83,34 +88,38 @@ impl UdsRouter {
 
 /// Router Handlers Implementations
 impl UdsRouter {
-    /// Handles any [`UdsRouterRequest::Connect`] messages received by
-    /// this node's worker
-    async fn handle_connect(&mut self, peer: String) -> Result<Address> {
+    /// Handles any [`UdsRouterRequest::Connect`] and [`UdsRouterRequest::ConnectWithOptions`]
+    /// messages received by this node's worker
+    ///
+    /// A connection marked as a Producer is only registered under its own address,
+    /// so that it is never used for the messages routed to the peer pathnames
+    async fn handle_connect(
+        &mut self,
+        peer: String,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Result<WorkerPair> {
         let (peer_addr, pathnames) = UdsRouterHandle::resolve_peer(peer)?;
 
+        let dedicated = flow_control_id.is_some();
         let router_handle = self.create_self_handle().await?;
-        let pair =
-            UdsSendWorker::start_pair(&self.ctx, router_handle, None, peer_addr, pathnames.clone())
-                .await?;
-
-        let path = match pair.peer().as_pathname() {
-            Some(p) => p,
-            None => return Err(TransportError::InvalidAddress.into()),
-        };
-
-        let str_path = match path.to_str() {
-            Some(s) => s,
-            None => return Err(TransportError::InvalidAddress.into()),
-        };
-
-        let uds_address = Address::new(UDS, str_path);
-        let mut accepts = vec![uds_address];
-        accepts.extend(pathnames.iter().map(|p| Address::new(UDS, p)));
+        let pair = UdsSendWorker::start_pair(
+            &self.ctx,
+            router_handle,
+            None,
+            peer_addr,
+            pathnames.clone(),
+            flow_control_id,
+        )
+        .await?;
+
+        let mut accepts = vec![pair.address().clone()];
+        if !dedicated {
+            accepts.extend(pathnames.iter().map(|p| Address::new(UDS, p)));
+        }
 
-        let self_addr = pair.tx_addr();
-        self.handle_register(accepts, self_addr.clone()).await?;
+        self.handle_register(accepts, pair.tx_addr()).await?;
 
-        Ok(self_addr)
+        Ok(pair)
     }
 
     /// Handles any [`UdsRouterRequest::Disconnect`] messages received by
@@ -140,16 +149,10 @@ impl UdsRouter {
             error!("UDS registration request failed due to an invalid address list. Please provide at least one valid Address.");
         }
 
-        let duplicate_addrs: Vec<String> = self
-            .map
+        let duplicate_addrs: Vec<String> = accepts
             .iter()
-            .filter_map(|(addr, _)| {
-                if self.map.contains_key(addr) {
-                    Some(addr.to_string())
-                } else {
-                    None
-                }
-            })
+            .filter(|addr| self.map.contains_key(addr))
+            .map(|addr| addr.to_string())
             .collect();
 
         if !duplicate_addrs.is_empty() {
@@ -243,7 +246,7 @@ impl UdsRouter {
         }
 
         if self.allow_auto_connection {
-            self.handle_connect(peer).await
+            self.handle_connect(peer, None).await.map(|p| p.tx_addr())
         } else {
             error!(
                 "Failed to resolve route, no existing connection to peer: {}",
@@ -280,11 +283,23 @@ impl Worker for UdsRouter {
                         .await?;
                 }
                 UdsRouterRequest::Connect { peer } => {
-                    let res = self.handle_connect(peer).await;
+                    let res = self.handle_connect(peer, None).await.map(|p| p.tx_addr());
 
                     ctx.send(return_route, UdsRouterResponse::Connect(res))
                         .await?;
                 }
+                UdsRouterRequest::ConnectWithOptions {
+                    peer,
+                    flow_control_id,
+                } => {
+                    let res = self
+                        .handle_connect(peer, Some(flow_control_id))
+                        .await
+                        .map(|p| p.tx_addr());
+
+                    ctx.send(return_route, UdsRouterResponse::ConnectWithOptions(res))
+                        .await?;
+                }
                 UdsRouterRequest::Disconnect { peer } => {
                     let res = self.handle_disconnect(peer).await;
 
@@ -6,6 +6,7 @@ use ockam_node::{Context, HasContext};
 use crate::{
     parse_socket_addr,
     router::{UdsRouter, UdsRouterHandle},
+    UdsConnectionOptions,
 };
 
 /// High level management interface for UDS transports
@@ -77,6 +78,33 @@ impl UdsTransport {
         self.router_handle.connect(peer.as_ref()).await
     }
 
+    /// Connects the [`UdsTransport`] to the given socket peer, with the given options.
+    ///
+    /// The messages received from the peer are only delivered to the consumers
+    /// of the [`FlowControlId`](ockam_core::flow_control::FlowControlId) of the options.
+    /// Returns the address of the local worker sending messages over that connection.
+    ///
+    /// ```rust
+    /// use ockam_transport_uds::{UdsConnectionOptions, UdsTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let uds = UdsTransport::create(&ctx).await?;
+    /// let options = UdsConnectionOptions::new();
+    /// let flow_control_id = options.flow_control_id();
+    /// uds.connect_with_options("/tmp/socket-name", options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: UdsConnectionOptions,
+    ) -> Result<Address> {
+        self.router_handle
+            .connect_with_options(peer.as_ref(), options.flow_control_id)
+            .await
+    }
+
     /// Disconnects the [`UdsTransport`] from the given socket peer.
     ///
     /// ```rust
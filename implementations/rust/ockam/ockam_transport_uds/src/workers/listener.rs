@@ -79,7 +79,8 @@ impl Processor for UdsListenProcessor {
         let local_addr = stream.local_addr().map_err(TransportError::from)?;
         let std_sock_addr = std_socket_addr_from_tokio(&local_addr)?;
         let (send_worker, pair) =
-            UdsSendWorker::new_pair(handle_clone, Some(stream), std_sock_addr, vec![]).await?;
+            UdsSendWorker::new_pair(handle_clone, Some(stream), std_sock_addr, vec![], None)
+                .await?;
 
         self.router_handle.register(&pair).await?;
         debug!("UDS connection registered");
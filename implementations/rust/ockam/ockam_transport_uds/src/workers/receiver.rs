@@ -1,4 +1,5 @@
 use crate::workers::UdsSendWorkerMsg;
+use crate::UdsLocalInfo;
 
 use ockam_core::{
     async_trait, Address, Decodable, LocalMessage, Processor, Result, TransportMessage,
@@ -94,8 +95,9 @@ impl Processor for UdsRecvProcessor {
         trace!("Message onward route: {}", msg.onward_route);
         trace!("Message return route: {}", msg.return_route);
 
-        // Forward the message to the next hop in the route
-        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+        // Forward the message to the next hop in the route, marked as received over a socket
+        ctx.forward(LocalMessage::new(msg, vec![UdsLocalInfo.to_local_info()]))
+            .await?;
 
         Ok(true)
     }
@@ -1,10 +1,12 @@
 use std::os::unix::net::SocketAddr;
 
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl};
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AllowAll, Any, Decodable, DenyAll, Encodable,
-    LocalMessage, Mailbox, Mailboxes, Message, Result, Routed, TransportMessage, Worker,
+    IncomingAccessControl, LocalMessage, Mailbox, Mailboxes, Message, OutgoingAccessControl,
+    Result, Routed, TransportMessage, Worker,
 };
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 use socket2::SockRef;
@@ -18,6 +20,7 @@ use tokio::{
 use tracing::{debug, error, trace, warn};
 
 use crate::router::UdsRouterHandle;
+use crate::{address_from_socket_addr, UDS};
 
 use super::UdsRecvProcessor;
 
@@ -25,21 +28,21 @@ use super::UdsRecvProcessor;
 #[derive(Debug)]
 pub(crate) struct WorkerPair {
     paths: Vec<String>,
-    peer: SocketAddr,
+    address: Address,
     tx_addr: Address,
 }
 
 impl WorkerPair {
+    /// Return the UDS address under which the connection is registered with the router
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
     /// Returns a reference to the peers pathnames
     pub fn paths(&self) -> &[String] {
         &self.paths
     }
 
-    /// Return a reference to the peers SocketAddr
-    pub fn peer(&self) -> &SocketAddr {
-        &self.peer
-    }
-
     /// Return a clone of the transmit
     pub fn tx_addr(&self) -> Address {
         self.tx_addr.clone()
@@ -56,9 +59,11 @@ pub(crate) struct UdsSendWorker {
     rx: Option<OwnedReadHalf>,
     tx: Option<OwnedWriteHalf>,
     peer: SocketAddr,
+    address: Address,
     internal_addr: Address,
     rx_addr: Address,
     rx_should_be_stopped: bool,
+    flow_control_id: Option<FlowControlId>,
 }
 
 impl UdsSendWorker {
@@ -67,8 +72,10 @@ impl UdsSendWorker {
         router_handle: UdsRouterHandle,
         stream: Option<UnixStream>,
         peer: SocketAddr,
+        address: Address,
         internal_addr: Address,
         rx_addr: Address,
+        flow_control_id: Option<FlowControlId>,
     ) -> Self {
         let (rx, tx) = match stream {
             Some(s) => {
@@ -83,9 +90,11 @@ impl UdsSendWorker {
             rx,
             tx,
             peer,
+            address,
             internal_addr,
             rx_addr,
             rx_should_be_stopped: true,
+            flow_control_id,
         }
     }
 
@@ -104,6 +113,7 @@ impl UdsSendWorker {
         stream: Option<UnixStream>,
         peer: SocketAddr,
         pathnames: Vec<String>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Result<(Self, WorkerPair)> {
         let role_str = if stream.is_none() {
             "initiator"
@@ -114,12 +124,31 @@ impl UdsSendWorker {
         let tx_addr = Address::random_tagged(&format!("UdsSendWorker_tx_addr_{role_str}"));
         let int_addr = Address::random_tagged(&format!("UdsSendWorker_int_addr_{role_str}"));
         let rx_addr = Address::random_tagged(&format!("UdsRecvProcessor_{role_str}"));
-        let sender = UdsSendWorker::new(router_handle, stream, peer.clone(), int_addr, rx_addr);
+
+        // The connections accepted by a listener all have the socket address of the listener,
+        // so each of them is registered with the router under its own address,
+        // like the connections marked as Producers
+        let address = address_from_socket_addr(&peer)?;
+        let address = if stream.is_none() && flow_control_id.is_none() {
+            address
+        } else {
+            Address::new(UDS, format!("{}#{}", address.address(), tx_addr.address()))
+        };
+
+        let sender = UdsSendWorker::new(
+            router_handle,
+            stream,
+            peer.clone(),
+            address.clone(),
+            int_addr,
+            rx_addr,
+            flow_control_id,
+        );
         Ok((
             sender,
             WorkerPair {
                 paths: pathnames,
-                peer,
+                address,
                 tx_addr,
             },
         ))
@@ -132,15 +161,25 @@ impl UdsSendWorker {
         stream: Option<UnixStream>,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Result<WorkerPair> {
         let udsrouter_main_addr = router_handle.main_addr().clone();
 
+        // Like a TCP connection, a connection marked as a Producer is used directly by the
+        // local workers, instead of being reached through the router
+        let incoming_access_control: Arc<dyn IncomingAccessControl> = if flow_control_id.is_some() {
+            Arc::new(AllowAll)
+        } else {
+            Arc::new(ockam_core::AllowSourceAddress(udsrouter_main_addr))
+        };
+
         trace!("Creating new UDS worker pair");
-        let (worker, pair) = Self::new_pair(router_handle, stream, peer, hostnames).await?;
+        let (worker, pair) =
+            Self::new_pair(router_handle, stream, peer, hostnames, flow_control_id).await?;
 
         let tx_mailbox = Mailbox::new(
             pair.tx_addr(),
-            Arc::new(ockam_core::AllowSourceAddress(udsrouter_main_addr)),
+            incoming_access_control,
             Arc::new(ockam_core::DenyAll),
         );
 
@@ -150,6 +189,18 @@ impl UdsSendWorker {
             Arc::new(ockam_core::DenyAll),
         );
 
+        // The Producer is known as soon as the connection is returned to the caller
+        if let Some(flow_control_id) = &worker.flow_control_id {
+            let flow_controls = ctx.flow_controls();
+            flow_controls.add_producer(
+                worker.rx_addr().clone(),
+                flow_control_id,
+                None,
+                vec![pair.tx_addr(), pair.address().clone()],
+            );
+            flow_controls.add_consumer(worker.internal_addr().clone(), flow_control_id);
+        }
+
         WorkerBuilder::new(worker)
             .with_mailboxes(Mailboxes::new(tx_mailbox, vec![internal_mailbox]))
             .start(ctx)
@@ -222,13 +273,24 @@ impl Worker for UdsSendWorker {
 
         let rx = self.rx.take().ok_or(TransportError::GenericIo)?;
 
-        let receiver = UdsRecvProcessor::new(
-            rx,
-            format!("{}#{}", crate::UDS, path.display()).into(),
-            self.internal_addr.clone(),
-        );
+        let receiver = UdsRecvProcessor::new(rx, self.address.clone(), self.internal_addr.clone());
+
+        // When the connection is a Producer, the messages received from the peer are only
+        // delivered to the consumers of its flow control id
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = match &self.flow_control_id {
+            Some(flow_control_id) => Arc::new(FlowControlOutgoingAccessControl::new(
+                ctx.flow_controls(),
+                flow_control_id.clone(),
+                None,
+            )),
+            None => Arc::new(AllowAll),
+        };
 
-        ctx.start_processor_with_access_control(self.rx_addr.clone(), receiver, DenyAll, AllowAll)
+        ProcessorBuilder::new(receiver)
+            .with_address(self.rx_addr.clone())
+            .with_incoming_access_control(DenyAll)
+            .with_outgoing_access_control_arc(outgoing_access_control)
+            .start(ctx)
             .await?;
 
         Ok(())
@@ -268,7 +330,13 @@ impl Worker for UdsSendWorker {
                 }
             }
         } else {
-            let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+            // The router forwards the messages it routes as a whole, while the local
+            // workers of a connection marked as a Producer send their messages directly
+            let mut msg = if &msg.src_addr() == self.router_handle.main_addr() {
+                LocalMessage::decode(msg.payload())?.into_transport_message()
+            } else {
+                msg.into_transport_message()
+            };
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
             msg.onward_route.step()?;
@@ -89,12 +89,18 @@ use std::net::SocketAddr;
 
 use ockam_core::{Result, TransportType};
 use ockam_transport_core::TransportError;
+
+pub use ockam_transport_tcp::TcpProxy;
+pub use options::*;
+pub use tls::*;
 pub use transport::*;
 
 use crate::router::{WebSocketRouter, WebSocketRouterHandle};
 
 mod error;
+mod options;
 mod router;
+mod tls;
 mod transport;
 mod workers;
 
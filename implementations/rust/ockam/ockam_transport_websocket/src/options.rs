@@ -0,0 +1,63 @@
+use crate::{TcpProxy, TlsClientConfig, TlsServerConfig};
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+
+/// Options for a WebSocket listener
+#[derive(Clone, Debug)]
+pub struct WebSocketListenerOptions {
+    pub(crate) tls: Option<TlsServerConfig>,
+}
+
+impl WebSocketListenerOptions {
+    /// Accept plain `ws://` connections
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { tls: None }
+    }
+
+    /// Accept `wss://` connections, terminating TLS with the given configuration
+    pub fn with_tls(mut self, tls: TlsServerConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+/// Options for an outgoing WebSocket connection
+///
+/// The receiver of the connection is marked as a Producer, so the messages
+/// coming from the peer are only delivered to the consumers of its [`FlowControlId`].
+#[derive(Clone, Debug)]
+pub struct WebSocketConnectionOptions {
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) tls: Option<TlsClientConfig>,
+    pub(crate) proxy: Option<TcpProxy>,
+}
+
+impl WebSocketConnectionOptions {
+    /// Open a plain `ws://` connection directly to the server.
+    /// The receiver is marked as a Producer with a random [`FlowControlId`]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            tls: None,
+            proxy: None,
+        }
+    }
+
+    /// Getter for freshly generated [`FlowControlId`]
+    pub fn flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+
+    /// Open a `wss://` connection, using the given TLS configuration
+    pub fn with_tls(mut self, tls: TlsClientConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Tunnel the connection through an HTTP or a SOCKS5 proxy
+    pub fn with_proxy(mut self, proxy: TcpProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+}
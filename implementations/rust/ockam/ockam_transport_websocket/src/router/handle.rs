@@ -7,7 +7,9 @@ use ockam_transport_core::TransportError;
 
 use crate::router::{WebSocketRouterRequest, WebSocketRouterResponse};
 use crate::workers::{WebSocketListenProcessor, WorkerPair};
-use crate::{parse_socket_addr, WebSocketAddress};
+use crate::{
+    parse_socket_addr, WebSocketAddress, WebSocketConnectionOptions, WebSocketListenerOptions,
+};
 
 /// A handle to connect to a WebSocketRouter.
 ///
@@ -62,9 +64,19 @@ impl WebSocketRouterHandle {
     }
 
     /// Bind an incoming connection listener for this router.
-    pub(crate) async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+    pub(crate) async fn bind(
+        &self,
+        addr: impl Into<SocketAddr>,
+        options: WebSocketListenerOptions,
+    ) -> Result<SocketAddr> {
         let socket_addr = addr.into();
-        WebSocketListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+        WebSocketListenProcessor::start(
+            &self.ctx,
+            self.async_try_clone().await?,
+            socket_addr,
+            options,
+        )
+        .await
     }
 
     /// Return the peer's `SocketAddr` and `hostnames` given a plain `String` address.
@@ -96,15 +108,26 @@ impl WebSocketRouterHandle {
     }
 
     /// Establish an outgoing WS connection on an existing transport.
-    pub(crate) async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+    ///
+    /// Returns the address of the sender worker for the connection. The receiver
+    /// is marked as a Producer with the flow control id of the options if `producer` is true.
+    pub(crate) async fn connect<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: WebSocketConnectionOptions,
+        producer: bool,
+    ) -> Result<Address> {
         // Get peer address and connect to it.
         let (peer_addr, hostnames) = Self::resolve_peer(peer.as_ref())?;
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        let pair =
+            WorkerPair::from_client(&self.ctx, peer_addr, hostnames, &options, producer).await?;
 
         // Handle node's register request.
-        self.register(&pair).await
+        self.register(&pair).await?;
+
+        Ok(pair.tx_addr())
     }
 }
@@ -12,7 +12,7 @@ use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 
 use crate::workers::WorkerPair;
-use crate::{WebSocketAddress, WS};
+use crate::{WebSocketAddress, WebSocketConnectionOptions, WS};
 use serde::{Deserialize, Serialize};
 
 mod handle;
@@ -182,13 +182,10 @@ impl WebSocketRouter {
 
         let _ = msg.transport_mut().onward_route.step()?;
         // Modify the transport message route
-        msg.transport_mut()
-            .onward_route
-            .modify()
-            .prepend(next.clone());
+        msg.transport_mut().onward_route.modify().prepend(next);
 
-        // Send the transport message to the connection worker
-        ctx.send(next.clone(), msg).await?;
+        // Forward the transport message to the connection worker
+        ctx.forward(msg).await?;
 
         Ok(())
     }
@@ -204,16 +201,11 @@ impl WebSocketRouter {
             return Err(TransportError::InvalidAddress.into());
         }
 
-        // Do not connect twice.
-        for accept in &accepts {
-            if self.map.contains_key(accept) {
-                return Err(TransportError::AlreadyConnected.into());
-            }
-        }
-
-        // Add a new entry for each hostname/address pair.
+        // Add a new entry for each hostname/address pair. Routes keep using the
+        // existing connection to a peer, another connection to the same peer is
+        // only reachable through the address of its sender.
         for accept in accepts {
-            self.map.insert(accept.clone(), self_addr.clone());
+            self.map.entry(accept).or_insert_with(|| self_addr.clone());
         }
 
         Ok(())
@@ -225,7 +217,14 @@ impl WebSocketRouter {
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        let pair = WorkerPair::from_client(
+            &self.ctx,
+            peer_addr,
+            hostnames,
+            &WebSocketConnectionOptions::new(),
+            false,
+        )
+        .await?;
 
         // Handle node's register request.
         let mut accepts = vec![pair.peer()];
@@ -0,0 +1,185 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+use rustls_pemfile::Item;
+
+/// TLS configuration of a WebSocket listener, accepting `wss://` connections
+///
+/// ```rust,no_run
+/// use ockam_transport_websocket::{TlsServerConfig, WebSocketTransport};
+/// # use ockam_node::Context;
+/// # use ockam_core::Result;
+/// # async fn test(ctx: Context) -> Result<()> {
+/// let tls = TlsServerConfig::from_pem_files("server.crt", "server.key")?;
+/// let ws = WebSocketTransport::create(&ctx).await?;
+/// ws.listen_with_tls("0.0.0.0:443", tls).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct TlsServerConfig {
+    config: Arc<ServerConfig>,
+}
+
+impl TlsServerConfig {
+    /// Create a configuration from a PEM encoded certificate chain, starting with the
+    /// server certificate, and the PEM encoded private key of the server certificate
+    pub fn from_pem(cert_chain: &[u8], private_key: &[u8]) -> Result<Self> {
+        let certificates = read_certificates(cert_chain)?;
+        if certificates.is_empty() {
+            return Err(tls_config_error("no certificate found"));
+        }
+        let private_key = read_private_key(private_key)?;
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certificates, private_key)
+            .map_err(|e| tls_config_error(format!("invalid certificate or private key: {e}")))?;
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+
+    /// Create a configuration from the files containing a PEM encoded certificate chain
+    /// and a PEM encoded private key
+    pub fn from_pem_files(
+        cert_chain: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+    ) -> Result<Self> {
+        Self::from_pem(&read_file(cert_chain)?, &read_file(private_key)?)
+    }
+
+    pub(crate) fn config(&self) -> Arc<ServerConfig> {
+        self.config.clone()
+    }
+}
+
+impl fmt::Debug for TlsServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsServerConfig").finish()
+    }
+}
+
+/// TLS configuration of outgoing `wss://` connections
+///
+/// The server certificate is verified against the trusted root certificates, for the
+/// server name. The server name defaults to the host name used to connect, or to the IP
+/// address of the server when connecting to a socket address.
+///
+/// ```rust,no_run
+/// use ockam_transport_websocket::{TlsClientConfig, WebSocketTransport};
+/// # use ockam_node::Context;
+/// # use ockam_core::Result;
+/// # async fn test(ctx: Context) -> Result<()> {
+/// let tls = TlsClientConfig::from_ca_file("ca.crt")?.with_server_name("relay.example.com");
+/// let ws = WebSocketTransport::create(&ctx).await?;
+/// ws.connect_with_tls("10.0.0.1:443", tls).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct TlsClientConfig {
+    config: Arc<ClientConfig>,
+    server_name: Option<String>,
+}
+
+impl TlsClientConfig {
+    /// Trust the root certificates of the platform
+    pub fn with_native_roots() -> Result<Self> {
+        let certificates = rustls_native_certs::load_native_certs()
+            .map_err(|e| tls_config_error(format!("cannot load the native certificates: {e}")))?;
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(
+            &certificates
+                .into_iter()
+                .map(|c| c.0)
+                .collect::<Vec<Vec<u8>>>(),
+        );
+        Ok(Self::from_roots(roots))
+    }
+
+    /// Only trust the root certificates of a PEM encoded CA bundle
+    pub fn from_ca_pem(ca_bundle: &[u8]) -> Result<Self> {
+        let certificates = read_certificates(ca_bundle)?;
+        if certificates.is_empty() {
+            return Err(tls_config_error("no CA certificate found"));
+        }
+        let mut roots = RootCertStore::empty();
+        for certificate in certificates {
+            roots
+                .add(&certificate)
+                .map_err(|e| tls_config_error(format!("invalid CA certificate: {e}")))?;
+        }
+        Ok(Self::from_roots(roots))
+    }
+
+    /// Only trust the root certificates of the file containing a PEM encoded CA bundle
+    pub fn from_ca_file(ca_bundle: impl AsRef<Path>) -> Result<Self> {
+        Self::from_ca_pem(&read_file(ca_bundle)?)
+    }
+
+    /// Set the server name sent with SNI and used to verify the server certificate
+    pub fn with_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    pub(crate) fn config(&self) -> Arc<ClientConfig> {
+        self.config.clone()
+    }
+
+    pub(crate) fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    fn from_roots(roots: RootCertStore) -> Self {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            config: Arc::new(config),
+            server_name: None,
+        }
+    }
+}
+
+impl fmt::Debug for TlsClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsClientConfig")
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+fn read_certificates(pem: &[u8]) -> Result<Vec<Certificate>> {
+    Ok(rustls_pemfile::certs(&mut &*pem)
+        .map_err(|e| tls_config_error(format!("invalid PEM certificates: {e}")))?
+        .into_iter()
+        .map(Certificate)
+        .collect())
+}
+
+fn read_private_key(pem: &[u8]) -> Result<PrivateKey> {
+    let items = rustls_pemfile::read_all(&mut &*pem)
+        .map_err(|e| tls_config_error(format!("invalid PEM private key: {e}")))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| tls_config_error("no private key found"))
+}
+
+fn read_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    std::fs::read(path)
+        .map_err(|e| tls_config_error(format!("cannot read {}: {e}", path.display())))
+}
+
+fn tls_config_error(message: impl Into<String>) -> Error {
+    Error::new(Origin::Transport, Kind::Invalid, message.into())
+}
@@ -5,7 +5,10 @@ use std::str::FromStr;
 use ockam_core::{async_trait, Address, Result};
 use ockam_node::{Context, HasContext};
 
-use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
+use crate::{
+    parse_socket_addr, TlsClientConfig, TlsServerConfig, WebSocketConnectionOptions,
+    WebSocketListenerOptions, WebSocketRouter, WebSocketRouterHandle, WS,
+};
 
 /// High level management interface for WebSocket transports.
 ///
@@ -78,7 +81,59 @@ impl WebSocketTransport {
     /// # Ok(()) }
     /// ```
     pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
-        self.router_handle.connect(peer).await
+        self.router_handle
+            .connect(peer, WebSocketConnectionOptions::new(), false)
+            .await
+            .map(|_| ())
+    }
+
+    /// Establish an outgoing WebSocket connection with the given options.
+    ///
+    /// The connection uses TLS (`wss://`) and goes through an HTTP proxy when
+    /// the options say so. The messages received from the peer are only delivered
+    /// to the consumers of the flow control id of the options. Returns the address
+    /// of the local worker sending messages to the peer.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ws = WebSocketTransport::create(&ctx).await?;
+    /// let sender = ws
+    ///     .connect_with_options("127.0.0.1:5000", WebSocketConnectionOptions::new())
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: WebSocketConnectionOptions,
+    ) -> Result<Address> {
+        self.router_handle.connect(peer, options, true).await
+    }
+
+    /// Establish an outgoing `wss://` connection, using the given TLS configuration.
+    ///
+    /// Returns the address of the local worker sending messages to the peer.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::{TlsClientConfig, WebSocketTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ws = WebSocketTransport::create(&ctx).await?;
+    /// let tls = TlsClientConfig::with_native_roots()?.with_server_name("example.com");
+    /// let sender = ws.connect_with_tls("example.com:443", tls).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_with_tls<S: AsRef<str>>(
+        &self,
+        peer: S,
+        tls: TlsClientConfig,
+    ) -> Result<Address> {
+        self.connect_with_options(peer, WebSocketConnectionOptions::new().with_tls(tls))
+            .await
     }
 
     /// Start listening to incoming connections on an existing transport.
@@ -96,9 +151,57 @@ impl WebSocketTransport {
     /// let ws = WebSocketTransport::create(&ctx).await?;
     /// ws.listen("127.0.0.1:8000").await?;
     /// # Ok(()) }
+    /// ```
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
+        self.listen_with_options(bind_addr, WebSocketListenerOptions::new())
+            .await
+    }
+
+    /// Start listening to incoming connections with the given options.
+    ///
+    /// The listener accepts `wss://` connections when the options contain a
+    /// TLS configuration. Returns the local address that this transport is
+    /// bound to.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::{WebSocketListenerOptions, WebSocketTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ws = WebSocketTransport::create(&ctx).await?;
+    /// ws.listen_with_options("127.0.0.1:8000", WebSocketListenerOptions::new())
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen_with_options<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        options: WebSocketListenerOptions,
+    ) -> Result<SocketAddr> {
         let bind_addr = parse_socket_addr(bind_addr)?;
-        self.router_handle.bind(bind_addr).await
+        self.router_handle.bind(bind_addr, options).await
+    }
+
+    /// Start listening to incoming `wss://` connections, terminating TLS with
+    /// the given configuration.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::{TlsServerConfig, WebSocketTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ws = WebSocketTransport::create(&ctx).await?;
+    /// let tls = TlsServerConfig::from_pem_files("cert.pem", "key.pem")?;
+    /// ws.listen_with_tls("0.0.0.0:443", tls).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen_with_tls<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        tls: TlsServerConfig,
+    ) -> Result<SocketAddr> {
+        self.listen_with_options(bind_addr, WebSocketListenerOptions::new().with_tls(tls))
+            .await
     }
 }
 
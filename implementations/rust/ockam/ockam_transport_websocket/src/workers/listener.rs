@@ -1,12 +1,18 @@
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
 
-use ockam_core::{async_trait, Address, AllowAll, Processor, Result};
+use ockam_core::{async_trait, Address, AllowAll, AsyncTryClone, DenyAll, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 
-use crate::{error::WebSocketError, workers::WorkerPair, WebSocketRouterHandle};
+use crate::workers::{TcpServerStream, WorkerPair};
+use crate::{error::WebSocketError, WebSocketListenerOptions, WebSocketRouterHandle};
+
+/// Maximum duration of the TLS and WebSocket handshakes of an incoming connection
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// A worker that runs in the background as a `Processor` waiting for incoming
 /// clients' connections.
@@ -15,6 +21,7 @@ use crate::{error::WebSocketError, workers::WorkerPair, WebSocketRouterHandle};
 /// registered by the router.
 pub(crate) struct WebSocketListenProcessor {
     inner: TcpListener,
+    tls_acceptor: Option<TlsAcceptor>,
     router_handle: WebSocketRouterHandle,
 }
 
@@ -24,6 +31,7 @@ impl WebSocketListenProcessor {
         ctx: &Context,
         router_handle: WebSocketRouterHandle,
         addr: SocketAddr,
+        options: WebSocketListenerOptions,
     ) -> Result<SocketAddr> {
         debug!("Binding WebSocketListener to {}", addr);
         let inner = TcpListener::bind(addr)
@@ -32,6 +40,7 @@ impl WebSocketListenProcessor {
         let saddr = inner.local_addr().map_err(TransportError::from)?;
         let processor = Self {
             inner,
+            tls_acceptor: options.tls.map(|tls| TlsAcceptor::from(tls.config())),
             router_handle,
         };
         let waddr = Address::random_tagged("WebSocketListenProcessor");
@@ -57,18 +66,63 @@ impl Processor for WebSocketListenProcessor {
 
         // Wait for an incoming connection
         let (tcp_stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
-        let ws_stream = tokio_tungstenite::accept_async(tcp_stream)
-            .await
-            .map_err(WebSocketError::from)?;
-        debug!("TCP connection accepted");
-
-        // Spawn a connection worker for it
-        let pair = WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?;
+        debug!("TCP connection accepted from {}", peer);
 
-        // Register the connection with the local TcpRouter
-        self.router_handle.register(&pair).await?;
-        debug!("TCP connection registered");
+        // The TLS and WebSocket handshakes run in their own task, so that a slow
+        // or silent client doesn't prevent other clients from connecting
+        let child_ctx = ctx
+            .new_detached(
+                Address::random_tagged("WebSocketListenProcessor.accept.detached"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        let router_handle = self.router_handle.async_try_clone().await?;
+        let tls_acceptor = self.tls_acceptor.clone();
+        ctx.runtime().spawn(async move {
+            if let Err(e) = accept(&child_ctx, router_handle, tls_acceptor, tcp_stream, peer).await
+            {
+                debug!("Incoming WebSocket connection from {} failed: {}", peer, e);
+            }
+        });
 
         Ok(true)
     }
 }
+
+/// Run the handshakes of an accepted connection, then register its worker pair
+async fn accept(
+    ctx: &Context,
+    router_handle: WebSocketRouterHandle,
+    tls_acceptor: Option<TlsAcceptor>,
+    tcp_stream: TcpStream,
+    peer: SocketAddr,
+) -> Result<()> {
+    let handshake = async {
+        let stream = match tls_acceptor {
+            Some(tls_acceptor) => {
+                let tls_stream = tls_acceptor.accept(tcp_stream).await.map_err(|e| {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    WebSocketError::Tls
+                })?;
+                TcpServerStream::Tls(Box::new(tls_stream))
+            }
+            None => TcpServerStream::Plain(tcp_stream),
+        };
+        tokio_tungstenite::accept_async(stream)
+            .await
+            .map_err(WebSocketError::from)
+    };
+    let ws_stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+        .await
+        .map_err(|_| WebSocketError::Transport(TransportError::ConnectionDrop))??;
+
+    // Spawn a connection worker for it
+    let pair = WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?;
+
+    // Register the connection with the local WebSocketRouter
+    router_handle.register(&pair).await?;
+    debug!("WebSocket connection registered");
+
+    Ok(())
+}
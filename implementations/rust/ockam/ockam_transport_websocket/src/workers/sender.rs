@@ -4,20 +4,23 @@ use std::time::Duration;
 
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
 use tokio_tungstenite::tungstenite::protocol::Message as WebSocketMessage;
+use tokio_tungstenite::Connector;
 
 use crate::error::WebSocketError;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl};
 use ockam_core::{
-    async_trait, route, Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox,
-    Mailboxes, Result, Routed, TransportMessage, Worker,
+    async_trait, route, Address, AllowAll, Any, Encodable, Mailbox, Mailboxes,
+    OutgoingAccessControl, Result, Routed, TransportMessage, Worker,
 };
-use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 
 use crate::workers::{
     AsyncStream, TcpClientStream, TcpServerStream, WebSocketRecvProcessor, WebSocketStream,
 };
-use crate::WebSocketAddress;
+use crate::{WebSocketAddress, WebSocketConnectionOptions};
 
 /// Transmit and receive peers of a WebSocket connection.
 #[derive(Debug)]
@@ -38,49 +41,20 @@ impl WorkerPair {
         self.tx_addr.clone()
     }
 
-    /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
-    /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
-    ///
-    /// The WebSocket stream is created when the `WebSocketSendWorker` is initialized.
+    /// Connect to a peer, spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor`
+    /// and return a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
     pub(crate) async fn from_client(
         ctx: &Context,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        options: &WebSocketConnectionOptions,
+        producer: bool,
     ) -> Result<WorkerPair> {
         trace!("Creating new WS worker pair");
 
-        let internal_addr = Address::random_tagged("WebSocketSender.internal.from_client");
-        let sender = WebSocketSendWorker::<TcpClientStream>::new(
-            peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
-        );
-
-        let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_client");
-
-        let mailboxes = Mailboxes::new(
-            Mailbox::new(
-                tx_addr.clone(),
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
-            ),
-            vec![Mailbox::new(
-                internal_addr,
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
-            )],
-        );
-        WorkerBuilder::new(sender)
-            .with_mailboxes(mailboxes)
-            .start(ctx)
-            .await?;
-
-        // Return a handle to the worker pair
-        Ok(WorkerPair {
-            hostnames,
-            peer: WebSocketAddress::from(peer).into(),
-            tx_addr,
-        })
+        let stream = connect(peer, &hostnames, options).await?;
+        let flow_control_id = producer.then(|| options.flow_control_id());
+        Self::start(ctx, stream, peer, hostnames, flow_control_id, "from_client").await
     }
 
     /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
@@ -93,15 +67,40 @@ impl WorkerPair {
     ) -> Result<WorkerPair> {
         trace!("Creating new WS worker pair");
 
-        let internal_addr = Address::random_tagged("WebSocketSender.internal.from_server");
-        let sender = WebSocketSendWorker::<TcpServerStream>::new(
+        Self::start(ctx, stream, peer, hostnames, None, "from_server").await
+    }
+
+    async fn start<S: AsyncStream>(
+        ctx: &Context,
+        stream: WebSocketStream<S>,
+        peer: SocketAddr,
+        hostnames: Vec<String>,
+        flow_control_id: Option<FlowControlId>,
+        side: &str,
+    ) -> Result<WorkerPair> {
+        let internal_addr = Address::random_tagged(&format!("WebSocketSender.internal.{side}"));
+        let rx_addr = Address::random_tagged("WebSocketSendWorker.rx_addr");
+        let tx_addr = Address::random_tagged(&format!("WebSocketSender.tx_addr.{side}"));
+
+        // The Producer is known as soon as the connection is returned to the caller
+        if let Some(flow_control_id) = &flow_control_id {
+            ctx.flow_controls().add_producer(
+                rx_addr.clone(),
+                flow_control_id,
+                None,
+                vec![tx_addr.clone()],
+            );
+        }
+
+        let sender = WebSocketSendWorker::new(
             stream,
             peer,
             internal_addr.clone(),
+            rx_addr,
             DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
+            flow_control_id,
         );
 
-        let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_server");
         let mailboxes = Mailboxes::new(
             Mailbox::new(
                 tx_addr.clone(),
@@ -128,6 +127,46 @@ impl WorkerPair {
     }
 }
 
+/// Open a WebSocket connection to a peer, through a proxy and over TLS if configured
+async fn connect(
+    peer: SocketAddr,
+    hostnames: &[String],
+    options: &WebSocketConnectionOptions,
+) -> Result<WebSocketStream<TcpClientStream>> {
+    // The host name used to connect, if any, is preferred to the resolved socket address
+    let authority = hostnames
+        .first()
+        .cloned()
+        .unwrap_or_else(|| peer.to_string());
+
+    let tcp_stream = match &options.proxy {
+        Some(proxy) => proxy.connect(&authority).await?,
+        None => TcpStream::connect(peer)
+            .await
+            .map_err(TransportError::from)?,
+    };
+
+    let (url, connector) = match &options.tls {
+        Some(tls) => {
+            let authority = match tls.server_name() {
+                Some(server_name) => format!("{}:{}", server_name, peer.port()),
+                None => authority,
+            };
+            (
+                format!("wss://{authority}"),
+                Connector::Rustls(tls.config()),
+            )
+        }
+        None => (format!("ws://{authority}"), Connector::Plain),
+    };
+
+    let (stream, _) =
+        tokio_tungstenite::client_async_tls_with_config(url, tcp_stream, None, Some(connector))
+            .await
+            .map_err(WebSocketError::from)?;
+    Ok(stream)
+}
+
 /// A WebSocket sending message worker.
 ///
 /// This half of the worker is created when spawning a new connection
@@ -141,8 +180,10 @@ where
     ws_sink: Option<SplitSink<WebSocketStream<S>, WebSocketMessage>>,
     peer: SocketAddr,
     internal_addr: Address,
+    rx_addr: Address,
     heartbeat: DelayedEvent<Vec<u8>>,
     heartbeat_interval: Option<Duration>,
+    flow_control_id: Option<FlowControlId>,
 }
 
 impl<S> WebSocketSendWorker<S>
@@ -151,15 +192,24 @@ where
 {
     async fn handle_initialize(&mut self, ctx: &mut Context) -> Result<()> {
         if let Some(ws_stream) = self.ws_stream.take() {
-            let rx_addr = Address::random_tagged("WebSocketSendWorker.rx_addr");
             let receiver = WebSocketRecvProcessor::new(ws_stream, self.peer);
-            ctx.start_processor_with_access_control(
-                rx_addr.clone(),
-                receiver,
-                AllowAll, // FIXME: @ac
-                AllowAll, // FIXME: @ac
-            )
-            .await?;
+            // When the connection is a Producer, the messages received from the peer are only
+            // delivered to the consumers of its flow control id
+            let outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+                match &self.flow_control_id {
+                    Some(flow_control_id) => Arc::new(FlowControlOutgoingAccessControl::new(
+                        ctx.flow_controls(),
+                        flow_control_id.clone(),
+                        None,
+                    )),
+                    None => Arc::new(AllowAll), // FIXME: @ac
+                };
+            ProcessorBuilder::new(receiver)
+                .with_address(self.rx_addr.clone())
+                .with_incoming_access_control(AllowAll) // FIXME: @ac
+                .with_outgoing_access_control_arc(outgoing_access_control)
+                .start(ctx)
+                .await?;
         } else {
             return Err(TransportError::GenericIo.into());
         }
@@ -205,7 +255,7 @@ where
             }
             debug!("Sent heartbeat to peer {}", self.peer);
         } else {
-            let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+            let mut msg = msg.into_transport_message();
 
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
@@ -226,12 +276,17 @@ where
     }
 }
 
-impl WebSocketSendWorker<TcpServerStream> {
+impl<S> WebSocketSendWorker<S>
+where
+    S: AsyncStream,
+{
     fn new(
-        stream: WebSocketStream<TcpServerStream>,
+        stream: WebSocketStream<S>,
         peer: SocketAddr,
         internal_addr: Address,
+        rx_addr: Address,
         heartbeat: DelayedEvent<Vec<u8>>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Self {
         let (ws_sink, ws_stream) = stream.split();
         Self {
@@ -239,64 +294,23 @@ impl WebSocketSendWorker<TcpServerStream> {
             ws_stream: Some(ws_stream),
             peer,
             internal_addr,
+            rx_addr,
             heartbeat,
             heartbeat_interval: None,
+            flow_control_id,
         }
     }
 }
 
-impl WebSocketSendWorker<TcpClientStream> {
-    fn new(peer: SocketAddr, internal_addr: Address, heartbeat: DelayedEvent<Vec<u8>>) -> Self {
-        Self {
-            ws_stream: None,
-            ws_sink: None,
-            peer,
-            internal_addr,
-            heartbeat,
-            heartbeat_interval: None,
-        }
-    }
-
-    async fn initialize_stream(&mut self) -> Result<()> {
-        if self.ws_stream.is_none() {
-            let peer = WebSocketAddress::from(self.peer).to_string();
-            let (stream, _) = tokio_tungstenite::connect_async(peer)
-                .await
-                .map_err(WebSocketError::from)?;
-            let (ws_sink, ws_stream) = stream.split();
-            self.ws_sink = Some(ws_sink);
-            self.ws_stream = Some(ws_stream);
-        }
-        Ok(())
-    }
-}
-
-#[async_trait]
-impl Worker for WebSocketSendWorker<TcpServerStream> {
-    type Message = Any;
-    type Context = Context;
-
-    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.handle_initialize(ctx).await?;
-        Ok(())
-    }
-
-    async fn handle_message(
-        &mut self,
-        ctx: &mut Context,
-        msg: Routed<Self::Message>,
-    ) -> Result<()> {
-        self.handle_msg(ctx, msg).await
-    }
-}
-
 #[async_trait]
-impl Worker for WebSocketSendWorker<TcpClientStream> {
+impl<S> Worker for WebSocketSendWorker<S>
+where
+    S: AsyncStream,
+{
     type Message = Any;
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.initialize_stream().await?;
         self.handle_initialize(ctx).await?;
         Ok(())
     }
@@ -1,22 +1,71 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+
 /// Type alias for `tokio_tungstenite::WebSocketStream`.
 pub(crate) type WebSocketStream<S> = tokio_tungstenite::WebSocketStream<S>;
 
 /// Stream created when a server accepts a new connection.
-pub(crate) type TcpServerStream = tokio::net::TcpStream;
+///
+/// TLS is terminated by the listener when it is configured with a certificate.
+pub(crate) enum TcpServerStream {
+    Plain(TcpStream),
+    Tls(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+}
 
 /// Stream created when a client connects to a server.
-pub(crate) type TcpClientStream = tokio_tungstenite::MaybeTlsStream<TcpServerStream>;
+pub(crate) type TcpClientStream = tokio_tungstenite::MaybeTlsStream<TcpStream>;
 
 /// Trait alias to define an AsyncStream returned
 /// when creating or accepting WebSocket connections.
 ///
 /// This is used to reduce the complexity of the definition
 /// of the structs that use WebSocket streams.
-pub(crate) trait AsyncStream:
-    tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static
-{
-}
+pub(crate) trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
 
 impl AsyncStream for TcpClientStream {}
 
 impl AsyncStream for TcpServerStream {}
+
+impl AsyncRead for TcpServerStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpServerStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            TcpServerStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TcpServerStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TcpServerStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            TcpServerStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpServerStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            TcpServerStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpServerStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            TcpServerStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Result, Routed, Worker};
 use ockam_node::Context;
-use ockam_transport_websocket::{WebSocketTransport, WS};
+use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketTransport, WS};
 
 #[ignore]
 #[ockam_macros::test]
@@ -30,6 +30,28 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn several_connections_to_the_same_peer(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    for _ in 0..2 {
+        let sender = transport
+            .connect_with_options(
+                listener_address.to_string(),
+                WebSocketConnectionOptions::new(),
+            )
+            .await?;
+        let reply: String = ctx
+            .send_and_receive(route![sender, "echoer"], "Hello".to_string())
+            .await?;
+        assert_eq!(reply, "Hello");
+    }
+
+    ctx.stop().await
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
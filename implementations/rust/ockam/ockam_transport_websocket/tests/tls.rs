@@ -0,0 +1,160 @@
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::test_utils::start_http_proxy;
+use ockam_transport_websocket::{
+    TcpProxy, TlsClientConfig, TlsServerConfig, WebSocketConnectionOptions, WebSocketTransport,
+};
+use tokio::net::TcpStream;
+
+#[ockam_macros::test]
+async fn wss_send_receive(ctx: &mut Context) -> Result<()> {
+    let (cert, key) = self_signed_certificate();
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport
+        .listen_with_tls("127.0.0.1:0", TlsServerConfig::from_pem(&cert, &key)?)
+        .await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let tls = TlsClientConfig::from_ca_pem(&cert)?.with_server_name("localhost");
+    let sender = transport
+        .connect_with_tls(listener_address.to_string(), tls)
+        .await?;
+
+    let reply: String = ctx
+        .send_and_receive(route![sender, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn wss_rejects_an_untrusted_certificate(ctx: &mut Context) -> Result<()> {
+    let (cert, key) = self_signed_certificate();
+    let (other_cert, _) = self_signed_certificate();
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport
+        .listen_with_tls("127.0.0.1:0", TlsServerConfig::from_pem(&cert, &key)?)
+        .await?;
+
+    // The server certificate isn't signed by the trusted CA
+    let tls = TlsClientConfig::from_ca_pem(&other_cert)?.with_server_name("localhost");
+    let res = transport
+        .connect_with_tls(listener_address.to_string(), tls)
+        .await;
+    assert!(res.is_err());
+
+    // The server certificate isn't valid for that server name
+    let tls = TlsClientConfig::from_ca_pem(&cert)?.with_server_name("example.com");
+    let res = transport
+        .connect_with_tls(listener_address.to_string(), tls)
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn a_silent_client_does_not_block_the_listener(ctx: &mut Context) -> Result<()> {
+    let (cert, key) = self_signed_certificate();
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport
+        .listen_with_tls("127.0.0.1:0", TlsServerConfig::from_pem(&cert, &key)?)
+        .await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // This client never starts the TLS handshake
+    let _silent = TcpStream::connect(listener_address).await.unwrap();
+
+    let tls = TlsClientConfig::from_ca_pem(&cert)?.with_server_name("localhost");
+    let sender = transport
+        .connect_with_tls(listener_address.to_string(), tls)
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![sender, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn wss_through_an_http_proxy(ctx: &mut Context) -> Result<()> {
+    let (cert, key) = self_signed_certificate();
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport
+        .listen_with_tls("127.0.0.1:0", TlsServerConfig::from_pem(&cert, &key)?)
+        .await?;
+    ctx.start_worker("echoer", Echoer).await?;
+    let proxy = start_http_proxy(None).await;
+
+    let tls = TlsClientConfig::from_ca_pem(&cert)?.with_server_name("localhost");
+    let sender = transport
+        .connect_with_options(
+            listener_address.to_string(),
+            WebSocketConnectionOptions::new()
+                .with_tls(tls)
+                .with_proxy(TcpProxy::http(proxy.address().to_string())),
+        )
+        .await?;
+
+    let reply: String = ctx
+        .send_and_receive(route![sender, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+    assert_eq!(proxy.targets(), vec![listener_address.to_string()]);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn connection_authenticated_by_the_http_proxy(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+    // The proxy refuses the connections without credentials
+    let proxy = start_http_proxy(Some("user:password")).await;
+
+    let res = transport
+        .connect_with_options(
+            listener_address.to_string(),
+            WebSocketConnectionOptions::new()
+                .with_proxy(TcpProxy::http(proxy.address().to_string())),
+        )
+        .await;
+    assert!(res.is_err());
+    assert!(proxy.targets().is_empty());
+
+    // The connections with credentials are accepted
+    transport
+        .connect_with_options(
+            listener_address.to_string(),
+            WebSocketConnectionOptions::new().with_proxy(
+                TcpProxy::http(proxy.address().to_string()).with_basic_auth("user", "password"),
+            ),
+        )
+        .await?;
+    assert_eq!(proxy.targets(), vec![listener_address.to_string()]);
+
+    ctx.stop().await
+}
+
+/// Return a PEM encoded self-signed certificate for `localhost` and its private key
+fn self_signed_certificate() -> (Vec<u8>, Vec<u8>) {
+    let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    (
+        certificate.serialize_pem().unwrap().into_bytes(),
+        certificate.serialize_private_key_pem().into_bytes(),
+    )
+}
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
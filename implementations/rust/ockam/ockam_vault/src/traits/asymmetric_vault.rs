@@ -51,6 +51,32 @@ pub mod tests {
         assert!(secret.is_ok());
     }
 
+    /// This test checks that both sides of a NIST P-256 Diffie-Hellman exchange
+    /// compute the same secret
+    pub async fn test_ec_diffie_hellman_nist_p256(
+        vault: &mut (impl AsymmetricVault + EphemeralSecretsStore),
+    ) {
+        let attributes = SecretAttributes::NistP256;
+        let key_id_1 = vault.create_ephemeral_secret(attributes).await.unwrap();
+        let key_id_2 = vault.create_ephemeral_secret(attributes).await.unwrap();
+        let public_key_1 = vault.get_public_key(&key_id_1).await.unwrap();
+        let public_key_2 = vault.get_public_key(&key_id_2).await.unwrap();
+
+        let secret_1 = vault
+            .ec_diffie_hellman(&key_id_1, &public_key_2)
+            .await
+            .unwrap();
+        let secret_2 = vault
+            .ec_diffie_hellman(&key_id_2, &public_key_1)
+            .await
+            .unwrap();
+
+        let secret_1 = vault.get_ephemeral_secret(&secret_1, "ecdh").await.unwrap();
+        let secret_2 = vault.get_ephemeral_secret(&secret_2, "ecdh").await.unwrap();
+        assert_eq!(secret_1.secret().length(), 32);
+        assert_eq!(secret_1, secret_2);
+    }
+
     /// This test checks the creation of a derived HKDF key
     pub async fn test_hkdf_sha256(vault: &mut (impl AsymmetricVault + EphemeralSecretsStore)) {
         let salt_value = b"hkdf_test";
@@ -1,4 +1,4 @@
-use crate::{PublicKey, Secret, SecretAttributes, StoredSecret};
+use crate::{PublicKey, Secret, SecretAttributes, StoredSecret, VaultError};
 use ockam_core::{async_trait, compat::boxed::Box, KeyId, Result};
 
 /// This traits provides all the functionalities related to the management of secrets
@@ -39,6 +39,20 @@ pub trait PersistentSecretsStore: SecretsStoreReader + Sync + Send {
     async fn create_persistent_secret(&self, attributes: SecretAttributes) -> Result<KeyId>;
     /// Remove a persistent secret from the vault.
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool>;
+
+    /// Import a secret and persist it to long-term memory
+    async fn import_persistent_secret(
+        &self,
+        _secret: Secret,
+        _attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        Err(VaultError::SecretNotImportable.into())
+    }
+
+    /// Export a persistent secret, if the underlying security module allows it
+    async fn export_persistent_secret(&self, _key_id: &KeyId) -> Result<StoredSecret> {
+        Err(VaultError::SecretNotExportable.into())
+    }
 }
 
 /// This traits supports the retrieval of public information for a given secret
@@ -1,10 +1,11 @@
-use crate::{PublicKey, SecretAttributes, Signature};
+use crate::{PublicKey, Secret, SecretAttributes, Signature, StoredSecret, VaultError};
 use ockam_core::compat::boxed::Box;
 use ockam_core::{async_trait, KeyId, Result};
 
 /// A SecurityModule provides several functions related to secrets:
 ///   - create and persist secrets
 ///   - delete secrets
+///   - import and export secrets, when the security module allows it
 ///   - return the public key for a given key id
 ///   - return the key id for a given public key
 ///   - use a secret to sign a message
@@ -30,6 +31,18 @@ pub trait SecurityModule: Sync + Send {
     /// Delete a secret
     async fn delete_secret(&self, key_id: KeyId) -> Result<bool>;
 
+    /// Import an existing secret and return its key id.
+    /// By default, secrets can only be created inside the security module
+    async fn import_secret(&self, _secret: Secret, _attributes: SecretAttributes) -> Result<KeyId> {
+        Err(VaultError::SecretNotImportable.into())
+    }
+
+    /// Export a secret, in order to back it up or to move it to another security module.
+    /// Most hardware security modules don't allow their secrets to be exported
+    async fn export_secret(&self, _key_id: &KeyId) -> Result<StoredSecret> {
+        Err(VaultError::SecretNotExportable.into())
+    }
+
     /// Sign a message with a given key
     async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature>;
 
@@ -16,3 +16,6 @@ pub const AES128_SECRET_LENGTH_USIZE: usize = 16;
 
 /// NISTP256 private key length.
 pub const NISTP256_SECRET_LENGTH_U32: u32 = 32;
+
+/// NISTP256 public key length, as an uncompressed SEC1 point.
+pub const NISTP256_PUBLIC_LENGTH_USIZE: usize = 65;
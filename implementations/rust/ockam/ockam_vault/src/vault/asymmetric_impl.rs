@@ -1,7 +1,7 @@
 use crate::constants::CURVE25519_SECRET_LENGTH_U32;
 use crate::{
     AsymmetricVault, Buffer, EphemeralSecretsStore, Implementation, PublicKey, Secret,
-    SecretAttributes, SecretType, StoredSecret, Vault, VaultError,
+    SecretAttributes, SecretType, StoredSecret, Vault, VaultError, VaultSecurityModule,
 };
 use arrayref::array_ref;
 use ockam_core::compat::vec::Vec;
@@ -117,7 +117,15 @@ impl Vault {
             SecretType::Buffer | SecretType::Aes | SecretType::Ed25519 => {
                 Err(VaultError::UnknownEcdhKeyType.into())
             }
-            SecretType::NistP256 => Err(VaultError::UnknownEcdhKeyType.into()),
+            SecretType::NistP256 => {
+                use p256::pkcs8::{DecodePrivateKey, DecodePublicKey};
+                let sk = p256::SecretKey::from_pkcs8_der(stored_secret.secret().as_ref())
+                    .map_err(VaultSecurityModule::from_pkcs8)?;
+                let pk_t = p256::PublicKey::from_public_key_der(peer_public_key.data())
+                    .map_err(|_| VaultError::UnknownEcdhKeyType)?;
+                let secret = p256::ecdh::diffie_hellman(sk.to_nonzero_scalar(), pk_t.as_affine());
+                Ok(secret.raw_secret_bytes().to_vec())
+            }
         }
     }
 }
@@ -135,6 +143,9 @@ mod tests {
     #[ockam_macros::vault_test]
     fn test_ec_diffie_hellman_curve25519() {}
 
+    #[ockam_macros::vault_test]
+    fn test_ec_diffie_hellman_nist_p256() {}
+
     #[ockam_macros::vault_test]
     fn test_hkdf_sha256() {}
 }
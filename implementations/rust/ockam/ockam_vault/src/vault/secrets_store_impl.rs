@@ -76,6 +76,18 @@ impl PersistentSecretsStore for VaultSecretsStore {
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.security_module.delete_secret(key_id.clone()).await
     }
+
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.security_module.import_secret(secret, attributes).await
+    }
+
+    async fn export_persistent_secret(&self, key_id: &KeyId) -> Result<StoredSecret> {
+        self.security_module.export_secret(key_id).await
+    }
 }
 
 #[async_trait]
@@ -127,6 +139,14 @@ impl SecurityModule for VaultSecretsStore {
         self.security_module.delete_secret(key_id).await
     }
 
+    async fn import_secret(&self, secret: Secret, attributes: SecretAttributes) -> Result<KeyId> {
+        self.security_module.import_secret(secret, attributes).await
+    }
+
+    async fn export_secret(&self, key_id: &KeyId) -> Result<StoredSecret> {
+        self.security_module.export_secret(key_id).await
+    }
+
     async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
         self.security_module.sign(key_id, message).await
     }
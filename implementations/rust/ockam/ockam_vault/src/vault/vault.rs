@@ -155,6 +155,20 @@ impl PersistentSecretsStore for Vault {
     async fn delete_persistent_secret(&self, key_id: KeyId) -> Result<bool> {
         self.secrets_store.delete_persistent_secret(key_id).await
     }
+
+    async fn import_persistent_secret(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.secrets_store
+            .import_persistent_secret(secret, attributes)
+            .await
+    }
+
+    async fn export_persistent_secret(&self, key_id: &KeyId) -> Result<StoredSecret> {
+        self.secrets_store.export_persistent_secret(key_id).await
+    }
 }
 
 #[async_trait]
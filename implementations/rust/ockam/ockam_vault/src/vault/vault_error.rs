@@ -45,6 +45,10 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// The security module does not allow secrets to be exported
+    SecretNotExportable,
+    /// The security module does not allow secrets to be imported
+    SecretNotImportable,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -73,6 +77,8 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::SecretNotExportable => write!(f, "secrets can not be exported"),
+            Self::SecretNotImportable => write!(f, "secrets can not be imported"),
         }
     }
 }
@@ -90,6 +96,7 @@ impl From<VaultError> for Error {
             | InvalidPrivateKeyLen
             | InvalidX25519SecretLength => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound(_) | SecretNotFound => Kind::NotFound,
+            SecretNotExportable | SecretNotImportable => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
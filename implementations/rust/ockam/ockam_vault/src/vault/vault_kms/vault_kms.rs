@@ -68,6 +68,19 @@ impl SecurityModule for VaultSecurityModule {
         self.storage.delete(&key_id).await.map(|r| r.is_some())
     }
 
+    /// Store an existing secret
+    async fn import_secret(&self, secret: Secret, attributes: SecretAttributes) -> Result<KeyId> {
+        let key_id = Self::compute_key_id(&secret, &attributes).await?;
+        let stored_secret = StoredSecret::create(secret, attributes)?;
+        self.storage.put(key_id.clone(), stored_secret).await?;
+        Ok(key_id)
+    }
+
+    /// Return a stored secret
+    async fn export_secret(&self, key_id: &KeyId) -> Result<StoredSecret> {
+        self.get_secret(key_id, "exported secret").await
+    }
+
     async fn verify(
         &self,
         public_key: &PublicKey,
@@ -0,0 +1,13 @@
+//! PKCS#11 implementation of the ockam_vault::SecurityModule trait
+//!
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod vault;
+
+pub use vault::*;
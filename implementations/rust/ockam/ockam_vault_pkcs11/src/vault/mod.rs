@@ -0,0 +1,5 @@
+mod pkcs11_client;
+mod pkcs11_security_module;
+
+pub use pkcs11_client::*;
+pub use pkcs11_security_module::*;
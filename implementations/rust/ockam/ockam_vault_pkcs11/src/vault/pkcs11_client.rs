@@ -0,0 +1,439 @@
+use core::fmt;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use once_cell::sync::Lazy;
+use p256::pkcs8::EncodePublicKey;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tracing as log;
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, KeyId, Result};
+use ockam_node::tokio::task::spawn_blocking;
+use ockam_vault::{PublicKey, SecretType, Signature};
+
+/// DER encoding of the OID of the NIST P-256 curve (prime256v1)
+const NIST_P256_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Length of the CKA_ID attribute of the keys created by the client
+const KEY_ID_LENGTH: u32 = 16;
+
+/// Label of the keys created by the client
+const KEY_LABEL: &[u8] = b"ockam";
+
+/// A PKCS#11 library can only be initialized once per process, so its context is shared
+/// by all the clients and is never finalized
+static CONTEXTS: Lazy<Mutex<BTreeMap<PathBuf, Pkcs11>>> = Lazy::new(Default::default);
+
+/// PKCS#11 client.
+///
+/// The calls to the PKCS#11 library block until the token answers, so the
+/// [`TokenClient`] implementation runs them on the threads reserved for blocking tasks.
+#[derive(Clone)]
+pub struct Pkcs11Client {
+    session: Arc<Mutex<Session>>,
+}
+
+/// PKCS#11 configuration.
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    module: PathBuf,
+    slot: Option<u64>,
+    token_label: Option<String>,
+    pin: Option<String>,
+}
+
+impl Pkcs11Config {
+    /// Create a new configuration for the PKCS#11 library at the given path.
+    /// By default the first slot containing an initialized token is used
+    pub fn new(module: impl Into<PathBuf>) -> Pkcs11Config {
+        Pkcs11Config {
+            module: module.into(),
+            slot: None,
+            token_label: None,
+            pin: None,
+        }
+    }
+
+    /// Use a specific slot.
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Use the token with a specific label.
+    pub fn token_label(mut self, label: impl Into<String>) -> Self {
+        self.token_label = Some(label.into());
+        self
+    }
+
+    /// Log in to the token as a normal user with this PIN.
+    pub fn pin(mut self, pin: impl Into<String>) -> Self {
+        self.pin = Some(pin.into());
+        self
+    }
+
+    /// Return the path of the PKCS#11 library
+    pub fn module(&self) -> &Path {
+        &self.module
+    }
+}
+
+/// The PIN must not end up in logs
+impl fmt::Debug for Pkcs11Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11Config")
+            .field("module", &self.module)
+            .field("slot", &self.slot)
+            .field("token_label", &self.token_label)
+            .finish()
+    }
+}
+
+impl Pkcs11Client {
+    /// Load the PKCS#11 library, open a session on the configured token and log in.
+    pub fn new(config: Pkcs11Config) -> Result<Pkcs11Client> {
+        let context = Self::context(&config.module)?;
+        let slot = Self::find_slot(&context, &config)?;
+        let session = context.open_rw_session(slot).map_err(Error::from)?;
+        if let Some(pin) = &config.pin {
+            session
+                .login(UserType::User, Some(&AuthPin::new(pin.clone())))
+                .map_err(|err| {
+                    log::error!(%err, "failed to log in to the pkcs11 token");
+                    Error::from(err)
+                })?;
+        }
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+        })
+    }
+
+    /// Load the PKCS#11 library and open a session without blocking the async runtime
+    pub async fn create(config: Pkcs11Config) -> Result<Pkcs11Client> {
+        spawn_blocking(move || Self::new(config))
+            .await
+            .map_err(|e| Error::Task(e.to_string()))?
+    }
+
+    /// Run a call to the token on a thread where blocking is allowed
+    async fn blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(Pkcs11Client) -> Result<T> + Send + 'static,
+    {
+        let client = self.clone();
+        spawn_blocking(move || f(client))
+            .await
+            .map_err(|e| Error::Task(e.to_string()))?
+    }
+
+    /// Return the context of a PKCS#11 library, loading and initializing it if necessary
+    fn context(module: &Path) -> Result<Pkcs11> {
+        let mut contexts = CONTEXTS.lock().unwrap();
+        if let Some(context) = contexts.get(module) {
+            return Ok(context.clone());
+        }
+        let context = Pkcs11::new(module).map_err(|err| {
+            log::error!(%err, module = %module.display(), "failed to load the pkcs11 library");
+            Error::from(err)
+        })?;
+        context
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(Error::from)?;
+        contexts.insert(module.to_path_buf(), context.clone());
+        Ok(context)
+    }
+
+    /// Return the configured slot or the first slot with a token matching the configured label
+    fn find_slot(context: &Pkcs11, config: &Pkcs11Config) -> Result<Slot> {
+        for slot in context
+            .get_slots_with_initialized_token()
+            .map_err(Error::from)?
+        {
+            if let Some(id) = config.slot {
+                if slot.id() != id {
+                    continue;
+                }
+            }
+            if let Some(label) = &config.token_label {
+                let info = context.get_token_info(slot).map_err(Error::from)?;
+                if info.label().trim_end() != label {
+                    continue;
+                }
+            }
+            return Ok(slot);
+        }
+        log::error!(?config, "no pkcs11 token found");
+        Err(Error::MissingToken.into())
+    }
+
+    /// Create a new NIST P-256 key-pair on the token and return its ID.
+    pub fn create_key(&self) -> Result<KeyId> {
+        log::trace!("create new key");
+        let session = self.session.lock().unwrap();
+        let id = session
+            .generate_random_vec(KEY_ID_LENGTH)
+            .map_err(Error::from)?;
+        let public_template = [
+            Attribute::Token(true),
+            Attribute::Verify(true),
+            Attribute::EcParams(NIST_P256_PARAMS.to_vec()),
+            Attribute::Id(id.clone()),
+            Attribute::Label(KEY_LABEL.to_vec()),
+        ];
+        let private_template = [
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Id(id.clone()),
+            Attribute::Label(KEY_LABEL.to_vec()),
+        ];
+        session
+            .generate_key_pair(
+                &Mechanism::EccKeyPairGen,
+                &public_template,
+                &private_template,
+            )
+            .map_err(|err| {
+                log::error!(%err, "failed to create new key");
+                Error::Create(err)
+            })?;
+        let key_id = hex::encode(id);
+        log::debug!(%key_id, "created new key");
+        Ok(key_id)
+    }
+
+    /// Delete both parts of a key-pair from the token.
+    pub fn delete_key(&self, key_id: &KeyId) -> Result<bool> {
+        log::trace!(%key_id, "delete key");
+        let session = self.session.lock().unwrap();
+        let mut deleted = false;
+        for class in [ObjectClass::PRIVATE_KEY, ObjectClass::PUBLIC_KEY] {
+            if let Some(object) = find_key(&session, class, key_id)? {
+                session.destroy_object(object).map_err(|err| {
+                    log::error!(%key_id, %err, "failed to delete key");
+                    Error::Delete {
+                        keyid: key_id.to_string(),
+                        error: err,
+                    }
+                })?;
+                deleted = true;
+            }
+        }
+        if !deleted {
+            log::debug!(%key_id, "key does not exist");
+        }
+        Ok(deleted)
+    }
+
+    /// Get the public key part of a key-pair stored on the token.
+    pub fn public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
+        log::trace!(%key_id, "get public key");
+        let session = self.session.lock().unwrap();
+        let object = find_key(&session, ObjectClass::PUBLIC_KEY, key_id)?
+            .ok_or_else(|| Error::MissingKey(key_id.to_string()))?;
+        let attributes = session
+            .get_attributes(object, &[AttributeType::EcPoint])
+            .map_err(|err| {
+                log::error!(%key_id, %err, "failed to get public key");
+                Error::Export {
+                    keyid: key_id.to_string(),
+                    error: err,
+                }
+            })?;
+        match attributes.first() {
+            Some(Attribute::EcPoint(ec_point)) => ec_point_to_public_key(ec_point),
+            _ => {
+                log::error!(%key_id, "key type not supported to get a public key");
+                Err(Error::UnsupportedKeyType.into())
+            }
+        }
+    }
+
+    /// List the ids of all the elliptic curve keys stored on the token.
+    pub fn list_keys(&self) -> Result<Vec<KeyId>> {
+        let session = self.session.lock().unwrap();
+        let objects = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::PUBLIC_KEY),
+                Attribute::KeyType(KeyType::EC),
+            ])
+            .map_err(|err| {
+                log::error!(%err, "failed to list all keys");
+                Error::MissingKeys
+            })?;
+        let mut result = vec![];
+        for object in objects {
+            let attributes = session
+                .get_attributes(object, &[AttributeType::Id])
+                .map_err(Error::from)?;
+            if let Some(Attribute::Id(id)) = attributes.first() {
+                result.push(hex::encode(id))
+            }
+        }
+        Ok(result)
+    }
+
+    /// Sign a message with the private part of a key-pair stored on the token.
+    pub fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
+        log::trace!(%key_id, "sign message");
+        let session = self.session.lock().unwrap();
+        let object = find_key(&session, ObjectClass::PRIVATE_KEY, key_id)?
+            .ok_or_else(|| Error::MissingKey(key_id.to_string()))?;
+        // not all tokens support CKM_ECDSA_SHA256, so the message is hashed locally
+        let signature = session
+            .sign(&Mechanism::Ecdsa, object, &Sha256::digest(message))
+            .map_err(|err| {
+                log::error!(%key_id, %err, "failed to sign message");
+                Error::Sign {
+                    keyid: key_id.to_string(),
+                    error: err,
+                }
+            })?;
+        log::debug!(%key_id, "signed message");
+        raw_signature_to_der(&signature)
+    }
+}
+
+/// This trait is introduced to help with the testing of the Pkcs11SecurityModule
+#[async_trait]
+pub(crate) trait TokenClient {
+    async fn create_key(&self) -> Result<KeyId>;
+
+    async fn delete_key(&self, key_id: &KeyId) -> Result<bool>;
+
+    async fn public_key(&self, key_id: &KeyId) -> Result<PublicKey>;
+
+    async fn list_keys(&self) -> Result<Vec<KeyId>>;
+
+    async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl TokenClient for Pkcs11Client {
+    async fn create_key(&self) -> Result<KeyId> {
+        self.blocking(|client| client.create_key()).await
+    }
+
+    async fn delete_key(&self, key_id: &KeyId) -> Result<bool> {
+        let key_id = key_id.clone();
+        self.blocking(move |client| client.delete_key(&key_id))
+            .await
+    }
+
+    async fn public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
+        let key_id = key_id.clone();
+        self.blocking(move |client| client.public_key(&key_id))
+            .await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<KeyId>> {
+        self.blocking(|client| client.list_keys()).await
+    }
+
+    async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
+        let key_id = key_id.clone();
+        let message = message.to_vec();
+        self.blocking(move |client| client.sign(&key_id, &message))
+            .await
+    }
+}
+
+/// Return the object of a given class having a CKA_ID attribute matching the key id
+fn find_key(session: &Session, class: ObjectClass, key_id: &KeyId) -> Result<Option<ObjectHandle>> {
+    let id = hex::decode(key_id).map_err(|_| Error::MissingKey(key_id.to_string()))?;
+    let objects = session
+        .find_objects(&[Attribute::Class(class), Attribute::Id(id)])
+        .map_err(Error::from)?;
+    Ok(objects.first().copied())
+}
+
+/// Convert the CKA_EC_POINT attribute of a public key to the DER encoding used by the
+/// software vault for NIST P-256 public keys
+pub(crate) fn ec_point_to_public_key(ec_point: &[u8]) -> Result<PublicKey> {
+    // CKA_EC_POINT should be a DER encoded OCTET STRING wrapping the 65 bytes of the
+    // uncompressed point, but some tokens return the raw point
+    let point = match ec_point {
+        [0x04, 0x41, point @ ..] if point.len() == 65 && point[0] == 0x04 => point,
+        _ => ec_point,
+    };
+    let public_key = p256::PublicKey::from_sec1_bytes(point)
+        .map_err(|e| Error::InvalidPublicKey(e.to_string()))?;
+    let der = public_key
+        .to_public_key_der()
+        .map_err(|e| Error::InvalidPublicKey(e.to_string()))?;
+    Ok(PublicKey::new(der.as_ref().to_vec(), SecretType::NistP256))
+}
+
+/// Convert a raw PKCS#11 ECDSA signature (r || s) to the DER encoding used by the
+/// software vault
+pub(crate) fn raw_signature_to_der(signature: &[u8]) -> Result<Signature> {
+    let signature = p256::ecdsa::Signature::from_slice(signature)
+        .map_err(|e| Error::InvalidSignature(e.to_string()))?;
+    Ok(Signature::new(signature.to_der().as_bytes().to_vec()))
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum Error {
+    #[error("pkcs11 error: {0}")]
+    Pkcs11(#[from] cryptoki::error::Error),
+    #[error("pkcs11 error creating new key: {0}")]
+    Create(#[source] cryptoki::error::Error),
+    #[error("pkcs11 error signing message with key {keyid}")]
+    Sign {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("pkcs11 error exporting public key {keyid}")]
+    Export {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("pkcs11 error deleting key {keyid}")]
+    Delete {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("no pkcs11 token matches the configuration")]
+    MissingToken,
+    #[error("key {0} was not found on the pkcs11 token")]
+    MissingKey(String),
+    #[error("pkcs11 did not return a key id")]
+    MissingKeyId,
+    #[error("pkcs11 did not return the list of existing keys")]
+    MissingKeys,
+    #[error("invalid public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+    #[error("pkcs11 task failed: {0}")]
+    Task(String),
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        let kind = match e {
+            Error::MissingToken | Error::MissingKey(_) | Error::MissingKeyId => Kind::NotFound,
+            Error::InvalidPublicKey(_) | Error::InvalidSignature(_) => Kind::Invalid,
+            Error::UnsupportedKeyType => Kind::Unsupported,
+            Error::Task(_) => Kind::Internal,
+            _ => Kind::Io,
+        };
+        ockam_core::Error::new(Origin::Vault, kind, e)
+    }
+}
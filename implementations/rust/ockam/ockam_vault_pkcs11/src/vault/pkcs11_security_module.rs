@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use p256::pkcs8::DecodePublicKey;
+use tracing::error;
+
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, KeyId, Result};
+use ockam_node::{FileKeyValueStorage, KeyValueStorage};
+use ockam_vault::{PublicKey, SecretAttributes, SecretType, SecurityModule, Signature, VaultError};
+
+use crate::vault::pkcs11_client::{Pkcs11Client, Pkcs11Config, TokenClient};
+
+/// Security module implementation using a PKCS#11 token.
+///
+/// Only NIST P-256 keys are supported. They are created on the token and can't be extracted.
+/// A local storage maps public keys to the ids of the keys on the token.
+pub struct Pkcs11SecurityModule {
+    client: Arc<dyn TokenClient + Send + Sync>,
+    storage: Arc<dyn KeyValueStorage<PublicKey, KeyId>>,
+}
+
+impl Pkcs11SecurityModule {
+    /// Create a new PKCS#11 security module
+    pub async fn new(
+        config: Pkcs11Config,
+        storage: Arc<dyn KeyValueStorage<PublicKey, KeyId>>,
+    ) -> Result<Self> {
+        Ok(Pkcs11SecurityModule {
+            client: Arc::new(Pkcs11Client::create(config).await?),
+            storage,
+        })
+    }
+
+    /// Create a new PKCS#11 security module, with a specific file storage path
+    pub async fn create_with_storage_path(
+        config: Pkcs11Config,
+        path: &Path,
+    ) -> Result<Arc<dyn SecurityModule>> {
+        Self::create_with_key_value_storage(
+            config,
+            Arc::new(FileKeyValueStorage::create(path).await?),
+        )
+        .await
+    }
+
+    /// Create a new PKCS#11 security module, with a specific key value storage
+    pub async fn create_with_key_value_storage(
+        config: Pkcs11Config,
+        storage: Arc<dyn KeyValueStorage<PublicKey, KeyId>>,
+    ) -> Result<Arc<dyn SecurityModule>> {
+        Ok(Arc::new(Self::new(config, storage).await?))
+    }
+
+    /// Return the key id corresponding to a public key from the token
+    /// This function reads the public key of every key on the token
+    /// This is why the mapping is stored locally once it is known
+    pub(crate) async fn get_key_id_from_public_key(&self, public_key: &PublicKey) -> Result<KeyId> {
+        for key_id in self.client.list_keys().await? {
+            let one_public_key = self.client.public_key(&key_id).await?;
+            if &one_public_key == public_key {
+                return Ok(key_id);
+            }
+        }
+        error!(%public_key, "key id not found for public key {}", public_key);
+        Err(Error::new(
+            Origin::Vault,
+            Kind::NotFound,
+            crate::vault::pkcs11_client::Error::MissingKeyId,
+        ))
+    }
+}
+
+#[async_trait]
+impl SecurityModule for Pkcs11SecurityModule {
+    async fn create_secret(&self, attributes: SecretAttributes) -> Result<KeyId> {
+        if attributes.secret_type() == SecretType::NistP256 {
+            self.client.create_key().await
+        } else {
+            Err(VaultError::InvalidKeyType.into())
+        }
+    }
+
+    async fn get_public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
+        let public_key = self.client.public_key(key_id).await?;
+
+        // store the public key <-> key id mapping locally in order to avoid
+        // reading all the keys of the token when computing an identity identifier
+        if self.storage.get(&public_key).await?.is_none() {
+            self.storage.put(public_key.clone(), key_id.clone()).await?;
+        }
+        Ok(public_key)
+    }
+
+    async fn get_key_id(&self, public_key: &PublicKey) -> Result<KeyId> {
+        // try to get the key id from local storage first
+        if let Some(key_id) = self.storage.get(public_key).await? {
+            Ok(key_id)
+        } else {
+            let key_id = self.get_key_id_from_public_key(public_key).await?;
+            self.storage.put(public_key.clone(), key_id.clone()).await?;
+            Ok(key_id)
+        }
+    }
+
+    async fn get_attributes(&self, _key_id: &KeyId) -> Result<SecretAttributes> {
+        Ok(SecretAttributes::NistP256)
+    }
+
+    async fn delete_secret(&self, key_id: KeyId) -> Result<bool> {
+        self.client.delete_key(&key_id).await
+    }
+
+    async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
+        self.client.sign(key_id, message).await
+    }
+
+    /// Verify the signature of a message locally
+    async fn verify(
+        &self,
+        public_key: &PublicKey,
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<bool> {
+        use p256::ecdsa::{signature::Verifier as _, Signature, VerifyingKey};
+
+        let verifying_key =
+            VerifyingKey::from_public_key_der(public_key.data()).map_err(Self::from_pkcs8)?;
+        let ecdsa_signature = Signature::from_der(signature.as_ref()).map_err(Self::from_ecdsa)?;
+        Ok(verifying_key.verify(message, &ecdsa_signature).is_ok())
+    }
+}
+
+impl Pkcs11SecurityModule {
+    pub(crate) fn from_ecdsa(e: p256::ecdsa::Error) -> Error {
+        Error::new(Origin::Vault, Kind::Unknown, e)
+    }
+
+    pub(crate) fn from_pkcs8<T: core::fmt::Display>(e: T) -> Error {
+        Error::new(Origin::Vault, Kind::Unknown, e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::pkcs11_client::{ec_point_to_public_key, raw_signature_to_der};
+    use ockam_core::compat::rand::thread_rng;
+    use ockam_node::InMemoryKeyValueStorage;
+    use p256::ecdsa::SigningKey;
+    use p256::pkcs8::EncodePublicKey;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use SecretAttributes::*;
+
+    /// This test needs to be executed with the following environment variables
+    /// PKCS11_MODULE: path of the PKCS#11 library, for example /usr/lib/softhsm/libsofthsm2.so
+    /// PKCS11_PIN: user PIN of the first initialized token
+    ///
+    /// A SoftHSM2 token can be initialized with
+    /// softhsm2-util --init-token --free --label ockam --pin 1234 --so-pin 1234
+    #[tokio::test]
+    #[ignore]
+    async fn test_sign_verify_with_token() -> Result<()> {
+        let config = Pkcs11Config::new(std::env::var("PKCS11_MODULE").unwrap())
+            .pin(std::env::var("PKCS11_PIN").unwrap());
+        let security_module =
+            Pkcs11SecurityModule::new(config, InMemoryKeyValueStorage::create()).await?;
+
+        let key_id = security_module.create_secret(NistP256).await?;
+        let message = b"hello world";
+        let signature = security_module.sign(&key_id, &message[..]).await?;
+        let public_key = security_module.get_public_key(&key_id).await?;
+        assert!(
+            security_module
+                .verify(&public_key, message, &signature)
+                .await?
+        );
+
+        // the key id can be found from the public key on the token
+        assert_eq!(
+            security_module
+                .get_key_id_from_public_key(&public_key)
+                .await?,
+            key_id
+        );
+
+        // the key can't be extracted
+        assert!(security_module.export_secret(&key_id).await.is_err());
+
+        assert!(security_module.delete_secret(key_id.clone()).await?);
+        assert!(security_module.sign(&key_id, &message[..]).await.is_err());
+        Ok(())
+    }
+
+    /// This test checks that the signatures and public keys returned by a token
+    /// can be verified and that the public key <-> key id mapping is stored locally
+    #[tokio::test]
+    async fn test_sign_verify_and_storage() -> Result<()> {
+        let client = Arc::new(FakeTokenClient::default());
+        let storage = InMemoryKeyValueStorage::create();
+        let security_module = Pkcs11SecurityModule {
+            client: client.clone(),
+            storage: storage.clone(),
+        };
+
+        assert!(security_module.create_secret(Ed25519).await.is_err());
+        let key_id = security_module.create_secret(NistP256).await?;
+        let public_key = security_module.get_public_key(&key_id).await?;
+        assert_eq!(storage.get(&public_key).await?, Some(key_id.clone()));
+
+        let signature = security_module.sign(&key_id, b"hello").await?;
+        assert!(
+            security_module
+                .verify(&public_key, b"hello", &signature)
+                .await?
+        );
+        assert!(
+            !security_module
+                .verify(&public_key, b"hello!", &signature)
+                .await?
+        );
+
+        // retrieving the key id must not list the keys of the token
+        *client.can_list.lock().unwrap() = false;
+        assert_eq!(security_module.get_key_id(&public_key).await?, key_id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_point_conversion() {
+        let signing_key = SigningKey::random(&mut thread_rng());
+        let expected = signing_key
+            .verifying_key()
+            .to_public_key_der()
+            .unwrap()
+            .as_ref()
+            .to_vec();
+        let point = signing_key.verifying_key().to_encoded_point(false);
+
+        // DER encoded OCTET STRING
+        let mut octet_string = vec![0x04, point.len() as u8];
+        octet_string.extend_from_slice(point.as_bytes());
+        let public_key = ec_point_to_public_key(&octet_string).unwrap();
+        assert_eq!(public_key.data(), expected.as_slice());
+
+        // raw point
+        let public_key = ec_point_to_public_key(point.as_bytes()).unwrap();
+        assert_eq!(public_key.data(), expected.as_slice());
+
+        // raw point whose first coordinate byte looks like the length of an OCTET STRING
+        let point = loop {
+            let point = SigningKey::random(&mut thread_rng())
+                .verifying_key()
+                .to_encoded_point(false);
+            if point.as_bytes()[1] as usize == point.len() - 2 {
+                break point;
+            }
+        };
+        assert!(ec_point_to_public_key(point.as_bytes()).is_ok());
+    }
+
+    // TESTS IMPLEMENTATION
+
+    #[derive(Default)]
+    struct FakeTokenClient {
+        keys: Mutex<HashMap<KeyId, SigningKey>>,
+        can_list: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl TokenClient for FakeTokenClient {
+        async fn create_key(&self) -> Result<KeyId> {
+            let mut keys = self.keys.lock().unwrap();
+            let key_id = (keys.len() + 1).to_string();
+            keys.insert(key_id.clone(), SigningKey::random(&mut thread_rng()));
+            Ok(key_id)
+        }
+
+        async fn delete_key(&self, key_id: &KeyId) -> Result<bool> {
+            Ok(self.keys.lock().unwrap().remove(key_id).is_some())
+        }
+
+        async fn public_key(&self, key_id: &KeyId) -> Result<PublicKey> {
+            let point = self.keys.lock().unwrap()[key_id]
+                .verifying_key()
+                .to_encoded_point(false);
+            ec_point_to_public_key(point.as_bytes())
+        }
+
+        async fn list_keys(&self) -> Result<Vec<KeyId>> {
+            if !*self.can_list.lock().unwrap() {
+                return Err(Error::new(Origin::Api, Kind::Other, "can't list keys"));
+            }
+            Ok(self.keys.lock().unwrap().keys().cloned().collect())
+        }
+
+        /// Return a raw signature, as PKCS#11 tokens do
+        async fn sign(&self, key_id: &KeyId, message: &[u8]) -> Result<Signature> {
+            use p256::ecdsa::signature::Signer;
+            let signature: p256::ecdsa::Signature = self.keys.lock().unwrap()[key_id].sign(message);
+            raw_signature_to_der(&signature.to_bytes())
+        }
+    }
+}